@@ -22,7 +22,13 @@ pub use baremetal::BareMetal;
 pub use k8s::K8s;
 
 use crate::args::Envs;
+use crate::libs::capture::NetworkCapture;
+use crate::libs::checkpoint::CheckpointFile;
 use crate::libs::children::Children;
+use crate::libs::preemption::Preemption;
+use crate::libs::progress::ProgressFile;
+use crate::libs::simulated_internet::SimulatedInternet;
+use crate::libs::stream::OutputStream;
 use crate::libs::{results, tags, Target};
 use crate::{from_now, log, Worker};
 
@@ -103,6 +109,8 @@ pub enum JobStatus {
     Failed(Option<i32>),
     /// This job is ongoing
     OnGoing,
+    /// This job was preempted by the scaler
+    Preempted,
 }
 
 /// The different types of in flight jobs being executed by a Thorium agent
@@ -167,6 +175,12 @@ pub struct Agent {
     pub runtime: Option<u64>,
     /// A map of repos to their checked out commits
     commits: HashMap<String, String>,
+    /// Whether the scaler has preempted this worker
+    preemption: Preemption,
+    /// Whether this job was preempted and should be returned to the queue
+    pub preempted: bool,
+    /// Whether our capture sidecar should capture this jobs network traffic
+    capture_network: bool,
 }
 
 impl Agent {
@@ -182,6 +196,10 @@ impl Agent {
         let (sender, receiver) = crossbeam::channel::unbounded();
         // instance our executor
         let executor = get_executor(worker, target, &job, &sender)?;
+        // only k8s pods have a capture sidecar since kvm and bare metal reactors capture
+        // traffic themselves
+        let capture_network =
+            target.image.capture_network && matches!(worker.args.env, Envs::K8s(_));
         let agent = Agent {
             thorium: worker.thorium.clone(),
             image: target.image.clone(),
@@ -193,6 +211,9 @@ impl Agent {
             completed: false,
             runtime: None,
             commits: HashMap::default(),
+            preemption: worker.preemption.clone(),
+            preempted: false,
+            capture_network,
         };
         Ok(agent)
     }
@@ -311,11 +332,15 @@ impl Agent {
     ///
     /// * `in_flight`: The info about our active job
     /// * `reader` - The reader to pull logs from
+    /// * `progress` - The file our job reports its progress to
+    /// * `checkpoint` - The file our job writes its checkpoint blobs to
     #[instrument(name = "agents::monitor", skip_all, err(Debug))]
     async fn monitor(
         &mut self,
         mut in_flight: InFlight,
         reader: &mut BufReader<File>,
+        progress: &mut ProgressFile,
+        checkpoint: &mut CheckpointFile,
     ) -> Result<JobStatus, Error> {
         // get timestamps to track how long this job has been running for
         let start = Instant::now();
@@ -327,6 +352,19 @@ impl Agent {
         loop {
             // send any logs in our log file
             self.send_file_logs(reader).await?;
+            // send any logs in our logs channel such as streamed progress messages
+            self.send_channel_logs().await?;
+            // report any new progress without failing our job if Thorium rejects it
+            if let Err(error) = progress.check(&self.thorium, &self.job, &self.sender).await {
+                log!(self.sender, "Failed to report progress: {}", error);
+            }
+            // save any new checkpoint blob without failing our job if Thorium rejects it
+            if let Err(error) = checkpoint
+                .check(&self.thorium, &self.job, &self.sender)
+                .await
+            {
+                log!(self.sender, "Failed to save checkpoint: {}", error);
+            }
             // check if this job has finished executing or not yet
             match in_flight.finished().await? {
                 JobStatus::Finished(code) => {
@@ -354,6 +392,16 @@ impl Agent {
                 in_flight.cancel().await?;
                 return Ok(JobStatus::Failed(None));
             }
+            // check if the scaler has preempted this worker
+            if self.preemption.preempted() {
+                event!(Level::INFO, msg = "Job preempted");
+                // stop our job and save its latest checkpoint blob
+                in_flight.cancel().await?;
+                checkpoint
+                    .save(&self.thorium, &self.job, &self.sender)
+                    .await?;
+                return Ok(JobStatus::Preempted);
+            }
             // sleep for 100 ms before checking if this job has finished  again
             tokio::time::sleep(sleep).await;
         }
@@ -380,6 +428,15 @@ impl Agent {
         Ok(())
     }
 
+    /// Tell Thorium this job was preempted and should be returned to the queue
+    #[instrument(name = "agents::preempt", skip_all, err(Debug))]
+    pub async fn preempt(&mut self) -> Result<(), Error> {
+        // send any logs in our logs channel
+        self.send_channel_logs().await?;
+        self.thorium.jobs.preempt(&self.job.id).await?;
+        Ok(())
+    }
+
     /// Tell Thorium this job failed with an error message
     ///
     /// # Arguments
@@ -425,6 +482,12 @@ pub trait AgentExecutor {
     /// Get the paths to this executors current jobs results and result files
     fn result_paths(&self, image: &Image) -> (String, String);
 
+    /// Get the path this executors current job should write its progress to
+    fn progress_path(&self, image: &Image) -> String;
+
+    /// Get the path this executors current job should write its checkpoint blobs to
+    fn checkpoint_path(&self, image: &Image) -> String;
+
     /// Setup the environment for executing a single job in Thorium
     ///
     /// # Arguments
@@ -446,11 +509,13 @@ pub trait AgentExecutor {
     /// * `image` - The Image to execute a job for
     /// * `job` - The job we are executing
     /// * `log_file` - Where to write job logs to
+    /// * `env` - Any extra env vars to set for this job
     async fn execute(
         &mut self,
         image: &Image,
         job: &GenericJob,
         log_file: &str,
+        env: &HashMap<String, String>,
     ) -> Result<InFlight, Error>;
 
     /// Collect any result from a completed job
@@ -542,15 +607,85 @@ pub async fn sub_execute(
         .executor
         .setup(&agent.image, &agent.job, &mut agent.commits)
         .await?;
+    // start streaming output if this image streams its output
+    let mut stream = OutputStream::start(
+        &agent.thorium,
+        &agent.image,
+        &agent.job,
+        &agent.commits,
+        &agent.sender,
+    )
+    .await?;
+    // get any env vars our job needs to find our output stream
+    let mut env = stream
+        .as_ref()
+        .map(|stream| stream.env.clone())
+        .unwrap_or_default();
+    // tell our job where it can report its progress
+    let mut progress = ProgressFile::new(agent.executor.progress_path(&agent.image));
+    let (key, value) = progress.env();
+    env.insert(key, value);
+    // restore any checkpoint blob this job saved before losing its last worker
+    let mut checkpoint = CheckpointFile::new(agent.executor.checkpoint_path(&agent.image));
+    checkpoint
+        .restore(&agent.thorium, &agent.job, &agent.sender)
+        .await?;
+    let (key, value) = checkpoint.env();
+    env.insert(key, value);
+    // start capturing this jobs network traffic if our image captures it
+    let capture = if agent.capture_network {
+        Some(NetworkCapture::start(&agent.job, &agent.sender).await?)
+    } else {
+        None
+    };
+    // start tracking our requests to the simulated internet if we are redirected to it
+    let simulated_internet = SimulatedInternet::start();
     // start executing this job
     let in_flight = agent
         .executor
-        .execute(&agent.image, &agent.job, log_path)
+        .execute(&agent.image, &agent.job, log_path, &env)
         .await?;
     // send any logs in our logs channel
     agent.send_channel_logs().await?;
     // wait for this job to finish exeucting
-    let status = agent.monitor(in_flight, reader).await?;
+    let status = agent
+        .monitor(in_flight, reader, &mut progress, &mut checkpoint)
+        .await?;
+    // stop capturing network traffic and submit it unless this job is being requeued
+    if let Some(capture) = capture {
+        let submit = !matches!(status, JobStatus::Preempted);
+        if let Err(error) = capture
+            .finish(
+                &agent.thorium,
+                &agent.job,
+                &agent.image,
+                submit,
+                &agent.sender,
+            )
+            .await
+        {
+            log!(agent.sender, "Failed to submit network capture: {}", error);
+        }
+    }
+    // attach our simulated internet requests if this job finished so they are collected
+    if let (Some(simulated_internet), JobStatus::Finished(_)) = (simulated_internet, &status) {
+        let (_, result_files_path) = agent.executor.result_paths(&agent.image);
+        if let Err(error) = simulated_internet
+            .finish(&result_files_path, &agent.sender)
+            .await
+        {
+            log!(
+                agent.sender,
+                "Failed to get simulated internet requests: {}",
+                error
+            );
+        }
+    }
+    // stop streaming output and get any results that were streamed
+    let streamed = match stream.as_mut() {
+        Some(stream) => stream.stop().await?,
+        None => Vec::default(),
+    };
     // send any remaining logs from our log file
     agent.send_file_logs(reader).await?;
     // if this job finished successfully then look for results
@@ -566,21 +701,41 @@ pub async fn sub_execute(
                 .await?;
             // send any logs in our logs channel
             agent.send_channel_logs().await?;
+            // skip the missing results warning if our results were streamed instead
+            let (results_path, _) = agent.executor.result_paths(&agent.image);
+            let skip = !streamed.is_empty()
+                && raw_results.files.is_empty()
+                && !Path::new(&results_path).exists();
             // submit our results and tags
-            let results = results::submit(
+            let mut results = if skip {
+                Vec::default()
+            } else {
+                results::submit(
+                    &agent.thorium,
+                    &raw_results,
+                    &agent.job,
+                    &agent.image,
+                    &mut agent.sender,
+                )
+                .await?
+            };
+            // keep track of which results were submitted for our inputs
+            let submitted = results.len();
+            // tie any children to our streamed results too
+            results.extend(streamed);
+            // send any logs in our logs channel
+            agent.send_channel_logs().await?;
+            // submit any tags we found
+            tags::submit(
                 &agent.thorium,
-                &raw_results,
+                tag_bundle,
                 &agent.job,
-                &agent.image,
+                &results[..submitted],
                 &mut agent.sender,
             )
             .await?;
             // send any logs in our logs channel
             agent.send_channel_logs().await?;
-            // submit any tags we found
-            tags::submit(&agent.thorium, tag_bundle, &agent.job, &mut agent.sender).await?;
-            // send any logs in our logs channel
-            agent.send_channel_logs().await?;
             // collect any children files
             let mut childs = agent.executor.children(&agent.image).await?;
             // send any logs in our logs channel
@@ -602,6 +757,14 @@ pub async fn sub_execute(
             code
         }
         JobStatus::Failed(code) => code,
+        JobStatus::Preempted => {
+            log!(agent.sender, "Worker preempted, returning job to the queue");
+            // mark this job as preempted so it is returned to the queue
+            agent.preempted = true;
+            agent.send_channel_logs().await?;
+            agent.executor.clean_up(&agent.image, &agent.job).await?;
+            return Ok(());
+        }
         JobStatus::OnGoing => {
             return Err(Error::new(format!("Job {} is still ongoing", agent.job.id)))
         }
@@ -653,6 +816,16 @@ pub async fn execute(mut agent: Agent, log_path: String) {
     let mut reader = BufReader::new(log_file);
     // try executing this job and report any failures if the occur
     match sub_execute(&mut agent, &mut reader, &log_path).await {
+        // job was preempted so return it to the queue
+        Ok(()) if agent.preempted => {
+            event!(Level::INFO, msg = "Returning preempted job");
+            check!(agent.preempt().await);
+            // delete this jobs log file
+            if let Err(error) = tokio::fs::remove_file(log_path).await {
+                // log this error but continue on since it probably doesn't matter
+                event!(Level::INFO, msg = error.to_string());
+            }
+        }
         // job completed so mark it as complete and proceed
         Ok(()) => {
             event!(Level::INFO, msg = "Proceeding with reaction");
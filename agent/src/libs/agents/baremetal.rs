@@ -65,6 +65,10 @@ pub struct BareMetal {
     pub tags_path: PathBuf,
     /// The path to write children to
     pub children_path: PathBuf,
+    /// The path to write progress to
+    pub progress_path: PathBuf,
+    /// The path to write checkpoint blobs to
+    pub checkpoint_path: PathBuf,
     /// The paths to any downloaded sample files
     samples: Vec<PathBuf>,
     /// The paths to any downloaded ephemeral files
@@ -95,6 +99,8 @@ impl BareMetal {
         let result_files_path = isolate(&target.image.output_collection.files.result_files, &id)?;
         let tags_path = isolate(&target.image.output_collection.files.tags, &id)?;
         let children_path = isolate(&target.image.output_collection.children, &id)?;
+        let progress_path = isolate(&target.image.output_collection.files.progress, &id)?;
+        let checkpoint_path = isolate(&target.image.output_collection.files.checkpoint, &id)?;
         // build our baremetal object
         let bare_metal = BareMetal {
             thorium: target.thorium.clone(),
@@ -109,6 +115,8 @@ impl BareMetal {
             result_files_path,
             tags_path,
             children_path,
+            progress_path,
+            checkpoint_path,
             samples: Vec::default(),
             ephemerals: Vec::default(),
             repos: Vec::default(),
@@ -131,6 +139,16 @@ impl AgentExecutor for BareMetal {
         (results, result_files)
     }
 
+    /// Get the path this executors current job should write its progress to
+    fn progress_path(&self, _: &Image) -> String {
+        self.progress_path.to_string_lossy().to_string()
+    }
+
+    /// Get the path this executors current job should write its checkpoint blobs to
+    fn checkpoint_path(&self, _: &Image) -> String {
+        self.checkpoint_path.to_string_lossy().to_string()
+    }
+
     /// Setup the environment for executing a single job in Thorium
     ///
     /// # Arguments
@@ -167,6 +185,8 @@ impl AgentExecutor for BareMetal {
         purge!(self.result_files_path);
         purge!(self.tags_path);
         purge!(self.children_path);
+        purge!(self.progress_path);
+        purge!(self.checkpoint_path);
         // setup dependendency base paths that are isolated by job ids
         std::fs::create_dir_all(&self.samples_path)?;
         std::fs::create_dir_all(&self.ephemerals_path)?;
@@ -175,6 +195,8 @@ impl AgentExecutor for BareMetal {
         std::fs::create_dir_all(&self.results_path.parent().unwrap())?;
         std::fs::create_dir_all(&self.result_files_path)?;
         std::fs::create_dir_all(&self.tags_path)?;
+        std::fs::create_dir_all(&self.progress_path.parent().unwrap())?;
+        std::fs::create_dir_all(&self.checkpoint_path.parent().unwrap())?;
         // build the paths for storing children files
         children::setup(&self.children_path).await?;
         // download any data required for this job
@@ -254,9 +276,10 @@ impl AgentExecutor for BareMetal {
     /// * `image` - The Image to execute a job for
     /// * `job` - The job we are executing
     /// * `log_file` - Where to write job logs to
+    /// * `env` - Any extra env vars to set for this job
     #[instrument(
         name = "AgentExecutor<BareMetal>::execute",
-        skip(self, image, job),
+        skip(self, image, job, env),
         err(Debug)
     )]
     async fn execute(
@@ -264,6 +287,7 @@ impl AgentExecutor for BareMetal {
         image: &Image,
         job: &GenericJob,
         log_path: &str,
+        env: &HashMap<String, String>,
     ) -> Result<InFlight, Error> {
         // get the correct way to pass our dependency paths to our job
         let sample_args = build_path_args!(job.samples, self.samples, image.dependencies.samples);
@@ -296,6 +320,7 @@ impl AgentExecutor for BareMetal {
         };
         // build the command we want to execute
         let built = cmd
+            .resume(job, &self.checkpoint_path.to_string_lossy())
             .build(
                 sample_args,
                 ephemeral_args,
@@ -319,6 +344,7 @@ impl AgentExecutor for BareMetal {
         // execute built command
         let child = Command::new(built[0].clone())
             .args(&built[1..])
+            .envs(env)
             .stdout(log_file.try_clone()?)
             .stderr(log_file)
             .spawn()?;
@@ -391,6 +417,8 @@ impl AgentExecutor for BareMetal {
         purge_parent!(self.result_files_path);
         purge_parent!(self.tags_path);
         purge_parent!(self.children_path);
+        purge_parent!(self.progress_path);
+        purge_parent!(self.checkpoint_path);
         Ok(())
     }
 }
@@ -146,6 +146,16 @@ impl AgentExecutor for K8s {
         (results, result_files)
     }
 
+    /// Get the path this executors current job should write its progress to
+    fn progress_path(&self, image: &Image) -> String {
+        image.output_collection.files.progress.clone()
+    }
+
+    /// Get the path this executors current job should write its checkpoint blobs to
+    fn checkpoint_path(&self, image: &Image) -> String {
+        image.output_collection.files.checkpoint.clone()
+    }
+
     /// Setup the environment for executing a single job in Thorium
     ///
     /// # Arguments
@@ -181,6 +191,17 @@ impl AgentExecutor for K8s {
         std::fs::create_dir_all(&image.dependencies.tags.location)?;
         // setup result base paths
         std::fs::create_dir_all(&image.output_collection.files.result_files)?;
+        // remove any stale progress from a previous job and make sure its parent exists
+        purge!(image.output_collection.files.progress);
+        if let Some(progress_parent) = Path::new(&image.output_collection.files.progress).parent() {
+            std::fs::create_dir_all(progress_parent)?;
+        }
+        // remove any stale checkpoint from a previous job and make sure its parent exists
+        purge!(image.output_collection.files.checkpoint);
+        let checkpoint_path = Path::new(&image.output_collection.files.checkpoint);
+        if let Some(checkpoint_parent) = checkpoint_path.parent() {
+            std::fs::create_dir_all(checkpoint_parent)?;
+        }
         // get the parent to our results path
         let results_path = Path::new(&image.output_collection.files.results);
         if let Some(result_parent) = results_path.parent() {
@@ -272,9 +293,10 @@ impl AgentExecutor for K8s {
     /// * `image` - The Image to execute a job for
     /// * `job` - The job we are executing
     /// * `log_file` - Where to write job logs to
+    /// * `env` - Any extra env vars to set for this job
     #[instrument(
         name = "AgentExecutor<K8s>::execute",
-        skip(self, image, job),
+        skip(self, image, job, env),
         err(Debug)
     )]
     async fn execute(
@@ -282,6 +304,7 @@ impl AgentExecutor for K8s {
         image: &Image,
         job: &GenericJob,
         log_path: &str,
+        env: &HashMap<String, String>,
     ) -> Result<InFlight, Error> {
         // get the correct way to pass our dependency paths to our job
         let sample_args = build_path_args!(job.samples, self.samples, image.dependencies.samples);
@@ -297,6 +320,7 @@ impl AgentExecutor for K8s {
         let children_args = build_path_args!(self.children, image.dependencies.children);
         // build command to execute
         let built = registry::Cmd::new(image, job, &self.entrypoint, &self.cmd)
+            .resume(job, &image.output_collection.files.checkpoint)
             .build(
                 sample_args,
                 ephemeral_args,
@@ -326,6 +350,7 @@ impl AgentExecutor for K8s {
         // execute built command
         let cmd_spawn = Command::new(built[0].clone())
             .args(&built[1..])
+            .envs(env)
             .stdout(log_file.try_clone()?)
             .stderr(log_file)
             .spawn();
@@ -413,6 +438,8 @@ impl AgentExecutor for K8s {
         purge!(image.output_collection.files.results);
         purge!(image.output_collection.files.result_files);
         purge!(image.output_collection.files.tags);
+        purge!(image.output_collection.files.progress);
+        purge!(image.output_collection.files.checkpoint);
         // remove any children files/dirs
         purge!(image.output_collection.children);
         Ok(())
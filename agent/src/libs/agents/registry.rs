@@ -26,6 +26,8 @@ pub struct Cmd {
     pub src: Vec<String>,
     /// The command built from overlaying our values ontop of the source command
     pub built: Vec<String>,
+    /// The path to a restored checkpoint blob to resume from
+    pub resume: Option<String>,
 }
 
 impl Cmd {
@@ -46,6 +48,7 @@ impl Cmd {
             opts: job.args.opts.clone(),
             src: cmd.to_owned(),
             built: entrypoint.to_owned(),
+            resume: None,
         };
         // if this job is a generator then inject in the job and reaction id kwarg
         if job.generator {
@@ -92,6 +95,21 @@ impl Cmd {
         }
     }
 
+    /// Resume from a restored checkpoint blob if this job saved one
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job we are building a command for
+    /// * `checkpoint` - The path our checkpoint blob was restored to
+    #[must_use]
+    pub fn resume(mut self, job: &GenericJob, checkpoint: &str) -> Self {
+        // only resume jobs that saved a checkpoint blob
+        if job.checkpoint_blob.is_some() {
+            self.resume = Some(checkpoint.to_owned());
+        }
+        self
+    }
+
     /// Overlays positional args from the job and source into the built command
     fn inject_positionals(&mut self) {
         // check if we should override all positional args
@@ -389,6 +407,10 @@ impl Cmd {
         self.inject_reaction_id(image, job);
         // add our output path if its set
         self.overwrite_arg(output, &image.args.output);
+        // add the path to our restored checkpoint blob if we are resuming
+        if let Some(resume) = self.resume.take() {
+            self.overwrite_arg(&resume, &image.args.resume);
+        }
         // overlay custom args ontop of the docker images entrypoint/cmd
         // inject any positional args
         self.inject_positionals();
@@ -406,9 +428,9 @@ mod tests {
     use chrono::prelude::*;
     use std::collections::{HashMap, HashSet};
     use thorium::models::{
-        ChildFilters, CommitishKinds, Dependencies, GenericJob, GenericJobArgs, Image, ImageArgs,
-        ImageVersion, JobStatus, OutputCollection, OutputDisplayType, RepoDependency, Resources,
-        ResultDependencySettings, SecurityContext,
+        ChildFilters, CommitishKinds, CpuArch, Dependencies, GenericJob, GenericJobArgs, Image,
+        ImageArgs, ImageVersion, JobCheckpointBlob, JobStatus, OutputCollection, OutputDisplayType,
+        RepoDependency, Resources, ResultDependencySettings, SecurityContext,
     };
     use uuid::Uuid;
 
@@ -442,6 +464,7 @@ mod tests {
                 },
             ],
             trigger_depth: None,
+            checkpoint_blob: None,
         }
     }
 
@@ -460,6 +483,7 @@ mod tests {
             timeout: None,
             resources: Resources::default(),
             spawn_limit: thorium::models::SpawnLimits::Unlimited,
+            priority: thorium::models::PriorityClass::default(),
             env: HashMap::default(),
             args: ImageArgs::default(),
             runtime: 600.0,
@@ -470,12 +494,16 @@ mod tests {
             used_by: Vec::default(),
             collect_logs: true,
             generator: false,
+            capture_network: false,
+            arch: CpuArch::default(),
+            emulation: false,
             dependencies: Dependencies::default(),
             display_type: OutputDisplayType::default(),
             output_collection: OutputCollection::default(),
             child_filters: ChildFilters::default(),
             clean_up: None,
             kvm: None,
+            microvm: None,
             bans: HashMap::default(),
             network_policies: HashSet::default(),
         }
@@ -1191,6 +1219,78 @@ mod tests {
         assert_eq!(built_set, args_set);
     }
 
+    /// Test a job that is resuming from a checkpoint blob
+    #[tokio::test]
+    async fn resume() {
+        // create a temporary log channel
+        let (mut logs_tx, _logs_rx) = crossbeam::channel::unbounded::<String>();
+        // generate an image that takes its checkpoint as a kwarg
+        let mut image = generate_image();
+        image.args.resume = ArgStrategy::Kwarg("--resume".into());
+        // generate a job that has saved a checkpoint blob
+        let mut job = generate_job();
+        job.checkpoint_blob = Some(JobCheckpointBlob {
+            size: 26,
+            uploaded: Utc::now(),
+        });
+        let cmd = Cmd::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py"],
+        )
+        .resume(&job, "/tmp/thorium/checkpoint");
+        let built = cmd
+            .build(
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                "results",
+                &image,
+                &job,
+                &mut logs_tx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            built,
+            vec_string!(
+                "/usr/bin/python3",
+                "corn.py",
+                "--resume",
+                "/tmp/thorium/checkpoint"
+            )
+        );
+        // jobs without a checkpoint blob should not be passed a resume arg
+        job.checkpoint_blob = None;
+        let cmd = Cmd::new(
+            &image,
+            &job,
+            slice_string!["/usr/bin/python3"],
+            slice_string!["corn.py"],
+        )
+        .resume(&job, "/tmp/thorium/checkpoint");
+        let built = cmd
+            .build(
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                "results",
+                &image,
+                &job,
+                &mut logs_tx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(built, vec_string!("/usr/bin/python3", "corn.py"));
+    }
+
     /// Test a job with switch overlays
     #[tokio::test]
     async fn switches() {
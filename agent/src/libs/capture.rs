@@ -0,0 +1,136 @@
+//! Captures the network traffic of jobs running in k8s
+//!
+//! Pods for images that capture network traffic have a capture sidecar that
+//! shares this pods network namespace and scratch space. The agent tells the
+//! sidecar when to start and stop capturing by writing marker files to the
+//! shared capture directory and then submits the finished pcap as a child of
+//! each sample in the job.
+
+use crossbeam::channel::Sender;
+use std::path::PathBuf;
+use thorium::models::{GenericJob, Image, OriginRequest, SampleRequest};
+use thorium::{Error, Thorium};
+use tokio::time::{Duration, Instant};
+use tracing::{event, instrument, Level};
+
+use crate::log;
+
+/// The directory shared with our capture sidecar
+const CAPTURE_DIR: &str = "/tmp/thorium-capture";
+/// How long to wait for our capture sidecar to finish writing a pcap
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often to check if our capture sidecar has finished writing a pcap
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Remove a file if it exists
+///
+/// # Arguments
+///
+/// * `path` - The path to the file to remove
+async fn remove_file(path: &PathBuf) {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => (),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+        Err(error) => event!(
+            Level::ERROR,
+            msg = "Failed to remove capture file",
+            path = path.to_string_lossy().to_string(),
+            error = error.to_string()
+        ),
+    }
+}
+
+/// A network capture for a single job
+pub struct NetworkCapture {
+    /// The name to use for this captures files
+    name: String,
+}
+
+impl NetworkCapture {
+    /// Get the path to one of this captures files
+    ///
+    /// # Arguments
+    ///
+    /// * `ext` - The extension of the file to get
+    fn path(&self, ext: &str) -> PathBuf {
+        PathBuf::from(CAPTURE_DIR).join(format!("{}.{ext}", self.name))
+    }
+
+    /// Tell our capture sidecar to start capturing a jobs network traffic
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to capture network traffic for
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "NetworkCapture::start", skip_all, err(Debug))]
+    pub async fn start(job: &GenericJob, logs: &Sender<String>) -> Result<Self, Error> {
+        let capture = NetworkCapture {
+            name: job.id.to_string(),
+        };
+        // tell our sidecar to start capturing
+        tokio::fs::create_dir_all(CAPTURE_DIR).await?;
+        tokio::fs::File::create(capture.path("start")).await?;
+        log!(logs, "Capturing network traffic");
+        Ok(capture)
+    }
+
+    /// Stop capturing network traffic and submit our pcap if requested
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client to Thorium
+    /// * `job` - The job we captured network traffic for
+    /// * `image` - The image this job was for
+    /// * `submit` - Whether to submit our pcap or just discard it
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "NetworkCapture::finish", skip_all, err(Debug))]
+    pub async fn finish(
+        self,
+        thorium: &Thorium,
+        job: &GenericJob,
+        image: &Image,
+        submit: bool,
+        logs: &Sender<String>,
+    ) -> Result<(), Error> {
+        // tell our sidecar to stop capturing
+        tokio::fs::File::create(self.path("stop")).await?;
+        // wait for our sidecar to finish writing our pcap
+        let pcap = self.path("pcap");
+        let done = self.path("pcap.done");
+        let start = Instant::now();
+        while !done.exists() {
+            if start.elapsed() > STOP_TIMEOUT {
+                self.clean_up().await;
+                return Err(Error::new("Timed out waiting for network capture to stop"));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        if submit {
+            // submit our pcap as a child of every sample in this job
+            for sha256 in &job.samples {
+                let origin = OriginRequest::transformed(
+                    sha256,
+                    Some(image.name.clone()),
+                    vec!["capture_network"],
+                    None,
+                );
+                let req = SampleRequest::new(&pcap, vec![job.group.clone()]).origin(origin);
+                thorium.files.create(req).await?;
+            }
+            log!(
+                logs,
+                "Submitted network capture for {} samples",
+                job.samples.len()
+            );
+        }
+        self.clean_up().await;
+        Ok(())
+    }
+
+    /// Remove this captures files
+    async fn clean_up(&self) {
+        for ext in ["start", "stop", "pcap", "pcap.done"] {
+            remove_file(&self.path(ext)).await;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Saves and restores the checkpoint blobs of running jobs
+//!
+//! Tools can write a checkpoint blob to their checkpoint file at any time. The
+//! latest blob is saved to Thorium so that if this job loses its worker it can
+//! be restored before the job is executed again.
+
+use crossbeam::channel::Sender;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use thorium::models::GenericJob;
+use thorium::{Error, Thorium};
+use tokio::io::AsyncWriteExt;
+use tokio::time::{Duration, Instant};
+use tracing::instrument;
+
+use crate::log;
+
+/// The env var that tells tools where to write their checkpoint blobs
+const CHECKPOINT_FILE: &str = "THORIUM_CHECKPOINT_FILE";
+/// How often to check our checkpoint file for changes
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A file that a running tool writes its latest checkpoint blob to
+pub struct CheckpointFile {
+    /// The path to the checkpoint file
+    path: PathBuf,
+    /// When our checkpoint file was last modified
+    modified: Option<SystemTime>,
+    /// When we last checked our checkpoint file
+    checked: Option<Instant>,
+}
+
+impl CheckpointFile {
+    /// Create a new checkpoint file watcher
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the checkpoint file
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        CheckpointFile {
+            path: path.into(),
+            modified: None,
+            checked: None,
+        }
+    }
+
+    /// Get the env vars to set so tools can find this checkpoint file
+    pub fn env(&self) -> (String, String) {
+        (
+            CHECKPOINT_FILE.to_owned(),
+            self.path.to_string_lossy().to_string(),
+        )
+    }
+
+    /// Restore the last checkpoint blob this job saved if it has one
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client to Thorium
+    /// * `job` - The job we are restoring a checkpoint blob for
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "CheckpointFile::restore", skip_all, err(Debug))]
+    pub async fn restore(
+        &mut self,
+        thorium: &Thorium,
+        job: &GenericJob,
+        logs: &Sender<String>,
+    ) -> Result<(), Error> {
+        // skip jobs that have never saved a checkpoint blob
+        let Some(blob) = &job.checkpoint_blob else {
+            return Ok(());
+        };
+        log!(
+            logs,
+            "Restoring {} byte checkpoint from {}",
+            blob.size,
+            blob.uploaded
+        );
+        // download and write our checkpoint blob to disk
+        let data = thorium.jobs.download_checkpoint_blob(&job.id).await?;
+        let mut file = tokio::fs::File::create(&self.path).await?;
+        file.write_all(&data).await?;
+        file.flush().await?;
+        // don't save our restored checkpoint blob again unless the tool changes it
+        self.modified = Some(file.metadata().await?.modified()?);
+        Ok(())
+    }
+
+    /// Save our latest checkpoint blob to Thorium if it has changed
+    ///
+    /// Tools should write their checkpoint blob to a temporary file and then
+    /// rename it over their checkpoint file so partial blobs are never saved.
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client to Thorium
+    /// * `job` - The job we are saving a checkpoint blob for
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "CheckpointFile::check", skip_all, err(Debug))]
+    pub async fn check(
+        &mut self,
+        thorium: &Thorium,
+        job: &GenericJob,
+        logs: &Sender<String>,
+    ) -> Result<(), Error> {
+        // don't check our checkpoint file too often
+        if self
+            .checked
+            .is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.checked = Some(Instant::now());
+        // skip our checkpoint file if it hasn't been written or hasn't changed
+        let modified = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => metadata.modified()?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(Error::from(error)),
+        };
+        if self.modified == Some(modified) {
+            return Ok(());
+        }
+        self.modified = Some(modified);
+        // save our latest checkpoint blob
+        let blob = thorium
+            .jobs
+            .upload_checkpoint_blob(&job.id, &self.path)
+            .await?;
+        log!(logs, "Saved {} byte checkpoint", blob.size);
+        Ok(())
+    }
+
+    /// Save our latest checkpoint blob to Thorium now if it has changed
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client to Thorium
+    /// * `job` - The job we are saving a checkpoint blob for
+    /// * `logs` - The logs to send to the API
+    pub async fn save(
+        &mut self,
+        thorium: &Thorium,
+        job: &GenericJob,
+        logs: &Sender<String>,
+    ) -> Result<(), Error> {
+        // ignore our check interval since this is our last chance to save
+        self.checked = None;
+        self.check(thorium, job, logs).await
+    }
+}
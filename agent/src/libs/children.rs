@@ -19,7 +19,7 @@ use tracing::{event, instrument, Level};
 use uuid::Uuid;
 use walkdir::WalkDir;
 
-use super::helpers;
+use super::{helpers, tags};
 use crate::log;
 
 /// A cache of compiled child filter regular expressions mapped to their raw
@@ -317,6 +317,18 @@ impl CarvedChildren {
     }
 }
 
+/// The kinds of children a running tool can stream to the agent
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamedChild {
+    /// A child file built from source
+    Source,
+    /// A child file unpacked from a sample
+    Unpacked,
+    /// A child file carved from a sample
+    Carved,
+}
+
 /// The different types of children files to submit to Thorium
 pub struct Children {
     /// The root path where children are located, configured in the image
@@ -333,7 +345,7 @@ pub struct Children {
 
 /// Submit children files 10 at a time for a given sample/repo
 macro_rules! submit {
-    ($sample:expr, $children:expr, $origin:expr, $results:expr, $groups:expr, $depth:expr, $tags:expr, $thorium:expr, $logs:expr, $msg:literal) => {
+    ($sample:expr, $children:expr, $origin:expr, $job:expr, $results:expr, $groups:expr, $depth:expr, $tags:expr, $thorium:expr, $logs:expr, $msg:literal) => {
         async {
             // submit any children 10 at a time
             stream::iter($children.clone())
@@ -341,7 +353,9 @@ macro_rules! submit {
                     // if any children were found then generate our origin requests
                     let origin = $origin($sample, &child).result_ids($results.to_vec());
                     // build this origins sample request
-                    let req = SampleRequest::new(child, $groups.to_vec()).origin(origin);
+                    let req = SampleRequest::new(child, $groups.to_vec())
+                        .origin(origin)
+                        .provenance(tags::provenance($job, None));
                     // set our trigger depth if we have one
                     let mut req = match $depth {
                         Some(trigger_depth) => req.trigger_depth(trigger_depth),
@@ -412,6 +426,31 @@ impl Children {
         Ok(children)
     }
 
+    /// Build children from a single file streamed by a running tool
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The root path children are collected at for this image
+    /// * `kind` - The kind of child this file is
+    /// * `path` - The path the streamed child was written to
+    /// * `logs` - The logs to send to the API
+    pub fn streamed<P: AsRef<Path>>(
+        root: P,
+        kind: StreamedChild,
+        path: PathBuf,
+        logs: &mut Sender<String>,
+    ) -> Result<Self, Error> {
+        // build a Children object and collect any tags
+        let mut children = Children::new(root).collect_tags(logs)?;
+        // add our streamed child to the right list
+        match kind {
+            StreamedChild::Source => children.source.push(path),
+            StreamedChild::Unpacked => children.unpacked.push(path),
+            StreamedChild::Carved => children.carved.unknown.push(path),
+        }
+        Ok(children)
+    }
+
     /// Returns true if there are no children
     fn is_empty(&self) -> bool {
         self.unpacked.is_empty() && self.source.is_empty() && self.carved.is_empty()
@@ -597,6 +636,7 @@ impl Children {
                             supporting,
                         )
                     },
+                    job,
                     results,
                     groups,
                     depth,
@@ -639,6 +679,7 @@ impl Children {
                 |sample: &String, _child: &Path| {
                     OriginRequest::unpacked(sample.clone(), Some(tool.to_owned()))
                 },
+                job,
                 results,
                 groups,
                 depth,
@@ -772,6 +813,7 @@ impl Children {
                         )
                     }
                 },
+                job,
                 results,
                 groups,
                 depth,
@@ -788,6 +830,7 @@ impl Children {
                 |sample: &String, _child: &Path| {
                     OriginRequest::carved_unknown(sample.clone(), Some(tool.to_owned()))
                 },
+                job,
                 results,
                 groups,
                 depth,
@@ -1,5 +1,5 @@
 use chrono::prelude::*;
-use thorium::models::Pools;
+use thorium::models::{ImageScaler, Pools};
 use tracing::{event, instrument, Level};
 
 use super::Target;
@@ -37,6 +37,16 @@ impl Lifetime {
     /// * `target` - The target job for this worker
     #[instrument(name = "Lifetime::new", skip_all)]
     pub fn new(target: &Target) -> Self {
+        // kvm workers run in a disposable vm that is torn down after a single job and bare
+        // metal workers whose traffic is captured only run one job so their capture is tied to it
+        let captured =
+            target.image.scaler == ImageScaler::BareMetal && target.image.capture_network;
+        if target.image.scaler == ImageScaler::Kvm || captured {
+            return Lifetime::JobCount {
+                current: 0,
+                limit: 1,
+            };
+        }
         // if this was spawned under the fairshare pool then set a lifetime of 1 minute at most
         match (target.pool, &target.image.lifetime) {
             // fair share spawned workers with no lifetime can only execute 1 minute worth of jobs before dying
@@ -1,13 +1,20 @@
 mod agents;
+mod capture;
+mod checkpoint;
 mod children;
 mod helpers;
 mod lifetime;
+mod preemption;
+mod progress;
 mod results;
+mod simulated_internet;
+mod stream;
 mod tags;
 mod target;
 mod worker;
 
 use lifetime::Lifetime;
+pub(crate) use preemption::Preemption;
 pub(crate) use results::RawResults;
 pub(crate) use tags::TagBundle;
 pub use target::{CurrentTarget, Target};
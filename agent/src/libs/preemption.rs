@@ -0,0 +1,52 @@
+//! Watches for the scaler preempting this worker
+//!
+//! The scaler preempts low priority workers by deleting them with a grace
+//! period. This sends a SIGTERM to the agent so it can save a final checkpoint
+//! and return its job to the queue before it is killed.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{event, Level};
+
+/// Whether this worker has been preempted or not
+#[derive(Debug, Clone, Default)]
+pub struct Preemption {
+    /// Whether we have received a SIGTERM yet
+    preempted: Arc<AtomicBool>,
+}
+
+impl Preemption {
+    /// Start listening for this worker to be preempted
+    pub fn listen() -> Self {
+        let preemption = Preemption::default();
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            // listen for sigterms from the scaler
+            match signal(SignalKind::terminate()) {
+                Ok(mut terminate) => {
+                    let preempted = preemption.preempted.clone();
+                    tokio::spawn(async move {
+                        if terminate.recv().await.is_some() {
+                            event!(Level::INFO, msg = "Worker preempted");
+                            preempted.store(true, Ordering::SeqCst);
+                        }
+                    });
+                }
+                Err(error) => {
+                    event!(
+                        Level::ERROR,
+                        msg = "Failed to listen for preemption",
+                        error = error.to_string()
+                    );
+                }
+            }
+        }
+        preemption
+    }
+
+    /// Check if this worker has been preempted
+    pub fn preempted(&self) -> bool {
+        self.preempted.load(Ordering::SeqCst)
+    }
+}
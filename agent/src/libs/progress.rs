@@ -0,0 +1,118 @@
+//! Reports the progress of running jobs to Thorium
+//!
+//! Tools can report their progress by writing it to a progress file or by
+//! posting it to the output stream if their image streams its output.
+
+use crossbeam::channel::Sender;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use thorium::models::{GenericJob, JobProgressUpdate};
+use thorium::{Error, Thorium};
+use tokio::time::{Duration, Instant};
+use tracing::instrument;
+
+use crate::log;
+
+/// The env var that tells tools where to write their progress
+const PROGRESS_FILE: &str = "THORIUM_PROGRESS_FILE";
+/// How often to check our progress file for changes
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Parse a progress update written by a tool
+///
+/// Progress can either be a JSON progress update or just a percent.
+///
+/// # Arguments
+///
+/// * `raw` - The raw progress to parse
+pub fn parse(raw: &str) -> Result<JobProgressUpdate, Error> {
+    let raw = raw.trim();
+    // a bare number is just a percent
+    if let Ok(percent) = raw.trim_end_matches('%').parse::<f32>() {
+        return Ok(JobProgressUpdate::default().percent(percent));
+    }
+    let update = serde_json::from_str(raw)?;
+    Ok(update)
+}
+
+/// A file that a running tool writes its latest progress to
+pub struct ProgressFile {
+    /// The path to the progress file
+    path: PathBuf,
+    /// When our progress file was last modified
+    modified: Option<SystemTime>,
+    /// When we last checked our progress file
+    checked: Option<Instant>,
+}
+
+impl ProgressFile {
+    /// Create a new progress file watcher
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the progress file
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        ProgressFile {
+            path: path.into(),
+            modified: None,
+            checked: None,
+        }
+    }
+
+    /// Get the env vars to set so tools can find this progress file
+    pub fn env(&self) -> (String, String) {
+        (
+            PROGRESS_FILE.to_owned(),
+            self.path.to_string_lossy().to_string(),
+        )
+    }
+
+    /// Send our latest progress to Thorium if it has changed
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client to Thorium
+    /// * `job` - The job we are reporting progress for
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "ProgressFile::check", skip_all, err(Debug))]
+    pub async fn check(
+        &mut self,
+        thorium: &Thorium,
+        job: &GenericJob,
+        logs: &Sender<String>,
+    ) -> Result<(), Error> {
+        // don't check our progress file too often
+        if self
+            .checked
+            .is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.checked = Some(Instant::now());
+        // skip our progress file if it hasn't been written or hasn't changed
+        let modified = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => metadata.modified()?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(Error::from(error)),
+        };
+        if self.modified == Some(modified) {
+            return Ok(());
+        }
+        self.modified = Some(modified);
+        // parse and report our latest progress
+        let raw = tokio::fs::read_to_string(&self.path).await?;
+        // skip empty files as the tool may be in the middle of writing them
+        if raw.trim().is_empty() {
+            return Ok(());
+        }
+        let update = match parse(&raw) {
+            Ok(update) => update,
+            Err(error) => {
+                log!(logs, "Failed to parse progress file: {}", error);
+                return Ok(());
+            }
+        };
+        thorium.jobs.progress(&job.id, &update).await?;
+        Ok(())
+    }
+}
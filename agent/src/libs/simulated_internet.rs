@@ -0,0 +1,76 @@
+//! Attaches the requests jobs make to the simulated internet as result files
+//!
+//! Workers whose egress is redirected to the simulated internet are given the url
+//! to pull its request logs from. The simulated internet logs requests by source
+//! ip so we pull the requests made while a job was running and write them to the
+//! jobs result files directory before results are collected. Only workers with an
+//! ip of their own can be told apart so workers without one never attach logs.
+
+use chrono::{DateTime, Utc};
+use crossbeam::channel::Sender;
+use std::path::Path;
+use thorium::Error;
+use tracing::instrument;
+
+use crate::log;
+
+/// The name of the result file to write simulated internet requests to
+const LOG_NAME: &str = "simulated_internet.log";
+
+/// The requests a single job made to the simulated internet
+pub struct SimulatedInternet {
+    /// The url to pull request logs from
+    url: String,
+    /// The ip our requests come from
+    ip: String,
+    /// When this job started
+    start: DateTime<Utc>,
+}
+
+impl SimulatedInternet {
+    /// Start tracking the requests a job makes if we are redirected to the simulated internet
+    pub fn start() -> Option<Self> {
+        // only workers redirected to the simulated internet have a logs url set
+        let url = std::env::var("THORIUM_SIMULATED_INTERNET_LOGS").ok()?;
+        // workers without their own ip would pull the requests of other workers too
+        let ip = std::env::var("THORIUM_POD_IP").ok()?;
+        Some(SimulatedInternet {
+            url,
+            ip,
+            start: Utc::now(),
+        })
+    }
+
+    /// Pull the requests this job made and write them to its result files
+    ///
+    /// # Arguments
+    ///
+    /// * `result_files` - The directory to write our request log to
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "SimulatedInternet::finish", skip_all, err(Debug))]
+    pub async fn finish(self, result_files: &str, logs: &Sender<String>) -> Result<(), Error> {
+        // get the requests made while this job was running
+        let query = [
+            ("start", self.start.to_rfc3339()),
+            ("end", Utc::now().to_rfc3339()),
+            ("ip", self.ip),
+        ];
+        let requests = reqwest::Client::new()
+            .get(&self.url)
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        // write our requests to this jobs result files
+        tokio::fs::create_dir_all(result_files).await?;
+        tokio::fs::write(Path::new(result_files).join(LOG_NAME), &requests).await?;
+        log!(
+            logs,
+            "Attached {} bytes of simulated internet requests",
+            requests.len()
+        );
+        Ok(())
+    }
+}
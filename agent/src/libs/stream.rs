@@ -0,0 +1,481 @@
+//! Serves a local endpoint that running tools can stream output to
+//!
+//! Anything streamed to the agent is forwarded to Thorium as soon as it is
+//! received instead of waiting for the tool to exit.
+
+use axum::body::Body;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use crossbeam::channel::Sender;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use thorium::models::{GenericJob, Image, OutputDisplayType, OutputHandler};
+use thorium::{Error, Thorium};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{event, instrument, Level};
+use uuid::Uuid;
+
+use super::children::{Children, StreamedChild};
+use super::progress;
+use super::results::{self, RawResults, ResultTarget};
+use super::tags;
+use crate::log;
+
+/// The env var that tells tools the url to stream output to
+const OUTPUT_URL: &str = "THORIUM_OUTPUT_URL";
+/// The env var that tells tools the unix socket to stream output to
+const OUTPUT_SOCKET: &str = "THORIUM_OUTPUT_SOCKET";
+/// The max size of a streamed result in bytes
+///
+/// Larger results should be written to disk so they can be stored as result files.
+const MAX_RESULT: usize = 1_000_000;
+/// How long to wait for in flight requests to finish when stopping
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An error returned to a tool streaming output
+#[derive(Debug)]
+struct StreamError {
+    /// The status code to return
+    code: StatusCode,
+    /// The error message to return
+    msg: String,
+}
+
+impl StreamError {
+    /// Create a new stream error
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The status code to return
+    /// * `msg` - The error message to return
+    fn new<T: Into<String>>(code: StatusCode, msg: T) -> Self {
+        StreamError {
+            code,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl From<Error> for StreamError {
+    /// Forward an error from talking to Thorium as a bad gateway
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error to forward
+    fn from(error: Error) -> Self {
+        // if Thorium rejected this then pass its status code back to the tool
+        let code = match error.status() {
+            Some(code) if code.is_client_error() => {
+                StatusCode::from_u16(code.as_u16()).unwrap_or(StatusCode::BAD_REQUEST)
+            }
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        StreamError::new(code, error.to_string())
+    }
+}
+
+impl From<std::io::Error> for StreamError {
+    /// Return any IO errors as an internal error
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The IO error to return
+    fn from(error: std::io::Error) -> Self {
+        StreamError::new(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+    }
+}
+
+impl IntoResponse for StreamError {
+    /// Build a response for this error
+    fn into_response(self) -> Response {
+        (self.code, self.msg).into_response()
+    }
+}
+
+/// The state shared by our stream handlers
+struct StreamState {
+    /// A client to Thorium
+    thorium: Thorium,
+    /// The image we are executing a job for
+    image: Image,
+    /// The job we are executing
+    job: GenericJob,
+    /// A map of repos to their checked out commits
+    commits: HashMap<String, String>,
+    /// A sender for a channel of logs to add for this job
+    logs: Sender<String>,
+    /// Where to write streamed children before submitting them
+    scratch: PathBuf,
+    /// The ids of any results that have been streamed
+    results: Mutex<Vec<Uuid>>,
+}
+
+impl StreamState {
+    /// Get the ids of any results that have been streamed so far
+    fn results(&self) -> Result<Vec<Uuid>, Error> {
+        let results = self
+            .results
+            .lock()
+            .map_err(|err| Error::new(format!("Error locking streamed results: {err}")))?;
+        Ok(results.clone())
+    }
+}
+
+/// The query params for streaming a result
+#[derive(Deserialize, Debug)]
+struct ResultParams {
+    /// The display type for this result (defaults to the image's display type)
+    display_type: Option<OutputDisplayType>,
+}
+
+/// The query params for streaming a child file
+#[derive(Deserialize, Debug)]
+struct ChildParams {
+    /// The file name to give this child
+    name: Option<String>,
+}
+
+/// Forward a streamed result to Thorium
+///
+/// # Arguments
+///
+/// * `state` - The shared stream state
+/// * `params` - The query params for this result
+/// * `body` - The serialized result
+#[instrument(name = "stream::results", skip_all, err(Debug))]
+async fn stream_results(
+    State(state): State<Arc<StreamState>>,
+    Query(params): Query<ResultParams>,
+    body: String,
+) -> Result<Json<Vec<Uuid>>, StreamError> {
+    // get our own copy of our log sender
+    let mut logs = state.logs.clone();
+    // get the display type for this result
+    let display_type = params.display_type.unwrap_or(state.image.display_type);
+    // build our raw results
+    let raw = RawResults {
+        scan: display_type == OutputDisplayType::Json,
+        results: ResultTarget::Db(body),
+        files: Vec::default(),
+        display_type,
+    };
+    // extract any auto tags before we submit anything
+    let bundle = tags::from_results(&state.job, &raw, &state.image.output_collection, &mut logs)
+        .map_err(|err| StreamError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+    // submit our results
+    let ids = results::submit(&state.thorium, &raw, &state.job, &state.image, &mut logs).await?;
+    log!(logs, "Streamed results {:?}", ids);
+    // track these results so children can be tied to them
+    state
+        .results
+        .lock()
+        .map_err(|err| Error::new(format!("Error locking streamed results: {err}")))?
+        .extend(ids.iter().copied());
+    // submit any auto tags we found
+    if !bundle.is_empty() {
+        tags::submit(&state.thorium, bundle, &state.job, &ids, &mut logs).await?;
+    }
+    Ok(Json(ids))
+}
+
+/// Forward streamed tags to Thorium
+///
+/// # Arguments
+///
+/// * `state` - The shared stream state
+/// * `map` - The tag keys and values to add
+#[instrument(name = "stream::tags", skip_all, err(Debug))]
+async fn stream_tags(
+    State(state): State<Arc<StreamState>>,
+    Json(map): Json<HashMap<String, Value>>,
+) -> Result<StatusCode, StreamError> {
+    // get our own copy of our log sender
+    let mut logs = state.logs.clone();
+    // build the tags for our inputs
+    let bundle = tags::from_map(&state.job, map, &mut logs)
+        .map_err(|err| StreamError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+    // submit these tags
+    tags::submit(&state.thorium, bundle, &state.job, &[], &mut logs).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Write a streamed child to disk and forward it to Thorium
+///
+/// # Arguments
+///
+/// * `state` - The shared stream state
+/// * `kind` - The kind of child being streamed
+/// * `params` - The query params for this child
+/// * `body` - The body containing the child file
+#[instrument(name = "stream::children", skip_all, err(Debug))]
+async fn stream_child(
+    State(state): State<Arc<StreamState>>,
+    Path(kind): Path<StreamedChild>,
+    Query(params): Query<ChildParams>,
+    body: Body,
+) -> Result<StatusCode, StreamError> {
+    // get our own copy of our log sender
+    let mut logs = state.logs.clone();
+    // only use the file name portion of any name we were given
+    let id = Uuid::new_v4();
+    let name = params
+        .name
+        .as_deref()
+        .and_then(|name| std::path::Path::new(name).file_name())
+        .map_or_else(
+            || id.to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+    // write this child to its own dir so names can't collide
+    let dir = state.scratch.join(id.to_string());
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(name);
+    let mut file = tokio::fs::File::create(&path).await?;
+    // stream this child to disk
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk =
+            chunk.map_err(|err| StreamError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    // get the results streamed so far to tie this child to
+    let results = state.results()?;
+    // submit this child
+    let submitted = match Children::streamed(
+        &state.image.output_collection.children,
+        kind,
+        path,
+        &mut logs,
+    ) {
+        Ok(mut children) => {
+            children
+                .submit(
+                    &state.thorium,
+                    &state.job,
+                    &results,
+                    state.job.trigger_depth,
+                    &state.commits,
+                    &state.image,
+                    &mut logs,
+                )
+                .await
+        }
+        Err(error) => Err(error),
+    };
+    // remove our child now that its been submitted
+    if let Err(error) = tokio::fs::remove_dir_all(&dir).await {
+        event!(
+            Level::ERROR,
+            msg = "Failed to remove streamed child",
+            error = error.to_string()
+        );
+    }
+    submitted?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Report streamed progress to Thorium or add it to this jobs logs
+///
+/// Structured progress updates or bare percents are reported to Thorium while
+/// anything else is treated as a progress message and logged.
+///
+/// # Arguments
+///
+/// * `state` - The shared stream state
+/// * `body` - The progress update or message
+#[instrument(name = "stream::progress", skip_all, err(Debug))]
+async fn stream_progress(
+    State(state): State<Arc<StreamState>>,
+    body: String,
+) -> Result<StatusCode, StreamError> {
+    match progress::parse(&body) {
+        // report this structured progress to Thorium
+        Ok(update) => {
+            state.thorium.jobs.progress(&state.job.id, &update).await?;
+        }
+        // add each line of this message to our logs
+        Err(_) => {
+            for line in body.lines() {
+                log!(state.logs, "Progress: {}", line);
+            }
+        }
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Build the routes for streaming output
+///
+/// # Arguments
+///
+/// * `state` - The shared stream state
+fn router(state: Arc<StreamState>) -> Router {
+    Router::new()
+        .route("/results", post(stream_results))
+        .route("/tags", post(stream_tags))
+        .route("/children/{kind}", post(stream_child))
+        .route("/progress", post(stream_progress))
+        .layer(DefaultBodyLimit::max(MAX_RESULT))
+        .with_state(state)
+}
+
+/// An endpoint a running tool can stream output to
+pub struct OutputStream {
+    /// The env vars to set so the tool can find this endpoint
+    pub env: HashMap<String, String>,
+    /// The state shared by our stream handlers
+    state: Arc<StreamState>,
+    /// The unix socket we are listening on if we are using one
+    socket: Option<PathBuf>,
+    /// A channel to tell our server to shut down
+    shutdown: Option<oneshot::Sender<()>>,
+    /// The task serving our endpoint
+    handle: JoinHandle<()>,
+}
+
+impl OutputStream {
+    /// Start serving an output stream if this image streams its output
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client to Thorium
+    /// * `image` - The image we are executing a job for
+    /// * `job` - The job we are executing
+    /// * `commits` - A map of repos to their checked out commits
+    /// * `logs` - The logs to send to the API
+    #[instrument(name = "OutputStream::start", skip_all, err(Debug))]
+    pub async fn start(
+        thorium: &Thorium,
+        image: &Image,
+        job: &GenericJob,
+        commits: &HashMap<String, String>,
+        logs: &Sender<String>,
+    ) -> Result<Option<Self>, Error> {
+        // only serve an output stream if this image uses one
+        if image.output_collection.handler != OutputHandler::Stream {
+            return Ok(None);
+        }
+        // build the state for our handlers
+        let state = Arc::new(StreamState {
+            thorium: thorium.clone(),
+            image: image.clone(),
+            job: job.clone(),
+            commits: commits.clone(),
+            logs: logs.clone(),
+            scratch: std::env::temp_dir()
+                .join("thorium-stream")
+                .join(job.id.to_string()),
+            results: Mutex::new(Vec::default()),
+        });
+        let app = router(state.clone());
+        // build the channel to tell our server to shutdown with
+        let (shutdown, rx) = oneshot::channel::<()>();
+        let signal = async move {
+            let _ = rx.await;
+        };
+        let mut env = HashMap::with_capacity(1);
+        // listen on a unix socket if one was set otherwise listen on localhost
+        let (socket, handle) = match &image.output_collection.stream.socket {
+            #[cfg(unix)]
+            Some(socket) => {
+                let path = PathBuf::from(socket);
+                // make sure our parent dir exists and remove any stale socket
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                if path.exists() {
+                    tokio::fs::remove_file(&path).await?;
+                }
+                let listener = tokio::net::UnixListener::bind(&path)?;
+                env.insert(OUTPUT_SOCKET.to_owned(), socket.clone());
+                log!(logs, "Streaming output on {}", socket);
+                let handle = tokio::spawn(async move {
+                    let server = axum::serve(listener, app).with_graceful_shutdown(signal);
+                    if let Err(error) = server.await {
+                        event!(
+                            Level::ERROR,
+                            msg = "Output stream failed",
+                            error = error.to_string()
+                        );
+                    }
+                });
+                (Some(path), handle)
+            }
+            #[cfg(not(unix))]
+            Some(_) => {
+                return Err(Error::new(
+                    "Streaming output over a unix socket is only supported on unix",
+                ))
+            }
+            None => {
+                let port = image.output_collection.stream.port.unwrap_or(0);
+                let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+                let url = format!("http://{}", listener.local_addr()?);
+                log!(logs, "Streaming output on {}", url);
+                env.insert(OUTPUT_URL.to_owned(), url);
+                let handle = tokio::spawn(async move {
+                    let server = axum::serve(listener, app).with_graceful_shutdown(signal);
+                    if let Err(error) = server.await {
+                        event!(
+                            Level::ERROR,
+                            msg = "Output stream failed",
+                            error = error.to_string()
+                        );
+                    }
+                });
+                (None, handle)
+            }
+        };
+        let stream = OutputStream {
+            env,
+            state,
+            socket,
+            shutdown: Some(shutdown),
+            handle,
+        };
+        Ok(Some(stream))
+    }
+
+    /// Stop serving this output stream and get the ids of any streamed results
+    #[instrument(name = "OutputStream::stop", skip_all, err(Debug))]
+    pub async fn stop(&mut self) -> Result<Vec<Uuid>, Error> {
+        // tell our server to stop and wait for any in flight requests
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut self.handle)
+                .await
+                .is_err()
+            {
+                event!(Level::ERROR, msg = "Timed out stopping output stream");
+                self.handle.abort();
+            }
+        }
+        // clean up our socket and any leftover children
+        if let Some(socket) = &self.socket {
+            if socket.exists() {
+                tokio::fs::remove_file(socket).await?;
+            }
+        }
+        if self.state.scratch.exists() {
+            tokio::fs::remove_dir_all(&self.state.scratch).await?;
+        }
+        self.state.results()
+    }
+}
+
+impl Drop for OutputStream {
+    /// Make sure our server is stopped if we error out
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
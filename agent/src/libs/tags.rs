@@ -7,10 +7,12 @@ use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use thorium::models::backends::TagSupport;
 use thorium::models::{
-    AutoTag, AutoTagLogic, GenericJob, OutputCollection, Repo, Sample, TagRequest,
+    AutoTag, AutoTagLogic, GenericJob, OutputCollection, Repo, Sample, TagProvenanceRequest,
+    TagRequest,
 };
 use thorium::{Error, Thorium};
 use tracing::instrument;
+use uuid::Uuid;
 
 use crate::{fail, log};
 
@@ -25,6 +27,14 @@ pub struct TagBundle {
     repos: Option<TagRequest<Repo>>,
 }
 
+impl TagBundle {
+    /// Check if this bundle has no tags to submit
+    pub fn is_empty(&self) -> bool {
+        self.samples.iter().all(|req| req.tags.is_empty())
+            && self.repos.iter().all(|req| req.tags.is_empty())
+    }
+}
+
 /// determine if the target output value exists at the root or not
 ///
 /// # Arguments
@@ -194,6 +204,75 @@ impl RawTags {
         req.tags = self.tags.clone();
         req
     }
+
+    /// Adds tags from a map of user provided tag values
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - The tag keys and values to add
+    /// * `logs` - The logs to send to the API
+    fn add_map(
+        &mut self,
+        map: HashMap<String, Value>,
+        logs: &mut Sender<String>,
+    ) -> Result<(), Error> {
+        // crawl through all tags and try to add them to our tag map
+        for (key, value) in map {
+            // try to extract the values based on type
+            match value {
+                // The value for this is just a string so just add it
+                Value::String(value) => self.add_ref(key, value),
+                // The value for this is just a number so just add it
+                Value::Number(value) => self.add_ref(key, value.to_string()),
+                // The value for this is just a bool so just add it
+                Value::Bool(value) => self.add_ref(key, value.to_string()),
+                // the value for this is an array so crawl the values in the array
+                Value::Array(values) => {
+                    for value in values {
+                        // try to add each idividual value and error on objects or nested vectors
+                        match value {
+                            // The value for this is just a string so just add it
+                            Value::String(value) => self.add_ref(&key, value),
+                            // The value for this is just a number so just add it
+                            Value::Number(value) => self.add_ref(&key, value.to_string()),
+                            // The value for this is just a bool so just add it
+                            Value::Bool(value) => self.add_ref(&key, value.to_string()),
+                            // error out on unsupported types
+                            _ => fail!(logs, "ERROR: Only strings, numbers, or bools are allowed in nested tag arrays"),
+                        }
+                    }
+                }
+                // error out on unsupported types
+                _ => fail!(
+                    logs,
+                    "ERROR: Only strings, numbers, bools, or arrays are allowed in the tags file"
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Build a tag bundle for the inputs to a job
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job we are building tags for
+    fn to_bundle(&self, job: &GenericJob) -> TagBundle {
+        // get our trigger depth
+        let depth = job.trigger_depth.unwrap_or(0);
+        // start with an empty tag bundle
+        let mut bundle = TagBundle::default();
+        // add in the tags if we have a corresponding input
+        if !job.repos.is_empty() {
+            // get the repo tag req and add it
+            bundle.repos = Some(self.to_req(depth));
+        }
+        if !job.samples.is_empty() {
+            // add this to our bundle
+            bundle.samples = Some(self.to_req(depth));
+        }
+        bundle
+    }
 }
 
 /// Tries to extract tags from a result
@@ -304,43 +383,37 @@ fn overlay(mut tags: RawTags, path: &Path, logs: &mut Sender<String>) -> Result<
             Ok(map) => map,
             Err(err) => return Err(Error::from(err)),
         };
-        // crawl through all tags and try to add them to our tag map
-        for (key, value) in map {
-            // try to extract the values based on type
-            match value {
-                // The value for this is just a string so just add it
-                Value::String(value) => tags.add_ref(key, value),
-                // The value for this is just a number so just add it
-                Value::Number(value) => tags.add_ref(key, value.to_string()),
-                // The value for this is just a bool so just add it
-                Value::Bool(value) => tags.add_ref(key, value.to_string()),
-                // the value for this is an array so crawl the values in the array
-                Value::Array(values) => {
-                    for value in values {
-                        // try to add each idividual value and error on objects or nested vectors
-                        match value {
-                            // The value for this is just a string so just add it
-                            Value::String(value) => tags.add_ref(&key, value),
-                            // The value for this is just a number so just add it
-                            Value::Number(value) => tags.add_ref(&key, value.to_string()),
-                            // The value for this is just a bool so just add it
-                            Value::Bool(value) => tags.add_ref(&key, value.to_string()),
-                            // error out on unsupported types
-                            _ => fail!(logs, "ERROR: Only strings, numbers, or bools are allowed in nested tag arrays"),
-                        }
-                    }
-                }
-                // error out on unsupported types
-                _ => fail!(
-                    logs,
-                    "ERROR: Only strings, numbers, bools, or arrays are allowed in the tags file"
-                ),
-            }
-        }
+        // add these tags to our tag map
+        tags.add_map(map, logs)?;
     }
     Ok(tags)
 }
 
+/// Extract tags from a result based on the image's auto tag settings
+///
+/// # Arguments
+///
+/// * `output`: The serialized output to look for tags in
+/// * `settings` - The settings to use for output collection
+/// * `logs` - The logs to send to the API
+fn auto_tag(
+    output: &RawResults,
+    settings: &OutputCollection,
+    logs: &mut Sender<String>,
+) -> Result<RawTags, Error> {
+    // skip extracting tags if we didn't get any results
+    if output.scan && !settings.auto_tag.is_empty() {
+        // get our results
+        let results = output.results.get_results();
+        // build an extractor
+        let mut extractor = Extractor::default();
+        // extract any tags from any dumped results
+        extractor.extract(results, settings, logs)
+    } else {
+        Ok(RawTags::default())
+    }
+}
+
 /// Gather all tags for a specific job
 ///
 /// # Arguments
@@ -358,39 +431,83 @@ pub async fn collect<P: AsRef<Path>>(
     path: P,
     logs: &mut Sender<String>,
 ) -> Result<TagBundle, Error> {
-    // skip extracting tags if we didn't get any results
-    let raw = if output.scan && !settings.auto_tag.is_empty() {
-        // get our results
-        let results = output.results.get_results();
-        // build an extractor
-        let mut extractor = Extractor::default();
-        // extract any tags from any dumped results
-        extractor.extract(results, settings, logs)?
-    } else {
-        RawTags::default()
-    };
+    // extract any tags from our results
+    let raw = auto_tag(output, settings, logs)?;
     // read in any tags from our tags file and overlay them on our tags object
     let raw = overlay(raw, path.as_ref(), logs)?;
     // log any tags we discovered
     for (key, values) in &raw.tags {
         log!(logs, "Found tags {}={}", key, values.iter().join(", "));
     }
-    // get our trigger depth
-    let depth = job.trigger_depth.unwrap_or(0);
-    // start with an empty tag bundle
-    let mut bundle = TagBundle::default();
-    // add in the tags if we have a corresponding input
-    if !job.repos.is_empty() {
-        // get the repo tag req and add it
-        bundle.repos = Some(raw.to_req(depth));
+    // build the tag requests for our inputs
+    Ok(raw.to_bundle(job))
+}
+
+/// Extract tags from a result streamed by a running tool
+///
+/// # Arguments
+///
+/// * `job` - The job we are executing
+/// * `output`: The serialized output to look for tags in
+/// * `settings` - The settings to use for output collection
+/// * `logs` - The logs to send to the API
+#[instrument(name = "tags::from_results", skip_all, err(Debug))]
+pub fn from_results(
+    job: &GenericJob,
+    output: &RawResults,
+    settings: &OutputCollection,
+    logs: &mut Sender<String>,
+) -> Result<TagBundle, Error> {
+    // extract any tags from our results
+    let raw = auto_tag(output, settings, logs)?;
+    // log any tags we discovered
+    for (key, values) in &raw.tags {
+        log!(logs, "Found tags {}={}", key, values.iter().join(", "));
+    }
+    // build the tag requests for our inputs
+    Ok(raw.to_bundle(job))
+}
+
+/// Build the tags for a job from a map of tag values sent by a running tool
+///
+/// # Arguments
+///
+/// * `job` - The job we are executing
+/// * `map` - The tag keys and values to build tags from
+/// * `logs` - The logs to send to the API
+#[instrument(name = "tags::from_map", skip_all, err(Debug))]
+pub fn from_map(
+    job: &GenericJob,
+    map: HashMap<String, Value>,
+    logs: &mut Sender<String>,
+) -> Result<TagBundle, Error> {
+    // add our tags to an empty set of raw tags
+    let mut raw = RawTags::default();
+    raw.add_map(map, logs)?;
+    // log any tags we were sent
+    for (key, values) in &raw.tags {
+        log!(logs, "Streamed tags {}={}", key, values.iter().join(", "));
     }
-    // do samples last as its likely to be the most common
-    // so we can just take instead of cloning
-    if !job.samples.is_empty() {
-        // add this to our bundle
-        bundle.samples = Some(raw.to_req(depth));
+    // build the tag requests for our inputs
+    Ok(raw.to_bundle(job))
+}
+
+/// Build the provenance for tags set by this job
+///
+/// The API determines the tool and its version from the job itself.
+///
+/// # Arguments
+///
+/// * `job` - The job that found these tags
+/// * `result` - The result these tags were extracted from if one exists
+pub fn provenance(job: &GenericJob, result: Option<&Uuid>) -> TagProvenanceRequest {
+    // these tags were set by our job
+    let mut provenance = TagProvenanceRequest::default().job(job.id);
+    // tie these tags to the result they came from if we have one
+    if let Some(result) = result {
+        provenance = provenance.result(*result);
     }
-    Ok(bundle)
+    provenance
 }
 
 /// Submit any collected tags to Thorium
@@ -400,18 +517,22 @@ pub async fn collect<P: AsRef<Path>>(
 /// * `thorium` - A client for the Thorium API
 /// * `bundle` - The tag bundles to submit
 /// * `job` - The job we are executing
+/// * `results` - The ids of the results for our samples then repos in order
 /// * `logs` - The logs to send to the API
 #[instrument(name = "tags::submit", skip_all, err(Debug))]
 pub async fn submit(
     thorium: &Thorium,
     bundle: TagBundle,
     job: &GenericJob,
+    results: &[Uuid],
     logs: &mut Sender<String>,
 ) -> Result<(), Error> {
     // if any tags were found then create them for our samples
     if let Some(req) = bundle.samples {
         // try to add these tags to our input samples
-        for sha256 in &job.samples {
+        for (i, sha256) in job.samples.iter().enumerate() {
+            // record where these tags came from
+            let req = req.clone().provenance(provenance(job, results.get(i)));
             // Add new tags to these files
             log!(logs, "Adding tags to {}", sha256);
             thorium.files.tag(sha256, &req).await?;
@@ -420,7 +541,11 @@ pub async fn submit(
     // if any tags were found then create them for our repos
     if let Some(req) = bundle.repos {
         // try to add these tags to our input repos
-        for repo in &job.repos {
+        for (i, repo) in job.repos.iter().enumerate() {
+            // our repo results come after our sample results
+            let result = results.get(job.samples.len() + i);
+            // record where these tags came from
+            let req = req.clone().provenance(provenance(job, result));
             // Add new tags to these files
             log!(logs, "Adding tags to {}", repo.url);
             thorium.repos.tag(&repo.url, &req).await?;
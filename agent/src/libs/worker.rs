@@ -6,7 +6,7 @@ use thorium::models::{StageLogsAdd, WorkerStatus};
 use tracing::{Level, event, instrument, span};
 
 use super::agents::{self, Agent};
-use super::{CurrentTarget, Lifetime, Target};
+use super::{CurrentTarget, Lifetime, Preemption, Target};
 use crate::args::Args;
 
 /// A worker used to execute jobs in Thorium
@@ -23,6 +23,8 @@ pub struct Worker {
     pub lifetime: Lifetime,
     /// Stop claiming new jobs as an update is needed
     pub halt_claiming: bool,
+    /// Whether the scaler has preempted this worker
+    pub preemption: Preemption,
 }
 
 impl Worker {
@@ -49,6 +51,7 @@ impl Worker {
             node,
             lifetime,
             halt_claiming: false,
+            preemption: Preemption::listen(),
         };
         Ok(worker)
     }
@@ -82,7 +85,11 @@ impl Worker {
     /// Claims and executes jobs on a worker
     async fn claim_jobs(&mut self) -> bool {
         // skip any targets with active jobs
-        if self.target.active.is_some() || self.lifetime.exceeded() || self.halt_claiming {
+        if self.target.active.is_some()
+            || self.lifetime.exceeded()
+            || self.halt_claiming
+            || self.preemption.preempted()
+        {
             return false;
         }
         // get any jobs if they exist
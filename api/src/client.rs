@@ -24,7 +24,9 @@ mod repos;
 mod search;
 mod streams;
 mod system;
+mod tags;
 mod traits;
+mod trees;
 mod updates;
 mod users;
 mod utils;
@@ -36,6 +38,7 @@ pub use error::Error;
 pub use events::Events;
 pub use files::Files;
 pub use groups::Groups;
+pub use helpers::api_capture_filter;
 pub use images::Images;
 pub use jobs::Jobs;
 pub use keys::Keys;
@@ -49,7 +52,9 @@ pub use search::events::{SearchEvents, SearchEventsClient};
 pub use search::Search;
 pub use streams::Streams;
 pub use system::System;
+pub use tags::Tags;
 pub use traits::ResultsClient;
+pub use trees::Trees;
 pub use updates::Updates;
 pub use users::Users;
 
@@ -67,6 +72,8 @@ cfg_if::cfg_if! {
         pub use search::SearchBlocking;
         pub use streams::StreamsBlocking;
         pub use system::SystemBlocking;
+        pub use tags::TagsBlocking;
+        pub use trees::TreesBlocking;
         pub use users::UsersBlocking;
         pub use events::EventsBlocking;
     }
@@ -240,6 +247,8 @@ impl ThoriumClientBuilder {
         let updates = Updates::new(&self.host, &auth_str, &client);
         let events = Events::new(&self.host, &auth_str, &client);
         let network_policies = NetworkPolicies::new(&self.host, &auth_str, &client);
+        let tags = Tags::new(&self.host, &auth_str, &client);
+        let trees = Trees::new(&self.host, &auth_str, &client);
         // build Thorium client
         let client = Thorium {
             basic,
@@ -256,6 +265,8 @@ impl ThoriumClientBuilder {
             repos,
             events,
             network_policies,
+            tags,
+            trees,
             host: self.host,
             auth_str,
             expires,
@@ -314,6 +325,8 @@ impl ThoriumClientBuilder {
         let repos = ReposBlocking::new(&self.host, &auth_str, &client);
         let events = EventsBlocking::new(&self.host, &auth_str);
         let network_policies = NetworkPoliciesBlocking::new(&self.host, &auth_str);
+        let tags = TagsBlocking::new(&self.host, &auth_str, &client);
+        let trees = TreesBlocking::new(&self.host, &auth_str, &client);
         // build Thorium client
         let client = ThoriumBlocking {
             basic,
@@ -330,6 +343,8 @@ impl ThoriumClientBuilder {
             repos,
             events,
             network_policies,
+            tags,
+            trees,
             host: self.host,
             auth_str,
             expires,
@@ -372,6 +387,10 @@ pub struct Thorium {
     pub events: Events,
     /// Handles network policies routes in Thorium
     pub network_policies: NetworkPolicies,
+    /// Handles tag routes in Thorium
+    pub tags: Tags,
+    /// Handles tree routes in Thorium
+    pub trees: Trees,
     /// The host/url to reach Thorium at
     pub host: String,
     /// The auth str to use when reverting from a masquerade
@@ -414,6 +433,10 @@ pub struct ThoriumBlocking {
     pub events: EventsBlocking,
     /// Handles network policies routes in Thorium
     pub network_policies: NetworkPoliciesBlocking,
+    /// Handles tag routes in Thorium
+    pub tags: TagsBlocking,
+    /// Handles tree routes in Thorium
+    pub trees: TreesBlocking,
     /// The host/url to reach Thorium at
     pub host: String,
     /// The auth str to use when reverting from a masquerade
@@ -672,6 +695,8 @@ impl Thorium {
         self.files = Files::new(&self.host, &auth_str, &self.client);
         self.repos = Repos::new(&self.host, &auth_str, &self.client);
         self.events = Events::new(&self.host, &auth_str, &self.client);
+        self.tags = Tags::new(&self.host, &auth_str, &self.client);
+        self.trees = Trees::new(&self.host, &auth_str, &self.client);
         Ok(())
     }
 
@@ -698,6 +723,8 @@ impl Thorium {
         self.files = Files::new(&self.host, &auth_str, &self.client);
         self.repos = Repos::new(&self.host, &auth_str, &self.client);
         self.events = Events::new(&self.host, &auth_str, &self.client);
+        self.tags = Tags::new(&self.host, &auth_str, &self.client);
+        self.trees = Trees::new(&self.host, &auth_str, &self.client);
     }
 
     /// Revert back to our original user from a masquerade
@@ -715,6 +742,8 @@ impl Thorium {
         self.files = Files::new(&self.host, &self.auth_str, &self.client);
         self.repos = Repos::new(&self.host, &self.auth_str, &self.client);
         self.events = Events::new(&self.host, &self.auth_str, &self.client);
+        self.tags = Tags::new(&self.host, &self.auth_str, &self.client);
+        self.trees = Trees::new(&self.host, &self.auth_str, &self.client);
     }
 }
 
@@ -792,6 +821,8 @@ impl ThoriumBlocking {
         self.files = FilesBlocking::new(&self.host, &auth_str, &self.client);
         self.repos = ReposBlocking::new(&self.host, &auth_str, &self.client);
         self.events = EventsBlocking::new(&self.host, &auth_str, &self.client);
+        self.tags = TagsBlocking::new(&self.host, &auth_str, &self.client);
+        self.trees = TreesBlocking::new(&self.host, &auth_str, &self.client);
         Ok(())
     }
 
@@ -818,6 +849,8 @@ impl ThoriumBlocking {
         self.files = FilesBlocking::new(&self.host, &auth_str, &self.client);
         self.repos = ReposBlocking::new(&self.host, &auth_str, &self.client);
         self.events = EventsBlocking::new(&self.host, &auth_str, &self.client);
+        self.tags = TagsBlocking::new(&self.host, &auth_str, &self.client);
+        self.trees = TreesBlocking::new(&self.host, &auth_str, &self.client);
     }
 
     /// Revert back to our original user from a masquerade
@@ -835,5 +868,7 @@ impl ThoriumBlocking {
         self.files = FilesBlocking::new(&self.host, &self.auth_str, &self.client);
         self.repos = ReposBlocking::new(&self.host, &self.auth_str, &self.client);
         self.events = EventsBlocking::new(&self.host, &self.auth_str, &self.client);
+        self.tags = TagsBlocking::new(&self.host, &self.auth_str, &self.client);
+        self.trees = TreesBlocking::new(&self.host, &self.auth_str, &self.client);
     }
 }
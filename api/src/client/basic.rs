@@ -1,5 +1,6 @@
 use super::Error;
-use crate::send;
+use crate::models::ResponseCounts;
+use crate::{send, send_build};
 
 #[derive(Clone)]
 pub struct Basic {
@@ -123,4 +124,30 @@ impl Basic {
         // send this request and build a string
         Ok(send!(self.client, req)?.status().is_success())
     }
+
+    /// Get how many responses this API instance has sent
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// let counts = thorium.basic.responses().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn responses(&self) -> Result<ResponseCounts, Error> {
+        // build request
+        let req = self.client.get(format!("{}/api/health/responses", self.host));
+        // send this request and deserialize our response counts
+        send_build!(self.client, req, ResponseCounts)
+    }
 }
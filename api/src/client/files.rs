@@ -1,10 +1,12 @@
+use bytes::Bytes;
 use cart_rs::UncartStream;
 use futures::stream::StreamExt;
 use futures::TryStreamExt;
 use reqwest::StatusCode;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use tokio::fs::OpenOptions;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio_util::io::StreamReader;
 use uuid::Uuid;
 
@@ -14,17 +16,21 @@ use tracing::instrument;
 use super::traits::{GenericClient, ResultsClient, ResultsClientHelper, TransferProgress};
 use super::Error;
 use crate::models::{
-    Attachment, CartedSample, CommentRequest, CommentResponse, Cursor, DeleteCommentParams,
-    DownloadedSample, FileDeleteOpts, FileDownloadOpts, FileListOpts, OutputMap, OutputRequest,
-    OutputResponse, ResultGetParams, Sample, SampleCheck, SampleCheckResponse, SampleListLine,
-    SampleRequest, SampleSubmissionResponse, SubmissionUpdate, TagDeleteRequest, TagRequest,
-    UncartedSample,
+    Attachment, CartedSample, CommentRequest, CommentResponse, CommentUpdate, Cursor,
+    DeleteCommentParams, DownloadedSample, FileDeleteOpts, FileDownloadOpts, FileListOpts,
+    OutputMap, OutputRequest, OutputResponse, ResultGetParams, Sample, SampleCheck,
+    SampleCheckResponse, SampleListLine, SampleRequest, SampleSubmissionResponse, SubmissionUpdate,
+    TagDeleteRequest, TagProvenance, TagProvenanceParams, TagRequest, UncartedSample,
+    UploadSession, UploadSessionRequest,
 };
 use crate::{
     add_date, add_query, add_query_bool, add_query_list, add_query_list_clone, send, send_build,
     send_bytes,
 };
 
+/// The number of times to try uploading a single chunk in a resumable upload
+const CHUNK_RETRIES: usize = 3;
+
 /// A handler for the files routes in Thorium
 #[derive(Clone)]
 pub struct Files {
@@ -162,6 +168,346 @@ impl Files {
         send_build!(self.client, req, SampleSubmissionResponse)
     }
 
+    /// Creates a [`Sample`] in Thorium by uploading a file from disk in resumable chunks
+    ///
+    /// If this upload is interrupted then calling this again with the same file will resume
+    /// it by only uploading the chunks Thorium has not yet received.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_req` - The file request to use to add a file to Thorium (must have a path set)
+    /// * `sha256` - The sha256 of the file being uploaded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use thorium::models::SampleRequest;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // buld the file request
+    /// let file_req = SampleRequest::new("disk.img", vec!("plants".to_owned()));
+    /// // the sha256 of the file we are uploading
+    /// let sha256 = "325030adff0665689b0360ac9c8398cd62a2377e98e06ad7d3914fabacb0daef";
+    /// // upload this file in chunks
+    /// thorium.files.create_chunked(file_req, sha256).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Files::create_chunked",
+            skip(self, file_req),
+            err(Debug)
+        )
+    )]
+    pub async fn create_chunked(
+        &self,
+        file_req: SampleRequest,
+        sha256: &str,
+    ) -> Result<SampleSubmissionResponse, Error> {
+        // chunked uploads can only be used with files on disk
+        let Some(path) = file_req.path.clone() else {
+            return Err(Error::new("Chunked uploads require a path to a file"));
+        };
+        // get the size of the file we are uploading
+        let size = tokio::fs::metadata(&path).await?.len();
+        // start or resume an upload session for this file
+        let session_req = UploadSessionRequest::from_sample_request(file_req, sha256, size);
+        let session = self.create_upload(&session_req).await?;
+        // open the file we are uploading
+        let mut file = tokio::fs::File::open(&path).await?;
+        // upload any chunks that Thorium doesn't already have
+        for chunk in session.missing() {
+            // get the range of bytes in this chunk
+            let Some(range) = session.chunk_range(chunk) else {
+                return Err(Error::new(format!("Chunk {chunk} is out of bounds")));
+            };
+            // read this chunk from disk
+            file.seek(SeekFrom::Start(range.start)).await?;
+            let len = usize::try_from(range.end - range.start)
+                .map_err(|err| Error::new(format!("Chunk {chunk} is too large: {err}")))?;
+            let mut buff = vec![0; len];
+            file.read_exact(&mut buff).await?;
+            let buff = Bytes::from(buff);
+            // upload this chunk retrying a few times if it fails
+            let mut attempt = 1;
+            loop {
+                match self.upload_chunk(&session.id, chunk, buff.clone()).await {
+                    Ok(_) => break,
+                    Err(_) if attempt < CHUNK_RETRIES => attempt += 1,
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        // all of our chunks are uploaded so submit this file
+        self.finish_upload(&session.id).await
+    }
+
+    /// Starts a resumable upload session or resumes an existing one for the same file
+    ///
+    /// # Arguments
+    ///
+    /// * `session_req` - The upload session to start
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use thorium::models::UploadSessionRequest;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build the upload session request
+    /// let sha256 = "325030adff0665689b0360ac9c8398cd62a2377e98e06ad7d3914fabacb0daef";
+    /// let session_req = UploadSessionRequest::new(sha256, 21_474_836_480, vec!("plants"));
+    /// // start this upload session
+    /// let session = thorium.files.create_upload(&session_req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::create_upload", skip_all, err(Debug))
+    )]
+    pub async fn create_upload(
+        &self,
+        session_req: &UploadSessionRequest,
+    ) -> Result<UploadSession, Error> {
+        // build url for starting an upload session
+        let url = format!("{base}/api/files/uploads/", base = self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .json(session_req);
+        // send this request
+        send_build!(self.client, req, UploadSession)
+    }
+
+    /// Gets the status of a resumable upload session
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the upload session to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get the status of an upload session
+    /// let session = thorium.files.get_upload(&Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::get_upload", skip(self), err(Debug))
+    )]
+    pub async fn get_upload(&self, id: &Uuid) -> Result<UploadSession, Error> {
+        // build url for getting an upload session
+        let url = format!("{base}/api/files/uploads/{id}", base = self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request
+        send_build!(self.client, req, UploadSession)
+    }
+
+    /// Lists the live resumable upload sessions for the current user
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // list our upload sessions
+    /// let sessions = thorium.files.list_uploads().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::list_uploads", skip_all, err(Debug))
+    )]
+    pub async fn list_uploads(&self) -> Result<Vec<UploadSession>, Error> {
+        // build url for listing upload sessions
+        let url = format!("{base}/api/files/uploads/", base = self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request
+        send_build!(self.client, req, Vec<UploadSession>)
+    }
+
+    /// Uploads a single chunk of a file in a resumable upload session
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the upload session this chunk is for
+    /// * `chunk` - The index of the chunk being uploaded (starting at 0)
+    /// * `data` - The bytes in this chunk
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // upload the first chunk of a file
+    /// thorium.files.upload_chunk(&Uuid::new_v4(), 0, vec![0; 1024]).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::upload_chunk", skip(self, data), err(Debug))
+    )]
+    pub async fn upload_chunk<B: Into<reqwest::Body>>(
+        &self,
+        id: &Uuid,
+        chunk: u64,
+        data: B,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for uploading a chunk
+        let url = format!("{base}/api/files/uploads/{id}/{chunk}", base = self.host);
+        // build request
+        let req = self
+            .client
+            .put(&url)
+            .header("authorization", &self.token)
+            .header("content-type", "application/octet-stream")
+            .body(data)
+            // give each chunk plenty of time to upload
+            .timeout(std::time::Duration::from_secs(3_600));
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Finishes a resumable upload session and submits the uploaded file as a [`Sample`]
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the upload session to finish
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // finish an upload session
+    /// thorium.files.finish_upload(&Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::finish_upload", skip(self), err(Debug))
+    )]
+    pub async fn finish_upload(&self, id: &Uuid) -> Result<SampleSubmissionResponse, Error> {
+        // build url for finishing an upload session
+        let url = format!("{base}/api/files/uploads/{id}/finish", base = self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            // Thorium has to hash and cart the entire file so use a really long timeout
+            // 86,400 seconds == a day
+            .timeout(std::time::Duration::from_secs(86_400));
+        // send this request
+        send_build!(self.client, req, SampleSubmissionResponse)
+    }
+
+    /// Aborts a resumable upload session and deletes any uploaded chunks
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the upload session to abort
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    /// use uuid::Uuid;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // abort an upload session
+    /// thorium.files.abort_upload(&Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Files::abort_upload", skip(self), err(Debug))
+    )]
+    pub async fn abort_upload(&self, id: &Uuid) -> Result<reqwest::Response, Error> {
+        // build url for aborting an upload session
+        let url = format!("{base}/api/files/uploads/{id}", base = self.host);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
     /// Gets details about a specific [`Sample`] in Thorium
     ///
     /// # Arguments
@@ -732,6 +1078,63 @@ impl Files {
         send!(self.client, req)
     }
 
+    /// Gets where the tags on a sample came from
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sample to get tag provenance for
+    /// * `params` - The params to filter tag provenance with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::{Thorium, models::{TagProvenanceParams, TagSource}};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // only get the tags that were set directly by analysts
+    /// let params = TagProvenanceParams::default().source(TagSource::User);
+    /// // get where this sample's tags came from
+    /// let provenance = thorium.files.tag_provenance("856926b48a936b50e92682807bdae12d5ce39abf509d4c0be82e1327b548705f", &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Files::tag_provenance",
+            skip(self, params),
+            err(Debug)
+        )
+    )]
+    pub async fn tag_provenance(
+        &self,
+        sha256: &str,
+        params: &TagProvenanceParams,
+    ) -> Result<Vec<TagProvenance>, Error> {
+        // build url for getting tag provenance
+        let url = format!("{}/api/files/tag-provenance/{}", self.host, sha256);
+        // build our query params
+        let mut query = vec![];
+        add_query_list!(query, "groups[]", &params.groups);
+        add_query!(query, "source", params.source);
+        add_query!(query, "min_confidence", params.min_confidence);
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send this request and build our tag provenance from the response
+        send_build!(self.client, req, Vec<TagProvenance>)
+    }
+
     /// Adds a new comment to a sample
     ///
     /// # Arguments
@@ -830,6 +1233,61 @@ impl Files {
         send!(self.client, req)
     }
 
+    /// Edits or reacts to a comment on a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The SHA256 of the file the comment is on
+    /// * `comment_id` - The UUID of the comment to update
+    /// * `update` - The update to apply to the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{CommentReaction, CommentUpdate};
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the sample the comment is on
+    /// let sha256 = "856926b48a936b50e92682807bdae12d5ce39abf509d4c0be82e1327b548705f";
+    /// let id = Uuid::new_v4();
+    /// // acknowledge this comment
+    /// let update = CommentUpdate::default().add_reaction(CommentReaction::Acknowledge);
+    /// thorium.files.update_comment(&sha256, &id, &update).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn update_comment<T: AsRef<str>>(
+        &self,
+        sha256: T,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for updating a comment
+        let url = format!(
+            "{}/api/files/comment/{}/{}",
+            self.host,
+            sha256.as_ref(),
+            comment_id
+        );
+        // build request
+        let req = self
+            .client
+            .patch(&url)
+            .json(update)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
     /// Downloads a comment attachment
     ///
     /// # Arguments
@@ -1025,4 +1483,185 @@ impl ResultsClient for Files {
         self.download_result_file_generic(sha256, tool, result_id, path)
             .await
     }
+    /// Adds a comment to a specific result for a file
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_req` - The comment request to send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::CommentRequest;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the file whose result we are commenting on
+    /// let sha256 = "63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f";
+    /// // comment on this result
+    /// let comment_req = CommentRequest::text("This result looks wrong");
+    /// thorium.files.comment_result(sha256, "tool", &Uuid::new_v4(), comment_req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    async fn comment_result<T: AsRef<str>>(
+        &self,
+        sha256: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_req: CommentRequest,
+    ) -> Result<CommentResponse, Error> {
+        self.comment_result_generic(sha256, tool, result_id, comment_req)
+            .await
+    }
+
+    /// Deletes a comment from a specific result for a file
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_id` - The uuid of the comment to delete
+    /// * `params` - The parameters to use when deleting the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::DeleteCommentParams;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the file whose result we are deleting a comment from
+    /// let sha256 = "63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f";
+    /// // delete a comment from this result
+    /// let params = DeleteCommentParams::default();
+    /// thorium.files.delete_result_comment(sha256, "tool", &Uuid::new_v4(), &Uuid::new_v4(), &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    async fn delete_result_comment<T: AsRef<str>>(
+        &self,
+        sha256: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        params: &DeleteCommentParams,
+    ) -> Result<reqwest::Response, Error> {
+        self.delete_result_comment_generic(sha256, tool, result_id, comment_id, params)
+            .await
+    }
+
+    /// Edits or reacts to a comment on a specific result for a file
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_id` - The uuid of the comment to update
+    /// * `update` - The update to apply to the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::CommentUpdate;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the file whose result we are updating a comment on
+    /// let sha256 = "63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f";
+    /// // edit a comment on this result
+    /// let update = CommentUpdate::default().comment("I am an edited comment");
+    /// thorium.files.update_result_comment(sha256, "tool", &Uuid::new_v4(), &Uuid::new_v4(), &update).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    async fn update_result_comment<T: AsRef<str>>(
+        &self,
+        sha256: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error> {
+        self.update_result_comment_generic(sha256, tool, result_id, comment_id, update)
+            .await
+    }
+
+    /// Downloads an attachment from a comment on a specific result for a file
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment` - The id of the comment to download an attachment from
+    /// * `attachment` - The id of the attachment to download
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the file whose result comment we are downloading an attachment from
+    /// let sha256 = "63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f";
+    /// // download an attachment from a comment on this result
+    /// thorium.files.download_result_comment_attachment(sha256, "tool", &Uuid::new_v4(), &Uuid::new_v4(), &Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    async fn download_result_comment_attachment<T: AsRef<str>>(
+        &self,
+        sha256: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment: &Uuid,
+        attachment: &Uuid,
+    ) -> Result<Attachment, Error> {
+        self.download_result_comment_attachment_generic(
+            sha256, tool, result_id, comment, attachment,
+        )
+        .await
+    }
 }
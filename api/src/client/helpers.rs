@@ -89,6 +89,24 @@ pub(super) async fn build_blocking_reqwest_client(
     Ok(builder.build()?)
 }
 
+/// Build a BPF filter that drops traffic to and from the Thorium API
+///
+/// This keeps an agent's own requests to the API out of the network captures of its jobs.
+///
+/// # Arguments
+///
+/// * `api` - The url of the Thorium API
+pub fn api_capture_filter(api: &str) -> Result<String, Error> {
+    let url = url::Url::parse(api)?;
+    // get the host and port this api is reachable at
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return Err(Error::new(format!("{api} does not have a host and port")));
+    };
+    // drop any brackets around ipv6 addresses and any trailing dot on fully qualified names
+    let host = host.trim_start_matches('[').trim_end_matches([']', '.']);
+    Ok(format!("not (host {host} and port {port})"))
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! send {
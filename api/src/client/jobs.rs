@@ -1,4 +1,6 @@
+use bytes::Bytes;
 use chrono::prelude::*;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[cfg(feature = "trace")]
@@ -6,10 +8,10 @@ use tracing::instrument;
 
 use super::Error;
 use crate::models::{
-    Checkpoint, Deadline, GenericJob, HandleJobResponse, ImageScaler, JobResets, RunningJob,
-    StageLogsAdd,
+    Checkpoint, Deadline, GenericJob, HandleJobResponse, ImageScaler, JobCheckpointBlob,
+    JobProgress, JobProgressUpdate, JobResets, RunningJob, StageLogsAdd,
 };
-use crate::{send, send_build};
+use crate::{multipart_file, send, send_build, send_bytes};
 
 #[derive(Clone)]
 pub struct Jobs {
@@ -341,6 +343,51 @@ impl Jobs {
         send_build!(self.client, req, HandleJobResponse)
     }
 
+    /// Tell Thorium this job was preempted and should be returned to the queue
+    ///
+    /// This is used by the agent when its worker is evicted to make room for urgent work.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_id` - The id of the job that was preempted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // return our preempted job to the queue
+    /// thorium.jobs.preempt(&Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Jobs::preempt",
+            skip_all,
+            fields(job = job_id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn preempt(&self, job_id: &Uuid) -> Result<HandleJobResponse, Error> {
+        // build url for preempting a job
+        let url = format!("{base}/api/jobs/handle/{job_id}/preempt", base = &self.host);
+        // build request
+        let req = self.client.post(&url).header("authorization", &self.token);
+        // send this request and build a json value from the response
+        send_build!(self.client, req, HandleJobResponse)
+    }
+
     /// Set a new checkpoint for this job
     ///
     /// This is mainly used by generators but it can be used by anything that can resume execution
@@ -406,6 +453,177 @@ impl Jobs {
         send_build!(self.client, req, HandleJobResponse)
     }
 
+    /// Report the progress of a running job
+    ///
+    /// # Arguments
+    ///
+    /// * `job_id` - The job to report progress for
+    /// * `update` - The progress to report
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::{Thorium, models::JobProgressUpdate};
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // start with the id of the job we are running
+    /// let job_id = Uuid::new_v4();
+    /// // report that we are almost halfway through fuzzing with an hour left
+    /// let update = JobProgressUpdate::default().percent(42.5).phase("fuzzing").eta(3600);
+    /// thorium.jobs.progress(&job_id, &update).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Jobs::progress",
+            skip_all,
+            fields(job = job_id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn progress(
+        &self,
+        job_id: &Uuid,
+        update: &JobProgressUpdate,
+    ) -> Result<JobProgress, Error> {
+        // build url for reporting progress
+        let url = format!(
+            "{base}/api/jobs/handle/{job_id}/progress",
+            base = &self.host,
+            job_id = job_id
+        );
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .json(update);
+        // send this request and build a json value from the response
+        send_build!(self.client, req, JobProgress)
+    }
+
+    /// Save a checkpoint blob for a running job so it can resume after losing its worker
+    ///
+    /// Any checkpoint blob this job previously saved will be overwritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_id` - The job to save a checkpoint blob for
+    /// * `path` - The path to the checkpoint blob to upload
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // start with the id of the job we are running
+    /// let job_id = Uuid::new_v4();
+    /// // save this jobs latest checkpoint
+    /// thorium.jobs.upload_checkpoint_blob(&job_id, "/tmp/thorium/checkpoint").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Jobs::upload_checkpoint_blob",
+            skip(self, path),
+            fields(job = job_id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn upload_checkpoint_blob<P: Into<PathBuf>>(
+        &self,
+        job_id: &Uuid,
+        path: P,
+    ) -> Result<JobCheckpointBlob, Error> {
+        // build url for saving a checkpoint blob
+        let url = format!(
+            "{base}/api/jobs/handle/{job_id}/checkpoint/blob",
+            base = &self.host,
+            job_id = job_id
+        );
+        // build the form containing our checkpoint blob
+        let path = path.into();
+        let form = multipart_file!(reqwest::multipart::Form::new(), "data", path);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .multipart(form);
+        // send this request and build a json value from the response
+        send_build!(self.client, req, JobCheckpointBlob)
+    }
+
+    /// Download the last checkpoint blob a job saved
+    ///
+    /// # Arguments
+    ///
+    /// * `job_id` - The job to download a checkpoint blob for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // start with the id of the job we are resuming
+    /// let job_id = Uuid::new_v4();
+    /// // download this jobs last checkpoint
+    /// let blob = thorium.jobs.download_checkpoint_blob(&job_id).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Jobs::download_checkpoint_blob",
+            skip(self),
+            fields(job = job_id.to_string()),
+            err(Debug)
+        )
+    )]
+    pub async fn download_checkpoint_blob(&self, job_id: &Uuid) -> Result<Bytes, Error> {
+        // build url for downloading a checkpoint blob
+        let url = format!(
+            "{base}/api/jobs/handle/{job_id}/checkpoint/blob",
+            base = &self.host,
+            job_id = job_id
+        );
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send request
+        send_bytes!(self.client, req)
+    }
+
     /// List the deadlines between two timestamps up to a certain limit
     ///
     /// Due to how sorted sets work in redis if you have more deadlines then your limit it can
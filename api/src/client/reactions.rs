@@ -5,10 +5,11 @@ use uuid::Uuid;
 
 use super::{Cursor, Error, LogsCursor};
 use crate::models::{
-    BulkReactionResponse, Reaction, ReactionCreation, ReactionListParams, ReactionRequest,
+    Attachment, BulkReactionResponse, CommentRequest, CommentResponse, CommentUpdate,
+    DeleteCommentParams, Reaction, ReactionCreation, ReactionListParams, ReactionRequest,
     ReactionStatus, ReactionUpdate, StageLogs, StageLogsAdd, StatusUpdate,
 };
-use crate::{send, send_build, send_bytes};
+use crate::{add_query_list, send, send_build, send_bytes};
 
 /// An async Reactions handler for the Thorium client
 #[derive(Clone)]
@@ -988,4 +989,229 @@ impl Reactions {
         // send request
         send_bytes!(self.client, req)
     }
+
+    /// Adds a new comment to a [`Reaction`]
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is from
+    /// * `id` - The reaction to comment on
+    /// * `comment_req` - The comment request to send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::CommentRequest;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // comment on this reaction
+    /// let reaction = Uuid::parse_str("e0ca2720-50e0-4103-a412-344bbb714240")?;
+    /// let comment_req = CommentRequest::text("This failed because of a bad config");
+    /// thorium.reactions.comment("Corn", &reaction, comment_req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::comment", skip(self, comment_req), fields(id = id.to_string()), err(Debug))
+    )]
+    pub async fn comment(
+        &self,
+        group: &str,
+        id: &Uuid,
+        comment_req: CommentRequest,
+    ) -> Result<CommentResponse, Error> {
+        // build url for commenting on a reaction
+        let url = format!("{}/api/reactions/comment/{}/{}", self.host, group, id);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .multipart(comment_req.to_form().await?)
+            .header("authorization", &self.token);
+        // send request
+        send_build!(self.client, req, CommentResponse)
+    }
+
+    /// Deletes a comment from a [`Reaction`]
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is from
+    /// * `id` - The reaction to delete a comment from
+    /// * `comment_id` - The UUID of the comment to delete
+    /// * `params` - The parameters to use when deleting the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::DeleteCommentParams;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // delete a comment from this reaction
+    /// let reaction = Uuid::parse_str("e0ca2720-50e0-4103-a412-344bbb714240")?;
+    /// let comment = Uuid::new_v4();
+    /// let params = DeleteCommentParams::default();
+    /// thorium.reactions.delete_comment("Corn", &reaction, &comment, &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::delete_comment", skip(self), fields(id = id.to_string()), err(Debug))
+    )]
+    pub async fn delete_comment(
+        &self,
+        group: &str,
+        id: &Uuid,
+        comment_id: &Uuid,
+        params: &DeleteCommentParams,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for deleting a comment on a reaction
+        let url = format!(
+            "{}/api/reactions/comment/{}/{}/{}",
+            self.host, group, id, comment_id
+        );
+        // add groups to query if provided
+        let mut query = vec![];
+        add_query_list!(query, "groups[]", params.groups);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send request
+        send!(self.client, req)
+    }
+
+    /// Edits or reacts to a comment on a [`Reaction`]
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is from
+    /// * `id` - The reaction the comment is on
+    /// * `comment_id` - The UUID of the comment to update
+    /// * `update` - The update to apply to the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{CommentReaction, CommentUpdate};
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // acknowledge a comment on this reaction
+    /// let reaction = Uuid::parse_str("e0ca2720-50e0-4103-a412-344bbb714240")?;
+    /// let comment = Uuid::new_v4();
+    /// let update = CommentUpdate::default().add_reaction(CommentReaction::Acknowledge);
+    /// thorium.reactions.update_comment("Corn", &reaction, &comment, &update).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::update_comment", skip(self, update), fields(id = id.to_string()), err(Debug))
+    )]
+    pub async fn update_comment(
+        &self,
+        group: &str,
+        id: &Uuid,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for updating a comment on a reaction
+        let url = format!(
+            "{}/api/reactions/comment/{}/{}/{}",
+            self.host, group, id, comment_id
+        );
+        // build request
+        let req = self
+            .client
+            .patch(&url)
+            .json(update)
+            .header("authorization", &self.token);
+        // send request
+        send!(self.client, req)
+    }
+
+    /// Downloads an attachment from a comment on a [`Reaction`]
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this reaction is from
+    /// * `id` - The reaction the comment is on
+    /// * `comment` - The id of the comment to download an attachment from
+    /// * `attachment` - The id of the attachment to download
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // download an attachment from a comment on this reaction
+    /// let reaction = Uuid::parse_str("e0ca2720-50e0-4103-a412-344bbb714240")?;
+    /// let (comment, attachment) = (Uuid::new_v4(), Uuid::new_v4());
+    /// thorium.reactions.download_attachment("Corn", &reaction, &comment, &attachment).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(name = "Thorium::Reactions::download_attachment", skip(self), fields(id = id.to_string()), err(Debug))
+    )]
+    pub async fn download_attachment(
+        &self,
+        group: &str,
+        id: &Uuid,
+        comment: &Uuid,
+        attachment: &Uuid,
+    ) -> Result<Attachment, Error> {
+        // build url for downloading a reaction comment attachment
+        let url = format!(
+            "{base}/api/reactions/comment/download/{group}/{id}/{comment}/{attachment}",
+            base = self.host,
+        );
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send request and read it as bytes
+        let data = send_bytes!(self.client, req)?;
+        // build our attachment object from the bytes
+        Ok(Attachment { data })
+    }
 }
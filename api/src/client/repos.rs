@@ -16,13 +16,16 @@ use tracing::instrument;
 use super::traits::{GenericClient, ResultsClient, ResultsClientHelper, TransferProgress};
 use super::Error;
 use crate::models::{
-    Attachment, CommitListOpts, Commitish, CommitishDetails, CommitishMapRequest, Cursor,
-    OutputMap, OutputRequest, OutputResponse, Repo, RepoCreateResponse, RepoDataUploadResponse,
-    RepoDownloadOpts, RepoListLine, RepoListOpts, RepoRequest, ResultGetParams, TagDeleteRequest,
-    TagRequest, TarredRepo, UntarredRepo,
+    Attachment, CommentRequest, CommentResponse, CommentUpdate, CommitListOpts, Commitish,
+    CommitishDetails, CommitishMapRequest, Cursor, DeleteCommentParams, OutputMap, OutputRequest,
+    OutputResponse, Repo, RepoCreateResponse, RepoDataUploadResponse, RepoDownloadOpts,
+    RepoListLine, RepoListOpts, RepoMirror, RepoMirrorClaimOpts, RepoMirrorFetch,
+    RepoMirrorRequest, RepoRequest, ResultGetParams, TagDeleteRequest, TagProvenance,
+    TagProvenanceParams, TagRequest, TarredRepo, UntarredRepo,
 };
 use crate::{
     add_date, add_query, add_query_bool, add_query_list, add_query_list_clone, send, send_build,
+    send_bytes,
 };
 
 /// repos handler for the Thorium client
@@ -324,6 +327,291 @@ impl Repos {
         send!(self.client, req)
     }
 
+    /// Gets where the tags on a repo came from
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The repo to get tag provenance for
+    /// * `params` - The params to filter tag provenance with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::{Thorium, models::{TagProvenanceParams, TagSource}};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // only get the tags that were set directly by analysts
+    /// let params = TagProvenanceParams::default().source(TagSource::User);
+    /// // get where this repo's tags came from
+    /// let provenance = thorium.repos.tag_provenance("github.com/rust-lang/rust", &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Repos::tag_provenance",
+            skip(self, params),
+            err(Debug)
+        )
+    )]
+    pub async fn tag_provenance(
+        &self,
+        url: &str,
+        params: &TagProvenanceParams,
+    ) -> Result<Vec<TagProvenance>, Error> {
+        // build url for getting tag provenance
+        let url = format!("{}/api/repos/tag-provenance/{}", self.host, url);
+        // build our query params
+        let mut query = vec![];
+        add_query_list!(query, "groups[]", &params.groups);
+        add_query!(query, "source", params.source);
+        add_query!(query, "min_confidence", params.min_confidence);
+        // build request
+        let req = self
+            .client
+            .get(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send this request and build our tag provenance from the response
+        send_build!(self.client, req, Vec<TagProvenance>)
+    }
+
+    /// Adds a new comment to a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo to comment on
+    /// * `comment_req` - The comment request to send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::{Thorium, models::CommentRequest};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a request to add a comment to this repo
+    /// let comment_req = CommentRequest::text("Corn is tasty");
+    /// // comment on this repo
+    /// thorium.repos.comment("github.com/rust-lang/rust", comment_req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::comment", skip(self, comment_req), err(Debug))
+    )]
+    pub async fn comment(
+        &self,
+        repo: &str,
+        comment_req: CommentRequest,
+    ) -> Result<CommentResponse, Error> {
+        // build url for commenting on a repo
+        let url = format!(
+            "{}/api/repos/comment/{}",
+            self.host,
+            repo.trim_end_matches('/')
+        );
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .multipart(comment_req.to_form().await?)
+            .header("authorization", &self.token);
+        // send this request
+        send_build!(self.client, req, CommentResponse)
+    }
+
+    /// Deletes a comment from a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo to delete a comment from
+    /// * `comment_id` - The UUID of the comment to delete
+    /// * `params` - The parameters to use when deleting the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::DeleteCommentParams;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// let id = Uuid::new_v4();
+    /// // optionally add specific groups to delete the comment from
+    /// let params = DeleteCommentParams::default().groups(vec!["corn", "taco"]);
+    /// // delete the comment with the above id from this repo
+    /// thorium.repos.delete_comment("github.com/rust-lang/rust", &id, &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::delete_comment", skip(self), err(Debug))
+    )]
+    pub async fn delete_comment(
+        &self,
+        repo: &str,
+        comment_id: &Uuid,
+        params: &DeleteCommentParams,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for deleting a comment
+        let url = format!(
+            "{}/api/repos/comment/{}/{}",
+            self.host,
+            repo.trim_end_matches('/'),
+            comment_id
+        );
+        // add groups to query if provided
+        let mut query = vec![];
+        add_query_list!(query, "groups[]", params.groups);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token)
+            .query(&query);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Edits or reacts to a comment on a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo the comment is on
+    /// * `comment_id` - The UUID of the comment to update
+    /// * `update` - The update to apply to the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{CommentReaction, CommentUpdate};
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// let id = Uuid::new_v4();
+    /// // acknowledge the comment with the above id on this repo
+    /// let update = CommentUpdate::default().add_reaction(CommentReaction::Acknowledge);
+    /// thorium.repos.update_comment("github.com/rust-lang/rust", &id, &update).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "Thorium::Repos::update_comment",
+            skip(self, update),
+            err(Debug)
+        )
+    )]
+    pub async fn update_comment(
+        &self,
+        repo: &str,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for updating a comment
+        let url = format!(
+            "{}/api/repos/comment/{}/{}",
+            self.host,
+            repo.trim_end_matches('/'),
+            comment_id
+        );
+        // build request
+        let req = self
+            .client
+            .patch(&url)
+            .json(update)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Downloads a repo comment attachment
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo with the comment
+    /// * `comment` - The id of the comment to download an attachment from
+    /// * `attachment` - The id of the attachment to download
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // have a repo with a comment and an attachment
+    /// let comment = Uuid::new_v4();
+    /// let attachment = Uuid::new_v4();
+    /// // download a comment attachment
+    /// thorium.repos.download_attachment("github.com/rust-lang/rust", &comment, &attachment).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::download_attachment", skip(self), err(Debug))
+    )]
+    pub async fn download_attachment(
+        &self,
+        repo: &str,
+        comment: &Uuid,
+        attachment: &Uuid,
+    ) -> Result<Attachment, Error> {
+        // build url for getting a comment attachment
+        let url = format!(
+            "{base}/api/repos/comment-attachments/{repo}/{comment}/{attachment}",
+            base = self.host,
+            repo = repo.trim_end_matches('/'),
+        );
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and read it as bytes
+        let data = send_bytes!(self.client, req)?;
+        // build our attachment object from the bytes
+        Ok(Attachment { data })
+    }
+
     /// Downloads the zip for a specific repo
     ///
     /// If you are going to immediately unzip this repo then you want `download_unpack` instead.
@@ -669,56 +957,29 @@ impl Repos {
         )
         .await
     }
-}
-
-impl GenericClient for Repos {
-    /// Provide the base url to the repo routes in the API
-    fn base_url(&self) -> String {
-        format!("{}/api/repos", self.host)
-    }
-
-    /// Provide the configured client from `self`
-    fn client(&self) -> &reqwest::Client {
-        &self.client
-    }
-
-    /// Provide the configured auth token from `self`
-    fn token(&self) -> &str {
-        &self.token
-    }
-}
-
-impl ResultsClientHelper for Repos {
-    /// The underlying type that has the results/outputs (see [`crate::models::results::OutputSupport`])
-    type OutputSupport = Repo;
-}
-
-impl ResultsClient for Repos {
-    /// The underlying type that has the results/outputs (see [`crate::models::results::OutputSupport`])
-    type OutputSupport = Repo;
 
-    /// Creates an [`Output`] in Thorium for repos
+    /// Start mirroring a remote repo in Thorium
+    ///
+    /// Thorium will periodically fetch this repo and ingest any new commits, branches, and tags.
     ///
     /// # Arguments
     ///
-    /// * `output_req` - The output request to use to add output from a tool
+    /// * `req` - The remote repo to start mirroring
     ///
     /// # Examples
     ///
     /// ```
-    /// use thorium::Thorium;
-    /// use thorium::client::ResultsClient;
-    /// use thorium::models::{OutputRequest, OutputDisplayType, Repo};
+    /// use thorium::{Thorium, models::RepoMirrorRequest};
     /// # use thorium::Error;
     ///
     /// # async fn exec() -> Result<(), Error> {
-    /// // create thorium client
+    /// // create Thorium client
     /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
-    /// // build our output request
-    /// let repo = "github.com/rust-lang/rust".to_owned();
-    /// let output_req = OutputRequest::<Repo>::new(repo, "TestTool", "I am an output", OutputDisplayType::String);
-    /// // try to create result in thorium
-    /// thorium.repos.create_result(output_req).await?;
+    /// // build a request to mirror this repo every 6 hours
+    /// let req = RepoMirrorRequest::new("https://github.com/rust-lang/rust", vec!("CornPeeps"))
+    ///     .interval(21600);
+    /// // start mirroring this repo
+    /// let mirror = thorium.repos.create_mirror(&req).await?;
     /// # // allow test code to be compiled but don't unwrap as no API instance would be up
     /// # Ok(())
     /// # }
@@ -728,36 +989,34 @@ impl ResultsClient for Repos {
     /// ```
     #[cfg_attr(
         feature = "trace",
-        instrument(name = "ResultsClient<Repos>::create_results", skip_all, err(Debug))
+        instrument(name = "Thorium::Repos::create_mirror", skip(self, req), err(Debug))
     )]
-    async fn create_result(
-        &self,
-        output_req: OutputRequest<Repo>,
-    ) -> Result<OutputResponse, Error> {
-        self.create_result_generic(output_req).await
+    pub async fn create_mirror(&self, req: &RepoMirrorRequest) -> Result<RepoMirror, Error> {
+        // build url for creating a repo mirror
+        let url = format!("{}/api/repos/mirrors/", self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .json(req)
+            .header("authorization", &self.token);
+        // send this request and build our mirror from the response
+        send_build!(self.client, req, RepoMirror)
     }
 
-    /// Gets results for a specific repo
-    ///
-    /// # Arguments
-    ///
-    /// * `repo` - The repo to get results for
-    /// * `params` - The params to use when getting this repo's results
+    /// Lists the repo mirrors we can see
     ///
     /// # Examples
     ///
     /// ```
     /// use thorium::Thorium;
-    /// use thorium::client::ResultsClient;
-    /// use thorium::models::ResultGetParams;
     /// # use thorium::Error;
     ///
     /// # async fn exec() -> Result<(), Error> {
     /// // create Thorium client
     /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
-    /// // get any results for this repo
-    /// let repo = "github.com/user/repo";
-    /// thorium.repos.get_results(repo, &ResultGetParams::default()).await?;
+    /// // list the repos being mirrored
+    /// let mirrors = thorium.repos.list_mirrors().await?;
     /// # // allow test code to be compiled but don't unwrap as no API instance would be up
     /// # Ok(())
     /// # }
@@ -767,16 +1026,335 @@ impl ResultsClient for Repos {
     /// ```
     #[cfg_attr(
         feature = "trace",
-        instrument(name = "ResultsClient<Repos>::get_results", skip(self), fields(repo = repo.as_ref()), err(Debug))
+        instrument(name = "Thorium::Repos::list_mirrors", skip(self), err(Debug))
     )]
-    async fn get_results<T: AsRef<str>>(
-        &self,
-        repo: T,
-        params: &ResultGetParams,
-    ) -> Result<OutputMap, Error> {
-        // trim any ending '/' from the repo URL
-        let repo_trimmed = repo.as_ref().trim_end_matches('/');
-        self.get_results_generic(repo_trimmed, params).await
+    pub async fn list_mirrors(&self) -> Result<Vec<RepoMirror>, Error> {
+        // build url for listing repo mirrors
+        let url = format!("{}/api/repos/mirrors/", self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build our mirrors from the response
+        send_build!(self.client, req, Vec<RepoMirror>)
+    }
+
+    /// Gets a repo mirror
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo whose mirror to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get the mirror for this repo
+    /// let mirror = thorium.repos.get_mirror("github.com/rust-lang/rust").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::get_mirror", skip(self), err(Debug))
+    )]
+    pub async fn get_mirror(&self, repo: &str) -> Result<RepoMirror, Error> {
+        // build url for getting a repo mirror
+        let url = format!("{}/api/repos/mirror/{}", self.host, repo);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build our mirror from the response
+        send_build!(self.client, req, RepoMirror)
+    }
+
+    /// Stops mirroring a repo
+    ///
+    /// Any data already ingested for this repo will not be deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo to stop mirroring
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // stop mirroring this repo
+    /// thorium.repos.delete_mirror("github.com/rust-lang/rust").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::delete_mirror", skip(self), err(Debug))
+    )]
+    pub async fn delete_mirror(&self, repo: &str) -> Result<reqwest::Response, Error> {
+        // build url for deleting a repo mirror
+        let url = format!("{}/api/repos/mirror/{}", self.host, repo);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Fetches a mirrored repo as soon as possible
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo to fetch
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // fetch this repo without waiting for its next scheduled fetch
+    /// thorium.repos.sync_mirror("github.com/rust-lang/rust").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::sync_mirror", skip(self), err(Debug))
+    )]
+    pub async fn sync_mirror(&self, repo: &str) -> Result<reqwest::Response, Error> {
+        // build url for syncing a repo mirror
+        let url = format!("{}/api/repos/mirror-sync/{}", self.host, repo);
+        // build request
+        let req = self.client.post(&url).header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+
+    /// Claims the repo mirrors that are due to be fetched
+    ///
+    /// This is only available to admins as it returns the credentials for each mirror.
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The options for claiming mirrors
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::{Thorium, models::RepoMirrorClaimOpts};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // claim up to 5 mirrors to fetch
+    /// let mirrors = thorium.repos.claim_mirrors(&RepoMirrorClaimOpts::default().limit(5)).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::claim_mirrors", skip(self), err(Debug))
+    )]
+    pub async fn claim_mirrors(
+        &self,
+        opts: &RepoMirrorClaimOpts,
+    ) -> Result<Vec<RepoMirror>, Error> {
+        // build url for claiming repo mirrors
+        let url = format!("{}/api/repos/mirrors/claim/", self.host);
+        // build our query opts
+        let query = vec![("limit", opts.limit)];
+        // build request
+        let req = self
+            .client
+            .patch(&url)
+            .query(&query)
+            .header("authorization", &self.token);
+        // send this request and build our mirrors from the response
+        send_build!(self.client, req, Vec<RepoMirror>)
+    }
+
+    /// Saves the outcome of fetching a mirrored repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The url of the repo that was fetched
+    /// * `fetch` - The outcome of this fetch
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::{Thorium, models::RepoMirrorFetch};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // this fetch failed so record why
+    /// let fetch = RepoMirrorFetch::failed("remote not found");
+    /// thorium.repos.mirror_fetched("github.com/rust-lang/rust", &fetch).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Repos::mirror_fetched", skip(self, fetch), err(Debug))
+    )]
+    pub async fn mirror_fetched(
+        &self,
+        repo: &str,
+        fetch: &RepoMirrorFetch,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for saving a repo mirror fetch
+        let url = format!("{}/api/repos/mirror-fetched/{}", self.host, repo);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .json(fetch)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+}
+
+impl GenericClient for Repos {
+    /// Provide the base url to the repo routes in the API
+    fn base_url(&self) -> String {
+        format!("{}/api/repos", self.host)
+    }
+
+    /// Provide the configured client from `self`
+    fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Provide the configured auth token from `self`
+    fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl ResultsClientHelper for Repos {
+    /// The underlying type that has the results/outputs (see [`crate::models::results::OutputSupport`])
+    type OutputSupport = Repo;
+}
+
+impl ResultsClient for Repos {
+    /// The underlying type that has the results/outputs (see [`crate::models::results::OutputSupport`])
+    type OutputSupport = Repo;
+
+    /// Creates an [`Output`] in Thorium for repos
+    ///
+    /// # Arguments
+    ///
+    /// * `output_req` - The output request to use to add output from a tool
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::{OutputRequest, OutputDisplayType, Repo};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build our output request
+    /// let repo = "github.com/rust-lang/rust".to_owned();
+    /// let output_req = OutputRequest::<Repo>::new(repo, "TestTool", "I am an output", OutputDisplayType::String);
+    /// // try to create result in thorium
+    /// thorium.repos.create_result(output_req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "ResultsClient<Repos>::create_results", skip_all, err(Debug))
+    )]
+    async fn create_result(
+        &self,
+        output_req: OutputRequest<Repo>,
+    ) -> Result<OutputResponse, Error> {
+        self.create_result_generic(output_req).await
+    }
+
+    /// Gets results for a specific repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The repo to get results for
+    /// * `params` - The params to use when getting this repo's results
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::ResultGetParams;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get any results for this repo
+    /// let repo = "github.com/user/repo";
+    /// thorium.repos.get_results(repo, &ResultGetParams::default()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "ResultsClient<Repos>::get_results", skip(self), fields(repo = repo.as_ref()), err(Debug))
+    )]
+    async fn get_results<T: AsRef<str>>(
+        &self,
+        repo: T,
+        params: &ResultGetParams,
+    ) -> Result<OutputMap, Error> {
+        // trim any ending '/' from the repo URL
+        let repo_trimmed = repo.as_ref().trim_end_matches('/');
+        self.get_results_generic(repo_trimmed, params).await
     }
 
     /// Downloads a specific result file for a repo
@@ -835,4 +1413,233 @@ impl ResultsClient for Repos {
         self.download_result_file_generic(repo_trimmed, tool, result_id, path)
             .await
     }
+    /// Adds a comment to a specific result for a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The repo the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_req` - The comment request to send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::CommentRequest;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the repo whose result we are commenting on
+    /// let repo = "github.com/user/repo";
+    /// // comment on this result
+    /// let comment_req = CommentRequest::text("This result looks wrong");
+    /// thorium.repos.comment_result(repo, "tool", &Uuid::new_v4(), comment_req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "ResultsClient<Repos>::comment_result",
+            skip(self, repo, result_id, comment_req),
+            fields(repo = repo.as_ref()),
+            err(Debug)
+        )
+    )]
+    async fn comment_result<T: AsRef<str>>(
+        &self,
+        repo: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_req: CommentRequest,
+    ) -> Result<CommentResponse, Error> {
+        // trim any ending '/' from the repo URL
+        let repo_trimmed = repo.as_ref().trim_end_matches('/');
+        self.comment_result_generic(repo_trimmed, tool, result_id, comment_req)
+            .await
+    }
+
+    /// Deletes a comment from a specific result for a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The repo the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_id` - The uuid of the comment to delete
+    /// * `params` - The parameters to use when deleting the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::DeleteCommentParams;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the repo whose result we are deleting a comment from
+    /// let repo = "github.com/user/repo";
+    /// // delete a comment from this result
+    /// let params = DeleteCommentParams::default();
+    /// thorium.repos.delete_result_comment(repo, "tool", &Uuid::new_v4(), &Uuid::new_v4(), &params).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "ResultsClient<Repos>::delete_result_comment",
+            skip(self, repo, result_id, comment_id, params),
+            fields(repo = repo.as_ref()),
+            err(Debug)
+        )
+    )]
+    async fn delete_result_comment<T: AsRef<str>>(
+        &self,
+        repo: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        params: &DeleteCommentParams,
+    ) -> Result<reqwest::Response, Error> {
+        // trim any ending '/' from the repo URL
+        let repo_trimmed = repo.as_ref().trim_end_matches('/');
+        self.delete_result_comment_generic(repo_trimmed, tool, result_id, comment_id, params)
+            .await
+    }
+
+    /// Edits or reacts to a comment on a specific result for a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The repo the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_id` - The uuid of the comment to update
+    /// * `update` - The update to apply to the comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use thorium::models::CommentUpdate;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the repo whose result we are updating a comment on
+    /// let repo = "github.com/user/repo";
+    /// // edit a comment on this result
+    /// let update = CommentUpdate::default().comment("I am an edited comment");
+    /// thorium.repos.update_result_comment(repo, "tool", &Uuid::new_v4(), &Uuid::new_v4(), &update).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "ResultsClient<Repos>::update_result_comment",
+            skip(self, repo, result_id, comment_id, update),
+            fields(repo = repo.as_ref()),
+            err(Debug)
+        )
+    )]
+    async fn update_result_comment<T: AsRef<str>>(
+        &self,
+        repo: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error> {
+        // trim any ending '/' from the repo URL
+        let repo_trimmed = repo.as_ref().trim_end_matches('/');
+        self.update_result_comment_generic(repo_trimmed, tool, result_id, comment_id, update)
+            .await
+    }
+
+    /// Downloads an attachment from a comment on a specific result for a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The repo the result is for
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment` - The id of the comment to download an attachment from
+    /// * `attachment` - The id of the attachment to download
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::client::ResultsClient;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // the repo whose result comment we are downloading an attachment from
+    /// let repo = "github.com/user/repo";
+    /// // download an attachment from a comment on this result
+    /// thorium.repos.download_result_comment_attachment(repo, "tool", &Uuid::new_v4(), &Uuid::new_v4(), &Uuid::new_v4()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(
+            name = "ResultsClient<Repos>::download_result_comment_attachment",
+            skip(self, repo, result_id, comment, attachment),
+            fields(repo = repo.as_ref()),
+            err(Debug)
+        )
+    )]
+    async fn download_result_comment_attachment<T: AsRef<str>>(
+        &self,
+        repo: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment: &Uuid,
+        attachment: &Uuid,
+    ) -> Result<Attachment, Error> {
+        // trim any ending '/' from the repo URL
+        let repo_trimmed = repo.as_ref().trim_end_matches('/');
+        self.download_result_comment_attachment_generic(
+            repo_trimmed,
+            tool,
+            result_id,
+            comment,
+            attachment,
+        )
+        .await
+    }
 }
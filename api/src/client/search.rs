@@ -1,8 +1,8 @@
 //! The search support for the Thorium client
 
 use super::{Error, SearchEvents};
-use crate::models::{Cursor, ElasticDoc, ElasticSearchOpts};
-use crate::{add_date, add_query, add_query_list};
+use crate::models::{Cursor, ElasticDoc, ElasticSearchOpts, SavedSearch, SavedSearchRequest};
+use crate::{add_date, add_query, add_query_list, send, send_build};
 
 pub mod events;
 
@@ -156,4 +156,143 @@ impl Search {
         )
         .await
     }
+
+    /// Saves a search so it can be run again later
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The search to save
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::SavedSearchRequest;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build the search to save
+    /// let req = SavedSearchRequest::new("corn-pe", "corn AND pe32").group("CornPeeps");
+    /// // save our search
+    /// let saved = thorium.search.create_saved(&req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn create_saved(&self, req: &SavedSearchRequest) -> Result<SavedSearch, Error> {
+        // build the url for saving a search
+        let url = format!("{}/api/search/saved/", self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .json(req);
+        // send this request and build a saved search from the response
+        send_build!(self.client, req, SavedSearch)
+    }
+
+    /// Gets a search that we have saved
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the saved search to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get a saved search and build the options to run it with
+    /// let saved = thorium.search.get_saved("corn-pe").await?;
+    /// let cursor = thorium.search.search(&saved.to_opts()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn get_saved(&self, name: &str) -> Result<SavedSearch, Error> {
+        // build the url for getting a saved search
+        let url = format!("{}/api/search/saved/{}", self.host, name);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build a saved search from the response
+        send_build!(self.client, req, SavedSearch)
+    }
+
+    /// Lists all of the searches we have saved sorted by name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // list our saved searches
+    /// let saved = thorium.search.list_saved().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn list_saved(&self) -> Result<Vec<SavedSearch>, Error> {
+        // build the url for listing saved searches
+        let url = format!("{}/api/search/saved/", self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build a list of saved searches from the response
+        send_build!(self.client, req, Vec<SavedSearch>)
+    }
+
+    /// Deletes a search that we have saved
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the saved search to delete
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // delete a saved search
+    /// thorium.search.delete_saved("corn-pe").await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn delete_saved(&self, name: &str) -> Result<reqwest::Response, Error> {
+        // build the url for deleting a saved search
+        let url = format!("{}/api/search/saved/{}", self.host, name);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
 }
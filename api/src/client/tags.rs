@@ -0,0 +1,255 @@
+//! Client handler for tag routes in Thorium
+
+use uuid::Uuid;
+
+use super::Error;
+use crate::models::{BulkTagJob, BulkTagRequest};
+use crate::{send, send_build};
+
+#[cfg(feature = "trace")]
+use tracing::instrument;
+
+/// A handler for the tags routes in Thorium
+#[derive(Clone)]
+pub struct Tags {
+    /// The host/url that Thorium can be reached at
+    host: String,
+    /// token to use for auth
+    token: String,
+    /// A reqwest client for reqwests
+    client: reqwest::Client,
+}
+
+impl Tags {
+    /// Creates a new tags handler
+    ///
+    /// Instead of directly creating this handler you likely want to simply create a
+    /// `thorium::Thorium` and use the handler within that instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - url/ip of the Thorium api
+    /// * `token` - The token used for authentication
+    /// * `client` - The reqwest client to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::client::Tags;
+    ///
+    /// let client = reqwest::Client::new();
+    /// let tags = Tags::new("http://127.0.0.1", "token", &client);
+    /// ```
+    #[must_use]
+    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+        // build tags route handler
+        Tags {
+            host: host.to_owned(),
+            token: token.to_owned(),
+            client: client.clone(),
+        }
+    }
+}
+
+// only include blocking structs if the sync feature is enabled
+cfg_if::cfg_if! {
+    if #[cfg(feature = "sync")] {
+        #[derive(Clone)]
+        pub struct TagsBlocking {
+            host: String,
+            /// token to use for auth
+            token: String,
+            client: reqwest::Client,
+        }
+
+        impl TagsBlocking {
+            /// creates a new blocking tags handler
+            ///
+            /// Instead of directly creating this handler you likely want to simply create a
+            /// `thorium::ThoriumBlocking` and use the handler within that instead.
+            ///
+            ///
+            /// # Arguments
+            ///
+            /// * `host` - The url/ip of the Thorium api
+            /// * `token` - The token used for authentication
+            /// * `client` - The reqwest client to use
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use thorium::client::TagsBlocking;
+            ///
+            /// let tags = TagsBlocking::new("http://127.0.0.1", "token");
+            /// ```
+            pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+                // build tags route handler
+                TagsBlocking {
+                    host: host.to_owned(),
+                    token: token.to_owned(),
+                    client: client.clone(),
+                }
+            }
+        }
+    }
+}
+
+#[syncwrap::clone_impl]
+impl Tags {
+    /// Start a [`BulkTagJob`] that renames, replaces, or deletes tags across many items
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The bulk tag operation to run
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::BulkTagRequest;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // rename the malware_family key to family for all files in a group
+    /// let req = BulkTagRequest::rename_key("malware_family", "family").group("CornGroup");
+    /// let job = thorium.tags.bulk(&req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Tags::bulk", skip(self), err(Debug))
+    )]
+    pub async fn bulk(&self, req: &BulkTagRequest) -> Result<BulkTagJob, Error> {
+        // build url for starting a bulk tag job
+        let url = format!("{base}/api/tags/bulk/", base = self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .json(req);
+        // send this request and build a bulk tag job from the response
+        send_build!(self.client, req, BulkTagJob)
+    }
+
+    /// List the [`BulkTagJob`]s we can see from newest to oldest
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // list our bulk tag jobs
+    /// let jobs = thorium.tags.list_bulk().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Tags::list_bulk", skip(self), err(Debug))
+    )]
+    pub async fn list_bulk(&self) -> Result<Vec<BulkTagJob>, Error> {
+        // build url for listing bulk tag jobs
+        let url = format!("{base}/api/tags/bulk/", base = self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build a list of bulk tag jobs from the response
+        send_build!(self.client, req, Vec<BulkTagJob>)
+    }
+
+    /// Get the status of a [`BulkTagJob`]
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the bulk tag job to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// # let id = uuid::Uuid::new_v4();
+    /// // check how far along this bulk tag job is
+    /// let job = thorium.tags.get_bulk(&id).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Tags::get_bulk", skip(self), err(Debug))
+    )]
+    pub async fn get_bulk(&self, id: &Uuid) -> Result<BulkTagJob, Error> {
+        // build url for getting a bulk tag job
+        let url = format!("{base}/api/tags/bulk/{id}", base = self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send this request and build a bulk tag job from the response
+        send_build!(self.client, req, BulkTagJob)
+    }
+
+    /// Cancel a [`BulkTagJob`]
+    ///
+    /// Any items that were already modified will not be reverted.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the bulk tag job to cancel
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// # let id = uuid::Uuid::new_v4();
+    /// // stop this bulk tag job
+    /// thorium.tags.cancel_bulk(&id).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Tags::cancel_bulk", skip(self), err(Debug))
+    )]
+    pub async fn cancel_bulk(&self, id: &Uuid) -> Result<reqwest::Response, Error> {
+        // build url for cancelling a bulk tag job
+        let url = format!("{base}/api/tags/bulk/{id}", base = self.host);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send this request
+        send!(self.client, req)
+    }
+}
@@ -7,10 +7,10 @@ use crate::{
     add_query_bool, add_query_list,
     client::Error,
     models::{
-        backends::OutputSupport, Attachment, KeySupport, OutputMap, OutputRequest, OutputResponse,
-        ResultGetParams,
+        backends::OutputSupport, Attachment, CommentRequest, CommentResponse, CommentUpdate,
+        DeleteCommentParams, KeySupport, OutputMap, OutputRequest, OutputResponse, ResultGetParams,
     },
-    send_build, send_bytes,
+    send, send_build, send_bytes,
 };
 
 /// A helper trait containing generic implementations for `ResultsClient`
@@ -115,6 +115,140 @@ pub trait ResultsClientHelper: GenericClient {
         // build our attachment object from the bytes
         Ok(Attachment { data })
     }
+
+    /// Adds a comment to a specific result for the type of `Self::OutputSupport`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use to access the data the results are attached to
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_req` - The comment request to send
+    async fn comment_result_generic<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_req: CommentRequest,
+    ) -> Result<CommentResponse, Error> {
+        // build url for commenting on a result
+        let url = format!(
+            "{base}/result-comments/{key}/{tool}/{result_id}",
+            base = self.base_url(),
+            key = key.as_ref()
+        );
+        // build request
+        let req = self
+            .client()
+            .post(&url)
+            .multipart(comment_req.to_form().await?)
+            .header("authorization", self.token());
+        // send this request
+        send_build!(self.client(), req, CommentResponse)
+    }
+
+    /// Deletes a comment from a specific result for the type of `Self::OutputSupport`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use to access the data the results are attached to
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_id` - The uuid of the comment to delete
+    /// * `params` - The parameters to use when deleting the comment
+    async fn delete_result_comment_generic<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        params: &DeleteCommentParams,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for deleting a result comment
+        let url = format!(
+            "{base}/result-comments/{key}/{tool}/{result_id}/{comment_id}",
+            base = self.base_url(),
+            key = key.as_ref()
+        );
+        // add groups to query if provided
+        let mut query = vec![];
+        add_query_list!(query, "groups[]", params.groups);
+        // build request
+        let req = self
+            .client()
+            .delete(&url)
+            .header("authorization", self.token())
+            .query(&query);
+        // send this request
+        send!(self.client(), req)
+    }
+
+    /// Edits or reacts to a comment on a specific result for the type of `Self::OutputSupport`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use to access the data the results are attached to
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment_id` - The uuid of the comment to update
+    /// * `update` - The update to apply to the comment
+    async fn update_result_comment_generic<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error> {
+        // build url for updating a result comment
+        let url = format!(
+            "{base}/result-comments/{key}/{tool}/{result_id}/{comment_id}",
+            base = self.base_url(),
+            key = key.as_ref()
+        );
+        // build request
+        let req = self
+            .client()
+            .patch(&url)
+            .json(update)
+            .header("authorization", self.token());
+        // send this request
+        send!(self.client(), req)
+    }
+
+    /// Downloads an attachment from a comment on a specific result for the type of `Self::OutputSupport`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use to access the data the results are attached to
+    /// * `tool` - The tool that made this result
+    /// * `result_id` - The uuid for this result
+    /// * `comment` - The id of the comment to download an attachment from
+    /// * `attachment` - The id of the attachment to download
+    async fn download_result_comment_attachment_generic<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment: &Uuid,
+        attachment: &Uuid,
+    ) -> Result<Attachment, Error> {
+        // build url for downloading a result comment attachment
+        let url = format!(
+            "{base}/result-comment-attachments/{key}/{tool}/{result_id}/{comment}/{attachment}",
+            base = self.base_url(),
+            key = key.as_ref()
+        );
+        // build request
+        let req = self
+            .client()
+            .get(&url)
+            .header("authorization", self.token());
+        // send this request and read it as bytes
+        let data = send_bytes!(self.client(), req)?;
+        // build our attachment object from the bytes
+        Ok(Attachment { data })
+    }
 }
 
 /// Describes a client that is capable of creating and retrieving results for a
@@ -147,4 +281,39 @@ pub trait ResultsClient {
     where
         T: AsRef<str>,
         P: AsRef<Path>;
+
+    async fn comment_result<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_req: CommentRequest,
+    ) -> Result<CommentResponse, Error>;
+
+    async fn delete_result_comment<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        params: &DeleteCommentParams,
+    ) -> Result<reqwest::Response, Error>;
+
+    async fn update_result_comment<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment_id: &Uuid,
+        update: &CommentUpdate,
+    ) -> Result<reqwest::Response, Error>;
+
+    async fn download_result_comment_attachment<T: AsRef<str>>(
+        &self,
+        key: T,
+        tool: &str,
+        result_id: &Uuid,
+        comment: &Uuid,
+        attachment: &Uuid,
+    ) -> Result<Attachment, Error>;
 }
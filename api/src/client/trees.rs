@@ -0,0 +1,254 @@
+//! Client handler for tree routes in Thorium
+
+use uuid::Uuid;
+
+use super::Error;
+use crate::models::{Tree, TreeGrowQuery, TreeParams, TreeQuery};
+use crate::send_build;
+
+#[cfg(feature = "trace")]
+use tracing::instrument;
+
+/// A handler for the trees routes in Thorium
+#[derive(Clone)]
+pub struct Trees {
+    /// The host/url that Thorium can be reached at
+    host: String,
+    /// token to use for auth
+    token: String,
+    /// A reqwest client for reqwests
+    client: reqwest::Client,
+}
+
+impl Trees {
+    /// Creates a new trees handler
+    ///
+    /// Instead of directly creating this handler you likely want to simply create a
+    /// `thorium::Thorium` and use the handler within that instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - url/ip of the Thorium api
+    /// * `token` - The token used for authentication
+    /// * `client` - The reqwest client to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::client::Trees;
+    ///
+    /// let client = reqwest::Client::new();
+    /// let trees = Trees::new("http://127.0.0.1", "token", &client);
+    /// ```
+    #[must_use]
+    pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+        // build trees route handler
+        Trees {
+            host: host.to_owned(),
+            token: token.to_owned(),
+            client: client.clone(),
+        }
+    }
+}
+
+// only include blocking structs if the sync feature is enabled
+cfg_if::cfg_if! {
+    if #[cfg(feature = "sync")] {
+        #[derive(Clone)]
+        pub struct TreesBlocking {
+            host: String,
+            /// token to use for auth
+            token: String,
+            client: reqwest::Client,
+        }
+
+        impl TreesBlocking {
+            /// creates a new blocking trees handler
+            ///
+            /// Instead of directly creating this handler you likely want to simply create a
+            /// `thorium::ThoriumBlocking` and use the handler within that instead.
+            ///
+            ///
+            /// # Arguments
+            ///
+            /// * `host` - The url/ip of the Thorium api
+            /// * `token` - The token used for authentication
+            /// * `client` - The reqwest client to use
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use thorium::client::TreesBlocking;
+            ///
+            /// let trees = TreesBlocking::new("http://127.0.0.1", "token");
+            /// ```
+            pub fn new(host: &str, token: &str, client: &reqwest::Client) -> Self {
+                // build trees route handler
+                TreesBlocking {
+                    host: host.to_owned(),
+                    token: token.to_owned(),
+                    client: client.clone(),
+                }
+            }
+        }
+    }
+}
+
+#[syncwrap::clone_impl]
+impl Trees {
+    /// Start building a [`Tree`] in Thorium
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The initial data to build this tree from
+    /// * `params` - The params to use when growing this tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{TreeParams, TreeQuery};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a tree starting from a single sample
+    /// let query = TreeQuery::default()
+    ///     .sample("63b9ceb0c7e5ccf4bcba3d8a6bac0cbf4cb3d4d2e2e7f0b7fcd1a36e0d8bd1e9");
+    /// let tree = thorium.trees.start(&query, &TreeParams::default()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Trees::start", skip(self), err(Debug))
+    )]
+    pub async fn start(&self, query: &TreeQuery, params: &TreeParams) -> Result<Tree, Error> {
+        // build url for starting a tree
+        let url = format!("{base}/api/trees/", base = self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .header("authorization", &self.token)
+            .query(&[("limit", params.limit)])
+            .json(query);
+        // send this request and build a tree from the response
+        send_build!(self.client, req, Tree)
+    }
+
+    /// Grow some nodes in an existing [`Tree`] in Thorium
+    ///
+    /// Only the newly added nodes and the links to them will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The id of the tree to grow
+    /// * `query` - The nodes to grow
+    /// * `params` - The params to use when growing this tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{TreeGrowQuery, TreeParams};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// # let cursor = uuid::Uuid::new_v4();
+    /// // grow a single node in this tree
+    /// let query = TreeGrowQuery { growable: vec![1234] };
+    /// let grown = thorium.trees.grow(&cursor, &query, &TreeParams::default()).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Trees::grow", skip(self), err(Debug))
+    )]
+    pub async fn grow(
+        &self,
+        cursor: &Uuid,
+        query: &TreeGrowQuery,
+        params: &TreeParams,
+    ) -> Result<Tree, Error> {
+        // build url for growing a tree
+        let url = format!("{base}/api/trees/{cursor}", base = self.host);
+        // build request
+        let req = self
+            .client
+            .patch(&url)
+            .header("authorization", &self.token)
+            .query(&[("limit", params.limit)])
+            .json(query);
+        // send this request and build a tree from the response
+        send_build!(self.client, req, Tree)
+    }
+
+    /// Build a [`Tree`] out to a specific depth one ring at a time
+    ///
+    /// Each ring of the tree is grown in its own request and merged into
+    /// a single tree so large trees don't have to be built in one request.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The initial data to build this tree from
+    /// * `depth` - The number of rings to grow this tree out to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::TreeQuery;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // build a tree three rings deep starting from a repo
+    /// let query = TreeQuery::default().repo("github.com/curl/curl");
+    /// let tree = thorium.trees.build(&query, 3).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    #[cfg_attr(
+        feature = "trace",
+        instrument(name = "Thorium::Trees::build", skip(self), err(Debug))
+    )]
+    pub async fn build(&self, query: &TreeQuery, depth: usize) -> Result<Tree, Error> {
+        // only grow a single ring per request
+        let params = TreeParams { limit: 1 };
+        // start our tree with its initial nodes and first ring
+        let mut tree = self.start(query, &params).await?;
+        // keep growing our tree until we reach our depth or run out of nodes to grow
+        for _ in 1..depth {
+            // stop early if there is nothing left to grow
+            if tree.growable.is_empty() {
+                break;
+            }
+            // grow the next ring of this tree
+            let grow = TreeGrowQuery {
+                growable: tree.growable.clone(),
+            };
+            let grown = self.grow(&tree.id, &grow, &params).await?;
+            // merge this ring into our tree
+            tree.merge(grown);
+        }
+        Ok(tree)
+    }
+}
@@ -1,7 +1,12 @@
 use base64::Engine as _;
 
+use uuid::Uuid;
+
 use super::{helpers, ClientSettings, Error};
-use crate::models::{AuthResponse, ScrubbedUser, UserCreate, UserUpdate};
+use crate::models::{
+    AuthResponse, Notification, ScrubbedUser, Subscription, SubscriptionRequest,
+    SubscriptionResponse, User, UserCreate, UserUpdate,
+};
 use crate::{send, send_build};
 
 /// users handler for the Thorium client
@@ -468,4 +473,213 @@ impl Users {
         // send request
         send!(self.client, req)
     }
+
+    /// Get all of the notifications in our inbox
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get the notifications in our inbox
+    /// let notifications = thorium.users.notifications().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn notifications(&self) -> Result<Vec<Notification<User>>, Error> {
+        // build url for getting our notifications
+        let url = format!("{}/api/users/notifications/", self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send request and build our notifications
+        send_build!(self.client, req, Vec<Notification<User>>)
+    }
+
+    /// Delete a notification from our inbox
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the notification to delete
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // delete a notification from our inbox
+    /// let id = Uuid::parse_str("7c3c1e1c-5b8c-4b0e-9f0b-2f5f6b3b8c11").unwrap();
+    /// thorium.users.delete_notification(&id).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn delete_notification(&self, id: &Uuid) -> Result<reqwest::Response, Error> {
+        // build url for deleting a notification
+        let url = format!("{}/api/users/notifications/{}", self.host, id);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send request
+        send!(self.client, req)
+    }
+
+    /// Delete all of the notifications in our inbox
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // clear our inbox
+    /// thorium.users.clear_notifications().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn clear_notifications(&self) -> Result<reqwest::Response, Error> {
+        // build url for clearing our notifications
+        let url = format!("{}/api/users/notifications/", self.host);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send request
+        send!(self.client, req)
+    }
+
+    /// Subscribe to something in Thorium to get notifications when it changes
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The subscription request
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use thorium::models::{SubscriptionEvent, SubscriptionRequest, SubscriptionTarget};
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // get notified whenever a reaction in the harvest pipeline fails
+    /// let req = SubscriptionRequest::new(SubscriptionTarget::pipeline("CornPeeps", "harvest"))
+    ///     .event(SubscriptionEvent::FailedReactions);
+    /// thorium.users.subscribe(&req).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn subscribe(
+        &self,
+        req: &SubscriptionRequest,
+    ) -> Result<SubscriptionResponse, Error> {
+        // build url for subscribing
+        let url = format!("{}/api/users/notifications/subscriptions/", self.host);
+        // build request
+        let req = self
+            .client
+            .post(&url)
+            .json(req)
+            .header("authorization", &self.token);
+        // send request and build our response
+        send_build!(self.client, req, SubscriptionResponse)
+    }
+
+    /// List our subscriptions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // list our subscriptions
+    /// let subscriptions = thorium.users.list_subscriptions().await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn list_subscriptions(&self) -> Result<Vec<Subscription>, Error> {
+        // build url for listing our subscriptions
+        let url = format!("{}/api/users/notifications/subscriptions/", self.host);
+        // build request
+        let req = self.client.get(&url).header("authorization", &self.token);
+        // send request and build our subscriptions
+        send_build!(self.client, req, Vec<Subscription>)
+    }
+
+    /// Delete one of our subscriptions
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the subscription to delete
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::Thorium;
+    /// use uuid::Uuid;
+    /// # use thorium::Error;
+    ///
+    /// # async fn exec() -> Result<(), Error> {
+    /// // create Thorium client
+    /// let thorium = Thorium::build("http://127.0.0.1").token("<token>").build().await?;
+    /// // stop following something
+    /// let id = Uuid::parse_str("7c3c1e1c-5b8c-4b0e-9f0b-2f5f6b3b8c11").unwrap();
+    /// thorium.users.unsubscribe(&id).await?;
+    /// # // allow test code to be compiled but don't unwrap as no API instance would be up
+    /// # Ok(())
+    /// # }
+    /// # tokio_test::block_on(async {
+    /// #    exec().await
+    /// # });
+    /// ```
+    pub async fn unsubscribe(&self, id: &Uuid) -> Result<reqwest::Response, Error> {
+        // build url for deleting a subscription
+        let url = format!("{}/api/users/notifications/subscriptions/{}", self.host, id);
+        // build request
+        let req = self
+            .client
+            .delete(&url)
+            .header("authorization", &self.token);
+        // send request
+        send!(self.client, req)
+    }
 }
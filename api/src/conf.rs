@@ -8,8 +8,8 @@ use std::path::{Path, PathBuf};
 use base64::Engine as _;
 
 use crate::models::{
-    Image, ImageScaler, NetworkPolicyCustomK8sRule, NetworkPolicyCustomLabel, NetworkPolicyRuleRaw,
-    NetworkProtocol, UnixInfo,
+    CpuArch, Image, ImageScaler, NetworkPolicyCustomK8sRule, NetworkPolicyCustomLabel,
+    NetworkPolicyRuleRaw, NetworkProtocol, Resources, UnixInfo,
 };
 
 /// Helps serde default a value to false
@@ -429,6 +429,34 @@ fn default_fair_share_divisor() -> u64 {
     1
 }
 
+/// Helps serde default the preemption grace period for k8s pods to 60 seconds
+fn default_preemption_grace_period() -> u64 {
+    60
+}
+
+/// Helps serde default the image used to capture network traffic in k8s pods
+fn default_capture_image() -> String {
+    "docker.io/nicolaka/netshoot:latest".to_owned()
+}
+
+/// The simulated internet service that egress from sandboxed tools is redirected to
+///
+/// This is expected to be an INetSim like service that answers every DNS query with
+/// its own address and serves fake HTTP, SMTP, and other common protocols.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct SimulatedInternet {
+    /// The namespace the simulated internet service is running in
+    pub namespace: String,
+    /// The labels selecting the simulated internet service's pods
+    #[serde(default)]
+    pub pod_labels: BTreeMap<String, String>,
+    /// The cluster IP of the simulated internet's DNS server
+    pub dns: String,
+    /// The url to pull the requests a pod made to the simulated internet from
+    #[serde(default)]
+    pub logs: Option<String>,
+}
+
 /// The settings for a single k8s cluster
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct K8sCluster {
@@ -504,6 +532,15 @@ pub struct K8s {
     /// The divisor to use when calculating what % of resources to reduce fair share ranks by
     #[serde(default = "default_fair_share_divisor")]
     pub fair_share_divisor: u64,
+    /// How long preempted pods have to save a checkpoint and return their job in seconds
+    #[serde(default = "default_preemption_grace_period")]
+    pub preemption_grace_period: u64,
+    /// The image to use for network capture sidecars (must contain sh and tcpdump)
+    #[serde(default = "default_capture_image")]
+    pub capture_image: String,
+    /// The simulated internet service to redirect sandboxed tools to
+    #[serde(default)]
+    pub simulated_internet: Option<SimulatedInternet>,
 }
 
 impl Default for K8s {
@@ -515,6 +552,9 @@ impl Default for K8s {
             dwell: default_dwell(),
             fair_share: FairShareWeights::default(),
             fair_share_divisor: default_fair_share_divisor(),
+            preemption_grace_period: default_preemption_grace_period(),
+            capture_image: default_capture_image(),
+            simulated_internet: None,
         }
     }
 }
@@ -779,6 +819,9 @@ pub struct KvmCluster {
     /// The nodes in the cluster to run Thorium jobs on by hostname/ip and any restrictions
     #[serde(default)]
     pub nodes: HashMap<String, KvmNodeSettings>,
+    /// Whether the reactors in this cluster launch microVMs instead of libvirt vms
+    #[serde(default)]
+    pub microvm: bool,
     /// The max positive sway when configuring agents
     #[serde(default = "default_max_sway")]
     pub max_sway: u64,
@@ -808,6 +851,10 @@ impl Kvm {
     pub fn restrictions(&self, restrictions: &mut WorkerRestrictions) {
         // crawl over our baremetal clusters
         for (cluster_name, cluster) in &self.clusters {
+            // track which clusters launch microVMs
+            if cluster.microvm {
+                restrictions.microvm_clusters.insert(cluster_name.clone());
+            }
             // crawl over the nodes in this cluster
             for (node_name, node) in &cluster.nodes {
                 // if this node has image restrictions then add those
@@ -879,6 +926,16 @@ fn default_decreasing_fair_share() -> u32 {
     600
 }
 
+/// Helps serde default the node provisioning check to 30 seconds
+fn default_provision() -> u32 {
+    30
+}
+
+/// Helps serde default the repo mirror fetch check to 300 seconds
+fn default_repo_mirrors() -> u32 {
+    300
+}
+
 /// The time delay between different tasks carried out in the scaler
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ScalerTaskDelays {
@@ -903,6 +960,12 @@ pub struct ScalerTaskDelays {
     /// How long to wait between decreasing fair share ranks
     #[serde(default = "default_decreasing_fair_share")]
     pub decrease_fair_share: u32,
+    /// How long to wait between checking if nodes should be provisioned or retired
+    #[serde(default = "default_provision")]
+    pub provision: u32,
+    /// How long to wait between checking for mirrored repos that need to be fetched
+    #[serde(default = "default_repo_mirrors")]
+    pub repo_mirrors: u32,
 }
 
 impl Default for ScalerTaskDelays {
@@ -916,6 +979,8 @@ impl Default for ScalerTaskDelays {
             resources: default_resources(),
             cleanup: default_cleanup(),
             decrease_fair_share: default_decreasing_fair_share(),
+            provision: default_provision(),
+            repo_mirrors: default_repo_mirrors(),
         }
     }
 }
@@ -954,6 +1019,9 @@ pub struct WorkerRestrictions {
     pub clusters: HashSet<String>,
     /// The groups/images that have cluster/node preferences or restrictions
     pub images: HashMap<String, HashMap<String, ClusterImageRestrictions>>,
+    /// The kvm clusters that launch microVMs instead of libvirt vms
+    #[serde(default)]
+    pub microvm_clusters: HashSet<String>,
 }
 
 impl WorkerRestrictions {
@@ -962,6 +1030,12 @@ impl WorkerRestrictions {
     /// If an empty list of nodes is returned that means
     #[must_use]
     pub fn check<'a>(&'a self, cluster: &str, image: &Image) -> IsRestricted<'a> {
+        // kvm images can only be spawned on clusters that launch the same kind of vm
+        if image.scaler == ImageScaler::Kvm
+            && image.microvm.is_some() != self.microvm_clusters.contains(cluster)
+        {
+            return IsRestricted::WrongCluster;
+        }
         // try to get the restrictions for this image
         if let Some(group_restrictions) = self.images.get(&image.group) {
             if let Some(image_restrictions) = group_restrictions.get(&image.name) {
@@ -1026,6 +1100,97 @@ impl Default for Crane {
     }
 }
 
+/// Helps serde default how long demand must exceed capacity before provisioning to 300 seconds
+fn default_provision_dwell() -> u64 {
+    300
+}
+
+/// Helps serde default how long a provisioned node can be idle before being retired to 900 seconds
+fn default_provision_idle() -> u64 {
+    900
+}
+
+/// Helps serde default the max number of provisioned nodes to 10
+fn default_provision_max_nodes() -> u64 {
+    10
+}
+
+/// Helps serde default how long a provisioning hook can run before being abandoned to 300 seconds
+fn default_provision_timeout() -> u64 {
+    300
+}
+
+/// A shape of node that can be provisioned
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct NodeShape {
+    /// The name of this shape (the flavor or instance type to request)
+    pub name: String,
+    /// The number of cpu cores a node of this shape has
+    pub cpu: u64,
+    /// The amount of memory a node of this shape has in mebibytes
+    pub memory: u64,
+    /// The amount of ephemeral storage a node of this shape has in mebibytes
+    #[serde(default)]
+    pub ephemeral_storage: u64,
+    /// The number of Nvidia GPUs a node of this shape has
+    #[serde(default)]
+    pub nvidia_gpu: u64,
+    /// The number of AMD GPUs a node of this shape has
+    #[serde(default)]
+    pub amd_gpu: u64,
+    /// The CPU architecture of this shape
+    #[serde(default)]
+    pub arch: CpuArch,
+}
+
+impl NodeShape {
+    /// Get the resources a node of this shape provides
+    #[must_use]
+    pub fn resources(&self) -> Resources {
+        // build the resources for this shape
+        let mut resources = Resources::new(self.cpu * 1000, self.memory, self.ephemeral_storage, 0);
+        // add any gpus
+        resources.nvidia_gpu = self.nvidia_gpu;
+        resources.amd_gpu = self.amd_gpu;
+        resources
+    }
+}
+
+/// The settings for provisioning new nodes when queued demand exceeds capacity
+///
+/// Either a command or a url must be set. Requests are sent as JSON to the command on
+/// stdin or POSTed to the url and a JSON response is expected back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct Provisioner {
+    /// The executable to call to provision or retire nodes
+    #[serde(default)]
+    pub command: Option<PathBuf>,
+    /// Any extra args to pass to our provisioning command
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The endpoint to call to provision or retire nodes
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The cluster that provisioned nodes will join
+    ///
+    /// Any node in this cluster is treated as provisioned so it can be retired once idle.
+    pub cluster: String,
+    /// How long demand must exceed capacity before provisioning nodes in seconds
+    #[serde(default = "default_provision_dwell")]
+    pub dwell: u64,
+    /// How long a provisioned node must be idle before it is retired in seconds
+    #[serde(default = "default_provision_idle")]
+    pub idle: u64,
+    /// The max number of nodes that can be provisioned at once
+    #[serde(default = "default_provision_max_nodes")]
+    pub max_nodes: u64,
+    /// How long a single call to our command or url can run before it is abandoned in seconds
+    #[serde(default = "default_provision_timeout")]
+    pub timeout: u64,
+    /// The shapes of nodes that can be provisioned
+    pub shapes: Vec<NodeShape>,
+}
+
 /// The settings for the Thorium scalers
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct Scaler {
@@ -1053,6 +1218,9 @@ pub struct Scaler {
     /// The crane specific setttings
     #[serde(default)]
     pub crane: Crane,
+    /// The settings for provisioning new nodes if autoscaling is enabled
+    #[serde(default)]
+    pub provisioner: Option<Provisioner>,
 }
 
 impl Default for Scaler {
@@ -1066,6 +1234,7 @@ impl Default for Scaler {
             kvm: Kvm::default(),
             tasks: ScalerTaskDelays::default(),
             crane: Crane::default(),
+            provisioner: None,
         }
     }
 }
@@ -1400,6 +1569,16 @@ fn default_files_partition_size() -> u16 {
     180
 }
 
+/// Helps serde default the size of resumable upload chunks to 64 mebibytes
+fn default_files_upload_chunk_size() -> u64 {
+    67_108_864
+}
+
+/// Helps serde default how long idle resumable uploads are retained to 1 day
+fn default_files_upload_expire() -> u64 {
+    86_400
+}
+
 /// The settings for saving/Carting files to the backend
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct Files {
@@ -1415,6 +1594,12 @@ pub struct Files {
     /// The number of seconds each partition in the database should cover
     #[serde(default = "default_files_partition_size")]
     pub partition_size: u16,
+    /// The size in bytes of each chunk in a resumable upload (must be at least 5 mebibytes)
+    #[serde(default = "default_files_upload_chunk_size")]
+    pub upload_chunk_size: u64,
+    /// How long in seconds an idle resumable upload is kept before it is cleaned up
+    #[serde(default = "default_files_upload_expire")]
+    pub upload_expire: u64,
 }
 
 impl Default for Files {
@@ -1424,6 +1609,8 @@ impl Default for Files {
             bucket: default_files_bucket(),
             earliest: default_files_earliest(),
             partition_size: default_files_partition_size(),
+            upload_chunk_size: default_files_upload_chunk_size(),
+            upload_expire: default_files_upload_expire(),
         }
     }
 }
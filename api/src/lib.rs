@@ -126,7 +126,7 @@ fn build_app(
     use axum::{http::Request, response::Response};
     use routes::{
         basic, binaries, docs, events, files, groups, images, jobs, network_policies, pipelines,
-        reactions, repos, search, streams, system, trees, ui, users,
+        reactions, repos, search, streams, system, tags, trees, ui, users,
     };
     use std::time::Duration;
     use tower_http::set_header::SetResponseHeaderLayer;
@@ -154,6 +154,7 @@ fn build_app(
     app = system::mount(app);
     app = ui::mount(app);
     app = users::mount(app);
+    app = tags::mount(app);
     app = trees::mount(app);
     // setup our tracing
     let trace_provider = trace::setup("ThoriumAPI", &conf.thorium.tracing);
@@ -167,6 +168,7 @@ fn build_app(
             Method::POST,
             Method::PATCH,
             Method::DELETE,
+            Method::PUT,
         ]);
         // cast the domains we want to add to the correct type
         let origins = conf
@@ -204,6 +206,8 @@ fn build_app(
                 .on_response(|response: &Response, latency: Duration, span: &Span| {
                     // get our status code
                     let code = response.status();
+                    // count this response so rollouts can check our error rate
+                    crate::models::ResponseCounts::record(code);
                     // build our response event
                     event!(
                         parent: span,
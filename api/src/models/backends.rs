@@ -25,8 +25,11 @@ mod backends_reexport {
     pub mod search;
     pub mod setup;
     pub mod streams;
+    pub mod subscriptions;
     pub mod system;
+    pub mod tags;
     pub mod trees;
+    pub mod uploads;
     pub mod users;
     pub mod version;
     pub mod volumes;
@@ -45,6 +48,7 @@ mod backends_reexport_client {
 
     pub use support::NotificationSupport;
     pub use support::OutputSupport;
+    pub use support::SubscriptionSupport;
     pub use support::TagSupport;
 }
 
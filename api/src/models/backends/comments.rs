@@ -1,18 +1,32 @@
 //! Wrappers for interacting with comments
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
 use aws_sdk_s3::primitives::ByteStream;
 use axum::extract::Multipart;
-use tracing::instrument;
+use chrono::prelude::*;
+use tracing::{event, instrument, Level};
 use uuid::Uuid;
 
 use super::db;
-use crate::models::{CommentForm, CommentResponse, Group, GroupAllowAction, User};
+use crate::models::backends::SubscriptionSupport;
+use crate::models::{
+    Comment, CommentForm, CommentResponse, CommentRevision, CommentUpdate, Group, GroupAllowAction,
+    Notification, NotificationLevel, SubscriptionEvent, User,
+};
 use crate::utils::{ApiError, Shared};
-use crate::{bad, can_create_all};
+use crate::{bad, can_create_all, not_found, unauthorized};
+
+pub trait CommentSupport: SubscriptionSupport {
+    /// Get the key that comments on this object are saved under
+    fn comment_key(&self) -> String;
+
+    /// Get the groups this object can be seen in
+    fn comment_groups(&self) -> HashSet<&str>;
+
+    /// Get the comments on this object
+    fn comments(&self) -> &[Comment];
 
-pub trait CommentSupport {
     /// Creates a new comment
     ///
     /// # Arguments
@@ -26,7 +40,44 @@ pub trait CommentSupport {
         user: &User,
         req: Multipart,
         shared: &Shared,
-    ) -> Result<CommentResponse, ApiError>;
+    ) -> Result<CommentResponse, ApiError> {
+        // build our comment form
+        let mut form = CommentForm::default();
+        // get the key and groups for this object
+        let key = self.comment_key();
+        let groups = self.comment_groups();
+        // try to save this comment to the backend
+        match create_comment_helper(user, &key, &groups, self.comments(), req, &mut form, shared)
+            .await
+        {
+            Ok(()) => {
+                // let anyone following this object know about this new comment
+                if let Some(target) = self.subscription_target() {
+                    let msg = format!("{} commented on {}", user.username, target);
+                    db::subscriptions::notify(
+                        &target,
+                        SubscriptionEvent::Comments,
+                        &form.groups,
+                        Some(&user.username),
+                        msg,
+                        shared,
+                    )
+                    .await;
+                }
+                Ok(CommentResponse { id: form.id })
+            }
+            Err(err) => {
+                // delete all our dangling comment attachments
+                for (_, s3_id) in form.attachments {
+                    // build the path to delete this attachment at in s3
+                    let s3_path = format!("{}/{}/{}", &key, form.id, s3_id);
+                    // delete this attachment from s3
+                    shared.s3.attachments.delete(&s3_path).await?;
+                }
+                Err(err)
+            }
+        }
+    }
 
     /// Deletes a comment
     ///
@@ -43,7 +94,107 @@ pub trait CommentSupport {
         groups: &[String],
         id: &Uuid,
         shared: &Shared,
-    ) -> Result<(), ApiError>;
+    ) -> Result<(), ApiError> {
+        // get the comment at the given id
+        let Some(comment) = self.comments().iter().find(|comment| &comment.id == id) else {
+            return not_found!("Error deleting comment: comment not found".to_string());
+        };
+        // return an unauthorized error if the user is not the comment author or isn't an admin
+        if user.username != comment.author && !user.is_admin() {
+            return unauthorized!(
+                "Error deleting comment: comments can only be deleted by their authors".to_string()
+            );
+        }
+        // delete from all comment groups of none were given,
+        // otherwise check the comment is in all of the given groups
+        let groups = if groups.is_empty() {
+            &comment.groups
+        } else {
+            if !groups.iter().all(|group| comment.groups.contains(group)) {
+                return bad!(
+                    "Error deleting comment: comment is not in all of the given groups".to_string()
+                );
+            }
+            groups
+        };
+        // get the key comments on this object are saved under
+        let key = self.comment_key();
+        db::comments::delete(&key, groups, comment, shared).await?;
+        db::comments::prune_attachments(&[comment.to_owned()], &key, shared).await
+    }
+
+    /// Updates the text or reactions for a comment
+    ///
+    /// Only the author of a comment can edit its text but anyone who can see it can react to it.
+    /// The text a comment had before an edit is kept as a revision.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is updating the comment
+    /// * `id` - The id of the comment to update
+    /// * `update` - The update to apply to this comment
+    /// * `shared` - Shared Thorium objects
+    #[allow(async_fn_in_trait)]
+    async fn update_comment(
+        &self,
+        user: &User,
+        id: &Uuid,
+        update: CommentUpdate,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // get the comment at the given id
+        let Some(comment) = self.comments().iter().find(|comment| &comment.id == id) else {
+            return not_found!("Error updating comment: comment not found".to_string());
+        };
+        let mut comment = comment.clone();
+        // get the key comments on this object are saved under
+        let key = self.comment_key();
+        // track any users that are newly mentioned by this edit
+        let mut mentions = BTreeSet::default();
+        if let Some(text) = update.comment {
+            // return an unauthorized error if the user is not the comment author
+            if user.username != comment.author {
+                return unauthorized!(
+                    "Error updating comment: comments can only be edited by their authors"
+                        .to_string()
+                );
+            }
+            // only edit this comment if its text actually changed
+            if text != comment.comment {
+                // don't notify users that were already mentioned before this edit
+                let previous = Comment::parse_mentions(&comment.comment);
+                mentions = Comment::parse_mentions(&text)
+                    .into_iter()
+                    .filter(|username| !previous.contains(username))
+                    .collect();
+                // keep the current text as a revision
+                let written = comment.edited.unwrap_or(comment.uploaded);
+                let old = std::mem::replace(&mut comment.comment, text);
+                comment.revisions.push(CommentRevision {
+                    comment: old,
+                    written,
+                });
+                comment.edited = Some(Utc::now());
+                // save our new text
+                db::comments::update(&key, &comment, shared).await?;
+            }
+        }
+        // add and remove this users reactions
+        if !update.add_reactions.is_empty() || !update.remove_reactions.is_empty() {
+            db::comments::react(
+                user,
+                &key,
+                &comment,
+                &update.add_reactions,
+                &update.remove_reactions,
+                shared,
+            )
+            .await?;
+        }
+        // notify any newly mentioned users
+        notify_mentions(user, &key, &comment.id, &comment.groups, mentions, shared).await;
+        Ok(())
+    }
 
     /// Downloads an attachment from a specific comment
     ///
@@ -58,7 +209,22 @@ pub trait CommentSupport {
         comment: &Uuid,
         attachment: &Uuid,
         shared: &Shared,
-    ) -> Result<ByteStream, ApiError>;
+    ) -> Result<ByteStream, ApiError> {
+        // make sure this is a valid comment for this object
+        if let Some(comment) = self.comments().iter().find(|com| &com.id == comment) {
+            // make sure this attachment is from this comment
+            if comment.attachments.iter().any(|(_, id)| attachment == id) {
+                // build the path to this atachment
+                let path = format!("{}/{}/{}", self.comment_key(), comment.id, attachment);
+                // download and return this attachment
+                return shared.s3.attachments.download(&path).await;
+            }
+        }
+        not_found!(format!(
+            "Attachment {} for comment {} not found",
+            attachment, comment
+        ))
+    }
 }
 
 /// Helps create a new comment for an object
@@ -68,6 +234,7 @@ pub trait CommentSupport {
 /// * `user` - The user that is adding new comments
 /// * `key` - The key for the object this comment is for
 /// * `groups` - The groups to add this comment too if no groups are specified
+/// * `comments` - The comments already on this object
 /// * `req` - The multipart form containing our new comment and any attachments
 /// * `form` - The comment form to add our multipart entries too
 /// * `shared` - Shared objects in Thorium
@@ -80,6 +247,7 @@ pub async fn create_comment_helper(
     user: &User,
     key: &str,
     groups: &HashSet<&str>,
+    comments: &[Comment],
     mut req: Multipart,
     form: &mut CommentForm,
     shared: &Shared,
@@ -108,10 +276,22 @@ pub async fn create_comment_helper(
                 .insert(file_name.unwrap_or_else(|| s3_id.to_string()), s3_id);
         }
     }
+    // make sure the comment we are replying to is on this object
+    let parent = match &form.parent {
+        Some(parent) => match comments.iter().find(|comment| &comment.id == parent) {
+            Some(parent) => Some(parent),
+            None => return not_found!(format!("Comment {parent} to reply to not found")),
+        },
+        None => None,
+    };
     // provide sane defaults if the user provided no groups, otherwise check that they are valid
     if form.groups.is_empty() {
-        // get the groups we can see this sample in
-        form.groups.extend(groups.iter().map(ToString::to_string));
+        match parent {
+            // replies default to the groups of the comment they are replying to
+            Some(parent) => form.groups.extend(parent.groups.iter().cloned()),
+            // get the groups we can see this object in
+            None => form.groups.extend(groups.iter().map(ToString::to_string)),
+        }
         // make sure we can actually upload files to all the requested groups
         let groups = Group::authorize_check_allow_all(
             user,
@@ -135,5 +315,91 @@ pub async fn create_comment_helper(
         can_create_all!(groups, user, shared);
     }
     // save the new comment into scylla
-    db::files::create_comment(user, key, form, shared).await
+    db::comments::create(user, key, form, shared).await?;
+    // notify any users mentioned in this comment
+    let mentions = Comment::parse_mentions(&form.comment);
+    notify_mentions(user, key, &form.id, &form.groups, mentions, shared).await;
+    Ok(())
+}
+
+/// Notifies the users mentioned in a comment
+///
+/// Failing to notify users is logged instead of failing the comment since it has already
+/// been saved.
+///
+/// # Arguments
+///
+/// * `author` - The user that mentioned these users
+/// * `key` - The key for the object this comment is on
+/// * `id` - The id of the comment these users were mentioned in
+/// * `groups` - The groups this comment is in
+/// * `mentions` - The usernames that were mentioned
+/// * `shared` - Shared objects in Thorium
+async fn notify_mentions(
+    author: &User,
+    key: &str,
+    id: &Uuid,
+    groups: &[String],
+    mentions: BTreeSet<String>,
+    shared: &Shared,
+) {
+    if let Err(err) = notify_mentions_helper(author, key, id, groups, mentions, shared).await {
+        event!(
+            Level::ERROR,
+            msg = "Failed to notify mentioned users",
+            error = err.to_string()
+        );
+    }
+}
+
+/// Helps notify the users mentioned in a comment
+///
+/// Mentions of users that don't exist or that can't see this comment are ignored.
+///
+/// # Arguments
+///
+/// * `author` - The user that mentioned these users
+/// * `key` - The key for the object this comment is on
+/// * `id` - The id of the comment these users were mentioned in
+/// * `groups` - The groups this comment is in
+/// * `mentions` - The usernames that were mentioned
+/// * `shared` - Shared objects in Thorium
+#[instrument(
+    name = "backends::comments::notify_mentions",
+    skip(author, groups, shared),
+    err(Debug)
+)]
+async fn notify_mentions_helper(
+    author: &User,
+    key: &str,
+    id: &Uuid,
+    groups: &[String],
+    mentions: BTreeSet<String>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    for username in mentions {
+        // don't notify users that mention themselves
+        if username == author.username {
+            continue;
+        }
+        // skip any mentions of users that don't exist
+        let Ok(mentioned) = User::force_get(&username, shared).await else {
+            continue;
+        };
+        // skip any users that can't see this comment
+        if !mentioned.is_admin_or_analyst()
+            && !groups.iter().any(|group| mentioned.groups.contains(group))
+        {
+            continue;
+        }
+        // build and save this users notification
+        let msg = format!(
+            "{} mentioned you in comment {} on {}",
+            author.username, id, key
+        );
+        let notification: Notification<User> =
+            Notification::new(mentioned.username, msg, NotificationLevel::Info);
+        db::notifications::create(notification, None, shared).await?;
+    }
+    Ok(())
 }
@@ -1,4 +1,5 @@
 pub mod census;
+pub mod comments;
 pub mod cursors;
 pub mod elastic;
 mod errors;
@@ -19,9 +20,11 @@ pub mod results;
 pub mod s3;
 pub mod search;
 pub mod streams;
+pub mod subscriptions;
 pub mod system;
 pub mod tags;
 pub mod trees;
+pub mod uploads;
 pub mod users;
 
 pub use cursors::{
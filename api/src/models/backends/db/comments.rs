@@ -0,0 +1,452 @@
+//! Saves comments on objects into the backend
+//!
+//! Comments are stored by the key of the object they are on (a sha256, repo url, or id).
+
+use chrono::prelude::*;
+use std::collections::hash_map::Entry::{Occupied, Vacant};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{event, instrument, Level};
+use uuid::Uuid;
+
+use crate::models::{Comment, CommentForm, CommentReaction, CommentRow, User};
+use crate::utils::{ApiError, Shared};
+use crate::{log_scylla_err, serialize};
+
+/// Gets all the comments for an object
+///
+/// # Arguments
+///
+/// * `groups` - The groups to restrict our returned comments too
+/// * `key` - The key for the object to get comments for
+/// * `list` - The vector to add our comments too
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::comments::get", skip(list, shared), err(Debug))]
+pub async fn get(
+    groups: &Vec<String>,
+    key: &str,
+    list: &mut Vec<Comment>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // if we have more then 100 groups then chunk it into bathes of 100  otherwise just get our info
+    if groups.len() > 100 {
+        // break our groups into chunks of 100
+        for chunk in groups.chunks(100) {
+            // turn our group chunk into a vec
+            let chunk_vec = chunk.to_vec();
+            // get this chunks data
+            let query = shared
+                .scylla
+                .session
+                .execute_unpaged(&shared.scylla.prep.comments.get, (chunk_vec, key))
+                .await?;
+            // build a btreemap to collect all comments and then sort them
+            let mut map: BTreeMap<DateTime<Utc>, HashMap<Uuid, Comment>> = BTreeMap::default();
+            // enable casting to types for this query
+            let query_rows = query.into_rows_result()?;
+            // set the type to cast this stream too
+            let typed_iter = query_rows.rows::<CommentRow>()?;
+            // crawl over rows and add them to our tag map while logging any errors
+            typed_iter
+                .filter_map(|res| log_scylla_err!(res))
+                .for_each(|comment| {
+                    // get an entry to the nested map of comments by id
+                    let id_map = map.entry(comment.uploaded).or_default();
+                    // get our comments doc so we can add a group or insert it
+                    match id_map.entry(comment.id) {
+                        // this comment has already been added so just add a new group to it
+                        Occupied(entry) => entry.into_mut().groups.push(comment.group),
+                        Vacant(entry) => {
+                            // try to turn this row into a comment
+                            let res = Comment::try_from(comment);
+                            // if we can deserialize this string then insert it
+                            if let Some(cast) = log_scylla_err!(res) {
+                                entry.insert(cast);
+                            }
+                        }
+                    }
+                });
+            // flatten our map of comments and append it to our list of comments
+            list.extend(map.into_iter().flat_map(|(_, map)| map.into_values()));
+        }
+    } else {
+        // we have less then 100 groups so just get their data
+        let query = shared
+            .scylla
+            .session
+            .execute_unpaged(&shared.scylla.prep.comments.get, (groups, key))
+            .await?;
+        // enable casting to types for this query
+        let query_rows = query.into_rows_result()?;
+        // set the type to cast this stream too
+        let typed_iter = query_rows.rows::<CommentRow>()?;
+        // build a btreemap to collect all comments and then sort them
+        let mut map: BTreeMap<DateTime<Utc>, HashMap<Uuid, Comment>> = BTreeMap::default();
+        // crawl over rows and add them to our tag map while logging any errors
+        typed_iter
+            .filter_map(|res| log_scylla_err!(res))
+            .for_each(|comment| {
+                // get an entry to the nested map of comments by id
+                let id_map = map.entry(comment.uploaded).or_default();
+                // get our comments doc so we can add a group or insert it
+                match id_map.entry(comment.id) {
+                    // this comment has already been added so just add a new group to it
+                    Occupied(entry) => entry.into_mut().groups.push(comment.group),
+                    Vacant(entry) => {
+                        // try to turn this row into a comment
+                        let res = Comment::try_from(comment);
+                        // if we can deserialize this string then insert it
+                        if let Some(cast) = log_scylla_err!(res) {
+                            entry.insert(cast);
+                        }
+                    }
+                }
+            });
+        // flatten our map of comments and append it to our list of comments
+        list.extend(map.into_iter().flat_map(|(_, map)| map.into_values()));
+    }
+    Ok(())
+}
+
+/// Gets all the comments for many objects at once
+///
+/// # Arguments
+///
+/// * `groups` - The groups to restrict our returned comments too
+/// * `keys` - The keys for the objects to get comments for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::comments::get_many", skip(shared), err(Debug))]
+pub async fn get_many(
+    groups: &[String],
+    keys: &[String],
+    shared: &Shared,
+) -> Result<HashMap<String, Vec<Comment>>, ApiError> {
+    // build a btreemap to collect and sort the comments for each object
+    let mut maps: HashMap<String, BTreeMap<DateTime<Utc>, HashMap<Uuid, Comment>>> =
+        HashMap::with_capacity(keys.len());
+    // scylla limits the partitions a query can hit so keep groups * keys at 100 or less
+    let group_chunk = groups.len().clamp(1, 100);
+    let key_chunk = (100 / group_chunk).max(1);
+    for groups in groups.chunks(group_chunk) {
+        for keys in keys.chunks(key_chunk) {
+            // get this chunks data
+            let query = shared
+                .scylla
+                .session
+                .execute_unpaged(
+                    &shared.scylla.prep.comments.get_many,
+                    (groups.to_vec(), keys.to_vec()),
+                )
+                .await?;
+            // enable casting to types for this query
+            let query_rows = query.into_rows_result()?;
+            // set the type to cast this stream too
+            let typed_iter = query_rows.rows::<CommentRow>()?;
+            // crawl over rows and add them to the right objects comments while logging any errors
+            typed_iter
+                .filter_map(|res| log_scylla_err!(res))
+                .for_each(|comment| {
+                    // get an entry to the nested map of comments by id for this object
+                    let id_map = maps
+                        .entry(comment.sha256.clone())
+                        .or_default()
+                        .entry(comment.uploaded)
+                        .or_default();
+                    // get our comments doc so we can add a group or insert it
+                    match id_map.entry(comment.id) {
+                        // this comment has already been added so just add a new group to it
+                        Occupied(entry) => entry.into_mut().groups.push(comment.group),
+                        Vacant(entry) => {
+                            // try to turn this row into a comment
+                            let res = Comment::try_from(comment);
+                            // if we can deserialize this string then insert it
+                            if let Some(cast) = log_scylla_err!(res) {
+                                entry.insert(cast);
+                            }
+                        }
+                    }
+                });
+        }
+    }
+    // flatten each objects map of comments into a sorted list
+    let comments = maps
+        .into_iter()
+        .map(|(key, map)| {
+            (
+                key,
+                map.into_values().flat_map(HashMap::into_values).collect(),
+            )
+        })
+        .collect();
+    Ok(comments)
+}
+
+/// Creates a comment for a specific object
+///
+/// # Arguments
+///
+/// * `user` - The user that is adding new comments
+/// * `key` - The key for the object to comment on
+/// * `form` - The comment to save to scylla
+/// * `shared` - Shared objects in Thorium
+#[instrument(name = "db::comments::create", skip(user, form, shared), err(Debug))]
+pub async fn create(
+    user: &User,
+    key: &str,
+    form: &CommentForm,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // serialize our s3 paths
+    let paths = serialize!(&form.attachments);
+    // get the current timestamp
+    let now = Utc::now();
+    // create a comment row for each group
+    for group in form.groups.iter() {
+        shared
+            .scylla
+            .session
+            .execute_unpaged(
+                &shared.scylla.prep.comments.insert,
+                (
+                    &group,
+                    &key,
+                    now,
+                    &form.id,
+                    &user.username,
+                    &form.comment,
+                    &paths,
+                    &form.parent,
+                ),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Gets all of the groups a comment is in
+///
+/// # Arguments
+///
+/// * `key` - The key for the object this comment is on
+/// * `id` - The id of the comment to get the groups for
+/// * `shared` - Shared Thorium objects
+async fn get_groups(key: &str, id: &Uuid, shared: &Shared) -> Result<Vec<String>, ApiError> {
+    // get all of the groups this comment is in
+    let query = shared
+        .scylla
+        .session
+        .execute_unpaged(&shared.scylla.prep.comments.get_groups, (id, key))
+        .await?;
+    // enable casting to types for this query
+    let query_rows = query.into_rows_result()?;
+    // cast each row to a group
+    let mut groups = Vec::default();
+    for row in query_rows.rows::<(String,)>()? {
+        let (group,) = row?;
+        groups.push(group);
+    }
+    Ok(groups)
+}
+
+/// Updates the text and revisions for a comment on a specific object
+///
+/// The comment is updated in every group it is in and not just the groups the current user can
+/// see so that all groups have the same copy of this comment.
+///
+/// # Arguments
+///
+/// * `key` - The key for the object this comment is on
+/// * `comment` - The updated comment to save
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::comments::update", skip(comment, shared), err(Debug))]
+pub async fn update(key: &str, comment: &Comment, shared: &Shared) -> Result<(), ApiError> {
+    // serialize our revisions
+    let revisions = serialize!(&comment.revisions);
+    // update this comment in each of its groups
+    for group in get_groups(key, &comment.id, shared).await? {
+        // raise instead of just logging errors so groups don't get out of sync
+        shared
+            .scylla
+            .session
+            .execute_unpaged(
+                &shared.scylla.prep.comments.update,
+                (
+                    &comment.comment,
+                    comment.edited,
+                    &revisions,
+                    &group,
+                    key,
+                    comment.uploaded,
+                    &comment.id,
+                ),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Adds and removes a users reactions on a comment on a specific object
+///
+/// Reactions are added to and removed from a set in place so reactions from other users
+/// that land at the same time are not lost.
+///
+/// # Arguments
+///
+/// * `user` - The user that is reacting
+/// * `key` - The key for the object this comment is on
+/// * `comment` - The comment being reacted to
+/// * `add` - The reactions to add
+/// * `remove` - The reactions to remove
+/// * `shared` - Shared Thorium objects
+#[instrument(
+    name = "db::comments::react",
+    skip(user, comment, shared),
+    fields(user = user.username),
+    err(Debug)
+)]
+pub async fn react(
+    user: &User,
+    key: &str,
+    comment: &Comment,
+    add: &[CommentReaction],
+    remove: &[CommentReaction],
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // pair each reaction with the user leaving it
+    let pair = |reaction: &CommentReaction| (reaction.as_str().to_owned(), user.username.clone());
+    let add = add.iter().map(pair).collect::<Vec<(String, String)>>();
+    let remove = remove.iter().map(pair).collect::<Vec<(String, String)>>();
+    // update this comments reactions in each of its groups
+    for group in get_groups(key, &comment.id, shared).await? {
+        // add any new reactions
+        if !add.is_empty() {
+            shared
+                .scylla
+                .session
+                .execute_unpaged(
+                    &shared.scylla.prep.comments.add_reactions,
+                    (&add, &group, key, comment.uploaded, &comment.id),
+                )
+                .await?;
+        }
+        // remove any old reactions
+        if !remove.is_empty() {
+            shared
+                .scylla
+                .session
+                .execute_unpaged(
+                    &shared.scylla.prep.comments.remove_reactions,
+                    (&remove, &group, key, comment.uploaded, &comment.id),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Prunes the attachments for the given list of comments if needed
+///
+/// Attachments are only pruned if the comment is no longer reachable (does not exist)
+///
+/// # Arguments
+///
+/// * `comments` - The comments to prune attachments for
+/// * `key` - The key for the object these comments were on
+/// * `shared` - Shared Thorium objects
+pub async fn prune_attachments(
+    comments: &[Comment],
+    key: &str,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // downselect down to only those comments that have attachments
+    let has_attachments = comments
+        .iter()
+        .filter(|comment| !comment.attachments.is_empty())
+        .map(|comment| &comment.id)
+        .collect::<Vec<&Uuid>>();
+    // determine which comment attachments are still reachable (the comments exist)
+    let reachable = exists(&has_attachments, shared).await?;
+    // downselect to comments that are unreachable
+    let unreachable = comments
+        .iter()
+        .filter(|comment| !comment.attachments.is_empty())
+        .filter(|comment| !reachable.contains(&comment.id));
+    // crawl through and delete all of our comment attachments
+    for comment in unreachable {
+        // delete all of this comments files
+        for s3_id in comment.attachments.values() {
+            // build the path to save this attachment at in s3
+            let s3_path = format!("{}/{}/{}", key, &comment.id, s3_id);
+            shared.s3.attachments.delete(&s3_path).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Determines which of the given comments still exist in Thorium
+///
+/// # Arguments
+///
+/// * `ids` - The comment ids to check for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::comments::exists", skip(shared), err(Debug))]
+async fn exists(ids: &[&Uuid], shared: &Shared) -> Result<HashSet<Uuid>, ApiError> {
+    // build our hashset of comments
+    let mut found = HashSet::with_capacity(ids.len());
+    // break these ids into chunks of 100
+    for ids_chunk in ids.chunks(100) {
+        // execute this query
+        let query = shared
+            .scylla
+            .session
+            .execute_unpaged(&shared.scylla.prep.comments.exists, (ids_chunk,))
+            .await?;
+        // enable casting to types for this query
+        let query_rows = query.into_rows_result()?;
+        // get the rows from this query if they were returned
+        for cast in query_rows.rows::<(Option<Uuid>,)>()? {
+            // raise instead of just logging errors as ignoring them can cause
+            // dangling references in the Db
+            let (id_opt,) = cast?;
+            // skip any rows without ids
+            if let Some(id) = id_opt {
+                // track that this comment id was still found
+                found.insert(id);
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Deletes a comment for a specific object
+///
+/// This doesn't delete any of the comments attachments.
+///
+/// # Arguments
+///
+/// * `key` - The key for the object to delete the comment from
+/// * `groups` - The groups to delete this comment for
+/// * `comment` - The comment to delete
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::comments::delete", skip(comment, shared), err(Debug))]
+pub async fn delete(
+    key: &str,
+    groups: &[String],
+    comment: &Comment,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // log the comment we are deleting
+    event!(Level::INFO, comment = &comment.id.to_string());
+    // delete this comment for each of our target groups
+    for group in groups {
+        // delete this tag row
+        shared
+            .scylla
+            .session
+            .execute_unpaged(
+                &shared.scylla.prep.comments.delete,
+                (group, key, comment.uploaded, &comment.id),
+            )
+            .await?;
+    }
+    Ok(())
+}
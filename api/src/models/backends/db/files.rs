@@ -10,16 +10,14 @@ use uuid::Uuid;
 use super::ScyllaCursor;
 use crate::models::backends::TagSupport;
 use crate::models::{
-    Comment, CommentForm, CommentRow, Event, FileListParams, ResultSearchEvent, Sample,
-    SampleCheck, SampleCheckResponse, SampleForm, SampleListLine, SampleSubmissionResponse,
-    Submission, SubmissionChunk, SubmissionRow, SubmissionUpdate, TagDeleteRequest, TagRequest,
-    TagSearchEvent, User,
+    Event, FileListParams, ResultSearchEvent, Sample, SampleCheck, SampleCheckResponse, SampleForm,
+    SampleListLine, SampleSubmissionResponse, Submission, SubmissionChunk, SubmissionRow,
+    SubmissionUpdate, TagDeleteRequest, TagProvenance, TagRequest, TagSearchEvent, User,
 };
 use crate::utils::s3::StandardHashes;
 use crate::utils::{helpers, ApiError, Shared};
 use crate::{
-    conflict, for_groups, internal_err, log_scylla_err, not_found, same_vec, serialize,
-    unauthorized,
+    conflict, for_groups, internal_err, log_scylla_err, not_found, same_vec, unauthorized,
 };
 
 /// Deletes a submission from multiple groups, breaking into chunks of 100 if > 100
@@ -190,13 +188,15 @@ async fn add_child(
 ///
 /// * `user` - The user who is saving this file
 /// * `upload` - The sample to save to the backend
+/// * `origins` - Where this samples tags came from
 /// * `shared` - Shared Thorium objects
 /// * `span` - The span to log traces under
 #[rustfmt::skip]
-#[instrument(name = "db::files::create", skip(user, form, shared), err(Debug))]
+#[instrument(name = "db::files::create", skip(user, form, origins, shared), err(Debug))]
 pub async fn create(
     user: &User,
     mut form: SampleForm,
+    origins: &[TagProvenance],
     hashes: StandardHashes,
     shared: &Shared,
 ) -> Result<SampleSubmissionResponse, ApiError> {
@@ -229,6 +229,7 @@ pub async fn create(
                     user,
                     hashes.sha256.clone(),
                     req,
+                    origins,
                     &earliest,
                     shared,
                 )
@@ -275,6 +276,7 @@ pub async fn create(
         user,
         hashes.sha256.clone(),
         req,
+        origins,
         &earliest,
         shared,
     )
@@ -363,101 +365,6 @@ pub async fn authorize(
     Ok(())
 }
 
-/// Gets all the comments for a sample
-///
-/// # Arguments
-///
-/// * `groups` - The groups to restrict our returned comments too
-/// * `sha256` - The sha256 to get comments for
-/// * `list` - The vector to add our tags too
-/// * `shared` - Shared Thorium objects
-#[instrument(name = "db::files::get_comments", skip(list, shared), err(Debug))]
-async fn get_comments(
-    groups: &Vec<String>,
-    sha256: &str,
-    list: &mut Vec<Comment>,
-    shared: &Shared,
-) -> Result<(), ApiError> {
-    // if we have more then 100 groups then chunk it into bathes of 100  otherwise just get our info
-    if groups.len() > 100 {
-        // break our groups into chunks of 100
-        for chunk in groups.chunks(100) {
-            // turn our group chunk into a vec
-            let chunk_vec = chunk.to_vec();
-            // get this chunks data
-            let query = shared
-                .scylla
-                .session
-                .execute_unpaged(&shared.scylla.prep.comments.get, (chunk_vec, sha256))
-                .await?;
-            // build a btreemap to collect all comments and then sort them
-            let mut map: BTreeMap<DateTime<Utc>, HashMap<Uuid, Comment>> = BTreeMap::default();
-            // enable casting to types for this query
-            let query_rows = query.into_rows_result()?;
-            // set the type to cast this stream too
-            let typed_iter = query_rows.rows::<CommentRow>()?;
-            // crawl over rows and add them to our tag map while logging any errors
-            typed_iter
-                .filter_map(|res| log_scylla_err!(res))
-                .for_each(|comment| {
-                    // get an entry to the nested map of comments by id
-                    let id_map = map.entry(comment.uploaded).or_default();
-                    // get our comments doc so we can add a group or insert it
-                    match id_map.entry(comment.id) {
-                        // this comment has already been added so just add a new group to it
-                        Occupied(entry) => entry.into_mut().groups.push(comment.group),
-                        Vacant(entry) => {
-                            // try to turn this row into a comment
-                            let res = Comment::try_from(comment);
-                            // if we can deserialize this string then insert it
-                            if let Some(cast) = log_scylla_err!(res) {
-                                entry.insert(cast);
-                            }
-                        }
-                    }
-                });
-            // flatten our map of comments and append it to our list of comments
-            list.extend(map.into_iter().flat_map(|(_, map)| map.into_values()));
-        }
-    } else {
-        // we have less then 100 groups so just get their data
-        let query = shared
-            .scylla
-            .session
-            .execute_unpaged(&shared.scylla.prep.comments.get, (groups, sha256))
-            .await?;
-        // enable casting to types for this query
-        let query_rows = query.into_rows_result()?;
-        // set the type to cast this stream too
-        let typed_iter = query_rows.rows::<CommentRow>()?;
-        // build a btreemap to collect all comments and then sort them
-        let mut map: BTreeMap<DateTime<Utc>, HashMap<Uuid, Comment>> = BTreeMap::default();
-        // crawl over rows and add them to our tag map while logging any errors
-        typed_iter
-            .filter_map(|res| log_scylla_err!(res))
-            .for_each(|comment| {
-                // get an entry to the nested map of comments by id
-                let id_map = map.entry(comment.uploaded).or_default();
-                // get our comments doc so we can add a group or insert it
-                match id_map.entry(comment.id) {
-                    // this comment has already been added so just add a new group to it
-                    Occupied(entry) => entry.into_mut().groups.push(comment.group),
-                    Vacant(entry) => {
-                        // try to turn this row into a comment
-                        let res = Comment::try_from(comment);
-                        // if we can deserialize this string then insert it
-                        if let Some(cast) = log_scylla_err!(res) {
-                            entry.insert(cast);
-                        }
-                    }
-                }
-            });
-        // flatten our map of comments and append it to our list of comments
-        list.extend(map.into_iter().flat_map(|(_, map)| map.into_values()));
-    }
-    Ok(())
-}
-
 /// Gets all submissions for a specific set of groups and a sha256 and cast it to a sample
 ///
 /// # Arguments
@@ -544,7 +451,7 @@ pub async fn get(
         }
         // get the tags and comments for this sample
         sample.get_tags(groups, shared).await?;
-        get_comments(groups, sha256, &mut sample.comments, shared).await?;
+        super::comments::get(groups, sha256, &mut sample.comments, shared).await?;
         // return our sample
         return Ok(Some(sample));
     };
@@ -735,10 +642,10 @@ async fn prune_access(
     // delete all comment rows for our groups + sample
     for comment in &sample.comments {
         // delete the rows for this comment
-        delete_comment(&sample.sha256, groups, comment, shared).await?;
+        super::comments::delete(&sample.sha256, groups, comment, shared).await?;
     }
     // prune comment attachments now that the comments are deleted
-    prune_comment_attachments(&sample.comments, &sample.sha256, shared).await?;
+    super::comments::prune_attachments(&sample.comments, &sample.sha256, shared).await?;
     // create events to delete search info for this sample in the pruned groups
     let tag_event = TagSearchEvent::deleted::<Sample>(sample.sha256.clone(), groups.clone());
     let result_event = ResultSearchEvent::deleted::<Sample>(sample.sha256.clone(), groups.clone());
@@ -915,156 +822,3 @@ pub async fn update(
         ))
     }
 }
-
-/// Creates a comment for a specific sha256
-///
-/// # Arguments
-///
-/// * `user` - The user that is adding new comments
-/// * `sha256` - The sha256 to comment on
-/// * `form` - The comment to save to scylla
-/// * `shared` - Shared objects in Thorium
-#[instrument(
-    name = "db::files::create_comment",
-    skip(user, form, shared),
-    err(Debug)
-)]
-pub async fn create_comment<'v>(
-    user: &User,
-    sha256: &str,
-    form: &CommentForm,
-    shared: &Shared,
-) -> Result<(), ApiError> {
-    // serialize our s3 paths
-    let paths = serialize!(&form.attachments);
-    // get the current timestamp
-    let now = Utc::now();
-    // create a comment row for each group
-    for group in form.groups.iter() {
-        shared
-            .scylla
-            .session
-            .execute_unpaged(
-                &shared.scylla.prep.comments.insert,
-                (
-                    &group,
-                    &sha256,
-                    now,
-                    &form.id,
-                    &user.username,
-                    &form.comment,
-                    &paths,
-                ),
-            )
-            .await?;
-    }
-    Ok(())
-}
-
-/// Prunes the attachments for the given list of comments if needed
-///
-/// Attachments are only pruned if the comment is no longer reachable (does not exist)
-///
-/// # Arguments
-///
-/// * `comments` - The comments to prune attachments for
-/// * `sha256` - The SHA256 of the associated file
-/// * `shared` - Shared Thorium objects
-pub async fn prune_comment_attachments(
-    comments: &[Comment],
-    sha256: &str,
-    shared: &Shared,
-) -> Result<(), ApiError> {
-    // downselect down to only those comments that have attachments
-    let has_attachments = comments
-        .iter()
-        .filter(|comment| !comment.attachments.is_empty())
-        .map(|comment| &comment.id)
-        .collect::<Vec<&Uuid>>();
-    // determine which comment attachments are still reachable (the comments exist)
-    let reachable = comments_exist(&has_attachments, shared).await?;
-    // downselect to comments that are unreachable
-    let unreachable = comments
-        .iter()
-        .filter(|comment| !comment.attachments.is_empty())
-        .filter(|comment| !reachable.contains(&comment.id));
-    // crawl through and delete all of our comment attachments
-    for comment in unreachable {
-        // delete all of this comments files
-        for s3_id in comment.attachments.values() {
-            // build the path to save this attachment at in s3
-            let s3_path = format!("{}/{}/{}", sha256, &comment.id, s3_id);
-            shared.s3.attachments.delete(&s3_path).await?;
-        }
-    }
-    Ok(())
-}
-
-/// Determines which of the given comments still exist in Thorium
-///
-/// # Arguments
-///
-/// * `ids` - The comment ids to check for
-/// * `shared` - Shared Thorium objects
-#[instrument(name = "db::files::comment_exists", skip(shared), err(Debug))]
-async fn comments_exist(ids: &[&Uuid], shared: &Shared) -> Result<HashSet<Uuid>, ApiError> {
-    // build our hashset of comments
-    let mut found = HashSet::with_capacity(ids.len());
-    // break these ids into chunks of 100
-    for ids_chunk in ids.chunks(100) {
-        // execute this query
-        let query = shared
-            .scylla
-            .session
-            .execute_unpaged(&shared.scylla.prep.comments.exists, (ids_chunk,))
-            .await?;
-        // enable casting to types for this query
-        let query_rows = query.into_rows_result()?;
-        // get the rows from this query if they were returned
-        for cast in query_rows.rows::<(Option<Uuid>,)>()? {
-            // raise instead of just logging errors as ignoring them can cause
-            // dangling references in the Db
-            let (id_opt,) = cast?;
-            // skip any rows without ids
-            if let Some(id) = id_opt {
-                // track that this comment id was still found
-                found.insert(id);
-            }
-        }
-    }
-    Ok(found)
-}
-
-/// Deletes a comment for a specific sha256
-///
-/// This doesn't delete any of the comments attachments.
-///
-/// # Arguments
-///
-/// * `sha256` - The sha256 to delete the comment from
-/// * `groups` - The groups to delete this comment for
-/// * `comment` - The comment to delete
-/// * `shared` - Shared Thorium objects
-#[instrument(name = "db::files::delete_comment", skip(comment, shared), err(Debug))]
-pub async fn delete_comment(
-    sha256: &str,
-    groups: &[String],
-    comment: &Comment,
-    shared: &Shared,
-) -> Result<(), ApiError> {
-    // log the comment we are deleting
-    event!(Level::INFO, comment = &comment.id.to_string());
-    // delete this comment for each of our target groups
-    for group in groups {
-        // delete this tag row
-        shared
-            .scylla
-            .session
-            .execute_unpaged(
-                &shared.scylla.prep.comments.delete,
-                (group, sha256, comment.uploaded, &comment.id),
-            )
-            .await?;
-    }
-    Ok(())
-}
@@ -7,8 +7,7 @@ use super::keys::{EventKeys, GroupKeys, UserKeys};
 use crate::models::{Group, GroupList, GroupRequest, Image, NetworkPolicy, Pipeline, User};
 use crate::utils::{ApiError, Shared};
 use crate::{
-    conn, hset_del_opt_serialize, hsetnx_opt_serialize, log_err, not_found,
-    query, serialize,
+    conn, hset_del_opt_serialize, hsetnx_opt_serialize, log_err, not_found, query, serialize,
 };
 
 /// Adds the commands to modify users groups to a redis pipeline
@@ -80,7 +79,9 @@ pub async fn create(
         // invalidate our cache status
         .cmd("hset").arg(cache_status).arg("status").arg(true)
         // set our group allowed settings
-        .cmd("hset").arg(&keys.data).arg("allowed").arg(serialize!(&cast.allowed));
+        .cmd("hset").arg(&keys.data).arg("allowed").arg(serialize!(&cast.allowed))
+        // set our groups tag vocabulary
+        .cmd("hset").arg(&keys.data).arg("tag_vocabulary").arg(serialize!(&cast.tag_vocabulary));
     // update user accounts
     modify_users!(pipe, &cast.owners.combined, "sadd", &cast.name, shared);
     modify_users!(pipe, &cast.managers.combined, "sadd", &cast.name, shared);
@@ -408,6 +409,8 @@ pub async fn update(
     pipe.cmd("hset").arg(cache_status).arg("status").arg(true);
     // set our group allowed settings
     pipe.cmd("hset").arg(&keys.data).arg("allowed").arg(serialize!(&group.allowed));
+    // set our groups tag vocabulary
+    pipe.cmd("hset").arg(&keys.data).arg("tag_vocabulary").arg(serialize!(&group.tag_vocabulary));
     // execute pipeline and check if it failed
     () = pipe.atomic().query_async(conn!(shared)).await?;
     Ok(())
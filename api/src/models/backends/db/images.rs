@@ -39,6 +39,7 @@ pub fn build(
         .cmd("hsetnx").arg(&keys.data).arg("scaler").arg(serialize!(&cast.scaler))
         .cmd("hsetnx").arg(&keys.data).arg("resources").arg(serialize!(&cast.resources))
         .cmd("hsetnx").arg(&keys.data).arg("spawn_limit").arg(serialize!(&cast.spawn_limit))
+        .cmd("hsetnx").arg(&keys.data).arg("priority").arg(serialize!(&cast.priority))
         .cmd("hsetnx").arg(&keys.data).arg("runtime").arg(cast.runtime)
         .cmd("hsetnx").arg(&keys.data).arg("volumes").arg(serialize!(&cast.volumes))
         .cmd("hsetnx").arg(&keys.data).arg("env").arg(serialize!(&cast.env))
@@ -48,6 +49,10 @@ pub fn build(
         .cmd("hsetnx").arg(&keys.data).arg("collect_logs")
             .arg(serialize!(&cast.collect_logs))
         .cmd("hsetnx").arg(&keys.data).arg("generator").arg(serialize!(&cast.generator))
+        .cmd("hsetnx").arg(&keys.data).arg("capture_network")
+            .arg(serialize!(&cast.capture_network))
+        .cmd("hsetnx").arg(&keys.data).arg("arch").arg(serialize!(&cast.arch))
+        .cmd("hsetnx").arg(&keys.data).arg("emulation").arg(serialize!(&cast.emulation))
         .cmd("hsetnx").arg(&keys.data).arg("dependencies").arg(serialize!(&cast.dependencies))
         .cmd("hsetnx").arg(&keys.data).arg("display_type").arg(serialize!(&cast.display_type))
         .cmd("hsetnx").arg(&keys.data).arg("output_collection").arg(serialize!(&cast.output_collection))
@@ -63,6 +68,7 @@ pub fn build(
     hsetnx_opt_serialize!(pipe, &keys.data, "description", &cast.description);
     hsetnx_opt_serialize!(pipe, &keys.data, "clean_up", &cast.clean_up);
     hsetnx_opt_serialize!(pipe, &keys.data, "kvm", &cast.kvm);
+    hsetnx_opt_serialize!(pipe, &keys.data, "microvm", &cast.microvm);
     // invalidate this images scaler cache
     pipe.cmd("hset").arg(&syskey.data).arg(cast.scaler.cache_key()).arg(true);
     Ok(())
@@ -177,6 +183,7 @@ pub async fn update(image: &Image, shared: &Shared) -> Result<(), ApiError> {
     pipe.cmd("hset").arg(&keys.data).arg("scaler").arg(serialize!(&image.scaler))
         .cmd("hset").arg(&keys.data).arg("resources").arg(serialize!(&image.resources))
         .cmd("hset").arg(&keys.data).arg("spawn_limit").arg(serialize!(&image.spawn_limit))
+        .cmd("hset").arg(&keys.data).arg("priority").arg(serialize!(&image.priority))
         .cmd("hset").arg(&keys.data).arg("volumes").arg(serialize!(&image.volumes))
         .cmd("hset").arg(&keys.data).arg("env").arg(serialize!(&image.env))
         .cmd("hset").arg(&keys.data).arg("args").arg(serialize!(&image.args))
@@ -185,6 +192,10 @@ pub async fn update(image: &Image, shared: &Shared) -> Result<(), ApiError> {
         .cmd("hset").arg(&keys.data).arg("collect_logs")
             .arg(serialize!(&image.collect_logs))
         .cmd("hset").arg(&keys.data).arg("generator").arg(serialize!(&image.generator))
+        .cmd("hset").arg(&keys.data).arg("capture_network")
+            .arg(serialize!(&image.capture_network))
+        .cmd("hset").arg(&keys.data).arg("arch").arg(serialize!(&image.arch))
+        .cmd("hset").arg(&keys.data).arg("emulation").arg(serialize!(&image.emulation))
         .cmd("hset").arg(&syskey.data).arg("scaler_cache").arg(true)
         .cmd("hset").arg(&keys.data).arg("dependencies").arg(serialize!(&image.dependencies))
         .cmd("hset").arg(&keys.data).arg("display_type").arg(serialize!(&image.display_type))
@@ -201,6 +212,7 @@ pub async fn update(image: &Image, shared: &Shared) -> Result<(), ApiError> {
     hset_del_opt_serialize!(pipe, &keys.data, "description", &image.description);
     hset_del_opt_serialize!(pipe, &keys.data, "clean_up", &image.clean_up);
     hset_del_opt_serialize!(pipe, &keys.data, "kvm", &image.kvm);
+    hset_del_opt_serialize!(pipe, &keys.data, "microvm", &image.microvm);
     // invalidate this images scaler cache
     pipe.cmd("hset").arg(&syskey.data).arg(image.scaler.cache_key()).arg(true);
     // save image to backend
@@ -417,7 +429,7 @@ fn update_counts(images: &[Image]) -> usize {
     // first count the non-optional fields (ones that should always be true)
     // this code is pretty ugly since it works off a magic number but there's
     // not really a better way ¯\_(ツ)_/¯
-    let mut cnt = images.len() * 19;
+    let mut cnt = images.len() * 23;
     // count optional fields that contain a value for each image
     images.iter().for_each(|image| cnt += add_opts(image));
     cnt
@@ -438,6 +450,7 @@ fn add_opts(image: &Image) -> usize {
     cnt += usize::from(image.description.is_some());
     cnt += usize::from(image.clean_up.is_some());
     cnt += usize::from(image.kvm.is_some());
+    cnt += usize::from(image.microvm.is_some());
     cnt
 }
 
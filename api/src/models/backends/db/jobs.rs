@@ -7,10 +7,12 @@ use uuid::Uuid;
 
 use super::keys::{images::ImageKeys, jobs::JobKeys, reactions::ReactionKeys, streams::StreamKeys};
 use super::{logs, reactions, streams, system};
+use crate::models::deadlines::priority_stream_data;
 use crate::models::{
-    Checkpoint, GenericJobArgs, ImageScaler, JobActions, JobDetailsList, JobHandleStatus, JobList,
-    JobReactionIds, JobResets, JobStatus, Pipeline, RawJob, Reaction, ReactionStatus, RunningJob,
-    StageLogsAdd, StatusRequest, StatusUpdate, StreamObj, User, Worker, WorkerName,
+    Checkpoint, GenericJobArgs, ImageScaler, JobActions, JobCheckpointBlob, JobDetailsList,
+    JobHandleStatus, JobList, JobProgress, JobReactionIds, JobResetRequestor, JobResets, JobStatus,
+    Pipeline, PriorityClass, RawJob, Reaction, ReactionStatus, RunningJob, StageLogsAdd,
+    StatusRequest, StatusUpdate, StreamObj, SystemComponents, User, Worker, WorkerName,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
@@ -110,6 +112,10 @@ pub async fn build<'a>(
     if let Some(trigger_depth) = &cast.trigger_depth {
         pipe.cmd("hsetnx").arg(&keys.data).arg("trigger_depth").arg(trigger_depth);
     }
+    // if this job has a non standard priority class then set it
+    if !cast.priority.is_standard() {
+        pipe.cmd("hsetnx").arg(&keys.data).arg("priority").arg(serialize!(&cast.priority));
+    }
     // create status log for this job
     let update_cast = StatusUpdate::new(StatusRequest::from_job(cast, JobActions::Created), None);
     logs::build(pipe, &[update_cast], shared)?;
@@ -252,15 +258,21 @@ async fn prune_dangling(
 ) -> Result<(), ApiError> {
     // build a redis pipeline to prune this dangling job
     let mut pipe = redis::pipe();
-    // build the json objects to remove from the deadlines and running sorted sets
-    let deadline_obj = format!(
-        "{{\"group\":\"{}\",\"pipeline\":\"{}\",\"stage\":\"{}\",\"creator\":\"{}\",\"job_id\":\"{}\",\"reaction\":\"{}\"}}",
-        worker.group,
-        worker.pipeline,
-        worker.stage,
-        worker.user,
-        job,
-        reaction);
+    // this jobs data is gone so remove its deadline for any priority class it could have had
+    for priority in [PriorityClass::Standard, PriorityClass::Preemptible, PriorityClass::Urgent] {
+        // build the json object to remove from the deadlines sorted set
+        let deadline_obj = format!(
+            "{{\"group\":\"{}\",\"pipeline\":\"{}\",\"stage\":\"{}\",\"creator\":\"{}\",\"job_id\":\"{}\",\"reaction\":\"{}\"{}}}",
+            worker.group,
+            worker.pipeline,
+            worker.stage,
+            worker.user,
+            job,
+            reaction,
+            priority_stream_data(priority));
+        pipe.cmd("zrem")
+            .arg(&StreamKeys::system_scaler(scaler, "deadlines", shared)).arg(deadline_obj);
+    }
     // build the object to remove from the running stream
     let running_obj = format!("{{\"job_id\":\"{job}\",\"worker\":\"{}\"}}", worker.name);
     // build the entry for the status stream
@@ -268,8 +280,6 @@ async fn prune_dangling(
     // remove this jobs data
     pipe.cmd("del").arg(JobKeys::data_str(&job, shared))
         .cmd("zrem").arg(&dest).arg(serialize!(&status_entry))
-        .cmd("zrem")
-            .arg(&StreamKeys::system_scaler(scaler, "deadlines", shared)).arg(deadline_obj)
         .cmd("zrem")
             .arg(&StreamKeys::system_scaler(scaler, "running", shared)).arg(running_obj);
     // execute this pipeline
@@ -482,6 +492,104 @@ pub async fn set_args(job: &RawJob, shared: &Shared) -> Result<JobHandleStatus,
     Ok(JobHandleStatus::Checkpointed)
 }
 
+/// Saves the latest progress reported by a running job
+///
+/// The jobs status is checked in the same script that saves its progress so a job that finishes
+/// while its progress is being reported never has stale progress left behind.
+///
+/// # Arguments
+///
+/// * `job` - The job to save progress for
+/// * `progress` - The progress to save
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::jobs::set_progress", skip_all, fields(job = job.id.to_string()), err(Debug))]
+pub async fn set_progress(
+    job: &RawJob,
+    progress: &JobProgress,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // only save progress if this job is still running
+    let script = redis::Script::new(
+        r"
+        if redis.call('hget', KEYS[1], 'status') ~= ARGV[1] then
+            return 0
+        end
+        redis.call('hset', KEYS[2], ARGV[2], ARGV[3])
+        return 1",
+    );
+    let saved: bool = script
+        .key(JobKeys::data(&job.id, shared))
+        .key(ReactionKeys::progress(&job.group, &job.reaction, shared))
+        .arg(serialize!(&JobStatus::Running))
+        .arg(job.id.to_string())
+        .arg(serialize!(progress))
+        .invoke_async(conn!(shared))
+        .await?;
+    // error on jobs that are not running
+    if !saved {
+        return conflict!(format!(
+            "job {} must be running to report progress",
+            &job.id
+        ));
+    }
+    Ok(())
+}
+
+/// Builds the path to a jobs checkpoint blob in s3
+///
+/// # Arguments
+///
+/// * `id` - The id of the job to build a checkpoint blob path for
+pub fn checkpoint_blob_path(id: &Uuid) -> String {
+    format!("checkpoints/{id}")
+}
+
+/// Saves a reference to the checkpoint blob a running job uploaded
+///
+/// # Arguments
+///
+/// * `job` - The job to save a checkpoint blob for
+/// * `blob` - The checkpoint blob that was uploaded
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::jobs::set_checkpoint_blob", skip_all, fields(job = job.id.to_string()), err(Debug))]
+pub async fn set_checkpoint_blob(
+    job: &RawJob,
+    blob: &JobCheckpointBlob,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build key to this jobs data
+    let key = JobKeys::data(&job.id, shared);
+    // overwrite any checkpoint blob this job previously saved
+    let _: () = query!(
+        cmd("hset")
+            .arg(key)
+            .arg("checkpoint_blob")
+            .arg(serialize!(blob)),
+        shared
+    )
+    .await?;
+    Ok(())
+}
+
+/// Deletes a jobs checkpoint blob if it saved one
+///
+/// # Arguments
+///
+/// * `job` - The job to delete a checkpoint blob for
+/// * `shared` - Shared Thorium objects
+pub async fn delete_checkpoint_blob(job: &RawJob, shared: &Shared) -> Result<(), ApiError> {
+    // only try to delete checkpoint blobs that were saved
+    if job.checkpoint_blob.is_some() {
+        // build the path to this jobs checkpoint blob
+        let path = checkpoint_blob_path(&job.id);
+        // delete this checkpoint blob and our reference to it
+        shared.s3.ephemeral.delete(&path).await?;
+        let key = JobKeys::data(&job.id, shared);
+        let _: () = query!(cmd("hdel").arg(key).arg("checkpoint_blob"), shared).await?;
+    }
+    Ok(())
+}
+
 /// Sets a jobs status to be sleeping
 ///
 /// This is used to let generator jobs return to Thorium and later be respawned. They must still be
@@ -517,6 +625,56 @@ pub async fn sleep(
     Ok(JobHandleStatus::Sleeping)
 }
 
+/// Returns a preempted job to the queue
+///
+/// This is used by agents whose workers were evicted by the scaler to make room for urgent
+/// work. The job keeps its deadline and checkpoint blob so it can be resumed by the next
+/// worker that claims it.
+///
+/// # Arguments
+///
+/// * `job` - The job to preempt
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::jobs::preempt", skip_all, fields(job = job.id.to_string()), err(Debug))]
+pub async fn preempt(
+    job: RawJob,
+    shared: &Shared,
+) -> Result<JobHandleStatus, ApiError> {
+    // only running jobs can be preempted
+    if job.status != JobStatus::Running {
+        return conflict!(format!("job {} must be running to be preempted", &job.id));
+    }
+    // build the status queues keys for this job
+    let src = JobKeys::status_queue(&job.group, &job.pipeline, &job.stage, &job.creator, &job.status, shared);
+    let dest = JobKeys::status_queue(&job.group, &job.pipeline, &job.stage, &job.creator, &JobStatus::Created, shared);
+    // cast our job claim data
+    let job_claim = serialize!(&JobReactionIds::new(job.id, job.reaction));
+    // cast this job to a deadline stream object
+    let stream_obj = StreamObj::from(&job);
+    // build a redis pipeline to return this job to the queue
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    pipe.cmd("hset").arg(JobKeys::data(&job.id, shared))
+            .arg("status").arg(serialize!(&JobStatus::Created))
+        // move this job back to the created status queue
+        .cmd("zrem").arg(src).arg(&job_claim)
+        .cmd("zadd").arg(dest).arg(job.deadline.timestamp()).arg(&job_claim)
+        // remove this job from the running stream
+        .cmd("zrem").arg(StreamKeys::system_scaler(job.scaler, "running", shared))
+            .arg(serialize!(&serde_json::json!({"job_id": job.id, "worker": job.worker})))
+        // add this job back to the deadlines stream if its not already there
+        .cmd("zadd").arg(StreamKeys::system_scaler(job.scaler, "deadlines", shared))
+            .arg(stream_obj.timestamp).arg(stream_obj.data);
+    // log that this job was reset by the scaler that preempted it
+    let requestor = JobResetRequestor::Component(SystemComponents::Scaler(job.scaler));
+    let update_cast = StatusUpdate::new(StatusRequest::from_job(&job, JobActions::Reset(requestor)), None);
+    logs::build(&mut pipe, &[update_cast], shared)?;
+    // execute this redis pipeline
+    let _: () = pipe.query_async(conn!(shared)).await?;
+    Ok(JobHandleStatus::Preempted)
+}
+
 /// Proceeds with a running job
 ///
 /// This marks a running job as complete and will continue a reaction if the current stage of that reaction
@@ -597,7 +755,10 @@ pub async fn proceed(
             .arg(stream_obj.data)
         // remove job from running stream
         .cmd("zrem").arg(StreamKeys::system_scaler(job.scaler, "running", shared))
-            .arg(force_serialize!(&serde_json::json!({"job_id": job.id, "worker": job.worker})));
+            .arg(force_serialize!(&serde_json::json!({"job_id": job.id, "worker": job.worker})))
+        // clear any progress this job reported
+        .cmd("hdel").arg(ReactionKeys::progress(&job.group, &job.reaction, shared))
+            .arg(job.id.to_string());
     // save this jobs logs to the backend
     reactions::add_stage_logs(&job.reaction, &job.stage, logs, shared).await?;
     // execute redis pipeline
@@ -606,15 +767,17 @@ pub async fn proceed(
         // execute our query and get the response based on if the job is a generator or not
         let progress = if job.generator {
             // execute the query with our job generator srem
-            let full: (u64, u64, u64, u64, bool, u64, u64, u64, u64, u64, u64) = pipe.atomic().query_async(conn!(shared)).await?;
+            let full: (u64, u64, u64, u64, bool, u64, u64, u64, u64, u64, u64, u64) = pipe.atomic().query_async(conn!(shared)).await?;
             // downselect to just the first two values
             (full.0, full.1)
         } else {
             // execute the query without the job generator srem
-            let full: (u64, u64, u64, u64, bool, u64, u64, u64, u64, u64) = pipe.atomic().query_async(conn!(shared)).await?;
+            let full: (u64, u64, u64, u64, bool, u64, u64, u64, u64, u64, u64) = pipe.atomic().query_async(conn!(shared)).await?;
             // downselect to just the first two values
             (full.0, full.1)
         };
+        // this job is complete so it will never need to resume from its checkpoint blob
+        delete_checkpoint_blob(&job, shared).await?;
         // check if we should proceed or not
         progress.0 >= progress.1
     } else {
@@ -694,6 +857,9 @@ pub async fn error<'a>(
         let gen_key = ReactionKeys::generators(&job.group, &job.reaction, shared);
         pipe.cmd("srem").arg(gen_key).arg(&job.id.to_string());
     }
+    // clear any progress this job reported
+    pipe.cmd("hdel").arg(ReactionKeys::progress(&job.group, &job.reaction, shared))
+        .arg(job.id.to_string());
     // save this jobs logs to scylla
     reactions::add_stage_logs(&job.reaction, &job.stage, logs, shared).await?;
     // create and save status log
@@ -701,6 +867,8 @@ pub async fn error<'a>(
     logs::build(&mut pipe, &[update_cast], shared)?;
     // execute redis pipeline
     let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+    // this job failed so it will never need to resume from its checkpoint blob
+    delete_checkpoint_blob(&job, shared).await?;
     // error out reaction as well
     let reaction = reactions::get(&job.group, &job.reaction, shared).await?;
     reactions::fail(reaction, shared).await?;
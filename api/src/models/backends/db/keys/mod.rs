@@ -14,6 +14,7 @@ pub mod search;
 pub mod streams;
 pub mod system;
 pub mod tags;
+pub mod uploads;
 pub mod users;
 
 pub use events::EventKeys;
@@ -26,4 +27,5 @@ pub use reactions::{ReactionKeys, SubReactionLists};
 pub use search::events::SearchEventKeys;
 pub use streams::StreamKeys;
 pub use system::SystemKeys;
+pub use uploads::UploadKeys;
 pub use users::UserKeys;
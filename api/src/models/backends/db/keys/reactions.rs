@@ -17,6 +17,8 @@ pub struct ReactionKeys {
     pub group_set: String,
     /// The key to all sub reactions for this reaction
     pub sub: String,
+    /// The key to the last reported progress for this reactions jobs
+    pub progress: String,
 }
 
 impl ReactionKeys {
@@ -39,6 +41,8 @@ impl ReactionKeys {
         let group_set = Self::group_set(&reaction.group, &reaction.status, shared);
         // build key to sub reactions set
         let sub = ReactionKeys::sub_set(&reaction.group, &reaction.id, shared);
+        // build key to this reactions job progress
+        let progress = ReactionKeys::progress(&reaction.group, &reaction.id, shared);
         // build key object
         ReactionKeys {
             data,
@@ -47,6 +51,7 @@ impl ReactionKeys {
             jobs,
             group_set,
             sub,
+            progress,
         }
     }
 
@@ -284,6 +289,38 @@ impl ReactionKeys {
             reaction = reaction,
         )
     }
+
+    /// Builds key to the last reported progress for this reactions running jobs
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group the reaction is in
+    /// * `reaction` - The reaction id
+    /// * `shared` - Shared Thorium objects
+    pub fn progress(group: &str, reaction: &Uuid, shared: &Shared) -> String {
+        format!(
+            "{ns}:reaction_progress:{group}:{reaction}",
+            ns = shared.config.thorium.namespace,
+            group = group,
+            reaction = reaction,
+        )
+    }
+
+    /// Builds key to the last reported progress for this reactions running jobs
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group the reaction is in
+    /// * `reaction` - The reaction id
+    /// * `shared` - Shared Thorium objects
+    pub fn progress_str(group: &str, reaction: &str, shared: &Shared) -> String {
+        format!(
+            "{ns}:reaction_progress:{group}:{reaction}",
+            ns = shared.config.thorium.namespace,
+            group = group,
+            reaction = reaction,
+        )
+    }
 }
 
 /// Keys to all sub reaction lists for a reaction
@@ -77,3 +77,41 @@ pub fn census_keys(
         keys.push(key);
     }
 }
+
+/// Build the key to the hash of all repo mirrors
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+pub fn mirrors(shared: &Shared) -> String {
+    format!(
+        "{namespace}:repos:mirrors:data",
+        namespace = shared.config.thorium.namespace,
+    )
+}
+
+/// Build the key to the sorted set of when each repo mirror should next be fetched
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+pub fn mirror_schedule(shared: &Shared) -> String {
+    format!(
+        "{namespace}:repos:mirrors:schedule",
+        namespace = shared.config.thorium.namespace,
+    )
+}
+
+/// Build the key to the new commitishes held until the last request of an upload
+///
+/// # Arguments
+///
+/// * `repo` - The url of the repo these commitishes are being added to
+/// * `data` - The sha256 of the repo data these commitishes are being added from
+/// * `shared` - Shared Thorium objects
+pub fn held_commitishes(repo: &str, data: &str, shared: &Shared) -> String {
+    format!(
+        "{namespace}:repos:held_commitishes:{repo}:{data}",
+        namespace = shared.config.thorium.namespace,
+    )
+}
@@ -136,3 +136,52 @@ pub fn census_stream_case_insensitive<T: std::fmt::Display>(
         year = year,
     )
 }
+
+/// Build the key to the set of values a tag key has had in a group
+///
+/// # Arguments
+///
+/// * `kind` - The kind of tag we are getting/setting census info for
+/// * `group` - The group to look for census info for
+/// * `key` - The tag key to use
+/// * `shared` - Shared Thorium objects
+pub fn census_values<T: std::fmt::Display>(
+    kind: TagType,
+    group: &T,
+    key: &str,
+    shared: &Shared,
+) -> String {
+    format!(
+        "{namespace}:census:tags:values:{kind}:{group}:{key}",
+        namespace = shared.config.thorium.namespace,
+        kind = kind,
+        group = group,
+        key = key,
+    )
+}
+
+/// Build the key to a bulk tag jobs data
+///
+/// # Arguments
+///
+/// * `id` - The id of the bulk tag job
+/// * `shared` - Shared Thorium objects
+pub fn bulk_job<T: std::fmt::Display>(id: &T, shared: &Shared) -> String {
+    format!(
+        "{namespace}:tags:bulk:data:{id}",
+        namespace = shared.config.thorium.namespace,
+        id = id,
+    )
+}
+
+/// Build the key to the sorted set of all bulk tag jobs
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+pub fn bulk_jobs(shared: &Shared) -> String {
+    format!(
+        "{namespace}:tags:bulk:jobs",
+        namespace = shared.config.thorium.namespace,
+    )
+}
@@ -0,0 +1,59 @@
+use uuid::Uuid;
+
+use crate::utils::Shared;
+
+/// The keys to store/retrieve resumable upload sessions
+pub struct UploadKeys;
+
+impl UploadKeys {
+    /// Builds the key to an upload sessions data
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the upload session
+    /// * `shared` - Shared Thorium objects
+    pub fn data(id: &Uuid, shared: &Shared) -> String {
+        format!(
+            "{ns}:uploads:data:{id}",
+            ns = shared.config.thorium.namespace,
+            id = id,
+        )
+    }
+
+    /// Builds the key to the chunks that have been uploaded for an upload session
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the upload session
+    /// * `shared` - Shared Thorium objects
+    pub fn chunks(id: &Uuid, shared: &Shared) -> String {
+        format!(
+            "{ns}:uploads:chunks:{id}",
+            ns = shared.config.thorium.namespace,
+            id = id,
+        )
+    }
+
+    /// Builds the key to the map of sha256s to upload sessions for a user
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The user whose upload sessions to map
+    /// * `shared` - Shared Thorium objects
+    pub fn user(username: &str, shared: &Shared) -> String {
+        format!(
+            "{ns}:uploads:users:{username}",
+            ns = shared.config.thorium.namespace,
+            username = username,
+        )
+    }
+
+    /// Builds the key to the sorted set of when upload sessions expire
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared Thorium objects
+    pub fn expire(shared: &Shared) -> String {
+        format!("{ns}:uploads:expire", ns = shared.config.thorium.namespace)
+    }
+}
@@ -64,6 +64,20 @@ impl UserKeys {
         format!("{ns}:users_token_map", ns = shared.config.thorium.namespace)
     }
 
+    /// Builds the key to the searches a user has saved
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose saved searches to build a key for
+    /// * `shared` - Shared Thorium objects
+    pub fn saved_searches(user: &str, shared: &Shared) -> String {
+        format!(
+            "{ns}:user_saved_searches:{user}",
+            ns = shared.config.thorium.namespace,
+            user = user,
+        )
+    }
+
     // user data key
     ///
     /// # Arguments
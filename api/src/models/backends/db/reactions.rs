@@ -13,7 +13,8 @@ use super::{images, jobs, pipelines, streams};
 use crate::models::{
     BulkReactionResponse, Group, JobHandleStatus, JobList, JobResetRequestor, JobResets, Pipeline,
     RawJob, Reaction, ReactionActions, ReactionExpire, ReactionList, ReactionRequest,
-    ReactionStatus, StageLogs, StageLogsAdd, StatusRequest, StatusUpdate, SystemComponents, User,
+    ReactionStatus, StageLogs, StageLogsAdd, StatusRequest, StatusUpdate, SubscriptionEvent,
+    SubscriptionTarget, SystemComponents, User,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
@@ -123,6 +124,8 @@ pub async fn build<'a>(
         // set our trigger depth
         pipe.cmd("hsetnx").arg(&keys.data).arg("trigger_depth").arg(trigger_depth);
     }
+    // set this reactions priority class
+    pipe.cmd("hsetnx").arg(&keys.data).arg("priority").arg(serialize!(&cast.priority));
     // add to any required tag lists
     let pipe = cast.tags.iter()
         .fold(pipe, |pipe, tag|
@@ -276,16 +279,18 @@ pub async fn get(
     let data_key = ReactionKeys::data(group, id, shared);
     let jobs_key = ReactionKeys::jobs(group, id, shared);
     let gen_key = ReactionKeys::generators(group, id, shared);
+    let progress_key = ReactionKeys::progress(group, id, shared);
     // get reaction data
-    let (raw, jobs, gens): (HashMap<String, String>, Vec<String>, Vec<String>) = redis::pipe()
+    let (raw, jobs, gens, progress): ReactionData = redis::pipe()
         .cmd("hgetall").arg(&data_key)
         .cmd("smembers").arg(&jobs_key)
         .cmd("smembers").arg(&gen_key)
+        .cmd("hgetall").arg(&progress_key)
         .query_async(conn!(shared))
         .await?;
     // cast to reaction
     // return 404 if no data was retrieved
-    let reaction = Reaction::try_from((raw, jobs, gens))?;
+    let reaction = Reaction::try_from((raw, jobs, gens, progress))?;
     Ok(reaction)
 }
 
@@ -505,7 +510,12 @@ pub async fn list_sub_status(
 }
 
 /// A single redis response for reaction details
-type ReactionData = (HashMap<String, String>, Vec<String>, Vec<String>);
+type ReactionData = (
+    HashMap<String, String>,
+    Vec<String>,
+    Vec<String>,
+    HashMap<String, String>,
+);
 
 /// Lists all reactions details in the redis backend for a group
 ///
@@ -527,7 +537,8 @@ pub async fn list_details(
             pipe
                 .cmd("hgetall").arg(&ReactionKeys::data_str(group, id, shared))
                 .cmd("smembers").arg(&ReactionKeys::jobs_str(group, id, shared))
-                .cmd("smembers").arg(&ReactionKeys::generators_str(group, id, shared)))
+                .cmd("smembers").arg(&ReactionKeys::generators_str(group, id, shared))
+                .cmd("hgetall").arg(&ReactionKeys::progress_str(group, id, shared)))
         .query_async(conn!(shared)).await?;
     // cast to reaction structs
     let reactions = cast!(raw, Reaction::try_from);
@@ -974,9 +985,49 @@ pub async fn fail(
     parent_proceed(&reaction, progress, shared).await?;
     // try to delete any ephemeral files
     delete_ephemeral(&reaction, shared).await?;
+    // let anyone following this reactions pipeline, samples, or repos know it failed
+    notify_failed(&reaction, shared).await;
     Ok(ReactionStatus::Failed)
 }
 
+/// Notify any users following a failed reactions pipeline, samples, or repos
+///
+/// # Arguments
+///
+/// * `reaction` - The reaction that failed
+/// * `shared` - Shared Thorium objects
+async fn notify_failed(reaction: &Reaction, shared: &Shared) {
+    // failed reactions can only be seen by members of the reactions group
+    let groups = [reaction.group.clone()];
+    // build the targets that should be notified about this failure
+    let targets = std::iter::once(SubscriptionTarget::pipeline(
+        &reaction.group,
+        &reaction.pipeline,
+    ))
+    .chain(reaction.samples.iter().map(SubscriptionTarget::sample))
+    .chain(
+        reaction
+            .repos
+            .iter()
+            .map(|repo| SubscriptionTarget::repo(&repo.url)),
+    );
+    for target in targets {
+        let msg = format!(
+            "Reaction {} in pipeline {}/{} failed for {}",
+            reaction.id, reaction.group, reaction.pipeline, target
+        );
+        super::subscriptions::notify(
+            &target,
+            SubscriptionEvent::FailedReactions,
+            &groups,
+            None,
+            msg,
+            shared,
+        )
+        .await;
+    }
+}
+
 /// Saves stage logs into scylla
 ///
 /// # Arguments
@@ -1199,6 +1250,10 @@ pub async fn delete(reaction: &Reaction, shared: &Shared) -> Result<(), ApiError
         );
         // execute our redis pipeline
         let _: () = pipe.atomic().query_async(conn!(shared)).await?;
+        // delete any checkpoint blobs these jobs saved
+        for job in &jobs.details {
+            jobs::delete_checkpoint_blob(job, shared).await?;
+        }
         // check if our cursor has been exhausted
         if jobs.cursor.is_none() {
             break
@@ -1220,6 +1275,7 @@ pub async fn delete(reaction: &Reaction, shared: &Shared) -> Result<(), ApiError
     let _: () = pipe.atomic()
         .cmd("del").arg(&keys.data)
         .cmd("del").arg(&keys.logs)
+        .cmd("del").arg(&keys.progress)
         .cmd("zrem").arg(&ReactionKeys::group_set(&reaction.group, &reaction.status, shared))
             .arg(&reaction.id.to_string())
         .cmd("srem").arg(&keys.set).arg(reaction.id.to_string())
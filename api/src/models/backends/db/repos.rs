@@ -1,6 +1,7 @@
 //! Handle repo interactions with the backend
 
 use axum::http::StatusCode;
+use bb8_redis::redis::cmd;
 use chrono::prelude::*;
 use futures::stream::{self, StreamExt};
 use itertools::Itertools;
@@ -10,16 +11,18 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use tracing::{event, instrument, Level};
 use uuid::Uuid;
 
-use super::{ExistsCursor, ScyllaCursor};
+use super::{keys, ExistsCursor, ScyllaCursor};
 use crate::models::backends::TagSupport;
 use crate::models::{
-    Commitish, CommitishDetails, CommitishKinds, CommitishListParams, CommitishMapRequest, Repo,
-    RepoCheckout, RepoListLine, RepoListParams, RepoRequest, RepoRow, RepoScheme, RepoSubmission,
-    RepoSubmissionChunk, RepoUrlComponents, TagRequest, TagType, User,
+    BranchData, Commitish, CommitishDetails, CommitishKinds, CommitishListParams,
+    CommitishMapRequest, CommitishRequest, Repo, RepoCheckout, RepoListLine, RepoListParams,
+    RepoMirror, RepoRequest, RepoRow, RepoScheme, RepoSubmission, RepoSubmissionChunk,
+    RepoUrlComponents, TagProvenanceRequest, TagRequest, TagType, User,
 };
 use crate::utils::{helpers, ApiError, Shared};
 use crate::{
-    bad, internal_err, log_scylla_err, not_found, same_vec, serialize, serialize_opt, unauthorized,
+    bad, conn, deserialize, internal_err, log_scylla_err, not_found, query, same_vec, serialize,
+    serialize_opt, unauthorized,
 };
 
 /// Check if a user already has a matching repo submission
@@ -286,8 +289,12 @@ pub async fn create(user: &User, req: RepoRequest, shared: &Shared) -> Result<St
     let mut tag_req = TagRequest::<Repo>::default().groups(req.groups.clone());
     // move our tags over to our tag request
     tag_req.tags = req.tags;
+    // these tags were set by this user
+    let origins = TagProvenanceRequest::default()
+        .resolve(user, TagType::Repos, &repo.url, &req.groups, shared)
+        .await?;
     // save our files tags to scylla
-    super::tags::create(user, repo.url.clone(), tag_req, &earliest, shared).await?;
+    super::tags::create(user, repo.url.clone(), tag_req, &origins, &earliest, shared).await?;
     Ok(repo.url)
 }
 
@@ -331,6 +338,7 @@ async fn get_updated_repo(
                     default_checkout,
                     earliest: submission.earliest.clone(),
                     submissions: vec![submission],
+                    comments: Vec::default(),
                 })
             } else {
                 Err(err)
@@ -515,6 +523,8 @@ pub async fn get(
         Some(mut repo) => {
             // get the tags for this repo
             super::tags::get(TagType::Repos, groups, url, &mut repo.tags, shared).await?;
+            // get the comments for this repo
+            super::comments::get(groups, url, &mut repo.comments, shared).await?;
             // return our repo
             Ok(repo)
         }
@@ -976,6 +986,169 @@ pub async fn commitish_exists(
     not_found!(format!("Repo {} does not have commit {}", repo, commit))
 }
 
+/// Get which of some commitishes a repo already has
+///
+/// # Arguments
+///
+/// * `groups` - The groups to look for commitishes in
+/// * `repo` - The repo to check for commitishes in
+/// * `kind` - The kind of commitishes to check
+/// * `keys` - The commitishes to check
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::repos::stored_commitishes", skip(keys, shared), err(Debug))]
+pub async fn stored_commitishes(
+    groups: &[String],
+    repo: &str,
+    kind: CommitishKinds,
+    keys: &[String],
+    shared: &Shared,
+) -> Result<HashSet<String>, ApiError> {
+    let mut stored = HashSet::with_capacity(keys.len());
+    // scylla limits the partitions a query can hit so keep groups * keys at 100 or less
+    let group_chunk = groups.len().clamp(1, 100);
+    let key_chunk = (100 / group_chunk).max(1);
+    for groups in groups.chunks(group_chunk) {
+        for keys in keys.chunks(key_chunk) {
+            // check which of this chunks commitishes exist
+            let query = shared
+                .scylla
+                .session
+                .execute_unpaged(
+                    &shared.scylla.prep.commitishes.exists_many,
+                    (kind, groups, repo, keys),
+                )
+                .await?;
+            // enable rows on this response
+            let query_rows = query.into_rows_result()?;
+            // add the commitishes we found while logging any errors
+            stored.extend(
+                query_rows
+                    .rows::<(String,)>()?
+                    .filter_map(|row| log_scylla_err!(row))
+                    .map(|(key,)| key),
+            );
+        }
+    }
+    Ok(stored)
+}
+
+/// Get the commits that some branches already point to in a repo
+///
+/// # Arguments
+///
+/// * `groups` - The groups to look for branches in
+/// * `repo` - The repo to get branches from
+/// * `branches` - The names of the branches to get
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::repos::branch_heads", skip(branches, shared), err(Debug))]
+pub async fn branch_heads(
+    groups: &[String],
+    repo: &str,
+    branches: &[String],
+    shared: &Shared,
+) -> Result<HashMap<String, HashSet<String>>, ApiError> {
+    let mut heads: HashMap<String, HashSet<String>> = HashMap::with_capacity(branches.len());
+    // scylla limits the partitions a query can hit so keep groups * branches at 100 or less
+    let group_chunk = groups.len().clamp(1, 100);
+    let branch_chunk = (100 / group_chunk).max(1);
+    for groups in groups.chunks(group_chunk) {
+        for branches in branches.chunks(branch_chunk) {
+            // get the data for this chunk of branches
+            let query = shared
+                .scylla
+                .session
+                .execute_unpaged(
+                    &shared.scylla.prep.commitishes.get_data_many,
+                    (CommitishKinds::Branch, groups, repo, branches),
+                )
+                .await?;
+            // enable rows on this response
+            let query_rows = query.into_rows_result()?;
+            // add the commit each branch points to in each group while logging any errors
+            for (name, data) in query_rows
+                .rows::<(String, String)>()?
+                .filter_map(|row| log_scylla_err!(row))
+            {
+                let branch: BranchData = deserialize!(&data);
+                heads.entry(name).or_default().insert(branch.commit);
+            }
+        }
+    }
+    Ok(heads)
+}
+
+/// How long to hold the new commitishes from an upload whose last request never arrives
+const HELD_COMMITISHES_TTL: i64 = 86_400;
+
+/// Hold the new commitishes from an upload until its last request arrives
+///
+/// # Arguments
+///
+/// * `repo` - The repo these commitishes are being added to
+/// * `data` - The sha256 of the repo data these commitishes are being added from
+/// * `commitishes` - The new commitishes to hold
+/// * `shared` - Shared Thorium objects
+#[instrument(
+    name = "db::repos::hold_commitishes",
+    skip(commitishes, shared),
+    fields(held = commitishes.len()),
+    err(Debug)
+)]
+pub async fn hold_commitishes(
+    repo: &str,
+    data: &str,
+    commitishes: &HashMap<String, CommitishRequest>,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // skip requests that don't have anything new
+    if commitishes.is_empty() {
+        return Ok(());
+    }
+    let key = keys::repos::held_commitishes(repo, data, shared);
+    // add these commitishes and push back when they expire
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for (name, commitish) in commitishes {
+        pipe.cmd("hset")
+            .arg(&key)
+            .arg(name)
+            .arg(serialize!(commitish));
+    }
+    pipe.cmd("expire").arg(&key).arg(HELD_COMMITISHES_TTL);
+    let _: () = pipe.query_async(conn!(shared)).await?;
+    Ok(())
+}
+
+/// Take the new commitishes held from the earlier requests of an upload
+///
+/// # Arguments
+///
+/// * `repo` - The repo these commitishes are being added to
+/// * `data` - The sha256 of the repo data these commitishes are being added from
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::repos::take_held_commitishes", skip(shared), err(Debug))]
+pub async fn take_held_commitishes(
+    repo: &str,
+    data: &str,
+    shared: &Shared,
+) -> Result<HashMap<String, CommitishRequest>, ApiError> {
+    let key = keys::repos::held_commitishes(repo, data, shared);
+    // get and clear our held commitishes at the same time
+    let (raw, _): (HashMap<String, String>, u64) = redis::pipe()
+        .atomic()
+        .cmd("hgetall")
+        .arg(&key)
+        .cmd("del")
+        .arg(&key)
+        .query_async(conn!(shared))
+        .await?;
+    let mut held = HashMap::with_capacity(raw.len());
+    for (name, raw) in raw {
+        held.insert(name, deserialize!(&raw));
+    }
+    Ok(held)
+}
+
 /// Get the latest commit for a repo
 ///
 /// # Arguments
@@ -1168,3 +1341,148 @@ pub async fn list(
     cursor.save(shared).await?;
     Ok(cursor)
 }
+
+/// Saves a repo mirror into redis and schedules its next fetch
+///
+/// # Arguments
+///
+/// * `mirror` - The repo mirror to save
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::repos::save_mirror", skip_all, fields(url = &mirror.url), err(Debug))]
+pub async fn save_mirror(mirror: &RepoMirror, shared: &Shared) -> Result<(), ApiError> {
+    // build the keys to our mirror data and schedule
+    let data = keys::repos::mirrors(shared);
+    let schedule = keys::repos::mirror_schedule(shared);
+    // save this mirror and schedule its next fetch
+    let _: () = redis::pipe()
+        .atomic()
+        .cmd("hset").arg(&data).arg(&mirror.url).arg(serialize!(mirror))
+        .cmd("zadd").arg(&schedule).arg(mirror.next_fetch.timestamp()).arg(&mirror.url)
+        .query_async(conn!(shared))
+        .await?;
+    Ok(())
+}
+
+/// Gets a repo mirror from redis if it exists
+///
+/// # Arguments
+///
+/// * `url` - The url of the repo whose mirror to get
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::repos::get_mirror", skip(shared), err(Debug))]
+pub async fn get_mirror(url: &str, shared: &Shared) -> Result<Option<RepoMirror>, ApiError> {
+    // build the key to our mirror data
+    let data = keys::repos::mirrors(shared);
+    // get this mirrors data if it exists
+    let raw: Option<String> = query!(cmd("hget").arg(&data).arg(url), shared).await?;
+    match raw {
+        Some(raw) => Ok(Some(deserialize!(&raw))),
+        None => Ok(None),
+    }
+}
+
+/// Lists all repo mirrors
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::repos::list_mirrors", skip_all, err(Debug))]
+pub async fn list_mirrors(shared: &Shared) -> Result<Vec<RepoMirror>, ApiError> {
+    // build the key to our mirror data
+    let data = keys::repos::mirrors(shared);
+    // get the data for all of our mirrors
+    let raw: Vec<String> = query!(cmd("hvals").arg(&data), shared).await?;
+    // cast our mirrors
+    let mut mirrors = Vec::with_capacity(raw.len());
+    for raw in &raw {
+        mirrors.push(deserialize!(raw));
+    }
+    Ok(mirrors)
+}
+
+/// Deletes a repo mirror from redis
+///
+/// This does not delete the repo or any of the data already ingested for it.
+///
+/// # Arguments
+///
+/// * `url` - The url of the repo whose mirror to delete
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::repos::delete_mirror", skip(shared), err(Debug))]
+pub async fn delete_mirror(url: &str, shared: &Shared) -> Result<(), ApiError> {
+    // build the keys to our mirror data and schedule
+    let data = keys::repos::mirrors(shared);
+    let schedule = keys::repos::mirror_schedule(shared);
+    // stop tracking this mirror
+    let _: () = redis::pipe()
+        .atomic()
+        .cmd("hdel").arg(&data).arg(url)
+        .cmd("zrem").arg(&schedule).arg(url)
+        .query_async(conn!(shared))
+        .await?;
+    Ok(())
+}
+
+/// Claims repo mirrors that are due to be fetched
+///
+/// Claimed mirrors are rescheduled a full interval out so that if their fetcher
+/// dies they will eventually be retried.
+///
+/// # Arguments
+///
+/// * `limit` - The max number of mirrors to claim
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::repos::claim_mirrors", skip(shared), err(Debug))]
+pub async fn claim_mirrors(limit: usize, shared: &Shared) -> Result<Vec<RepoMirror>, ApiError> {
+    // build the keys to our mirror data and schedule
+    let data = keys::repos::mirrors(shared);
+    let schedule = keys::repos::mirror_schedule(shared);
+    // get the mirrors that are due to be fetched
+    let now = Utc::now();
+    let due: Vec<String> = query!(
+        cmd("zrangebyscore")
+            .arg(&schedule)
+            .arg("-inf")
+            .arg(now.timestamp())
+            .arg("limit")
+            .arg(0)
+            .arg(limit),
+        shared
+    )
+    .await?;
+    if due.is_empty() {
+        return Ok(Vec::default());
+    }
+    // try to remove each due mirror from the schedule so only one fetcher claims it
+    let mut pipe = redis::pipe();
+    for url in &due {
+        pipe.cmd("zrem").arg(&schedule).arg(url);
+    }
+    let removed: Vec<bool> = pipe.query_async(conn!(shared)).await?;
+    let claimed = due
+        .into_iter()
+        .zip(removed)
+        .filter_map(|(url, removed)| removed.then_some(url))
+        .collect::<Vec<String>>();
+    if claimed.is_empty() {
+        return Ok(Vec::default());
+    }
+    // get the data for the mirrors we claimed
+    let raw: Vec<Option<String>> = query!(cmd("hmget").arg(&data).arg(&claimed), shared).await?;
+    let mut mirrors = Vec::with_capacity(claimed.len());
+    let mut pipe = redis::pipe();
+    for raw in raw.into_iter().flatten() {
+        let mirror: RepoMirror = deserialize!(&raw);
+        // push this mirrors next fetch out by its interval in case our fetcher dies
+        let lease = mirror.after_interval(now);
+        pipe.cmd("zadd")
+            .arg(&schedule)
+            .arg(lease.timestamp())
+            .arg(&mirror.url);
+        mirrors.push(mirror);
+    }
+    let _: () = pipe.query_async(conn!(shared)).await?;
+    Ok(mirrors)
+}
@@ -2,14 +2,14 @@
 
 use chrono::prelude::*;
 use itertools::Itertools;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tracing::{event, instrument, span, Level, Span};
 use uuid::Uuid;
 
 use crate::models::backends::OutputSupport;
 use crate::models::{
-    Output, OutputDisplayType, OutputForm, OutputId, OutputIdRow, OutputKind, OutputMap, OutputRow,
-    ResultSearchEvent,
+    Comment, Output, OutputDisplayType, OutputForm, OutputId, OutputIdRow, OutputKind, OutputMap,
+    OutputRow, ResultSearchEvent,
 };
 use crate::utils::{helpers, ApiError, Shared};
 use crate::{internal_err, log_scylla_err, unauthorized};
@@ -405,6 +405,26 @@ pub async fn get(
             );
         }
     }
+    // get the groups and keys to get the comments on our results with
+    let mut comment_groups = HashSet::new();
+    let mut comment_keys = Vec::new();
+    for output in outputs.results.values().flatten() {
+        comment_groups.extend(output.groups.iter().cloned());
+        comment_keys.push(output.id.to_string());
+    }
+    let comment_groups = comment_groups.into_iter().collect::<Vec<String>>();
+    // get the comments for all of our results at once
+    let mut comments = super::comments::get_many(&comment_groups, &comment_keys, shared).await?;
+    for output in outputs.results.values_mut().flatten() {
+        if let Some(mut found) = comments.remove(&output.id.to_string()) {
+            // only return comments in the groups we can see this result in
+            for comment in &mut found {
+                comment.groups.retain(|group| output.groups.contains(group));
+            }
+            found.retain(|comment| !comment.groups.is_empty());
+            output.comments = found;
+        }
+    }
     Ok(outputs)
 }
 
@@ -416,12 +436,14 @@ pub async fn get(
 /// * `key` - The key to prune results from
 /// * `result_id` - The id of the result to possibly prune
 /// * `files` - The files in s3 to prune if neccesary
+/// * `comments` - The comments on this result to prune if neccesary
 /// * `shared` - Shared Thorium objects
 async fn prune_helper(
     kind: OutputKind,
     key: &str,
     result_id: &Uuid,
     files: &[String],
+    comments: &[Comment],
     shared: &Shared,
 ) -> Result<(), ApiError> {
     // query scylla to see if this result is reachable
@@ -448,6 +470,12 @@ async fn prune_helper(
             let s3_path = format!("{}/{}", &result_id, name);
             shared.s3.results.delete(&s3_path).await?;
         }
+        // delete any comments on this result and their attachments
+        let comment_key = result_id.to_string();
+        for comment in comments {
+            super::comments::delete(&comment_key, &comment.groups, comment, shared).await?;
+        }
+        super::comments::prune_attachments(comments, &comment_key, shared).await?;
     }
     Ok(())
 }
@@ -513,13 +541,13 @@ pub async fn prune(
         }
         // if a result was pruned then add this to our list of pruned ids
         if prune_flag {
-            pruned.push((&result.id, &result.files));
+            pruned.push((&result.id, &result.files, &result.comments));
         }
     }
     // crawl ove the result files that might need to be pruned
-    for (result_id, files) in pruned.iter() {
+    for (result_id, files, comments) in pruned.iter() {
         // prune this result if its no longer needed
-        prune_helper(kind, key, result_id, files, shared).await?;
+        prune_helper(kind, key, result_id, files, comments, shared).await?;
     }
     Ok(())
 }
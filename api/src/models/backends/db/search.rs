@@ -1,10 +1,13 @@
 //! Handles searches, including creating/retrieving cursors in the db and sending requests to Elastic
 
+use bb8_redis::redis::cmd;
 use tracing::instrument;
 
+use super::keys::UserKeys;
 use super::ElasticCursor;
-use crate::models::{ApiCursor, ElasticDoc, ElasticSearchParams};
+use crate::models::{ApiCursor, ElasticDoc, ElasticSearchParams, SavedSearch, User};
 use crate::utils::{ApiError, Shared};
+use crate::{conflict, deserialize, not_found, query, serialize};
 
 pub mod events;
 
@@ -38,3 +41,87 @@ pub async fn search(
         })
     }
 }
+
+/// Saves a search for a user
+///
+/// # Arguments
+///
+/// * `user` - The user that is saving this search
+/// * `saved` - The search to save
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::search::save", skip(user, saved, shared), fields(user = &user.username, name = &saved.name), err(Debug))]
+pub async fn save(user: &User, saved: &SavedSearch, shared: &Shared) -> Result<(), ApiError> {
+    // build the key to this users saved searches
+    let key = UserKeys::saved_searches(&user.username, shared);
+    // only save this search if one with the same name doesn't already exist
+    let added: bool = query!(
+        cmd("hsetnx")
+            .arg(&key)
+            .arg(&saved.name)
+            .arg(serialize!(saved)),
+        shared
+    )
+    .await?;
+    if !added {
+        return conflict!(format!("Saved search {} already exists", saved.name));
+    }
+    Ok(())
+}
+
+/// Gets a search that a user has saved
+///
+/// # Arguments
+///
+/// * `user` - The user whose saved search to get
+/// * `name` - The name of the saved search to get
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::search::get_saved", skip(user, shared), fields(user = &user.username), err(Debug))]
+pub async fn get_saved(user: &User, name: &str, shared: &Shared) -> Result<SavedSearch, ApiError> {
+    // build the key to this users saved searches
+    let key = UserKeys::saved_searches(&user.username, shared);
+    // get this saved search
+    let raw: Option<String> = query!(cmd("hget").arg(&key).arg(name), shared).await?;
+    match raw {
+        Some(raw) => Ok(deserialize!(&raw)),
+        None => not_found!(format!("Saved search {name} not found")),
+    }
+}
+
+/// Lists all of the searches a user has saved
+///
+/// # Arguments
+///
+/// * `user` - The user whose saved searches to list
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::search::list_saved", skip_all, fields(user = &user.username), err(Debug))]
+pub async fn list_saved(user: &User, shared: &Shared) -> Result<Vec<SavedSearch>, ApiError> {
+    // build the key to this users saved searches
+    let key = UserKeys::saved_searches(&user.username, shared);
+    // get all of this users saved searches
+    let raw: Vec<String> = query!(cmd("hvals").arg(&key), shared).await?;
+    // deserialize our saved searches
+    let mut saved = Vec::with_capacity(raw.len());
+    for raw_saved in &raw {
+        saved.push(deserialize!(raw_saved));
+    }
+    Ok(saved)
+}
+
+/// Deletes a search that a user has saved
+///
+/// # Arguments
+///
+/// * `user` - The user whose saved search to delete
+/// * `name` - The name of the saved search to delete
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::search::delete_saved", skip(user, shared), fields(user = &user.username), err(Debug))]
+pub async fn delete_saved(user: &User, name: &str, shared: &Shared) -> Result<(), ApiError> {
+    // build the key to this users saved searches
+    let key = UserKeys::saved_searches(&user.username, shared);
+    // delete this saved search
+    let removed: u64 = query!(cmd("hdel").arg(&key).arg(name), shared).await?;
+    if removed == 0 {
+        return not_found!(format!("Saved search {name} not found"));
+    }
+    Ok(())
+}
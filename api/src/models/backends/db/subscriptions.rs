@@ -0,0 +1,319 @@
+//! Logic for interacting with subscriptions in the database
+
+use chrono::prelude::*;
+use std::collections::{HashMap, HashSet};
+use tracing::{event, instrument, Level};
+use uuid::Uuid;
+
+use crate::models::{
+    Notification, NotificationLevel, Subscription, SubscriptionEvent, SubscriptionTarget, User,
+};
+use crate::utils::{ApiError, Shared};
+use crate::{deserialize, serialize};
+
+/// The columns we get when reading subscriptions from scylla
+type SubscriptionColumns = (String, Uuid, String, String, bool, DateTime<Utc>);
+
+/// Cast a row from scylla to a subscription
+///
+/// # Arguments
+///
+/// * `row` - The row to cast
+fn cast(row: SubscriptionColumns) -> Result<Subscription, ApiError> {
+    let (user, id, target, events, email, created) = row;
+    // build our subscription
+    let subscription = Subscription {
+        id,
+        user,
+        target: deserialize!(&target),
+        events: deserialize!(&events),
+        email,
+        created,
+    };
+    Ok(subscription)
+}
+
+/// Save a subscription to scylla
+///
+/// # Arguments
+///
+/// * `subscription` - The subscription to save
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::subscriptions::create", skip_all, err(Debug))]
+pub async fn create(subscription: &Subscription, shared: &Shared) -> Result<(), ApiError> {
+    // save the subscription to scylla
+    shared
+        .scylla
+        .session
+        .execute_unpaged(
+            &shared.scylla.prep.subscriptions.insert,
+            (
+                subscription.target.kind(),
+                subscription.target.key(),
+                &subscription.user,
+                subscription.id,
+                serialize!(&subscription.target),
+                serialize!(&subscription.events),
+                subscription.email,
+                subscription.created,
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Lists all of a users subscriptions
+///
+/// # Arguments
+///
+/// * `username` - The user whose subscriptions to list
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::subscriptions::list", skip(shared), err(Debug))]
+pub async fn list(username: &str, shared: &Shared) -> Result<Vec<Subscription>, ApiError> {
+    // query for this users subscriptions
+    let query = shared
+        .scylla
+        .session
+        .execute_unpaged(&shared.scylla.prep.subscriptions.list, (username,))
+        .await?;
+    // enable rows on this query response
+    let query_rows = query.into_rows_result()?;
+    // instance a list of subscriptions with the right size
+    let mut subscriptions = Vec::with_capacity(query_rows.rows_num());
+    // cast our rows to subscriptions
+    for row in query_rows.rows::<SubscriptionColumns>()? {
+        subscriptions.push(cast(row?)?);
+    }
+    // show the oldest subscriptions first
+    subscriptions.sort_by_key(|sub| sub.created);
+    Ok(subscriptions)
+}
+
+/// Gets a specific subscription for a user if it exists
+///
+/// # Arguments
+///
+/// * `username` - The user whose subscription to get
+/// * `id` - The id of the subscription to get
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::subscriptions::get", skip(shared), err(Debug))]
+pub async fn get(
+    username: &str,
+    id: &Uuid,
+    shared: &Shared,
+) -> Result<Option<Subscription>, ApiError> {
+    // query for this subscription
+    let query = shared
+        .scylla
+        .session
+        .execute_unpaged(&shared.scylla.prep.subscriptions.get, (username, id))
+        .await?;
+    // enable rows on this query response
+    let query_rows = query.into_rows_result()?;
+    // cast the first row we find to a subscription
+    match query_rows.maybe_first_row::<SubscriptionColumns>()? {
+        Some(row) => Ok(Some(cast(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Deletes a subscription
+///
+/// # Arguments
+///
+/// * `subscription` - The subscription to delete
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::subscriptions::delete", skip_all, err(Debug))]
+pub async fn delete(subscription: &Subscription, shared: &Shared) -> Result<(), ApiError> {
+    // delete the subscription in scylla
+    shared
+        .scylla
+        .session
+        .execute_unpaged(
+            &shared.scylla.prep.subscriptions.delete,
+            (
+                subscription.target.kind(),
+                subscription.target.key(),
+                &subscription.user,
+                subscription.id,
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Gets all subscriptions to a specific target
+///
+/// # Arguments
+///
+/// * `target` - The target to get subscriptions for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::subscriptions::get_target", skip(shared), err(Debug))]
+pub async fn get_target(
+    target: &SubscriptionTarget,
+    shared: &Shared,
+) -> Result<Vec<Subscription>, ApiError> {
+    // query for the subscriptions to this target
+    let query = shared
+        .scylla
+        .session
+        .execute_unpaged(
+            &shared.scylla.prep.subscriptions.get_target,
+            (target.kind(), target.key()),
+        )
+        .await?;
+    // enable rows on this query response
+    let query_rows = query.into_rows_result()?;
+    // instance a list of subscriptions with the right size
+    let mut subscriptions = Vec::with_capacity(query_rows.rows_num());
+    // cast our rows to subscriptions
+    for row in query_rows.rows::<SubscriptionColumns>()? {
+        let subscription = cast(row?)?;
+        // different tags can share the same key so make sure this is the right target
+        if &subscription.target == target {
+            subscriptions.push(subscription);
+        }
+    }
+    Ok(subscriptions)
+}
+
+/// Notify all users subscribed to a target about an event
+///
+/// Notifications are best effort so any errors are logged instead of returned.
+///
+/// # Arguments
+///
+/// * `target` - The target this event happened to
+/// * `event` - The kind of event that happened
+/// * `groups` - The groups this event can be seen in
+/// * `actor` - The user that caused this event if one did
+/// * `msg` - The message to notify users with
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::subscriptions::notify", skip(groups, msg, shared))]
+pub async fn notify(
+    target: &SubscriptionTarget,
+    event: SubscriptionEvent,
+    groups: &[String],
+    actor: Option<&str>,
+    msg: String,
+    shared: &Shared,
+) {
+    if let Err(err) = notify_helper(target, event, groups, actor, msg, shared).await {
+        event!(
+            Level::ERROR,
+            msg = "Failed to notify subscribers",
+            error = err.to_string()
+        );
+    }
+}
+
+/// Notify all users following any of the tags that were just added to a sample or repo
+///
+/// # Arguments
+///
+/// * `item` - The sample or repo that was tagged
+/// * `tags` - The tags that were added
+/// * `groups` - The groups these tags were added in
+/// * `actor` - The user that added these tags
+/// * `shared` - Shared Thorium objects
+pub async fn notify_tagged(
+    item: &SubscriptionTarget,
+    tags: &HashMap<String, HashSet<String>>,
+    groups: &[String],
+    actor: &str,
+    shared: &Shared,
+) {
+    for (key, values) in tags {
+        for value in values {
+            let target = SubscriptionTarget::tag(key, value);
+            let msg = format!("{item} was tagged with {key}={value}");
+            notify(
+                &target,
+                SubscriptionEvent::Tagged,
+                groups,
+                Some(actor),
+                msg,
+                shared,
+            )
+            .await;
+        }
+    }
+}
+
+/// Helps notify all users subscribed to a target about an event
+///
+/// # Arguments
+///
+/// * `target` - The target this event happened to
+/// * `event` - The kind of event that happened
+/// * `groups` - The groups this event can be seen in
+/// * `actor` - The user that caused this event if one did
+/// * `msg` - The message to notify users with
+/// * `shared` - Shared Thorium objects
+async fn notify_helper(
+    target: &SubscriptionTarget,
+    event: SubscriptionEvent,
+    groups: &[String],
+    actor: Option<&str>,
+    msg: String,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // failed reactions are more pressing then other events
+    let level = match event {
+        SubscriptionEvent::FailedReactions => NotificationLevel::Warn,
+        _ => NotificationLevel::Info,
+    };
+    // get everyone subscribed to this target
+    let subscriptions = get_target(target, shared).await?;
+    // track the addresses to email about this event
+    let mut emails = Vec::default();
+    for subscription in subscriptions {
+        // skip users that don't care about this event or that caused it
+        if !subscription.wants(event) || actor == Some(subscription.user.as_str()) {
+            continue;
+        }
+        // skip any users that no longer exist
+        let Ok(user) = super::users::get(&subscription.user, shared).await else {
+            continue;
+        };
+        // skip any users that can't see this event
+        if !user.is_admin_or_analyst() && !groups.iter().any(|group| user.groups.contains(group)) {
+            continue;
+        }
+        // add this notification to the users inbox
+        let notification: Notification<User> =
+            Notification::new(user.username.clone(), msg.clone(), level.clone());
+        if let Err(err) = super::notifications::create(notification, None, shared).await {
+            // one failed notification shouldn't stop the rest of our subscribers from being notified
+            event!(
+                Level::ERROR,
+                msg = "Failed to notify subscriber",
+                user = user.username,
+                error = err.to_string()
+            );
+            continue;
+        }
+        // email this user too if they asked for it and have a verified email
+        if subscription.email && user.verified {
+            emails.push(user.email);
+        }
+    }
+    // send our emails in the background so a slow mail server doesn't hold up this request
+    if let Some(client) = shared.email.as_ref().filter(|_| !emails.is_empty()) {
+        let client = client.clone();
+        let subject = format!("Thorium: update on {target}");
+        tokio::spawn(async move {
+            for addr in emails {
+                // log any emails we fail to send and keep emailing everyone else
+                if let Err(err) = client.send(&addr, subject.clone(), msg.clone()).await {
+                    event!(
+                        Level::ERROR,
+                        msg = "Failed to email subscriber",
+                        error = err.to_string()
+                    );
+                }
+            }
+        });
+    }
+    Ok(())
+}
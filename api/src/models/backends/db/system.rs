@@ -343,6 +343,8 @@ pub async fn register(node: &NodeRegistration, shared: &Shared) -> Result<(), Ap
                 &node.name,
                 serialize!(&NodeHealth::Registered),
                 serialize!(&node.resources),
+                serialize!(&node.arch),
+                serialize!(&node.emulated),
             ),
         )
         .await?;
@@ -537,6 +539,25 @@ pub async fn update_node(
             )
             .await?;
     }
+    // update this nodes architectures if they changed
+    if update.arch.is_some() || update.emulated.is_some() {
+        // fall back to our current architectures for anything not in this update
+        let arch = update.arch.unwrap_or(node.arch);
+        let emulated = update.emulated.as_ref().unwrap_or(&node.emulated);
+        shared
+            .scylla
+            .session
+            .execute_unpaged(
+                &shared.scylla.prep.nodes.update_arch,
+                (
+                    serialize!(&arch),
+                    serialize!(emulated),
+                    &node.cluster,
+                    &node.name,
+                ),
+            )
+            .await?;
+    }
     Ok(())
 }
 
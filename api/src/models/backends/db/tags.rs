@@ -4,17 +4,20 @@
 //! of data they are tied too. This is largely becuause how they determine the
 //! timestamp each tag should be uploaded at.
 
+use bb8_redis::redis::cmd;
 use chrono::prelude::*;
 use std::collections::{HashMap, HashSet};
 use tracing::{event, instrument, Level};
+use uuid::Uuid;
 
 use super::keys::tags;
 use crate::models::backends::TagSupport;
 use crate::models::{
-    Event, FullTagRow, TagDeleteRequest, TagMap, TagRequest, TagRow, TagSearchEvent, TagType, User,
+    BulkTagJob, Event, FullTagRow, TagDeleteRequest, TagMap, TagProvenance, TagProvenanceRow,
+    TagRequest, TagRow, TagSearchEvent, TagType, User,
 };
 use crate::utils::{helpers, ApiError, Shared};
-use crate::{bad, conn, internal_err, log_scylla_err};
+use crate::{bad, conn, deserialize, internal_err, log_scylla_err, query, serialize};
 
 /// Save new tags into scylla
 ///
@@ -23,12 +26,13 @@ use crate::{bad, conn, internal_err, log_scylla_err};
 /// * `user` - The user that is creating tags
 /// * `key` - The key to the item we are tagging
 /// * `req` - The request containing the tags to create and groups to save them in
+/// * `origins` - Where these tags came from in each group
 /// * `earliest` - The earliest each group has seen this item
 /// * `shared` - Shared Thorium objects
 #[rustfmt::skip]
 #[instrument(
     name = "db::tags::create",
-    skip(user, req, earliest, shared),
+    skip(user, req, origins, earliest, shared),
     fields(kind = T::tag_kind().as_str()),
     err(Debug)
 )]
@@ -36,6 +40,7 @@ pub async fn create<T: TagSupport>(
     user: &User,
     key: String,
     req: TagRequest<T>,
+    origins: &[TagProvenance],
     earliest: &HashMap<&String, DateTime<Utc>>,
     shared: &Shared,
 ) -> Result<(), ApiError> {
@@ -69,6 +74,10 @@ pub async fn create<T: TagSupport>(
                                 ),
                             )
                             .await?;
+                        // save where this tag came from
+                        for origin in origins.iter().filter(|origin| &origin.group == group) {
+                            save_provenance(kind, &key, tag_key, tag_value, origin, shared).await?;
+                        }
                         // build the keys for this tags census info
                         let count_key = tags::census_count(
                             T::tag_kind(),
@@ -104,11 +113,13 @@ pub async fn create<T: TagSupport>(
                             year,
                             shared,
                         );
+                        let values_key = tags::census_values(T::tag_kind(), group, tag_key, shared);
                         // add data into redis
                         pipe.cmd("hincrby").arg(count_key).arg(bucket).arg(1)
                             .cmd("hincrby").arg(count_key_case_insensitive).arg(bucket).arg(1)
                             .cmd("zadd").arg(stream_key_case_insensitive).arg(bucket).arg(bucket)
-                            .cmd("zadd").arg(stream_key).arg(bucket).arg(bucket);
+                            .cmd("zadd").arg(stream_key).arg(bucket).arg(bucket)
+                            .cmd("sadd").arg(values_key).arg(tag_value);
                     }
                 }
             }
@@ -143,12 +154,13 @@ pub async fn create<T: TagSupport>(
 /// * `user` - The user that is creating tags
 /// * `key` - The key to the item we are tagging
 /// * `req` - The request containing the tags to create and groups to save them in
+/// * `origins` - Where these tags came from in each group
 /// * `earliest` - The earliest each group has seen this item
 /// * `shared` - Shared Thorium objects
 #[rustfmt::skip]
 #[instrument(
     name = "db::tags::create_owned",
-    skip(user, req, earliest, shared),
+    skip(user, req, origins, earliest, shared),
     fields(kind = T::tag_kind().as_str()),
     err(Debug)
 )]
@@ -156,6 +168,7 @@ pub async fn create_owned<T: TagSupport>(
     user: &User,
     key: String,
     req: TagRequest<T>,
+    origins: &[TagProvenance],
     earliest: &HashMap<String, DateTime<Utc>>,
     shared: &Shared,
 ) -> Result<(), ApiError> {
@@ -198,6 +211,10 @@ pub async fn create_owned<T: TagSupport>(
                                 ),
                             )
                             .await?;
+                        // save where this tag came from
+                        for origin in origins.iter().filter(|origin| &origin.group == group) {
+                            save_provenance(kind, &key, tag_key, tag_value, origin, shared).await?;
+                        }
                         // build the keys for this tags census info
                         let count_key = tags::census_count(
                             T::tag_kind(),
@@ -233,11 +250,13 @@ pub async fn create_owned<T: TagSupport>(
                             year,
                             shared,
                         );
+                        let values_key = tags::census_values(T::tag_kind(), group, tag_key, shared);
                         // add data into redis
                         pipe.cmd("hincrby").arg(count_key).arg(bucket).arg(1)
                             .cmd("hincrby").arg(count_key_case_insensitive).arg(bucket).arg(1)
                             .cmd("zadd").arg(stream_key_case_insensitive).arg(bucket).arg(bucket)
-                            .cmd("zadd").arg(stream_key).arg(bucket).arg(bucket);
+                            .cmd("zadd").arg(stream_key).arg(bucket).arg(bucket)
+                            .cmd("sadd").arg(values_key).arg(tag_value);
                     }
                 }
             }
@@ -266,6 +285,51 @@ pub async fn create_owned<T: TagSupport>(
     Ok(())
 }
 
+/// Save where a single tag came from into scylla
+///
+/// Each source and tool keeps its own provenance so a tool setting a tag again will never
+/// overwrite an analyst confirming it.
+///
+/// # Arguments
+///
+/// * `kind` - The type of tag we are saving provenance for
+/// * `item` - The item this tag is on
+/// * `tag_key` - The key for this tag
+/// * `tag_value` - The value for this tag
+/// * `origin` - Where this tag came from in its group
+/// * `shared` - Shared Thorium objects
+async fn save_provenance(
+    kind: TagType,
+    item: &str,
+    tag_key: &str,
+    tag_value: &str,
+    origin: &TagProvenance,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    shared
+        .scylla
+        .session
+        .execute_unpaged(
+            &shared.scylla.prep.tags.insert_provenance,
+            (
+                kind,
+                item,
+                &origin.group,
+                tag_key,
+                tag_value,
+                origin.source,
+                &origin.user,
+                origin.tool.as_deref().unwrap_or_default(),
+                &origin.tool_version,
+                origin.result,
+                origin.confidence,
+                origin.timestamp,
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
 /// Get the full tag rows for some specific tags
 ///
 /// # Arguments
@@ -445,6 +509,15 @@ pub async fn delete<T: TagSupport>(
                                 // mark that we deleted at least one tag in this group
                                 groups_deleted.insert(group);
                             }
+                            // delete where this tag came from
+                            shared
+                                .scylla
+                                .session
+                                .execute_unpaged(
+                                    &shared.scylla.prep.tags.delete_provenance,
+                                    (kind, key, group, tag_key, value),
+                                )
+                                .await?;
                         }
                     }
                 }
@@ -544,3 +617,207 @@ pub async fn get(
     }
     Ok(())
 }
+
+/// Gets where the tags for a specific item came from
+///
+/// # Arguments
+///
+/// * `tag_type` - The type of tags to get provenance for
+/// * `groups` - The groups to restrict our returned tag provenance too
+/// * `item` - The item to get tag provenance for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::get_provenance", skip(shared), err(Debug))]
+pub async fn get_provenance(
+    tag_type: TagType,
+    groups: &[String],
+    item: &str,
+    shared: &Shared,
+) -> Result<Vec<TagProvenance>, ApiError> {
+    let mut provenance = Vec::with_capacity(30);
+    // break our groups into chunks of 100
+    for chunk in groups.chunks(100) {
+        // get the provenance for this chunk of groups
+        let query = shared
+            .scylla
+            .session
+            .execute_unpaged(
+                &shared.scylla.prep.tags.get_provenance,
+                (tag_type, item, chunk),
+            )
+            .await?;
+        // enable casting to types for this query
+        let query_rows = query.into_rows_result()?;
+        // cast our rows while logging any errors
+        provenance.extend(
+            query_rows
+                .rows::<TagProvenanceRow>()?
+                .filter_map(|row| log_scylla_err!(row))
+                .map(TagProvenance::from),
+        );
+    }
+    Ok(provenance)
+}
+
+/// How long to keep finished bulk tag jobs around for in seconds (1 week)
+const BULK_JOB_RETENTION: i64 = 604_800;
+
+/// Lists the values a tag key has had in a group based on its census info
+///
+/// Values whose tags have all been deleted may still be listed.
+///
+/// # Arguments
+///
+/// * `kind` - The type of tags to list values for
+/// * `group` - The group to list values in
+/// * `key` - The tag key to list values for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::list_values", skip(shared), err(Debug))]
+pub async fn list_values(
+    kind: TagType,
+    group: &str,
+    key: &str,
+    shared: &Shared,
+) -> Result<HashSet<String>, ApiError> {
+    // get the set of values this key has had in this group
+    let values_key = tags::census_values(kind, &group, key, shared);
+    let values: HashSet<String> = query!(cmd("smembers").arg(&values_key), shared).await?;
+    Ok(values)
+}
+
+/// Saves a bulk tag job into redis
+///
+/// # Arguments
+///
+/// * `job` - The bulk tag job to save
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::tags::save_bulk_job", skip_all, fields(id = job.id.to_string()), err(Debug))]
+pub async fn save_bulk_job(job: &BulkTagJob, shared: &Shared) -> Result<(), ApiError> {
+    // build the keys to this jobs data
+    let data = tags::bulk_job(&job.id, shared);
+    let jobs = tags::bulk_jobs(shared);
+    // save this job and track it
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .cmd("hset").arg(&data).arg("job").arg(serialize!(job))
+        .cmd("hset").arg(&data).arg("heartbeat").arg(Utc::now().timestamp())
+        .cmd("zadd").arg(&jobs).arg(job.created.timestamp()).arg(job.id.to_string());
+    // clean up finished jobs after a while
+    if job.is_finished() {
+        pipe.cmd("expire").arg(&data).arg(BULK_JOB_RETENTION);
+    }
+    let _: () = pipe.query_async(conn!(shared)).await?;
+    Ok(())
+}
+
+/// Gets a bulk tag job from redis if it exists
+///
+/// # Arguments
+///
+/// * `id` - The id of the bulk tag job to get
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::get_bulk_job", skip(shared), err(Debug))]
+pub async fn get_bulk_job(id: &Uuid, shared: &Shared) -> Result<Option<BulkTagJob>, ApiError> {
+    // build the key to this jobs data
+    let data = tags::bulk_job(id, shared);
+    // get this jobs data and heartbeat if it exists
+    let (raw, heartbeat): (Option<String>, Option<i64>) =
+        query!(cmd("hmget").arg(&data).arg("job").arg("heartbeat"), shared).await?;
+    match raw {
+        Some(raw) => Ok(Some(cast_bulk_job(&raw, heartbeat)?)),
+        None => Ok(None),
+    }
+}
+
+/// Cast a bulk tag job from redis and add its last heartbeat
+///
+/// # Arguments
+///
+/// * `raw` - The serialized bulk tag job
+/// * `heartbeat` - The last time this jobs runner checked in as a unix timestamp
+fn cast_bulk_job(raw: &str, heartbeat: Option<i64>) -> Result<BulkTagJob, ApiError> {
+    let mut job: BulkTagJob = deserialize!(raw);
+    job.heartbeat = heartbeat.and_then(|secs| DateTime::from_timestamp(secs, 0));
+    Ok(job)
+}
+
+/// Lists all bulk tag jobs from newest to oldest
+///
+/// # Arguments
+///
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::list_bulk_jobs", skip_all, err(Debug))]
+pub async fn list_bulk_jobs(shared: &Shared) -> Result<Vec<BulkTagJob>, ApiError> {
+    // get the ids of all tracked jobs
+    let jobs_key = tags::bulk_jobs(shared);
+    let ids: Vec<String> = query!(cmd("zrevrange").arg(&jobs_key).arg(0).arg(-1), shared).await?;
+    if ids.is_empty() {
+        return Ok(Vec::default());
+    }
+    // get the data for all of these jobs
+    let mut pipe = redis::pipe();
+    for id in &ids {
+        pipe.cmd("hmget")
+            .arg(tags::bulk_job(id, shared))
+            .arg("job")
+            .arg("heartbeat");
+    }
+    let raw: Vec<(Option<String>, Option<i64>)> = pipe.query_async(conn!(shared)).await?;
+    // cast our jobs and keep track of any that have expired
+    let mut jobs = Vec::with_capacity(raw.len());
+    let mut expired = Vec::default();
+    for (id, (raw, heartbeat)) in ids.iter().zip(raw) {
+        match raw {
+            Some(raw) => jobs.push(cast_bulk_job(&raw, heartbeat)?),
+            None => expired.push(id),
+        }
+    }
+    // stop tracking any expired jobs
+    if !expired.is_empty() {
+        let _: () = query!(cmd("zrem").arg(&jobs_key).arg(expired), shared).await?;
+    }
+    Ok(jobs)
+}
+
+/// Updates the heartbeat for a running bulk tag job
+///
+/// # Arguments
+///
+/// * `id` - The id of the bulk tag job that is still running
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::bulk_job_heartbeat", skip(shared), err(Debug))]
+pub async fn bulk_job_heartbeat(id: &Uuid, shared: &Shared) -> Result<(), ApiError> {
+    // build the key to this jobs data
+    let data = tags::bulk_job(id, shared);
+    let now = Utc::now().timestamp();
+    let _: () = query!(cmd("hset").arg(&data).arg("heartbeat").arg(now), shared).await?;
+    Ok(())
+}
+
+/// Flags a bulk tag job to be cancelled
+///
+/// # Arguments
+///
+/// * `id` - The id of the bulk tag job to cancel
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::cancel_bulk_job", skip(shared), err(Debug))]
+pub async fn cancel_bulk_job(id: &Uuid, shared: &Shared) -> Result<(), ApiError> {
+    // build the key to this jobs data
+    let data = tags::bulk_job(id, shared);
+    let _: () = query!(cmd("hset").arg(&data).arg("cancelled").arg(1), shared).await?;
+    Ok(())
+}
+
+/// Checks if a bulk tag job has been flagged to be cancelled
+///
+/// # Arguments
+///
+/// * `id` - The id of the bulk tag job to check
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::tags::bulk_job_cancelled", skip(shared), err(Debug))]
+pub async fn bulk_job_cancelled(id: &Uuid, shared: &Shared) -> Result<bool, ApiError> {
+    // build the key to this jobs data
+    let data = tags::bulk_job(id, shared);
+    let cancelled: bool = query!(cmd("hexists").arg(&data).arg("cancelled"), shared).await?;
+    Ok(cancelled)
+}
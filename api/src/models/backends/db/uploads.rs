@@ -0,0 +1,252 @@
+//! Saves and retrieves resumable upload sessions from Redis
+
+use bb8_redis::redis::cmd;
+use chrono::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::keys::UploadKeys;
+use crate::models::{UploadSession, UploadedChunk};
+use crate::utils::{ApiError, Shared};
+use crate::{conn, deserialize, deserialize_ext, extract, not_found, query, serialize};
+
+/// Saves a new upload session into Redis
+///
+/// # Arguments
+///
+/// * `session` - The upload session to save
+/// * `s3_upload_id` - The id of the S3 multipart upload backing this session
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::uploads::create", skip(session, shared), fields(id = session.id.to_string()), err(Debug))]
+pub async fn create(
+    session: &UploadSession,
+    s3_upload_id: &str,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build the keys to this upload sessions data
+    let data = UploadKeys::data(&session.id, shared);
+    let user = UploadKeys::user(&session.creator, shared);
+    let expire = UploadKeys::expire(shared);
+    // save this upload session and track when it should expire
+    let _: () = redis::pipe().atomic()
+        .cmd("hset").arg(&data).arg("session").arg(serialize!(session))
+        .cmd("hset").arg(&data).arg("s3_upload_id").arg(s3_upload_id)
+        .cmd("hset").arg(&data).arg("expires").arg(session.expires.timestamp())
+        .cmd("hset").arg(&user).arg(&session.sha256).arg(session.id.to_string())
+        .cmd("zadd").arg(&expire).arg(session.expires.timestamp()).arg(session.id.to_string())
+        .query_async(conn!(shared)).await?;
+    Ok(())
+}
+
+/// Gets an upload session from Redis
+///
+/// # Arguments
+///
+/// * `id` - The id of the upload session to get
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::uploads::get", skip(shared), err(Debug))]
+pub async fn get(id: &Uuid, shared: &Shared) -> Result<UploadSession, ApiError> {
+    // build the keys to this upload sessions data
+    let data = UploadKeys::data(id, shared);
+    let chunks = UploadKeys::chunks(id, shared);
+    // get this sessions data and the chunks that have been uploaded
+    let (mut raw, uploaded): (HashMap<String, String>, Vec<u64>) = redis::pipe()
+        .cmd("hgetall").arg(&data)
+        .cmd("hkeys").arg(&chunks)
+        .query_async(conn!(shared)).await?;
+    // return 404 if this session doesn't exist
+    if raw.is_empty() {
+        return not_found!(format!("Upload session {id} not found"));
+    }
+    // cast our upload session
+    let mut session: UploadSession = deserialize_ext!(raw, "session");
+    // get when this session will expire
+    let expires = extract!(raw, "expires").parse::<i64>()?;
+    session.expires = DateTime::from_timestamp(expires, 0).unwrap_or(session.expires);
+    // add the chunks that have already been uploaded
+    session.uploaded = uploaded.into_iter().collect();
+    Ok(session)
+}
+
+/// Gets the id of the S3 multipart upload backing an upload session
+///
+/// # Arguments
+///
+/// * `id` - The id of the upload session
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::uploads::s3_upload_id", skip(shared), err(Debug))]
+pub async fn s3_upload_id(id: &Uuid, shared: &Shared) -> Result<String, ApiError> {
+    // build the key to this upload sessions data
+    let data = UploadKeys::data(id, shared);
+    // get the id of our multipart upload
+    let upload_id: Option<String> =
+        query!(cmd("hget").arg(&data).arg("s3_upload_id"), shared).await?;
+    match upload_id {
+        Some(upload_id) => Ok(upload_id),
+        None => not_found!(format!("Upload session {id} not found")),
+    }
+}
+
+/// Gets the details on all chunks that have been uploaded for an upload session
+///
+/// # Arguments
+///
+/// * `id` - The id of the upload session
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::uploads::chunks", skip(shared), err(Debug))]
+pub async fn chunks(id: &Uuid, shared: &Shared) -> Result<BTreeMap<u64, UploadedChunk>, ApiError> {
+    // build the key to this upload sessions chunks
+    let key = UploadKeys::chunks(id, shared);
+    // get all of the chunks that have been uploaded
+    let raw: HashMap<u64, String> = query!(cmd("hgetall").arg(&key), shared).await?;
+    // deserialize each of our chunks
+    let mut chunks = BTreeMap::default();
+    for (chunk, raw_chunk) in raw {
+        chunks.insert(chunk, deserialize!(&raw_chunk));
+    }
+    Ok(chunks)
+}
+
+/// Saves an uploaded chunk and pushes back when an upload session expires
+///
+/// # Arguments
+///
+/// * `session` - The upload session this chunk is for
+/// * `chunk` - The chunk that was uploaded
+/// * `uploaded` - The info on the chunk that was uploaded
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::uploads::save_chunk", skip(session, uploaded, shared), fields(id = session.id.to_string()), err(Debug))]
+pub async fn save_chunk(
+    session: &UploadSession,
+    chunk: u64,
+    uploaded: &UploadedChunk,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build the keys to this upload sessions data
+    let data = UploadKeys::data(&session.id, shared);
+    let chunks = UploadKeys::chunks(&session.id, shared);
+    let expire = UploadKeys::expire(shared);
+    // save this chunk and push back when this session expires
+    let _: () = redis::pipe().atomic()
+        .cmd("hset").arg(&chunks).arg(chunk).arg(serialize!(uploaded))
+        .cmd("hset").arg(&data).arg("expires").arg(session.expires.timestamp())
+        .cmd("zadd").arg(&expire).arg(session.expires.timestamp()).arg(session.id.to_string())
+        .query_async(conn!(shared)).await?;
+    Ok(())
+}
+
+/// Finds an existing upload session for a specific sha256 by a user
+///
+/// # Arguments
+///
+/// * `username` - The user whose upload sessions to search
+/// * `sha256` - The sha256 to look for an upload session for
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::uploads::find", skip(shared), err(Debug))]
+pub async fn find(username: &str, sha256: &str, shared: &Shared) -> Result<Option<Uuid>, ApiError> {
+    // build the key to this users upload sessions
+    let key = UploadKeys::user(username, shared);
+    // check if this user has an upload session for this sha256
+    let id: Option<String> = query!(cmd("hget").arg(&key).arg(sha256), shared).await?;
+    // cast our id if we found one
+    match id {
+        Some(id) => Ok(Some(Uuid::parse_str(&id)?)),
+        None => Ok(None),
+    }
+}
+
+/// Lists the ids of all upload sessions for a user
+///
+/// # Arguments
+///
+/// * `username` - The user whose upload sessions to list
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::uploads::list", skip(shared), err(Debug))]
+pub async fn list(username: &str, shared: &Shared) -> Result<Vec<Uuid>, ApiError> {
+    // build the key to this users upload sessions
+    let key = UploadKeys::user(username, shared);
+    // get the ids of all of this users upload sessions
+    let raw: Vec<String> = query!(cmd("hvals").arg(&key), shared).await?;
+    // cast our ids
+    let ids = raw
+        .iter()
+        .map(|id| Uuid::parse_str(id))
+        .collect::<Result<Vec<Uuid>, uuid::Error>>()?;
+    Ok(ids)
+}
+
+/// Lists the ids of upload sessions that have expired
+///
+/// # Arguments
+///
+/// * `now` - The timestamp to list expired sessions up to
+/// * `limit` - The max number of expired sessions to list
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::uploads::expired", skip(shared), err(Debug))]
+pub async fn expired(now: i64, limit: u64, shared: &Shared) -> Result<Vec<Uuid>, ApiError> {
+    // build the key to our expiration set
+    let key = UploadKeys::expire(shared);
+    // get the ids of any upload sessions that have expired
+    let raw: Vec<String> = query!(
+        cmd("zrangebyscore").arg(&key).arg(0).arg(now).arg("LIMIT").arg(0).arg(limit),
+        shared).await?;
+    // cast our ids
+    let ids = raw
+        .iter()
+        .map(|id| Uuid::parse_str(id))
+        .collect::<Result<Vec<Uuid>, uuid::Error>>()?;
+    Ok(ids)
+}
+
+/// Deletes an upload session from Redis
+///
+/// # Arguments
+///
+/// * `id` - The id of the upload session to delete
+/// * `creator` - The user that created this upload session
+/// * `sha256` - The sha256 this upload session was for
+/// * `shared` - Shared Thorium objects
+#[rustfmt::skip]
+#[instrument(name = "db::uploads::delete", skip(shared), err(Debug))]
+pub async fn delete(
+    id: &Uuid,
+    creator: &str,
+    sha256: &str,
+    shared: &Shared,
+) -> Result<(), ApiError> {
+    // build the keys to this upload sessions data
+    let data = UploadKeys::data(id, shared);
+    let chunks = UploadKeys::chunks(id, shared);
+    let user = UploadKeys::user(creator, shared);
+    let expire = UploadKeys::expire(shared);
+    // delete this upload sessions data
+    let _: () = redis::pipe().atomic()
+        .cmd("del").arg(&data)
+        .cmd("del").arg(&chunks)
+        .cmd("hdel").arg(&user).arg(sha256)
+        .cmd("zrem").arg(&expire).arg(id.to_string())
+        .query_async(conn!(shared)).await?;
+    Ok(())
+}
+
+/// Removes an upload session from our expiration set without touching its data
+///
+/// This is used when an upload sessions data is already gone.
+///
+/// # Arguments
+///
+/// * `id` - The id of the upload session to stop tracking
+/// * `shared` - Shared Thorium objects
+#[instrument(name = "db::uploads::untrack", skip(shared), err(Debug))]
+pub async fn untrack(id: &Uuid, shared: &Shared) -> Result<(), ApiError> {
+    // build the key to our expiration set
+    let key = UploadKeys::expire(shared);
+    // stop tracking this session
+    let _: () = query!(cmd("zrem").arg(&key).arg(id.to_string()), shared).await?;
+    Ok(())
+}
@@ -492,6 +492,7 @@ pub fn build_delete(
     pipe.cmd("srem").arg(&keys.global).arg(&user.username)
         .cmd("del").arg(&keys.data)
         .cmd("del").arg(&keys.groups)
+        .cmd("del").arg(UserKeys::saved_searches(&user.username, shared))
         .cmd("hdel").arg(&keys.tokens).arg(&user.token);
     // if this users role is analyst then add them to the analyst set
     if user.role == UserRole::Analyst {
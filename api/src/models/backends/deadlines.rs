@@ -105,6 +105,7 @@ impl TryFrom<StreamObj> for Deadline {
             job_id: frag.job_id,
             reaction: frag.reaction,
             deadline: datetime,
+            priority: frag.priority,
         };
         Ok(deadline)
     }
@@ -2,12 +2,13 @@
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use chrono::prelude::*;
+use std::collections::{HashMap, HashSet};
 use tracing::instrument;
 use uuid::Uuid;
 
 use super::db::{self};
 use crate::models::backends::TagSupport;
-use crate::models::{EventCacheStatus, EventPopOpts};
+use crate::models::{CommitishMapRequest, CommitishRequest, EventCacheStatus, EventPopOpts};
 use crate::{
     is_admin,
     models::{Event, EventData, EventRow, EventType, TagRequest},
@@ -97,6 +98,94 @@ impl Event {
         }
     }
 
+    /// Get the commitishes in a request that are new to a repo
+    ///
+    /// Commits that were already stored are skipped so re-ingesting a repo doesn't trigger
+    /// pipelines on its history again. Branches are only kept if they are new or moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The commitishes being added
+    /// * `stored_commits` - The commits in this request that were already stored
+    /// * `branch_heads` - The commits the branches in this request already pointed to
+    #[must_use]
+    pub fn new_commitishes(
+        req: &CommitishMapRequest,
+        stored_commits: &HashSet<String>,
+        branch_heads: &HashMap<String, HashSet<String>>,
+    ) -> HashMap<String, CommitishRequest> {
+        req.commitishes
+            .iter()
+            .filter(|(key, commitish)| match commitish {
+                CommitishRequest::Commit(_) => !stored_commits.contains(*key),
+                CommitishRequest::Branch(branch) => !branch_heads
+                    .get(*key)
+                    .is_some_and(|heads| heads.contains(&branch.commit)),
+                CommitishRequest::Tag(_) => false,
+            })
+            .map(|(key, commitish)| (key.clone(), commitish.clone()))
+            .collect()
+    }
+
+    /// Create a new commitish event if any new commits or branches were added
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is adding new commitishes
+    /// * `repo` - The url of the repo these commitishes are being added to
+    /// * `req` - The last request of commitishes being added
+    /// * `new` - The new commits and branches from every request in this upload
+    #[must_use]
+    pub fn new_commitish(
+        user: &User,
+        repo: &str,
+        req: &CommitishMapRequest,
+        new: &HashMap<String, CommitishRequest>,
+    ) -> Option<Self> {
+        // get the new commits and branches and the parents and changed paths for each commit
+        let mut commits = Vec::with_capacity(new.len());
+        let mut branches = HashMap::default();
+        let mut parents = HashMap::default();
+        let mut paths = HashMap::default();
+        for (key, commitish) in new {
+            match commitish {
+                CommitishRequest::Commit(commit) => {
+                    commits.push((commit.timestamp, key.clone()));
+                    parents.insert(key.clone(), commit.parents.clone());
+                    paths.insert(key.clone(), commit.paths.clone());
+                }
+                CommitishRequest::Branch(branch) => {
+                    branches.insert(key.clone(), branch.commit.clone());
+                }
+                CommitishRequest::Tag(_) => (),
+            }
+        }
+        // skip requests that don't add any new commits or branches
+        if commits.is_empty() && branches.is_empty() {
+            return None;
+        }
+        // order our commits from newest to oldest
+        commits.sort_unstable_by(|a, b| b.cmp(a));
+        // build our event data
+        let data = EventData::NewCommitish {
+            repo: repo.to_owned(),
+            groups: req.groups.clone(),
+            commits: commits.into_iter().map(|(_, hash)| hash).collect(),
+            branches,
+            parents,
+            paths,
+        };
+        // build our commitish event
+        Some(Event {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            parent: None,
+            user: user.username.clone(),
+            data,
+            depth: req.trigger_depth,
+        })
+    }
+
     /// Pop some events from a specific queue
     #[instrument(name = "Event::pop", skip(user, shared), err(Debug))]
     pub async fn pop(
@@ -9,7 +9,7 @@ use futures_util::stream::{self, StreamExt};
 use futures_util::{Future, TryStreamExt};
 use scylla::errors::ExecutionError;
 use scylla::response::query_result::QueryResult;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
 use tracing::instrument;
 use uuid::Uuid;
@@ -17,14 +17,14 @@ use uuid::Uuid;
 use super::db::{self, CursorCore, ScyllaCursorSupport};
 use super::CommentSupport;
 use crate::models::{
-    ApiCursor, CarvedOrigin, CarvedOriginTypes, Comment, CommentForm, CommentResponse, CommentRow,
+    ApiCursor, CarvedOrigin, CarvedOriginTypes, Comment, CommentForm, CommentReaction, CommentRow,
     DeleteCommentParams, DeleteSampleParams, FileListParams, Group, GroupAllowAction, Origin,
     OriginForm, OriginRequest, OriginTypes, S3Objects, Sample, SampleCheck, SampleCheckResponse,
     SampleForm, SampleListLine, SampleSubmissionResponse, Submission, SubmissionChunk,
-    SubmissionListRow, SubmissionRow, SubmissionUpdate, TagListRow, TagType, User,
-    ZipDownloadParams,
+    SubmissionListRow, SubmissionRow, SubmissionUpdate, SubscriptionEvent, SubscriptionTarget,
+    TagListRow, TagType, User, ZipDownloadParams,
 };
-use crate::utils::{ApiError, Shared};
+use crate::utils::{ApiError, Shared, StandardHashes};
 use crate::{
     bad, can_create_all, can_modify, deserialize, disjoint, for_groups, not_found, serialize,
     unauthorized, update_opt,
@@ -116,6 +116,15 @@ impl SampleForm {
                 "origin[dest_port]" => self.origin.dest_port = Some(field.text().await?.parse()?),
                 "origin[proto]" => self.origin.proto = Some(field.text().await?.parse()?),
                 "trigger_depth" => self.trigger_depth = field.text().await?.parse()?,
+                "provenance[job]" => {
+                    self.provenance.job = Some(Uuid::from_str(&field.text().await?)?);
+                }
+                "provenance[result]" => {
+                    self.provenance.result = Some(Uuid::from_str(&field.text().await?)?);
+                }
+                "provenance[confidence]" => {
+                    self.provenance.confidence = Some(field.text().await?.parse()?);
+                }
                 // this is the data so return it so we can stream it to s3
                 "data" => return Ok(Some(field)),
                 _ => {
@@ -150,6 +159,8 @@ impl CommentForm {
         if let Some(name) = field.name() {
             match name {
                 "comment" => self.comment = field.text().await?,
+                "groups" => self.groups.push(field.text().await?),
+                "parent" => self.parent = Some(Uuid::from_str(&field.text().await?)?),
                 // this is an attachment  so return it so we can stream it to s3
                 "files" => return Ok(Some(field)),
                 _ => return bad!(format!("{} is not a valid form name", name)),
@@ -211,6 +222,33 @@ impl Sample {
         let Some(hashes) = hashes_opt else {
             return bad!(format!("Data entry must be set!"));
         };
+        // set our file name if one was found
+        form.file_name = file_opt;
+        // save this samples metadata
+        Self::create_from_form(user, s3_id, form, hashes, shared).await
+    }
+
+    /// Saves the metadata for a sample whose data has already been streamed into s3
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The User trying to save this sample
+    /// * `s3_id` - The s3 id this samples data was saved at
+    /// * `form` - The metadata for this sample
+    /// * `hashes` - The hashes of this samples data
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(
+        name = "Sample::create_from_form",
+        skip(user, form, hashes, shared),
+        err(Debug)
+    )]
+    pub(crate) async fn create_from_form(
+        user: &User,
+        s3_id: &Uuid,
+        mut form: SampleForm,
+        hashes: StandardHashes,
+        shared: &Shared,
+    ) -> Result<SampleSubmissionResponse, ApiError> {
         // make sure we actually have groups
         if form.groups.is_empty() {
             return bad!(format!(
@@ -223,12 +261,20 @@ impl Sample {
                 .await?;
         // make sure we have the roles to upload samples in all of these groups
         can_create_all!(groups, user, shared);
-        // set our file name if one was found
-        form.file_name = file_opt;
+        // rewrite our tags to their canonical forms and make sure our groups allow them
+        Group::apply_tag_vocabularies(&groups, &mut form.tags, true)?;
         // determine if this file already exists in s3
         let exists = db::s3::object_exists(S3Objects::File, &hashes.sha256, shared).await?;
+        // keep the parent and groups for this sample so we can notify the parents followers
+        let parent = form.origin.parent.clone();
+        let child_groups = form.groups.clone();
+        // determine where this samples tags came from
+        let origins = form
+            .provenance
+            .resolve(user, TagType::Files, &hashes.sha256, &form.groups, shared)
+            .await?;
         // add this samples metadata to scylla
-        match db::files::create(user, form, hashes, shared).await {
+        match db::files::create(user, form, &origins, hashes, shared).await {
             Ok(resp) => {
                 // add our new object if it doesn't already exist
                 if !exists {
@@ -237,6 +283,20 @@ impl Sample {
                 } else {
                     shared.s3.files.delete(&s3_id.to_string()).await?;
                 }
+                // let anyone following this samples parent know about its new child
+                if let Some(parent) = parent {
+                    let target = SubscriptionTarget::sample(parent);
+                    let msg = format!("New child {} of {}", resp.sha256, target);
+                    db::subscriptions::notify(
+                        &target,
+                        SubscriptionEvent::Children,
+                        &child_groups,
+                        Some(&user.username),
+                        msg,
+                        shared,
+                    )
+                    .await;
+                }
                 Ok(resp)
             }
             Err(err) => Err(err),
@@ -727,118 +787,19 @@ impl Sample {
 }
 
 impl CommentSupport for Sample {
-    /// Creates a new comment
-    ///
-    /// # Arguments
-    ///
-    /// * `user` - The user that is adding a comment
-    /// * `req` - The multipart form containing the new comment
-    /// * `shared` - Shared Thorium objects
-    #[allow(async_fn_in_trait)]
-    async fn create_comment(
-        &self,
-        user: &User,
-        req: Multipart,
-        shared: &Shared,
-    ) -> Result<CommentResponse, ApiError> {
-        // build our comment form
-        let mut form = CommentForm::default();
-        // get the groups we can see this sample is in
-        let groups = self.groups();
-        // try to save this comment to the backend
-        match super::comments::create_comment_helper(
-            user,
-            &self.sha256,
-            &groups,
-            req,
-            &mut form,
-            shared,
-        )
-        .await
-        {
-            Ok(()) => Ok(CommentResponse { id: form.id }),
-            Err(err) => {
-                // delete all our dangling comment attachments
-                for (_, s3_id) in form.attachments {
-                    // build the path to delete this attachment at in s3
-                    let s3_path = format!("{}/{}/{}", &self.sha256, form.id, s3_id);
-                    // delete this attachment from s3
-                    shared.s3.attachments.delete(&s3_path).await?;
-                }
-                Err(err)
-            }
-        }
+    /// Get the key that comments on this sample are saved under
+    fn comment_key(&self) -> String {
+        self.sha256.clone()
     }
 
-    /// Deletes a comment
-    ///
-    /// # Arguments
-    ///
-    /// * `user` - The user that is deleting the comment
-    /// * `groups` - The groups to delete the comment from
-    /// * `id` - The id of the comment to delete
-    /// * `shared` - Shared Thorium objects
-    async fn delete_comment(
-        &self,
-        user: &User,
-        groups: &[String],
-        id: &Uuid,
-        shared: &Shared,
-    ) -> Result<(), ApiError> {
-        // get the comment at the given id
-        let Some(comment) = self.comments.iter().find(|comment| &comment.id == id) else {
-            return not_found!("Error deleting comment: comment not found".to_string());
-        };
-        // return an unauthorized error if the user is not the comment author or isn't an admin
-        if user.username != comment.author && !user.is_admin() {
-            return unauthorized!(
-                "Error deleting comment: comments can only be deleted by their authors".to_string()
-            );
-        }
-        // delete from all comment groups of none were given,
-        // otherwise check the comment is in all of the given groups
-        let groups = if groups.is_empty() {
-            &comment.groups
-        } else {
-            if !groups.iter().all(|group| comment.groups.contains(group)) {
-                return bad!(
-                    "Error deleting comment: comment is not in all of the given groups".to_string()
-                );
-            }
-            groups
-        };
-        db::files::delete_comment(&self.sha256, groups, comment, shared).await?;
-        db::files::prune_comment_attachments(&[comment.to_owned()], &self.sha256, shared).await
+    /// Get the groups this sample can be seen in
+    fn comment_groups(&self) -> HashSet<&str> {
+        self.groups()
     }
 
-    /// Downloads an attachment from a specific comment
-    ///
-    /// # Arguments
-    ///
-    /// * `comment` - The id of the comment to download
-    /// * `attachment` - The id of the attachment to download
-    /// * `shared` - Shared Thorium objects
-    #[allow(async_fn_in_trait)]
-    async fn download_attachment(
-        &self,
-        comment: &Uuid,
-        attachment: &Uuid,
-        shared: &Shared,
-    ) -> Result<ByteStream, ApiError> {
-        // make sure this is a valid comment for this sample
-        if let Some(comment) = self.comments.iter().find(|com| &com.id == comment) {
-            // make sure this attachment is from this comment
-            if comment.attachments.iter().any(|(_, id)| attachment == id) {
-                // build the path to this atachment
-                let path = format!("{}/{}/{}", self.sha256, comment.id, attachment);
-                // download and return this attachment
-                return shared.s3.attachments.download(&path).await;
-            }
-        }
-        not_found!(format!(
-            "Attachment {} for comment {} not found",
-            attachment, comment
-        ))
+    /// Get the comments on this sample
+    fn comments(&self) -> &[Comment] {
+        &self.comments
     }
 }
 
@@ -1020,6 +981,50 @@ impl OriginForm {
     }
 }
 
+impl TryFrom<OriginRequest> for OriginForm {
+    type Error = ApiError;
+    /// converts a [`OriginRequest`] into an [`OriginForm`]
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The origin request
+    fn try_from(req: OriginRequest) -> Result<Self, Self::Error> {
+        let form = OriginForm {
+            origin_type: OriginTypes::from_str(&req.origin_type)?,
+            result_ids: req.result_ids,
+            url: req.url,
+            name: req.name,
+            tool: req.tool,
+            parent: req.parent,
+            flags: req.flags,
+            cmd: req.cmd,
+            sniffer: req.sniffer,
+            source: req.source,
+            destination: req.destination,
+            incident: req.incident,
+            cover_term: req.cover_term,
+            mission_team: req.mission_team,
+            network: req.network,
+            machine: req.machine,
+            location: req.location,
+            memory_type: req.memory_type,
+            reconstructed: req.reconstructed,
+            base_addr: req.base_addr,
+            repo: req.repo,
+            commitish: req.commitish,
+            commit: req.commit,
+            system: req.system,
+            supporting: req.supporting,
+            src_ip: req.src_ip,
+            dest_ip: req.dest_ip,
+            src_port: req.src_port,
+            dest_port: req.dest_port,
+            proto: req.proto,
+        };
+        Ok(form)
+    }
+}
+
 impl TryFrom<OriginRequest> for Origin {
     type Error = ApiError;
     /// converts a [`OriginRequest`] into an [`Origin`]
@@ -1188,14 +1193,29 @@ impl TryFrom<CommentRow> for Comment {
     ///
     /// * `row` - The comment to convert
     fn try_from(row: CommentRow) -> Result<Self, Self::Error> {
-        let comment = Comment {
+        let mut comment = Comment {
             groups: vec![row.group],
             id: row.id,
             author: row.author,
             uploaded: row.uploaded,
             comment: row.comment,
             attachments: deserialize!(&row.files),
+            parent: row.parent,
+            edited: row.edited,
+            revisions: match &row.revisions {
+                Some(revisions) => deserialize!(revisions),
+                None => Vec::default(),
+            },
+            reactions: BTreeMap::default(),
         };
+        // group the users that left each reaction
+        for (reaction, username) in row.reactions.unwrap_or_default() {
+            comment
+                .reactions
+                .entry(CommentReaction::from_str(&reaction)?)
+                .or_default()
+                .insert(username);
+        }
         Ok(comment)
     }
 }
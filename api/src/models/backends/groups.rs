@@ -2,6 +2,7 @@
 //! Currently only Redis is supported
 
 use ldap3::{Scope, SearchEntry};
+use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use tracing::{event, instrument, Level};
 
@@ -11,7 +12,7 @@ use crate::models::groups::GroupUsers;
 use crate::models::{
     Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList, GroupList,
     GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsersUpdate, ImageScaler,
-    Pipeline, User,
+    Pipeline, TagVocabulary, User,
 };
 use crate::utils::{bounder, ApiError, Shared};
 use crate::{
@@ -37,6 +38,8 @@ impl GroupRequest {
         User::exists_many(&self.monitors.direct, shared).await?;
         // get a list of analysts in Thorium
         let analysts = db::users::get_analysts(shared).await?;
+        // make sure our tag vocabulary is valid
+        self.tag_vocabulary.validate()?;
         // inject creator as an owner if he is not already in there
         if !self.owners.direct.contains(&user.username) {
             self.owners.direct.insert(user.username.clone());
@@ -56,6 +59,7 @@ impl GroupRequest {
             monitors,
             description: self.description,
             allowed: self.allowed,
+            tag_vocabulary: self.tag_vocabulary,
         };
         // fix this groups roles if its needed
         cast.fix();
@@ -148,6 +152,94 @@ impl GroupAllowedUpdate {
     }
 }
 
+impl TagVocabulary {
+    /// Make sure this tag vocabulary is valid
+    pub fn validate(&self) -> Result<(), ApiError> {
+        // make sure the regexes for all of our keys compile
+        self.compile_regexes()?;
+        for (key, rules) in &self.keys {
+            // make sure value aliases point to allowed values
+            if !rules.values.is_empty() {
+                if let Some((alias, canonical)) = rules
+                    .aliases
+                    .iter()
+                    .find(|(_, canonical)| !rules.values.contains(*canonical))
+                {
+                    return bad!(format!(
+                        "Alias {alias} for tag key {key} points to {canonical} which is not an allowed value"
+                    ));
+                }
+            }
+        }
+        // make sure no key aliases point to other aliases
+        if let Some((alias, canonical)) = self
+            .aliases
+            .iter()
+            .find(|(_, canonical)| self.aliases.contains_key(*canonical))
+        {
+            return bad!(format!(
+                "Alias {alias} points to {canonical} which is also an alias"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compile the regexes for each key in this vocabulary
+    ///
+    /// Each regex is anchored so the entire value must match.
+    pub fn compile_regexes(&self) -> Result<HashMap<&str, Regex>, ApiError> {
+        let mut regexes = HashMap::with_capacity(self.keys.len());
+        for (key, rules) in &self.keys {
+            if let Some(regex) = &rules.regex {
+                // anchor our regex so the entire value must match
+                match Regex::new(&format!("^(?:{regex})$")) {
+                    Ok(regex) => regexes.insert(key.as_str(), regex),
+                    Err(err) => return bad!(format!("Invalid regex for tag key {key}: {err}")),
+                };
+            }
+        }
+        Ok(regexes)
+    }
+
+    /// Make sure a canonical tag is allowed by this vocabulary
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group this vocabulary is for
+    /// * `regexes` - The compiled regexes for this vocabulary
+    /// * `key` - The tag key to check
+    /// * `value` - The tag value to check
+    pub fn check(
+        &self,
+        group: &str,
+        regexes: &HashMap<&str, Regex>,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ApiError> {
+        match self.keys.get(key) {
+            Some(rules) => {
+                // make sure this is an allowed value
+                if !rules.values.is_empty() && !rules.values.contains(value) {
+                    return bad!(format!(
+                        "{value} is not an allowed value for tag {key} in group {group}"
+                    ));
+                }
+                // make sure this value matches our regex
+                if regexes.get(key).is_some_and(|regex| !regex.is_match(value)) {
+                    return bad!(format!(
+                        "{value} does not match the format for tag {key} in group {group}"
+                    ));
+                }
+                Ok(())
+            }
+            None if self.strict => {
+                bad!(format!("{key} is not an allowed tag key in group {group}"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
 impl GroupList {
     /// Creates a new group list object
     ///
@@ -200,6 +292,68 @@ macro_rules! fix_roles {
 }
 
 impl Group {
+    /// Rewrite tags to their canonical forms and make sure they are allowed in some groups
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups these tags are being added to
+    /// * `tags` - The tags to normalize and check
+    /// * `upload` - Whether these tags are for a new upload and must include any required tags
+    pub fn apply_tag_vocabularies(
+        groups: &[Group],
+        tags: &mut HashMap<String, HashSet<String>>,
+        upload: bool,
+    ) -> Result<(), ApiError> {
+        // rewrite our tags to their canonical forms for each group
+        for group in groups {
+            *tags = group.tag_vocabulary.normalize_tags(std::mem::take(tags));
+        }
+        // make sure all of our rewritten tags are allowed in all groups
+        for group in groups
+            .iter()
+            .filter(|group| !group.tag_vocabulary.is_empty())
+        {
+            // compile this vocabularies regexes once for all of our tags
+            let regexes = group.tag_vocabulary.compile_regexes()?;
+            for (key, values) in tags.iter() {
+                for value in values {
+                    group
+                        .tag_vocabulary
+                        .check(&group.name, &regexes, key, value)?;
+                }
+            }
+            // make sure any required tags were set on uploads
+            if upload {
+                let missing = group.tag_vocabulary.missing_required(tags);
+                if !missing.is_empty() {
+                    return bad!(format!(
+                        "Group {} requires the following tags on upload: {:?}",
+                        group.name, missing
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrite tags to their canonical forms and make sure they are allowed in some groups by name
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The names of the groups these tags are being added to
+    /// * `tags` - The tags to normalize and check
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Group::apply_tag_vocabularies_by_name", skip_all, err(Debug))]
+    pub async fn apply_tag_vocabularies_by_name(
+        groups: &[String],
+        tags: &mut HashMap<String, HashSet<String>>,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // get the details for these groups
+        let groups = db::groups::list_details(groups.iter(), shared).await?;
+        Self::apply_tag_vocabularies(&groups, tags, false)
+    }
+
     /// Creates a group object in the backend
     ///
     /// # Arguments
@@ -801,6 +955,11 @@ impl Group {
         update_clear!(self.description, update.clear_description);
         // update our allowed settings
         update.allowed.update(&mut self);
+        // replace our tag vocabulary if a new one was set
+        if let Some(vocabulary) = update.tag_vocabulary.take() {
+            vocabulary.validate()?;
+            self.tag_vocabulary = vocabulary;
+        }
         // save updated group to the backend
         db::groups::update(&self, &added, &removed, shared).await?;
         Ok(self)
@@ -923,6 +1082,7 @@ impl TryFrom<RawGroupData> for Group {
             monitors,
             description: deserialize_opt!(data, "description"),
             allowed: deserialize_ext!(data, "allowed", GroupAllowed::default()),
+            tag_vocabulary: deserialize_ext!(data, "tag_vocabulary", TagVocabulary::default()),
         };
         Ok(group)
     }
@@ -989,6 +1149,7 @@ impl
             monitors,
             description: deserialize_opt!(data, "description"),
             allowed: deserialize_ext!(data, "allowed", GroupAllowed::default()),
+            tag_vocabulary: deserialize_ext!(data, "tag_vocabulary", TagVocabulary::default()),
         };
         Ok(group)
     }
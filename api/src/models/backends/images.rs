@@ -15,13 +15,13 @@ use crate::models::system::{
     BARE_METAL_CACHE_KEY, EXTERNAL_CACHE_KEY, K8S_CACHE_KEY, KVM_CACHE_KEY, WINDOWS_CACHE_KEY,
 };
 use crate::models::{
-    ChildFilters, ChildFiltersUpdate, Cleanup, CleanupUpdate, Dependencies, DependenciesUpdate,
-    Group, GroupAllowAction, Image, ImageArgs, ImageArgsUpdate, ImageBan, ImageBanKind,
-    ImageBanUpdate, ImageDetailsList, ImageKey, ImageList, ImageListParams,
-    ImageNetworkPolicyUpdate, ImageRequest, ImageScaler, ImageUpdate, Kvm, KvmUpdate,
-    NetworkPolicy, OutputCollection, OutputDisplayType, PipelineBan, PipelineBanKind,
-    PipelineBanUpdate, PipelineKey, Resources, ResourcesRequest, ResourcesUpdate, SecurityContext,
-    SecurityContextUpdate, SpawnLimits, SystemSettings, User,
+    ChildFilters, ChildFiltersUpdate, Cleanup, CleanupUpdate, CpuArch, Dependencies,
+    DependenciesUpdate, Group, GroupAllowAction, Image, ImageArgs, ImageArgsUpdate, ImageBan,
+    ImageBanKind, ImageBanUpdate, ImageDetailsList, ImageKey, ImageLifetime, ImageList,
+    ImageListParams, ImageNetworkPolicyUpdate, ImageRequest, ImageScaler, ImageUpdate, Kvm,
+    KvmUpdate, MicroVm, NetworkPolicy, OutputCollection, OutputDisplayType, PipelineBan,
+    PipelineBanKind, PipelineBanUpdate, PipelineKey, PriorityClass, Resources, ResourcesRequest,
+    ResourcesUpdate, SecurityContext, SecurityContextUpdate, SpawnLimits, SystemSettings, User,
 };
 use crate::utils::{bounder, ApiError, Shared};
 use crate::{
@@ -136,6 +136,87 @@ where
     Ok(())
 }
 
+/// Check that microVM settings are only set for images scaled by the kvm scaler
+///
+/// # Arguments
+///
+/// * `scaler` - The scaler for this image
+/// * `microvm` - The microVM settings for this image
+fn validate_microvm(scaler: ImageScaler, microvm: Option<&MicroVm>) -> Result<(), ApiError> {
+    if let Some(microvm) = microvm {
+        // microVMs are launched by kvm reactors
+        if scaler != ImageScaler::Kvm {
+            return bad!("MicroVM settings require the Kvm scaler".to_owned());
+        }
+        // make sure we have a kernel and rootfs to boot
+        if microvm.kernel.trim().is_empty() || microvm.rootfs.trim().is_empty() {
+            return bad!("MicroVM settings require a kernel and rootfs".to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Check that network capture is only enabled for scalers that can capture traffic
+///
+/// K8s captures traffic with a sidecar, kvm reactors capture traffic on each vms tap, and bare
+/// metal reactors capture traffic on the veth of each workers network namespace.
+///
+/// # Arguments
+///
+/// * `scaler` - The scaler for this image
+/// * `capture_network` - Whether network capture is enabled for this image
+fn validate_capture_network(scaler: ImageScaler, capture_network: bool) -> Result<(), ApiError> {
+    if capture_network
+        && !matches!(
+            scaler,
+            ImageScaler::K8s | ImageScaler::Kvm | ImageScaler::BareMetal
+        )
+    {
+        return bad!(format!("Network capture is not supported for the {scaler} scaler"));
+    }
+    Ok(())
+}
+
+/// Check that emulation is only enabled for scalers that can run images under qemu-user
+///
+/// Emulation relies on the `binfmt_misc` handlers of the host so only containers and bare metal
+/// processes can be emulated. Vms must always be spawned on a node with a matching architecture.
+///
+/// # Arguments
+///
+/// * `scaler` - The scaler for this image
+/// * `emulation` - Whether emulation is enabled for this image
+fn validate_emulation(scaler: ImageScaler, emulation: bool) -> Result<(), ApiError> {
+    if emulation && !matches!(scaler, ImageScaler::K8s | ImageScaler::BareMetal) {
+        return bad!(format!(
+            "Emulation is not supported for the {scaler} scaler"
+        ));
+    }
+    Ok(())
+}
+
+/// Make sure kvm images only ever execute one job per vm
+///
+/// Kvm images default to a lifetime of one job so that no state is left behind between jobs.
+///
+/// # Arguments
+///
+/// * `scaler` - The scaler for this image
+/// * `lifetime` - The lifetime for this image
+fn enforce_kvm_lifetime(
+    scaler: ImageScaler,
+    lifetime: &mut Option<ImageLifetime>,
+) -> Result<(), ApiError> {
+    if scaler == ImageScaler::Kvm {
+        match lifetime {
+            Some(lifetime) if *lifetime == ImageLifetime::jobs(1) => (),
+            Some(_) => return bad!("Kvm images must have a lifetime of one job".to_owned()),
+            None => *lifetime = Some(ImageLifetime::jobs(1)),
+        }
+    }
+    Ok(())
+}
+
 impl ChildFilters {
     /// Check that all given child filters are valid
     fn validate(&self) -> Result<(), ApiError> {
@@ -156,7 +237,7 @@ impl ImageRequest {
     ///
     /// * `user` - The user that is casting this request to an image
     /// * `settings` - The Thorium [`SystemSettings`]
-    pub fn cast(self, user: &User, settings: &SystemSettings) -> Result<Image, ApiError> {
+    pub fn cast(mut self, user: &User, settings: &SystemSettings) -> Result<Image, ApiError> {
         // make sure our resource requests are valid
         let resources = Resources::try_from(self.resources)?;
         // validate all volumes
@@ -170,6 +251,14 @@ impl ImageRequest {
             // make sure we are an admin
             is_admin!(user);
         }
+        // make sure microVM settings are only set on images our kvm reactors will spawn
+        validate_microvm(self.scaler, self.microvm.as_ref())?;
+        // make sure kvm images only execute one job per vm
+        enforce_kvm_lifetime(self.scaler, &mut self.lifetime)?;
+        // make sure our scaler can capture network traffic if its requested
+        validate_capture_network(self.scaler, self.capture_network)?;
+        // make sure our scaler can emulate this image if its requested
+        validate_emulation(self.scaler, self.emulation)?;
         // cast to an Image
         let image = Image {
             group: self.group,
@@ -181,6 +270,7 @@ impl ImageRequest {
             timeout: self.timeout,
             resources,
             spawn_limit: self.spawn_limit,
+            priority: self.priority,
             scaler: self.scaler,
             runtime: 600.0,
             volumes: self.volumes,
@@ -192,12 +282,16 @@ impl ImageRequest {
             used_by: Vec::default(),
             collect_logs: self.collect_logs,
             generator: self.generator,
+            capture_network: self.capture_network,
+            arch: self.arch,
+            emulation: self.emulation,
             dependencies: self.dependencies,
             display_type: self.display_type,
             output_collection: self.output_collection,
             child_filters: self.child_filters,
             clean_up: self.clean_up,
             kvm: self.kvm,
+            microvm: self.microvm,
             bans: HashMap::default(),
             network_policies: self.network_policies,
         };
@@ -220,6 +314,7 @@ impl ImageArgsUpdate {
         update_opt!(image.args.commit, self.commit);
         update_clear!(image.args.commit, self.clear_commit);
         update!(image.args.output, self.output);
+        update!(image.args.resume, self.resume);
     }
 }
 
@@ -485,9 +580,9 @@ impl KvmUpdate {
     /// # Arguments
     ///
     /// * `image` - The image to apply this update too
-    pub fn update(self, image: &mut Image) -> Result<(), ApiError> {
+    pub fn update(mut self, image: &mut Image) -> Result<(), ApiError> {
         // extract any existing kvm settings
-        let kvm = match image.kvm.take() {
+        let mut kvm = match image.kvm.take() {
             Some(mut kvm) => {
                 // apply any updates
                 update!(kvm.xml, self.xml);
@@ -497,14 +592,20 @@ impl KvmUpdate {
             }
             None => {
                 // we have no existing settings so make sure all required options are set
-                match (self.xml, self.qcow2) {
-                    (Some(xml), Some(qcow2)) => Kvm { xml, qcow2 },
+                match (self.xml.take(), self.qcow2.take()) {
+                    (Some(xml), Some(qcow2)) => Kvm::new(xml, qcow2),
                     // we have no updates to apply
-                    (None, None) => return Ok(()),
+                    (None, None) if self.memory_state.is_none() && self.dump_memory.is_none() => {
+                        return Ok(())
+                    }
                     _ => return bad!("xml and qcow2 must both be set".to_owned()),
                 }
             }
         };
+        // update how our vms are restored and whether their memory is dumped
+        update_opt!(kvm.memory_state, self.memory_state);
+        update_clear!(kvm.memory_state, self.clear_memory_state);
+        update!(kvm.dump_memory, self.dump_memory);
         // set our new settings
         image.kvm = Some(kvm);
         Ok(())
@@ -934,6 +1035,9 @@ impl Image {
             // set our new validated image
             self.image = Some(image.to_owned());
         }
+        // track whether this update explicitly changes our lifetime or scaler
+        let lifetime_changed = update.lifetime.is_some();
+        let scaler_changed = update.scaler.is_some() || update.clear_lifetime;
         // overlay update on the Image data
         update_opt!(self.version, update.version);
         update_opt!(self.timeout, update.timeout);
@@ -948,11 +1052,24 @@ impl Image {
         }
         // update our spawn limit
         update!(self.spawn_limit, update.spawn_limit);
+        // update our priority class
+        update!(self.priority, update.priority);
         // clear fields if requested
         update_clear!(self.version, update.clear_version);
         update_clear!(self.image, update.clear_image);
         update_clear!(self.lifetime, update.clear_lifetime);
         update_clear!(self.description, update.clear_description);
+        update_opt!(self.microvm, update.microvm);
+        update_clear!(self.microvm, update.clear_microvm);
+        // make sure our microVM settings are still valid for our scaler
+        validate_microvm(self.scaler, self.microvm.as_ref())?;
+        // make sure kvm images still only execute one job per vm but only reject lifetimes that
+        // were requested so existing images can still be updated
+        if lifetime_changed {
+            enforce_kvm_lifetime(self.scaler, &mut self.lifetime)?;
+        } else if scaler_changed && self.scaler == ImageScaler::Kvm {
+            self.lifetime = Some(ImageLifetime::jobs(1));
+        }
         // update our images args if any updates were found
         if let Some(args) = update.args.take() {
             args.update(&mut self);
@@ -963,6 +1080,13 @@ impl Image {
         }
         update!(self.collect_logs, update.collect_logs);
         update!(self.generator, update.generator);
+        update!(self.capture_network, update.capture_network);
+        // make sure our scaler can still capture network traffic if its requested
+        validate_capture_network(self.scaler, self.capture_network)?;
+        update!(self.arch, update.arch);
+        update!(self.emulation, update.emulation);
+        // make sure our scaler can still emulate this image if its requested
+        validate_emulation(self.scaler, self.emulation)?;
         // update any dependency settings
         update.dependencies.update(&mut self);
         // update display_type
@@ -1151,6 +1275,7 @@ impl TryFrom<(HashMap<String, String>, Vec<String>)> for Image {
             image: deserialize_ext!(map, "image", None),
             resources: deserialize_ext!(map, "resources", Resources::internal_default()),
             spawn_limit: deserialize_ext!(map, "spawn_limit", SpawnLimits::Unlimited),
+            priority: deserialize_ext!(map, "priority", PriorityClass::default()),
             lifetime: deserialize_ext!(map, "lifetime", None),
             timeout: deserialize_ext!(map, "timeout", None),
             runtime: extract!(map, "runtime").parse::<f64>()?,
@@ -1163,6 +1288,9 @@ impl TryFrom<(HashMap<String, String>, Vec<String>)> for Image {
             used_by,
             collect_logs: deserialize_ext!(map, "collect_logs", true),
             generator: deserialize_ext!(map, "generator", false),
+            capture_network: deserialize_ext!(map, "capture_network", false),
+            arch: deserialize_ext!(map, "arch", CpuArch::default()),
+            emulation: deserialize_ext!(map, "emulation", false),
             dependencies: deserialize_ext!(map, "dependencies", Dependencies::default()),
             display_type: deserialize_ext!(map, "display_type", OutputDisplayType::default()),
             output_collection: deserialize_ext!(
@@ -1173,6 +1301,7 @@ impl TryFrom<(HashMap<String, String>, Vec<String>)> for Image {
             child_filters: deserialize_ext!(map, "child_filters", ChildFilters::default()),
             clean_up: deserialize_opt!(map, "clean_up"),
             kvm: deserialize_opt!(map, "kvm"),
+            microvm: deserialize_opt!(map, "microvm"),
             bans: deserialize_ext!(map, "bans", HashMap::default()),
             network_policies: deserialize_ext!(map, "network_policies", HashSet::default()),
         };
@@ -1,20 +1,25 @@
 //! Wrappers for interacting with jobs within Thorium with different backends
 //! Currently only Redis is supported
 
+use aws_sdk_s3::primitives::ByteStream;
+use axum::extract::Multipart;
 use chrono::prelude::*;
 use std::collections::HashMap;
 use tracing::{event, instrument, Level};
 use uuid::Uuid;
 
 use super::db;
+use crate::models::deadlines::priority_stream_data;
 use crate::models::{
-    Checkpoint, GenericJob, GenericJobArgs, Group, ImageJobInfo, ImageScaler, JobDetailsList,
-    JobHandleStatus, JobList, JobResets, JobStatus, Pipeline, RawJob, Reaction, RunningJob,
-    StageLogsAdd, Stream, StreamObj, User, WorkerName,
+    Checkpoint, GenericJob, GenericJobArgs, Group, ImageJobInfo, ImageScaler, JobCheckpointBlob,
+    JobDetailsList, JobHandleStatus, JobList, JobProgress, JobProgressUpdate, JobResets, JobStatus,
+    Pipeline, PriorityClass, RawJob, Reaction, RunningJob, StageLogsAdd, Stream, StreamObj, User,
+    WorkerName,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
-    deserialize, deserialize_ext, deserialize_opt, extract, is_admin, not_found, serialize,
+    bad, conflict, deserialize, deserialize_ext, deserialize_opt, extract, is_admin, not_found,
+    serialize,
 };
 
 impl JobList {
@@ -96,6 +101,8 @@ impl RawJob {
             parent_ephemeral: reaction.parent_ephemeral.clone(),
             repos: reaction.repos.clone(),
             trigger_depth: reaction.trigger_depth,
+            priority: reaction.priority,
+            checkpoint_blob: None,
         };
         Ok(cast)
     }
@@ -141,6 +148,8 @@ impl RawJob {
             parent_ephemeral: deserialize_ext!(raw, "parent_ephemeral", HashMap::default()),
             repos: deserialize_ext!(raw, "repos", Vec::default()),
             trigger_depth: deserialize_opt!(raw, "trigger_depth"),
+            checkpoint_blob: deserialize_opt!(raw, "checkpoint_blob"),
+            priority: deserialize_ext!(raw, "priority", PriorityClass::default()),
         };
         Ok(job)
     }
@@ -252,6 +261,124 @@ impl RawJob {
         db::jobs::set_args(&self, shared).await
     }
 
+    /// Saves the latest progress reported by this job
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is reporting progress
+    /// * `group` - The group this job is tied to
+    /// * `update` - The progress that was reported
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Job::progress", skip(self, user, group, shared), err(Debug))]
+    pub async fn progress(
+        &self,
+        user: &User,
+        group: &Group,
+        update: JobProgressUpdate,
+        shared: &Shared,
+    ) -> Result<JobProgress, ApiError> {
+        // make sure this user can report progress for jobs from this group
+        group.editable(user)?;
+        // make sure our percent is within bounds
+        if let Some(percent) = update.percent {
+            if !(0.0..=100.0).contains(&percent) {
+                return bad!(format!("percent must be between 0 and 100 not {percent}"));
+            }
+        }
+        // make sure our eta can be converted to a timestamp
+        if let Some(eta) = update.eta {
+            let valid = i64::try_from(eta)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|left| Utc::now().checked_add_signed(left))
+                .is_some();
+            if !valid {
+                return bad!(format!("eta of {eta} seconds is too far in the future"));
+            }
+        }
+        // build and save our progress
+        let progress = JobProgress::new(self.id, &self.stage, update);
+        db::jobs::set_progress(self, &progress, shared).await?;
+        Ok(progress)
+    }
+
+    /// Saves a checkpoint blob for a running job so it can resume after losing its worker
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is saving a checkpoint blob
+    /// * `group` - The group this job is tied to
+    /// * `upload` - The multipart form containing our checkpoint blob
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(
+        name = "Job::upload_checkpoint_blob",
+        skip(self, user, group, upload, shared),
+        err(Debug)
+    )]
+    pub async fn upload_checkpoint_blob(
+        &self,
+        user: &User,
+        group: &Group,
+        mut upload: Multipart,
+        shared: &Shared,
+    ) -> Result<JobCheckpointBlob, ApiError> {
+        // make sure this user can checkpoint jobs from this group
+        group.editable(user)?;
+        // only running jobs can save checkpoint blobs
+        if self.status != JobStatus::Running {
+            return conflict!(format!(
+                "job {} must be running to save a checkpoint blob",
+                &self.id
+            ));
+        }
+        // build the path to save this checkpoint blob at in s3
+        let path = db::jobs::checkpoint_blob_path(&self.id);
+        // find our checkpoint blob in this multipart form
+        while let Some(field) = upload.next_field().await? {
+            if field.name() == Some("data") {
+                // stream this checkpoint blob into s3 overwriting any prior checkpoint
+                shared.s3.ephemeral.stream(&path, field).await?;
+                // save a reference to this checkpoint blob on our job
+                let blob = JobCheckpointBlob {
+                    size: shared.s3.ephemeral.size(&path).await?,
+                    uploaded: Utc::now(),
+                };
+                db::jobs::set_checkpoint_blob(self, &blob, shared).await?;
+                return Ok(blob);
+            }
+        }
+        bad!("A data form entry containing the checkpoint blob must be set".to_owned())
+    }
+
+    /// Downloads the last checkpoint blob this job saved
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is downloading a checkpoint blob
+    /// * `group` - The group this job is tied to
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(
+        name = "Job::download_checkpoint_blob",
+        skip(self, user, group, shared),
+        err(Debug)
+    )]
+    pub async fn download_checkpoint_blob(
+        &self,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<ByteStream, ApiError> {
+        // make sure this user can see jobs from this group
+        group.viewable(user)?;
+        // make sure this job has saved a checkpoint blob
+        if self.checkpoint_blob.is_none() {
+            return not_found!(format!("job {} has no checkpoint blob", &self.id));
+        }
+        // build the path to this checkpoint blob in s3
+        let path = db::jobs::checkpoint_blob_path(&self.id);
+        shared.s3.ephemeral.download(&path).await
+    }
+
     /// Sets a job status as sleeping in redis
     ///
     /// This does not complete a job and complete must still be called.
@@ -276,6 +403,28 @@ impl RawJob {
         db::jobs::sleep(self, checkpoint, shared).await
     }
 
+    /// Returns a preempted job to the queue
+    ///
+    /// The job will keep its checkpoint blob so the next worker can resume it.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is preempting this job
+    /// * `group` - The name of the group this job is tied to
+    /// * `shared` - Shared objects in Thorium
+    #[instrument(name = "Job::preempt", skip(self, user, group, shared), err(Debug))]
+    pub async fn preempt(
+        self,
+        user: &User,
+        group: &Group,
+        shared: &Shared,
+    ) -> Result<JobHandleStatus, ApiError> {
+        // make sure this user can preempt jobs from this group
+        group.editable(user)?;
+        // return this job to the queue
+        db::jobs::preempt(self, shared).await
+    }
+
     /// Resets jobs in bulk
     ///
     /// This will set all of the jobs statuses back to created except for any
@@ -343,13 +492,14 @@ impl RawJob {
     pub fn stream_data(&self) -> String {
         // cast deadline data to stream data without timestamp
         // were using a format macro so we get a consistent order
-        format!("{{\"group\":\"{}\",\"pipeline\":\"{}\",\"stage\":\"{}\",\"creator\":\"{}\",\"job_id\":\"{}\",\"reaction\":\"{}\"}}",
+        format!("{{\"group\":\"{}\",\"pipeline\":\"{}\",\"stage\":\"{}\",\"creator\":\"{}\",\"job_id\":\"{}\",\"reaction\":\"{}\"{}}}",
             self.group,
             self.pipeline,
             self.stage,
             self.creator,
             self.id,
-            self.reaction)
+            self.reaction,
+            priority_stream_data(self.priority))
     }
 }
 
@@ -435,6 +585,7 @@ impl TryFrom<RawJob> for GenericJob {
             parent_ephemeral: raw.parent_ephemeral,
             repos: raw.repos,
             trigger_depth: raw.trigger_depth,
+            checkpoint_blob: raw.checkpoint_blob,
         };
         Ok(cast)
     }
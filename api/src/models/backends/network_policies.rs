@@ -546,6 +546,27 @@ macro_rules! validate_allowed_groups {
     };
 }
 
+/// Validate that only egress rules use the simulated internet and that tools
+/// redirected to the simulated internet can't also reach the real one
+macro_rules! validate_simulated_internet {
+    ($ingress:expr, $egress:expr) => {{
+        // the simulated internet only redirects egress traffic
+        if $ingress.any(|rule| rule.simulated_internet) {
+            return bad!("Only egress rules can use the simulated internet".to_string());
+        }
+        // make sure we aren't mixing the simulated internet with the real one
+        if $egress.clone().any(|rule| rule.simulated_internet)
+            && $egress.any(|rule| rule.allowed_internet || rule.allowed_all)
+        {
+            return bad!(
+                "Egress rules cannot allow internet or all access with the simulated internet"
+                    .to_string()
+            );
+        }
+        Ok::<(), ApiError>(())
+    }};
+}
+
 /// Cast a list of `NetworkPolicyRuleRaw` to `NetworkPolicyRule`, propagating
 /// any errors that occur with a given message
 macro_rules! cast_rules {
@@ -610,6 +631,8 @@ impl NetworkPolicyRequest {
         let egress = self.egress.iter().flatten();
         // validate the rules' allowed groups
         validate_allowed_groups!(ingress.clone(), egress.clone(), shared).await?;
+        // validate any simulated internet rules
+        validate_simulated_internet!(ingress.clone(), egress.clone())?;
         // validate ingress labels
         validate_labels!(ingress)?;
         // validate egress labels
@@ -727,6 +750,11 @@ impl NetworkPolicyUpdate {
             self.deny_all_egress,
             "egress"
         );
+        // make sure our updated rules use the simulated internet correctly
+        validate_simulated_internet!(
+            network_policy.ingress.iter().flatten(),
+            network_policy.egress.iter().flatten()
+        )?;
         Ok(network_policy)
     }
 }
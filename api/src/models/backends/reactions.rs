@@ -8,10 +8,13 @@ use tracing::{event, instrument, span, Level, Span};
 use uuid::Uuid;
 
 use super::db;
+use super::CommentSupport;
+use crate::models::backends::SubscriptionSupport;
 use crate::models::{
-    BulkReactionResponse, GenericJobArgs, Group, GroupAllowAction, JobList, Pipeline, Reaction,
-    ReactionDetailsList, ReactionExpire, ReactionList, ReactionRequest, ReactionStatus,
-    ReactionUpdate, Repo, RepoDependency, Sample, StageLogs, StageLogsAdd, StatusUpdate, User,
+    BulkReactionResponse, Comment, GenericJobArgs, Group, GroupAllowAction, JobList, JobProgress,
+    Pipeline, PriorityClass, Reaction, ReactionDetailsList, ReactionExpire, ReactionList,
+    ReactionRequest, ReactionStatus, ReactionUpdate, Repo, RepoDependency, Sample, StageLogs,
+    StageLogsAdd, StatusUpdate, SubscriptionTarget, User,
 };
 use crate::utils::{bounder, ApiError, Shared};
 use crate::{
@@ -162,6 +165,9 @@ impl ReactionRequest {
             parent_ephemeral,
             repos,
             trigger_depth: self.trigger_depth,
+            priority: self.priority,
+            job_progress: HashMap::default(),
+            comments: Vec::default(),
         };
         Ok((cast, pipeline))
     }
@@ -389,7 +395,10 @@ impl Reaction {
         );
         // make sure we are a member of this group and it exists
         let group = Group::authorize(user, group, shared).await?;
-        let reaction = db::reactions::get(&group.name, id, shared).await?;
+        let mut reaction = db::reactions::get(&group.name, id, shared).await?;
+        // get the comments for this reaction
+        let groups = vec![group.name.clone()];
+        db::comments::get(&groups, &id.to_string(), &mut reaction.comments, shared).await?;
         Ok((group, reaction))
     }
 
@@ -654,7 +663,13 @@ impl Reaction {
         // make sure we can modify reactions in this group
         can_delete!(self, group, user);
         // use correct backend for deleteing this reaction
-        db::reactions::delete(self, shared).await
+        db::reactions::delete(self, shared).await?;
+        // delete any comments on this reaction and their attachments
+        let key = self.id.to_string();
+        for comment in &self.comments {
+            db::comments::delete(&key, &comment.groups, comment, shared).await?;
+        }
+        db::comments::prune_attachments(&self.comments, &key, shared).await
     }
 
     /// Deletes all reactions in a pipeline from the backend
@@ -785,6 +800,30 @@ impl Reaction {
     }
 }
 
+impl SubscriptionSupport for Reaction {
+    /// Comments on reactions notify users following their pipeline
+    fn subscription_target(&self) -> Option<SubscriptionTarget> {
+        Some(SubscriptionTarget::pipeline(&self.group, &self.pipeline))
+    }
+}
+
+impl CommentSupport for Reaction {
+    /// Get the key that comments on this reaction are saved under
+    fn comment_key(&self) -> String {
+        self.id.to_string()
+    }
+
+    /// Get the groups this reaction can be seen in
+    fn comment_groups(&self) -> HashSet<&str> {
+        HashSet::from([self.group.as_str()])
+    }
+
+    /// Get the comments on this reaction
+    fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+}
+
 /// This should probably a TryFrom but I am unsure how to enforce that ApiError implements Deserialize
 impl ReactionExpire {
     pub(super) fn cast(raw: &str) -> Result<Self, ApiError> {
@@ -794,19 +833,31 @@ impl ReactionExpire {
     }
 }
 
-impl TryFrom<(HashMap<String, String>, Vec<String>, Vec<String>)> for Reaction {
+impl
+    TryFrom<(
+        HashMap<String, String>,
+        Vec<String>,
+        Vec<String>,
+        HashMap<String, String>,
+    )> for Reaction
+{
     type Error = ApiError;
 
     /// Try to cast a HashMap of strings and a vector of strings into a Reaction
     ///
     /// # Arguments
     ///
-    /// * `raw` - The HashMap and strings to cast into a Reaction
+    /// * `raw` - The HashMaps and strings to cast into a Reaction
     fn try_from(
-        raw: (HashMap<String, String>, Vec<String>, Vec<String>),
+        raw: (
+            HashMap<String, String>,
+            Vec<String>,
+            Vec<String>,
+            HashMap<String, String>,
+        ),
     ) -> Result<Self, Self::Error> {
-        // unwrap into hashmap and vector of job ids
-        let (mut map, raw_jobs, raw_generators) = raw;
+        // unwrap into hashmaps and vectors of job ids
+        let (mut map, raw_jobs, raw_generators, raw_progress) = raw;
         // return 404 if hashmap is empty
         if map.is_empty() {
             return not_found!("reaction not found".to_string());
@@ -826,6 +877,16 @@ impl TryFrom<(HashMap<String, String>, Vec<String>, Vec<String>)> for Reaction {
             .filter_map(Result::ok)
             .collect();
 
+        // cast any reported job progress, skipping any that are malformed
+        let job_progress = raw_progress
+            .into_iter()
+            .filter_map(|(id, raw)| {
+                let id = Uuid::parse_str(&id).ok()?;
+                let progress = serde_json::from_str::<JobProgress>(&raw).ok()?;
+                Some((id, progress))
+            })
+            .collect();
+
         // cast to a Reaction
         let reaction = Reaction {
             id: Uuid::parse_str(&extract!(map, "id"))?,
@@ -849,6 +910,9 @@ impl TryFrom<(HashMap<String, String>, Vec<String>, Vec<String>)> for Reaction {
             parent_ephemeral: deserialize_ext!(map, "parent_ephemeral", HashMap::default()),
             repos: deserialize_ext!(map, "repos", Vec::default()),
             trigger_depth: deserialize_opt!(map, "trigger_depth"),
+            priority: deserialize_ext!(map, "priority", PriorityClass::default()),
+            job_progress,
+            comments: Vec::default(),
         };
         Ok(reaction)
     }
@@ -14,16 +14,19 @@ use url::Url;
 use uuid::Uuid;
 
 use super::db::{self, CursorCore, ScyllaCursorSupport};
+use super::CommentSupport;
 use crate::models::{
-    ApiCursor, Branch, Commit, Commitish, CommitishDetails, CommitishKinds, CommitishListParams,
-    CommitishListRow, CommitishMapRequest, GitTag, Group, GroupAllowAction, Repo, RepoDataForm,
-    RepoDownloadOpts, RepoListLine, RepoListParams, RepoListRow, RepoRequest, RepoRow, RepoScheme,
-    RepoSubmission, RepoSubmissionChunk, RepoUrlComponents, S3Objects, TagListRow, TagMap, TagType,
-    User, UserRole,
+    ApiCursor, Branch, Comment, Commit, Commitish, CommitishDetails, CommitishKinds,
+    CommitishListParams, CommitishListRow, CommitishMapRequest, CommitishRequest, Event, GitTag,
+    Group, GroupAllowAction, Repo, RepoDataForm, RepoDownloadOpts, RepoListLine, RepoListParams,
+    RepoListRow, RepoMirror, RepoMirrorClaimOpts, RepoMirrorFetch, RepoMirrorRequest, RepoRequest,
+    RepoRow, RepoScheme, RepoSubmission, RepoSubmissionChunk, RepoUrlComponents, S3Objects,
+    TagListRow, TagMap, TagType, User, UserRole,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
-    bad, can_create_all, deserialize, deserialize_opt, for_groups, not_found, unauthorized,
+    bad, can_create_all, conflict, deserialize, deserialize_opt, for_groups, is_admin, not_found,
+    unauthorized,
 };
 
 /// Check if an option contains a non-empty value and cast it to a String or error
@@ -138,7 +141,7 @@ impl Repo {
     #[instrument(name = "Repo::create", skip(user, shared), err(Debug))]
     pub async fn create(
         user: &User,
-        req: RepoRequest,
+        mut req: RepoRequest,
         shared: &Shared,
     ) -> Result<String, ApiError> {
         // require at least some groups to be set
@@ -151,6 +154,8 @@ impl Repo {
                 .await?;
         // make sure we have the roles to upload samples in all of these groups
         can_create_all!(groups, user, shared);
+        // rewrite our tags to their canonical forms and make sure our groups allow them
+        Group::apply_tag_vocabularies(&groups, &mut req.tags, true)?;
         // add this repo to scylla
         db::repos::create(user, req, shared).await
     }
@@ -445,11 +450,67 @@ impl Repo {
         // validate any user specified groups or get defaults
         self.validate_check_allow_groups(user, &mut req.groups, GroupAllowAction::Repos, shared)
             .await?;
+        // build an event for any new commits or branches before we consume our request
+        let event = self.commitish_event(user, data, &req, shared).await?;
         // save these commitishes to the backend
         db::repos::add_commitishes(self, data, req, shared).await?;
+        // save our event so any commitish triggers can be checked
+        if let Some(event) = event {
+            db::events::create(&event, shared).await?;
+        }
         Ok(())
     }
 
+    /// Build an event for any new commits or branches being added to this repo
+    ///
+    /// No event is built while a repo's history is first being ingested. Commitishes that
+    /// are uploaded across multiple requests are held until the last request so that a
+    /// single event is built with all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is saving these commitishes
+    /// * `data` - The sha256 of the data we are saving commitishes from
+    /// * `req` - The commitishes being saved
+    /// * `shared` - Shared objects in Thorium
+    async fn commitish_event(
+        &self,
+        user: &User,
+        data: &str,
+        req: &CommitishMapRequest,
+        shared: &Shared,
+    ) -> Result<Option<Event>, ApiError> {
+        // repos get their earliest commit once their first ingest has finished
+        if self.earliest.is_none() {
+            return Ok(None);
+        }
+        // find the commits and branch heads we already have so we only trigger on new ones
+        let mut commits = Vec::with_capacity(req.commitishes.len());
+        let mut branches = Vec::default();
+        for (key, commitish) in &req.commitishes {
+            match commitish {
+                CommitishRequest::Commit(_) => commits.push(key.clone()),
+                CommitishRequest::Branch(_) => branches.push(key.clone()),
+                CommitishRequest::Tag(_) => (),
+            }
+        }
+        let kind = CommitishKinds::Commit;
+        let stored_commits =
+            db::repos::stored_commitishes(&req.groups, &self.url, kind, &commits, shared).await?;
+        let heads = db::repos::branch_heads(&req.groups, &self.url, &branches, shared).await?;
+        let mut new = Event::new_commitishes(req, &stored_commits, &heads);
+        // hold onto anything new until the last request of this upload
+        if !req.end {
+            db::repos::hold_commitishes(&self.url, data, &new, shared).await?;
+            return Ok(None);
+        }
+        // add anything new from the earlier requests in this upload
+        for (key, commitish) in db::repos::take_held_commitishes(&self.url, data, shared).await? {
+            new.entry(key).or_insert(commitish);
+        }
+        Ok(Event::new_commitish(user, &self.url, req, &new))
+    }
+
     /// List the commitishes for a specific repo
     ///
     /// # Arguments
@@ -611,6 +672,23 @@ impl Repo {
     }
 }
 
+impl CommentSupport for Repo {
+    /// Get the key that comments on this repo are saved under
+    fn comment_key(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Get the groups this repo can be seen in
+    fn comment_groups(&self) -> HashSet<&str> {
+        self.groups().into_iter().map(String::as_str).collect()
+    }
+
+    /// Get the comments on this repo
+    fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+}
+
 impl TryFrom<&Url> for RepoScheme {
     type Error = ApiError;
 
@@ -769,6 +847,7 @@ impl TryFrom<(RepoRow, &User)> for Repo {
             default_checkout,
             submissions: vec![submission],
             earliest: row.earliest,
+            comments: Vec::default(),
         };
         Ok(repo)
     }
@@ -1462,6 +1541,240 @@ impl ScyllaCursorSupport for RepoListLine {
     }
 }
 
+/// The shortest number of seconds allowed between fetches of a mirrored repo
+const MIN_MIRROR_INTERVAL: u64 = 300;
+
+/// The longest number of seconds allowed between fetches of a mirrored repo (30 days)
+const MAX_MIRROR_INTERVAL: u64 = 2_592_000;
+
+impl RepoMirrorRequest {
+    /// Make sure this repo mirror request is valid
+    fn validate(&self) -> Result<(), ApiError> {
+        // we can only fetch mirrors over HTTP(S)
+        if !self.remote.starts_with("https://") && !self.remote.starts_with("http://") {
+            return bad!(format!(
+                "Mirrored repos must be fetched over HTTP(S): {}",
+                self.remote
+            ));
+        }
+        // require at least some groups to be set
+        if self.groups.is_empty() {
+            return bad!("At least one group must be specified!".to_owned());
+        }
+        // don't allow mirrors to be fetched too often
+        if self.interval < MIN_MIRROR_INTERVAL {
+            return bad!(format!(
+                "Mirrors cannot be fetched more often than every {MIN_MIRROR_INTERVAL} seconds"
+            ));
+        }
+        // don't allow mirrors to go too long between fetches
+        if self.interval > MAX_MIRROR_INTERVAL {
+            return bad!(format!(
+                "Mirrors must be fetched at least every {MAX_MIRROR_INTERVAL} seconds"
+            ));
+        }
+        // make sure any credentials are complete and never sent in plaintext
+        match &self.credentials {
+            Some(creds) if creds.username.is_empty() || creds.token.is_empty() => {
+                bad!("Mirror credentials must have a username and token!".to_owned())
+            }
+            Some(_) if !self.remote.starts_with("https://") => {
+                bad!("Mirrors with credentials must be fetched over HTTPS!".to_owned())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl RepoMirror {
+    /// Get when this mirror should be fetched next if it was fetched at a specific time
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The time this mirror was fetched or claimed at
+    pub fn after_interval(&self, from: DateTime<Utc>) -> DateTime<Utc> {
+        // cap our interval in case this mirror was created before intervals were limited
+        let interval = self.interval.min(MAX_MIRROR_INTERVAL) as i64;
+        from.checked_add_signed(chrono::Duration::seconds(interval))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Check if a user can see this mirror
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user to check
+    fn visible(&self, user: &User) -> bool {
+        user.is_admin() || self.groups.iter().any(|group| user.groups.contains(group))
+    }
+
+    /// Start mirroring a remote repo
+    ///
+    /// This will register the repo in Thorium if it doesn't already exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is creating this mirror
+    /// * `req` - The mirror to create
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::create", skip_all, fields(remote = &req.remote), err(Debug))]
+    pub async fn create(
+        user: &User,
+        req: RepoMirrorRequest,
+        shared: &Shared,
+    ) -> Result<RepoMirror, ApiError> {
+        // make sure this request is valid
+        req.validate()?;
+        // make sure this repo isn't already being mirrored
+        let url = RepoUrlComponents::parse(&req.remote)?.get_url();
+        if db::repos::get_mirror(&url, shared).await?.is_some() {
+            return conflict!(format!("{url} is already being mirrored"));
+        }
+        // register this repo in Thorium
+        let repo_req = RepoRequest::new(
+            &req.remote,
+            req.groups.clone(),
+            req.default_checkout.clone(),
+        );
+        let url = Repo::create(user, repo_req, shared).await?;
+        // build and save our mirror so it is fetched right away
+        let mirror = RepoMirror::new(url, &user.username, req);
+        db::repos::save_mirror(&mirror, shared).await?;
+        Ok(mirror.scrub())
+    }
+
+    /// Get a repo mirror
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is getting this mirror
+    /// * `url` - The url of the repo whose mirror to get
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::get", skip(user, shared), err(Debug))]
+    pub async fn get(user: &User, url: &str, shared: &Shared) -> Result<RepoMirror, ApiError> {
+        // only return mirrors in groups this user is in
+        match db::repos::get_mirror(url, shared).await? {
+            Some(mirror) if mirror.visible(user) => Ok(mirror.scrub()),
+            _ => not_found!(format!("Repo mirror for {url} not found")),
+        }
+    }
+
+    /// List the repo mirrors a user can see
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is listing mirrors
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::list", skip_all, err(Debug))]
+    pub async fn list(user: &User, shared: &Shared) -> Result<Vec<RepoMirror>, ApiError> {
+        let mut mirrors = db::repos::list_mirrors(shared)
+            .await?
+            .into_iter()
+            .filter(|mirror| mirror.visible(user))
+            .map(RepoMirror::scrub)
+            .collect::<Vec<RepoMirror>>();
+        mirrors.sort_unstable_by(|a, b| a.url.cmp(&b.url));
+        Ok(mirrors)
+    }
+
+    /// Stop mirroring a repo
+    ///
+    /// Any data already ingested for this repo will not be deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is deleting this mirror
+    /// * `url` - The url of the repo to stop mirroring
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::delete", skip(user, shared), err(Debug))]
+    pub async fn delete(user: &User, url: &str, shared: &Shared) -> Result<(), ApiError> {
+        // make sure this mirror exists and we can see it
+        let mirror = RepoMirror::get(user, url, shared).await?;
+        // only admins and the creator of a mirror can delete it
+        if !user.is_admin() && mirror.creator != user.username {
+            return unauthorized!();
+        }
+        db::repos::delete_mirror(url, shared).await
+    }
+
+    /// Fetch a mirrored repo as soon as possible
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is requesting this fetch
+    /// * `url` - The url of the repo to fetch
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::sync", skip(user, shared), err(Debug))]
+    pub async fn sync(user: &User, url: &str, shared: &Shared) -> Result<(), ApiError> {
+        // make sure this mirror exists and we can see it
+        RepoMirror::get(user, url, shared).await?;
+        // get the unscrubbed mirror so we don't lose its credentials
+        match db::repos::get_mirror(url, shared).await? {
+            Some(mut mirror) => {
+                // schedule this mirror to be fetched now
+                mirror.next_fetch = Utc::now();
+                db::repos::save_mirror(&mirror, shared).await
+            }
+            None => not_found!(format!("Repo mirror for {url} not found")),
+        }
+    }
+
+    /// Claim the mirrors that are due to be fetched along with their credentials
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is claiming mirrors
+    /// * `limit` - The max number of mirrors to claim
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::claim", skip(user, shared), err(Debug))]
+    pub async fn claim(
+        user: &User,
+        limit: usize,
+        shared: &Shared,
+    ) -> Result<Vec<RepoMirror>, ApiError> {
+        // only admins can claim mirrors since this returns their credentials
+        is_admin!(user);
+        db::repos::claim_mirrors(limit, shared).await
+    }
+
+    /// Save the outcome of fetching a mirror and schedule its next fetch
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that fetched this mirror
+    /// * `url` - The url of the repo that was fetched
+    /// * `fetch` - The outcome of this fetch
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "RepoMirror::fetched", skip(user, fetch, shared), err(Debug))]
+    pub async fn fetched(
+        user: &User,
+        url: &str,
+        fetch: RepoMirrorFetch,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // only admins can report mirror fetches
+        is_admin!(user);
+        // get the mirror that was fetched
+        let Some(mut mirror) = db::repos::get_mirror(url, shared).await? else {
+            return not_found!(format!("Repo mirror for {url} not found"));
+        };
+        // update this mirror with the outcome of this fetch
+        let now = Utc::now();
+        mirror.last_fetch = Some(now);
+        mirror.next_fetch = mirror.after_interval(now);
+        match fetch.error {
+            // keep our old refs so the next fetch ingests anything we missed
+            Some(error) => mirror.last_error = Some(error),
+            None => {
+                mirror.refs = fetch.refs;
+                mirror.ingested = fetch.ingested;
+                mirror.last_error = None;
+            }
+        }
+        db::repos::save_mirror(&mirror, shared).await
+    }
+}
+
 impl ApiCursor<RepoListLine> {
     /// Gets the details for the repos in a cursor
     ///
@@ -1549,3 +1862,20 @@ where
         }
     }
 }
+
+impl<S> FromRequestParts<S> for RepoMirrorClaimOpts
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // try to extract our query
+        if let Some(query) = parts.uri.query() {
+            // try to deserialize our query string
+            Ok(serde_qs::Config::new(5, false).deserialize_str(query)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
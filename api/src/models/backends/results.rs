@@ -5,20 +5,23 @@ use axum::extract::multipart::Field;
 use axum::extract::{FromRequestParts, Multipart};
 use axum::http::request::Parts;
 use axum::http::StatusCode;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::{instrument, Span};
 use uuid::Uuid;
 
 use super::db::{self};
-use crate::models::backends::OutputSupport;
+use super::CommentSupport;
+use crate::models::backends::{OutputSupport, SubscriptionSupport};
 use crate::models::{
-    AutoTag, AutoTagUpdate, ImageVersion, Output, OutputChunk, OutputCollection,
+    AutoTag, AutoTagUpdate, Comment, ImageVersion, Output, OutputChunk, OutputCollection,
     OutputCollectionUpdate, OutputDisplayType, OutputForm, OutputFormBuilder, OutputKind,
-    OutputMap, OutputRow, Repo, ResultGetParams, Sample, User,
+    OutputMap, OutputRow, Repo, ResultGetParams, Sample, SubscriptionEvent, TagProvenanceRequest,
+    User,
 };
 use crate::utils::{ApiError, Shared};
-use crate::{bad, deserialize, update, update_clear, update_opt};
+use crate::{bad, deserialize, not_found, update, update_clear, update_opt};
 
 impl<O: OutputSupport> OutputFormBuilder<O> {
     /// Adds a multipart field to our sample form
@@ -142,8 +145,25 @@ impl<O: OutputSupport> OutputFormBuilder<O> {
             .add("Results", &form.tool);
         // get the earliest each group has seen this object
         let earliest = object.earliest();
+        // these tags were set by this user
+        let origins = TagProvenanceRequest::default()
+            .resolve(user, O::tag_kind(), &key, &form.groups, shared)
+            .await?;
         // add the tags for this result
-        db::tags::create(user, key, tag_req, &earliest, shared).await?;
+        db::tags::create(user, key, tag_req, &origins, &earliest, shared).await?;
+        // let anyone following this object know about these new results
+        if let Some(target) = object.subscription_target() {
+            let msg = format!("New {} results for {}", form.tool, target);
+            db::subscriptions::notify(
+                &target,
+                SubscriptionEvent::Results,
+                &form.groups,
+                None,
+                msg,
+                shared,
+            )
+            .await;
+        }
         Ok(())
     }
 
@@ -250,6 +270,7 @@ impl OutputMap {
             files: row.files.unwrap_or_default(),
             display_type: row.display_type,
             children: row.children.unwrap_or_default(),
+            comments: Vec::default(),
         };
         // push our results
         results.push(output);
@@ -268,6 +289,43 @@ impl OutputMap {
 }
 
 impl Output {
+    /// Get a single result for a specific object
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The full key to get our result at
+    /// * `item` - The object we are getting a result for
+    /// * `user` - The user that is getting this result
+    /// * `tool` - The name of the tool this result is from
+    /// * `result_id` - The id of the result to get
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Output::get", skip(item, user, shared), err(Debug))]
+    pub async fn get<T: OutputSupport>(
+        key: &str,
+        item: &T,
+        user: &User,
+        tool: &str,
+        result_id: &Uuid,
+        shared: &Shared,
+    ) -> Result<Self, ApiError> {
+        // only get results for this tool including any hidden ones
+        let params = ResultGetParams {
+            hidden: true,
+            tools: vec![tool.to_owned()],
+            ..ResultGetParams::default()
+        };
+        // get this tools results and find the requested one
+        let mut outputs = OutputMap::get(key, item, user, params, shared).await?;
+        let found = outputs
+            .results
+            .remove(tool)
+            .and_then(|results| results.into_iter().find(|output| &output.id == result_id));
+        match found {
+            Some(output) => Ok(output),
+            None => not_found!(format!("Result {result_id} for {tool} not found")),
+        }
+    }
+
     /// Downloads a result file
     ///
     /// # Arguments
@@ -302,6 +360,25 @@ impl Output {
     }
 }
 
+impl SubscriptionSupport for Output {}
+
+impl CommentSupport for Output {
+    /// Get the key that comments on this result are saved under
+    fn comment_key(&self) -> String {
+        self.id.to_string()
+    }
+
+    /// Get the groups this result can be seen in
+    fn comment_groups(&self) -> HashSet<&str> {
+        self.groups.iter().map(String::as_str).collect()
+    }
+
+    /// Get the comments on this result
+    fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+}
+
 impl AutoTag {
     /// Update this auto tag settings object
     ///
@@ -322,11 +399,18 @@ impl OutputCollection {
     /// # Arguments
     ///
     /// * `update` - The update to apply
-    pub fn update(&mut self, update: OutputCollectionUpdate) {
+    pub fn update(&mut self, mut update: OutputCollectionUpdate) {
         update!(self.handler, update.handler);
+        // update our stream handler settings
+        update_opt!(self.stream.port, update.stream.port);
+        update_opt!(self.stream.socket, update.stream.socket);
+        update_clear!(self.stream.port, update.stream.clear_port);
+        update_clear!(self.stream.socket, update.stream.clear_socket);
         update!(self.files.results, update.files.results);
         update!(self.files.result_files, update.files.result_files);
         update!(self.files.tags, update.files.tags);
+        update!(self.files.progress, update.files.progress);
+        update!(self.files.checkpoint, update.files.checkpoint);
         update!(self.children, update.children);
         // update the names in the files handler
         self.files
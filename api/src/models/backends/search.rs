@@ -3,9 +3,13 @@
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use chrono::{DateTime, TimeZone, Utc};
+use tracing::instrument;
 
 use super::db;
-use crate::models::{ApiCursor, ElasticDoc, ElasticIndex, ElasticSearchParams, User};
+use crate::bad;
+use crate::models::{
+    ApiCursor, ElasticDoc, ElasticIndex, ElasticSearchParams, SavedSearch, SavedSearchRequest, User,
+};
 use crate::utils::{ApiError, Shared};
 
 pub mod events;
@@ -79,3 +83,88 @@ where
         }
     }
 }
+
+/// The max length of a saved search name
+const MAX_SAVED_SEARCH_NAME_LEN: usize = 128;
+
+impl SavedSearch {
+    /// Save a search for a user
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is saving this search
+    /// * `req` - The search to save
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "SavedSearch::create", skip_all, fields(user = &user.username, name = &req.name), err(Debug))]
+    pub async fn create(
+        user: &User,
+        req: SavedSearchRequest,
+        shared: &Shared,
+    ) -> Result<SavedSearch, ApiError> {
+        // make sure this name can be used in a url
+        if req.name.is_empty() {
+            return bad!("Saved searches cannot have an empty name!".to_string());
+        }
+        if req.name.len() > MAX_SAVED_SEARCH_NAME_LEN {
+            return bad!(format!(
+                "Saved search names cannot be longer then {MAX_SAVED_SEARCH_NAME_LEN} characters"
+            ));
+        }
+        if req.name.contains(['/', '?', '#', '%']) {
+            return bad!(format!(
+                "Saved search names cannot contain '/', '?', '#', or '%': {}",
+                req.name
+            ));
+        }
+        // make sure we are searching at least one index
+        if req.indexes.is_empty() {
+            return bad!("Saved searches must search at least one index!".to_string());
+        }
+        // make sure this user can search any requested groups
+        if !req.groups.is_empty() {
+            let mut groups = req.groups.clone();
+            user.authorize_groups(&mut groups, shared).await?;
+        }
+        // build and save our search
+        let saved = SavedSearch::from(req);
+        db::search::save(user, &saved, shared).await?;
+        Ok(saved)
+    }
+
+    /// Get a search that a user has saved
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose saved search to get
+    /// * `name` - The name of the saved search to get
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "SavedSearch::get", skip(user, shared), fields(user = &user.username), err(Debug))]
+    pub async fn get(user: &User, name: &str, shared: &Shared) -> Result<SavedSearch, ApiError> {
+        db::search::get_saved(user, name, shared).await
+    }
+
+    /// List all of the searches a user has saved sorted by name
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose saved searches to list
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "SavedSearch::list", skip_all, fields(user = &user.username), err(Debug))]
+    pub async fn list(user: &User, shared: &Shared) -> Result<Vec<SavedSearch>, ApiError> {
+        let mut saved = db::search::list_saved(user, shared).await?;
+        saved.sort_unstable_by(|left, right| left.name.cmp(&right.name));
+        Ok(saved)
+    }
+
+    /// Delete a search that a user has saved
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose saved search to delete
+    /// * `name` - The name of the saved search to delete
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "SavedSearch::delete", skip(user, shared), fields(user = &user.username), err(Debug))]
+    pub async fn delete(user: &User, name: &str, shared: &Shared) -> Result<(), ApiError> {
+        db::search::delete_saved(user, name, shared).await
+    }
+}
@@ -18,6 +18,7 @@ mod repos;
 mod results;
 mod s3;
 mod samples;
+mod subscriptions;
 mod tags;
 mod tools;
 
@@ -32,9 +33,11 @@ use repos::ReposPreparedStatements;
 use results::ResultsPreparedStatements;
 use s3::S3PreparedStatements;
 use samples::SamplesPreparedStatements;
+use subscriptions::SubscriptionsPreparedStatements;
 use tags::TagsPreparedStatements;
 //use tools::ToolsPreparedStatements;
 
+use crate::models::migrations;
 use crate::{setup, Conf};
 
 /// The diffferent groups of prepared statements for scylla
@@ -61,6 +64,8 @@ pub struct ScyllaPreparedStatements {
     pub s3: S3PreparedStatements,
     /// The samples related prepared statements
     pub samples: SamplesPreparedStatements,
+    /// The subscriptions related prepared statements
+    pub subscriptions: SubscriptionsPreparedStatements,
     /// The tags related prepared statements
     pub tags: TagsPreparedStatements,
 }
@@ -85,6 +90,7 @@ impl ScyllaPreparedStatements {
         let results = ResultsPreparedStatements::new(session, config).await;
         let s3 = S3PreparedStatements::new(session, config).await;
         let samples = SamplesPreparedStatements::new(session, config).await;
+        let subscriptions = SubscriptionsPreparedStatements::new(session, config).await;
         let tags = TagsPreparedStatements::new(session, config).await;
         // build our grouped prepared statement object
         ScyllaPreparedStatements {
@@ -99,6 +105,7 @@ impl ScyllaPreparedStatements {
             results,
             s3,
             samples,
+            subscriptions,
             tags,
         }
     }
@@ -223,14 +230,61 @@ async fn setup_keyspace(session: &Session, config: &Conf) {
         .expect("Failed to setup keyspace");
 }
 
+/// Setup the schema version table and check that our schema is up to date
+///
+/// Fresh keyspaces have all of their tables created at the latest schema so every migration is
+/// marked as applied. Existing keyspaces must be migrated with Thoradm.
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `config` - The Thorium config
+/// * `fresh` - Whether this keyspace had no tables before the API created them
+async fn setup_schema_version(session: &Session, config: &Conf, fresh: bool) {
+    let ns = &config.thorium.namespace;
+    // setup the schema version table
+    migrations::setup_schema_version_table(session, ns)
+        .await
+        .expect("Failed to setup schema version table");
+    // our tables were just created at the latest schema
+    if fresh {
+        migrations::stamp_latest(session, ns)
+            .await
+            .expect("Failed to set schema version");
+        return;
+    }
+    // get the current version of our schema
+    let current = migrations::get_schema_version(session, ns)
+        .await
+        .expect("Failed to get schema version")
+        .map_or(0, |row| row.version);
+    // warn if this schema still needs to be migrated
+    let latest = migrations::latest_version();
+    if current < latest {
+        setup!(
+            config.thorium.tracing.local.level,
+            format!(
+                "Scylla schema is at version {current} but version {latest} is expected, \
+                run `thoradm migrate apply` to migrate it"
+            )
+        );
+    }
+}
+
 /// Build a session and setup tables/materialized views/prepared statements
 async fn build(config: Conf) -> Scylla {
     // Create a new session for scylla
     let session = new_session(&config).await;
+    // check if this keyspace is new before we create any tables
+    let fresh = migrations::is_fresh(&session, &config.thorium.namespace)
+        .await
+        .expect("Failed to check for existing tables");
     // setup our keyspace if it doesn't already exist
     setup_keyspace(&session, &config).await;
     // get our tables/materialized views and prepared statements
     let prep = ScyllaPreparedStatements::new(&session, &config).await;
+    // track the version of our schema
+    setup_schema_version(&session, &config, fresh).await;
     // build our scylla client
     Scylla { session, prep }
 }
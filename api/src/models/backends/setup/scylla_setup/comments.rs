@@ -11,10 +11,20 @@ pub struct CommentsPreparedStatements {
     pub insert: PreparedStatement,
     /// Get a comment
     pub get: PreparedStatement,
+    /// Get the comments for many objects
+    pub get_many: PreparedStatement,
     /// Delete a comment
     pub delete: PreparedStatement,
     /// Check if a comment exists
     pub exists: PreparedStatement,
+    /// Update a comment
+    pub update: PreparedStatement,
+    /// Add reactions to a comment
+    pub add_reactions: PreparedStatement,
+    /// Remove reactions from a comment
+    pub remove_reactions: PreparedStatement,
+    /// Get the groups a comment is in
+    pub get_groups: PreparedStatement,
 }
 
 impl CommentsPreparedStatements {
@@ -32,14 +42,24 @@ impl CommentsPreparedStatements {
         // setup our prepared statements
         let insert = insert(session, config).await;
         let get = get(session, config).await;
+        let get_many = get_many(session, config).await;
         let delete = delete(session, config).await;
         let exists = exists(session, config).await;
+        let update = update(session, config).await;
+        let add_reactions = add_reactions(session, config).await;
+        let remove_reactions = remove_reactions(session, config).await;
+        let get_groups = get_groups(session, config).await;
         // build our prepared statement object
         CommentsPreparedStatements {
             insert,
             get,
+            get_many,
             delete,
             exists,
+            update,
+            add_reactions,
+            remove_reactions,
+            get_groups,
         }
     }
 }
@@ -61,6 +81,10 @@ async fn setup_comments_table(session: &Session, config: &Conf) {
             author TEXT,
             comment TEXT,
             files TEXT,
+            parent UUID,
+            edited TIMESTAMP,
+            revisions TEXT,
+            reactions SET<FROZEN<TUPLE<TEXT, TEXT>>>,
             PRIMARY KEY ((group, sha256), uploaded, id)) \
             WITH CLUSTERING ORDER BY (uploaded ASC)",
         ns = &config.thorium.namespace,
@@ -106,8 +130,8 @@ async fn insert(session: &Session, config: &Conf) -> PreparedStatement {
     session
         .prepare(format!(
             "INSERT INTO {}.comments \
-                (group, sha256, uploaded, id, author, comment, files) \
-                VALUES (?, ?, ?, ?, ?, ?, ?)",
+                (group, sha256, uploaded, id, author, comment, files, parent) \
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
             &config.thorium.namespace
         ))
         .await
@@ -124,7 +148,8 @@ async fn get(session: &Session, config: &Conf) -> PreparedStatement {
     // build comments insert prepared statement
     session
         .prepare(format!(
-            "SELECT group, sha256, uploaded, id, author, comment, files \
+            "SELECT group, sha256, uploaded, id, author, comment, files, parent, edited, \
+                revisions, reactions \
                 FROM {}.comments \
                 WHERE group IN ? AND sha256 = ?",
             &config.thorium.namespace
@@ -133,6 +158,26 @@ async fn get(session: &Session, config: &Conf) -> PreparedStatement {
         .expect("Failed to prepare scylla comments get statement")
 }
 
+/// Gets all comments for many objects from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn get_many(session: &Session, config: &Conf) -> PreparedStatement {
+    // build comments get many prepared statement
+    session
+        .prepare(format!(
+            "SELECT group, sha256, uploaded, id, author, comment, files, parent, edited, \
+                revisions, reactions \
+                FROM {}.comments \
+                WHERE group IN ? AND sha256 IN ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla comments get many statement")
+}
+
 /// Deletes a comment row from scylla
 ///
 /// # Arguments
@@ -173,3 +218,94 @@ async fn exists(session: &Session, config: &Conf) -> PreparedStatement {
         .await
         .expect("Failed to prepare scylla comment exists statement")
 }
+
+/// Updates the text and revisions for a comment row in scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn update(session: &Session, config: &Conf) -> PreparedStatement {
+    // build comment update prepared statement
+    session
+        .prepare(format!(
+            "UPDATE {}.comments \
+                SET comment = ?, edited = ?, revisions = ? \
+                WHERE group = ? \
+                AND sha256 = ? \
+                AND uploaded = ? \
+                AND id = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla comment update statement")
+}
+
+/// Adds reactions to a comment row in scylla
+///
+/// Reactions are added to a set so concurrent reactions don't overwrite each other. The
+/// comment must still exist so a reaction can't recreate a deleted comment.
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn add_reactions(session: &Session, config: &Conf) -> PreparedStatement {
+    // build comment add reactions prepared statement
+    session
+        .prepare(format!(
+            "UPDATE {}.comments \
+                SET reactions = reactions + ? \
+                WHERE group = ? \
+                AND sha256 = ? \
+                AND uploaded = ? \
+                AND id = ? \
+                IF EXISTS",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla comment add reactions statement")
+}
+
+/// Removes reactions from a comment row in scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn remove_reactions(session: &Session, config: &Conf) -> PreparedStatement {
+    // build comment remove reactions prepared statement
+    session
+        .prepare(format!(
+            "UPDATE {}.comments \
+                SET reactions = reactions - ? \
+                WHERE group = ? \
+                AND sha256 = ? \
+                AND uploaded = ? \
+                AND id = ? \
+                IF EXISTS",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla comment remove reactions statement")
+}
+
+/// Gets all of the groups a comment is in from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn get_groups(session: &Session, config: &Conf) -> PreparedStatement {
+    // build comment get groups prepared statement
+    session
+        .prepare(format!(
+            "SELECT group \
+                FROM {}.comments_by_id \
+                WHERE id = ? \
+                AND sha256 = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla comment get groups statement")
+}
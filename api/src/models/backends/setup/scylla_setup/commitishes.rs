@@ -15,6 +15,10 @@ pub struct CommitishesPreparedStatements {
     pub get_data: PreparedStatement,
     /// Check if a commitish exists
     pub exists: PreparedStatement,
+    /// Check which of many commitishes exist
+    pub exists_many: PreparedStatement,
+    /// Get the data for many commitishes
+    pub get_data_many: PreparedStatement,
     /// Get the repo data from a commitish
     pub get_repo_data: PreparedStatement,
     /// Get the number of repo datas that have commits tied to them
@@ -43,6 +47,8 @@ impl CommitishesPreparedStatements {
         let insert_list = insert_list(session, config).await;
         let get_data = get_data(session, config).await;
         let exists = exists(session, config).await;
+        let exists_many = exists_many(session, config).await;
+        let get_data_many = get_data_many(session, config).await;
         let get_repo_data = get_repo_data(session, config).await;
         let get_repo_data_count = get_repo_data_count(session, config).await;
         let list_ties = list_ties(session, config).await;
@@ -53,6 +59,8 @@ impl CommitishesPreparedStatements {
             insert_list,
             get_data,
             exists,
+            exists_many,
+            get_data_many,
             get_repo_data,
             get_repo_data_count,
             list_ties,
@@ -221,6 +229,44 @@ async fn exists(session: &Session, config: &Conf) -> PreparedStatement {
         .expect("Failed to prepare scylla commitish exists get statement")
 }
 
+/// build the commitish exists many get prepared statement
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn exists_many(session: &Session, config: &Conf) -> PreparedStatement {
+    // build commitish exists many get prepared statement
+    session
+        .prepare(format!(
+            "SELECT key \
+                FROM {}.commitishes \
+                WHERE kind = ? AND group IN ? AND repo = ? AND key IN ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla commitish exists many get statement")
+}
+
+/// build the commitish details get many prepared statement
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn get_data_many(session: &Session, config: &Conf) -> PreparedStatement {
+    // build the commitish details get many prepared statement
+    session
+        .prepare(format!(
+            "SELECT key, data \
+                FROM {}.commitishes \
+                WHERE kind = ? AND group IN ? AND repo = ? AND key IN ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla commitish details get many statement")
+}
+
 /// build the commit repo data get prepared statement
 ///
 /// # Arguments
@@ -17,6 +17,8 @@ pub struct NodesPreparedStatements {
     pub update: PreparedStatement,
     /// Update the heart beat value for a node
     pub update_heart_beat: PreparedStatement,
+    /// Update the architectures for a node
+    pub update_arch: PreparedStatement,
     /// Get the ties when listing nodes
     pub list_ties: PreparedStatement,
     /// Get all the nodes for a specific clustser
@@ -43,6 +45,7 @@ impl NodesPreparedStatements {
         let get_many = get_many(session, config).await;
         let update = update(session, config).await;
         let update_heart_beat = update_heart_beat(session, config).await;
+        let update_arch = update_arch(session, config).await;
         let list_ties = list_ties(session, config).await;
         let list = list(session, config).await;
         let list_details_ties = list_details_ties(session, config).await;
@@ -54,6 +57,7 @@ impl NodesPreparedStatements {
             get_many,
             update,
             update_heart_beat,
+            update_arch,
             list_ties,
             list,
             list_details_ties,
@@ -79,6 +83,8 @@ async fn setup_node_table(session: &Session, config: &Conf) {
             health TEXT,
             resources TEXT,
             heart_beat TIMESTAMP,
+            arch TEXT,
+            emulated TEXT,
             PRIMARY KEY ((cluster), node))",
         ns = &config.thorium.namespace,
     );
@@ -99,8 +105,8 @@ async fn insert(session: &Session, config: &Conf) -> PreparedStatement {
     session
         .prepare(format!(
             "INSERT INTO {}.nodes \
-                (cluster, node, health, resources) \
-                VALUES (?, ?, ?, ?)",
+                (cluster, node, health, resources, arch, emulated) \
+                VALUES (?, ?, ?, ?, ?, ?)",
             &config.thorium.namespace
         ))
         .await
@@ -117,7 +123,7 @@ async fn get(session: &Session, config: &Conf) -> PreparedStatement {
     // build node get prepared statement
     session
         .prepare(format!(
-            "SELECT cluster, node, health, resources, heart_beat \
+            "SELECT cluster, node, health, resources, heart_beat, arch, emulated \
                 FROM {}.nodes \
                 WHERE cluster = ? AND node = ?",
             &config.thorium.namespace
@@ -136,7 +142,7 @@ async fn get_many(session: &Session, config: &Conf) -> PreparedStatement {
     // build node get many prepared statement
     session
         .prepare(format!(
-            "SELECT cluster, node, health, resources, heart_beat \
+            "SELECT cluster, node, health, resources, heart_beat, arch, emulated \
                 FROM {}.nodes \
                 WHERE cluster in ? AND node in ?",
             &config.thorium.namespace
@@ -183,6 +189,25 @@ async fn update_heart_beat(session: &Session, config: &Conf) -> PreparedStatemen
         .expect("Failed to prepare scylla node heart beat update statement")
 }
 
+/// build the node architecture update prepared statement
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn update_arch(session: &Session, config: &Conf) -> PreparedStatement {
+    // build node update arch prepared statement
+    session
+        .prepare(format!(
+            "UPDATE {}.nodes \
+                SET arch = ?, emulated = ? \
+                WHERE cluster = ? AND node = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla node arch update statement")
+}
+
 /// build the node list ties prepared statement
 ///
 /// # Arguments
@@ -233,7 +258,7 @@ async fn list_details_ties(session: &Session, config: &Conf) -> PreparedStatemen
     // build node list details ties prepared statement
     session
         .prepare(format!(
-            "SELECT cluster, node, health, resources, heart_beat \
+            "SELECT cluster, node, health, resources, heart_beat, arch, emulated \
                 FROM {}.nodes \
                 WHERE cluster = ? AND node > ? \
                 LIMIT ?",
@@ -253,7 +278,7 @@ async fn list_details(session: &Session, config: &Conf) -> PreparedStatement {
     // build node list details prepared statement
     session
         .prepare(format!(
-            "SELECT cluster, node, health, resources, heart_beat \
+            "SELECT cluster, node, health, resources, heart_beat, arch, emulated \
                 FROM {}.nodes \
                 WHERE cluster = ? \
                 LIMIT ?",
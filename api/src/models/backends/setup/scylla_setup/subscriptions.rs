@@ -0,0 +1,187 @@
+//! Setup the subscriptions tables/prepared statements in Scylla
+
+use scylla::client::session::Session;
+use scylla::statement::prepared::PreparedStatement;
+
+use crate::Conf;
+
+/// The prepared statments for subscriptions
+pub struct SubscriptionsPreparedStatements {
+    /// Insert a new subscription
+    pub insert: PreparedStatement,
+    /// Get all subscriptions to a specific target
+    pub get_target: PreparedStatement,
+    /// List all of a users subscriptions
+    pub list: PreparedStatement,
+    /// Get a specific subscription for a user
+    pub get: PreparedStatement,
+    /// Delete a subscription
+    pub delete: PreparedStatement,
+}
+
+impl SubscriptionsPreparedStatements {
+    /// Build a new subscriptions prepared statement struct
+    ///
+    /// # Arguments
+    ///
+    /// * `sessions` - The scylla session to use
+    /// * `config` - The Thorium config
+    pub async fn new(session: &Session, config: &Conf) -> Self {
+        // setup the subscriptions table
+        setup_subscriptions_table(session, config).await;
+        // setup our prepared statements
+        let insert = insert(session, config).await;
+        let get_target = get_target(session, config).await;
+        let list = list(session, config).await;
+        let get = get(session, config).await;
+        let delete = delete(session, config).await;
+        // build our prepared statement object
+        SubscriptionsPreparedStatements {
+            insert,
+            get_target,
+            list,
+            get,
+            delete,
+        }
+    }
+}
+
+/// Setup the subscriptions table for Thorium
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `config` - The Thorium config
+async fn setup_subscriptions_table(session: &Session, config: &Conf) {
+    // build cmd for table insert
+    let table_create = format!(
+        "CREATE TABLE IF NOT EXISTS {ns}.subscriptions (\
+            kind TEXT, \
+            key TEXT, \
+            username TEXT, \
+            id UUID, \
+            target TEXT, \
+            events TEXT, \
+            email BOOLEAN, \
+            created TIMESTAMP, \
+            PRIMARY KEY ((kind, key), username, id))",
+        ns = &config.thorium.namespace,
+    );
+    session
+        .query_unpaged(table_create, &[])
+        .await
+        .expect("failed to add subscriptions table");
+    // build the cmd for the materialized view of each users subscriptions
+    let table_create = format!(
+        "CREATE MATERIALIZED VIEW IF NOT EXISTS {ns}.subscriptions_by_user AS \
+            SELECT kind, key, username, id, target, events, email, created \
+            FROM {ns}.subscriptions \
+            WHERE kind IS NOT NULL \
+            AND key IS NOT NULL \
+            AND username IS NOT NULL \
+            AND id IS NOT NULL \
+            PRIMARY KEY (username, id, kind, key)",
+        ns = &config.thorium.namespace,
+    );
+    session
+        .query_unpaged(table_create, &[])
+        .await
+        .expect("failed to add subscriptions materialized view");
+}
+
+/// Inserts a new subscription into scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn insert(session: &Session, config: &Conf) -> PreparedStatement {
+    // build subscription insert prepared statement
+    session
+        .prepare(format!(
+            "INSERT INTO {}.subscriptions \
+                (kind, key, username, id, target, events, email, created) \
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla subscription insert statement")
+}
+
+/// Gets all subscriptions to a specific target from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn get_target(session: &Session, config: &Conf) -> PreparedStatement {
+    // build subscriptions get target prepared statement
+    session
+        .prepare(format!(
+            "SELECT username, id, target, events, email, created \
+                FROM {}.subscriptions \
+                WHERE kind = ? AND key = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla subscriptions get target statement")
+}
+
+/// Lists all of a users subscriptions from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn list(session: &Session, config: &Conf) -> PreparedStatement {
+    // build subscriptions list prepared statement
+    session
+        .prepare(format!(
+            "SELECT username, id, target, events, email, created \
+                FROM {}.subscriptions_by_user \
+                WHERE username = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla subscriptions list statement")
+}
+
+/// Gets a specific subscription for a user from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn get(session: &Session, config: &Conf) -> PreparedStatement {
+    // build subscription get prepared statement
+    session
+        .prepare(format!(
+            "SELECT username, id, target, events, email, created \
+                FROM {}.subscriptions_by_user \
+                WHERE username = ? AND id = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla subscription get statement")
+}
+
+/// Deletes a specific subscription
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn delete(session: &Session, config: &Conf) -> PreparedStatement {
+    // build subscription delete prepared statement
+    session
+        .prepare(format!(
+            "DELETE FROM {}.subscriptions \
+                WHERE kind = ? \
+                AND key = ? \
+                AND username = ? \
+                AND id = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla subscription delete statement")
+}
@@ -23,6 +23,12 @@ pub struct TagsPreparedStatements {
     pub list_ties_case_insensitive: PreparedStatement,
     /// Pull tag rows for a specific cursor page regardless of key/value case
     pub list_pull_case_insensitive: PreparedStatement,
+    /// Insert the provenance of a tag
+    pub insert_provenance: PreparedStatement,
+    /// Get the provenance of all tags for an item
+    pub get_provenance: PreparedStatement,
+    /// Delete the provenance of a tag
+    pub delete_provenance: PreparedStatement,
 }
 
 impl TagsPreparedStatements {
@@ -38,6 +44,8 @@ impl TagsPreparedStatements {
         // setup the tags materialized view
         setup_tags_by_item_mat_view(session, config).await;
         setup_tags_case_insensitve_mat_view(session, config).await;
+        // setup the tag provenance table
+        setup_tag_provenance_table(session, config).await;
         // setup our prepared statements
         let insert = insert(session, config).await;
         let get = get(session, config).await;
@@ -47,6 +55,9 @@ impl TagsPreparedStatements {
         let list_pull = list_pull(session, config).await;
         let list_ties_case_insensitive = list_ties_case_insensitive(session, config).await;
         let list_pull_case_insensitive = list_pull_case_insensitive(session, config).await;
+        let insert_provenance = insert_provenance(session, config).await;
+        let get_provenance = get_provenance(session, config).await;
+        let delete_provenance = delete_provenance(session, config).await;
         // build our prepared statement object
         TagsPreparedStatements {
             insert,
@@ -57,6 +68,9 @@ impl TagsPreparedStatements {
             list_pull,
             list_ties_case_insensitive,
             list_pull_case_insensitive,
+            insert_provenance,
+            get_provenance,
+            delete_provenance,
         }
     }
 }
@@ -154,6 +168,37 @@ async fn setup_tags_case_insensitve_mat_view(session: &Session, config: &Conf) {
         .expect("failed to add tags case-insensitive materialized view");
 }
 
+/// Setup the tag provenance table for Thorium
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `config` - The Thorium config
+async fn setup_tag_provenance_table(session: &Session, config: &Conf) {
+    // build cmd for tag provenance table
+    let table_create = format!(
+        "CREATE TABLE IF NOT EXISTS {ns}.tag_provenance (\
+            type TEXT, \
+            item TEXT, \
+            group TEXT, \
+            key TEXT, \
+            value TEXT, \
+            source TEXT, \
+            username TEXT, \
+            tool TEXT, \
+            tool_version TEXT, \
+            result UUID, \
+            confidence FLOAT, \
+            created TIMESTAMP, \
+            PRIMARY KEY ((type, item), group, key, value, source, tool))",
+        ns = &config.thorium.namespace,
+    );
+    session
+        .query_unpaged(table_create, &[])
+        .await
+        .expect("failed to add tag provenance table");
+}
+
 /// build the tags insert prepared statement
 ///
 /// # Arguments
@@ -340,3 +385,63 @@ async fn list_pull_case_insensitive(session: &Session, config: &Conf) -> Prepare
         .await
         .expect("Failed to prepare scylla list tag pull case insensitive statement")
 }
+
+/// Inserts the provenance of a tag into scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn insert_provenance(session: &Session, config: &Conf) -> PreparedStatement {
+    // build tag provenance insert prepared statement
+    session
+        .prepare(format!(
+            "INSERT INTO {}.tag_provenance \
+                (type, item, group, key, value, source, username, tool, tool_version, result, confidence, created) \
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla tag provenance insert statement")
+}
+
+/// Gets the provenance of all tags for an item from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn get_provenance(session: &Session, config: &Conf) -> PreparedStatement {
+    // build tag provenance get prepared statement
+    session
+        .prepare(format!(
+            "SELECT group, key, value, source, username, tool, tool_version, result, confidence, created \
+                FROM {}.tag_provenance \
+                WHERE type = ? AND item = ? AND group IN ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla tag provenance get statement")
+}
+
+/// Deletes the provenance of a tag from scylla
+///
+/// # Arguments
+///
+/// * `sessions` - The scylla session to use
+/// * `conf` - The Thorium config
+async fn delete_provenance(session: &Session, config: &Conf) -> PreparedStatement {
+    // build tag provenance delete prepared statement
+    session
+        .prepare(format!(
+            "DELETE FROM {}.tag_provenance \
+                WHERE type = ? \
+                AND item = ? \
+                AND group = ? \
+                AND key = ? \
+                AND value = ?",
+            &config.thorium.namespace
+        ))
+        .await
+        .expect("Failed to prepare scylla tag provenance delete statement")
+}
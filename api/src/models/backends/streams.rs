@@ -6,6 +6,7 @@ use chrono::prelude::*;
 use tracing::{instrument, span, Level, Span};
 
 use super::db;
+use crate::models::deadlines::priority_stream_data;
 use crate::models::{Deadline, Group, RawJob, Stream, StreamDepth, StreamObj, User};
 use crate::utils::{ApiError, Shared};
 use crate::{at_least, bad};
@@ -19,13 +20,14 @@ impl From<Deadline> for StreamObj {
     fn from(deadline: Deadline) -> Self {
         // cast deadline data to stream data without timestamp
         // were using a format macro so we get a consistent order
-        let data = format!("{{\"group\":\"{}\",\"pipeline\":\"{}\",\"stage\":\"{}\",\"creator\":\"{}\",\"job_id\":\"{}\",\"reaction\":\"{}\"}}",
+        let data = format!("{{\"group\":\"{}\",\"pipeline\":\"{}\",\"stage\":\"{}\",\"creator\":\"{}\",\"job_id\":\"{}\",\"reaction\":\"{}\"{}}}",
             deadline.group,
             deadline.pipeline,
             deadline.stage,
             deadline.creator,
             deadline.job_id,
-            deadline.reaction);
+            deadline.reaction,
+            priority_stream_data(deadline.priority));
 
         // cast to stream object
         StreamObj {
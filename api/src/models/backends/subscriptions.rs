@@ -0,0 +1,86 @@
+//! Handles subscriptions to things in Thorium in the backend
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::db;
+use crate::models::{
+    Pipeline, Repo, Sample, Subscription, SubscriptionRequest, SubscriptionTarget, User,
+};
+use crate::utils::{ApiError, Shared};
+use crate::{bad, conflict, not_found};
+
+impl Subscription {
+    /// Subscribe a user to something in Thorium
+    ///
+    /// Users can only subscribe to samples, repos, and pipelines they can see.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is subscribing
+    /// * `req` - The subscription request
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Subscription::create", skip_all, fields(user = &user.username), err(Debug))]
+    pub async fn create(
+        user: &User,
+        req: SubscriptionRequest,
+        shared: &Shared,
+    ) -> Result<Subscription, ApiError> {
+        // make sure this user can see what they are subscribing to
+        match &req.target {
+            SubscriptionTarget::Sample { sha256 } => {
+                Sample::get(user, sha256, shared).await?;
+            }
+            SubscriptionTarget::Repo { url } => {
+                Repo::get(user, url, shared).await?;
+            }
+            SubscriptionTarget::Tag { key, value } => {
+                if key.is_empty() || value.is_empty() {
+                    return bad!("Tag subscriptions must have a key and a value!".to_string());
+                }
+            }
+            SubscriptionTarget::Pipeline { group, pipeline } => {
+                Pipeline::get(user, group, pipeline, shared).await?;
+            }
+        }
+        // don't let users subscribe to the same thing twice
+        let existing = db::subscriptions::list(&user.username, shared).await?;
+        if let Some(sub) = existing.iter().find(|sub| sub.target == req.target) {
+            return conflict!(format!(
+                "Already subscribed to {} with subscription {}",
+                req.target, sub.id
+            ));
+        }
+        // build and save our subscription
+        let subscription = Subscription::new(&user.username, req);
+        db::subscriptions::create(&subscription, shared).await?;
+        Ok(subscription)
+    }
+
+    /// List all of a users subscriptions
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose subscriptions to list
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Subscription::list", skip_all, fields(user = &user.username), err(Debug))]
+    pub async fn list(user: &User, shared: &Shared) -> Result<Vec<Subscription>, ApiError> {
+        db::subscriptions::list(&user.username, shared).await
+    }
+
+    /// Delete one of a users subscriptions
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose subscription to delete
+    /// * `id` - The id of the subscription to delete
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "Subscription::delete", skip(user, shared), fields(user = &user.username), err(Debug))]
+    pub async fn delete(user: &User, id: &Uuid, shared: &Shared) -> Result<(), ApiError> {
+        // get this subscription if it exists
+        match db::subscriptions::get(&user.username, id, shared).await? {
+            Some(subscription) => db::subscriptions::delete(&subscription, shared).await,
+            None => not_found!(format!("Subscription {id} not found")),
+        }
+    }
+}
@@ -2,8 +2,10 @@
 
 mod notifications;
 mod outputs;
+mod subscriptions;
 mod tags;
 
 pub use notifications::NotificationSupport;
 pub use outputs::OutputSupport;
+pub use subscriptions::SubscriptionSupport;
 pub use tags::TagSupport;
@@ -1,5 +1,5 @@
 //! Support for interacting with outputs (results)
-use super::TagSupport;
+use super::{SubscriptionSupport, TagSupport};
 use crate::models::KeySupport;
 use crate::models::{OutputKind, TagRequest};
 
@@ -20,7 +20,7 @@ cfg_if::cfg_if! {
 }
 
 /// The trait for results support in Thorium
-pub trait OutputSupport: TagSupport + KeySupport {
+pub trait OutputSupport: TagSupport + KeySupport + SubscriptionSupport {
     /// Get the tag kind to write to the DB
     fn output_kind() -> OutputKind;
 
@@ -0,0 +1,10 @@
+//! Support for notifying users subscribed to an entity
+use crate::models::SubscriptionTarget;
+
+/// Describes an entity that users can subscribe to for notifications
+pub trait SubscriptionSupport {
+    /// Get the target users subscribe to in order to follow this entity if it has one
+    fn subscription_target(&self) -> Option<SubscriptionTarget> {
+        None
+    }
+}
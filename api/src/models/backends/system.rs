@@ -1,4 +1,5 @@
 use axum::extract::FromRequestParts;
+use axum::http::StatusCode;
 use axum::http::request::Parts;
 use futures::TryStreamExt;
 use futures::stream::{self, StreamExt};
@@ -7,6 +8,7 @@ use scylla::errors::ExecutionError;
 use scylla::response::query_result::QueryResult;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::{Level, Span, instrument, span};
 use uuid::Uuid;
 
@@ -16,14 +18,14 @@ use crate::models::{
     ApiCursor, Backup, Group, GroupRequest, GroupUsersRequest, HostPath, HostPathWhitelistUpdate,
     Image, ImageBan, ImageBanKind, ImageBanUpdate, ImageKey, ImageScaler, Node, NodeGetParams,
     NodeListLine, NodeListParams, NodeRegistration, NodeRow, NodeUpdate, Pipeline, PipelineBan,
-    PipelineBanKind, PipelineBanUpdate, PipelineKey, SystemInfo, SystemSettings,
+    PipelineBanKind, PipelineBanUpdate, PipelineKey, ResponseCounts, SystemInfo, SystemSettings,
     SystemSettingsUpdate, SystemStats, User, VolumeTypes, Worker, WorkerDeleteMap,
     WorkerRegistrationList, WorkerUpdate, conversions,
 };
 use crate::utils::{ApiError, Shared};
 use crate::{
-    bad, deserialize, deserialize_ext, extract, is_admin, log_scylla_err, not_found, unauthorized,
-    update,
+    bad, deserialize, deserialize_ext, deserialize_opt, extract, is_admin, log_scylla_err,
+    not_found, unauthorized, update,
 };
 
 /// Check if Thorium is healthy
@@ -37,6 +39,35 @@ pub async fn health(shared: &Shared) -> Result<bool, ApiError> {
     db::system::health(shared).await
 }
 
+/// The total number of responses this API instance has sent
+static RESPONSES: AtomicU64 = AtomicU64::new(0);
+
+/// The number of server error responses this API instance has sent
+static SERVER_ERRORS: AtomicU64 = AtomicU64::new(0);
+
+impl ResponseCounts {
+    /// Count a response sent by this API instance
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status code of the response that was sent
+    pub fn record(status: StatusCode) {
+        RESPONSES.fetch_add(1, Ordering::Relaxed);
+        if status.is_server_error() {
+            SERVER_ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the number of responses this API instance has sent so far
+    #[must_use]
+    pub fn current() -> Self {
+        ResponseCounts {
+            total: RESPONSES.load(Ordering::Relaxed),
+            server_errors: SERVER_ERRORS.load(Ordering::Relaxed),
+        }
+    }
+}
+
 /// Returns a string denoting this server as a Thorium server
 ///
 /// # Arguments
@@ -694,6 +725,9 @@ impl TryFrom<NodeRow> for Node {
         let health = deserialize!(&row.health);
         // build our resources objects
         let resources = deserialize!(&row.resources);
+        // nodes registered before architectures were tracked are x86_64 nodes without emulation
+        let arch = deserialize_opt!(row.arch.as_ref()).unwrap_or_default();
+        let emulated = deserialize_opt!(row.emulated.as_ref()).unwrap_or_default();
         // build our node struct
         let node = Node {
             cluster: row.cluster,
@@ -702,6 +736,8 @@ impl TryFrom<NodeRow> for Node {
             resources,
             workers: HashMap::default(),
             heart_beat: row.heart_beat,
+            arch,
+            emulated,
         };
         Ok(node)
     }
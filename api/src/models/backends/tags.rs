@@ -0,0 +1,630 @@
+//! Handles bulk tag operations in the backend
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use chrono::prelude::*;
+use std::sync::Arc;
+use tracing::{event, instrument, Level};
+use uuid::Uuid;
+
+use super::db;
+use super::db::ScyllaCursor;
+use crate::models::backends::TagSupport;
+use crate::models::{
+    BulkTagJob, BulkTagOperation, BulkTagRequest, BulkTagStatus, FileListParams, Group, Image,
+    JobStatus, RawJob, Repo, RepoListLine, RepoListParams, Sample, SampleListLine,
+    TagDeleteRequest, TagProvenance, TagProvenanceParams, TagProvenanceRequest, TagRequest,
+    TagSource, TagType, User,
+};
+use crate::utils::{ApiError, Shared};
+use crate::{bad, conflict, not_found};
+
+/// The number of items to modify between each checkpoint of a bulk tag job
+const BATCH_SIZE: usize = 100;
+
+/// The max number of errors to retain for a single bulk tag job
+const MAX_ERRORS: usize = 100;
+
+/// How often a running bulk tag job checks in
+const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long a bulk tag job can go without checking in before it is considered orphaned in seconds
+const ORPHAN_TIMEOUT: i64 = 300;
+
+impl BulkTagRequest {
+    /// Make sure this bulk tag request is valid
+    fn validate(&self) -> Result<(), ApiError> {
+        // bulk operations must be limited to specific groups
+        if self.groups.is_empty() {
+            return bad!("At least one group must be specified!".to_owned());
+        }
+        // make sure none of our keys or values are empty and that we actually change something
+        let (fields, changes) = match &self.operation {
+            BulkTagOperation::RenameKey { from, to } => (vec![from, to], from != to),
+            BulkTagOperation::ReplaceValue { key, from, to } => (vec![key, from, to], from != to),
+            BulkTagOperation::Delete { key, value } => (vec![key, value], true),
+        };
+        if fields.iter().any(|field| field.is_empty()) {
+            return bad!("Tag keys and values cannot be empty!".to_owned());
+        }
+        if !changes {
+            return bad!("The new tag must be different from the old tag!".to_owned());
+        }
+        Ok(())
+    }
+
+    /// Make sure the tag this request replaces tags with is allowed in each group
+    ///
+    /// Replacement tags are rewritten to their canonical form when they are added so a
+    /// replacement that is rewritten back to the tag being replaced would be deleted
+    /// right after being added.
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups this request will modify tags in
+    fn check_vocabularies(&self, groups: &[Group]) -> Result<(), ApiError> {
+        for group in groups {
+            let vocab = &group.tag_vocabulary;
+            match &self.operation {
+                BulkTagOperation::RenameKey { from, to } => {
+                    // values differ between tags so we can only check our new key
+                    let canonical = vocab.canonical_key(to);
+                    if &canonical == from {
+                        return bad!(format!(
+                            "{to} is rewritten to {from} in group {}!",
+                            group.name
+                        ));
+                    }
+                    if vocab.strict && !vocab.keys.contains_key(&canonical) {
+                        return bad!(format!(
+                            "{canonical} is not an allowed tag key in group {}",
+                            group.name
+                        ));
+                    }
+                }
+                BulkTagOperation::ReplaceValue { key, from, to } => {
+                    let (new_key, new_value) = vocab.normalize(key, to);
+                    if &new_key == key && &new_value == from {
+                        return bad!(format!(
+                            "{key}={to} is rewritten to {key}={from} in group {}!",
+                            group.name
+                        ));
+                    }
+                    // make sure our canonical replacement is allowed in this group
+                    let regexes = vocab.compile_regexes()?;
+                    vocab.check(&group.name, &regexes, &new_key, &new_value)?;
+                }
+                BulkTagOperation::Delete { .. } => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TagProvenanceRequest {
+    /// Make sure this tag provenance request is valid
+    pub fn validate(&self) -> Result<(), ApiError> {
+        // make sure any confidence is between 0 and 1
+        match self.confidence {
+            Some(confidence) if !(0.0..=1.0).contains(&confidence) => bad!(format!(
+                "Tag confidence must be between 0 and 1 not {confidence}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Determine where the tags in a request came from in each of their groups
+    ///
+    /// Tags are only attributed to a tool when they are set by one of this user's running
+    /// jobs and the tool and its version always come from that job's image. Tags that keep
+    /// the provenance of an existing tag reuse it in any group that tag is in.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is setting these tags
+    /// * `kind` - The type of item these tags are on
+    /// * `item` - The item these tags are on
+    /// * `groups` - The groups these tags are being set in
+    /// * `shared` - Shared Thorium objects
+    #[instrument(
+        name = "TagProvenanceRequest::resolve",
+        skip(self, user, shared),
+        err(Debug)
+    )]
+    pub async fn resolve(
+        &self,
+        user: &User,
+        kind: TagType,
+        item: &str,
+        groups: &[String],
+        shared: &Shared,
+    ) -> Result<Vec<TagProvenance>, ApiError> {
+        // make sure this request is valid
+        self.validate()?;
+        let mut origins = Vec::with_capacity(groups.len());
+        // reuse the provenance of the tag we are replacing if it has any
+        if let Some((key, value)) = &self.keep {
+            let existing = db::tags::get_provenance(kind, groups, item, shared).await?;
+            origins.extend(
+                existing
+                    .into_iter()
+                    .filter(|prov| &prov.key == key && &prov.value == value),
+            );
+        }
+        // determine what set these tags
+        let (source, tool, tool_version) = match &self.job {
+            Some(id) => {
+                // make sure this job is one of our running jobs
+                let (_, job) = RawJob::get(user, id, shared).await?;
+                if job.creator != user.username || job.status != JobStatus::Running {
+                    return bad!(format!("Job {id} is not one of your running jobs"));
+                }
+                // get the version of the image that ran this job
+                let (_, image) = Image::get(user, &job.group, &job.stage, shared).await?;
+                (TagSource::Tool, Some(job.stage), image.version)
+            }
+            None => (TagSource::User, None, None),
+        };
+        // build the provenance for any groups we didn't reuse provenance in
+        let now = Utc::now();
+        for group in groups {
+            if origins.iter().any(|prov| &prov.group == group) {
+                continue;
+            }
+            origins.push(TagProvenance {
+                group: group.clone(),
+                key: String::default(),
+                value: String::default(),
+                source,
+                user: user.username.clone(),
+                tool: tool.clone(),
+                tool_version: tool_version.clone(),
+                result: self.result,
+                confidence: self.confidence,
+                timestamp: now,
+            });
+        }
+        Ok(origins)
+    }
+}
+
+impl BulkTagJob {
+    /// Create and start a new bulk tag job
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is creating this bulk tag job
+    /// * `req` - The bulk tag operation to run
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "BulkTagJob::create", skip(user, shared), err(Debug))]
+    pub async fn create(
+        user: &User,
+        req: BulkTagRequest,
+        shared: &Arc<Shared>,
+    ) -> Result<BulkTagJob, ApiError> {
+        // make sure this request is valid
+        req.validate()?;
+        // make sure we can modify data in all of the requested groups
+        let groups = Group::authorize_all(user, &req.groups, shared).await?;
+        for group in &groups {
+            group.modifiable(user)?;
+        }
+        // make sure our replacement tags are allowed in these groups
+        req.check_vocabularies(&groups)?;
+        // build and save our new job
+        let job = BulkTagJob::new(&user.username, req);
+        db::tags::save_bulk_job(&job, shared).await?;
+        // run this job in the background
+        let bg_user = user.clone();
+        let bg_job = job.clone();
+        let bg_shared = shared.clone();
+        tokio::spawn(async move {
+            // keep this jobs heartbeat fresh until it finishes
+            let heartbeat = tokio::spawn(BulkTagJob::heartbeat(bg_job.id, bg_shared.clone()));
+            bg_job.run(&bg_user, &bg_shared).await;
+            heartbeat.abort();
+        });
+        Ok(job)
+    }
+
+    /// Periodically check in for a running bulk tag job
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the bulk tag job that is running
+    /// * `shared` - Shared Thorium objects
+    async fn heartbeat(id: Uuid, shared: Arc<Shared>) {
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(error) = db::tags::bulk_job_heartbeat(&id, &shared).await {
+                event!(
+                    Level::ERROR,
+                    msg = "Failed to update bulk tag job heartbeat",
+                    id = id.to_string(),
+                    error = error.to_string()
+                );
+            }
+        }
+    }
+
+    /// Check if this job was abandoned by the API that was running it
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    fn is_orphaned(&self, now: DateTime<Utc>) -> bool {
+        // jobs that have never checked in are judged by when they were created
+        let last_seen = self.heartbeat.unwrap_or(self.created);
+        !self.is_finished() && (now - last_seen).num_seconds() > ORPHAN_TIMEOUT
+    }
+
+    /// Fail this job if the API running it stopped checking in
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared Thorium objects
+    async fn reap(mut self, shared: &Shared) -> Result<Self, ApiError> {
+        let now = Utc::now();
+        if self.is_orphaned(now) {
+            self.error("Bulk tag job was orphaned by the API running it".to_owned());
+            self.status = BulkTagStatus::Failed;
+            self.finished = Some(now);
+            db::tags::save_bulk_job(&self, shared).await?;
+        }
+        Ok(self)
+    }
+
+    /// Get a bulk tag job by id
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is getting this bulk tag job
+    /// * `id` - The id of the bulk tag job to get
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "BulkTagJob::get", skip(user, shared), err(Debug))]
+    pub async fn get(user: &User, id: &Uuid, shared: &Shared) -> Result<BulkTagJob, ApiError> {
+        // only admins and the creator of a job can see it
+        match db::tags::get_bulk_job(id, shared).await? {
+            Some(job) if user.is_admin() || job.creator == user.username => job.reap(shared).await,
+            _ => not_found!(format!("Bulk tag job {id} not found")),
+        }
+    }
+
+    /// List bulk tag jobs from newest to oldest
+    ///
+    /// Admins can see all jobs while other users can only see their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is listing bulk tag jobs
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "BulkTagJob::list", skip_all, err(Debug))]
+    pub async fn list(user: &User, shared: &Shared) -> Result<Vec<BulkTagJob>, ApiError> {
+        let mut jobs = db::tags::list_bulk_jobs(shared).await?;
+        // only keep our own jobs if we are not an admin
+        if !user.is_admin() {
+            jobs.retain(|job| job.creator == user.username);
+        }
+        // fail any jobs whose API stopped running them
+        let mut reaped = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            reaped.push(job.reap(shared).await?);
+        }
+        Ok(reaped)
+    }
+
+    /// Cancel a bulk tag job
+    ///
+    /// Any items already modified by this job will not be reverted.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is cancelling this bulk tag job
+    /// * `id` - The id of the bulk tag job to cancel
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "BulkTagJob::cancel", skip(user, shared), err(Debug))]
+    pub async fn cancel(user: &User, id: &Uuid, shared: &Shared) -> Result<(), ApiError> {
+        // make sure this job exists and we can see it
+        let job = BulkTagJob::get(user, id, shared).await?;
+        // we can't cancel jobs that are already finished
+        if job.is_finished() {
+            return conflict!(format!("Bulk tag job {id} is already {}", job.status));
+        }
+        db::tags::cancel_bulk_job(id, shared).await
+    }
+
+    /// Run this bulk tag job to completion
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that created this bulk tag job
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "BulkTagJob::run", skip_all, fields(id = self.id.to_string()))]
+    async fn run(mut self, user: &User, shared: &Shared) {
+        // mark that we have started this job
+        self.status = BulkTagStatus::Running;
+        self.started = Some(Utc::now());
+        // run this job and mark if it failed
+        if let Err(error) = self.run_helper(user, shared).await {
+            self.error(format!("Bulk tag job failed: {error}"));
+            self.status = BulkTagStatus::Failed;
+        } else if self.status == BulkTagStatus::Running {
+            self.status = BulkTagStatus::Completed;
+        }
+        // save the final state of this job
+        self.finished = Some(Utc::now());
+        if let Err(error) = db::tags::save_bulk_job(&self, shared).await {
+            event!(
+                Level::ERROR,
+                msg = "Failed to save bulk tag job",
+                error = error.to_string()
+            );
+        }
+    }
+
+    /// Apply this jobs operation to each tag it targets
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that created this bulk tag job
+    /// * `shared` - Shared Thorium objects
+    async fn run_helper(&mut self, user: &User, shared: &Shared) -> Result<(), ApiError> {
+        // save that we are running
+        db::tags::save_bulk_job(self, shared).await?;
+        // handle each group one at a time so replacement tags stay in the same group
+        for group in self.groups.clone() {
+            for (key, value) in self.targets(&group, shared).await? {
+                // apply our operation to all items with this tag in this group
+                let keep_going = match self.tag_type {
+                    TagType::Files => self.apply_files(user, &group, key, value, shared).await?,
+                    TagType::Repos => self.apply_repos(user, &group, key, value, shared).await?,
+                };
+                // stop early if we were cancelled
+                if !keep_going {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the tags this job should modify in a group
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to get targeted tags in
+    /// * `shared` - Shared Thorium objects
+    async fn targets(
+        &self,
+        group: &str,
+        shared: &Shared,
+    ) -> Result<Vec<(String, String)>, ApiError> {
+        match &self.operation {
+            // renames need to find every value this key has
+            BulkTagOperation::RenameKey { from, .. } => {
+                let values = db::tags::list_values(self.tag_type, group, from, shared).await?;
+                Ok(values
+                    .into_iter()
+                    .map(|value| (from.clone(), value))
+                    .collect())
+            }
+            BulkTagOperation::ReplaceValue { key, from, .. } => {
+                Ok(vec![(key.clone(), from.clone())])
+            }
+            BulkTagOperation::Delete { key, value } => Ok(vec![(key.clone(), value.clone())]),
+        }
+    }
+
+    /// Apply this jobs operation to all files with a specific tag in a group
+    ///
+    /// Returns false if this job was cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that created this bulk tag job
+    /// * `group` - The group to modify tags in
+    /// * `key` - The key of the tag to modify
+    /// * `value` - The value of the tag to modify
+    /// * `shared` - Shared Thorium objects
+    async fn apply_files(
+        &mut self,
+        user: &User,
+        group: &str,
+        key: String,
+        value: String,
+        shared: &Shared,
+    ) -> Result<bool, ApiError> {
+        // get the tag we are replacing this one with if we have one
+        let replacement = self.operation.replacement(&key, &value);
+        // build a cursor over all files with this tag in this group
+        let params = FileListParams {
+            groups: vec![group.to_owned()],
+            tags: [(key.clone(), vec![value.clone()])].into(),
+            limit: BATCH_SIZE,
+            ..Default::default()
+        };
+        let mut cursor = ScyllaCursor::<SampleListLine>::from_params(params, true, shared).await?;
+        loop {
+            // get the next batch of files
+            cursor.next(shared).await?;
+            for line in cursor.data.drain(..) {
+                self.matched += 1;
+                // only count matches if this is a dry run
+                if self.dry_run {
+                    continue;
+                }
+                // replace this files tag
+                let result = async {
+                    let sample = Sample::get(user, &line.sha256, shared).await?;
+                    // add our new tag first so we never lose data
+                    if let Some((new_key, new_value)) = &replacement {
+                        let add = TagRequest::<Sample>::default()
+                            .group(group)
+                            .add(new_key, new_value)
+                            .provenance(TagProvenanceRequest::default().keep(&key, &value));
+                        sample.tag(user, add, shared).await?;
+                    }
+                    let del = TagDeleteRequest::<Sample>::default()
+                        .group(group)
+                        .add(&key, &value);
+                    sample.delete_tags(user, del, shared).await
+                }
+                .await;
+                match result {
+                    Ok(()) => self.modified += 1,
+                    Err(error) => self.error(format!("{}: {error}", line.sha256)),
+                }
+            }
+            // save our progress and check if we were cancelled
+            if !self.checkpoint(shared).await? {
+                return Ok(false);
+            }
+            // stop once we have crawled all matching files
+            if cursor.exhausted() {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Apply this jobs operation to all repos with a specific tag in a group
+    ///
+    /// Returns false if this job was cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that created this bulk tag job
+    /// * `group` - The group to modify tags in
+    /// * `key` - The key of the tag to modify
+    /// * `value` - The value of the tag to modify
+    /// * `shared` - Shared Thorium objects
+    async fn apply_repos(
+        &mut self,
+        user: &User,
+        group: &str,
+        key: String,
+        value: String,
+        shared: &Shared,
+    ) -> Result<bool, ApiError> {
+        // get the tag we are replacing this one with if we have one
+        let replacement = self.operation.replacement(&key, &value);
+        // build a cursor over all repos with this tag in this group
+        let params = RepoListParams {
+            groups: vec![group.to_owned()],
+            tags: [(key.clone(), vec![value.clone()])].into(),
+            limit: BATCH_SIZE,
+            ..Default::default()
+        };
+        let mut cursor = ScyllaCursor::<RepoListLine>::from_params(params, true, shared).await?;
+        loop {
+            // get the next batch of repos
+            cursor.next(shared).await?;
+            for line in cursor.data.drain(..) {
+                self.matched += 1;
+                // only count matches if this is a dry run
+                if self.dry_run {
+                    continue;
+                }
+                // replace this repos tag
+                let result = async {
+                    let repo = Repo::get(user, &line.url, shared).await?;
+                    // add our new tag first so we never lose data
+                    if let Some((new_key, new_value)) = &replacement {
+                        let add = TagRequest::<Repo>::default()
+                            .group(group)
+                            .add(new_key, new_value)
+                            .provenance(TagProvenanceRequest::default().keep(&key, &value));
+                        repo.tag(user, add, shared).await?;
+                    }
+                    let del = TagDeleteRequest::<Repo>::default()
+                        .group(group)
+                        .add(&key, &value);
+                    repo.delete_tags(user, del, shared).await
+                }
+                .await;
+                match result {
+                    Ok(()) => self.modified += 1,
+                    Err(error) => self.error(format!("{}: {error}", line.url)),
+                }
+            }
+            // save our progress and check if we were cancelled
+            if !self.checkpoint(shared).await? {
+                return Ok(false);
+            }
+            // stop once we have crawled all matching repos
+            if cursor.exhausted() {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Save this jobs progress and check if it should keep going
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared Thorium objects
+    async fn checkpoint(&mut self, shared: &Shared) -> Result<bool, ApiError> {
+        // stop if this job has been cancelled
+        if db::tags::bulk_job_cancelled(&self.id, shared).await? {
+            self.status = BulkTagStatus::Cancelled;
+            return Ok(false);
+        }
+        db::tags::save_bulk_job(self, shared).await?;
+        Ok(true)
+    }
+
+    /// Track an error for this job
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The error to track
+    fn error(&mut self, msg: String) {
+        event!(Level::ERROR, id = self.id.to_string(), msg);
+        // don't let our list of errors grow forever
+        if self.errors.len() < MAX_ERRORS {
+            self.errors.push(msg);
+        }
+    }
+}
+
+impl TagProvenance {
+    /// List where the tags on an item came from
+    ///
+    /// The groups in our params must already be validated for this item.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item to get tag provenance for
+    /// * `params` - The params to filter our tag provenance with
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "TagProvenance::list", skip(params, shared), err(Debug))]
+    pub async fn list<T: TagSupport>(
+        item: &str,
+        params: &TagProvenanceParams,
+        shared: &Shared,
+    ) -> Result<Vec<TagProvenance>, ApiError> {
+        // get where this items tags came from
+        let mut provenance =
+            db::tags::get_provenance(T::tag_kind(), &params.groups, item, shared).await?;
+        // only keep the tags that match our filters
+        provenance.retain(|prov| params.matches(prov));
+        Ok(provenance)
+    }
+}
+
+impl<S> FromRequestParts<S> for TagProvenanceParams
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // try to extract our query
+        if let Some(query) = parts.uri.query() {
+            // try to deserialize our query string
+            Ok(serde_qs::Config::new(5, false).deserialize_str(query)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Build out trees based on data in Thorium's database
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
@@ -8,16 +8,22 @@ use uuid::Uuid;
 
 use super::db;
 use crate::bad;
-use crate::models::trees::TreeTags;
+use crate::models::backends::OutputSupport;
+use crate::models::trees::{TreeCommitish, TreeTags};
 use crate::models::{
-    Sample, Tree, TreeNode, TreeNodeData, TreeParams, TreeQuery, TreeSupport, User,
+    FileListOpts, OutputMap, Repo, ResultGetParams, Sample, Tree, TreeNode, TreeNodeData,
+    TreeParams, TreeQuery, TreeRelationships, TreeSupport, User,
 };
 use crate::utils::{ApiError, Shared};
 
 impl TreeQuery {
     /// Make sure our query is not empty and error if it is
     pub fn check_empty(&self) -> Result<(), ApiError> {
-        if self.samples.is_empty() && self.tags.is_empty() {
+        if self.samples.is_empty()
+            && self.tags.is_empty()
+            && self.repos.is_empty()
+            && self.commits.is_empty()
+        {
             bad!("Initial starting data must be set!".to_owned())
         } else {
             Ok(())
@@ -37,10 +43,88 @@ impl TreeNode {
         match &self.data {
             TreeNodeData::Sample(sample) => sample.gather_children(user, shared).await,
             TreeNodeData::Tag(tags) => tags.gather_children(user, shared).await,
+            TreeNodeData::Repo(repo) => repo.gather_children(user, shared).await,
+            TreeNodeData::Commitish(commitish) => commitish.gather_children(user, shared).await,
         }
     }
 }
 
+/// Gather the samples that were found as children by the results for an object
+///
+/// Children we can't see are skipped.
+///
+/// # Arguments
+///
+/// * `key` - The key to get results for
+/// * `item` - The object to get results for
+/// * `user` - The user that is growing this tree
+/// * `shared` - Shared Thorium objects
+pub async fn result_children<T: OutputSupport>(
+    key: &str,
+    item: &T,
+    user: &User,
+    shared: &Shared,
+) -> Result<Vec<TreeNode>, ApiError> {
+    // get the results for this object
+    let outputs = OutputMap::get(key, item, user, ResultGetParams::default(), shared).await?;
+    // map each child to the results that found it
+    let mut found: HashMap<String, Vec<TreeRelationships>> = HashMap::default();
+    for (tool, results) in outputs.results {
+        // crawl the results for this tool
+        for result in results {
+            // add a relationship for each child this result found
+            for sha256 in result.children.into_keys() {
+                // get an entry to this childs relationships
+                let entry = found.entry(sha256).or_default();
+                // add this relationship
+                entry.push(TreeRelationships::ResultChildOf {
+                    tool: tool.clone(),
+                    result: result.id,
+                });
+            }
+        }
+    }
+    // build a node for each child we can see
+    let mut children = Vec::with_capacity(found.len());
+    for (sha256, relationships) in found {
+        // skip any children that we can't see
+        if let Ok(sample) = Sample::get(user, &sha256, shared).await {
+            // wrap this sample in a node
+            children.push(TreeNode::new(relationships, TreeNodeData::Sample(sample)));
+        }
+    }
+    Ok(children)
+}
+
+/// List the details for every sample matching some list options
+///
+/// # Arguments
+///
+/// * `user` - The user that is growing this tree
+/// * `opts` - The options to list samples with
+/// * `shared` - Shared Thorium objects
+pub async fn list_samples(
+    user: &User,
+    mut opts: FileListOpts,
+    shared: &Shared,
+) -> Result<Vec<Sample>, ApiError> {
+    // keep a list of all of the samples we find
+    let mut samples = Vec::default();
+    loop {
+        // get the next page of samples
+        let list = Sample::list(user, opts.clone(), true, shared).await?;
+        // get the details on these samples
+        let mut details = list.details(user, shared).await?;
+        samples.append(&mut details.data);
+        // stop once we have listed every sample
+        match details.cursor {
+            Some(cursor) => opts = opts.cursor(cursor),
+            None => break,
+        }
+    }
+    Ok(samples)
+}
+
 impl Tree {
     /// Build or get an existing tree from params
     pub async fn from_query(
@@ -56,6 +140,8 @@ impl Tree {
         // TODO this in parallel?
         let samples = Sample::gather_initial(user, &query, shared).await?;
         let tags = TreeTags::gather_initial(user, &query, shared).await?;
+        let repos = Repo::gather_initial(user, &query, shared).await?;
+        let commits = TreeCommitish::gather_initial(user, &query, shared).await?;
         // add our initial samples
         for sample in samples {
             tree.add_initial(sample);
@@ -64,6 +150,10 @@ impl Tree {
         for tag in tags {
             tree.add_initial(tag);
         }
+        // add our initial repos and commits
+        for node in repos.into_iter().chain(commits) {
+            tree.add_initial(node);
+        }
         Ok(tree)
     }
 
@@ -120,10 +210,26 @@ impl Tree {
     }
 
     /// Trim a new to only new nodes that have not already been sent
-    pub fn trim(&mut self, added: HashSet<u64>) {
+    ///
+    /// Links from the nodes we grew are kept even if their child was already sent so that
+    /// new links to existing nodes are not lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `grown` - The nodes that were grown
+    /// * `added` - The nodes that were newly added to this tree
+    pub fn trim(&mut self, grown: &[u64], added: HashSet<u64>) {
         // drop any info from nodes that we have already sent
         self.data_map.retain(|key, _| added.contains(key));
-        self.branches.retain(|key, _| added.contains(key));
+        // only keep the links from the nodes we grew or to our new nodes
+        self.branches.retain(|parent, children| {
+            // all links from a grown or new node were made while growing
+            if grown.contains(parent) || added.contains(parent) {
+                return true;
+            }
+            children.retain(|child| added.contains(child));
+            !children.is_empty()
+        });
     }
 
     /// Save this trees info to the db
@@ -0,0 +1,460 @@
+//! Handles resumable uploads of large samples
+
+use aws_sdk_s3::types::CompletedPart;
+use axum::http::StatusCode;
+use bytes::Bytes;
+use chrono::prelude::*;
+use std::collections::{BTreeSet, HashSet};
+use tracing::{event, instrument, Level};
+use uuid::Uuid;
+
+use super::db;
+use crate::models::{
+    Group, GroupAllowAction, OriginForm, S3Objects, Sample, SampleForm, SampleSubmissionResponse,
+    TagProvenanceRequest, UploadSession, UploadSessionRequest, UploadedChunk, User,
+};
+use crate::utils::{ApiError, Shared};
+use crate::{bad, can_create_all, conflict, not_found, unauthorized};
+
+/// The smallest chunk size S3 will accept for all but the last part of a multipart upload
+const MIN_CHUNK_SIZE: u64 = 5_242_880;
+
+/// The max number of parts S3 allows in a single multipart upload
+const MAX_CHUNKS: u64 = 10_000;
+
+/// The max number of expired upload sessions to clean up at once
+const EXPIRE_BATCH: u64 = 1_000;
+
+/// Build the path to the staging object for an upload session in the ephemeral bucket
+///
+/// # Arguments
+///
+/// * `id` - The id of the upload session
+fn staging_path(id: &Uuid) -> String {
+    format!("uploads/{id}")
+}
+
+impl UploadSession {
+    /// Make sure a request to resume this upload session asks for the same sample
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The upload session request that is resuming this session
+    fn matches(&self, req: &UploadSessionRequest) -> Result<(), ApiError> {
+        // make sure we are uploading a file of the same size
+        if self.size != req.size {
+            return conflict!(format!(
+                "Upload session {} is for a {} byte file not a {} byte file",
+                self.id, self.size, req.size
+            ));
+        }
+        // make sure we are uploading to the same groups
+        let groups = self.groups.iter().collect::<HashSet<_>>();
+        if groups != req.groups.iter().collect::<HashSet<_>>() {
+            return conflict!(format!(
+                "Upload session {} was started for different groups",
+                self.id
+            ));
+        }
+        // make sure we are uploading with the same tags
+        if self.tags != req.tags {
+            return conflict!(format!(
+                "Upload session {} was started with different tags",
+                self.id
+            ));
+        }
+        // make sure we are uploading with the same origin
+        if self.origin != req.origin {
+            return conflict!(format!(
+                "Upload session {} was started with a different origin",
+                self.id
+            ));
+        }
+        Ok(())
+    }
+
+    /// Start a new resumable upload session or resume an existing one
+    ///
+    /// If this user already has a live upload session for this sha256 then that session is
+    /// returned instead so clients can resume an interrupted upload. Resuming a session with
+    /// different groups, tags or a different origin is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is starting this upload session
+    /// * `req` - The upload session request
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::create", skip(user, req, shared), err(Debug))]
+    pub async fn create(
+        user: &User,
+        mut req: UploadSessionRequest,
+        shared: &Shared,
+    ) -> Result<UploadSession, ApiError> {
+        // make sure our sha256 is a valid sha256
+        if req.sha256.len() != 64 || !req.sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return bad!(format!("{} is not a valid sha256", req.sha256));
+        }
+        req.sha256 = req.sha256.to_lowercase();
+        // make sure we are actually uploading something
+        if req.size == 0 {
+            return bad!("Cannot start an upload session for an empty file".to_owned());
+        }
+        // make sure we actually have groups
+        if req.groups.is_empty() {
+            return bad!(format!(
+                "No groups provided! Sample must be uploaded to at least one group."
+            ));
+        }
+        // make sure we actually have access to all requested groups
+        let groups =
+            Group::authorize_check_allow_all(user, &req.groups, GroupAllowAction::Files, shared)
+                .await?;
+        // make sure we have the roles to upload samples in all of these groups
+        can_create_all!(groups, user, shared);
+        // make sure our tags are allowed before any data is uploaded
+        Group::apply_tag_vocabularies(&groups, &mut req.tags, true)?;
+        // make sure our origin is valid before any data is uploaded
+        if let Some(origin) = &req.origin {
+            OriginForm::try_from(origin.clone())?.to_origin()?;
+        }
+        // check if we already have a live upload session for this file
+        if let Some(id) = db::uploads::find(&user.username, &req.sha256, shared).await? {
+            match Self::get(user, &id, shared).await {
+                Ok(session) => {
+                    // make sure this request matches the session we are resuming
+                    session.matches(&req)?;
+                    return Ok(session);
+                }
+                // this session has already expired so just start a new one
+                Err(err) => event!(
+                    Level::INFO,
+                    msg = "Not resuming upload",
+                    id = id.to_string(),
+                    err = format!("{err:?}")
+                ),
+            }
+        }
+        // get our chunk size making sure we stay within S3's multipart limits
+        let chunk_size = std::cmp::max(
+            shared.config.thorium.files.upload_chunk_size,
+            MIN_CHUNK_SIZE,
+        );
+        let chunk_size = std::cmp::max(chunk_size, req.size.div_ceil(MAX_CHUNKS));
+        // get the current timestamp
+        let now = Utc::now();
+        // build our upload session
+        let session = UploadSession {
+            id: Uuid::new_v4(),
+            creator: user.username.clone(),
+            sha256: req.sha256,
+            size: req.size,
+            chunk_size,
+            chunks: req.size.div_ceil(chunk_size),
+            uploaded: BTreeSet::default(),
+            file_name: req.file_name,
+            groups: req.groups,
+            description: req.description,
+            tags: req.tags,
+            origin: req.origin,
+            trigger_depth: req.trigger_depth,
+            created: now,
+            expires: Self::next_expire(now, shared),
+        };
+        // start the multipart upload our chunks will be staged in
+        let path = staging_path(&session.id);
+        let upload_id = shared.s3.ephemeral.create_multipart(&path).await?;
+        // save this upload session
+        if let Err(err) = db::uploads::create(&session, &upload_id, shared).await {
+            // abort our multipart upload since we failed to save this session
+            shared
+                .s3
+                .ephemeral
+                .abort_multipart(&path, &upload_id)
+                .await?;
+            return Err(err);
+        }
+        Ok(session)
+    }
+
+    /// Get when an upload session should expire if it sees no more activity
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current timestamp
+    /// * `shared` - Shared Thorium objects
+    fn next_expire(now: DateTime<Utc>, shared: &Shared) -> DateTime<Utc> {
+        // cast our expiration to a duration
+        let expire = i64::try_from(shared.config.thorium.files.upload_expire).unwrap_or(i64::MAX);
+        now + chrono::Duration::seconds(expire)
+    }
+
+    /// Get an upload session
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is getting this upload session
+    /// * `id` - The id of the upload session to get
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::get", skip(user, shared), err(Debug))]
+    pub async fn get(user: &User, id: &Uuid, shared: &Shared) -> Result<UploadSession, ApiError> {
+        // get this upload session
+        let session = db::uploads::get(id, shared).await?;
+        // treat expired sessions as if they are already gone
+        if session.expires < Utc::now() {
+            return not_found!(format!("Upload session {id} not found"));
+        }
+        // only the creator of a session or an admin can access it
+        if session.creator != user.username && !user.is_admin() {
+            return unauthorized!();
+        }
+        Ok(session)
+    }
+
+    /// List all live upload sessions for a user
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose upload sessions to list
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::list", skip_all, err(Debug))]
+    pub async fn list(user: &User, shared: &Shared) -> Result<Vec<UploadSession>, ApiError> {
+        // get the ids of this users upload sessions
+        let ids = db::uploads::list(&user.username, shared).await?;
+        // get the details on each session skipping any that have expired
+        let mut sessions = Vec::with_capacity(ids.len());
+        for id in &ids {
+            match Self::get(user, id, shared).await {
+                Ok(session) => sessions.push(session),
+                Err(err) if err.code == StatusCode::NOT_FOUND => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Upload a single chunk of the file for this upload session
+    ///
+    /// Chunks can be uploaded in any order and a chunk that was already uploaded is overwritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - The index of the chunk being uploaded (starting at 0)
+    /// * `data` - The data in this chunk
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::upload_chunk", skip(self, data, shared), fields(id = self.id.to_string()), err(Debug))]
+    pub async fn upload_chunk(
+        mut self,
+        chunk: u64,
+        data: Bytes,
+        shared: &Shared,
+    ) -> Result<(), ApiError> {
+        // get the range of bytes this chunk should contain
+        let Some(range) = self.chunk_range(chunk) else {
+            return bad!(format!(
+                "Chunk {chunk} is out of bounds; upload session {} has {} chunks",
+                self.id, self.chunks
+            ));
+        };
+        // make sure this chunk is the right size
+        let size = data.len() as u64;
+        if size != range.end - range.start {
+            return bad!(format!(
+                "Chunk {chunk} must be {} bytes but got {size} bytes",
+                range.end - range.start
+            ));
+        }
+        // get the multipart upload to write this chunk too
+        let upload_id = db::uploads::s3_upload_id(&self.id, shared).await?;
+        // S3 part numbers start at 1 and our chunk count is bounded so this will always fit
+        let part_num = i32::try_from(chunk + 1)?;
+        // write this chunk to s3
+        let e_tag = shared
+            .s3
+            .ephemeral
+            .upload_part(&staging_path(&self.id), &upload_id, part_num, data)
+            .await?;
+        // push back when this session expires
+        self.expires = Self::next_expire(Utc::now(), shared);
+        // save this chunk
+        let uploaded = UploadedChunk { size, e_tag };
+        db::uploads::save_chunk(&self, chunk, &uploaded, shared).await
+    }
+
+    /// Finish this upload session and submit the uploaded file as a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is finishing this upload session
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::finish", skip(self, user, shared), fields(id = self.id.to_string()), err(Debug))]
+    pub async fn finish(
+        self,
+        user: &User,
+        shared: &Shared,
+    ) -> Result<SampleSubmissionResponse, ApiError> {
+        // make sure every chunk has been uploaded
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return bad!(format!(
+                "Upload session {} is missing {} chunks: {missing:?}",
+                self.id,
+                missing.len()
+            ));
+        }
+        // get the info on all of our uploaded chunks
+        let chunks = db::uploads::chunks(&self.id, shared).await?;
+        // build the list of parts to assemble making sure each chunk is the right size
+        let mut parts = Vec::with_capacity(chunks.len());
+        for (chunk, uploaded) in chunks {
+            // make sure this chunk is still the size we expect
+            match self.chunk_range(chunk) {
+                Some(range) if range.end - range.start == uploaded.size => (),
+                _ => return bad!(format!("Chunk {chunk} is invalid and must be re-uploaded")),
+            }
+            // S3 part numbers start at 1
+            let part_num = i32::try_from(chunk + 1)?;
+            parts.push(
+                CompletedPart::builder()
+                    .e_tag(uploaded.e_tag)
+                    .part_number(part_num)
+                    .build(),
+            );
+        }
+        // assemble our chunks into a single staged object
+        let path = staging_path(&self.id);
+        let upload_id = db::uploads::s3_upload_id(&self.id, shared).await?;
+        shared
+            .s3
+            .ephemeral
+            .complete_multipart(&path, &upload_id, parts)
+            .await?;
+        // our chunks can no longer be changed so submit our file and clean up our session
+        let result = self.submit(user, &path, shared).await;
+        shared.s3.ephemeral.delete(&path).await?;
+        db::uploads::delete(&self.id, &self.creator, &self.sha256, shared).await?;
+        result
+    }
+
+    /// Hash, cart, and save an assembled upload as a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is finishing this upload session
+    /// * `path` - The path to the assembled upload in the ephemeral bucket
+    /// * `shared` - Shared Thorium objects
+    async fn submit(
+        &self,
+        user: &User,
+        path: &str,
+        shared: &Shared,
+    ) -> Result<SampleSubmissionResponse, ApiError> {
+        // start streaming our assembled file back out of s3
+        let stream = shared.s3.ephemeral.download(path).await?;
+        // try to generate a random uuid for this sample
+        let s3_id = db::s3::generate_id(S3Objects::File, shared).await?;
+        // hash and cart our file into the files bucket
+        let hashes = shared
+            .s3
+            .files
+            .hash_cart_and_stream_bytes(&s3_id, stream)
+            .await?;
+        // make sure the file we got is the file we expected
+        if hashes.sha256 != self.sha256 {
+            shared.s3.files.delete(&s3_id.to_string()).await?;
+            return bad!(format!(
+                "Uploaded file has a sha256 of {} but {} was expected",
+                hashes.sha256, self.sha256
+            ));
+        }
+        // convert our origin if we have one
+        let origin = match &self.origin {
+            Some(origin) => OriginForm::try_from(origin.clone())?,
+            None => OriginForm::default(),
+        };
+        // build the form for this sample
+        let form = SampleForm {
+            groups: self.groups.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            origin,
+            file_name: self.file_name.clone(),
+            trigger_depth: self.trigger_depth,
+            provenance: TagProvenanceRequest::default(),
+        };
+        // save this samples metadata
+        match Sample::create_from_form(user, &s3_id, form, hashes, shared).await {
+            Ok(resp) => Ok(resp),
+            Err(err) => {
+                // delete our carted file if nothing references it
+                if !db::s3::s3_id_exists(S3Objects::File, &s3_id, shared).await? {
+                    shared.s3.files.delete(&s3_id.to_string()).await?;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Abort this upload session and delete any chunks that were uploaded
+    ///
+    /// # Arguments
+    ///
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::abort", skip(self, shared), fields(id = self.id.to_string()), err(Debug))]
+    pub async fn abort(self, shared: &Shared) -> Result<(), ApiError> {
+        // get the multipart upload our chunks are staged in
+        let upload_id = db::uploads::s3_upload_id(&self.id, shared).await?;
+        // drop any chunks that were uploaded
+        shared
+            .s3
+            .ephemeral
+            .abort_multipart(&staging_path(&self.id), &upload_id)
+            .await?;
+        // delete this upload session
+        db::uploads::delete(&self.id, &self.creator, &self.sha256, shared).await
+    }
+
+    /// Cleans up any upload sessions that have expired
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is cleaning up expired upload sessions
+    /// * `shared` - Shared Thorium objects
+    #[instrument(name = "UploadSession::expire", skip_all, err(Debug))]
+    pub async fn expire(user: &User, shared: &Shared) -> Result<(), ApiError> {
+        // only admins can clean up expired upload sessions
+        if !user.is_admin() {
+            return unauthorized!();
+        }
+        // get the current timestamp
+        let now = Utc::now().timestamp();
+        loop {
+            // get the next batch of expired upload sessions
+            let expired = db::uploads::expired(now, EXPIRE_BATCH, shared).await?;
+            // clean up each expired session
+            for id in &expired {
+                match db::uploads::get(id, shared).await {
+                    // this session still exists so abort it
+                    Ok(session) => {
+                        if let Err(err) = session.abort(shared).await {
+                            // log this error and stop tracking this session so we don't retry forever
+                            event!(
+                                Level::ERROR,
+                                msg = "Failed to abort upload",
+                                id = id.to_string(),
+                                err = format!("{err:?}")
+                            );
+                            db::uploads::untrack(id, shared).await?;
+                        }
+                    }
+                    // this sessions data is already gone so just stop tracking it
+                    Err(_) => db::uploads::untrack(id, shared).await?,
+                }
+            }
+            // stop once we have cleaned up all expired sessions
+            if (expired.len() as u64) < EXPIRE_BATCH {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
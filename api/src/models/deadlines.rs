@@ -4,7 +4,7 @@
 use chrono::prelude::*;
 use uuid::Uuid;
 
-use super::RawJob;
+use super::{PriorityClass, RawJob};
 
 /// A deadline for when a job must be started by
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -24,6 +24,9 @@ pub struct Deadline {
     pub reaction: Uuid,
     /// The timestamp the job must be started by
     pub deadline: chrono::DateTime<Utc>,
+    /// The priority class of the reaction this job is apart of
+    #[serde(default)]
+    pub priority: PriorityClass,
 }
 
 impl Deadline {
@@ -53,6 +56,7 @@ impl From<&RawJob> for Deadline {
             job_id: job.id.to_owned(),
             reaction: job.reaction.to_owned(),
             deadline: job.deadline,
+            priority: job.priority,
         }
     }
 }
@@ -72,6 +76,7 @@ impl From<RawJob> for Deadline {
             job_id: job.id,
             reaction: job.reaction,
             deadline: job.deadline,
+            priority: job.priority,
         }
     }
 }
@@ -92,4 +97,24 @@ pub(super) struct DeadlineFragment {
     pub job_id: Uuid,
     /// The reaction this job is apart of
     pub reaction: Uuid,
+    /// The priority class of the reaction this job is apart of
+    #[serde(default)]
+    pub priority: PriorityClass,
+}
+
+/// Serialize the priority class to add to a deadlines stream data
+///
+/// Standard priority classes are left out so that deadlines added before priority
+/// classes existed can still be found and removed from the deadline stream.
+///
+/// # Arguments
+///
+/// * `priority` - The priority class to serialize
+#[cfg(feature = "api")]
+pub(crate) fn priority_stream_data(priority: PriorityClass) -> String {
+    if priority.is_standard() {
+        String::new()
+    } else {
+        format!(",\"priority\":\"{}\"", priority.as_str())
+    }
 }
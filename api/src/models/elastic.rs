@@ -7,7 +7,7 @@ use uuid::Uuid;
 use strum::{EnumIter, IntoEnumIterator};
 
 /// The different elastic indexes
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
 #[cfg_attr(feature = "api", derive(EnumIter))]
 pub enum ElasticIndex {
@@ -85,7 +85,7 @@ impl ElasticIndex {
 }
 
 /// Returns all elastic indexes as a default
-fn default_search_indexes() -> Vec<ElasticIndex> {
+pub(super) fn default_search_indexes() -> Vec<ElasticIndex> {
     // only search on samples, as the Web UI is the only user of
     // search and only samples are currently supported there
     vec![ElasticIndex::SampleResults, ElasticIndex::SampleTags]
@@ -172,6 +172,61 @@ impl ElasticSearchOpts {
         self.indexes = indexes;
         self
     }
+
+    /// Set the groups to search in
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to search in
+    #[must_use]
+    pub fn groups<T: Into<String>>(mut self, groups: Vec<T>) -> Self {
+        self.groups = groups.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the most recent date to start searching at
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The date to start searching at
+    #[must_use]
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Set the oldest date to stop searching at
+    ///
+    /// # Arguments
+    ///
+    /// * `end` - The date to stop searching at
+    #[must_use]
+    pub fn end(mut self, end: DateTime<Utc>) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Set the number of documents to retrieve in a single request
+    ///
+    /// # Arguments
+    ///
+    /// * `page_size` - The page size to use
+    #[must_use]
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Limit the total number of documents to return
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The max number of documents to return
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
 }
 
 // A specific document in elastic
@@ -195,3 +250,38 @@ pub struct ElasticDoc {
     /// The sort values for this document
     pub sort: Vec<i64>,
 }
+
+impl ElasticDoc {
+    /// Get a string field from this document's source
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The field to get
+    fn source_str(&self, key: &str) -> Option<&str> {
+        self.source
+            .as_ref()
+            .and_then(|source| source.get(key))
+            .and_then(serde_json::Value::as_str)
+    }
+
+    /// Get the item (sha256 or repo) this document is for
+    ///
+    /// Documents without an item in their source fall back to their id, which is made up of
+    /// the item and the group they are in delimited by a '-'.
+    #[must_use]
+    pub fn item(&self) -> &str {
+        match self.source_str("item") {
+            Some(item) => item,
+            None => self.id.split_once('-').map_or(&self.id, |(item, _)| item),
+        }
+    }
+
+    /// Get the group this document is in
+    #[must_use]
+    pub fn group(&self) -> Option<&str> {
+        match self.source_str("group") {
+            Some(group) => Some(group),
+            None => self.id.split_once('-').map(|(_, group)| group),
+        }
+    }
+}
@@ -1,13 +1,21 @@
 //! The events in Thorium for triggers and other things to act on
 
 use chrono::prelude::*;
+use regex::RegexSet;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::{LazyLock, Mutex, PoisonError};
 use uuid::Uuid;
 
 use super::{InvalidEnum, TagType};
 
+/// The regex sets compiled from each set of trigger patterns
+///
+/// Triggers are checked against every event so their patterns are only compiled once.
+static ANCHORED_SETS: LazyLock<Mutex<HashMap<Vec<String>, Option<RegexSet>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::default()));
+
 /// The different types of events
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "scylla-utils", derive(thorium_derive::ScyllaStoreAsStr))]
@@ -41,6 +49,7 @@ impl From<&EventData> for EventType {
         match data {
             &EventData::NewSample { .. } => EventType::ReactionTrigger,
             &EventData::NewTags { .. } => EventType::ReactionTrigger,
+            &EventData::NewCommitish { .. } => EventType::ReactionTrigger,
         }
     }
 }
@@ -75,6 +84,22 @@ pub enum EventData {
         /// The new tags that were added
         tags: HashMap<String, HashSet<String>>,
     },
+    /// New commits or branches were added to a repo
+    NewCommitish {
+        /// The repo these commitishes were added to
+        repo: String,
+        /// The groups these commitishes were added to
+        groups: Vec<String>,
+        /// The new commits that were added from newest to oldest
+        commits: Vec<String>,
+        /// The branches that were added or updated and the commit they point to
+        branches: HashMap<String, String>,
+        /// The parents of each new commit
+        #[serde(default)]
+        parents: HashMap<String, Vec<String>>,
+        /// The paths that were changed by each new commit
+        paths: HashMap<String, Vec<String>>,
+    },
 }
 
 /// An request for a new event in Thorium
@@ -202,6 +227,188 @@ impl Event {
         // default to this trigger will not trigger
         TriggerPotential::CanNot
     }
+
+    /// Build a regex set that only matches entire values
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The regex patterns to match against
+    fn anchored_set(patterns: &[String]) -> Option<RegexSet> {
+        // reuse the set for these patterns if we already compiled it
+        let mut cache = ANCHORED_SETS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(set) = cache.get(patterns) {
+            return set.clone();
+        }
+        // make sure our patterns match the entire value
+        let anchored = patterns.iter().map(|pattern| format!("^(?:{pattern})$"));
+        // patterns are validated when triggers are created so skip any that fail
+        let set = RegexSet::new(anchored).ok();
+        cache.insert(patterns.to_vec(), set.clone());
+        set
+    }
+
+    /// Get the new commits that are on a branch
+    ///
+    /// Only parents that are also new commits are crawled.
+    ///
+    /// # Arguments
+    ///
+    /// * `head` - The commit this branch points to
+    /// * `parents` - The parents of each new commit
+    /// * `paths` - The paths changed by each new commit
+    fn new_commits_on<'a>(
+        head: &'a String,
+        parents: &'a HashMap<String, Vec<String>>,
+        paths: &HashMap<String, Vec<String>>,
+    ) -> HashSet<&'a String> {
+        let mut found = HashSet::default();
+        let mut stack = vec![head];
+        while let Some(commit) = stack.pop() {
+            // skip commits that aren't new or that we have already crawled
+            if !paths.contains_key(commit) || !found.insert(commit) {
+                continue;
+            }
+            if let Some(commit_parents) = parents.get(commit) {
+                stack.extend(commit_parents);
+            }
+        }
+        found
+    }
+
+    /// Get the commits that meet a new commitish trigger from most to least preferred
+    ///
+    /// Empty filters will match any branch or changed path. When filtering on branches, only
+    /// the paths changed by new commits on a matching branch are checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `commits` - The new commits from newest to oldest
+    /// * `branches` - The branches that were added or updated and the commit they point to
+    /// * `parents` - The parents of each new commit
+    /// * `paths` - The paths changed by each new commit
+    /// * `branch_patterns` - The patterns for the branches to trigger on
+    /// * `path_patterns` - The patterns for the changed paths to trigger on
+    fn commitish_matches<'a>(
+        commits: &'a [String],
+        branches: &'a HashMap<String, String>,
+        parents: &'a HashMap<String, Vec<String>>,
+        paths: &HashMap<String, Vec<String>>,
+        branch_patterns: &[String],
+        path_patterns: &[String],
+    ) -> Vec<&'a String> {
+        let path_set = Self::anchored_set(path_patterns);
+        // check if a commit changed any of the paths this trigger is looking for
+        let changed = |commit: &String| {
+            path_patterns.is_empty()
+                || path_set.as_ref().is_some_and(|set| {
+                    paths
+                        .get(commit)
+                        .is_some_and(|changed| changed.iter().any(|path| set.is_match(path)))
+                })
+        };
+        // get the branches this trigger could match sorted so we always pick the same one
+        let mut matched = branches.iter().collect::<Vec<(&String, &String)>>();
+        matched.sort_unstable();
+        if branch_patterns.is_empty() {
+            // any new commit can meet this trigger when we don't filter on branches
+            let hits = commits
+                .iter()
+                .filter(|commit| changed(commit))
+                .collect::<Vec<&String>>();
+            // fall back to our branches if no commits were added
+            if !hits.is_empty() || !path_patterns.is_empty() {
+                return hits;
+            }
+            return matched.into_iter().map(|(_, head)| head).collect();
+        }
+        // only keep branches whose names and new commits match this trigger
+        let Some(branch_set) = Self::anchored_set(branch_patterns) else {
+            return Vec::default();
+        };
+        matched
+            .into_iter()
+            .filter(|(name, _)| branch_set.is_match(name))
+            .filter(|(_, head)| {
+                // branches without new commits can still match if we don't filter on paths
+                path_patterns.is_empty()
+                    || Self::new_commits_on(head, parents, paths)
+                        .into_iter()
+                        .any(|commit| changed(commit))
+            })
+            .map(|(_, head)| head)
+            .collect()
+    }
+
+    /// Check if a new commitish event trigger occured
+    ///
+    /// # Arguments
+    ///
+    /// * `commits` - The new commits from newest to oldest
+    /// * `branches` - The branches that were added or updated and the commit they point to
+    /// * `parents` - The parents of each new commit
+    /// * `paths` - The paths changed by each new commit
+    /// * `branch_patterns` - The patterns for the branches to trigger on
+    /// * `path_patterns` - The patterns for the changed paths to trigger on
+    fn check_commitish_trigger(
+        commits: &[String],
+        branches: &HashMap<String, String>,
+        parents: &HashMap<String, Vec<String>>,
+        paths: &HashMap<String, Vec<String>>,
+        branch_patterns: &[String],
+        path_patterns: &[String],
+    ) -> TriggerPotential {
+        let matches = Self::commitish_matches(
+            commits,
+            branches,
+            parents,
+            paths,
+            branch_patterns,
+            path_patterns,
+        );
+        if matches.is_empty() {
+            TriggerPotential::CanNot
+        } else {
+            TriggerPotential::Confirmed
+        }
+    }
+
+    /// Get the commit that a reaction for a new commitish event should be run on
+    ///
+    /// This is the commit of the first matching branch if this trigger filters on
+    /// branches or the newest matching new commit if it does not.
+    ///
+    /// # Arguments
+    ///
+    /// * `trigger` - The trigger this event met
+    #[must_use]
+    pub fn commitish_target(&self, trigger: &EventTrigger) -> Option<&String> {
+        match (&self.data, trigger) {
+            (
+                EventData::NewCommitish {
+                    commits,
+                    branches,
+                    parents,
+                    paths,
+                    ..
+                },
+                EventTrigger::NewCommitish {
+                    branches: branch_patterns,
+                    paths: path_patterns,
+                },
+            ) => Self::commitish_matches(
+                commits,
+                branches,
+                parents,
+                paths,
+                branch_patterns,
+                path_patterns,
+            )
+            .into_iter()
+            .next(),
+            _ => None,
+        }
+    }
+
     /// Check if this event could potentially trigger a trigger
     pub fn could_trigger(&self, trigger: &EventTrigger) -> TriggerPotential {
         match (&self.data, trigger) {
@@ -214,8 +421,27 @@ impl Event {
                     not,
                 },
             ) => Self::check_tag_trigger(tag_type, tags, tag_types, required, not),
+            (
+                EventData::NewCommitish {
+                    commits,
+                    branches: new_branches,
+                    parents,
+                    paths: new_paths,
+                    ..
+                },
+                EventTrigger::NewCommitish { branches, paths },
+            ) => Self::check_commitish_trigger(
+                commits,
+                new_branches,
+                parents,
+                new_paths,
+                branches,
+                paths,
+            ),
             (EventData::NewSample { .. }, EventTrigger::Tag { .. }) => TriggerPotential::CanNot,
             (EventData::NewTags { .. }, EventTrigger::NewSample) => TriggerPotential::CanNot,
+            (EventData::NewCommitish { .. }, _) => TriggerPotential::CanNot,
+            (_, EventTrigger::NewCommitish { .. }) => TriggerPotential::CanNot,
         }
     }
 }
@@ -285,6 +511,15 @@ pub enum EventTrigger {
     },
     /// A trigger based on a new sample
     NewSample,
+    /// A trigger based on new commits or branches for a repo
+    NewCommitish {
+        /// The regex patterns for the branch names to trigger on (any branch if empty)
+        #[serde(default)]
+        branches: Vec<String>,
+        /// The regex patterns for the changed paths to trigger on (any path if empty)
+        #[serde(default)]
+        paths: Vec<String>,
+    },
 }
 
 /// The current event marks
@@ -323,3 +558,124 @@ impl EventCacheStatusOpts {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a new commitish event where `main` and `feature` share new commit `c2`
+    fn commitish_event() -> Event {
+        let owned = |values: &[&str]| values.iter().map(|v| (*v).to_owned()).collect::<Vec<_>>();
+        let data = EventData::NewCommitish {
+            repo: "github.com/corn/husk".to_owned(),
+            groups: owned(&["corn"]),
+            commits: owned(&["f1", "c3", "c2"]),
+            branches: [("main", "c3"), ("feature", "f1"), ("stale", "old")]
+                .into_iter()
+                .map(|(name, head)| (name.to_owned(), head.to_owned()))
+                .collect(),
+            parents: [("f1", "c2"), ("c3", "c2"), ("c2", "c1")]
+                .into_iter()
+                .map(|(commit, parent)| (commit.to_owned(), vec![parent.to_owned()]))
+                .collect(),
+            paths: [
+                ("f1", "docs/readme.md"),
+                ("c3", "src/main.rs"),
+                ("c2", "Cargo.toml"),
+            ]
+            .into_iter()
+            .map(|(commit, path)| (commit.to_owned(), vec![path.to_owned()]))
+            .collect(),
+        };
+        Event {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            parent: None,
+            user: "corn".to_owned(),
+            data,
+            depth: 0,
+        }
+    }
+
+    /// Build a new commitish trigger
+    fn trigger(branches: &[&str], paths: &[&str]) -> EventTrigger {
+        EventTrigger::NewCommitish {
+            branches: branches.iter().map(|v| (*v).to_owned()).collect(),
+            paths: paths.iter().map(|v| (*v).to_owned()).collect(),
+        }
+    }
+
+    /// Check if an event confirmed a trigger
+    fn confirmed(event: &Event, trigger: &EventTrigger) -> bool {
+        matches!(event.could_trigger(trigger), TriggerPotential::Confirmed)
+    }
+
+    #[test]
+    fn test_commitish_trigger_no_filters() {
+        let event = commitish_event();
+        let trigger = trigger(&[], &[]);
+        assert!(confirmed(&event, &trigger));
+        // the newest new commit should be targeted
+        assert_eq!(
+            event.commitish_target(&trigger).map(String::as_str),
+            Some("f1")
+        );
+    }
+
+    #[test]
+    fn test_commitish_trigger_branches() {
+        let event = commitish_event();
+        // branch patterns must match the entire branch name
+        assert!(confirmed(&event, &trigger(&["main"], &[])));
+        assert!(!confirmed(&event, &trigger(&["mai"], &[])));
+        assert!(!confirmed(&event, &trigger(&["release/.*"], &[])));
+        // branches that moved to an existing commit still match without path filters
+        let stale = trigger(&["stale"], &[]);
+        assert!(confirmed(&event, &stale));
+        assert_eq!(
+            event.commitish_target(&stale).map(String::as_str),
+            Some("old")
+        );
+        // but they have no new changed paths to match
+        assert!(!confirmed(&event, &trigger(&["stale"], &[".*"])));
+    }
+
+    #[test]
+    fn test_commitish_trigger_paths() {
+        let event = commitish_event();
+        // the newest commit that changed a matching path should be targeted
+        let src = trigger(&[], &["src/.*"]);
+        assert!(confirmed(&event, &src));
+        assert_eq!(event.commitish_target(&src).map(String::as_str), Some("c3"));
+        // path patterns must match the entire path
+        assert!(!confirmed(&event, &trigger(&[], &["src"])));
+        assert!(!confirmed(&event, &trigger(&[], &["tests/.*"])));
+    }
+
+    #[test]
+    fn test_commitish_trigger_paths_on_branch() {
+        let event = commitish_event();
+        // docs were only changed on our feature branch
+        assert!(!confirmed(&event, &trigger(&["main"], &["docs/.*"])));
+        let docs = trigger(&["main", "feature"], &["docs/.*"]);
+        assert!(confirmed(&event, &docs));
+        assert_eq!(
+            event.commitish_target(&docs).map(String::as_str),
+            Some("f1")
+        );
+        // shared parents count towards every branch they are on
+        let cargo = trigger(&["main"], &["Cargo\\.toml"]);
+        assert!(confirmed(&event, &cargo));
+        assert_eq!(
+            event.commitish_target(&cargo).map(String::as_str),
+            Some("c3")
+        );
+    }
+
+    #[test]
+    fn test_commitish_target_other_triggers() {
+        let event = commitish_event();
+        assert!(event.commitish_target(&EventTrigger::NewSample).is_none());
+        assert!(!confirmed(&event, &EventTrigger::NewSample));
+    }
+}
@@ -9,7 +9,7 @@ use cart_rs::UncartStream;
 use chrono::prelude::*;
 use indicatif::ProgressBar;
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ffi::OsString;
 use std::hash::Hasher;
 use std::net::IpAddr;
@@ -17,7 +17,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use uuid::Uuid;
 
-use super::{OnDiskFile, TreeSupport};
+use super::{OnDiskFile, TagProvenanceRequest, TreeSupport};
 use crate::{matches_adds, matches_removes, matches_update_opt, same};
 
 // api only imports
@@ -43,8 +43,8 @@ cfg_if::cfg_if! {
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "api", feature = "client"))] {
         use crate::models::scylla_utils::keys::KeySupport;
-        use super::backends::{OutputSupport, TagSupport};
-        use super::{OutputKind, TagRequest, TagType};
+        use super::backends::{OutputSupport, SubscriptionSupport, TagSupport};
+        use super::{OutputKind, SubscriptionTarget, TagRequest, TagType};
     }
 }
 
@@ -133,6 +133,8 @@ cfg_if::cfg_if! {
             pub file_name: Option<String>,
             /// The trigger depth for this sample request
             pub trigger_depth: u8,
+            /// Where this samples tags came from
+            pub provenance: TagProvenanceRequest,
         }
 
         /// A request for a comment about a specific sample
@@ -146,6 +148,8 @@ cfg_if::cfg_if! {
             pub comment: String,
             /// Mappings of attachment file names to S3 UUID's
             pub attachments: HashMap<String, Uuid>,
+            /// The comment this comment is a reply to
+            pub parent: Option<Uuid>,
         }
 
         impl Default for CommentForm {
@@ -155,7 +159,8 @@ cfg_if::cfg_if! {
                     id: Uuid::new_v4(),
                     groups: Vec::default(),
                     comment: String::default(),
-                    attachments: HashMap::default()
+                    attachments: HashMap::default(),
+                    parent: None,
                 }
             }
         }
@@ -348,6 +353,9 @@ pub struct SampleRequest {
     /// The trigger depth of this sample upload
     #[serde(default)]
     pub trigger_depth: u8,
+    /// Where this samples tags came from if they were not set directly by a user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<TagProvenanceRequest>,
 }
 
 impl SampleRequest {
@@ -379,6 +387,7 @@ impl SampleRequest {
             path: Some(path.into()),
             data: None,
             trigger_depth: 0,
+            provenance: None,
         }
     }
 
@@ -411,6 +420,7 @@ impl SampleRequest {
             path: None,
             data: Some(data),
             trigger_depth: 0,
+            provenance: None,
         }
     }
 
@@ -503,6 +513,28 @@ impl SampleRequest {
         self
     }
 
+    /// Sets where this samples tags came from
+    ///
+    /// # Arguments
+    ///
+    /// * `provenance` - Where this samples tags came from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{SampleRequest, TagProvenanceRequest};
+    /// use uuid::Uuid;
+    ///
+    /// SampleRequest::new("/corn.jpeg", vec!("CornPeeps"))
+    ///     .tag("plant", "corn")
+    ///     .provenance(TagProvenanceRequest::default().job(Uuid::new_v4()));
+    /// ```
+    #[must_use]
+    pub fn provenance(mut self, provenance: TagProvenanceRequest) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
     /// Create a multipart form from this sample request
     #[cfg(feature = "client")]
     pub async fn to_form(mut self) -> Result<reqwest::multipart::Form, Error> {
@@ -527,6 +559,15 @@ impl SampleRequest {
         };
         // if a trigger depth was set then add that to our form
         let form = form.text("trigger_depth", format!("{}", self.trigger_depth));
+        // add where our tags came from if it was set
+        let form = match self.provenance.take() {
+            Some(mut provenance) => {
+                let form = multipart_text_to_string!(form, "provenance[job]", provenance.job);
+                let form = multipart_text_to_string!(form, "provenance[result]", provenance.result);
+                multipart_text_to_string!(form, "provenance[confidence]", provenance.confidence)
+            }
+            None => form,
+        };
         // read in this file if a path was set
         let form = if let Some(path) = self.path.take() {
             // a path was set so read in that file and add it to the form
@@ -662,7 +703,7 @@ impl Default for OriginTypes {
 }
 
 /// A request to to set the origin for a submission
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
 pub struct OriginRequest {
     /// The type of origin this should be deserialized as
@@ -1946,7 +1987,10 @@ pub type TagMap = HashMap<String, HashMap<String, HashSet<String>>>;
 		"id": "ba788031-3c3a-4e62-a158-71bbce73b25a",
 		"author": "mcarson",
 		"comment": "This is definitely not malware",
-		"attachments": {}
+		"attachments": {},
+		"reactions": {
+			"Agree": ["bob"]
+		}
 	}
 )))]
 pub struct Comment {
@@ -1962,6 +2006,201 @@ pub struct Comment {
     pub comment: String,
     /// Mappings of file names to their S3 UUID
     pub attachments: HashMap<String, Uuid>,
+    /// The comment this comment is a reply to if it is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
+    /// When this comment was last edited if it has been
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited: Option<DateTime<Utc>>,
+    /// The previous revisions of this comment from oldest to newest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<CommentRevision>,
+    /// The users that have left each reaction on this comment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub reactions: BTreeMap<CommentReaction, BTreeSet<String>>,
+}
+
+impl Comment {
+    /// The most users a single comment can mention
+    pub const MAX_MENTIONS: usize = 20;
+
+    /// Get the usernames mentioned in a comment with an `@`
+    ///
+    /// An `@` directly after a username character (like in an email address) is not a mention.
+    /// Only the first [`Comment::MAX_MENTIONS`] users mentioned are returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - The comment text to get mentions from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::Comment;
+    ///
+    /// let mentions = Comment::parse_mentions("@alice can you look at this? cc @bob.");
+    /// assert!(mentions.contains("alice"));
+    /// assert!(mentions.contains("bob"));
+    /// ```
+    #[must_use]
+    pub fn parse_mentions(comment: &str) -> BTreeSet<String> {
+        // check if a char can be part of a username
+        let is_username_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+        let mut mentions = BTreeSet::default();
+        // track the previous char so we can skip email addresses
+        let mut prev = None;
+        for (index, c) in comment.char_indices() {
+            if c == '@' && !prev.is_some_and(is_username_char) {
+                // get the username that follows this @
+                let rest = &comment[index + 1..];
+                let end = rest
+                    .find(|c: char| !is_username_char(c))
+                    .unwrap_or(rest.len());
+                // a trailing period ends a sentence instead of the username
+                let username = rest[..end].trim_end_matches('.');
+                if !username.is_empty() {
+                    mentions.insert(username.to_owned());
+                    // stop once we have found the most mentions a comment can have
+                    if mentions.len() >= Self::MAX_MENTIONS {
+                        break;
+                    }
+                }
+            }
+            prev = Some(c);
+        }
+        mentions
+    }
+}
+
+/// A reaction a user can leave on a comment
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    clap::ValueEnum,
+)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum CommentReaction {
+    /// The comment has been seen and acknowledged
+    Acknowledge,
+    /// The user agrees with this comment
+    Agree,
+    /// The user disagrees with this comment
+    Disagree,
+    /// The issue raised in this comment has been resolved
+    Resolved,
+}
+
+impl CommentReaction {
+    /// Get this reaction as a str
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentReaction::Acknowledge => "Acknowledge",
+            CommentReaction::Agree => "Agree",
+            CommentReaction::Disagree => "Disagree",
+            CommentReaction::Resolved => "Resolved",
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl FromStr for CommentReaction {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Acknowledge" => Ok(CommentReaction::Acknowledge),
+            "Agree" => Ok(CommentReaction::Agree),
+            "Disagree" => Ok(CommentReaction::Disagree),
+            "Resolved" => Ok(CommentReaction::Resolved),
+            _ => crate::bad!(format!("Invalid comment reaction '{s}'")),
+        }
+    }
+}
+
+/// A previous version of an edited comment
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct CommentRevision {
+    /// The text of the comment at this revision
+    pub comment: String,
+    /// When this revision was written
+    pub written: DateTime<Utc>,
+}
+
+/// A comment and all of the replies to it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommentThread {
+    /// The comment that starts this thread
+    pub comment: Comment,
+    /// The replies to this comment in the order they were uploaded
+    pub replies: Vec<CommentThread>,
+}
+
+impl CommentThread {
+    /// Build the threads for a list of comments
+    ///
+    /// Replies to a comment that is not in this list (because it was deleted or is not visible)
+    /// start their own thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `comments` - The comments to thread
+    #[must_use]
+    pub fn build(comments: &[Comment]) -> Vec<CommentThread> {
+        // get the ids of all comments so we can find orphaned replies
+        let ids = comments
+            .iter()
+            .map(|comment| comment.id)
+            .collect::<HashSet<Uuid>>();
+        // group our replies by the comment they are replying to
+        let mut replies: HashMap<Uuid, Vec<&Comment>> = HashMap::default();
+        let mut roots = Vec::default();
+        for comment in comments {
+            match comment.parent {
+                Some(parent) if ids.contains(&parent) => {
+                    replies.entry(parent).or_default().push(comment);
+                }
+                _ => roots.push(comment),
+            }
+        }
+        // build the thread for each root comment
+        roots
+            .into_iter()
+            .map(|root| Self::build_helper(root, &replies))
+            .collect()
+    }
+
+    /// Build the thread for a single comment
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - The comment that starts this thread
+    /// * `replies` - The replies to each comment
+    fn build_helper(comment: &Comment, replies: &HashMap<Uuid, Vec<&Comment>>) -> CommentThread {
+        // build the threads for any replies to this comment
+        let replies = replies
+            .get(&comment.id)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child| Self::build_helper(child, replies))
+                    .collect()
+            })
+            .unwrap_or_default();
+        CommentThread {
+            comment: comment.clone(),
+            replies,
+        }
+    }
 }
 
 impl PartialEq<CommentRequest> for Comment {
@@ -1975,8 +2214,9 @@ impl PartialEq<CommentRequest> for Comment {
         if !req.groups.is_empty() {
             same!(self.groups, req.groups);
         }
-        // make sure the comment string is correct
+        // make sure the comment string and parent are correct
         same!(self.comment, req.comment);
+        same!(self.parent, req.parent);
         // make sure the on disk file info matches
         for on_disk in req.files.iter() {
             // build the path to check for
@@ -2212,10 +2452,41 @@ impl TagSupport for Sample {
             shared,
         )
         .await?;
+        // rewrite our tags to their canonical forms and make sure our groups allow them
+        super::Group::apply_tag_vocabularies_by_name(&req.groups, &mut req.tags, shared).await?;
         // get the earliest time this sample was uploaded for each group
         let earliest = self.earliest();
+        // keep the tags we are adding so we can notify anyone following them
+        let tags = req.tags.clone();
+        let groups = req.groups.clone();
+        // determine where these tags came from
+        let origins = req
+            .provenance
+            .clone()
+            .unwrap_or_default()
+            .resolve(user, TagType::Files, &self.sha256, &groups, shared)
+            .await?;
         // save our files tags to scylla
-        super::backends::db::tags::create(user, self.sha256.clone(), req, &earliest, shared).await
+        super::backends::db::tags::create(
+            user,
+            self.sha256.clone(),
+            req,
+            &origins,
+            &earliest,
+            shared,
+        )
+        .await?;
+        // let anyone following these tags know this sample was tagged
+        let item = SubscriptionTarget::sample(&self.sha256);
+        super::backends::db::subscriptions::notify_tagged(
+            &item,
+            &tags,
+            &groups,
+            &user.username,
+            shared,
+        )
+        .await;
+        Ok(())
     }
 
     /// Delete some tags from this sample
@@ -2288,6 +2559,14 @@ impl PartialEq<TagRequest<Sample>> for Sample {
     }
 }
 
+#[cfg(any(feature = "api", feature = "client"))]
+impl SubscriptionSupport for Sample {
+    /// Users follow samples by their sha256
+    fn subscription_target(&self) -> Option<SubscriptionTarget> {
+        Some(SubscriptionTarget::sample(&self.sha256))
+    }
+}
+
 #[cfg(any(feature = "api", feature = "client"))]
 impl OutputSupport for Sample {
     /// Get the tag kind to write to the DB
@@ -2460,6 +2739,38 @@ impl TreeSupport for Sample {
             // add this to our list of children nodes
             children.push(node);
         }
+        // add any children that our results found
+        let found =
+            super::backends::trees::result_children(&self.sha256, self, user, shared).await?;
+        super::TreeNode::merge(&mut children, found);
+        // add the repos and commits this sample was built from
+        let mut sources = Vec::default();
+        for sub in &self.submissions {
+            if let Origin::Source {
+                repo,
+                commitish,
+                commit,
+                ..
+            } = &sub.origin
+            {
+                // add the commit this sample was built from
+                let tree_commit = super::TreeCommitish {
+                    repo: repo.clone(),
+                    commitish: commitish.clone(),
+                    commit: commit.clone(),
+                };
+                let data = super::TreeNodeData::Commitish(tree_commit);
+                let relationships = vec![super::TreeRelationships::BuiltFrom];
+                sources.push(super::TreeNode::new(relationships, data));
+                // add the repo this sample was built from if we can see it
+                if let Ok(repo_data) = super::Repo::get(user, repo, shared).await {
+                    let data = super::TreeNodeData::Repo(repo_data);
+                    let relationships = vec![super::TreeRelationships::BuiltFrom];
+                    sources.push(super::TreeNode::new(relationships, data));
+                }
+            }
+        }
+        super::TreeNode::merge(&mut children, sources);
         Ok(children)
     }
 }
@@ -2739,6 +3050,9 @@ pub struct CommentRequest {
     pub files: Vec<OnDiskFile>,
     /// The attachemnts to upload directly
     pub buffers: Vec<Buffer>,
+    /// The comment this comment is a reply to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
 }
 
 impl CommentRequest {
@@ -2764,6 +3078,34 @@ impl CommentRequest {
             comment: comment.into(),
             files: Vec::default(),
             buffers: Vec::default(),
+            parent: None,
+        }
+    }
+
+    /// Creates a new request for a comment that isn't on a sample
+    ///
+    /// This is used when commenting on repos, reactions, or results where
+    /// the target is passed to the client directly instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - The comment to add
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::CommentRequest;
+    ///
+    /// let req = CommentRequest::text("I am a comment");
+    /// ```
+    pub fn text<C: Into<String>>(comment: C) -> Self {
+        CommentRequest {
+            sha256: String::default(),
+            groups: Vec::default(),
+            comment: comment.into(),
+            files: Vec::default(),
+            buffers: Vec::default(),
+            parent: None,
         }
     }
 
@@ -2900,6 +3242,29 @@ impl CommentRequest {
         self
     }
 
+    /// Sets the comment this comment is a reply to
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The id of the comment to reply to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::CommentRequest;
+    /// use uuid::Uuid;
+    ///
+    /// let sha256 = "63b0490d4736e740f26ea9483d55c254abe032845b70ba84ea463ca6582d106f";
+    /// let req = CommentRequest::new(sha256, "I am a reply")
+    ///     .parent(Uuid::new_v4());
+    /// ```
+    #[must_use]
+    pub fn parent(mut self, parent: Uuid) -> Self {
+        // set the comment we are replying to
+        self.parent = Some(parent);
+        self
+    }
+
     /// Create a multipart form from this comment request
     #[cfg(feature = "client")]
     pub async fn to_form(mut self) -> Result<reqwest::multipart::Form, Error> {
@@ -2909,6 +3274,8 @@ impl CommentRequest {
             .text("comment", self.comment);
         // add the groups to share this result with
         let mut form = multipart_list!(form, "groups", self.groups);
+        // add the comment we are replying to if we are replying
+        form = multipart_text_to_string!(form, "parent", self.parent);
         // add any files that were added by path
         for on_disk in self.files {
             // a path was set so read in that file and add it to the form
@@ -2975,6 +3342,84 @@ impl DeleteCommentParams {
     }
 }
 
+/// An update for a comment
+///
+/// Only the author of a comment can edit its text but anyone who can see a comment can react to it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct CommentUpdate {
+    /// The new text for this comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The reactions to add to this comment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_reactions: Vec<CommentReaction>,
+    /// The reactions to remove from this comment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove_reactions: Vec<CommentReaction>,
+}
+
+impl CommentUpdate {
+    /// Sets the new text for this comment
+    ///
+    /// The current text will be kept as a revision.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - The new text for this comment
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::CommentUpdate;
+    ///
+    /// let update = CommentUpdate::default().comment("I am an edited comment");
+    /// ```
+    #[must_use]
+    pub fn comment<T: Into<String>>(mut self, comment: T) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Adds a reaction to this comment
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction to add
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{CommentReaction, CommentUpdate};
+    ///
+    /// let update = CommentUpdate::default().add_reaction(CommentReaction::Acknowledge);
+    /// ```
+    #[must_use]
+    pub fn add_reaction(mut self, reaction: CommentReaction) -> Self {
+        self.add_reactions.push(reaction);
+        self
+    }
+
+    /// Removes a reaction from this comment
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction to remove
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{CommentReaction, CommentUpdate};
+    ///
+    /// let update = CommentUpdate::default().remove_reaction(CommentReaction::Acknowledge);
+    /// ```
+    #[must_use]
+    pub fn remove_reaction(mut self, reaction: CommentReaction) -> Self {
+        self.remove_reactions.push(reaction);
+        self
+    }
+}
+
 /// The options that you can set when listing files in Thorium
 ///
 /// Currently this only supports single tag queries but when ES support is added multi tag queries
@@ -2,6 +2,7 @@
 
 mod branches;
 mod commits;
+mod mirrors;
 mod repos;
 
 pub use commits::{
@@ -9,6 +10,9 @@ pub use commits::{
     Commitish, CommitishDetails, CommitishKinds, CommitishListParams, CommitishMapRequest,
     CommitishRequest, GitTag, GitTagDetails, GitTagRequest,
 };
+pub use mirrors::{
+    RepoMirror, RepoMirrorClaimOpts, RepoMirrorCredentials, RepoMirrorFetch, RepoMirrorRequest,
+};
 pub use repos::{
     Repo, RepoCheckout, RepoCreateResponse, RepoDataUploadResponse, RepoDependency,
     RepoDependencyRequest, RepoDownloadOpts, RepoListLine, RepoListOpts, RepoListParams,
@@ -24,8 +24,17 @@ pub struct CommitRequest {
     pub description: Option<String>,
     /// Whether this description was truncated or not
     pub truncated: bool,
+    /// The paths changed by this commit
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// The hashes of this commits parents
+    #[serde(default)]
+    pub parents: Vec<String>,
 }
 
+/// The max number of changed paths to track for a single commit
+const MAX_CHANGED_PATHS: usize = 10_000;
+
 impl CommitRequest {
     /// Create a new [`CommitRequest`]
     ///
@@ -63,6 +72,8 @@ impl CommitRequest {
             topic,
             description,
             truncated: false,
+            paths: Vec::default(),
+            parents: Vec::default(),
         };
         // truncate our description if needed
         req.truncate_description(524_288);
@@ -107,12 +118,49 @@ impl CommitRequest {
             topic: Some(topic),
             description,
             truncated: false,
+            paths: Vec::default(),
+            parents: Vec::default(),
         };
         // truncate our description if needed
         req.truncate_description(524_288);
         req
     }
 
+    /// Set this commits parents and the paths it changed based on a diff against its first parent
+    ///
+    /// Only the first 10,000 changed paths are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `repo` - The repo this commit is in
+    /// * `oid` - The id of the commit to diff
+    #[cfg(feature = "client")]
+    pub fn changed_paths(
+        mut self,
+        repo: &git2::Repository,
+        oid: git2::Oid,
+    ) -> Result<Self, crate::client::Error> {
+        // get the tree for this commit and its first parent if it has one
+        let commit = repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        let parent = commit
+            .parents()
+            .next()
+            .map(|parent| parent.tree())
+            .transpose()?;
+        // diff these trees to find the paths that were changed
+        let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)?;
+        self.paths = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .take(MAX_CHANGED_PATHS)
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        // track our parents so triggers can tell which branches these changes are on
+        self.parents = commit.parent_ids().map(|id| id.to_string()).collect();
+        Ok(self)
+    }
+
     /// Truncate this [`CommitRequest`]'s description to some number of bytes
     ///
     /// # Arguments
@@ -340,6 +388,9 @@ pub struct CommitishMapRequest {
     pub end: bool,
     /// A map of commit hashes and their info
     pub commitishes: HashMap<String, CommitishRequest>,
+    /// The trigger depth for this request
+    #[serde(default)]
+    pub trigger_depth: u8,
 }
 
 /// Info on a single commit for a repo
@@ -0,0 +1,285 @@
+//! A mirror is a repository that Thorium periodically fetches new commits for
+
+use chrono::prelude::*;
+use std::collections::HashMap;
+
+use super::RepoCheckout;
+
+/// The default number of seconds to wait between fetches of a mirrored repo
+fn default_mirror_interval() -> u64 {
+    3600
+}
+
+/// The credentials to use when fetching a mirrored repo over HTTP(S)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct RepoMirrorCredentials {
+    /// The username to authenticate as
+    pub username: String,
+    /// The token or password to authenticate with
+    pub token: String,
+}
+
+/// A request to start mirroring a remote repo in Thorium
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct RepoMirrorRequest {
+    /// The HTTP(S) url to clone and fetch this repo from
+    pub remote: String,
+    /// The groups to share this repo and its commits with
+    pub groups: Vec<String>,
+    /// The number of seconds to wait between fetches
+    #[serde(default = "default_mirror_interval")]
+    pub interval: u64,
+    /// The credentials to use when fetching this repo if it is not public
+    pub credentials: Option<RepoMirrorCredentials>,
+    /// The default checkout behavior for this repo
+    pub default_checkout: Option<RepoCheckout>,
+}
+
+impl RepoMirrorRequest {
+    /// Create a new repo mirror request
+    ///
+    /// # Arguments
+    ///
+    /// * `remote` - The HTTP(S) url to clone and fetch this repo from
+    /// * `groups` - The groups to share this repo with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::RepoMirrorRequest;
+    ///
+    /// RepoMirrorRequest::new("https://github.com/rust-lang/rust", vec!("CornPeeps"));
+    /// ```
+    pub fn new<R: Into<String>, G: Into<String>>(remote: R, groups: Vec<G>) -> Self {
+        RepoMirrorRequest {
+            remote: remote.into(),
+            groups: groups.into_iter().map(Into::into).collect(),
+            interval: default_mirror_interval(),
+            credentials: None,
+            default_checkout: None,
+        }
+    }
+
+    /// Set the number of seconds to wait between fetches of this repo
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The number of seconds to wait between fetches
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::RepoMirrorRequest;
+    ///
+    /// // fetch this repo every 6 hours
+    /// RepoMirrorRequest::new("https://github.com/rust-lang/rust", vec!("CornPeeps"))
+    ///     .interval(21600);
+    /// ```
+    #[must_use]
+    pub fn interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the credentials to fetch this repo with
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The username to authenticate as
+    /// * `token` - The token or password to authenticate with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::RepoMirrorRequest;
+    ///
+    /// RepoMirrorRequest::new("https://github.com/corn/private", vec!("CornPeeps"))
+    ///     .credentials("mcorn", "<token>");
+    /// ```
+    #[must_use]
+    pub fn credentials<U: Into<String>, T: Into<String>>(mut self, username: U, token: T) -> Self {
+        self.credentials = Some(RepoMirrorCredentials {
+            username: username.into(),
+            token: token.into(),
+        });
+        self
+    }
+
+    /// Set the default checkout behavior for this repo
+    ///
+    /// # Arguments
+    ///
+    /// * `checkout` - The default checkout behavior to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{RepoCheckout, RepoMirrorRequest};
+    ///
+    /// RepoMirrorRequest::new("https://github.com/rust-lang/rust", vec!("CornPeeps"))
+    ///     .default_checkout(RepoCheckout::branch("main"));
+    /// ```
+    #[must_use]
+    pub fn default_checkout(mut self, checkout: RepoCheckout) -> Self {
+        self.default_checkout = Some(checkout);
+        self
+    }
+}
+
+/// A remote repo that Thorium periodically fetches and ingests new commits for
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct RepoMirror {
+    /// The url of the repo in Thorium
+    pub url: String,
+    /// The HTTP(S) url this repo is fetched from
+    pub remote: String,
+    /// The groups new data for this repo is shared with
+    pub groups: Vec<String>,
+    /// The user that created this mirror
+    pub creator: String,
+    /// The number of seconds to wait between fetches
+    pub interval: u64,
+    /// Whether this mirror is fetched with credentials
+    pub authenticated: bool,
+    /// The credentials to fetch this repo with (only returned to the mirror fetcher)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<RepoMirrorCredentials>,
+    /// When this mirror was created
+    pub created: DateTime<Utc>,
+    /// When this mirror was last fetched
+    pub last_fetch: Option<DateTime<Utc>>,
+    /// When this mirror will next be fetched
+    pub next_fetch: DateTime<Utc>,
+    /// The commit each branch and tag pointed to at the last successful fetch
+    #[serde(default)]
+    pub refs: HashMap<String, String>,
+    /// The number of commitishes ingested by the last fetch
+    #[serde(default)]
+    pub ingested: u64,
+    /// The error hit by the last fetch if it failed
+    pub last_error: Option<String>,
+}
+
+impl RepoMirror {
+    /// Create a new repo mirror that should be fetched right away
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The url of the repo in Thorium
+    /// * `creator` - The user that is creating this mirror
+    /// * `req` - The request to build this mirror from
+    #[must_use]
+    pub fn new<U: Into<String>, C: Into<String>>(
+        url: U,
+        creator: C,
+        req: RepoMirrorRequest,
+    ) -> Self {
+        let now = Utc::now();
+        RepoMirror {
+            url: url.into(),
+            remote: req.remote,
+            groups: req.groups,
+            creator: creator.into(),
+            interval: req.interval,
+            authenticated: req.credentials.is_some(),
+            credentials: req.credentials,
+            created: now,
+            last_fetch: None,
+            next_fetch: now,
+            refs: HashMap::default(),
+            ingested: 0,
+            last_error: None,
+        }
+    }
+
+    /// Remove any credentials from this mirror so it can be shown to users
+    #[must_use]
+    pub fn scrub(mut self) -> Self {
+        self.credentials = None;
+        self
+    }
+}
+
+/// Default the mirror claim limit to 10
+fn default_mirror_claim_limit() -> usize {
+    10
+}
+
+/// The params for claiming mirrors that are due to be fetched
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct RepoMirrorClaimOpts {
+    /// The max number of mirrors to claim
+    #[serde(default = "default_mirror_claim_limit")]
+    pub limit: usize,
+}
+
+impl Default for RepoMirrorClaimOpts {
+    /// Create a default `RepoMirrorClaimOpts`
+    fn default() -> Self {
+        RepoMirrorClaimOpts {
+            limit: default_mirror_claim_limit(),
+        }
+    }
+}
+
+impl RepoMirrorClaimOpts {
+    /// Set the maximum number of mirrors to claim
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The limit to set
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// The outcome of fetching a mirrored repo
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct RepoMirrorFetch {
+    /// The commit each branch and tag now points to
+    #[serde(default)]
+    pub refs: HashMap<String, String>,
+    /// The number of commitishes that were ingested
+    #[serde(default)]
+    pub ingested: u64,
+    /// The error that caused this fetch to fail if it failed
+    pub error: Option<String>,
+}
+
+impl RepoMirrorFetch {
+    /// Build a successful mirror fetch
+    ///
+    /// # Arguments
+    ///
+    /// * `refs` - The commit each branch and tag now points to
+    /// * `ingested` - The number of commitishes that were ingested
+    #[must_use]
+    pub fn success(refs: HashMap<String, String>, ingested: u64) -> Self {
+        RepoMirrorFetch {
+            refs,
+            ingested,
+            error: None,
+        }
+    }
+
+    /// Build a failed mirror fetch
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error that caused this fetch to fail
+    #[must_use]
+    pub fn failed<T: Into<String>>(error: T) -> Self {
+        RepoMirrorFetch {
+            refs: HashMap::default(),
+            ingested: 0,
+            error: Some(error.into()),
+        }
+    }
+}
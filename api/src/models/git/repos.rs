@@ -3,12 +3,15 @@
 use chrono::prelude::*;
 use indicatif::ProgressBar;
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hasher;
 use std::path::Path;
 use std::path::PathBuf;
 use uuid::Uuid;
 
 use super::CommitishKinds;
-use crate::models::{KeySupport, TagMap};
+use crate::models::{
+    Comment, KeySupport, TagMap, TreeNode, TreeNodeData, TreeRelationships, TreeSupport,
+};
 
 // api only imports
 #[cfg(feature = "api")]
@@ -17,8 +20,8 @@ use crate::models::TagDeleteRequest;
 // api/client imports
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "api", feature = "client"))] {
-        use crate::models::{TagRequest, TagType, OutputKind};
-        use crate::models::backends::{TagSupport, OutputSupport};
+        use crate::models::{TagRequest, TagType, OutputKind, SubscriptionTarget};
+        use crate::models::backends::{TagSupport, OutputSupport, SubscriptionSupport};
     }
 }
 
@@ -345,6 +348,9 @@ pub struct Repo {
     pub submissions: Vec<RepoSubmissionChunk>,
     /// The earliest commit ever seen in this repo
     pub earliest: Option<DateTime<Utc>>,
+    /// Any comments for this repo
+    #[serde(default)]
+    pub comments: Vec<Comment>,
 }
 
 impl Repo {
@@ -486,11 +492,42 @@ impl TagSupport for Repo {
         use crate::models::GroupAllowAction;
         self.validate_check_allow_groups(user, &mut req.groups, GroupAllowAction::Tags, shared)
             .await?;
+        // rewrite our tags to their canonical forms and make sure our groups allow them
+        crate::models::Group::apply_tag_vocabularies_by_name(&req.groups, &mut req.tags, shared)
+            .await?;
         // get the earliest time this repo was uploaded for each group
         let earliest = self.earliest();
+        // keep the tags we are adding so we can notify anyone following them
+        let tags = req.tags.clone();
+        let groups = req.groups.clone();
+        // determine where these tags came from
+        let origins = req
+            .provenance
+            .clone()
+            .unwrap_or_default()
+            .resolve(user, TagType::Repos, &self.url, &groups, shared)
+            .await?;
         // save our repo's tags to scylla
-        crate::models::backends::db::tags::create(user, self.url.clone(), req, &earliest, shared)
-            .await
+        crate::models::backends::db::tags::create(
+            user,
+            self.url.clone(),
+            req,
+            &origins,
+            &earliest,
+            shared,
+        )
+        .await?;
+        // let anyone following these tags know this repo was tagged
+        let item = SubscriptionTarget::repo(&self.url);
+        crate::models::backends::db::subscriptions::notify_tagged(
+            &item,
+            &tags,
+            &groups,
+            &user.username,
+            shared,
+        )
+        .await;
+        Ok(())
     }
 
     /// Delete some tags from this repo
@@ -540,6 +577,14 @@ impl TagSupport for Repo {
     }
 }
 
+#[cfg(any(feature = "api", feature = "client"))]
+impl SubscriptionSupport for Repo {
+    /// Users follow repos by their url
+    fn subscription_target(&self) -> Option<SubscriptionTarget> {
+        Some(SubscriptionTarget::repo(&self.url))
+    }
+}
+
 #[cfg(any(feature = "api", feature = "client"))]
 impl OutputSupport for Repo {
     /// Get the tag kind to write to the DB
@@ -608,6 +653,74 @@ impl OutputSupport for Repo {
     }
 }
 
+impl TreeSupport for Repo {
+    /// Hash this child object
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed to set the hasher to use
+    fn tree_hash(&self, seed: i64) -> u64 {
+        // build a hasher
+        let mut hasher = gxhash::GxHasher::with_seed(seed);
+        // hash this repos url
+        hasher.write(self.url.as_bytes());
+        // finalize our hasher
+        hasher.finish()
+    }
+
+    /// Gather any initial nodes for a tree
+    #[cfg(feature = "api")]
+    async fn gather_initial(
+        user: &crate::models::User,
+        query: &crate::models::TreeQuery,
+        shared: &crate::utils::Shared,
+    ) -> Result<Vec<TreeNodeData>, crate::utils::ApiError> {
+        // build a list of initial data
+        let mut initial = Vec::with_capacity(query.repos.len());
+        // get all of our initial repos
+        for url in &query.repos {
+            // get this repos data
+            let repo = Repo::get(user, url, shared).await?;
+            // wrap this repo in a tree node data object
+            initial.push(TreeNodeData::Repo(repo));
+        }
+        Ok(initial)
+    }
+
+    /// Gather any children for this child node
+    #[cfg(feature = "api")]
+    async fn gather_children(
+        &self,
+        user: &crate::models::User,
+        shared: &crate::utils::Shared,
+    ) -> Result<Vec<TreeNode>, crate::utils::ApiError> {
+        // list all samples built from this repo
+        let opts = crate::models::FileListOpts::default().tag("Repo", &self.url);
+        let samples = crate::models::backends::trees::list_samples(user, opts, shared).await?;
+        // build a list of related children
+        let mut children = Vec::with_capacity(samples.len());
+        for sample in samples {
+            // samples are only built from this repo if they have a source origin for it
+            let built = sample.submissions.iter().any(|sub| match &sub.origin {
+                crate::models::Origin::Source { repo, .. } => *repo == self.url,
+                _ => false,
+            });
+            let relationships = if built {
+                vec![TreeRelationships::BuiltFrom]
+            } else {
+                Vec::default()
+            };
+            // wrap this sample in a node
+            children.push(TreeNode::new(relationships, TreeNodeData::Sample(sample)));
+        }
+        // add any children that our results found
+        let found =
+            crate::models::backends::trees::result_children(&self.url, self, user, shared).await?;
+        TreeNode::merge(&mut children, found);
+        Ok(children)
+    }
+}
+
 impl From<RepoSubmission> for Repo {
     fn from(sub: RepoSubmission) -> Self {
         // build repo with just curent submission
@@ -620,6 +733,7 @@ impl From<RepoSubmission> for Repo {
             default_checkout: None,
             tags: TagMap::default(),
             earliest: None,
+            comments: Vec::default(),
         };
         // add our current submission to this repo
         repo.add(sub);
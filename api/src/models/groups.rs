@@ -1,8 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
-use super::PipelineStats;
+use super::{PipelineStats, TagVocabulary};
 use crate::{
-    matches_adds, matches_clear, matches_clear_opt, matches_removes, matches_set,
+    matches_adds, matches_clear, matches_clear_opt, matches_removes, matches_set, matches_update,
     matches_update_opt, same,
 };
 
@@ -263,6 +263,9 @@ pub struct GroupRequest {
     /// The data that is allowed to be added to this group
     #[serde(default)]
     pub allowed: GroupAllowed,
+    /// The controlled tag vocabulary for this group
+    #[serde(default)]
+    pub tag_vocabulary: TagVocabulary,
 }
 
 impl GroupRequest {
@@ -300,6 +303,7 @@ impl GroupRequest {
             monitors: GroupUsersRequest::default(),
             description: None,
             allowed: GroupAllowed::default(),
+            tag_vocabulary: TagVocabulary::default(),
         }
     }
 
@@ -417,6 +421,27 @@ impl GroupRequest {
         self.description = Some(description.into());
         self
     }
+
+    /// Sets the controlled tag vocabulary for this group
+    ///
+    /// # Arguments
+    ///
+    /// * `vocabulary` - The tag vocabulary to enforce in this group
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{GroupRequest, TagKeyRules, TagVocabulary};
+    ///
+    /// let request = GroupRequest::new("CornGroup")
+    ///     .tag_vocabulary(TagVocabulary::default()
+    ///         .key("family", TagKeyRules::default().value("Emotet"))
+    ///         .alias("malware_family", "family"));
+    /// ```
+    pub fn tag_vocabulary(mut self, vocabulary: TagVocabulary) -> Self {
+        self.tag_vocabulary = vocabulary;
+        self
+    }
 }
 
 /// Helps serde default the group list limit to 50
@@ -718,6 +743,9 @@ pub struct GroupUpdate {
     /// Update what is allowed in this group
     #[serde(default)]
     pub allowed: GroupAllowedUpdate,
+    /// Replace this groups controlled tag vocabulary (an empty vocabulary removes all restrictions)
+    #[serde(default)]
+    pub tag_vocabulary: Option<TagVocabulary>,
 }
 
 impl GroupUpdate {
@@ -851,6 +879,26 @@ impl GroupUpdate {
         self
     }
 
+    /// Replace this groups controlled tag vocabulary
+    ///
+    /// # Arguments
+    ///
+    /// * `vocabulary` - The new tag vocabulary for this group
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{GroupUpdate, TagKeyRules, TagVocabulary};
+    ///
+    /// GroupUpdate::default()
+    ///     .tag_vocabulary(TagVocabulary::default()
+    ///         .key("family", TagKeyRules::default().value("Emotet").required()));
+    /// ```
+    pub fn tag_vocabulary(mut self, vocabulary: TagVocabulary) -> Self {
+        self.tag_vocabulary = Some(vocabulary);
+        self
+    }
+
     /// Check if this is update is empty
     pub fn is_empty(&self) -> bool {
         self.owners.is_empty()
@@ -860,6 +908,7 @@ impl GroupUpdate {
             && self.description.is_none()
             && !self.clear_description
             && self.allowed.is_empty()
+            && self.tag_vocabulary.is_none()
     }
 
     /// Check if a group update just removes a user
@@ -1005,6 +1054,9 @@ pub struct Group {
     /// The data that is allowed to be added to this group
     #[serde(default)]
     pub allowed: GroupAllowed,
+    /// The controlled tag vocabulary for this group
+    #[serde(default)]
+    pub tag_vocabulary: TagVocabulary,
 }
 
 impl Group {
@@ -1089,6 +1141,7 @@ impl PartialEq<GroupRequest> for Group {
         same!(self.users, request.users);
         same!(self.monitors, request.monitors);
         same!(self.description, request.description);
+        same!(self.tag_vocabulary, request.tag_vocabulary);
         true
     }
 }
@@ -1107,6 +1160,7 @@ impl PartialEq<GroupUpdate> for Group {
         same!(self.users, update.users);
         same!(self.monitors, update.monitors);
         matches_clear_opt!(self.description, update.description, update.clear_description);
+        matches_update!(self.tag_vocabulary, update.tag_vocabulary);
         true
     }
 }
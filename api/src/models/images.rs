@@ -590,6 +590,59 @@ impl Default for SpawnLimits {
     }
 }
 
+/// How urgently work should be scheduled compared to other work
+///
+/// Workers for preemptible images can be evicted by the scaler when urgent work
+/// needs their resources. Evicted workers save a checkpoint and return their job to
+/// the queue.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "trace", derive(valuable::Valuable))]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum PriorityClass {
+    /// Low priority work whose workers can be preempted by urgent work
+    Preemptible,
+    /// Normal priority work that is never preempted
+    #[default]
+    Standard,
+    /// SLA bound work that can preempt the workers of preemptible work
+    Urgent,
+}
+
+impl PriorityClass {
+    /// Cast our priority class to a str
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            PriorityClass::Preemptible => "Preemptible",
+            PriorityClass::Standard => "Standard",
+            PriorityClass::Urgent => "Urgent",
+        }
+    }
+
+    /// Whether this is the default standard priority class
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        *self == PriorityClass::Standard
+    }
+
+    /// Override this priority class with another if that one is not standard
+    ///
+    /// This is used to let a reactions priority class override its images.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The priority class to override with
+    #[must_use]
+    pub fn overridden_by(self, other: PriorityClass) -> PriorityClass {
+        // only override our priority class with non standard priority classes
+        if other.is_standard() {
+            self
+        } else {
+            other
+        }
+    }
+}
+
 /// The possible ways to pass these args in
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -651,6 +704,9 @@ pub struct ImageArgs {
     /// What kwarg pass the output location as
     #[serde(default)]
     pub output: ArgStrategy,
+    /// How to pass the path to a restored checkpoint blob when resuming a job
+    #[serde(default)]
+    pub resume: ArgStrategy,
 }
 
 /// The args to pass to all jobs for an image
@@ -686,6 +742,8 @@ pub struct ImageArgsUpdate {
     pub clear_commit: bool,
     /// What kwarg pass the output location as
     pub output: Option<ArgStrategy>,
+    /// How to pass the path to a restored checkpoint blob when resuming a job
+    pub resume: Option<ArgStrategy>,
 }
 
 impl ImageArgsUpdate {
@@ -785,6 +843,13 @@ impl ImageArgsUpdate {
         self.output = Some(output);
         self
     }
+
+    /// Set how to pass the path to a restored checkpoint blob
+    #[must_use]
+    pub fn resume(mut self, resume: ArgStrategy) -> Self {
+        self.resume = Some(resume);
+        self
+    }
 }
 
 /// List of image names with a cursor
@@ -3698,6 +3763,84 @@ impl ImageScaler {
     }
 }
 
+/// The CPU architecture an image is built for
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Copy,
+    clap::ValueEnum,
+    Default,
+    Hash,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum CpuArch {
+    /// 64 bit x86 (amd64)
+    #[default]
+    #[value(name = "x86_64")]
+    X86_64,
+    /// 64 bit arm (aarch64)
+    Arm64,
+    /// 64 bit risc-v
+    Riscv64,
+}
+
+impl std::fmt::Display for CpuArch {
+    /// write our architecture to this formatter
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CpuArch {
+    type Err = &'static str;
+    /// Cast a str to a `CpuArch`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x86_64" | "amd64" => Ok(CpuArch::X86_64),
+            "arm64" | "aarch64" => Ok(CpuArch::Arm64),
+            "riscv64" => Ok(CpuArch::Riscv64),
+            _ => Err("expected `x86_64` or `arm64` or `riscv64`"),
+        }
+    }
+}
+
+impl CpuArch {
+    /// Every architecture Thorium can target
+    pub const ALL: [CpuArch; 3] = [CpuArch::X86_64, CpuArch::Arm64, CpuArch::Riscv64];
+
+    /// Cast a [`CpuArch`] to a str
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            CpuArch::X86_64 => "x86_64",
+            CpuArch::Arm64 => "arm64",
+            CpuArch::Riscv64 => "riscv64",
+        }
+    }
+
+    /// Get the architecture of the host we are running on if Thorium supports it
+    #[must_use]
+    pub fn host() -> Option<Self> {
+        CpuArch::from_str(std::env::consts::ARCH).ok()
+    }
+
+    /// Get the name qemu-user registers its `binfmt_misc` handler for this architecture under
+    #[must_use]
+    pub fn qemu(&self) -> &str {
+        match self {
+            CpuArch::X86_64 => "qemu-x86_64",
+            CpuArch::Arm64 => "qemu-aarch64",
+            CpuArch::Riscv64 => "qemu-riscv64",
+        }
+    }
+}
+
 /// Adds an arg based on its arg strategy
 macro_rules! add_arg {
     ($setting:expr, $value:expr, $cmd:expr) => {
@@ -4003,6 +4146,9 @@ pub struct ImageRequest {
     /// The limit to use for how many workers of this image type can be spawned
     #[serde(default)]
     pub spawn_limit: SpawnLimits,
+    /// How urgently this images workers should be scheduled compared to other work
+    #[serde(default)]
+    pub priority: PriorityClass,
     /// Any volumes to bind in to this container
     #[serde(default)]
     pub volumes: Vec<Volume>,
@@ -4027,6 +4173,15 @@ pub struct ImageRequest {
     /// Whether this is a generator or not
     #[serde(default = "default_as_false")]
     pub generator: bool,
+    /// Whether to capture this images network traffic to a pcap for each job
+    #[serde(default = "default_as_false")]
+    pub capture_network: bool,
+    /// The CPU architecture this image is built for
+    #[serde(default)]
+    pub arch: CpuArch,
+    /// Whether this image can run under qemu-user emulation when no native node is available
+    #[serde(default = "default_as_false")]
+    pub emulation: bool,
     /// How to handle dependencies for this image
     #[serde(default)]
     pub dependencies: Dependencies,
@@ -4049,6 +4204,9 @@ pub struct ImageRequest {
     pub clean_up: Option<Cleanup>,
     /// The settings to use for Kvm jobs
     pub kvm: Option<Kvm>,
+    /// The settings to use for microVM jobs
+    #[serde(default)]
+    pub microvm: Option<MicroVm>,
     /// The set of network policies to apply to the image once it's been spawned
     ///
     /// This currently only applies to images scaled by K8's
@@ -4110,6 +4268,7 @@ impl ImageRequest {
             timeout: None,
             resources: ResourcesRequest::default(),
             spawn_limit: SpawnLimits::Unlimited,
+            priority: PriorityClass::default(),
             volumes: Vec::default(),
             env: HashMap::default(),
             args: ImageArgs::default(),
@@ -4118,12 +4277,16 @@ impl ImageRequest {
             security_context: None,
             collect_logs: true,
             generator: false,
+            capture_network: false,
+            arch: CpuArch::default(),
+            emulation: false,
             dependencies: Dependencies::default(),
             display_type: OutputDisplayType::default(),
             output_collection: OutputCollection::default(),
             child_filters: ChildFilters::default(),
             clean_up: None,
             kvm: None,
+            microvm: None,
             network_policies: HashSet::default(),
         }
     }
@@ -4216,6 +4379,17 @@ impl ImageRequest {
         self
     }
 
+    /// Sets the priority class for this images workers
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority class to set
+    #[must_use]
+    pub fn priority(mut self, priority: PriorityClass) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Adds an environment variable to set inside this image
     ///
     /// # Arguments
@@ -4306,6 +4480,35 @@ impl ImageRequest {
         self
     }
 
+    /// Tells Thorium to capture this images network traffic during each job
+    ///
+    /// The capture will be submitted as a pcap child of each sample in the job.
+    #[must_use]
+    pub fn capture_network(mut self) -> Self {
+        self.capture_network = true;
+        self
+    }
+
+    /// Set the CPU architecture this image is built for
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - The architecture to set
+    #[must_use]
+    pub fn arch(mut self, arch: CpuArch) -> Self {
+        self.arch = arch;
+        self
+    }
+
+    /// Allow this image to run under qemu-user emulation
+    ///
+    /// Emulation is only used when no node with this images architecture can spawn it.
+    #[must_use]
+    pub fn emulation(mut self) -> Self {
+        self.emulation = true;
+        self
+    }
+
     /// The dependency settings to use for this image
     ///
     /// # Arguments
@@ -4377,6 +4580,17 @@ where {
         self
     }
 
+    /// Set the microVM settings
+    ///
+    /// # Arguments
+    ///
+    /// * `microvm` - The microVM settings to set
+    #[must_use]
+    pub fn microvm(mut self, microvm: MicroVm) -> Self {
+        self.microvm = Some(microvm);
+        self
+    }
+
     /// Add the name of a network policy to apply to the image when it's spawned
     ///
     /// This currently only applies when the image is spawned with K8's
@@ -4424,6 +4638,7 @@ impl From<Image> for ImageRequest {
             timeout: image.timeout,
             resources,
             spawn_limit: image.spawn_limit,
+            priority: image.priority,
             volumes: image.volumes,
             env: image.env,
             args: image.args,
@@ -4432,12 +4647,16 @@ impl From<Image> for ImageRequest {
             security_context: Some(image.security_context),
             collect_logs: image.collect_logs,
             generator: image.generator,
+            capture_network: image.capture_network,
+            arch: image.arch,
+            emulation: image.emulation,
             dependencies: image.dependencies,
             display_type: image.display_type,
             output_collection: image.output_collection,
             child_filters: image.child_filters,
             clean_up: image.clean_up,
             kvm: image.kvm,
+            microvm: image.microvm,
             network_policies: image.network_policies,
         }
     }
@@ -4451,6 +4670,13 @@ pub struct KvmUpdate {
     pub xml: Option<String>,
     /// The path to the golden qcow2 image to use
     pub qcow2: Option<String>,
+    /// The path to a saved memory state of the golden vm to restore
+    pub memory_state: Option<String>,
+    /// Whether to clear the saved memory state or not
+    #[serde(default = "default_as_false")]
+    pub clear_memory_state: bool,
+    /// Whether to dump each vms memory once its job completes
+    pub dump_memory: Option<bool>,
 }
 
 /// An update to the image ban list containing bans to be added or removed
@@ -4608,6 +4834,8 @@ pub struct ImageUpdate {
     pub resources: Option<ResourcesUpdate>,
     /// The limit to use for how many workers of this image type can be spawned
     pub spawn_limit: Option<SpawnLimits>,
+    /// How urgently this images workers should be scheduled compared to other work
+    pub priority: Option<PriorityClass>,
     /// The volumes to add
     #[serde(default)]
     pub add_volumes: Vec<Volume>,
@@ -4644,6 +4872,12 @@ pub struct ImageUpdate {
     pub collect_logs: Option<bool>,
     /// Whether this is a generator or not
     pub generator: Option<bool>,
+    /// Whether to capture this images network traffic or not
+    pub capture_network: Option<bool>,
+    /// The CPU architecture this image is built for
+    pub arch: Option<CpuArch>,
+    /// Whether this image can run under qemu-user emulation or not
+    pub emulation: Option<bool>,
     /// Updates the dependency settings for this image
     #[serde(default)]
     pub dependencies: DependenciesUpdate,
@@ -4661,6 +4895,11 @@ pub struct ImageUpdate {
     /// The settings to use for Kvm jobs
     #[serde(default)]
     pub kvm: KvmUpdate,
+    /// The settings to use for microVM jobs
+    pub microvm: Option<MicroVm>,
+    /// Whether to clear the microVM settings or not
+    #[serde(default = "default_as_false")]
+    pub clear_microvm: bool,
     /// An update to the ban list containing a list of bans to add or remove
     #[serde(default)]
     pub bans: ImageBanUpdate,
@@ -4819,6 +5058,17 @@ impl ImageUpdate {
         self
     }
 
+    /// Sets the priority class for this images workers
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority class to set
+    #[must_use]
+    pub fn priority(mut self, priority: PriorityClass) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
     /// Adds a new [`Volume`] to add to the [`Image`] in this update
     ///
     /// # Arguments
@@ -4931,6 +5181,40 @@ impl ImageUpdate {
         self
     }
 
+    /// Sets the microVM settings to use
+    ///
+    /// # Arguments
+    ///
+    /// * `microvm` - The new microVM settings to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{ImageUpdate, MicroVm};
+    ///
+    /// ImageUpdate::default().microvm(MicroVm::new("/vms/vmlinux", "/vms/rootfs.ext4"));
+    /// ```
+    #[must_use]
+    pub fn microvm(mut self, microvm: MicroVm) -> Self {
+        self.microvm = Some(microvm);
+        self
+    }
+
+    /// Sets the clear microVM flag to true
+    ///
+    /// This will clear the images current microVM settings and set them to None.
+    ///
+    /// ```
+    /// use thorium::models::ImageUpdate;
+    ///
+    /// ImageUpdate::default().clear_microvm();
+    /// ```
+    #[must_use]
+    pub fn clear_microvm(mut self) -> Self {
+        self.clear_microvm = true;
+        self
+    }
+
     /// Sets the clear description flag to true
     ///
     /// This will clear the images current description and set it to None.
@@ -5000,6 +5284,53 @@ impl ImageUpdate {
         self
     }
 
+    /// Tells Thorium to capture this images network traffic during each job
+    #[must_use]
+    pub fn enable_capture_network(mut self) -> Self {
+        self.capture_network = Some(true);
+        self
+    }
+
+    /// Tells Thorium to stop capturing this images network traffic
+    #[must_use]
+    pub fn disable_capture_network(mut self) -> Self {
+        self.capture_network = Some(false);
+        self
+    }
+
+    /// Set the CPU architecture this image is built for
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - The new architecture to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{CpuArch, ImageUpdate};
+    ///
+    /// ImageUpdate::default().arch(CpuArch::Arm64);
+    /// ```
+    #[must_use]
+    pub fn arch(mut self, arch: CpuArch) -> Self {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// Allow this image to run under qemu-user emulation
+    #[must_use]
+    pub fn enable_emulation(mut self) -> Self {
+        self.emulation = Some(true);
+        self
+    }
+
+    /// Only run this image on nodes that natively match its architecture
+    #[must_use]
+    pub fn disable_emulation(mut self) -> Self {
+        self.emulation = Some(false);
+        self
+    }
+
     /// The updated dependency settings to use for this image
     ///
     /// # Arguments
@@ -5086,6 +5417,167 @@ pub struct Kvm {
     pub xml: String,
     /// The path to the golden qcow2 image to use
     pub qcow2: String,
+    /// The path to a saved memory state of the golden vm to restore instead of cold booting
+    #[serde(default)]
+    pub memory_state: Option<String>,
+    /// Whether to dump each vms memory once its job completes and submit it as a child
+    #[serde(default)]
+    pub dump_memory: bool,
+}
+
+impl Kvm {
+    /// Create new kvm settings
+    ///
+    /// # Arguments
+    ///
+    /// * `xml` - The path to the golden XML file to use
+    /// * `qcow2` - The path to the golden qcow2 image to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::Kvm;
+    ///
+    /// Kvm::new("/vms/win10.xml", "/vms/win10.qcow2");
+    /// ```
+    pub fn new<X: Into<String>, Q: Into<String>>(xml: X, qcow2: Q) -> Self {
+        Kvm {
+            xml: xml.into(),
+            qcow2: qcow2.into(),
+            memory_state: None,
+            dump_memory: false,
+        }
+    }
+
+    /// Restore each vm from a saved memory state of the golden vm
+    ///
+    /// The memory state must be saved with `virsh save` while the golden vm is
+    /// using the golden qcow2 image so that it matches each jobs overlay disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_state` - The path to the saved memory state to restore
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::Kvm;
+    ///
+    /// Kvm::new("/vms/win10.xml", "/vms/win10.qcow2").memory_state("/vms/win10.state");
+    /// ```
+    #[must_use]
+    pub fn memory_state<T: Into<String>>(mut self, memory_state: T) -> Self {
+        self.memory_state = Some(memory_state.into());
+        self
+    }
+
+    /// Dump each vms memory once its job completes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::Kvm;
+    ///
+    /// Kvm::new("/vms/win10.xml", "/vms/win10.qcow2").dump_memory();
+    /// ```
+    #[must_use]
+    pub fn dump_memory(mut self) -> Self {
+        self.dump_memory = true;
+        self
+    }
+}
+
+/// The hypervisors that can boot microVMs for jobs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum Hypervisor {
+    /// Boot microVMs with Firecracker
+    #[default]
+    Firecracker,
+    /// Boot microVMs with cloud-hypervisor
+    CloudHypervisor,
+}
+
+impl Hypervisor {
+    /// Get the name of the binary for this hypervisor
+    #[must_use]
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Hypervisor::Firecracker => "firecracker",
+            Hypervisor::CloudHypervisor => "cloud-hypervisor",
+        }
+    }
+}
+
+/// The settings for jobs executed in a per job microVM
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct MicroVm {
+    /// The hypervisor to boot this microVM with
+    #[serde(default)]
+    pub hypervisor: Hypervisor,
+    /// The path to the uncompressed kernel to boot on the reactor's node
+    pub kernel: String,
+    /// The path to the golden rootfs image to copy for each job on the reactor's node
+    pub rootfs: String,
+    /// Any extra kernel boot args to set
+    #[serde(default)]
+    pub boot_args: Option<String>,
+}
+
+impl MicroVm {
+    /// Create new microVM settings
+    ///
+    /// # Arguments
+    ///
+    /// * `kernel` - The path to the kernel to boot
+    /// * `rootfs` - The path to the golden rootfs image to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::MicroVm;
+    ///
+    /// MicroVm::new("/vms/vmlinux", "/vms/rootfs.ext4");
+    /// ```
+    pub fn new<K: Into<String>, R: Into<String>>(kernel: K, rootfs: R) -> Self {
+        MicroVm {
+            hypervisor: Hypervisor::default(),
+            kernel: kernel.into(),
+            rootfs: rootfs.into(),
+            boot_args: None,
+        }
+    }
+
+    /// Set the hypervisor to boot this microVM with
+    ///
+    /// # Arguments
+    ///
+    /// * `hypervisor` - The hypervisor to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{Hypervisor, MicroVm};
+    ///
+    /// MicroVm::new("/vms/vmlinux", "/vms/rootfs.ext4").hypervisor(Hypervisor::CloudHypervisor);
+    /// ```
+    #[must_use]
+    pub fn hypervisor(mut self, hypervisor: Hypervisor) -> Self {
+        self.hypervisor = hypervisor;
+        self
+    }
+
+    /// Set extra kernel boot args for this microVM
+    ///
+    /// # Arguments
+    ///
+    /// * `boot_args` - The boot args to add
+    #[must_use]
+    pub fn boot_args<T: Into<String>>(mut self, boot_args: T) -> Self {
+        self.boot_args = Some(boot_args.into());
+        self
+    }
 }
 
 /// The various kinds of bans an image can have
@@ -5245,6 +5737,9 @@ pub struct Image {
     pub resources: Resources,
     /// The limit to use for how many workers of this image type can be spawned
     pub spawn_limit: SpawnLimits,
+    /// How urgently this images workers should be scheduled compared to other work
+    #[serde(default)]
+    pub priority: PriorityClass,
     /// The environment variables to set
     #[serde(default)]
     pub env: HashMap<String, Option<String>>,
@@ -5268,6 +5763,15 @@ pub struct Image {
     pub collect_logs: bool,
     /// Whether this is a generator or not
     pub generator: bool,
+    /// Whether this images network traffic is captured for each job
+    #[serde(default)]
+    pub capture_network: bool,
+    /// The CPU architecture this image is built for
+    #[serde(default)]
+    pub arch: CpuArch,
+    /// Whether this image can run under qemu-user emulation when no native node is available
+    #[serde(default)]
+    pub emulation: bool,
     /// How to handle dependencies for this image
     #[serde(default)]
     pub dependencies: Dependencies,
@@ -5289,6 +5793,9 @@ pub struct Image {
     pub clean_up: Option<Cleanup>,
     /// The settings to use for Kvm jobs
     pub kvm: Option<Kvm>,
+    /// The settings to use for microVM jobs
+    #[serde(default)]
+    pub microvm: Option<MicroVm>,
     /// A list of reasons an image is banned mapped by ban UUID;
     /// if the list has any bans, the image cannot be spawned
     pub bans: HashMap<Uuid, ImageBan>,
@@ -5316,16 +5823,21 @@ impl PartialEq<ImageRequest> for Image {
         same!(self.timeout, request.timeout);
         same!(self.resources, request.resources);
         same!(self.spawn_limit, request.spawn_limit);
+        same!(self.priority, request.priority);
         same!(self.env, request.env);
         matches_vec!(&self.volumes, &request.volumes);
         same!(self.description, request.description);
         matches_update!(self.security_context, request.security_context);
         same!(self.collect_logs, request.collect_logs);
         same!(self.generator, request.generator);
+        same!(self.capture_network, request.capture_network);
+        same!(self.arch, request.arch);
+        same!(self.emulation, request.emulation);
         same!(self.dependencies, request.dependencies);
         same!(self.display_type, request.display_type);
         same!(self.output_collection, request.output_collection);
         same!(self.child_filters, request.child_filters);
+        same!(self.microvm, request.microvm);
         same!(self.network_policies, request.network_policies);
         true
     }
@@ -5346,10 +5858,12 @@ impl PartialEq<ImageUpdate> for Image {
         matches_update_opt!(self.timeout, update.timeout);
         matches_update!(self.resources, update.resources);
         matches_update!(self.spawn_limit, update.spawn_limit);
+        matches_update!(self.priority, update.priority);
         matches_clear_opt!(self.image, update.image, update.clear_image);
         matches_clear_opt!(self.version, update.version, update.clear_version);
         matches_adds!(self.volumes, update.add_volumes);
         matches_clear_opt!(self.description, update.description, update.clear_description);
+        matches_clear_opt!(self.microvm, update.microvm, update.clear_microvm);
         // build list of volume names
         let volume_names: Vec<String> = self.volumes.iter().map(|vol| vol.name.clone()).collect();
         // make sure we have removed any volumes requested for removal
@@ -5358,6 +5872,9 @@ impl PartialEq<ImageUpdate> for Image {
         matches_update!(self.security_context, update.security_context);
         matches_update!(self.collect_logs, update.collect_logs);
         matches_update!(self.generator, update.generator);
+        matches_update!(self.capture_network, update.capture_network);
+        matches_update!(self.arch, update.arch);
+        matches_update!(self.emulation, update.emulation);
         // make sure any dependency settings were updated
         same!(self.dependencies, update.dependencies);
         // make sure display type is updated
@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use std::fmt;
 use uuid::Uuid;
 
-use super::{ImageScaler, Reaction, RepoDependency, SystemComponents};
+use super::{ImageScaler, PriorityClass, Reaction, RepoDependency, SystemComponents};
 use crate::{matches_adds, matches_opt, matches_removes, matches_removes_map, same};
 
 /// A list of job ids with a cursor
@@ -185,6 +185,8 @@ pub enum JobHandleStatus {
     Sleeping,
     /// This job has been checkpointed
     Checkpointed,
+    /// This job was preempted and returned to the queue
+    Preempted,
 }
 
 /// response for handling Job command
@@ -202,6 +204,120 @@ pub struct Checkpoint {
     pub data: String,
 }
 
+/// A checkpoint blob a job saved so it can resume after losing its worker
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct JobCheckpointBlob {
+    /// The size of this checkpoint blob in bytes
+    pub size: u64,
+    /// When this checkpoint blob was uploaded
+    pub uploaded: DateTime<Utc>,
+}
+
+/// An update to the progress of a running job
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct JobProgressUpdate {
+    /// How far along this job is from 0 to 100
+    pub percent: Option<f32>,
+    /// The name of the phase this job is currently in
+    pub phase: Option<String>,
+    /// The number of seconds this job expects to still need
+    pub eta: Option<u64>,
+}
+
+impl JobProgressUpdate {
+    /// Set how far along this job is
+    ///
+    /// # Arguments
+    ///
+    /// * `percent` - How far along this job is from 0 to 100
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::JobProgressUpdate;
+    ///
+    /// JobProgressUpdate::default()
+    ///     .percent(42.5)
+    ///     .phase("fuzzing")
+    ///     .eta(3600);
+    /// ```
+    #[must_use]
+    pub fn percent(mut self, percent: f32) -> Self {
+        self.percent = Some(percent);
+        self
+    }
+
+    /// Set the name of the phase this job is currently in
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The name of the current phase
+    #[must_use]
+    pub fn phase<T: Into<String>>(mut self, phase: T) -> Self {
+        self.phase = Some(phase.into());
+        self
+    }
+
+    /// Set the number of seconds this job expects to still need
+    ///
+    /// # Arguments
+    ///
+    /// * `eta` - The number of seconds left
+    #[must_use]
+    pub fn eta(mut self, eta: u64) -> Self {
+        self.eta = Some(eta);
+        self
+    }
+}
+
+/// The last reported progress for a running job
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct JobProgress {
+    /// The job this progress is for
+    pub job: Uuid,
+    /// The stage of the pipeline this job is for
+    pub stage: String,
+    /// How far along this job is from 0 to 100
+    pub percent: Option<f32>,
+    /// The name of the phase this job is currently in
+    pub phase: Option<String>,
+    /// When this job expects to be complete
+    pub eta: Option<DateTime<Utc>>,
+    /// When this progress was last reported
+    pub updated: DateTime<Utc>,
+}
+
+impl JobProgress {
+    /// Build a progress report for a job from an update
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job this progress is for
+    /// * `stage` - The stage this job is for
+    /// * `update` - The progress update that was reported
+    #[must_use]
+    pub fn new(job: Uuid, stage: &str, update: JobProgressUpdate) -> Self {
+        let updated = Utc::now();
+        // convert our eta from seconds left to a timestamp
+        let eta = update
+            .eta
+            .and_then(|secs| i64::try_from(secs).ok())
+            .and_then(chrono::Duration::try_seconds)
+            .and_then(|left| updated.checked_add_signed(left));
+        JobProgress {
+            job,
+            stage: stage.to_owned(),
+            percent: update.percent,
+            phase: update.phase,
+            eta,
+            updated,
+        }
+    }
+}
+
 /// A raw job that Thorium will execute
 ///
 /// This should be cast to either a GenericJob or another known job
@@ -245,6 +361,11 @@ pub struct RawJob {
     pub repos: Vec<RepoDependency>,
     /// The trigger depth for this job if one was set
     pub trigger_depth: Option<u8>,
+    /// The checkpoint blob to restore before executing this job if one was saved
+    pub checkpoint_blob: Option<JobCheckpointBlob>,
+    /// The priority class of this jobs reaction
+    #[serde(default)]
+    pub priority: PriorityClass,
 }
 
 /// Keyword args for generic jobs
@@ -610,7 +731,7 @@ impl GenericJobArgsUpdate {
 /// Currently this is the only job we support but in the future I would like to
 /// have a way to allow users to tell Thorium how to bounds check jobs. That
 /// will likely be its own type.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
 pub struct GenericJob {
     /// The reaction this job is apart of
@@ -645,6 +766,9 @@ pub struct GenericJob {
     pub repos: Vec<RepoDependency>,
     /// The trigger depth for this job if one was set
     pub trigger_depth: Option<u8>,
+    /// The checkpoint blob to restore before executing this job if one was saved
+    #[serde(default)]
+    pub checkpoint_blob: Option<JobCheckpointBlob>,
 }
 
 /// checks that a vector of jobs matches a reaction request
@@ -23,9 +23,11 @@ pub mod results;
 mod scylla_utils;
 pub mod search;
 pub mod streams;
+pub mod subscriptions;
 pub mod system;
 pub mod tags;
 mod trees;
+pub mod uploads;
 pub mod users;
 mod version;
 mod volumes;
@@ -38,20 +40,21 @@ pub use events::{
     EventRequest, EventTrigger, EventType, TriggerPotential,
 };
 pub use files::{
-    Attachment, Buffer, CartedSample, CarvedOrigin, CarvedOriginTypes, Comment, CommentRequest,
-    CommentResponse, DeleteCommentParams, DeleteSampleParams, DownloadedSample, FileDeleteOpts,
-    FileDownloadOpts, FileListOpts, FileListParams, Origin, OriginRequest, OriginTypes,
-    PcapNetworkProtocol, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleRequest,
-    SampleSubmissionResponse, Submission, SubmissionChunk, SubmissionUpdate, Tag, TagMap,
-    ZipDownloadParams,
+    Attachment, Buffer, CartedSample, CarvedOrigin, CarvedOriginTypes, Comment, CommentReaction,
+    CommentRequest, CommentResponse, CommentRevision, CommentThread, CommentUpdate,
+    DeleteCommentParams, DeleteSampleParams, DownloadedSample, FileDeleteOpts, FileDownloadOpts,
+    FileListOpts, FileListParams, Origin, OriginRequest, OriginTypes, PcapNetworkProtocol, Sample,
+    SampleCheck, SampleCheckResponse, SampleListLine, SampleRequest, SampleSubmissionResponse,
+    Submission, SubmissionChunk, SubmissionUpdate, Tag, TagMap, ZipDownloadParams,
 };
 pub use git::{
     Branch, BranchDetails, BranchRequest, Commit, CommitDetails, CommitListOpts, CommitRequest,
     Commitish, CommitishDetails, CommitishKinds, CommitishListParams, CommitishMapRequest,
     CommitishRequest, GitTag, GitTagDetails, GitTagRequest, Repo, RepoCheckout, RepoCreateResponse,
     RepoDataUploadResponse, RepoDependency, RepoDependencyRequest, RepoDownloadOpts, RepoListLine,
-    RepoListOpts, RepoListParams, RepoRequest, RepoScheme, RepoSubmission, RepoSubmissionChunk,
-    RepoUrlComponents, TarredRepo,
+    RepoListOpts, RepoListParams, RepoMirror, RepoMirrorClaimOpts, RepoMirrorCredentials,
+    RepoMirrorFetch, RepoMirrorRequest, RepoRequest, RepoScheme, RepoSubmission,
+    RepoSubmissionChunk, RepoUrlComponents, TarredRepo,
 };
 pub use groups::{
     Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList, GroupList,
@@ -60,20 +63,20 @@ pub use groups::{
 };
 pub use images::{
     ArgStrategy, ChildFilters, ChildFiltersUpdate, ChildrenDependencySettings,
-    ChildrenDependencySettingsUpdate, Cleanup, CleanupUpdate, Dependencies, DependenciesUpdate,
-    DependencyPassStrategy, DependencySettingsUpdate, EphemeralDependencySettings,
-    EphemeralDependencySettingsUpdate, Image, ImageArgs, ImageArgsUpdate, ImageBan, ImageBanKind,
-    ImageBanUpdate, ImageDetailsList, ImageJobInfo, ImageLifetime, ImageList, ImageListParams,
-    ImageNetworkPolicyUpdate, ImageRequest, ImageScaler, ImageUpdate, ImageVersion, Kvm, KvmUpdate,
-    KwargDependency, RepoDependencySettings, Resources, ResourcesRequest, ResourcesUpdate,
-    ResultDependencySettings, ResultDependencySettingsUpdate, SampleDependencySettings,
-    SecurityContext, SecurityContextUpdate, SpawnLimits, TagDependencySettings,
-    TagDependencySettingsUpdate,
+    ChildrenDependencySettingsUpdate, Cleanup, CleanupUpdate, CpuArch, Dependencies,
+    DependenciesUpdate, DependencyPassStrategy, DependencySettingsUpdate,
+    EphemeralDependencySettings, EphemeralDependencySettingsUpdate, Hypervisor, Image, ImageArgs,
+    ImageArgsUpdate, ImageBan, ImageBanKind, ImageBanUpdate, ImageDetailsList, ImageJobInfo,
+    ImageLifetime, ImageList, ImageListParams, ImageNetworkPolicyUpdate, ImageRequest, ImageScaler,
+    ImageUpdate, ImageVersion, Kvm, KvmUpdate, KwargDependency, MicroVm, PriorityClass,
+    RepoDependencySettings, Resources, ResourcesRequest, ResourcesUpdate, ResultDependencySettings,
+    ResultDependencySettingsUpdate, SampleDependencySettings, SecurityContext,
+    SecurityContextUpdate, SpawnLimits, TagDependencySettings, TagDependencySettingsUpdate,
 };
 pub use jobs::{
     Checkpoint, GenericJob, GenericJobArgs, GenericJobArgsUpdate, GenericJobKwargs, GenericJobOpts,
-    HandleJobResponse, JobDetailsList, JobHandleStatus, JobList, JobListOpts, JobResetRequestor,
-    JobResets, JobStatus, RawJob, RunningJob,
+    HandleJobResponse, JobCheckpointBlob, JobDetailsList, JobHandleStatus, JobList, JobListOpts,
+    JobProgress, JobProgressUpdate, JobResetRequestor, JobResets, JobStatus, RawJob, RunningJob,
 };
 pub use logs::{Actions, JobActions, ReactionActions, StatusRequest, StatusUpdate};
 pub use network_policies::{
@@ -95,25 +98,30 @@ pub use requisitions::{Requisition, ScopedRequisition, SpawnedUpdate};
 pub use results::{
     AutoTag, AutoTagLogic, AutoTagUpdate, FilesHandler, FilesHandlerUpdate, OnDiskFile, Output,
     OutputChunk, OutputCollection, OutputCollectionUpdate, OutputDisplayType, OutputHandler,
-    OutputResponse, ResultGetParams,
+    OutputResponse, ResultGetParams, StreamHandler, StreamHandlerUpdate,
 };
 pub use search::events::{
     ResultSearchEvent, SearchEvent, SearchEventPopOpts, SearchEventStatus, SearchEventType,
     TagSearchEvent,
 };
+pub use search::{SavedSearch, SavedSearchRequest};
 pub use streams::{Stream, StreamDepth, StreamObj};
+pub use subscriptions::{
+    Subscription, SubscriptionEvent, SubscriptionRequest, SubscriptionResponse, SubscriptionTarget,
+};
 pub use system::{
     ActiveJob, Backup, HostPathWhitelistUpdate, Node, NodeGetParams, NodeHealth, NodeListLine,
-    NodeListParams, NodeRegistration, NodeUpdate, Pools, ScalerStats, SpawnMap, StreamerInfoUpdate,
-    SystemComponents, SystemInfo, SystemInfoParams, SystemSettings, SystemSettingsResetParams,
-    SystemSettingsUpdate, SystemSettingsUpdateParams, SystemStats, Worker, WorkerDelete,
-    WorkerDeleteMap, WorkerList, WorkerRegistration, WorkerRegistrationList, WorkerStatus,
-    WorkerUpdate,
+    NodeListParams, NodeRegistration, NodeUpdate, Pools, ResponseCounts, ScalerStats, SpawnMap,
+    StreamerInfoUpdate, SystemComponents, SystemInfo, SystemInfoParams, SystemSettings,
+    SystemSettingsResetParams, SystemSettingsUpdate, SystemSettingsUpdateParams, SystemStats,
+    Worker, WorkerDelete, WorkerDeleteMap, WorkerList, WorkerRegistration, WorkerRegistrationList,
+    WorkerStatus, WorkerUpdate,
 };
 pub use trees::{
-    Tree, TreeGrowQuery, TreeNode, TreeNodeData, TreeParams, TreeQuery, TreeRelationships,
-    TreeSupport,
+    Tree, TreeCommitish, TreeGrowQuery, TreeNode, TreeNodeData, TreeParams, TreeQuery,
+    TreeRelationships, TreeSupport,
 };
+pub use uploads::{UploadSession, UploadSessionRequest};
 pub use users::{
     AuthResponse, Key, ScrubbedUser, Theme, UnixInfo, User, UserCreate, UserRole, UserSettings,
     UserSettingsUpdate, UserUpdate,
@@ -141,6 +149,7 @@ cfg_if::cfg_if! {
         pub use files::{SampleForm, OriginForm, CommentForm};
         pub use git::RepoDataForm;
         pub use jobs::JobReactionIds;
+        pub use uploads::UploadedChunk;
         pub use backends::results::ResultFileDownloadParams;
         pub(crate) use backends::search::events::SearchEventBackend;
     }
@@ -149,7 +158,11 @@ cfg_if::cfg_if! {
 // api/client reexports
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "api", feature = "client"))] {
-        pub use tags::{TagDeleteRequest, TagRequest, TagType};
+        pub use tags::{
+            BulkTagJob, BulkTagOperation, BulkTagRequest, BulkTagStatus, TagDeleteRequest, TagKeyRules,
+            TagProvenance, TagProvenanceParams, TagProvenanceRequest, TagRequest, TagSource, TagType,
+            TagVocabulary,
+        };
         pub use notifications::{
             Notification, NotificationLevel, NotificationParams, NotificationRequest, NotificationType,
         };
@@ -169,10 +182,12 @@ cfg_if::cfg_if! {
         pub use scylla_utils::files::{SubmissionListRow, SubmissionRow, CommentRow};
         pub use scylla_utils::results::{OutputId, OutputIdRow, OutputRow, OutputFormBuilder, OutputForm};
         pub use scylla_utils::system::{WorkerRow, NodeRow, WorkerName};
-        pub use scylla_utils::tags::{TagRow, FullTagRow, TagListRow};
+        pub use scylla_utils::tags::{TagRow, FullTagRow, TagListRow, TagProvenanceRow};
         pub use scylla_utils::events::EventRow;
         pub use scylla_utils::s3::S3Objects;
         pub use scylla_utils::network_policies::{NetworkPolicyRow, NetworkPolicyListRow};
+        pub use scylla_utils::migrations;
+        pub use scylla_utils::errors::MigrationError;
         pub use census::{CensusSupport, CensusKeys};
         pub use tags::TagCensusCaseInsensitive;
 
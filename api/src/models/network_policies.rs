@@ -236,6 +236,12 @@ pub struct NetworkPolicyRule {
     /// Overrides all other settings except [`NetworkPolicySettings::ports`]
     #[serde(default)]
     pub allowed_all: bool,
+    /// Redirect egress traffic to the simulated internet instead of the real one,
+    /// sinkholing DNS and logging the requests tools make
+    ///
+    /// Only valid for egress rules
+    #[serde(default)]
+    pub simulated_internet: bool,
     /// A list of specific ports + protocols to allow
     ///
     /// If empty, traffic will be allowed on all ports + protocols
@@ -403,6 +409,12 @@ pub struct NetworkPolicyRuleRaw {
     /// Overrides all other settings except [`NetworkPolicySettings::ports`]
     #[serde(default)]
     pub allowed_all: bool,
+    /// Redirect egress traffic to the simulated internet instead of the real one,
+    /// sinkholing DNS and logging the requests tools make
+    ///
+    /// Only valid for egress rules
+    #[serde(default)]
+    pub simulated_internet: bool,
     /// A list of specific ports + protocols to allow
     ///
     /// If empty, traffic will be allowed on all ports + protocols
@@ -525,6 +537,15 @@ impl NetworkPolicyRuleRaw {
         self
     }
 
+    /// Redirect egress for this rule to the simulated internet
+    ///
+    /// This is only valid for egress rules
+    #[must_use]
+    pub fn simulated_internet(mut self) -> Self {
+        self.simulated_internet = true;
+        self
+    }
+
     /// Add a port or range of ports that this rule applies to
     ///
     /// # Arguments
@@ -603,6 +624,7 @@ impl TryFrom<NetworkPolicyRuleRaw> for NetworkPolicyRule {
             allowed_local: raw_rule.allowed_local,
             allowed_internet: raw_rule.allowed_internet,
             allowed_all: raw_rule.allowed_all,
+            simulated_internet: raw_rule.simulated_internet,
             ports: raw_rule.ports,
             allowed_custom: raw_rule.allowed_custom,
         })
@@ -1217,6 +1239,17 @@ pub struct NetworkPolicy {
     pub used_by: HashMap<String, Vec<String>>,
 }
 
+impl NetworkPolicy {
+    /// Returns true if this policy redirects egress to the simulated internet
+    #[must_use]
+    pub fn simulated_internet(&self) -> bool {
+        self.egress
+            .iter()
+            .flatten()
+            .any(|rule| rule.simulated_internet)
+    }
+}
+
 // add K8's api support for the scaler
 cfg_if::cfg_if! {
     if #[cfg(feature = "k8s")] {
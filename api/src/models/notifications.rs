@@ -29,6 +29,9 @@ pub enum NotificationType {
     /// This operation is working on pipeline notifications
     #[strum(serialize = "Pipelines")]
     Pipelines,
+    /// This operation is working on notifications for a specific user
+    #[strum(serialize = "Users")]
+    Users,
 }
 
 impl NotificationType {
@@ -37,6 +40,7 @@ impl NotificationType {
         match self {
             Self::Images => "Images",
             Self::Pipelines => "Pipelines",
+            Self::Users => "Users",
         }
     }
 }
@@ -11,7 +11,8 @@ use uuid::Uuid;
 use tokio::{fs::File, io::AsyncReadExt};
 
 use super::{
-    GenericJobArgs, GenericJobArgsUpdate, JobHandleStatus, RepoDependency, RepoDependencyRequest,
+    Comment, GenericJobArgs, GenericJobArgsUpdate, JobHandleStatus, JobProgress, PriorityClass,
+    RepoDependency, RepoDependencyRequest,
 };
 use crate::{matches_adds, matches_removes, matches_vec, same};
 
@@ -107,6 +108,9 @@ cfg_if::cfg_if! {
             pub repos: Vec<RepoDependencyRequest>,
             /// This reactions depth in triggers if this reaction was caused by a trigger
             pub trigger_depth: Option<u8>,
+            /// How urgently this reactions jobs should be scheduled compared to other work
+            #[serde(default)]
+            pub priority: PriorityClass,
         }
 
         impl TryFrom<RawReactionRequest> for ReactionRequest {
@@ -135,6 +139,7 @@ cfg_if::cfg_if! {
                     buffers: raw.buffers,
                     repos: raw.repos,
                     trigger_depth: raw.trigger_depth,
+                    priority: raw.priority,
                 };
                 Ok(converted)
             }
@@ -214,6 +219,11 @@ pub struct ReactionRequest {
     pub repos: Vec<RepoDependencyRequest>,
     /// This reactions depth in triggers if this reaction was caused by a trigger
     pub trigger_depth: Option<u8>,
+    /// How urgently this reactions jobs should be scheduled compared to other work
+    ///
+    /// Standard reactions use the priority class of their images.
+    #[serde(default)]
+    pub priority: PriorityClass,
 }
 
 impl ReactionRequest {
@@ -256,6 +266,7 @@ impl ReactionRequest {
             buffers: HashMap::default(),
             repos: Vec::default(),
             trigger_depth: None,
+            priority: PriorityClass::default(),
         }
     }
 
@@ -529,6 +540,26 @@ impl ReactionRequest {
         self.trigger_depth = Some(trigger_depth);
         self
     }
+
+    /// Set the priority class for this reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority class to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{PriorityClass, ReactionRequest};
+    ///
+    /// // create a reaction that can preempt the workers of preemptible images
+    /// let request = ReactionRequest::new("Corn", "harvest").priority(PriorityClass::Urgent);
+    /// ```
+    #[must_use]
+    pub fn priority(mut self, priority: PriorityClass) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 /// Helps serde default the reaction list limit to 50
@@ -808,7 +839,8 @@ impl From<JobHandleStatus> for ReactionStatus {
             JobHandleStatus::Waiting
             | JobHandleStatus::Proceeding
             | JobHandleStatus::Sleeping
-            | JobHandleStatus::Checkpointed => ReactionStatus::Started,
+            | JobHandleStatus::Checkpointed
+            | JobHandleStatus::Preempted => ReactionStatus::Started,
             JobHandleStatus::Completed => ReactionStatus::Completed,
             JobHandleStatus::Errored => ReactionStatus::Failed,
         }
@@ -863,6 +895,15 @@ pub struct Reaction {
     pub repos: Vec<RepoDependency>,
     /// This reactions depth in triggers if this reaction was caused by a trigger
     pub trigger_depth: Option<u8>,
+    /// How urgently this reactions jobs should be scheduled compared to other work
+    #[serde(default)]
+    pub priority: PriorityClass,
+    /// The last reported progress for any of this reactions running jobs
+    #[serde(default)]
+    pub job_progress: HashMap<Uuid, JobProgress>,
+    /// Any comments for this reaction
+    #[serde(default)]
+    pub comments: Vec<Comment>,
 }
 
 impl PartialEq<ReactionRequest> for Reaction {
@@ -886,6 +927,7 @@ impl PartialEq<ReactionRequest> for Reaction {
         same!(self.ephemeral.len(), request.buffers.len());
         // make sure our reaction depth is the same
         same!(self.trigger_depth, request.trigger_depth);
+        same!(self.priority, request.priority);
         true
     }
 }
@@ -11,7 +11,7 @@ use std::str::FromStr;
 use uuid::Uuid;
 
 use super::backends::OutputSupport;
-use super::{Buffer, ImageVersion, InvalidEnum};
+use super::{Buffer, Comment, ImageVersion, InvalidEnum};
 use crate::{
     matches_adds, matches_clear, matches_removes, matches_update, matches_update_opt, same,
 };
@@ -547,6 +547,9 @@ pub struct Output {
     pub display_type: OutputDisplayType,
     /// The children that were found when generating this result
     pub children: HashMap<String, Uuid>,
+    /// Any comments for this result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
 }
 
 #[cfg(any(feature = "api", feature = "client"))]
@@ -751,6 +754,8 @@ impl FromStr for OutputDisplayType {
 pub enum OutputHandler {
     /// Collect output from a location on disk
     Files,
+    /// Collect output streamed to the agent while the tool is running and from disk once it exits
+    Stream,
 }
 
 impl Default for OutputHandler {
@@ -773,6 +778,14 @@ fn default_result_files_path() -> String {
 fn default_tags_path() -> String {
     "/tmp/thorium/tags".into()
 }
+/// helps serde default the progress path
+fn default_progress_path() -> String {
+    "/tmp/thorium/progress".into()
+}
+/// helps serde default the checkpoint path
+fn default_checkpoint_path() -> String {
+    "/tmp/thorium/checkpoint".into()
+}
 
 /// The settings for collecting results from a specific location on disk
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -787,6 +800,12 @@ pub struct FilesHandler {
     /// The location to load tags to set from
     #[serde(default = "default_tags_path")]
     pub tags: String,
+    /// The location to read progress updates from
+    #[serde(default = "default_progress_path")]
+    pub progress: String,
+    /// The location to save and restore checkpoint blobs at
+    #[serde(default = "default_checkpoint_path")]
+    pub checkpoint: String,
     /// Any file names to restrict our handler to
     #[serde(default)]
     pub names: Vec<String>,
@@ -799,6 +818,8 @@ impl Default for FilesHandler {
             results: "/tmp/thorium/results".into(),
             result_files: "/tmp/thorium/result-files".into(),
             tags: "/tmp/thorium/tags".into(),
+            progress: "/tmp/thorium/progress".into(),
+            checkpoint: "/tmp/thorium/checkpoint".into(),
             names: Vec::default(),
         }
     }
@@ -843,6 +864,44 @@ impl FilesHandler {
         self
     }
 
+    /// Set the progress path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::FilesHandler;
+    ///
+    /// FilesHandler::default().progress("/data/progress");
+    /// ```
+    #[must_use]
+    pub fn progress<T: Into<String>>(mut self, path: T) -> Self {
+        self.progress = path.into();
+        self
+    }
+
+    /// Set the checkpoint path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::FilesHandler;
+    ///
+    /// FilesHandler::default().checkpoint("/data/checkpoint");
+    /// ```
+    #[must_use]
+    pub fn checkpoint<T: Into<String>>(mut self, path: T) -> Self {
+        self.checkpoint = path.into();
+        self
+    }
+
     /// Add a name to the list of results or result files to restrict to
     ///
     /// # Arguments
@@ -892,6 +951,8 @@ impl PartialEq<FilesHandlerUpdate> for FilesHandler {
         // make sure any updates were applied
         matches_update!(self.results, update.results);
         matches_update!(self.result_files, update.result_files);
+        matches_update!(self.progress, update.progress);
+        matches_update!(self.checkpoint, update.checkpoint);
         matches_adds!(self.names, update.add_names);
         // make sure we removed any requested names
         matches_removes!(self.names, update.remove_names);
@@ -899,6 +960,77 @@ impl PartialEq<FilesHandlerUpdate> for FilesHandler {
     }
 }
 
+/// The settings for collecting output streamed to the agent while a tool is running
+///
+/// The agent listens for output on localhost or a Unix socket and tells the tool where
+/// to send it with the `THORIUM_OUTPUT_URL` or `THORIUM_OUTPUT_SOCKET` env vars.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct StreamHandler {
+    /// The port on localhost to listen on (a random open port is used if not set)
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// The path to a Unix socket to listen on instead of a port
+    #[serde(default)]
+    pub socket: Option<String>,
+}
+
+impl StreamHandler {
+    /// Set the port on localhost to listen on
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - The port to listen on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::StreamHandler;
+    ///
+    /// StreamHandler::default().port(8473);
+    /// ```
+    #[must_use]
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set the path to a Unix socket to listen on instead of a port
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The path to the socket to listen on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::StreamHandler;
+    ///
+    /// StreamHandler::default().socket("/tmp/thorium/output.sock");
+    /// ```
+    #[must_use]
+    pub fn socket<T: Into<String>>(mut self, socket: T) -> Self {
+        self.socket = Some(socket.into());
+        self
+    }
+}
+
+impl PartialEq<StreamHandlerUpdate> for StreamHandler {
+    /// Check if a [`StreamHandler`] contains all the updates from a [`StreamHandlerUpdate`]
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - The updates to compare against
+    fn eq(&self, update: &StreamHandlerUpdate) -> bool {
+        // make sure any updates were applied
+        matches_update_opt!(self.port, update.port);
+        matches_update_opt!(self.socket, update.socket);
+        matches_clear!(self.port, update.clear_port);
+        matches_clear!(self.socket, update.clear_socket);
+        true
+    }
+}
+
 /// The logic to use to determine whether to create this tag or not
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -985,6 +1117,9 @@ pub struct OutputCollection {
     /// The file Handler settings
     #[serde(default)]
     pub files: FilesHandler,
+    /// The stream handler settings
+    #[serde(default)]
+    pub stream: StreamHandler,
     /// Where to look for child files to ingest,
     #[serde(default = "default_children")]
     pub children: String,
@@ -1002,6 +1137,7 @@ impl Default for OutputCollection {
         OutputCollection {
             handler: OutputHandler::default(),
             files: FilesHandler::default(),
+            stream: StreamHandler::default(),
             children: "/tmp/thorium/children".to_owned(),
             auto_tag: HashMap::default(),
             groups: Vec::default(),
@@ -1010,6 +1146,25 @@ impl Default for OutputCollection {
 }
 
 impl OutputCollection {
+    /// Set the handler used to collect output
+    ///
+    /// # Arguments
+    ///
+    /// * `handler` - The handler to use
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{OutputCollection, OutputHandler};
+    ///
+    /// OutputCollection::default().handler(OutputHandler::Stream);
+    /// ```
+    #[must_use]
+    pub fn handler(mut self, handler: OutputHandler) -> Self {
+        self.handler = handler;
+        self
+    }
+
     /// Set the settings for the Files Handler
     ///
     /// # Arguments
@@ -1032,6 +1187,27 @@ impl OutputCollection {
         self.files = files;
         self
     }
+
+    /// Set the settings for the Stream Handler
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The stream handler settings to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{OutputCollection, OutputHandler, StreamHandler};
+    ///
+    /// OutputCollection::default()
+    ///     .handler(OutputHandler::Stream)
+    ///     .stream(StreamHandler::default().socket("/tmp/thorium/output.sock"));
+    /// ```
+    #[must_use]
+    pub fn stream(mut self, stream: StreamHandler) -> Self {
+        self.stream = stream;
+        self
+    }
 }
 
 impl PartialEq<OutputCollectionUpdate> for OutputCollection {
@@ -1044,6 +1220,7 @@ impl PartialEq<OutputCollectionUpdate> for OutputCollection {
         // make sure any updates were applied
         matches_update!(self.handler, update.handler);
         same!(self.files, update.files);
+        same!(self.stream, update.stream);
         true
     }
 }
@@ -1058,6 +1235,10 @@ pub struct FilesHandlerUpdate {
     pub result_files: Option<String>,
     /// The location to load tags to set from
     pub tags: Option<String>,
+    /// The location to read progress updates from
+    pub progress: Option<String>,
+    /// The location to save and restore checkpoint blobs at
+    pub checkpoint: Option<String>,
     /// Any new file names to restrict our handler to
     #[serde(default)]
     pub add_names: Vec<String>,
@@ -1127,6 +1308,44 @@ impl FilesHandlerUpdate {
         self
     }
 
+    /// Set the progress path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::FilesHandlerUpdate;
+    ///
+    /// FilesHandlerUpdate::default().progress("/data/progress");
+    /// ```
+    #[must_use]
+    pub fn progress<T: Into<String>>(mut self, path: T) -> Self {
+        self.progress = Some(path.into());
+        self
+    }
+
+    /// Set the checkpoint path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::FilesHandlerUpdate;
+    ///
+    /// FilesHandlerUpdate::default().checkpoint("/data/checkpoint");
+    /// ```
+    #[must_use]
+    pub fn checkpoint<T: Into<String>>(mut self, path: T) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
     /// Add a name to the list of results or result files to restrict to
     ///
     /// # Arguments
@@ -1221,6 +1440,103 @@ impl PartialEq<FilesHandler> for FilesHandlerUpdate {
     }
 }
 
+/// The settings to update for collecting output streamed to the agent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct StreamHandlerUpdate {
+    /// The port on localhost to listen on
+    pub port: Option<u16>,
+    /// The path to a Unix socket to listen on instead of a port
+    pub socket: Option<String>,
+    /// Whether to clear the port and listen on a random open port
+    #[serde(default)]
+    pub clear_port: bool,
+    /// Whether to clear the Unix socket and listen on a port
+    #[serde(default)]
+    pub clear_socket: bool,
+}
+
+impl StreamHandlerUpdate {
+    /// Set the port on localhost to listen on
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - The port to listen on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::StreamHandlerUpdate;
+    ///
+    /// StreamHandlerUpdate::default().port(8473);
+    /// ```
+    #[must_use]
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set the path to a Unix socket to listen on instead of a port
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The path to the socket to listen on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::StreamHandlerUpdate;
+    ///
+    /// StreamHandlerUpdate::default().socket("/tmp/thorium/output.sock");
+    /// ```
+    #[must_use]
+    pub fn socket<T: Into<String>>(mut self, socket: T) -> Self {
+        self.socket = Some(socket.into());
+        self
+    }
+
+    /// Clear the port so a random open port is used
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::StreamHandlerUpdate;
+    ///
+    /// StreamHandlerUpdate::default().clear_port();
+    /// ```
+    #[must_use]
+    pub fn clear_port(mut self) -> Self {
+        self.clear_port = true;
+        self
+    }
+
+    /// Clear the Unix socket so a port is listened on instead
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::StreamHandlerUpdate;
+    ///
+    /// StreamHandlerUpdate::default().clear_socket();
+    /// ```
+    #[must_use]
+    pub fn clear_socket(mut self) -> Self {
+        self.clear_socket = true;
+        self
+    }
+}
+
+impl PartialEq<StreamHandler> for StreamHandlerUpdate {
+    /// Check if a [`StreamHandler`] contains all the updates from a [`StreamHandlerUpdate`]
+    ///
+    /// # Arguments
+    ///
+    /// * `handler` - The updated stream handler to compare against
+    fn eq(&self, handler: &StreamHandler) -> bool {
+        handler == self
+    }
+}
+
 /// The settings to update for extracting a single tag from a result
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -1300,6 +1616,9 @@ pub struct OutputCollectionUpdate {
     /// The file Handler settings
     #[serde(default)]
     pub files: FilesHandlerUpdate,
+    /// The stream handler settings
+    #[serde(default)]
+    pub stream: StreamHandlerUpdate,
     /// Update settings for automatically extracting a tag from results
     #[serde(default)]
     pub auto_tag: HashMap<String, AutoTagUpdate>,
@@ -1357,6 +1676,26 @@ impl OutputCollectionUpdate {
         self
     }
 
+    /// Set the settings for the stream handler
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The stream handler settings to set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{OutputCollectionUpdate, StreamHandlerUpdate};
+    ///
+    /// OutputCollectionUpdate::default()
+    ///     .stream(StreamHandlerUpdate::default().port(8473).clear_socket());
+    /// ```
+    #[must_use]
+    pub fn stream(mut self, stream: StreamHandlerUpdate) -> Self {
+        self.stream = stream;
+        self
+    }
+
     /// Adds an auto tag update
     ///
     /// # Arguments
@@ -1419,6 +1758,7 @@ impl PartialEq<OutputCollection> for OutputCollectionUpdate {
         // make sure any updates were applied
         matches_update!(collection.handler, self.handler);
         same!(collection.files, self.files);
+        same!(collection.stream, self.stream);
         // make sure that all auto tag updates are applied
         for (key, update) in &self.auto_tag {
             // determine if this update was properly applied
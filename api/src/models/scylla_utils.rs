@@ -8,6 +8,7 @@ mod scylla_utils_reexport {
     pub mod errors;
     pub mod events;
     pub mod files;
+    pub mod migrations;
     pub mod network_policies;
     pub mod repos;
     pub mod results;
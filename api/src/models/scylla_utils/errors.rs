@@ -19,3 +19,78 @@ impl std::fmt::Display for DeserializationError {
 
 // impl the std error trait for our error
 impl std::error::Error for DeserializationError {}
+
+/// The errors that can occur while migrating Thorium's Scylla schema
+#[derive(Debug)]
+pub enum MigrationError {
+    /// A query to Scylla failed
+    Query(scylla::errors::ExecutionError),
+    /// A query did not return rows
+    IntoRows(scylla::response::query_result::IntoRowsResultError),
+    /// The rows returned by a query could not be read
+    Rows(scylla::response::query_result::RowsError),
+    /// The first row returned by a query could not be read
+    FirstRow(scylla::response::query_result::MaybeFirstRowError),
+    /// A row could not be deserialized
+    Deserialize(scylla::deserialize::DeserializationError),
+    /// A statement in a migration failed
+    Failed {
+        /// The version of the migration that failed
+        version: i32,
+        /// The statement that failed
+        statement: String,
+        /// The error from Scylla
+        error: scylla::errors::ExecutionError,
+    },
+}
+
+impl std::fmt::Display for MigrationError {
+    /// Allow our migration errors to be displayed
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::Query(err) => write!(f, "Query Error: {err}"),
+            MigrationError::IntoRows(err) => write!(f, "IntoRows Error: {err}"),
+            MigrationError::Rows(err) => write!(f, "Rows Error: {err}"),
+            MigrationError::FirstRow(err) => write!(f, "FirstRow Error: {err}"),
+            MigrationError::Deserialize(err) => write!(f, "Deserialize Error: {err}"),
+            MigrationError::Failed {
+                version,
+                statement,
+                error,
+            } => write!(f, "Migration {version} failed on \"{statement}\": {error}"),
+        }
+    }
+}
+
+// impl the std error trait for our error
+impl std::error::Error for MigrationError {}
+
+impl From<scylla::errors::ExecutionError> for MigrationError {
+    fn from(error: scylla::errors::ExecutionError) -> Self {
+        MigrationError::Query(error)
+    }
+}
+
+impl From<scylla::response::query_result::IntoRowsResultError> for MigrationError {
+    fn from(error: scylla::response::query_result::IntoRowsResultError) -> Self {
+        MigrationError::IntoRows(error)
+    }
+}
+
+impl From<scylla::response::query_result::RowsError> for MigrationError {
+    fn from(error: scylla::response::query_result::RowsError) -> Self {
+        MigrationError::Rows(error)
+    }
+}
+
+impl From<scylla::response::query_result::MaybeFirstRowError> for MigrationError {
+    fn from(error: scylla::response::query_result::MaybeFirstRowError) -> Self {
+        MigrationError::FirstRow(error)
+    }
+}
+
+impl From<scylla::deserialize::DeserializationError> for MigrationError {
+    fn from(error: scylla::deserialize::DeserializationError) -> Self {
+        MigrationError::Deserialize(error)
+    }
+}
@@ -95,4 +95,12 @@ pub struct CommentRow {
     pub comment: String,
     /// Any paths in s3 to files/attachements for this comment in serialized form
     pub files: String,
+    /// The comment this comment is a reply to if it is one
+    pub parent: Option<Uuid>,
+    /// When this comment was last edited if it has been
+    pub edited: Option<DateTime<Utc>>,
+    /// The previous revisions of this comment in serialized form
+    pub revisions: Option<String>,
+    /// The reaction and username for each reaction left on this comment
+    pub reactions: Option<Vec<(String, String)>>,
 }
@@ -0,0 +1,305 @@
+//! Versioned migrations for Thorium's Scylla schema
+//!
+//! Tables are created by the API when it starts but creating a table that already exists will not
+//! change its columns or options. Any change to an existing table between releases must instead be
+//! added as a new migration at the end of [`MIGRATIONS`]. Migrations are applied in order by
+//! Thoradm (which the operator runs before upgrading the API) and each applied migration is
+//! recorded in the `schema_version` table.
+
+use chrono::prelude::*;
+use scylla::client::session::Session;
+use scylla::DeserializeRow;
+
+use super::errors::MigrationError;
+
+/// The component the Scylla schema versions are stored under in the `schema_version` table
+const SCHEMA_COMPONENT: &str = "scylla";
+
+/// A single versioned change to Thorium's Scylla schema
+pub struct Migration {
+    /// The version the schema will be at after this migration is applied
+    pub version: i32,
+    /// A short description of this migration
+    pub name: &'static str,
+    /// Build the CQL statements for this migration for a specific keyspace
+    pub statements: fn(&str) -> Vec<String>,
+}
+
+impl Migration {
+    /// Build the CQL statements for this migration
+    ///
+    /// # Arguments
+    ///
+    /// * `ns` - The keyspace to build statements for
+    #[must_use]
+    pub fn statements(&self, ns: &str) -> Vec<String> {
+        (self.statements)(ns)
+    }
+}
+
+/// The schema as of the first release to track schema versions
+///
+/// This makes no changes but gives clusters deployed before schema versions were tracked a
+/// version to start from.
+///
+/// # Arguments
+///
+/// * `_ns` - The keyspace to build statements for
+fn baseline(_ns: &str) -> Vec<String> {
+    Vec::new()
+}
+
+/// Track the CPU architectures of nodes
+///
+/// Nodes without an architecture are treated as `x86_64` nodes that cannot emulate anything.
+///
+/// # Arguments
+///
+/// * `ns` - The keyspace to build statements for
+fn node_architectures(ns: &str) -> Vec<String> {
+    vec![
+        format!("ALTER TABLE {ns}.nodes ADD arch TEXT"),
+        format!("ALTER TABLE {ns}.nodes ADD emulated TEXT"),
+    ]
+}
+
+/// Track replies, edits, and reactions on comments
+///
+/// Comments without a parent are top level comments and comments without reactions or revisions
+/// have never been reacted to or edited.
+///
+/// # Arguments
+///
+/// * `ns` - The keyspace to build statements for
+fn threaded_comments(ns: &str) -> Vec<String> {
+    vec![
+        format!("ALTER TABLE {ns}.comments ADD parent UUID"),
+        format!("ALTER TABLE {ns}.comments ADD edited TIMESTAMP"),
+        format!("ALTER TABLE {ns}.comments ADD revisions TEXT"),
+        format!("ALTER TABLE {ns}.comments ADD reactions SET<FROZEN<TUPLE<TEXT, TEXT>>>"),
+    ]
+}
+
+/// Every migration for Thorium's Scylla schema in the order they must be applied
+///
+/// New migrations must be appended with the next version and must never be reordered or edited
+/// once released. The tables created by the API at startup should always match the schema after
+/// the last migration is applied.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        statements: baseline,
+    },
+    Migration {
+        version: 2,
+        name: "node_architectures",
+        statements: node_architectures,
+    },
+    Migration {
+        version: 3,
+        name: "threaded_comments",
+        statements: threaded_comments,
+    },
+];
+
+/// Get the schema version that this version of Thorium expects
+#[must_use]
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Get the migrations that still need to be applied to a schema
+///
+/// # Arguments
+///
+/// * `current` - The version the schema is currently at
+#[must_use]
+pub fn pending(current: i32) -> &'static [Migration] {
+    // find the first migration that has not been applied yet
+    let start = MIGRATIONS
+        .iter()
+        .position(|migration| migration.version > current)
+        .unwrap_or(MIGRATIONS.len());
+    &MIGRATIONS[start..]
+}
+
+/// A single applied migration in the `schema_version` table
+#[derive(Debug, Serialize, Deserialize, DeserializeRow)]
+#[scylla(flavor = "enforce_order", skip_name_checks)]
+pub struct SchemaVersionRow {
+    /// The version the schema was at after this migration was applied
+    pub version: i32,
+    /// The name of the migration that was applied
+    pub name: String,
+    /// When this migration was applied
+    pub applied: DateTime<Utc>,
+}
+
+/// Check if a table exists in a keyspace
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace to check
+/// * `table` - The table to look for or None to check for any table
+async fn table_exists(
+    session: &Session,
+    ns: &str,
+    table: Option<&str>,
+) -> Result<bool, MigrationError> {
+    // get the tables in this keyspace
+    let query = session
+        .query_unpaged(
+            "SELECT table_name FROM system_schema.tables WHERE keyspace_name = ?",
+            (ns,),
+        )
+        .await?;
+    let query_rows = query.into_rows_result()?;
+    // check if any of our tables match
+    for row in query_rows.rows::<(String,)>()? {
+        let (name,) = row?;
+        if table.is_none_or(|table| table == name) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Check if a keyspace does not contain any tables yet
+///
+/// A fresh keyspace will have all of its tables created at the latest schema by the API.
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace to check
+pub async fn is_fresh(session: &Session, ns: &str) -> Result<bool, MigrationError> {
+    Ok(!table_exists(session, ns, None).await?)
+}
+
+/// Setup the schema version table for Thorium
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace to create the schema version table in
+pub async fn setup_schema_version_table(session: &Session, ns: &str) -> Result<(), MigrationError> {
+    // build cmd for the schema version table
+    let table_create = format!(
+        "CREATE TABLE IF NOT EXISTS {ns}.schema_version (\
+            component TEXT,
+            version INT,
+            name TEXT,
+            applied TIMESTAMP,
+            PRIMARY KEY ((component), version))
+            WITH CLUSTERING ORDER BY (version DESC)",
+        ns = ns,
+    );
+    // create the schema version table
+    session.query_unpaged(table_create, &[]).await?;
+    Ok(())
+}
+
+/// Get the last migration applied to a keyspace
+///
+/// None is returned if no migrations have been applied or the schema version table does not
+/// exist yet.
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace to get the schema version for
+pub async fn get_schema_version(
+    session: &Session,
+    ns: &str,
+) -> Result<Option<SchemaVersionRow>, MigrationError> {
+    // clusters deployed before schema versions were tracked will not have this table
+    if !table_exists(session, ns, Some("schema_version")).await? {
+        return Ok(None);
+    }
+    // get the latest version that has been applied
+    let query = session
+        .query_unpaged(
+            format!(
+                "SELECT version, name, applied \
+                FROM {ns}.schema_version \
+                WHERE component = ? \
+                LIMIT 1",
+                ns = ns,
+            ),
+            (SCHEMA_COMPONENT,),
+        )
+        .await?;
+    let query_rows = query.into_rows_result()?;
+    Ok(query_rows.maybe_first_row::<SchemaVersionRow>()?)
+}
+
+/// Record that a migration was applied to a keyspace
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace the migration was applied to
+/// * `migration` - The migration that was applied
+async fn record(session: &Session, ns: &str, migration: &Migration) -> Result<(), MigrationError> {
+    session
+        .query_unpaged(
+            format!(
+                "INSERT INTO {ns}.schema_version (component, version, name, applied) \
+                VALUES (?, ?, ?, ?) \
+                IF NOT EXISTS",
+                ns = ns,
+            ),
+            (
+                SCHEMA_COMPONENT,
+                migration.version,
+                migration.name,
+                Utc::now(),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Apply a single migration to a keyspace and record it in the schema version table
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace to apply this migration to
+/// * `migration` - The migration to apply
+pub async fn apply(
+    session: &Session,
+    ns: &str,
+    migration: &Migration,
+) -> Result<(), MigrationError> {
+    // run each statement in this migration in order
+    for statement in migration.statements(ns) {
+        if let Err(error) = session.query_unpaged(statement.clone(), &[]).await {
+            return Err(MigrationError::Failed {
+                version: migration.version,
+                statement,
+                error,
+            });
+        }
+    }
+    // record that this migration was applied
+    record(session, ns, migration).await
+}
+
+/// Mark every migration as applied without running them
+///
+/// This is used when the API creates all tables in a fresh keyspace as they will already be at
+/// the latest schema.
+///
+/// # Arguments
+///
+/// * `session` - The scylla session to use
+/// * `ns` - The keyspace to stamp
+pub async fn stamp_latest(session: &Session, ns: &str) -> Result<(), MigrationError> {
+    for migration in MIGRATIONS {
+        record(session, ns, migration).await?;
+    }
+    Ok(())
+}
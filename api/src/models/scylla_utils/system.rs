@@ -22,6 +22,10 @@ pub struct NodeRow {
     pub resources: String,
     /// The last time this node completed a health check
     pub heart_beat: Option<DateTime<Utc>>,
+    /// The serialized CPU architecture of this node
+    pub arch: Option<String>,
+    /// The serialized architectures this node can emulate
+    pub emulated: Option<String>,
 }
 
 /// An internal struct for getting worker info from the db
@@ -1,6 +1,9 @@
 //! The scylla utils for tags
 use chrono::prelude::*;
 use scylla::DeserializeRow;
+use uuid::Uuid;
+
+use crate::models::{ImageVersion, TagProvenance, TagSource};
 
 /// An internal struct containing one instance or row of a tag in scylla
 #[derive(Serialize, Deserialize, Debug, Clone, DeserializeRow)]
@@ -47,3 +50,51 @@ pub struct FullTagRow {
     /// The item we are getting tags for
     pub item: String,
 }
+
+/// An internal struct containing the provenance of a single tag in scylla
+#[derive(Debug, Clone, DeserializeRow)]
+#[scylla(flavor = "enforce_order", skip_name_checks)]
+pub struct TagProvenanceRow {
+    /// The group this tag is a part of
+    pub group: String,
+    /// The key for this tag
+    pub key: String,
+    /// The value for this tag
+    pub value: String,
+    /// Whether this tag was set by a user or a tool
+    pub source: TagSource,
+    /// The user that set this tag
+    pub username: String,
+    /// The tool that set this tag (empty for tags set by users)
+    pub tool: Option<String>,
+    /// The version of the tool that set this tag
+    pub tool_version: Option<ImageVersion>,
+    /// The result this tag was extracted from
+    pub result: Option<Uuid>,
+    /// How confident the source of this tag is in it
+    pub confidence: Option<f32>,
+    /// When this tag was set
+    pub created: DateTime<Utc>,
+}
+
+impl From<TagProvenanceRow> for TagProvenance {
+    /// Convert a tag provenance row to a tag provenance
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row to convert
+    fn from(row: TagProvenanceRow) -> Self {
+        TagProvenance {
+            group: row.group,
+            key: row.key,
+            value: row.value,
+            source: row.source,
+            user: row.username,
+            tool: row.tool.filter(|tool| !tool.is_empty()),
+            tool_version: row.tool_version,
+            result: row.result,
+            confidence: row.confidence,
+            timestamp: row.created,
+        }
+    }
+}
@@ -1,3 +1,6 @@
 //! Models for search
 
 pub mod events;
+mod saved;
+
+pub use saved::{SavedSearch, SavedSearchRequest};
@@ -0,0 +1,186 @@
+//! Searches that users have saved so they can be run again later
+
+use chrono::prelude::*;
+
+use crate::models::elastic::default_search_indexes;
+use crate::models::{ElasticIndex, ElasticSearchOpts};
+
+/// A request to save a search
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct SavedSearchRequest {
+    /// The name of this saved search
+    pub name: String,
+    /// The query to search with
+    pub query: String,
+    /// The indexes to search
+    #[serde(default = "default_search_indexes")]
+    pub indexes: Vec<ElasticIndex>,
+    /// The groups to search in (all of the users groups if empty)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// The most recent date to start searching at
+    pub start: Option<DateTime<Utc>>,
+    /// The oldest date to stop searching at
+    pub end: Option<DateTime<Utc>>,
+    /// A description of what this search finds
+    pub description: Option<String>,
+}
+
+impl SavedSearchRequest {
+    /// Create a new saved search request
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to save this search as
+    /// * `query` - The query to search with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{ElasticIndex, SavedSearchRequest};
+    ///
+    /// SavedSearchRequest::new("corn-pe", "corn AND pe32")
+    ///     .indexes(vec![ElasticIndex::SampleResults])
+    ///     .group("CornPeeps")
+    ///     .description("PE files that mention corn");
+    /// ```
+    pub fn new<N: Into<String>, Q: Into<String>>(name: N, query: Q) -> Self {
+        SavedSearchRequest {
+            name: name.into(),
+            query: query.into(),
+            indexes: default_search_indexes(),
+            groups: Vec::default(),
+            start: None,
+            end: None,
+            description: None,
+        }
+    }
+
+    /// Set the indexes to search
+    ///
+    /// # Arguments
+    ///
+    /// * `indexes` - The indexes to search
+    #[must_use]
+    pub fn indexes(mut self, indexes: Vec<ElasticIndex>) -> Self {
+        self.indexes = indexes;
+        self
+    }
+
+    /// Add a group to search in
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to search in
+    #[must_use]
+    pub fn group<T: Into<String>>(mut self, group: T) -> Self {
+        self.groups.push(group.into());
+        self
+    }
+
+    /// Set the most recent date to start searching at
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The date to start searching at
+    #[must_use]
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Set the oldest date to stop searching at
+    ///
+    /// # Arguments
+    ///
+    /// * `end` - The date to stop searching at
+    #[must_use]
+    pub fn end(mut self, end: DateTime<Utc>) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Set a description for this saved search
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - The description to set
+    #[must_use]
+    pub fn description<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// A search that a user has saved
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct SavedSearch {
+    /// The name of this saved search
+    pub name: String,
+    /// The query to search with
+    pub query: String,
+    /// The indexes to search
+    pub indexes: Vec<ElasticIndex>,
+    /// The groups to search in (all of the users groups if empty)
+    pub groups: Vec<String>,
+    /// The most recent date to start searching at
+    pub start: Option<DateTime<Utc>>,
+    /// The oldest date to stop searching at
+    pub end: Option<DateTime<Utc>>,
+    /// A description of what this search finds
+    pub description: Option<String>,
+    /// When this search was saved
+    pub created: DateTime<Utc>,
+}
+
+impl From<SavedSearchRequest> for SavedSearch {
+    /// Build a saved search from a request to save a search
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to save a search
+    fn from(req: SavedSearchRequest) -> Self {
+        SavedSearch {
+            name: req.name,
+            query: req.query,
+            indexes: req.indexes,
+            groups: req.groups,
+            start: req.start,
+            end: req.end,
+            description: req.description,
+            created: Utc::now(),
+        }
+    }
+}
+
+impl SavedSearch {
+    /// Build the options to run this saved search with
+    #[must_use]
+    pub fn to_opts(&self) -> ElasticSearchOpts {
+        let mut opts = ElasticSearchOpts::new(&self.query)
+            .indexes(self.indexes.clone())
+            .groups(self.groups.clone());
+        opts.start = self.start;
+        opts.end = self.end;
+        opts
+    }
+}
+
+impl PartialEq<SavedSearchRequest> for SavedSearch {
+    /// Check if a saved search matches the request that saved it
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to compare against
+    fn eq(&self, req: &SavedSearchRequest) -> bool {
+        self.name == req.name
+            && self.query == req.query
+            && self.indexes == req.indexes
+            && self.groups == req.groups
+            && self.start == req.start
+            && self.end == req.end
+            && self.description == req.description
+    }
+}
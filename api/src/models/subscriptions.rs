@@ -0,0 +1,312 @@
+//! Subscriptions that let users follow things in Thorium and get notified when they change
+
+use chrono::prelude::*;
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// Something in Thorium that a user can subscribe to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum SubscriptionTarget {
+    /// A sample
+    Sample {
+        /// The sha256 of the sample to follow
+        sha256: String,
+    },
+    /// A repo
+    Repo {
+        /// The url of the repo to follow
+        url: String,
+    },
+    /// Any sample or repo with a specific tag
+    Tag {
+        /// The tag key to follow
+        key: String,
+        /// The tag value to follow
+        value: String,
+    },
+    /// A pipeline
+    Pipeline {
+        /// The group the pipeline is in
+        group: String,
+        /// The name of the pipeline to follow
+        pipeline: String,
+    },
+}
+
+impl SubscriptionTarget {
+    /// Subscribe to a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample to follow
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::SubscriptionTarget;
+    ///
+    /// SubscriptionTarget::sample("63b9b3ad2a1ce4a1cf7d4fa5d0c3d6f0a4d4c7d4e5b1c2c6e7c1f0b9f0e6a3d1");
+    /// ```
+    pub fn sample<T: Into<String>>(sha256: T) -> Self {
+        SubscriptionTarget::Sample {
+            sha256: sha256.into(),
+        }
+    }
+
+    /// Subscribe to a repo
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The url of the repo to follow
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::SubscriptionTarget;
+    ///
+    /// SubscriptionTarget::repo("github.com/curl/curl");
+    /// ```
+    pub fn repo<T: Into<String>>(url: T) -> Self {
+        SubscriptionTarget::Repo { url: url.into() }
+    }
+
+    /// Subscribe to a tag key/value
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The tag key to follow
+    /// * `value` - The tag value to follow
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::SubscriptionTarget;
+    ///
+    /// SubscriptionTarget::tag("Family", "Corn");
+    /// ```
+    pub fn tag<K: Into<String>, V: Into<String>>(key: K, value: V) -> Self {
+        SubscriptionTarget::Tag {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Subscribe to a pipeline
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group the pipeline is in
+    /// * `pipeline` - The name of the pipeline to follow
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::SubscriptionTarget;
+    ///
+    /// SubscriptionTarget::pipeline("CornPeeps", "harvest");
+    /// ```
+    pub fn pipeline<G: Into<String>, P: Into<String>>(group: G, pipeline: P) -> Self {
+        SubscriptionTarget::Pipeline {
+            group: group.into(),
+            pipeline: pipeline.into(),
+        }
+    }
+
+    /// Get the kind of thing this target is
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SubscriptionTarget::Sample { .. } => "Sample",
+            SubscriptionTarget::Repo { .. } => "Repo",
+            SubscriptionTarget::Tag { .. } => "Tag",
+            SubscriptionTarget::Pipeline { .. } => "Pipeline",
+        }
+    }
+
+    /// Get the key subscriptions to this target are saved under
+    #[must_use]
+    pub fn key(&self) -> String {
+        match self {
+            SubscriptionTarget::Sample { sha256 } => sha256.clone(),
+            SubscriptionTarget::Repo { url } => url.clone(),
+            SubscriptionTarget::Tag { key, value } => format!("{key}={value}"),
+            SubscriptionTarget::Pipeline { group, pipeline } => format!("{group}/{pipeline}"),
+        }
+    }
+}
+
+impl std::fmt::Display for SubscriptionTarget {
+    /// Write this target in a human readable form
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter to write to
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubscriptionTarget::Sample { sha256 } => write!(f, "sample {sha256}"),
+            SubscriptionTarget::Repo { url } => write!(f, "repo {url}"),
+            SubscriptionTarget::Tag { key, value } => write!(f, "tag {key}={value}"),
+            SubscriptionTarget::Pipeline { group, pipeline } => {
+                write!(f, "pipeline {group}/{pipeline}")
+            }
+        }
+    }
+}
+
+/// The events that can send notifications to subscribed users
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    clap::ValueEnum,
+)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum SubscriptionEvent {
+    /// New results were added
+    Results,
+    /// A new comment was left
+    Comments,
+    /// A new child sample was uploaded
+    Children,
+    /// A reaction failed
+    FailedReactions,
+    /// A sample or repo was tagged with a followed tag
+    Tagged,
+}
+
+/// A request to subscribe to something in Thorium
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct SubscriptionRequest {
+    /// The thing to subscribe to
+    pub target: SubscriptionTarget,
+    /// The events to be notified about (all events if empty)
+    #[serde(default)]
+    pub events: BTreeSet<SubscriptionEvent>,
+    /// Whether to also send these notifications by email
+    #[serde(default)]
+    pub email: bool,
+}
+
+impl SubscriptionRequest {
+    /// Create a new subscription request
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The thing to subscribe to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{SubscriptionEvent, SubscriptionRequest, SubscriptionTarget};
+    ///
+    /// SubscriptionRequest::new(SubscriptionTarget::pipeline("CornPeeps", "harvest"))
+    ///     .event(SubscriptionEvent::FailedReactions)
+    ///     .email(true);
+    /// ```
+    #[must_use]
+    pub fn new(target: SubscriptionTarget) -> Self {
+        SubscriptionRequest {
+            target,
+            events: BTreeSet::default(),
+            email: false,
+        }
+    }
+
+    /// Add an event to be notified about
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to be notified about
+    #[must_use]
+    pub fn event(mut self, event: SubscriptionEvent) -> Self {
+        self.events.insert(event);
+        self
+    }
+
+    /// Set whether to also send these notifications by email
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - Whether to send emails
+    #[must_use]
+    pub fn email(mut self, email: bool) -> Self {
+        self.email = email;
+        self
+    }
+}
+
+/// The response from subscribing to something
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct SubscriptionResponse {
+    /// The id of the new subscription
+    pub id: Uuid,
+}
+
+/// A subscription a user has to something in Thorium
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct Subscription {
+    /// The id of this subscription
+    pub id: Uuid,
+    /// The user that owns this subscription
+    pub user: String,
+    /// The thing this user is subscribed to
+    pub target: SubscriptionTarget,
+    /// The events to be notified about (all events if empty)
+    pub events: BTreeSet<SubscriptionEvent>,
+    /// Whether to also send these notifications by email
+    pub email: bool,
+    /// When this subscription was created
+    pub created: DateTime<Utc>,
+}
+
+impl Subscription {
+    /// Create a new subscription from a request
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user that is subscribing
+    /// * `req` - The request to subscribe to something
+    #[must_use]
+    pub fn new<T: Into<String>>(user: T, req: SubscriptionRequest) -> Self {
+        Subscription {
+            id: Uuid::new_v4(),
+            user: user.into(),
+            target: req.target,
+            events: req.events,
+            email: req.email,
+            created: Utc::now(),
+        }
+    }
+
+    /// Check if this subscription wants to be notified about an event
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to check
+    #[must_use]
+    pub fn wants(&self, event: SubscriptionEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+impl PartialEq<SubscriptionRequest> for Subscription {
+    /// Check if a subscription matches the request that created it
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to compare against
+    fn eq(&self, req: &SubscriptionRequest) -> bool {
+        self.target == req.target && self.events == req.events && self.email == req.email
+    }
+}
@@ -9,7 +9,8 @@ use crate::models::conversions;
 use crate::{Conf, matches_adds, matches_removes, matches_update};
 
 use super::{
-    Group, GroupStats, Image, ImageScaler, InvalidEnum, Pipeline, Requisition, Resources, User,
+    CpuArch, Group, GroupStats, Image, ImageScaler, InvalidEnum, Pipeline, Requisition, Resources,
+    User,
 };
 
 /// The default IFF to use when initializing Thorium
@@ -83,6 +84,16 @@ impl ScalerStats {
 /// A map of spawned requisitions
 pub type SpawnMap<'a> = HashMap<&'a String, BTreeMap<u64, Vec<(Requisition, u64)>>>;
 
+/// The number of responses a single API instance has sent since it started
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct ResponseCounts {
+    /// The total number of responses sent
+    pub total: u64,
+    /// The number of responses that were server errors (5xx)
+    pub server_errors: u64,
+}
+
 /// Statistics about the current state of Thorium
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
@@ -512,6 +523,12 @@ pub struct NodeRegistration {
     pub name: String,
     /// The amount of resources this node has
     pub resources: Resources,
+    /// The CPU architecture of this node
+    #[serde(default)]
+    pub arch: CpuArch,
+    /// The architectures this node can run under qemu-user emulation
+    #[serde(default)]
+    pub emulated: Vec<CpuArch>,
 }
 
 impl NodeRegistration {
@@ -531,8 +548,32 @@ impl NodeRegistration {
             cluster: cluster.into(),
             name: name.into(),
             resources,
+            arch: CpuArch::default(),
+            emulated: Vec::default(),
         }
     }
+
+    /// Set the CPU architecture of this node
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - The architecture of this node
+    #[must_use]
+    pub fn arch(mut self, arch: CpuArch) -> Self {
+        self.arch = arch;
+        self
+    }
+
+    /// Add an architecture this node can run under qemu-user emulation
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - The architecture this node can emulate
+    #[must_use]
+    pub fn emulate(mut self, arch: CpuArch) -> Self {
+        self.emulated.push(arch);
+        self
+    }
 }
 
 /// The current health of this node
@@ -586,6 +627,12 @@ pub struct Node {
     pub workers: HashMap<String, Worker>,
     /// The last time this node completed a health check
     pub heart_beat: Option<DateTime<Utc>>,
+    /// The CPU architecture of this node
+    #[serde(default)]
+    pub arch: CpuArch,
+    /// The architectures this node can run under qemu-user emulation
+    #[serde(default)]
+    pub emulated: Vec<CpuArch>,
 }
 
 // A heartbeat for a nodes info
@@ -599,6 +646,12 @@ pub struct NodeUpdate {
     /// Whether this update is a heart beat or not
     #[serde(default)]
     pub heart_beat: bool,
+    /// The updated CPU architecture of this node
+    #[serde(default)]
+    pub arch: Option<CpuArch>,
+    /// The updated architectures this node can run under qemu-user emulation
+    #[serde(default)]
+    pub emulated: Option<Vec<CpuArch>>,
 }
 
 impl NodeUpdate {
@@ -612,9 +665,33 @@ impl NodeUpdate {
             health,
             resources,
             heart_beat: false,
+            arch: None,
+            emulated: None,
         }
     }
 
+    /// Set the CPU architecture of this node
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - The architecture of this node
+    #[must_use]
+    pub fn arch(mut self, arch: CpuArch) -> Self {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// Set the architectures this node can run under qemu-user emulation
+    ///
+    /// # Arguments
+    ///
+    /// * `emulated` - The architectures this node can emulate
+    #[must_use]
+    pub fn emulated(mut self, emulated: Vec<CpuArch>) -> Self {
+        self.emulated = Some(emulated);
+        self
+    }
+
     /// Set that this update should update the heart beat timestamp
     #[must_use]
     pub fn heart_beat(mut self) -> Self {
@@ -1,11 +1,13 @@
 //! Structures for tagging objects in Thorium
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::str::FromStr;
+use uuid::Uuid;
 
-use super::InvalidEnum;
 use super::backends::TagSupport;
+use super::{ImageVersion, InvalidEnum};
 
 /// The different types of tags
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Hash, Eq)]
@@ -71,6 +73,9 @@ pub struct TagRequest<T: TagSupport> {
     /// The trigger depth for this request
     #[serde(default)]
     pub trigger_depth: u8,
+    /// Where these tags came from if they were not set directly by a user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<TagProvenanceRequest>,
     /// The type we are implementing a tag request for
     #[serde(default)]
     #[cfg_attr(feature = "api", schema(ignore, value_type = String))]
@@ -85,6 +90,7 @@ impl<T: TagSupport> Clone for TagRequest<T> {
             groups: self.groups.clone(),
             tags: self.tags.clone(),
             trigger_depth: self.trigger_depth,
+            provenance: self.provenance.clone(),
             phantom: PhantomData,
         }
     }
@@ -226,6 +232,27 @@ impl<T: TagSupport> TagRequest<T> {
         self.trigger_depth = trigger_depth;
         self
     }
+
+    /// Set where these tags came from
+    ///
+    /// # Arguments
+    ///
+    /// * `provenance` - Where these tags came from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{Sample, TagProvenanceRequest, TagRequest};
+    ///
+    /// TagRequest::<Sample>::default()
+    ///     .add("family", "Qakbot")
+    ///     .provenance(TagProvenanceRequest::default().confidence(0.8));
+    /// ```
+    #[must_use]
+    pub fn provenance(mut self, provenance: TagProvenanceRequest) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
 }
 
 impl<T: TagSupport> Default for TagRequest<T> {
@@ -235,6 +262,7 @@ impl<T: TagSupport> Default for TagRequest<T> {
             groups: Vec::default(),
             tags: HashMap::with_capacity(1),
             trigger_depth: 0,
+            provenance: None,
             phantom: PhantomData::default(),
         }
     }
@@ -403,6 +431,912 @@ impl<T: TagSupport> Default for TagDeleteRequest<T> {
     }
 }
 
+/// Where a tag came from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "scylla-utils", derive(thorium_derive::ScyllaStoreAsStr))]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum TagSource {
+    /// This tag was set directly by a user
+    User,
+    /// This tag was set by a tool such as an image or a rule
+    Tool,
+}
+
+impl TagSource {
+    /// Cast our tag source to a str
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            TagSource::User => "User",
+            TagSource::Tool => "Tool",
+        }
+    }
+}
+
+impl FromStr for TagSource {
+    type Err = InvalidEnum;
+
+    /// Convert this str to a [`TagSource`]
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "User" => Ok(TagSource::User),
+            "Tool" => Ok(TagSource::Tool),
+            _ => Err(InvalidEnum(format!("Unknown TagSource: {raw}"))),
+        }
+    }
+}
+
+impl std::fmt::Display for TagSource {
+    /// Allow tag sources to be displayed
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Where the tags in a tag request came from
+///
+/// The API decides what set these tags. Tags are only attributed to a tool when they are set
+/// by one of the requesting user's running jobs. All other tags are treated as being set
+/// directly by the user that added them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct TagProvenanceRequest {
+    /// The running job that set these tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job: Option<Uuid>,
+    /// The result these tags were extracted from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Uuid>,
+    /// How confident the source of these tags is in them from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// An existing tag on this item whose provenance these tags should keep
+    ///
+    /// This is used when rewriting a tag so its new form keeps where it came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<(String, String)>,
+}
+
+impl TagProvenanceRequest {
+    /// Set the running job that set these tags
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The id of the job that set these tags
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagProvenanceRequest;
+    /// use uuid::Uuid;
+    ///
+    /// TagProvenanceRequest::default().job(Uuid::new_v4());
+    /// ```
+    #[must_use]
+    pub fn job(mut self, job: Uuid) -> Self {
+        self.job = Some(job);
+        self
+    }
+
+    /// Set the result these tags were extracted from
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The id of the result these tags were extracted from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagProvenanceRequest;
+    /// use uuid::Uuid;
+    ///
+    /// TagProvenanceRequest::default().job(Uuid::new_v4()).result(Uuid::new_v4());
+    /// ```
+    #[must_use]
+    pub fn result(mut self, result: Uuid) -> Self {
+        self.result = Some(result);
+        self
+    }
+
+    /// Set how confident the source of these tags is in them
+    ///
+    /// # Arguments
+    ///
+    /// * `confidence` - The confidence from 0 to 1
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagProvenanceRequest;
+    ///
+    /// TagProvenanceRequest::default().confidence(0.75);
+    /// ```
+    #[must_use]
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    /// Keep the provenance of an existing tag on this item
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the existing tag
+    /// * `value` - The value of the existing tag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagProvenanceRequest;
+    ///
+    /// // keep where family=emotet came from when rewriting it to family=Emotet
+    /// TagProvenanceRequest::default().keep("family", "emotet");
+    /// ```
+    #[must_use]
+    pub fn keep<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.keep = Some((key.into(), value.into()));
+        self
+    }
+}
+
+/// Who or what last set a single tag in a group and why
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct TagProvenance {
+    /// The group this tag is in
+    pub group: String,
+    /// The key for this tag
+    pub key: String,
+    /// The value for this tag
+    pub value: String,
+    /// Whether this tag was set by a user or a tool
+    pub source: TagSource,
+    /// The user that set this tag or that the tool ran as
+    pub user: String,
+    /// The tool that set this tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// The version of the tool that set this tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<ImageVersion>,
+    /// The result this tag was extracted from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Uuid>,
+    /// How confident the source of this tag is in it from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// When this tag was set
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The params for filtering tag provenance
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct TagProvenanceParams {
+    /// The groups to get tag provenance from (defaults to all of our groups)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Only get tags from this source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<TagSource>,
+    /// Only get tags with at least this confidence
+    ///
+    /// Tags without a confidence are treated as fully confident.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+}
+
+impl TagProvenanceParams {
+    /// Limit our tag provenance to a specific group
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to get tag provenance from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagProvenanceParams;
+    ///
+    /// TagProvenanceParams::default().group("CornGroup");
+    /// ```
+    #[must_use]
+    pub fn group<T: Into<String>>(mut self, group: T) -> Self {
+        self.groups.push(group.into());
+        self
+    }
+
+    /// Only get tags from a specific source
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source to get tags from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{TagProvenanceParams, TagSource};
+    ///
+    /// // only get tags set directly by analysts
+    /// TagProvenanceParams::default().source(TagSource::User);
+    /// ```
+    #[must_use]
+    pub fn source(mut self, source: TagSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Only get tags with at least some confidence
+    ///
+    /// # Arguments
+    ///
+    /// * `min_confidence` - The minimum confidence from 0 to 1
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagProvenanceParams;
+    ///
+    /// TagProvenanceParams::default().min_confidence(0.5);
+    /// ```
+    #[must_use]
+    pub fn min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    /// Check if a tags provenance matches these params
+    ///
+    /// # Arguments
+    ///
+    /// * `provenance` - The tag provenance to check
+    #[must_use]
+    pub fn matches(&self, provenance: &TagProvenance) -> bool {
+        // skip any tags from the wrong source
+        if self
+            .source
+            .is_some_and(|source| source != provenance.source)
+        {
+            return false;
+        }
+        // skip any tags that are not confident enough
+        match self.min_confidence {
+            Some(min) => provenance.confidence.unwrap_or(1.0) >= min,
+            None => true,
+        }
+    }
+}
+
+/// The rules for a single controlled tag key in a group's tag vocabulary
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct TagKeyRules {
+    /// The values this key is allowed to have (any value is allowed if this is empty)
+    #[serde(default)]
+    pub values: HashSet<String>,
+    /// A regex that values for this key must fully match
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// Aliases that are rewritten to a canonical value on ingest (e.g. `emotet` -> `Emotet`)
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// Whether this key must be set when files or repos are uploaded to this group
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl TagKeyRules {
+    /// Add an allowed value for this key
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to allow
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagKeyRules;
+    ///
+    /// TagKeyRules::default().value("Emotet").value("Qakbot");
+    /// ```
+    #[must_use]
+    pub fn value<T: Into<String>>(mut self, value: T) -> Self {
+        self.values.insert(value.into());
+        self
+    }
+
+    /// Set the regex that values for this key must fully match
+    ///
+    /// # Arguments
+    ///
+    /// * `regex` - The regex to match values against
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagKeyRules;
+    ///
+    /// TagKeyRules::default().regex("CVE-[0-9]{4}-[0-9]+");
+    /// ```
+    #[must_use]
+    pub fn regex<T: Into<String>>(mut self, regex: T) -> Self {
+        self.regex = Some(regex.into());
+        self
+    }
+
+    /// Add an alias that is rewritten to a canonical value
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias to rewrite
+    /// * `canonical` - The value to rewrite this alias to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagKeyRules;
+    ///
+    /// TagKeyRules::default().value("Emotet").alias("Heodo", "Emotet");
+    /// ```
+    #[must_use]
+    pub fn alias<A: Into<String>, C: Into<String>>(mut self, alias: A, canonical: C) -> Self {
+        self.aliases.insert(alias.into(), canonical.into());
+        self
+    }
+
+    /// Require this key to be set when files or repos are uploaded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagKeyRules;
+    ///
+    /// TagKeyRules::default().required();
+    /// ```
+    #[must_use]
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+}
+
+/// Find the canonical value for an alias ignoring case
+///
+/// # Arguments
+///
+/// * `aliases` - The aliases to search
+/// * `alias` - The alias to look for
+fn find_alias<'a>(aliases: &'a HashMap<String, String>, alias: &str) -> Option<&'a String> {
+    // check for an exact match first
+    match aliases.get(alias) {
+        Some(canonical) => Some(canonical),
+        None => aliases
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(alias))
+            .map(|(_, canonical)| canonical),
+    }
+}
+
+/// The controlled tag keys, values, and aliases for a group
+///
+/// Tags added to a group are rewritten to their canonical keys/values before being
+/// checked against this vocabulary. Keys and values are matched case-insensitively when
+/// rewriting so `family=emotet` will become `family=Emotet` if `Emotet` is an allowed value.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct TagVocabulary {
+    /// Whether tag keys that are not controlled by this vocabulary are rejected
+    #[serde(default)]
+    pub strict: bool,
+    /// The rules for each controlled tag key
+    #[serde(default)]
+    pub keys: HashMap<String, TagKeyRules>,
+    /// Aliases that are rewritten to a canonical key on ingest (e.g. `malware_family` -> `family`)
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+impl TagVocabulary {
+    /// Only allow controlled tag keys
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::TagVocabulary;
+    ///
+    /// TagVocabulary::default().strict();
+    /// ```
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Add a controlled tag key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The tag key to control
+    /// * `rules` - The rules for this key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{TagKeyRules, TagVocabulary};
+    ///
+    /// TagVocabulary::default().key("family", TagKeyRules::default().value("Emotet"));
+    /// ```
+    #[must_use]
+    pub fn key<T: Into<String>>(mut self, key: T, rules: TagKeyRules) -> Self {
+        self.keys.insert(key.into(), rules);
+        self
+    }
+
+    /// Add an alias that is rewritten to a canonical key
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias to rewrite
+    /// * `canonical` - The key to rewrite this alias to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{TagKeyRules, TagVocabulary};
+    ///
+    /// TagVocabulary::default()
+    ///     .key("family", TagKeyRules::default())
+    ///     .alias("malware_family", "family");
+    /// ```
+    #[must_use]
+    pub fn alias<A: Into<String>, C: Into<String>>(mut self, alias: A, canonical: C) -> Self {
+        self.aliases.insert(alias.into(), canonical.into());
+        self
+    }
+
+    /// Check if this vocabulary places no restrictions on tags
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.strict && self.keys.is_empty() && self.aliases.is_empty()
+    }
+
+    /// Get the canonical form of a tag key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to get the canonical form of
+    #[must_use]
+    pub fn canonical_key(&self, key: &str) -> String {
+        // controlled keys are already canonical
+        if self.keys.contains_key(key) {
+            return key.to_owned();
+        }
+        // rewrite any aliases
+        if let Some(canonical) = find_alias(&self.aliases, key) {
+            return canonical.clone();
+        }
+        // match controlled keys regardless of case
+        match self.keys.keys().find(|name| name.eq_ignore_ascii_case(key)) {
+            Some(name) => name.clone(),
+            None => key.to_owned(),
+        }
+    }
+
+    /// Get the canonical form of a tag value
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The canonical key this value is for
+    /// * `value` - The value to get the canonical form of
+    #[must_use]
+    pub fn canonical_value(&self, key: &str, value: &str) -> String {
+        // values for keys we don't control are left alone
+        let Some(rules) = self.keys.get(key) else {
+            return value.to_owned();
+        };
+        // allowed values are already canonical
+        if rules.values.contains(value) {
+            return value.to_owned();
+        }
+        // rewrite any aliases
+        if let Some(canonical) = find_alias(&rules.aliases, value) {
+            return canonical.clone();
+        }
+        // match allowed values regardless of case
+        match rules
+            .values
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(value))
+        {
+            Some(name) => name.clone(),
+            None => value.to_owned(),
+        }
+    }
+
+    /// Get the canonical form of a tag
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The tag key to normalize
+    /// * `value` - The tag value to normalize
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::{TagKeyRules, TagVocabulary};
+    ///
+    /// let vocab = TagVocabulary::default()
+    ///     .key("family", TagKeyRules::default().value("Emotet"))
+    ///     .alias("malware_family", "family");
+    /// let (key, value) = vocab.normalize("malware_family", "emotet");
+    /// assert_eq!(key, "family");
+    /// assert_eq!(value, "Emotet");
+    /// ```
+    #[must_use]
+    pub fn normalize(&self, key: &str, value: &str) -> (String, String) {
+        let key = self.canonical_key(key);
+        let value = self.canonical_value(&key, value);
+        (key, value)
+    }
+
+    /// Rewrite a map of tags to their canonical forms
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tags to normalize
+    #[must_use]
+    pub fn normalize_tags(
+        &self,
+        tags: HashMap<String, HashSet<String>>,
+    ) -> HashMap<String, HashSet<String>> {
+        // skip rewriting anything if this vocabulary is empty
+        if self.is_empty() {
+            return tags;
+        }
+        let mut normalized: HashMap<String, HashSet<String>> = HashMap::with_capacity(tags.len());
+        for (key, values) in tags {
+            for value in values {
+                let (key, value) = self.normalize(&key, &value);
+                normalized.entry(key).or_default().insert(value);
+            }
+        }
+        normalized
+    }
+
+    /// Get the required tag keys that are missing from a set of tags
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tags to check
+    #[must_use]
+    pub fn missing_required(&self, tags: &HashMap<String, HashSet<String>>) -> Vec<&String> {
+        let mut missing = self
+            .keys
+            .iter()
+            .filter(|(key, rules)| rules.required && tags.get(*key).is_none_or(HashSet::is_empty))
+            .map(|(key, _)| key)
+            .collect::<Vec<&String>>();
+        missing.sort_unstable();
+        missing
+    }
+}
+
+/// An operation to apply to tags across every matching item in some groups
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum BulkTagOperation {
+    /// Rename a tag key while keeping all of its values
+    RenameKey {
+        /// The key to rename
+        from: String,
+        /// The new name for this key
+        to: String,
+    },
+    /// Replace a single value for a tag key with another value
+    ReplaceValue {
+        /// The key whose value to replace
+        key: String,
+        /// The value to replace
+        from: String,
+        /// The value to replace it with
+        to: String,
+    },
+    /// Delete a single tag
+    Delete {
+        /// The key of the tag to delete
+        key: String,
+        /// The value of the tag to delete
+        value: String,
+    },
+}
+
+impl BulkTagOperation {
+    /// Get the tag that should replace an existing tag if this operation replaces tags
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the existing tag
+    /// * `value` - The value of the existing tag
+    #[must_use]
+    pub fn replacement(&self, key: &str, value: &str) -> Option<(String, String)> {
+        match self {
+            BulkTagOperation::RenameKey { to, .. } => Some((to.clone(), value.to_owned())),
+            BulkTagOperation::ReplaceValue { to, .. } => Some((key.to_owned(), to.clone())),
+            BulkTagOperation::Delete { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BulkTagOperation {
+    /// Cleanly print a bulk tag operation
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BulkTagOperation::RenameKey { from, to } => write!(f, "rename {from} -> {to}"),
+            BulkTagOperation::ReplaceValue { key, from, to } => {
+                write!(f, "replace {key}={from} -> {key}={to}")
+            }
+            BulkTagOperation::Delete { key, value } => write!(f, "delete {key}={value}"),
+        }
+    }
+}
+
+/// Help serde default bulk tag operations to file tags
+fn default_bulk_tag_type() -> TagType {
+    TagType::Files
+}
+
+/// A request to apply a tag operation to every matching item in some groups
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct BulkTagRequest {
+    /// The type of items whose tags to change
+    #[serde(default = "default_bulk_tag_type")]
+    pub tag_type: TagType,
+    /// The operation to apply
+    pub operation: BulkTagOperation,
+    /// The groups to apply this operation in
+    pub groups: Vec<String>,
+    /// Only count the items this operation would change
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl BulkTagRequest {
+    /// Create a new bulk tag request
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation to apply
+    fn new(operation: BulkTagOperation) -> Self {
+        BulkTagRequest {
+            tag_type: TagType::Files,
+            operation,
+            groups: Vec::default(),
+            dry_run: false,
+        }
+    }
+
+    /// Create a request to rename a tag key
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The key to rename
+    /// * `to` - The new name for this key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::BulkTagRequest;
+    ///
+    /// BulkTagRequest::rename_key("malware_family", "family").group("CornGroup");
+    /// ```
+    pub fn rename_key<F: Into<String>, T: Into<String>>(from: F, to: T) -> Self {
+        Self::new(BulkTagOperation::RenameKey {
+            from: from.into(),
+            to: to.into(),
+        })
+    }
+
+    /// Create a request to replace a single value for a tag key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key whose value to replace
+    /// * `from` - The value to replace
+    /// * `to` - The value to replace it with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::BulkTagRequest;
+    ///
+    /// BulkTagRequest::replace_value("family", "emotet", "Emotet").group("CornGroup");
+    /// ```
+    pub fn replace_value<K: Into<String>, F: Into<String>, T: Into<String>>(
+        key: K,
+        from: F,
+        to: T,
+    ) -> Self {
+        Self::new(BulkTagOperation::ReplaceValue {
+            key: key.into(),
+            from: from.into(),
+            to: to.into(),
+        })
+    }
+
+    /// Create a request to delete a single tag
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the tag to delete
+    /// * `value` - The value of the tag to delete
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::BulkTagRequest;
+    ///
+    /// BulkTagRequest::delete("plant", "corn").group("CornGroup");
+    /// ```
+    pub fn delete<K: Into<String>, V: Into<String>>(key: K, value: V) -> Self {
+        Self::new(BulkTagOperation::Delete {
+            key: key.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Apply this operation to repo tags instead of file tags
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::BulkTagRequest;
+    ///
+    /// BulkTagRequest::delete("plant", "corn").group("CornGroup").repos();
+    /// ```
+    #[must_use]
+    pub fn repos(mut self) -> Self {
+        self.tag_type = TagType::Repos;
+        self
+    }
+
+    /// Add a group to apply this operation in
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to apply this operation in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::BulkTagRequest;
+    ///
+    /// BulkTagRequest::delete("plant", "corn").group("CornGroup").group("Harvesters");
+    /// ```
+    #[must_use]
+    pub fn group<T: Into<String>>(mut self, group: T) -> Self {
+        self.groups.push(group.into());
+        self
+    }
+
+    /// Only count the items this operation would change
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::BulkTagRequest;
+    ///
+    /// BulkTagRequest::delete("plant", "corn").group("CornGroup").dry_run();
+    /// ```
+    #[must_use]
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+}
+
+/// The status of a bulk tag job
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub enum BulkTagStatus {
+    /// This job has not started yet
+    Pending,
+    /// This job is currently being applied
+    Running,
+    /// This job finished applying its operation
+    Completed,
+    /// This job hit an error and stopped early
+    Failed,
+    /// This job was cancelled before it finished
+    Cancelled,
+}
+
+impl std::fmt::Display for BulkTagStatus {
+    /// Cleanly print a bulk tag status
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BulkTagStatus::Pending => write!(f, "Pending"),
+            BulkTagStatus::Running => write!(f, "Running"),
+            BulkTagStatus::Completed => write!(f, "Completed"),
+            BulkTagStatus::Failed => write!(f, "Failed"),
+            BulkTagStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// A background job applying a tag operation across many items
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct BulkTagJob {
+    /// The id of this job
+    pub id: Uuid,
+    /// The user that created this job
+    pub creator: String,
+    /// The type of items whose tags are being changed
+    pub tag_type: TagType,
+    /// The operation being applied
+    pub operation: BulkTagOperation,
+    /// The groups this operation is being applied in
+    pub groups: Vec<String>,
+    /// Whether this job is only counting the items it would change
+    pub dry_run: bool,
+    /// The current status of this job
+    pub status: BulkTagStatus,
+    /// The number of items that have matched this operation so far
+    pub matched: u64,
+    /// The number of items this operation has been applied to so far
+    pub modified: u64,
+    /// Any errors hit while applying this operation
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// When this job was created
+    pub created: DateTime<Utc>,
+    /// When this job started running
+    pub started: Option<DateTime<Utc>>,
+    /// When this job finished
+    pub finished: Option<DateTime<Utc>>,
+    /// The last time the API running this job checked in
+    #[serde(default)]
+    pub heartbeat: Option<DateTime<Utc>>,
+}
+
+impl BulkTagJob {
+    /// Create a new pending bulk tag job
+    ///
+    /// # Arguments
+    ///
+    /// * `creator` - The user that is creating this job
+    /// * `req` - The request to build this job from
+    #[must_use]
+    pub fn new<T: Into<String>>(creator: T, req: BulkTagRequest) -> Self {
+        BulkTagJob {
+            id: Uuid::new_v4(),
+            creator: creator.into(),
+            tag_type: req.tag_type,
+            operation: req.operation,
+            groups: req.groups,
+            dry_run: req.dry_run,
+            status: BulkTagStatus::Pending,
+            matched: 0,
+            modified: 0,
+            errors: Vec::default(),
+            created: Utc::now(),
+            started: None,
+            finished: None,
+            heartbeat: None,
+        }
+    }
+
+    /// Check if this job is done running
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            BulkTagStatus::Completed | BulkTagStatus::Failed | BulkTagStatus::Cancelled
+        )
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "scylla-utils", derive(scylla::DeserializeRow))]
 #[cfg_attr(
@@ -4,7 +4,7 @@ use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::hash::Hasher;
 use uuid::Uuid;
 
-use super::{Origin, Sample};
+use super::{Origin, Repo, Sample};
 
 /// Help serde default the tree depth to 5
 fn default_tree_depth() -> usize {
@@ -116,7 +116,74 @@ impl TreeSupport for TreeTags {
     }
 }
 
+/// A specific commit in a repo that samples were built from
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeCommitish {
+    /// The url of the repo this commit is in
+    pub repo: String,
+    /// The branch, commit, or tag that was checked out if one was set
+    #[serde(default)]
+    pub commitish: Option<String>,
+    /// The commit hash
+    pub commit: String,
+}
+
+impl TreeSupport for TreeCommitish {
+    /// Hash this child object
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed to set the hasher to use
+    fn tree_hash(&self, seed: i64) -> u64 {
+        // build a hasher
+        let mut hasher = gxhash::GxHasher::with_seed(seed);
+        // hash our repo and commit but not our commitish so branches/tags on the same commit match
+        hasher.write(self.repo.as_bytes());
+        hasher.write(self.commit.as_bytes());
+        // finalize our hasher
+        hasher.finish()
+    }
+
+    /// Gather any initial nodes for a tree
+    #[cfg(feature = "api")]
+    async fn gather_initial(
+        _user: &super::User,
+        query: &TreeQuery,
+        _shared: &crate::utils::Shared,
+    ) -> Result<Vec<TreeNodeData>, crate::utils::ApiError> {
+        // wrap each of our initial commits in a node
+        let initial = query
+            .commits
+            .iter()
+            .map(|commit| TreeNodeData::Commitish(commit.clone()))
+            .collect();
+        Ok(initial)
+    }
+
+    /// Gather any children for this child node
+    #[cfg(feature = "api")]
+    async fn gather_children(
+        &self,
+        user: &super::User,
+        shared: &crate::utils::Shared,
+    ) -> Result<Vec<TreeNode>, crate::utils::ApiError> {
+        // list all samples built from this commit
+        let opts = super::FileListOpts::default()
+            .tag("Repo", &self.repo)
+            .tag("Commit", &self.commit);
+        let samples = super::backends::trees::list_samples(user, opts, shared).await?;
+        // all nodes we find were built from this commit
+        let relationships = vec![TreeRelationships::BuiltFrom];
+        // wrap each sample in a node
+        let children = samples
+            .into_iter()
+            .map(|sample| TreeNode::new(relationships.clone(), TreeNodeData::Sample(sample)))
+            .collect();
+        Ok(children)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TreeQuery {
     /// The sha256s of the initial samples to build this tree from
     #[serde(default)]
@@ -124,6 +191,38 @@ pub struct TreeQuery {
     /// The different tag filters to build this tree from
     #[serde(default)]
     pub tags: Vec<BTreeMap<String, BTreeSet<String>>>,
+    /// The urls of the initial repos to build this tree from
+    #[serde(default)]
+    pub repos: Vec<String>,
+    /// The initial commits to build this tree from
+    #[serde(default)]
+    pub commits: Vec<TreeCommitish>,
+}
+
+impl TreeQuery {
+    /// Add a sample to start building this tree from
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the sample to add
+    #[must_use]
+    pub fn sample<T: Into<String>>(mut self, sha256: T) -> Self {
+        // add this sample to our initial samples
+        self.samples.push(sha256.into());
+        self
+    }
+
+    /// Add a repo to start building this tree from
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The url of the repo to add
+    #[must_use]
+    pub fn repo<T: Into<String>>(mut self, url: T) -> Self {
+        // add this repo to our initial repos
+        self.repos.push(url.into());
+        self
+    }
 }
 
 pub trait TreeSupport:
@@ -164,6 +263,10 @@ pub enum TreeNodeData {
     Sample(Sample),
     /// A single specific tag in Thorium
     Tag(TreeTags),
+    /// A repo in Thorium
+    Repo(Repo),
+    /// A specific commit in a repo
+    Commitish(TreeCommitish),
 }
 
 impl TreeNodeData {
@@ -173,6 +276,8 @@ impl TreeNodeData {
         match self {
             Self::Sample(sample) => sample.tree_hash(1234),
             Self::Tag(tags) => tags.tree_hash(1234),
+            Self::Repo(repo) => repo.tree_hash(1234),
+            Self::Commitish(commitish) => commitish.tree_hash(1234),
         }
     }
 }
@@ -185,6 +290,15 @@ pub enum TreeRelationships {
     Origin(Origin),
     /// This node is related by tags
     Tags,
+    /// This node was built from its parent or its parent was built from it
+    BuiltFrom,
+    /// This node was found as a child by a result for its parent
+    ResultChildOf {
+        /// The tool whose result found this child
+        tool: String,
+        /// The id of the result that found this child
+        result: Uuid,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -200,6 +314,33 @@ impl TreeNode {
     pub fn new(relationship: Vec<TreeRelationships>, data: TreeNodeData) -> Self {
         TreeNode { relationship, data }
     }
+
+    /// Merge new nodes into a list of nodes combining the relationships of any duplicates
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - The nodes to merge into
+    /// * `new` - The new nodes to merge
+    pub fn merge(nodes: &mut Vec<TreeNode>, new: Vec<TreeNode>) {
+        // map the nodes we already have by hash
+        let mut existing: HashMap<u64, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.data.hash(), index))
+            .collect();
+        // add each new node or extend the relationships of an existing one
+        for node in new {
+            // hash this new node
+            let hash = node.data.hash();
+            match existing.get(&hash) {
+                Some(index) => nodes[*index].relationship.extend(node.relationship),
+                None => {
+                    existing.insert(hash, nodes.len());
+                    nodes.push(node);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -215,7 +356,7 @@ pub struct Tree {
     /// The data in the leaves of this tree
     pub branches: HashMap<u64, HashSet<u64>>,
     /// The nodes that have already been sent
-    #[serde(skip_serializing_if = "HashSet::is_empty")]
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub sent: HashSet<u64>,
 }
 
@@ -276,4 +417,25 @@ impl Tree {
             Some(hash)
         }
     }
+
+    /// Merge the nodes from a grown section of this tree into this tree
+    ///
+    /// # Arguments
+    ///
+    /// * `grown` - The newly grown section of this tree to merge in
+    pub fn merge(&mut self, grown: Tree) {
+        // add any new nodes to our data map
+        for (hash, node) in grown.data_map {
+            self.data_map.entry(hash).or_insert(node);
+        }
+        // link any new children to their parents
+        for (parent, children) in grown.branches {
+            // get an entry to this parents children
+            let entry = self.branches.entry(parent).or_default();
+            // add these children
+            entry.extend(children);
+        }
+        // the grown section has the latest set of growable nodes
+        self.growable = grown.growable;
+    }
 }
@@ -0,0 +1,239 @@
+//! Resumable uploads for very large samples
+//!
+//! Instead of streaming an entire file in a single multipart form, large files can be uploaded
+//! in numbered chunks within an upload session. Each chunk maps to a single part of an S3
+//! multipart upload so a failed chunk can be retried without restarting the whole upload. Once
+//! every chunk has been uploaded the session is finished and Thorium hashes and carts the
+//! assembled file before saving it as a normal sample submission.
+
+use chrono::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+
+use super::{OriginRequest, SampleRequest};
+
+/// A request to start a resumable upload session
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct UploadSessionRequest {
+    /// The sha256 of the file that is going to be uploaded
+    pub sha256: String,
+    /// The total size of the file in bytes
+    pub size: u64,
+    /// The name of this file if one is known
+    pub file_name: Option<String>,
+    /// The groups this sample is a part of
+    pub groups: Vec<String>,
+    /// A description for this sample
+    pub description: Option<String>,
+    /// The tags for this sample
+    #[serde(default)]
+    pub tags: HashMap<String, HashSet<String>>,
+    /// The origin of this sample if one exists
+    pub origin: Option<OriginRequest>,
+    /// The trigger depth of this sample upload
+    #[serde(default)]
+    pub trigger_depth: u8,
+}
+
+impl UploadSessionRequest {
+    /// Create a new upload session request
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256` - The sha256 of the file to upload
+    /// * `size` - The size of the file to upload in bytes
+    /// * `groups` - The groups to upload this file to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thorium::models::UploadSessionRequest;
+    ///
+    /// let sha256 = "325030adff0665689b0360ac9c8398cd62a2377e98e06ad7d3914fabacb0daef";
+    /// UploadSessionRequest::new(sha256, 21_474_836_480, vec!("CornPeeps"))
+    ///     .file_name("disk.img")
+    ///     .description("A forensic image of a corn field")
+    ///     .tag("plant", "corn");
+    /// ```
+    pub fn new<S: Into<String>, T: Into<String>>(sha256: S, size: u64, groups: Vec<T>) -> Self {
+        UploadSessionRequest {
+            sha256: sha256.into(),
+            size,
+            groups: groups.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Build an upload session request from the metadata in a sample request
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The sample request to pull metadata from
+    /// * `sha256` - The sha256 of the file to upload
+    /// * `size` - The size of the file to upload in bytes
+    #[must_use]
+    pub fn from_sample_request<S: Into<String>>(req: SampleRequest, sha256: S, size: u64) -> Self {
+        // get the name of this file if we are uploading it from disk
+        let file_name = req
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned());
+        UploadSessionRequest {
+            sha256: sha256.into(),
+            size,
+            file_name,
+            groups: req.groups,
+            description: req.description,
+            tags: req.tags,
+            origin: req.origin,
+            trigger_depth: req.trigger_depth,
+        }
+    }
+
+    /// Set the name of the file being uploaded
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the file
+    #[must_use]
+    pub fn file_name<T: Into<String>>(mut self, file_name: T) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Set the description for this sample
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - The description to set
+    #[must_use]
+    pub fn description<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a tag to this sample
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the tag to add
+    /// * `value` - The value of the tag to add
+    #[must_use]
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        // get an entry to this tags values
+        let entry = self.tags.entry(key.into()).or_default();
+        // add our new value
+        entry.insert(value.into());
+        self
+    }
+
+    /// Set the origin for this sample
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin to set
+    #[must_use]
+    pub fn origin(mut self, origin: OriginRequest) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Set the trigger depth for this sample
+    ///
+    /// # Arguments
+    ///
+    /// * `trigger_depth` - The trigger depth to set
+    #[must_use]
+    pub fn trigger_depth(mut self, trigger_depth: u8) -> Self {
+        self.trigger_depth = trigger_depth;
+        self
+    }
+}
+
+/// A resumable upload session for a single file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
+pub struct UploadSession {
+    /// The id of this upload session
+    pub id: Uuid,
+    /// The user that started this upload session
+    pub creator: String,
+    /// The sha256 the uploaded file must have once finished
+    pub sha256: String,
+    /// The total size of the file in bytes
+    pub size: u64,
+    /// The size of each chunk in bytes (the final chunk may be smaller)
+    pub chunk_size: u64,
+    /// The total number of chunks that must be uploaded
+    pub chunks: u64,
+    /// The chunks that have already been uploaded
+    #[serde(default)]
+    pub uploaded: BTreeSet<u64>,
+    /// The name of this file if one is known
+    pub file_name: Option<String>,
+    /// The groups this sample will be a part of
+    pub groups: Vec<String>,
+    /// A description for this sample
+    pub description: Option<String>,
+    /// The tags for this sample
+    #[serde(default)]
+    pub tags: HashMap<String, HashSet<String>>,
+    /// The origin of this sample if one exists
+    pub origin: Option<OriginRequest>,
+    /// The trigger depth of this sample upload
+    #[serde(default)]
+    pub trigger_depth: u8,
+    /// When this upload session was started
+    pub created: DateTime<Utc>,
+    /// When this upload session will expire if no more chunks are uploaded
+    pub expires: DateTime<Utc>,
+}
+
+impl UploadSession {
+    /// Get the byte range of a specific chunk in the file being uploaded
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - The chunk to get the range for
+    #[must_use]
+    pub fn chunk_range(&self, chunk: u64) -> Option<std::ops::Range<u64>> {
+        // make sure this chunk is part of this upload
+        if chunk >= self.chunks {
+            return None;
+        }
+        // get the start and end of this chunk
+        let start = chunk * self.chunk_size;
+        let end = std::cmp::min(start + self.chunk_size, self.size);
+        Some(start..end)
+    }
+
+    /// Get the chunks that still need to be uploaded
+    #[must_use]
+    pub fn missing(&self) -> Vec<u64> {
+        (0..self.chunks)
+            .filter(|chunk| !self.uploaded.contains(chunk))
+            .collect()
+    }
+
+    /// Check if every chunk in this session has been uploaded
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.uploaded.len() as u64 == self.chunks
+    }
+}
+
+// only support scylla and other api side only structs if the api features is enabled
+cfg_if::cfg_if! {
+    if #[cfg(feature = "api")] {
+        /// A chunk that has been written to S3 as part of a multipart upload
+        #[derive(Serialize, Deserialize, Debug, Clone)]
+        pub struct UploadedChunk {
+            /// The number of bytes in this chunk
+            pub size: u64,
+            /// The entity tag S3 returned for this part
+            pub e_tag: String,
+        }
+    }
+}
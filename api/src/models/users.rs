@@ -355,6 +355,47 @@ impl PartialEq<ScrubbedUser> for ScrubbedUser {
     }
 }
 
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "api", feature = "client"))] {
+        use crate::models::backends::NotificationSupport;
+        use crate::models::{KeySupport, NotificationType};
+
+        impl NotificationSupport for User {
+            /// Provide the user notification type
+            fn notification_type() -> NotificationType {
+                NotificationType::Users
+            }
+        }
+
+        impl KeySupport for User {
+            /// Users are uniquely identified by their username
+            type Key = String;
+
+            /// Users have no extra optional components for their keys
+            type ExtraKey = ();
+
+            /// Build the key for this user if we need the key as one field
+            ///
+            /// # Arguments
+            ///
+            /// * `key` - The key to build from
+            fn build_key(key: Self::Key, _extra: &Self::ExtraKey) -> String {
+                key
+            }
+
+            /// Build a URL component composed of the key to access the resource
+            ///
+            /// # Arguments
+            ///
+            /// * `key` - The root part of this key
+            /// * `extra` - Any extra info required to build this key
+            fn key_url(key: &Self::Key, _extra: Option<&Self::ExtraKey>) -> String {
+                key.clone()
+            }
+        }
+    }
+}
+
 /// Response to a sucessful auth
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "api", derive(utoipa::ToSchema))]
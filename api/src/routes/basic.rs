@@ -1,5 +1,5 @@
 use crate::models::backends::system;
-use crate::models::Version;
+use crate::models::{ResponseCounts, Version};
 use crate::utils::{ApiError, AppState};
 use axum::extract::Json;
 use axum::extract::State;
@@ -76,6 +76,21 @@ pub async fn health(State(state): State<AppState>) -> StatusCode {
     StatusCode::SERVICE_UNAVAILABLE
 }
 
+/// Return how many responses this API instance has sent
+///
+/// This is used to check the error rate of new API pods while they are rolled out.
+#[utoipa::path(
+    get,
+    path = "/api/health/responses",
+    responses(
+        (status = 200, description = "The responses this API instance has sent", body = ResponseCounts),
+    )
+)]
+#[instrument(name = "routes::basic::responses", skip_all)]
+pub async fn responses() -> Json<ResponseCounts> {
+    Json(ResponseCounts::current())
+}
+
 /// Return the current Thorium version
 ///
 /// # Arguments
@@ -102,8 +117,8 @@ pub async fn version() -> Result<Json<Version>, ApiError> {
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(identify, banner, health, version),
-    components(schemas(Version, ApiError)),
+    paths(identify, banner, health, responses, version),
+    components(schemas(ResponseCounts, Version, ApiError)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct BasicApiDocs;
@@ -125,4 +140,5 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/api/banner", get(banner))
         .route("/api/version", get(version))
         .route("/api/health", get(health))
+        .route("/api/health/responses", get(responses))
 }
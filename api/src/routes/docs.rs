@@ -18,6 +18,7 @@ use super::search::events::{ResultSearchEventApiDocs, TagSearchEventApiDocs};
 use super::search::SearchApiDocs;
 use super::streams::StreamApiDocs;
 use super::system::SystemApiDocs;
+use super::tags::TagApiDocs;
 use super::users::UserApiDocs;
 use super::BasicApiDocs;
 
@@ -101,6 +102,7 @@ pub fn mount(router: Router<AppState>, conf: &Conf) -> Router<AppState> {
                 )
                 .url("/api/stream/openapi.json", StreamApiDocs::openapi())
                 .url("/api/system/openapi.json", SystemApiDocs::openapi())
+                .url("/api/tags/openapi.json", TagApiDocs::openapi())
                 .url("/api/users/openapi.json", UserApiDocs::openapi()),
         )
 }
@@ -1,9 +1,10 @@
 //! The files related routes for Thorium
 
+use axum::body::Bytes;
 use axum::extract::{Json, Multipart, Path, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::{delete, get, patch, post};
+use axum::routing::{delete, get, patch, post, put};
 use axum::Router;
 use axum_extra::body::AsyncReadBody;
 use tracing::instrument;
@@ -13,12 +14,13 @@ use uuid::Uuid;
 use super::OpenApiSecurity;
 use crate::models::backends::{CommentSupport, TagSupport};
 use crate::models::{
-    ApiCursor, CarvedOrigin, Comment, CommentResponse, DeleteCommentParams, DeleteSampleParams,
-    FileListParams, ImageVersion, Origin, OriginRequest, Output, OutputDisplayType,
-    OutputFormBuilder, OutputHandler, OutputKind, OutputMap, OutputResponse, PcapNetworkProtocol,
-    ResultFileDownloadParams, ResultGetParams, Sample, SampleCheck, SampleCheckResponse,
-    SampleListLine, SampleSubmissionResponse, SubmissionChunk, SubmissionUpdate, TagDeleteRequest,
-    TagRequest, User, ZipDownloadParams,
+    ApiCursor, CarvedOrigin, Comment, CommentReaction, CommentResponse, CommentRevision,
+    CommentUpdate, DeleteCommentParams, DeleteSampleParams, FileListParams, ImageVersion, Origin,
+    OriginRequest, Output, OutputDisplayType, OutputFormBuilder, OutputHandler, OutputKind,
+    OutputMap, OutputResponse, PcapNetworkProtocol, ResultFileDownloadParams, ResultGetParams,
+    Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleSubmissionResponse,
+    SubmissionChunk, SubmissionUpdate, TagDeleteRequest, TagProvenance, TagProvenanceParams,
+    TagRequest, TagSource, UploadSession, UploadSessionRequest, User, ZipDownloadParams,
 };
 use crate::utils::{ApiError, AppState};
 
@@ -353,6 +355,47 @@ async fn delete_tags(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Gets where the tags on a sample came from
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting tag provenance
+/// * `sha256` - The sample to get tag provenance for
+/// * `params` - The params to filter tag provenance with
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/files/tag-provenance/:sha256",
+    params(
+        ("sha256" = String, Path, description = "Sha256 to get tag provenance for"),
+        ("params" = TagProvenanceParams, description = "Query params to filter tag provenance with")
+    ),
+    responses(
+        (status = 200, description = "Where this sample's tags came from", body = Vec<TagProvenance>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::tag_provenance", skip_all, err(Debug))]
+async fn tag_provenance(
+    user: User,
+    Path(sha256): Path<String>,
+    mut params: TagProvenanceParams,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<TagProvenance>>, ApiError> {
+    // get the sample we are getting tag provenance for
+    let sample = Sample::get(&user, &sha256, &state.shared).await?;
+    // make sure we can see this sample in the requested groups or default to all of them
+    sample
+        .validate_groups(&user, &mut params.groups, false, &state.shared)
+        .await?;
+    // get where this sample's tags came from
+    let provenance = TagProvenance::list::<Sample>(&sample.sha256, &params, &state.shared).await?;
+    Ok(Json(provenance))
+}
+
 /// Allow users to comment on a file in Thorium
 ///
 /// # Arguments
@@ -432,6 +475,47 @@ async fn delete_comment(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Allow users to edit or react to a file's comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is updating the comment
+/// * `sha256` - The sha256 of the sample the comment is on
+/// * `id` - The id of the comment to update
+/// * `state` - Shared Thorium objects
+/// * `update` - The update to apply to the comment
+#[utoipa::path(
+    patch,
+    path = "/api/files/comment/:sha256/:id",
+    params(
+        ("sha256" = String, Path, description = "Sha256 of the sample the comment is on"),
+        ("id" = Uuid, Path, description = "Uuid of the comment to update"),
+        ("update" = CommentUpdate, description = "The update to apply to the comment")
+    ),
+    responses(
+        (status = 204, description = "Sample comment updated"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::update_comment", skip_all, err(Debug))]
+async fn update_comment(
+    user: User,
+    Path((sha256, id)): Path<(String, Uuid)>,
+    State(state): State<AppState>,
+    Json(update): Json<CommentUpdate>,
+) -> Result<StatusCode, ApiError> {
+    // get the sample the comment we are updating is on
+    let sample = Sample::get(&user, &sha256, &state.shared).await?;
+    // update this comment in the backend
+    sample
+        .update_comment(&user, &id, update, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Allow users to download comment attachments
 ///
 /// # Arguments
@@ -665,11 +749,410 @@ async fn download_result_file(
     Ok(body)
 }
 
+/// Allow users to comment on a files result in Thorium
+///
+/// # Arguments
+///
+/// * `user` - The user that is commenting on this result
+/// * `sha256` - The sha256 of the sample this result is for
+/// * `tool` - The tool this result is from
+/// * `result_id` - The id of the result to comment on
+/// * `state` - Shared Thorium objects
+/// * `multipart` - The multipart form to parse
+#[utoipa::path(
+    post,
+    path = "/api/files/result-comments/:sha256/:tool/:result_id",
+    params(
+        ("sha256" = String, Path, description = "Sha256 of the sample the result is for"),
+        ("tool" = String, Path, description = "The tool the result is from"),
+        ("result_id" = Uuid, Path, description = "Uuid of the result to comment on"),
+        ("multipart", description = "The multipart form to parse to create comment"),
+    ),
+    responses(
+        (status = 200, description = "Comment creation response", body = CommentResponse),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::create_result_comment", skip_all, err(Debug))]
+async fn create_result_comment(
+    user: User,
+    Path((sha256, tool, result_id)): Path<(String, String, Uuid)>,
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<Json<CommentResponse>, ApiError> {
+    // get the sample and result we are commenting on
+    let sample = Sample::get(&user, &sha256, &state.shared).await?;
+    let output = Output::get(&sha256, &sample, &user, &tool, &result_id, &state.shared).await?;
+    // save this comment into the backend
+    let resp = output
+        .create_comment(&user, multipart, &state.shared)
+        .await?;
+    Ok(Json(resp))
+}
+
+/// Allow users to delete a comment on a files result
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting the comment
+/// * `params` - The url query params to use
+/// * `sha256` - The sha256 of the sample this result is for
+/// * `tool` - The tool this result is from
+/// * `result_id` - The id of the result to delete a comment from
+/// * `id` - The id of the comment to delete
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/files/result-comments/:sha256/:tool/:result_id/:id",
+    params(
+        ("sha256" = String, Path, description = "Sha256 of the sample the result is for"),
+        ("tool" = String, Path, description = "The tool the result is from"),
+        ("result_id" = Uuid, Path, description = "Uuid of the result to delete a comment from"),
+        ("id" = Uuid, Path, description = "Uuid of the comment to delete"),
+        ("params" = DeleteCommentParams, description = "Groups the comment to be deleted is part of")
+    ),
+    responses(
+        (status = 204, description = "Result comment deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::delete_result_comment", skip_all, err(Debug))]
+async fn delete_result_comment(
+    user: User,
+    params: DeleteCommentParams,
+    Path((sha256, tool, result_id, id)): Path<(String, String, Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // get the sample and result we are deleting a comment from
+    let sample = Sample::get(&user, &sha256, &state.shared).await?;
+    let output = Output::get(&sha256, &sample, &user, &tool, &result_id, &state.shared).await?;
+    // delete this comment from the backend
+    output
+        .delete_comment(&user, &params.groups, &id, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to edit or react to a comment on a files result
+///
+/// # Arguments
+///
+/// * `user` - The user that is updating the comment
+/// * `sha256` - The sha256 of the sample this result is for
+/// * `tool` - The tool this result is from
+/// * `result_id` - The id of the result the comment is on
+/// * `id` - The id of the comment to update
+/// * `state` - Shared Thorium objects
+/// * `update` - The update to apply to the comment
+#[utoipa::path(
+    patch,
+    path = "/api/files/result-comments/:sha256/:tool/:result_id/:id",
+    params(
+        ("sha256" = String, Path, description = "Sha256 of the sample the result is for"),
+        ("tool" = String, Path, description = "The tool the result is from"),
+        ("result_id" = Uuid, Path, description = "Uuid of the result the comment is on"),
+        ("id" = Uuid, Path, description = "Uuid of the comment to update"),
+        ("update" = CommentUpdate, description = "The update to apply to the comment")
+    ),
+    responses(
+        (status = 204, description = "Result comment updated"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::update_result_comment", skip_all, err(Debug))]
+async fn update_result_comment(
+    user: User,
+    Path((sha256, tool, result_id, id)): Path<(String, String, Uuid, Uuid)>,
+    State(state): State<AppState>,
+    Json(update): Json<CommentUpdate>,
+) -> Result<StatusCode, ApiError> {
+    // get the sample and result the comment we are updating is on
+    let sample = Sample::get(&user, &sha256, &state.shared).await?;
+    let output = Output::get(&sha256, &sample, &user, &tool, &result_id, &state.shared).await?;
+    // update this comment in the backend
+    output
+        .update_comment(&user, &id, update, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to download attachments on a files result comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading this comment attachment
+/// * `sha256` - The sha256 of the sample this result is for
+/// * `tool` - The tool this result is from
+/// * `result_id` - The id of the result this comment is on
+/// * `comment` - The comment ID we are downloading an attachment from
+/// * `attachment` - The id of the attachment to download
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/files/result-comment-attachments/:sha256/:tool/:result_id/:comment/:attachment",
+    params(
+        ("sha256" = String, Path, description = "Sha256 of the sample the result is for"),
+        ("tool" = String, Path, description = "The tool the result is from"),
+        ("result_id" = Uuid, Path, description = "Uuid of the result the comment is on"),
+        ("comment" = Uuid, Path, description = "Uuid of of the comment to download an attachment from"),
+        ("attachment" = Uuid, Path, description = "Uuid of the attachment to download")
+    ),
+    responses(
+        (status = 200, description = "Download a result comment attachment", body = Vec<u8>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(
+    name = "routes::files::download_result_comment_attachment",
+    skip_all,
+    err(Debug)
+)]
+async fn download_result_comment_attachment(
+    user: User,
+    Path((sha256, tool, result_id, comment, attachment)): Path<(String, String, Uuid, Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    // get the sample and result we are downloading an attachment from
+    let sample = Sample::get(&user, &sha256, &state.shared).await?;
+    let output = Output::get(&sha256, &sample, &user, &tool, &result_id, &state.shared).await?;
+    // download this attachment
+    let stream = output
+        .download_attachment(&comment, &attachment, &state.shared)
+        .await?;
+    // convert our byte stream to a streamable body
+    let body = AsyncReadBody::new(stream.into_async_read());
+    Ok(body)
+}
+
+/// Start a resumable upload session for a large file
+///
+/// If this user already has a live upload session for this sha256 then that session is returned
+/// so an interrupted upload can be resumed.
+///
+/// # Arguments
+///
+/// * `user` - The user that is starting this upload session
+/// * `state` - Shared Thorium objects
+/// * `req` - The upload session to start
+#[utoipa::path(
+    post,
+    path = "/api/files/uploads/",
+    params(
+        ("req" = UploadSessionRequest, description = "The upload session to start")
+    ),
+    responses(
+        (status = 200, description = "Upload session started or resumed", body = UploadSession),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::create_upload", skip_all, err(Debug))]
+async fn create_upload(
+    user: User,
+    State(state): State<AppState>,
+    Json(req): Json<UploadSessionRequest>,
+) -> Result<Json<UploadSession>, ApiError> {
+    // start or resume this upload session
+    let session = UploadSession::create(&user, req, &state.shared).await?;
+    Ok(Json(session))
+}
+
+/// List the live upload sessions for the current user
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing their upload sessions
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/files/uploads/",
+    params(),
+    responses(
+        (status = 200, description = "This users live upload sessions", body = Vec<UploadSession>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::list_uploads", skip_all, err(Debug))]
+async fn list_uploads(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<UploadSession>>, ApiError> {
+    // list this users upload sessions
+    let sessions = UploadSession::list(&user, &state.shared).await?;
+    Ok(Json(sessions))
+}
+
+/// Get the status of an upload session
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting this upload session
+/// * `id` - The id of the upload session to get
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/files/uploads/:id",
+    params(
+        ("id" = Uuid, Path, description = "The id of the upload session to get")
+    ),
+    responses(
+        (status = 200, description = "The upload session and the chunks it has received", body = UploadSession),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The upload session does not exist or has expired"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::get_upload", skip_all, err(Debug))]
+async fn get_upload(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<UploadSession>, ApiError> {
+    // get this upload session
+    let session = UploadSession::get(&user, &id, &state.shared).await?;
+    Ok(Json(session))
+}
+
+/// Upload a single chunk of a file in an upload session
+///
+/// # Arguments
+///
+/// * `user` - The user that is uploading this chunk
+/// * `id` - The id of the upload session this chunk is for
+/// * `chunk` - The index of the chunk being uploaded (starting at 0)
+/// * `state` - Shared Thorium objects
+/// * `data` - The raw bytes in this chunk
+#[utoipa::path(
+    put,
+    path = "/api/files/uploads/:id/:chunk",
+    params(
+        ("id" = Uuid, Path, description = "The id of the upload session this chunk is for"),
+        ("chunk" = u64, Path, description = "The index of the chunk being uploaded (starting at 0)"),
+    ),
+    request_body(content = Vec<u8>, description = "The raw bytes in this chunk", content_type = "application/octet-stream"),
+    responses(
+        (status = 204, description = "Chunk uploaded"),
+        (status = 400, description = "The chunk was out of bounds or the wrong size"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The upload session does not exist or has expired"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(
+    name = "routes::files::upload_chunk",
+    skip(user, state, data),
+    err(Debug)
+)]
+async fn upload_chunk(
+    user: User,
+    Path((id, chunk)): Path<(Uuid, u64)>,
+    State(state): State<AppState>,
+    data: Bytes,
+) -> Result<StatusCode, ApiError> {
+    // get this upload session
+    let session = UploadSession::get(&user, &id, &state.shared).await?;
+    // save this chunk
+    session.upload_chunk(chunk, data, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Finish an upload session and submit the uploaded file as a sample
+///
+/// # Arguments
+///
+/// * `user` - The user that is finishing this upload session
+/// * `id` - The id of the upload session to finish
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    post,
+    path = "/api/files/uploads/:id/finish",
+    params(
+        ("id" = Uuid, Path, description = "The id of the upload session to finish")
+    ),
+    responses(
+        (status = 200, description = "File uploaded to Thorium", body = SampleSubmissionResponse),
+        (status = 400, description = "Chunks are missing or the uploaded file did not match its sha256"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The upload session does not exist or has expired"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::finish_upload", skip_all, err(Debug))]
+async fn finish_upload(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<SampleSubmissionResponse>, ApiError> {
+    // get this upload session
+    let session = UploadSession::get(&user, &id, &state.shared).await?;
+    // assemble and submit our file
+    let resp = session.finish(&user, &state.shared).await?;
+    Ok(Json(resp))
+}
+
+/// Abort an upload session and delete any chunks that were uploaded
+///
+/// # Arguments
+///
+/// * `user` - The user that is aborting this upload session
+/// * `id` - The id of the upload session to abort
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/files/uploads/:id",
+    params(
+        ("id" = Uuid, Path, description = "The id of the upload session to abort")
+    ),
+    responses(
+        (status = 204, description = "Upload session aborted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The upload session does not exist or has expired"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::files::abort_upload", skip_all, err(Debug))]
+async fn abort_upload(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // get this upload session
+    let session = UploadSession::get(&user, &id, &state.shared).await?;
+    // abort it
+    session.abort(&state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(list, upload, list_details, get_sample, delete_sample, exists, download, download_as_zip, /*download_result_file,*/ update, tag, delete_tags, create_comment, delete_comment, download_attachment, get_results, upload_results),
-    components(schemas(ApiCursor<Sample>, ApiCursor<SampleListLine>, CarvedOrigin, Comment, CommentResponse, DeleteCommentParams, DeleteSampleParams,FileListParams, ImageVersion, Origin, OriginRequest, Output, OutputDisplayType, OutputHandler, OutputMap, OutputResponse, PcapNetworkProtocol, ResultGetParams, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleSubmissionResponse, SubmissionChunk, SubmissionUpdate, TagDeleteRequest<Sample>, TagRequest<Sample>, ZipDownloadParams)),
+    paths(list, upload, list_details, get_sample, delete_sample, exists, download, download_as_zip, /*download_result_file,*/ update, tag, delete_tags, tag_provenance, create_comment, delete_comment, update_comment, download_attachment, get_results, create_result_comment, delete_result_comment, update_result_comment, download_result_comment_attachment, upload_results, create_upload, list_uploads, get_upload, upload_chunk, finish_upload, abort_upload),
+    components(schemas(ApiCursor<Sample>, ApiCursor<SampleListLine>, CarvedOrigin, Comment, CommentReaction, CommentResponse, CommentRevision, CommentUpdate, DeleteCommentParams, DeleteSampleParams,FileListParams, ImageVersion, Origin, OriginRequest, Output, OutputDisplayType, OutputHandler, OutputMap, OutputResponse, PcapNetworkProtocol, ResultGetParams, Sample, SampleCheck, SampleCheckResponse, SampleListLine, SampleSubmissionResponse, SubmissionChunk, SubmissionUpdate, TagDeleteRequest<Sample>, TagProvenance, TagProvenanceParams, TagRequest<Sample>, TagSource, UploadSession, UploadSessionRequest, ZipDownloadParams)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct FileApiDocs;
@@ -702,8 +1185,12 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         )
         .route("/api/files/sample/{sha256}", patch(update))
         .route("/api/files/tags/{sha256}", post(tag).delete(delete_tags))
+        .route("/api/files/tag-provenance/{sha256}", get(tag_provenance))
         .route("/api/files/comment/{sha256}", post(create_comment))
-        .route("/api/files/comment/{sha256}/{id}", delete(delete_comment))
+        .route(
+            "/api/files/comment/{sha256}/{id}",
+            delete(delete_comment).patch(update_comment),
+        )
         .route(
             "/api/files/comment/download/{sha256}/{comment}/{name}",
             get(download_attachment),
@@ -716,4 +1203,23 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/api/files/result-files/{sha256}/{tool}/{result_id}",
             get(download_result_file),
         )
+        .route(
+            "/api/files/result-comments/{sha256}/{tool}/{result_id}",
+            post(create_result_comment),
+        )
+        .route(
+            "/api/files/result-comments/{sha256}/{tool}/{result_id}/{id}",
+            delete(delete_result_comment).patch(update_result_comment),
+        )
+        .route(
+            "/api/files/result-comment-attachments/{sha256}/{tool}/{result_id}/{comment}/{attachment}",
+            get(download_result_comment_attachment),
+        )
+        .route("/api/files/uploads/", get(list_uploads).post(create_upload))
+        .route(
+            "/api/files/uploads/{id}",
+            get(get_upload).delete(abort_upload),
+        )
+        .route("/api/files/uploads/{id}/{chunk}", put(upload_chunk))
+        .route("/api/files/uploads/{id}/finish", post(finish_upload))
 }
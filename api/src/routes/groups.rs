@@ -12,7 +12,8 @@ use crate::is_admin;
 use crate::models::{
     Group, GroupAllowAction, GroupAllowed, GroupAllowedUpdate, GroupDetailsList, GroupList,
     GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsers,
-    GroupUsersRequest, GroupUsersUpdate, PipelineStats, Roles, StageStats, User,
+    GroupUsersRequest, GroupUsersUpdate, PipelineStats, Roles, StageStats, TagKeyRules,
+    TagVocabulary, User,
 };
 use crate::utils::{ApiError, AppState};
 
@@ -286,7 +287,7 @@ async fn get_stats(
 #[derive(OpenApi)]
 #[openapi(
     paths(create, list, get_group, list_details, update, delete_group, sync_ldap, get_stats),
-    components(schemas(Group, GroupAllowed, GroupAllowedUpdate, GroupAllowAction, GroupDetailsList, GroupList, GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsers, GroupUsersUpdate, PipelineStats, Roles, StageStats)),
+    components(schemas(Group, GroupAllowed, GroupAllowedUpdate, GroupAllowAction, GroupDetailsList, GroupList, GroupListParams, GroupMap, GroupRequest, GroupStats, GroupUpdate, GroupUsersRequest, GroupUsers, GroupUsersUpdate, PipelineStats, Roles, StageStats, TagKeyRules, TagVocabulary)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct GroupApiDocs;
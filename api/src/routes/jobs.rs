@@ -1,9 +1,10 @@
-use axum::extract::{Json, Path, Query, State};
+use axum::extract::{Json, Multipart, Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::response::Response;
 use axum::routing::{get, patch, post};
 use axum::Router;
+use axum_extra::body::AsyncReadBody;
 use tracing::instrument;
 use utoipa::OpenApi;
 use uuid::Uuid;
@@ -12,9 +13,9 @@ use super::OpenApiSecurity;
 
 use crate::models::{
     Checkpoint, CommitishKinds, Deadline, GenericJob, GenericJobArgs, GenericJobOpts,
-    HandleJobResponse, ImageScaler, JobHandleStatus, JobListOpts, JobResetRequestor, JobResets,
-    JobStatus, Pipeline, RawJob, RepoDependency, RunningJob, StageLogLine, StageLogsAdd,
-    SystemComponents, User, WorkerName,
+    HandleJobResponse, ImageScaler, JobCheckpointBlob, JobHandleStatus, JobListOpts, JobProgress,
+    JobProgressUpdate, JobResetRequestor, JobResets, JobStatus, Pipeline, RawJob, RepoDependency,
+    RunningJob, StageLogLine, StageLogsAdd, SystemComponents, User, WorkerName,
 };
 use crate::utils::{ApiError, AppState};
 
@@ -205,6 +206,45 @@ async fn sleep(
     Ok((StatusCode::ACCEPTED, response).into_response())
 }
 
+/// Return a preempted job to the queue
+///
+/// This is used by agents whose workers are evicted to make room for urgent work.
+///
+/// # Arguments
+///
+/// * `user` - The user that is preempting this job
+/// * `id` - The uuid of the job that was preempted
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    post,
+    path = "/api/jobs/handle/:id/preempt",
+    params(
+        ("id" = Uuid, Path, description = "The uuid of the job that was preempted"),
+    ),
+    responses(
+        (status = 202, description = "Returned the specified job to the queue", body = HandleJobResponse),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "The specified job is not running"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::jobs::preempt", skip_all, fields(job = id.to_string()), err(Debug))]
+async fn preempt(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    // get job object
+    let (group, job) = RawJob::get(&user, &id, &state.shared).await?;
+    // return this job to the queue
+    let status = job.preempt(&user, &group, &state.shared).await?;
+    // build response
+    let response = Json(HandleJobResponse { status });
+    Ok((StatusCode::ACCEPTED, response).into_response())
+}
+
 /// Checkpoint generator job
 ///
 /// Only generator jobs should use checkpoints.
@@ -248,6 +288,125 @@ async fn checkpoint(
     Ok((StatusCode::ACCEPTED, response).into_response())
 }
 
+/// Save a checkpoint blob for a running job so it can resume after losing its worker
+///
+/// # Arguments
+///
+/// * `user` - The user that is saving a checkpoint blob for this job
+/// * `id` - The uuid of the job to save a checkpoint blob for
+/// * `state` - Shared Thorium objects
+/// * `multipart` - The multipart form containing the checkpoint blob
+#[utoipa::path(
+    post,
+    path = "/api/jobs/handle/:id/checkpoint/blob",
+    params(
+        ("id" = Uuid, Path, description = "The uuid of the job to save a checkpoint blob for"),
+        ("multipart", description = "The multipart form containing the checkpoint blob"),
+    ),
+    responses(
+        (status = 200, description = "Saved this jobs checkpoint blob", body = JobCheckpointBlob),
+        (status = 400, description = "No checkpoint blob was uploaded"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "This job is not running"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::jobs::upload_checkpoint_blob", skip_all, fields(job = id.to_string()), err(Debug))]
+async fn upload_checkpoint_blob(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<Json<JobCheckpointBlob>, ApiError> {
+    // get job object
+    let (group, job) = RawJob::get(&user, &id, &state.shared).await?;
+    // save this jobs checkpoint blob
+    let blob = job
+        .upload_checkpoint_blob(&user, &group, multipart, &state.shared)
+        .await?;
+    Ok(Json(blob))
+}
+
+/// Download the last checkpoint blob a job saved
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading this jobs checkpoint blob
+/// * `id` - The uuid of the job to download a checkpoint blob for
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/jobs/handle/:id/checkpoint/blob",
+    params(
+        ("id" = Uuid, Path, description = "The uuid of the job to download a checkpoint blob for"),
+    ),
+    responses(
+        (status = 200, description = "Checkpoint blob byte stream", body = Vec<u8>),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "This job has no checkpoint blob"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::jobs::download_checkpoint_blob", skip_all, fields(job = id.to_string()), err(Debug))]
+async fn download_checkpoint_blob(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    // get job object
+    let (group, job) = RawJob::get(&user, &id, &state.shared).await?;
+    // start streaming this jobs checkpoint blob from s3
+    let stream = job
+        .download_checkpoint_blob(&user, &group, &state.shared)
+        .await?;
+    // convert our byte stream to a streamable body
+    let body = AsyncReadBody::new(stream.into_async_read());
+    Ok(body)
+}
+
+/// Report the progress of a running job
+///
+/// # Arguments
+///
+/// * `user` - The user that is reporting progress for this job
+/// * `id` - The uuid of the job to report progress for
+/// * `state` - Shared Thorium objects
+/// * `update` - The progress to report
+#[utoipa::path(
+    post,
+    path = "/api/jobs/handle/:id/progress",
+    params(
+        ("id" = Uuid, Path, description = "The uuid of the job to report progress for"),
+        ("update" = JobProgressUpdate, description = "The progress to report"),
+    ),
+    responses(
+        (status = 200, description = "Saved this jobs progress", body = JobProgress),
+        (status = 400, description = "The reported progress was invalid"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "This job is not running"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::jobs::progress", skip_all, fields(job = id.to_string()), err(Debug))]
+async fn progress(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(update): Json<JobProgressUpdate>,
+) -> Result<Json<JobProgress>, ApiError> {
+    // get job object
+    let (group, job) = RawJob::get(&user, &id, &state.shared).await?;
+    // save this jobs progress
+    let progress = job.progress(&user, &group, update, &state.shared).await?;
+    Ok(Json(progress))
+}
+
 /// Resets jobs in bulk
 ///
 /// # Arguments
@@ -381,8 +540,8 @@ async fn bulk_running(
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(claim, proceed, error, sleep, checkpoint, bulk_reset, read_deadlines, bulk_running),
-    components(schemas(Checkpoint, CommitishKinds, Deadline, GenericJob, GenericJobArgs, GenericJobOpts, HandleJobResponse, ImageScaler, JobHandleStatus, JobListOpts, JobResetRequestor, JobResets, JobHandleStatus, JobStatus, RepoDependency, RunningJob, StageLogLine, StageLogsAdd, SystemComponents)),
+    paths(claim, proceed, error, sleep, preempt, checkpoint, upload_checkpoint_blob, download_checkpoint_blob, progress, bulk_reset, read_deadlines, bulk_running),
+    components(schemas(Checkpoint, CommitishKinds, Deadline, GenericJob, GenericJobArgs, GenericJobOpts, HandleJobResponse, ImageScaler, JobCheckpointBlob, JobHandleStatus, JobListOpts, JobProgress, JobProgressUpdate, JobResetRequestor, JobResets, JobHandleStatus, JobStatus, RepoDependency, RunningJob, StageLogLine, StageLogsAdd, SystemComponents)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct JobApiDocs;
@@ -407,7 +566,13 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/api/jobs/handle/{id}/proceed/{runtime}", post(proceed))
         .route("/api/jobs/handle/{id}/error", post(error))
         .route("/api/jobs/handle/{id}/sleep", post(sleep))
+        .route("/api/jobs/handle/{id}/preempt", post(preempt))
         .route("/api/jobs/handle/{id}/checkpoint", post(checkpoint))
+        .route(
+            "/api/jobs/handle/{id}/checkpoint/blob",
+            get(download_checkpoint_blob).post(upload_checkpoint_blob),
+        )
+        .route("/api/jobs/handle/{id}/progress", post(progress))
         .route("/api/jobs/bulk/reset", post(bulk_reset))
         .route(
             "/api/jobs/deadlines/{scaler}/{start}/{end}",
@@ -17,6 +17,7 @@ mod routes_reexport {
     mod shared;
     pub mod streams;
     pub mod system;
+    pub mod tags;
     pub mod trees;
     pub mod ui;
     pub mod users;
@@ -1,10 +1,10 @@
 use std::collections::HashMap;
 
-use axum::extract::{Json, Path, Query, State};
+use axum::extract::{Json, Multipart, Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::response::Response;
-use axum::routing::{get, post};
+use axum::routing::{delete, get, post};
 use axum::Router;
 use axum_extra::body::AsyncReadBody;
 use tracing::{instrument, span, Level, Span};
@@ -13,8 +13,10 @@ use uuid::Uuid;
 
 use super::OpenApiSecurity;
 use crate::bad;
+use crate::models::backends::CommentSupport;
 use crate::models::{
-    Actions, BulkReactionResponse, CommitishKinds, Group, HandleReactionResponse, ImageScaler,
+    Actions, BulkReactionResponse, CommentResponse, CommentUpdate, CommitishKinds,
+    DeleteCommentParams, Group, HandleReactionResponse, ImageScaler, JobProgress,
     JobResetRequestor, Pipeline, Reaction, ReactionDetailsList, ReactionIdResponse, ReactionList,
     ReactionListParams, ReactionRequest, ReactionStatus, ReactionUpdate, RepoDependency,
     RepoDependencyRequest, StageLogLine, StageLogs, StageLogsAdd, StatusUpdate, SystemComponents,
@@ -987,14 +989,189 @@ async fn download_ephemeral(
     Ok(body)
 }
 
+/// Allow users to comment on a reaction
+///
+/// # Arguments
+///
+/// * `user` - The user that is commenting on this reaction
+/// * `group` - The group this reaction is in
+/// * `id` - The uuid of the reaction to comment on
+/// * `state` - Shared Thorium objects
+/// * `multipart` - The multipart form to parse
+#[utoipa::path(
+    post,
+    path = "/api/reactions/comment/:group/:id",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("id" = Uuid, Path, description = "The uuid of the reaction to comment on"),
+        ("multipart", description = "The multipart form to parse to create comment"),
+    ),
+    responses(
+        (status = 200, description = "Comment creation response", body = CommentResponse),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+async fn create_comment(
+    user: User,
+    Path((group, id)): Path<(String, Uuid)>,
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<Json<CommentResponse>, ApiError> {
+    // start our create comment span
+    let span = span!(Level::INFO, "Create Reaction Comment Route");
+    // get the reaction we are commenting on
+    let (_, reaction) = Reaction::get(&user, &group, &id, &state.shared, &span).await?;
+    // save this comment into the backend
+    let resp = reaction
+        .create_comment(&user, multipart, &state.shared)
+        .await?;
+    Ok(Json(resp))
+}
+
+/// Allow users to delete a reaction's comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting the comment
+/// * `params` - The url query params to use
+/// * `group` - The group this reaction is in
+/// * `id` - The uuid of the reaction to delete a comment from
+/// * `comment` - The id of the comment to delete
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/reactions/comment/:group/:id/:comment",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("id" = Uuid, Path, description = "The uuid of the reaction to delete a comment from"),
+        ("comment" = Uuid, Path, description = "Uuid of the comment to delete"),
+        ("params" = DeleteCommentParams, description = "Groups the comment to be deleted is part of")
+    ),
+    responses(
+        (status = 204, description = "Reaction comment deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+async fn delete_comment(
+    user: User,
+    params: DeleteCommentParams,
+    Path((group, id, comment)): Path<(String, Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // start our delete comment span
+    let span = span!(Level::INFO, "Delete Reaction Comment Route");
+    // get the reaction we are deleting a comment from
+    let (_, reaction) = Reaction::get(&user, &group, &id, &state.shared, &span).await?;
+    // delete this comment from the backend
+    reaction
+        .delete_comment(&user, &params.groups, &comment, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to edit or react to a reaction's comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is updating the comment
+/// * `group` - The group this reaction is in
+/// * `id` - The uuid of the reaction the comment is on
+/// * `comment` - The id of the comment to update
+/// * `state` - Shared Thorium objects
+/// * `update` - The update to apply to the comment
+#[utoipa::path(
+    patch,
+    path = "/api/reactions/comment/:group/:id/:comment",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("id" = Uuid, Path, description = "The uuid of the reaction the comment is on"),
+        ("comment" = Uuid, Path, description = "Uuid of the comment to update"),
+        ("update" = CommentUpdate, description = "The update to apply to the comment")
+    ),
+    responses(
+        (status = 204, description = "Reaction comment updated"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+async fn update_comment(
+    user: User,
+    Path((group, id, comment)): Path<(String, Uuid, Uuid)>,
+    State(state): State<AppState>,
+    Json(update): Json<CommentUpdate>,
+) -> Result<StatusCode, ApiError> {
+    // start our update comment span
+    let span = span!(Level::INFO, "Update Reaction Comment Route");
+    // get the reaction the comment we are updating is on
+    let (_, reaction) = Reaction::get(&user, &group, &id, &state.shared, &span).await?;
+    // update this comment in the backend
+    reaction
+        .update_comment(&user, &comment, update, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to download reaction comment attachments
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading this comment attachment
+/// * `group` - The group this reaction is in
+/// * `id` - The uuid of the reaction this comment is on
+/// * `comment` - The comment ID we are downloading an attachment from
+/// * `attachment` - The id of the attachment to download
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/reactions/comment/download/:group/:id/:comment/:attachment",
+    params(
+        ("group" = String, Path, description = "The group this reaction is in"),
+        ("id" = Uuid, Path, description = "The uuid of the reaction the comment is on"),
+        ("comment" = Uuid, Path, description = "Uuid of of the comment to download an attachment from"),
+        ("attachment" = Uuid, Path, description = "Uuid of the attachment to download")
+    ),
+    responses(
+        (status = 200, description = "Comment attachment byte stream", body = Vec<u8>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+async fn download_attachment(
+    user: User,
+    Path((group, id, comment, attachment)): Path<(String, Uuid, Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    // start our download attachment span
+    let span = span!(Level::INFO, "Download Reaction Comment Attachment Route");
+    // get the reaction we are downloading an attachment from
+    let (_, reaction) = Reaction::get(&user, &group, &id, &state.shared, &span).await?;
+    // download this attachment
+    let stream = reaction
+        .download_attachment(&comment, &attachment, &state.shared)
+        .await?;
+    // convert our byte stream to a streamable body
+    let body = AsyncReadBody::new(stream.into_async_read());
+    Ok(body)
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
     paths(create, create_bulk, get_reaction, update, delete_reaction, handle, logs, stage_logs, add_stage_logs,
           list, list_details, list_status, list_status_details, list_tag, list_tag_details, list_group_set,
           list_group_set_details, list_sub, list_sub_details, list_sub_status_details, list_sub_status,
-          download_ephemeral),
-    components(schemas(Actions, BulkReactionResponse, CommitishKinds, HandleReactionResponse, ImageScaler, JobResetRequestor, Reaction, ReactionIdResponse, ReactionList, ReactionDetailsList, ReactionListParams, ReactionRequest, ReactionStatus, ReactionUpdate, RepoDependency, RepoDependencyRequest, StageLogs, StageLogsAdd, StageLogLine, StatusUpdate, SystemComponents)),
+          download_ephemeral, create_comment, delete_comment, update_comment, download_attachment),
+    components(schemas(Actions, BulkReactionResponse, CommentResponse, CommentUpdate, CommitishKinds, DeleteCommentParams, HandleReactionResponse, ImageScaler, JobProgress, JobResetRequestor, Reaction, ReactionIdResponse, ReactionList, ReactionDetailsList, ReactionListParams, ReactionRequest, ReactionStatus, ReactionUpdate, RepoDependency, RepoDependencyRequest, StageLogs, StageLogsAdd, StageLogLine, StatusUpdate, SystemComponents)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct ReactionApiDocs;
@@ -1068,4 +1245,13 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/api/reactions/ephemeral/{group}/{id}/{name}",
             get(download_ephemeral),
         )
+        .route("/api/reactions/comment/{group}/{id}", post(create_comment))
+        .route(
+            "/api/reactions/comment/{group}/{id}/{comment}",
+            delete(delete_comment).patch(update_comment),
+        )
+        .route(
+            "/api/reactions/comment/download/{group}/{id}/{comment}/{attachment}",
+            get(download_attachment),
+        )
 }
@@ -4,10 +4,11 @@ use axum::Router;
 use axum::extract::{Json, Multipart, Path, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::{get, post};
+use axum::routing::{get, patch, post};
 use axum_extra::body::AsyncReadBody;
 use tracing::instrument;
 use utoipa::OpenApi;
+use uuid::Uuid;
 
 /* TODO_UTOIPA: many routes in this file depend on path wildcards (e.g.
    /repos/data/\*repo_path), but Utoipa (and maybe OpenAPI?) does not
@@ -27,16 +28,18 @@ use utoipa::OpenApi;
 */
 
 use super::OpenApiSecurity;
-use crate::models::backends::TagSupport;
+use crate::models::backends::{CommentSupport, TagSupport};
 use crate::models::{
-    ApiCursor, Branch, BranchDetails, BranchRequest, Commit, CommitDetails, CommitRequest,
-    Commitish, CommitishDetails, CommitishKinds, CommitishListParams, CommitishMapRequest,
-    CommitishRequest, GitTag, GitTagDetails, GitTagRequest, Output, OutputFormBuilder, OutputKind,
-    OutputMap, OutputResponse, Repo, RepoCheckout, RepoCreateResponse, RepoDataUploadResponse,
-    RepoDownloadOpts, RepoListLine, RepoListParams, RepoRequest, RepoScheme, RepoSubmissionChunk,
-    ResultFileDownloadParams, ResultGetParams, TagDeleteRequest, TagRequest, User,
+    ApiCursor, Branch, BranchDetails, BranchRequest, CommentResponse, CommentUpdate, Commit,
+    CommitDetails, CommitRequest, Commitish, CommitishDetails, CommitishKinds, CommitishListParams,
+    CommitishMapRequest, CommitishRequest, DeleteCommentParams, GitTag, GitTagDetails,
+    GitTagRequest, Output, OutputFormBuilder, OutputKind, OutputMap, OutputResponse, Repo,
+    RepoCheckout, RepoCreateResponse, RepoDataUploadResponse, RepoDownloadOpts, RepoListLine,
+    RepoListParams, RepoMirror, RepoMirrorClaimOpts, RepoMirrorCredentials, RepoMirrorFetch,
+    RepoMirrorRequest, RepoRequest, RepoScheme, RepoSubmissionChunk, ResultFileDownloadParams,
+    ResultGetParams, TagDeleteRequest, TagProvenance, TagProvenanceParams, TagRequest, User,
 };
-use crate::utils::{ApiError, AppState, bounder};
+use crate::utils::{ApiError, AppState, Shared, bounder};
 
 /// Allow users to add a repo to Thorium
 ///
@@ -256,6 +259,47 @@ async fn delete_tags(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Gets where the tags on a repo came from
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting tag provenance
+/// * `repo_path` - The repo to get tag provenance for
+/// * `params` - The params to filter tag provenance with
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     get,
+//     path = "/api/repos/tag-provenance/*repo_path",
+//     params(
+//         ("path" = Vec<String>, Path, description = "The path containing this urls args"),
+//         ("params" = TagProvenanceParams, description = "Query params to filter tag provenance with"),
+//     ),
+//     responses(
+//         (status = 200, description = "Where this repo's tags came from", body = Vec<TagProvenance>),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::tag_provenance", skip_all, err(Debug))]
+async fn tag_provenance(
+    user: User,
+    Path(repo_path): Path<String>,
+    mut params: TagProvenanceParams,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<TagProvenance>>, ApiError> {
+    // get the repo we are getting tag provenance for
+    let repo = Repo::get(&user, &repo_path, &state.shared).await?;
+    // make sure we can see this repo in the requested groups or default to all of them
+    repo.validate_groups(&user, &mut params.groups, false, &state.shared)
+        .await?;
+    // get where this repo's tags came from
+    let provenance = TagProvenance::list::<Repo>(&repo.url, &params, &state.shared).await?;
+    Ok(Json(provenance))
+}
+
 /// List the commitshes for a repo
 ///
 /// # Arguments
@@ -447,6 +491,233 @@ async fn list_details(
     Ok(Json(cursor))
 }
 
+/// Start mirroring a remote repo in Thorium
+///
+/// # Arguments
+///
+/// * `user` - The user that is creating this mirror
+/// * `state` - Shared Thorium objects
+/// * `req` - The mirror to create
+#[utoipa::path(
+    post,
+    path = "/api/repos/mirrors/",
+    params(
+        ("req" = RepoMirrorRequest, description = "The remote repo to start mirroring"),
+    ),
+    responses(
+        (status = 200, description = "Repo mirror created", body = RepoMirror),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "This repo is already being mirrored"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::repos::create_mirror", skip_all, err(Debug))]
+async fn create_mirror(
+    user: User,
+    State(state): State<AppState>,
+    Json(req): Json<RepoMirrorRequest>,
+) -> Result<Json<RepoMirror>, ApiError> {
+    // start mirroring this repo
+    let mirror = RepoMirror::create(&user, req, &state.shared).await?;
+    Ok(Json(mirror))
+}
+
+/// List the repo mirrors a user can see
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing repo mirrors
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/repos/mirrors/",
+    responses(
+        (status = 200, description = "The repo mirrors this user can see", body = Vec<RepoMirror>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::repos::list_mirrors", skip_all, err(Debug))]
+async fn list_mirrors(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RepoMirror>>, ApiError> {
+    // list the mirrors this user can see
+    let mirrors = RepoMirror::list(&user, &state.shared).await?;
+    Ok(Json(mirrors))
+}
+
+/// Claim the repo mirrors that are due to be fetched
+///
+/// # Arguments
+///
+/// * `user` - The user that is claiming repo mirrors
+/// * `params` - The query params to use for this request
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    patch,
+    path = "/api/repos/mirrors/claim/",
+    params(
+        ("params" = RepoMirrorClaimOpts, description = "The query params to use for this request"),
+    ),
+    responses(
+        (status = 200, description = "The claimed repo mirrors and their credentials", body = Vec<RepoMirror>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::repos::claim_mirrors", skip_all, err(Debug))]
+async fn claim_mirrors(
+    user: User,
+    params: RepoMirrorClaimOpts,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RepoMirror>>, ApiError> {
+    // claim any mirrors that are due to be fetched
+    let mirrors = RepoMirror::claim(&user, params.limit, &state.shared).await?;
+    Ok(Json(mirrors))
+}
+
+/// Get a repo mirror
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting this repo mirror
+/// * `repo_path` - The url of the repo whose mirror to get
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     get,
+//     path = "/api/repos/mirror/*repo_path",
+//     params(
+//         ("repo_path" = String, Path, description = "The url of the repo whose mirror to get"),
+//     ),
+//     responses(
+//         (status = 200, description = "The repo mirror", body = RepoMirror),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::get_mirror", skip_all, err(Debug))]
+async fn get_mirror(
+    user: User,
+    Path(repo_path): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<RepoMirror>, ApiError> {
+    // get this repo mirror
+    let mirror = RepoMirror::get(&user, &repo_path, &state.shared).await?;
+    Ok(Json(mirror))
+}
+
+/// Stop mirroring a repo
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting this repo mirror
+/// * `repo_path` - The url of the repo to stop mirroring
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     delete,
+//     path = "/api/repos/mirror/*repo_path",
+//     params(
+//         ("repo_path" = String, Path, description = "The url of the repo to stop mirroring"),
+//     ),
+//     responses(
+//         (status = 204, description = "Repo mirror deleted"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::delete_mirror", skip_all, err(Debug))]
+async fn delete_mirror(
+    user: User,
+    Path(repo_path): Path<String>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // stop mirroring this repo
+    RepoMirror::delete(&user, &repo_path, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Fetch a mirrored repo as soon as possible
+///
+/// # Arguments
+///
+/// * `user` - The user that is requesting this fetch
+/// * `repo_path` - The url of the repo to fetch
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     post,
+//     path = "/api/repos/mirror-sync/*repo_path",
+//     params(
+//         ("repo_path" = String, Path, description = "The url of the repo to fetch"),
+//     ),
+//     responses(
+//         (status = 204, description = "Repo mirror scheduled to be fetched"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::sync_mirror", skip_all, err(Debug))]
+async fn sync_mirror(
+    user: User,
+    Path(repo_path): Path<String>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // schedule this mirror to be fetched now
+    RepoMirror::sync(&user, &repo_path, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Save the outcome of fetching a mirrored repo
+///
+/// # Arguments
+///
+/// * `user` - The user that fetched this repo
+/// * `repo_path` - The url of the repo that was fetched
+/// * `state` - Shared Thorium objects
+/// * `fetch` - The outcome of this fetch
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     post,
+//     path = "/api/repos/mirror-fetched/*repo_path",
+//     params(
+//         ("repo_path" = String, Path, description = "The url of the repo that was fetched"),
+//         ("fetch" = RepoMirrorFetch, description = "The outcome of this fetch"),
+//     ),
+//     responses(
+//         (status = 204, description = "Repo mirror fetch saved"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::mirror_fetched", skip_all, err(Debug))]
+async fn mirror_fetched(
+    user: User,
+    Path(repo_path): Path<String>,
+    State(state): State<AppState>,
+    Json(fetch): Json<RepoMirrorFetch>,
+) -> Result<StatusCode, ApiError> {
+    // save the outcome of this fetch
+    RepoMirror::fetched(&user, &repo_path, fetch, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Allow users to upload results for repos to Thorium
 ///
 /// # Arguments
@@ -590,13 +861,395 @@ async fn download_result_file(
     Err(ApiError::new(StatusCode::NOT_FOUND, None))
 }
 
+/// Pop a uuid off the end of a wildcard path
+///
+/// # Arguments
+///
+/// * `path_split` - The split wildcard path to pop from
+/// * `name` - The name of the uuid we are popping
+fn pop_uuid(path_split: &mut Vec<&str>, name: &str) -> Result<Uuid, ApiError> {
+    match path_split.pop() {
+        Some(raw) => bounder::uuid(raw, name),
+        None => Err(ApiError::new(StatusCode::NOT_FOUND, None)),
+    }
+}
+
+/// Pop a tool and result id off the end of a wildcard path and get that result
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting this result
+/// * `path_split` - The split wildcard path to pop from
+/// * `shared` - Shared Thorium objects
+async fn pop_result(
+    user: &User,
+    mut path_split: Vec<&str>,
+    shared: &Shared,
+) -> Result<Output, ApiError> {
+    // pop our result id and tool
+    let result_id = pop_uuid(&mut path_split, "result id")?;
+    let Some(tool) = path_split.pop() else {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, None));
+    };
+    // build our repo path from what's left
+    let repo_path = itertools::join(path_split.iter(), "/");
+    // get our repo and this result
+    let repo = Repo::get(user, &repo_path, shared).await?;
+    Output::get(&repo_path, &repo, user, tool, &result_id, shared).await
+}
+
+/// Allow users to comment on a repo in Thorium
+///
+/// # Arguments
+///
+/// * `user` - The user that is commenting on this repo
+/// * `repo_path` - The path of the repo to comment on
+/// * `state` - Shared Thorium objects
+/// * `multipart` - The multipart form to parse
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     post,
+//     path = "/api/repos/comment/*repo_path",
+//     params(
+//         ("repo_path" = String, Path, description = "The path of the repo to comment on"),
+//         ("multipart", description = "The multipart form to parse to create comment"),
+//     ),
+//     responses(
+//         (status = 200, description = "Comment creation response", body = CommentResponse),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::create_comment", skip_all, err(Debug))]
+async fn create_comment(
+    user: User,
+    Path(repo_path): Path<String>,
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<Json<CommentResponse>, ApiError> {
+    // get the repo we are commenting on
+    let repo = Repo::get(&user, &repo_path, &state.shared).await?;
+    // save this comment into the backend
+    let resp = repo.create_comment(&user, multipart, &state.shared).await?;
+    Ok(Json(resp))
+}
+
+/// Allow users to delete a repo's comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting the comment
+/// * `params` - The url query params to use
+/// * `path_params` - The repo path followed by the comment id
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     delete,
+//     path = "/api/repos/comment/*repo_path/:id",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the comment id"),
+//         ("params" = DeleteCommentParams, description = "Groups the comment to be deleted is part of")
+//     ),
+//     responses(
+//         (status = 204, description = "Repo comment deleted"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::delete_comment", skip_all, err(Debug))]
+async fn delete_comment(
+    user: User,
+    params: DeleteCommentParams,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // split the path on '/' and pop our comment id
+    let mut path_split: Vec<&str> = path_params.split('/').collect();
+    let id = pop_uuid(&mut path_split, "comment id")?;
+    // build our repo path from what's left
+    let repo_path = itertools::join(path_split.iter(), "/");
+    // get the repo we are deleting the comment from
+    let repo = Repo::get(&user, &repo_path, &state.shared).await?;
+    // delete this comment from the backend
+    repo.delete_comment(&user, &params.groups, &id, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to edit or react to a repo's comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is updating the comment
+/// * `path_params` - The repo path followed by the comment id
+/// * `state` - Shared Thorium objects
+/// * `update` - The update to apply to the comment
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     patch,
+//     path = "/api/repos/comment/*repo_path/:id",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the comment id"),
+//         ("update" = CommentUpdate, description = "The update to apply to the comment")
+//     ),
+//     responses(
+//         (status = 204, description = "Repo comment updated"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::update_comment", skip_all, err(Debug))]
+async fn update_comment(
+    user: User,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+    Json(update): Json<CommentUpdate>,
+) -> Result<StatusCode, ApiError> {
+    // split the path on '/' and pop our comment id
+    let mut path_split: Vec<&str> = path_params.split('/').collect();
+    let id = pop_uuid(&mut path_split, "comment id")?;
+    // build our repo path from what's left
+    let repo_path = itertools::join(path_split.iter(), "/");
+    // get the repo the comment we are updating is on
+    let repo = Repo::get(&user, &repo_path, &state.shared).await?;
+    // update this comment in the backend
+    repo.update_comment(&user, &id, update, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to download repo comment attachments
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading this comment attachment
+/// * `path_params` - The repo path followed by the comment and attachment ids
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     get,
+//     path = "/api/repos/comment-attachments/*repo_path/:comment/:attachment",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the comment and attachment ids"),
+//     ),
+//     responses(
+//         (status = 200, description = "Comment attachment bytestream", body = Vec<u8>),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::download_attachment", skip_all, err(Debug))]
+async fn download_attachment(
+    user: User,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    // split the path on '/' and pop our attachment and comment ids
+    let mut path_split: Vec<&str> = path_params.split('/').collect();
+    let attachment = pop_uuid(&mut path_split, "attachment id")?;
+    let comment = pop_uuid(&mut path_split, "comment id")?;
+    // build our repo path from what's left
+    let repo_path = itertools::join(path_split.iter(), "/");
+    // get the repo we are downloading an attachment from
+    let repo = Repo::get(&user, &repo_path, &state.shared).await?;
+    // download this attachment
+    let stream = repo
+        .download_attachment(&comment, &attachment, &state.shared)
+        .await?;
+    // convert our byte stream to a streamable body
+    let body = AsyncReadBody::new(stream.into_async_read());
+    Ok(body)
+}
+
+/// Allow users to comment on a repo's result in Thorium
+///
+/// # Arguments
+///
+/// * `user` - The user that is commenting on this result
+/// * `path_params` - The repo path followed by the tool and result id
+/// * `state` - Shared Thorium objects
+/// * `multipart` - The multipart form to parse
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     post,
+//     path = "/api/repos/result-comments/*repo_path/:tool/:result_id",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the tool and result id"),
+//         ("multipart", description = "The multipart form to parse to create comment"),
+//     ),
+//     responses(
+//         (status = 200, description = "Comment creation response", body = CommentResponse),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::create_result_comment", skip_all, err(Debug))]
+async fn create_result_comment(
+    user: User,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<Json<CommentResponse>, ApiError> {
+    // get the result we are commenting on
+    let path_split: Vec<&str> = path_params.split('/').collect();
+    let output = pop_result(&user, path_split, &state.shared).await?;
+    // save this comment into the backend
+    let resp = output
+        .create_comment(&user, multipart, &state.shared)
+        .await?;
+    Ok(Json(resp))
+}
+
+/// Allow users to delete a comment on a repo's result
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting the comment
+/// * `params` - The url query params to use
+/// * `path_params` - The repo path followed by the tool, result id, and comment id
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     delete,
+//     path = "/api/repos/result-comments/*repo_path/:tool/:result_id/:id",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the tool, result id, and comment id"),
+//         ("params" = DeleteCommentParams, description = "Groups the comment to be deleted is part of")
+//     ),
+//     responses(
+//         (status = 204, description = "Result comment deleted"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::delete_result_comment", skip_all, err(Debug))]
+async fn delete_result_comment(
+    user: User,
+    params: DeleteCommentParams,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // split the path on '/' and pop our comment id
+    let mut path_split: Vec<&str> = path_params.split('/').collect();
+    let id = pop_uuid(&mut path_split, "comment id")?;
+    // get the result we are deleting a comment from
+    let output = pop_result(&user, path_split, &state.shared).await?;
+    // delete this comment from the backend
+    output
+        .delete_comment(&user, &params.groups, &id, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to edit or react to a comment on a repo's result
+///
+/// # Arguments
+///
+/// * `user` - The user that is updating the comment
+/// * `path_params` - The repo path followed by the tool, result id, and comment id
+/// * `state` - Shared Thorium objects
+/// * `update` - The update to apply to the comment
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     patch,
+//     path = "/api/repos/result-comments/*repo_path/:tool/:result_id/:id",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the tool, result id, and comment id"),
+//         ("update" = CommentUpdate, description = "The update to apply to the comment")
+//     ),
+//     responses(
+//         (status = 204, description = "Result comment updated"),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(name = "routes::repos::update_result_comment", skip_all, err(Debug))]
+async fn update_result_comment(
+    user: User,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+    Json(update): Json<CommentUpdate>,
+) -> Result<StatusCode, ApiError> {
+    // split the path on '/' and pop our comment id
+    let mut path_split: Vec<&str> = path_params.split('/').collect();
+    let id = pop_uuid(&mut path_split, "comment id")?;
+    // get the result the comment we are updating is on
+    let output = pop_result(&user, path_split, &state.shared).await?;
+    // update this comment in the backend
+    output
+        .update_comment(&user, &id, update, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow users to download attachments on a repo's result comment
+///
+/// # Arguments
+///
+/// * `user` - The user that is downloading this comment attachment
+/// * `path_params` - The repo path followed by the tool, result, comment, and attachment ids
+/// * `state` - Shared Thorium objects
+// TODO_UTOIPA: WIDLCARD
+// #[utoipa::path(
+//     get,
+//     path = "/api/repos/result-comment-attachments/*repo_path/:tool/:result_id/:comment/:attachment",
+//     params(
+//         ("path_params" = String, Path, description = "The repo path followed by the tool, result, comment, and attachment ids"),
+//     ),
+//     responses(
+//         (status = 200, description = "Comment attachment bytestream", body = Vec<u8>),
+//         (status = 401, description = "This user is not authorized to access this route"),
+//     ),
+//     security(
+//         ("basic" = []),
+//     )
+// )]
+#[instrument(
+    name = "routes::repos::download_result_comment_attachment",
+    skip_all,
+    err(Debug)
+)]
+async fn download_result_comment_attachment(
+    user: User,
+    Path(path_params): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    // split the path on '/' and pop our attachment and comment ids
+    let mut path_split: Vec<&str> = path_params.split('/').collect();
+    let attachment = pop_uuid(&mut path_split, "attachment id")?;
+    let comment = pop_uuid(&mut path_split, "comment id")?;
+    // get the result we are downloading an attachment from
+    let output = pop_result(&user, path_split, &state.shared).await?;
+    // download this attachment
+    let stream = output
+        .download_attachment(&comment, &attachment, &state.shared)
+        .await?;
+    // convert our byte stream to a streamable body
+    let body = AsyncReadBody::new(stream.into_async_read());
+    Ok(body)
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
     // TODO_UTOIPA: WILDCARD add these back in once all the wildcard issues are resolved
     // paths(list, create, list_details, get_repo, upload, commitshes, update_commitishes, commitsh_details, download, tag, delete_tags, get_results, upload_results, download_result_file, bundle_results),
-    paths(list, create, list_details),
-    components(schemas(ApiCursor<Repo>, ApiCursor<RepoListLine>, Branch, BranchDetails, BranchRequest, Commit, CommitDetails, Commitish, CommitishDetails, CommitishKinds, CommitishMapRequest, CommitishRequest, CommitRequest, GitTag, GitTagDetails, GitTagRequest, OutputMap, OutputResponse, Repo, RepoCheckout, RepoCreateResponse, RepoDownloadOpts, RepoListParams, RepoDataUploadResponse, RepoRequest, RepoScheme, RepoSubmissionChunk, ResultGetParams, TagDeleteRequest<Repo>, TagRequest<Repo>)),
+    paths(list, create, list_details, create_mirror, list_mirrors, claim_mirrors),
+    components(schemas(ApiCursor<Repo>, ApiCursor<RepoListLine>, Branch, BranchDetails, BranchRequest, Commit, CommitDetails, Commitish, CommitishDetails, CommitishKinds, CommitishMapRequest, CommitishRequest, CommitRequest, GitTag, GitTagDetails, GitTagRequest, OutputMap, OutputResponse, Repo, RepoCheckout, RepoCreateResponse, RepoDownloadOpts, RepoListParams, RepoDataUploadResponse, RepoMirror, RepoMirrorClaimOpts, RepoMirrorCredentials, RepoMirrorFetch, RepoMirrorRequest, RepoRequest, RepoScheme, RepoSubmissionChunk, ResultGetParams, TagDeleteRequest<Repo>, TagRequest<Repo>)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct RepoApiDocs;
@@ -616,6 +1269,17 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
     router
         .route("/api/repos/", get(list).post(create))
         .route("/api/repos/details/", get(list_details))
+        .route("/api/repos/mirrors/", get(list_mirrors).post(create_mirror))
+        .route("/api/repos/mirrors/claim/", patch(claim_mirrors))
+        .route(
+            "/api/repos/mirror/{*repo_path}",
+            get(get_mirror).delete(delete_mirror),
+        )
+        .route("/api/repos/mirror-sync/{*repo_path}", post(sync_mirror))
+        .route(
+            "/api/repos/mirror-fetched/{*repo_path}",
+            post(mirror_fetched),
+        )
         .route("/api/repos/data/{*repo_path}", get(get_repo).post(upload))
         .route(
             "/api/repos/commitishes/{data}/{*repo_path}",
@@ -630,6 +1294,10 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/api/repos/tags/{*repo_path}",
             post(tag).delete(delete_tags),
         )
+        .route(
+            "/api/repos/tag-provenance/{*repo_path}",
+            get(tag_provenance),
+        )
         .route(
             "/api/repos/results/{*repo_path}",
             get(get_results).post(upload_results),
@@ -638,4 +1306,24 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
             "/api/repos/result-files/{*repo_path}",
             get(download_result_file),
         )
+        .route(
+            "/api/repos/comment/{*repo_path}",
+            post(create_comment)
+                .delete(delete_comment)
+                .patch(update_comment),
+        )
+        .route(
+            "/api/repos/comment-attachments/{*repo_path}",
+            get(download_attachment),
+        )
+        .route(
+            "/api/repos/result-comments/{*repo_path}",
+            post(create_result_comment)
+                .delete(delete_result_comment)
+                .patch(update_result_comment),
+        )
+        .route(
+            "/api/repos/result-comment-attachments/{*repo_path}",
+            get(download_result_comment_attachment),
+        )
 }
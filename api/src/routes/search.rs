@@ -1,6 +1,7 @@
 //! The search routes for Thorium
 
-use axum::extract::{Json, State};
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
 use axum::routing::get;
 use axum::Router;
 use tracing::instrument;
@@ -10,7 +11,7 @@ use super::OpenApiSecurity;
 use crate::models::backends;
 use crate::models::elastic::ElasticIndex;
 use crate::models::ElasticSearchParams;
-use crate::models::{ApiCursor, ElasticDoc, User};
+use crate::models::{ApiCursor, ElasticDoc, SavedSearch, SavedSearchRequest, User};
 use crate::utils::{ApiError, AppState};
 
 pub mod events;
@@ -47,11 +48,139 @@ async fn search(
     Ok(Json(cursor))
 }
 
+/// Save a search so it can be run again later
+///
+/// # Arguments
+///
+/// * `user` - The user that is saving this search
+/// * `state` - Shared Thorium objects
+/// * `req` - The search to save
+#[utoipa::path(
+    post,
+    path = "/api/search/saved/",
+    params(
+        ("req" = SavedSearchRequest, description = "The search to save"),
+    ),
+    responses(
+        (status = 200, description = "Search saved", body = SavedSearch),
+        (status = 400, description = "The saved search name or groups are invalid"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "A saved search with this name already exists"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::search::create_saved", skip_all, err(Debug))]
+async fn create_saved(
+    user: User,
+    State(state): State<AppState>,
+    Json(req): Json<SavedSearchRequest>,
+) -> Result<Json<SavedSearch>, ApiError> {
+    // save this search
+    let saved = SavedSearch::create(&user, req, &state.shared).await?;
+    Ok(Json(saved))
+}
+
+/// List the searches this user has saved
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing their saved searches
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/search/saved/",
+    params(),
+    responses(
+        (status = 200, description = "This user's saved searches sorted by name", body = Vec<SavedSearch>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::search::list_saved", skip_all, err(Debug))]
+async fn list_saved(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SavedSearch>>, ApiError> {
+    // list this users saved searches
+    let saved = SavedSearch::list(&user, &state.shared).await?;
+    Ok(Json(saved))
+}
+
+/// Get a search this user has saved
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting a saved search
+/// * `name` - The name of the saved search to get
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/search/saved/:name",
+    params(
+        ("name" = String, Path, description = "The name of the saved search to get"),
+    ),
+    responses(
+        (status = 200, description = "The saved search", body = SavedSearch),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "This saved search does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::search::get_saved", skip(user, state), err(Debug))]
+async fn get_saved(
+    user: User,
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<SavedSearch>, ApiError> {
+    // get this saved search
+    let saved = SavedSearch::get(&user, &name, &state.shared).await?;
+    Ok(Json(saved))
+}
+
+/// Delete a search this user has saved
+///
+/// # Arguments
+///
+/// * `user` - The user that is deleting a saved search
+/// * `name` - The name of the saved search to delete
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/search/saved/:name",
+    params(
+        ("name" = String, Path, description = "The name of the saved search to delete"),
+    ),
+    responses(
+        (status = 204, description = "Saved search deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "This saved search does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::search::delete_saved", skip(user, state), err(Debug))]
+async fn delete_saved(
+    user: User,
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // delete this saved search
+    SavedSearch::delete(&user, &name, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(search),
-    components(schemas(ApiCursor<ElasticDoc>, ElasticDoc, ElasticIndex, ElasticSearchParams)),
+    paths(search, create_saved, list_saved, get_saved, delete_saved),
+    components(schemas(ApiCursor<ElasticDoc>, ElasticDoc, ElasticIndex, ElasticSearchParams, SavedSearch, SavedSearchRequest)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct SearchApiDocs;
@@ -64,7 +193,13 @@ async fn openapi() -> Json<utoipa::openapi::OpenApi> {
 
 // * `router` - The router to add routes too
 pub fn mount(router: Router<AppState>) -> Router<AppState> {
-    let router = router.route("/api/search/", get(search));
+    let router = router
+        .route("/api/search/", get(search))
+        .route("/api/search/saved/", get(list_saved).post(create_saved))
+        .route(
+            "/api/search/saved/{name}",
+            get(get_saved).delete(delete_saved),
+        );
     // mount search events routes
     events::mount(router)
 }
@@ -10,7 +10,7 @@ use crate::models::images::{GenericBan, InvalidHostPathBan, InvalidUrlBan};
 use crate::models::pipelines::BannedImageBan;
 use crate::models::{
     ActiveJob, ApiCursor, ArgStrategy, AutoTag, AutoTagLogic, Backup, ChildFilters,
-    ChildFiltersUpdate, ChildrenDependencySettings, Cleanup, ConfigMap, Dependencies,
+    ChildFiltersUpdate, ChildrenDependencySettings, Cleanup, ConfigMap, CpuArch, Dependencies,
     DependencyPassStrategy, EphemeralDependencySettings, EventTrigger, FilesHandler, Group,
     GroupAllowed, GroupStats, GroupUsers, HostPath, HostPathTypes, HostPathWhitelistUpdate, Image,
     ImageArgs, ImageBan, ImageBanKind, ImageBanUpdate, ImageLifetime, ImageScaler, ImageVersion,
@@ -18,11 +18,11 @@ use crate::models::{
     NodeRegistration, NodeUpdate, OutputCollection, OutputDisplayType, OutputHandler, Pipeline,
     PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineStats, Pools, Reaction,
     RepoDependencySettings, Resources, ResultDependencySettings, SampleDependencySettings,
-    ScalerStats, Secret, SecurityContext, SpawnLimits, StageStats, SystemInfo, SystemInfoParams,
-    SystemSettings, SystemSettingsResetParams, SystemSettingsUpdate, SystemSettingsUpdateParams,
-    SystemStats, TagDependencySettings, TagType, Theme, UnixInfo, User, UserRole, UserSettings,
-    Volume, VolumeTypes, Worker, WorkerDelete, WorkerDeleteMap, WorkerRegistration,
-    WorkerRegistrationList, WorkerStatus, WorkerUpdate, NFS,
+    ScalerStats, Secret, SecurityContext, SpawnLimits, StageStats, StreamHandler, SystemInfo,
+    SystemInfoParams, SystemSettings, SystemSettingsResetParams, SystemSettingsUpdate,
+    SystemSettingsUpdateParams, SystemStats, TagDependencySettings, TagType, Theme, UnixInfo,
+    UploadSession, User, UserRole, UserSettings, Volume, VolumeTypes, Worker, WorkerDelete,
+    WorkerDeleteMap, WorkerRegistration, WorkerRegistrationList, WorkerStatus, WorkerUpdate, NFS,
 };
 use crate::utils::{ApiError, AppState};
 
@@ -333,6 +333,8 @@ async fn consistency_scan(
 async fn cleanup(user: User, State(state): State<AppState>) -> Result<StatusCode, ApiError> {
     // clean up any expired reactions from status lists
     Reaction::expire_lists(&user, &state.shared).await?;
+    // clean up any resumable uploads that have expired
+    UploadSession::expire(&user, &state.shared).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -749,7 +751,7 @@ async fn delete_workers(
 #[derive(OpenApi)]
 #[openapi(
     paths(init, info, stats, settings, settings_update, consistency_scan, settings_reset, cleanup, reset_cache, backup, restore, register_node, list_nodes, list_node_details, get_node, update_node, register_worker, delete_workers, get_worker, update_worker),
-    components(schemas(ActiveJob, ApiCursor<NodeListLine>, ArgStrategy, AutoTag, AutoTagLogic, Backup, BannedImageBan, ChildFilters, ChildFiltersUpdate, ChildrenDependencySettings, Cleanup, ConfigMap, Dependencies, DependencyPassStrategy, EphemeralDependencySettings, EventTrigger, FilesHandler, GenericBan, Group, GroupAllowed, GroupStats, GroupUsers, HostPath, HostPathTypes, HostPathWhitelistUpdate, Image, ImageArgs, ImageBan, ImageBanKind, ImageBanUpdate, ImageLifetime, ImageScaler, ImageVersion, InvalidHostPathBan, InvalidUrlBan, Kvm, KwargDependency, NFS, Node, NodeGetParams, NodeHealth, NodeListLine, NodeListParams, NodeRegistration, NodeUpdate, OutputCollection, OutputDisplayType, OutputHandler, Pipeline, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineStats, Pools, RepoDependencySettings, Resources, ResultDependencySettings, SampleDependencySettings, ScalerStats, Secret, SecurityContext, SpawnLimits, StageStats, SystemInfo, SystemInfoParams, SystemSettings, SystemSettingsUpdate, SystemSettingsResetParams, SystemSettingsUpdateParams, SystemStats, TagDependencySettings, TagType, Theme, UnixInfo, User, UserRole, UserSettings, Volume, VolumeTypes, Worker, WorkerDeleteMap, WorkerDelete, WorkerRegistration, WorkerRegistrationList, WorkerStatus, WorkerUpdate)),
+    components(schemas(ActiveJob, ApiCursor<NodeListLine>, ArgStrategy, AutoTag, AutoTagLogic, Backup, BannedImageBan, ChildFilters, ChildFiltersUpdate, ChildrenDependencySettings, Cleanup, ConfigMap, CpuArch, Dependencies, DependencyPassStrategy, EphemeralDependencySettings, EventTrigger, FilesHandler, GenericBan, Group, GroupAllowed, GroupStats, GroupUsers, HostPath, HostPathTypes, HostPathWhitelistUpdate, Image, ImageArgs, ImageBan, ImageBanKind, ImageBanUpdate, ImageLifetime, ImageScaler, ImageVersion, InvalidHostPathBan, InvalidUrlBan, Kvm, KwargDependency, NFS, Node, NodeGetParams, NodeHealth, NodeListLine, NodeListParams, NodeRegistration, NodeUpdate, OutputCollection, OutputDisplayType, OutputHandler, Pipeline, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineStats, Pools, RepoDependencySettings, Resources, ResultDependencySettings, SampleDependencySettings, ScalerStats, Secret, SecurityContext, SpawnLimits, StageStats, StreamHandler, SystemInfo, SystemInfoParams, SystemSettings, SystemSettingsUpdate, SystemSettingsResetParams, SystemSettingsUpdateParams, SystemStats, TagDependencySettings, TagType, Theme, UnixInfo, User, UserRole, UserSettings, Volume, VolumeTypes, Worker, WorkerDeleteMap, WorkerDelete, WorkerRegistration, WorkerRegistrationList, WorkerStatus, WorkerUpdate)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct SystemApiDocs;
@@ -0,0 +1,166 @@
+//! The routes supporting bulk tag operations in Thorium
+
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use tracing::instrument;
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use super::OpenApiSecurity;
+use crate::models::{BulkTagJob, BulkTagOperation, BulkTagRequest, BulkTagStatus, TagType, User};
+use crate::utils::{ApiError, AppState};
+
+/// Start a new bulk tag job
+///
+/// # Arguments
+///
+/// * `user` - The user that is starting this bulk tag job
+/// * `state` - Shared Thorium objects
+/// * `req` - The bulk tag operation to run
+#[utoipa::path(
+    post,
+    path = "/api/tags/bulk/",
+    params(
+        ("req" = BulkTagRequest, description = "The bulk tag operation to run")
+    ),
+    responses(
+        (status = 200, description = "The bulk tag job that was started", body = BulkTagJob),
+        (status = 400, description = "The bulk tag request was invalid"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::tags::create_bulk", skip_all, err(Debug))]
+async fn create_bulk(
+    user: User,
+    State(state): State<AppState>,
+    Json(req): Json<BulkTagRequest>,
+) -> Result<Json<BulkTagJob>, ApiError> {
+    // start this bulk tag job in the background
+    let job = BulkTagJob::create(&user, req, &state.shared).await?;
+    Ok(Json(job))
+}
+
+/// List bulk tag jobs
+///
+/// # Arguments
+///
+/// * `user` - The user that is listing bulk tag jobs
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/tags/bulk/",
+    responses(
+        (status = 200, description = "Bulk tag jobs from newest to oldest", body = Vec<BulkTagJob>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::tags::list_bulk", skip_all, err(Debug))]
+async fn list_bulk(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<BulkTagJob>>, ApiError> {
+    // list the bulk tag jobs we can see
+    let jobs = BulkTagJob::list(&user, &state.shared).await?;
+    Ok(Json(jobs))
+}
+
+/// Get the status of a bulk tag job
+///
+/// # Arguments
+///
+/// * `user` - The user that is getting this bulk tag job
+/// * `id` - The id of the bulk tag job to get
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/tags/bulk/:id",
+    params(
+        ("id" = Uuid, Path, description = "The id of the bulk tag job to get")
+    ),
+    responses(
+        (status = 200, description = "The requested bulk tag job", body = BulkTagJob),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The bulk tag job does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::tags::get_bulk", skip_all, err(Debug))]
+async fn get_bulk(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<BulkTagJob>, ApiError> {
+    // get this bulk tag job
+    let job = BulkTagJob::get(&user, &id, &state.shared).await?;
+    Ok(Json(job))
+}
+
+/// Cancel a bulk tag job
+///
+/// # Arguments
+///
+/// * `user` - The user that is cancelling this bulk tag job
+/// * `id` - The id of the bulk tag job to cancel
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/tags/bulk/:id",
+    params(
+        ("id" = Uuid, Path, description = "The id of the bulk tag job to cancel")
+    ),
+    responses(
+        (status = 204, description = "The bulk tag job will be cancelled"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The bulk tag job does not exist"),
+        (status = 409, description = "The bulk tag job is already finished"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::tags::cancel_bulk", skip_all, err(Debug))]
+async fn cancel_bulk(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // flag this bulk tag job to be cancelled
+    BulkTagJob::cancel(&user, &id, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The struct containing our openapi docs
+#[derive(OpenApi)]
+#[openapi(
+    paths(create_bulk, list_bulk, get_bulk, cancel_bulk),
+    components(schemas(BulkTagJob, BulkTagOperation, BulkTagRequest, BulkTagStatus, TagType)),
+    modifiers(&OpenApiSecurity),
+)]
+pub struct TagApiDocs;
+
+/// Return the openapi docs for these routes
+#[allow(dead_code)]
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(TagApiDocs::openapi())
+}
+
+/// Add the tags routes to our router
+///
+/// # Arguments
+///
+/// * `router` - The router to add routes too
+pub fn mount(router: Router<AppState>) -> Router<AppState> {
+    router
+        .route("/api/tags/bulk/", get(list_bulk).post(create_bulk))
+        .route("/api/tags/bulk/{id}", get(get_bulk).delete(cancel_bulk))
+}
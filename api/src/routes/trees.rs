@@ -62,12 +62,14 @@ async fn grow_tree(
     let mut tree = Tree::load(&user, &cursor, &state.shared).await?;
     // set our growable nodes
     tree.growable = query.growable;
+    // keep the nodes we are growing so we can keep their links when trimming
+    let grown = tree.growable.clone();
     // grow this tree
     let added = tree.grow(&user, &params, &state.shared).await?;
     // save the latest info on this tree
     tree.save(&user, &state.shared).await?;
     // trim to only the new info for this tree
-    tree.trim(added);
+    tree.trim(&grown, added);
     // empty our sent vec
     tree.sent.clear();
     Ok(Json(tree))
@@ -6,13 +6,16 @@ use axum::routing::{delete, get, post};
 use axum_extra::TypedHeader;
 use tracing::instrument;
 use utoipa::OpenApi;
+use uuid::Uuid;
 
-use super::OpenApiSecurity;
+use super::{OpenApiSecurity, shared};
 
 // our imports
+use crate::models::backends::NotificationSupport;
 use crate::models::{
-    AuthResponse, Key, ScrubbedUser, Theme, UnixInfo, User, UserCreate, UserRole, UserSettings,
-    UserSettingsUpdate, UserUpdate,
+    AuthResponse, Key, Notification, NotificationLevel, ScrubbedUser, Subscription,
+    SubscriptionEvent, SubscriptionRequest, SubscriptionResponse, SubscriptionTarget, Theme,
+    UnixInfo, User, UserCreate, UserRole, UserSettings, UserSettingsUpdate, UserUpdate,
 };
 use crate::utils::{ApiError, AppState};
 use crate::{is_admin, unauthorized, unavailable};
@@ -461,11 +464,208 @@ async fn sync_ldap(user: User, State(state): State<AppState>) -> Result<StatusCo
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Gets all of the notifications in the current users inbox
+///
+/// # Arguments
+///
+/// * `user` - The user whose notifications are being requested
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/users/notifications/",
+    params(
+        ("user" = User, description = "The user whose notifications are being requested"),
+    ),
+    responses(
+        //(status = 200, description = "Notifications in this users inbox", body = Vec<Notification<User>>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::users::get_notifications", skip_all, err(Debug))]
+async fn get_notifications(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Notification<User>>>, ApiError> {
+    // users are notified by their username
+    let key = user.username.clone();
+    // get all of the notifications in this users inbox
+    shared::notifications::get_notifications(user, key, &state.shared).await
+}
+
+/// Deletes a specific notification from the current users inbox
+///
+/// # Arguments
+///
+/// * `user` - The user whose notification is being deleted
+/// * `id` - The notification's unique ID
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/users/notifications/:id",
+    params(
+        ("user" = User, description = "The user whose notification is being deleted"),
+        ("id" = Uuid, Path, description = "The notification's unique ID"),
+    ),
+    responses(
+        (status = 204, description = "Notification deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The notification does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::users::delete_notification", skip_all, err(Debug))]
+async fn delete_notification(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // users are notified by their username
+    let key = user.username.clone();
+    // delete the notification
+    shared::notifications::delete_notification(user, key, None, id, &state.shared).await
+}
+
+/// Deletes all notifications in the current users inbox
+///
+/// # Arguments
+///
+/// * `user` - The user whose notifications are being cleared
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/users/notifications/",
+    params(
+        ("user" = User, description = "The user whose notifications are being cleared"),
+    ),
+    responses(
+        (status = 204, description = "All notifications deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::users::clear_notifications", skip_all, err(Debug))]
+async fn clear_notifications(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // delete all of the notifications in this users inbox
+    user.delete_all_notifications(&user.username, &state.shared)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Subscribes the current user to something in Thorium
+///
+/// # Arguments
+///
+/// * `user` - The user that is subscribing
+/// * `state` - Shared Thorium objects
+/// * `req` - The subscription request
+#[utoipa::path(
+    post,
+    path = "/api/users/notifications/subscriptions/",
+    params(
+        ("user" = User, description = "The user that is subscribing"),
+        ("req" = SubscriptionRequest, description = "The subscription request"),
+    ),
+    responses(
+        (status = 200, description = "Subscription created", body = SubscriptionResponse),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 409, description = "This user is already subscribed to this target"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::users::subscribe", skip_all, err(Debug))]
+async fn subscribe(
+    user: User,
+    State(state): State<AppState>,
+    Json(req): Json<SubscriptionRequest>,
+) -> Result<Json<SubscriptionResponse>, ApiError> {
+    // subscribe this user
+    let subscription = Subscription::create(&user, req, &state.shared).await?;
+    Ok(Json(SubscriptionResponse {
+        id: subscription.id,
+    }))
+}
+
+/// Lists the current users subscriptions
+///
+/// # Arguments
+///
+/// * `user` - The user whose subscriptions are being listed
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    get,
+    path = "/api/users/notifications/subscriptions/",
+    params(
+        ("user" = User, description = "The user whose subscriptions are being listed"),
+    ),
+    responses(
+        (status = 200, description = "This users subscriptions", body = Vec<Subscription>),
+        (status = 401, description = "This user is not authorized to access this route"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::users::list_subscriptions", skip_all, err(Debug))]
+async fn list_subscriptions(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Subscription>>, ApiError> {
+    // list this users subscriptions
+    let subscriptions = Subscription::list(&user, &state.shared).await?;
+    Ok(Json(subscriptions))
+}
+
+/// Deletes one of the current users subscriptions
+///
+/// # Arguments
+///
+/// * `user` - The user whose subscription is being deleted
+/// * `id` - The id of the subscription to delete
+/// * `state` - Shared Thorium objects
+#[utoipa::path(
+    delete,
+    path = "/api/users/notifications/subscriptions/:id",
+    params(
+        ("user" = User, description = "The user whose subscription is being deleted"),
+        ("id" = Uuid, Path, description = "The id of the subscription to delete"),
+    ),
+    responses(
+        (status = 204, description = "Subscription deleted"),
+        (status = 401, description = "This user is not authorized to access this route"),
+        (status = 404, description = "The subscription does not exist"),
+    ),
+    security(
+        ("basic" = []),
+    )
+)]
+#[instrument(name = "routes::users::unsubscribe", skip_all, err(Debug))]
+async fn unsubscribe(
+    user: User,
+    Path(id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, ApiError> {
+    // delete this subscription
+    Subscription::delete(&user, &id, &state.shared).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// The struct containing our openapi docs
 #[derive(OpenApi)]
 #[openapi(
-    paths(list, create, update, resend_email_verification, verify_email, list_details, auth, get_user, update_user, info, logout, logout_user, delete_user, sync_ldap),
-    components(schemas(AuthResponse, ScrubbedUser, Theme, UnixInfo, User, UserCreate, UserRole, UserSettings, UserSettingsUpdate, UserUpdate)),
+    paths(list, create, update, resend_email_verification, verify_email, list_details, auth, get_user, update_user, info, logout, logout_user, delete_user, sync_ldap, get_notifications, delete_notification, clear_notifications, subscribe, list_subscriptions, unsubscribe),
+    components(schemas(AuthResponse, NotificationLevel, ScrubbedUser, Subscription, SubscriptionEvent, SubscriptionRequest, SubscriptionResponse, SubscriptionTarget, Theme, UnixInfo, User, UserCreate, UserRole, UserSettings, UserSettingsUpdate, UserUpdate)),
     modifiers(&OpenApiSecurity),
 )]
 pub struct UserApiDocs;
@@ -503,4 +703,17 @@ pub fn mount(router: Router<AppState>) -> Router<AppState> {
         .route("/api/users/logout/{target}", get(logout_user))
         .route("/api/users/delete/{target}", delete(delete_user))
         .route("/api/users/sync/ldap", post(sync_ldap))
+        .route(
+            "/api/users/notifications/",
+            get(get_notifications).delete(clear_notifications),
+        )
+        .route("/api/users/notifications/{id}", delete(delete_notification))
+        .route(
+            "/api/users/notifications/subscriptions/",
+            get(list_subscriptions).post(subscribe),
+        )
+        .route(
+            "/api/users/notifications/subscriptions/{id}",
+            delete(unsubscribe),
+        )
 }
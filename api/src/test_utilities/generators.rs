@@ -1063,6 +1063,8 @@ pub fn gen_network_policy_rule(groups: &[String]) -> NetworkPolicyRuleRaw {
         allowed_local: rand::rngs::SmallRng::from_os_rng().random_bool(0.5),
         allowed_internet: rand::rngs::SmallRng::from_os_rng().random_bool(0.5),
         allowed_all: rand::rngs::SmallRng::from_os_rng().random_bool(0.5),
+        // the simulated internet conflicts with internet/all access so leave it disabled
+        simulated_internet: false,
         ports,
         allowed_custom,
     }
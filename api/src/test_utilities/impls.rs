@@ -24,6 +24,7 @@ impl PartialEq<Group> for GroupRequest {
         same!(group.users, self.users);
         same!(group.monitors, self.monitors);
         same!(group.description, self.description);
+        same!(group.tag_vocabulary, self.tag_vocabulary);
         true
     }
 }
@@ -51,6 +52,9 @@ impl PartialEq<Image> for ImageRequest {
         matches_update!(image.security_context, self.security_context);
         same!(image.collect_logs, self.collect_logs);
         same!(image.generator, self.generator);
+        same!(image.capture_network, self.capture_network);
+        same!(image.arch, self.arch);
+        same!(image.emulation, self.emulation);
         same!(image.dependencies, self.dependencies);
         same!(image.display_type, self.display_type);
         same!(image.output_collection, self.output_collection);
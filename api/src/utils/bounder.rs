@@ -420,6 +420,17 @@ pub fn triggers(triggers: &HashMap<String, EventTrigger>) -> Result<(), ApiError
                     return bad!(format!("tag triggers must have tag types set: {}", name));
                 }
             }
+            EventTrigger::NewCommitish { branches, paths } => {
+                // make sure all of our branch and path patterns are valid regexes
+                for pattern in branches.iter().chain(paths.iter()) {
+                    if let Err(err) = Regex::new(pattern) {
+                        return bad!(format!(
+                            "commitish trigger {} has an invalid pattern {}: {}",
+                            name, pattern, err
+                        ));
+                    }
+                }
+            }
         }
     }
     Ok(())
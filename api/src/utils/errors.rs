@@ -415,6 +415,12 @@ impl From<SdkError<aws_sdk_s3::operation::delete_object::DeleteObjectError>> for
     }
 }
 
+impl From<aws_sdk_s3::primitives::ByteStreamError> for ApiError {
+    fn from(error: aws_sdk_s3::primitives::ByteStreamError) -> Self {
+        bad_internal!(format!("Failed to stream object from s3 {:#?}", error))
+    }
+}
+
 impl From<tokio::task::JoinError> for ApiError {
     fn from(error: tokio::task::JoinError) -> Self {
         bad_internal!(format!("Tokio task failed to join: {:#?}", error))
@@ -201,6 +201,25 @@ impl S3Client {
         }
     }
 
+    /// Get the size of a file in s3 in bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to get the size of
+    #[instrument(name = "S3Client::size", skip(self), err(Debug))]
+    pub async fn size(&self, path: &str) -> Result<u64, ApiError> {
+        // head this path to get its size
+        let head = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .send()
+            .await?;
+        // s3 should never give us a negative size
+        Ok(head.content_length().unwrap_or_default().max(0) as u64)
+    }
+
     /// Stream a file into s3 while hashing and carting it
     ///
     /// # Arguments
@@ -353,6 +372,217 @@ impl S3Client {
         }
     }
 
+    /// Helps stream an existing byte stream into s3 while hashing and carting it
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to write this object to in s3
+    /// * `upload_id` - The id of the multipart upload being used
+    /// * `stream` - The byte stream to cart and write to s3
+    #[instrument(
+        name = "S3Client::hash_cart_and_stream_bytes_helper",
+        skip(self, stream),
+        err(Debug)
+    )]
+    async fn hash_cart_and_stream_bytes_helper(
+        &self,
+        path: &str,
+        upload_id: &str,
+        mut stream: ByteStream,
+    ) -> Result<StandardHashes, ApiError> {
+        // init our cart streamer and hashers
+        let mut cart = CartStreamManual::new(&self.password, 7_242_880)?;
+        let mut hashers = StandardHashers::default();
+        // track what part number we are on
+        let mut part_num = 1;
+        // keep a list of parts we have uploaded
+        let mut parts = Vec::with_capacity(10);
+        // stream this data through our hashers, cart, and to s3
+        while let Some(raw) = stream.try_next().await? {
+            // pass this chunk through our hashers
+            hashers.digest(&raw);
+            // add this buffer to our cart streamer
+            if cart.next_bytes(raw)? {
+                // keep processing these bytes until they are finished
+                while cart.process()? {
+                    // if our input buffer is full then pack
+                    if cart.ready() >= 5_242_880 {
+                        // get the bytes we are ready to write to s3
+                        let writable = cart.carted_bytes();
+                        // write this buffer to s3
+                        let e_tag = self
+                            .upload_part(path, upload_id, part_num, writable)
+                            .await?;
+                        // add this chunk to our parts list
+                        parts.push(
+                            CompletedPart::builder()
+                                .e_tag(e_tag)
+                                .part_number(part_num)
+                                .build(),
+                        );
+                        // consume the bytes we have written to s3
+                        cart.consume();
+                        // increment our part number
+                        part_num += 1;
+                    }
+                }
+            }
+        }
+        // finish carting our file
+        let writable = cart.finish()?;
+        // write this final buffer to s3
+        let e_tag = self
+            .upload_part(path, upload_id, part_num, writable)
+            .await?;
+        // add this chunk to our parts list
+        parts.push(
+            CompletedPart::builder()
+                .e_tag(e_tag)
+                .part_number(part_num)
+                .build(),
+        );
+        // finish this multipart upload
+        self.complete_multipart(path, upload_id, parts).await?;
+        Ok(hashers.finish())
+    }
+
+    /// Stream an existing byte stream into s3 while hashing and carting it
+    ///
+    /// # Arguments
+    ///
+    /// * `s3_id` - The id to use for this object in s3
+    /// * `stream` - The byte stream to cart and write to s3
+    #[instrument(
+        name = "S3Client::hash_cart_and_stream_bytes",
+        skip(self, stream),
+        err(Debug)
+    )]
+    pub async fn hash_cart_and_stream_bytes(
+        &self,
+        s3_id: &Uuid,
+        stream: ByteStream,
+    ) -> Result<StandardHashes, ApiError> {
+        // build the path to write this file too
+        let path = s3_id.to_string();
+        // initiate a multipart upload to s3
+        let upload_id = self.create_multipart(&path).await?;
+        // cart and stream this data to s3
+        match self
+            .hash_cart_and_stream_bytes_helper(&path, &upload_id, stream)
+            .await
+        {
+            Ok(hashes) => Ok(hashes),
+            Err(err) => {
+                // abort this multipart upload
+                self.abort_multipart(&path, &upload_id).await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Start a new multipart upload in s3
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to write this object to in s3
+    #[instrument(name = "S3Client::create_multipart", skip(self), err(Debug))]
+    pub async fn create_multipart(&self, path: &str) -> Result<String, ApiError> {
+        // initiate a multipart upload to s3
+        let init = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(path)
+            .content_type("application/octet-stream")
+            .send()
+            .await?;
+        // get our upload id
+        match init.upload_id() {
+            Some(upload_id) => Ok(upload_id.to_owned()),
+            None => unavailable!("Failed to get multipart upload ID".to_owned()),
+        }
+    }
+
+    /// Upload a single part of a multipart upload to s3
+    ///
+    /// Every part but the last must be at least 5 mebibytes in size.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the object being uploaded in s3
+    /// * `upload_id` - The id of the multipart upload being used
+    /// * `part_num` - The part number to upload (starting at 1)
+    /// * `data` - The data to write for this part
+    #[instrument(name = "S3Client::upload_part", skip(self, data), err(Debug))]
+    pub async fn upload_part<B: Into<SdkBody>>(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_num: i32,
+        data: B,
+    ) -> Result<String, ApiError> {
+        // write this part to s3
+        let part = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(path)
+            .upload_id(upload_id)
+            .body(ByteStream::from(data.into()))
+            .part_number(part_num)
+            .send()
+            .await?;
+        Ok(part.e_tag.unwrap_or_default())
+    }
+
+    /// Complete a multipart upload in s3
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the object being uploaded in s3
+    /// * `upload_id` - The id of the multipart upload to complete
+    /// * `parts` - The parts that were uploaded in order
+    #[instrument(name = "S3Client::complete_multipart", skip(self, parts), err(Debug))]
+    pub async fn complete_multipart(
+        &self,
+        path: &str,
+        upload_id: &str,
+        parts: Vec<CompletedPart>,
+    ) -> Result<(), ApiError> {
+        // build our complete multipart upload object
+        let completed_parts = CompletedMultipartUpload::builder()
+            .set_parts(Some(parts))
+            .build();
+        // finish this multipart upload
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(path)
+            .multipart_upload(completed_parts)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Abort a multipart upload in s3 and drop any parts that were uploaded
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the object being uploaded in s3
+    /// * `upload_id` - The id of the multipart upload to abort
+    #[instrument(name = "S3Client::abort_multipart", skip(self), err(Debug))]
+    pub async fn abort_multipart(&self, path: &str, upload_id: &str) -> Result<(), ApiError> {
+        self.client
+            .abort_multipart_upload()
+            .bucket(&self.bucket)
+            .key(path)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        Ok(())
+    }
+
     /// Helps stream a file into s3 while sha256 and carting it
     ///
     /// # Arguments
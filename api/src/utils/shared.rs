@@ -11,9 +11,9 @@ use std::sync::Arc;
 use tokio::fs;
 
 use super::s3::S3;
-use crate::info;
 use crate::models::backends::setup::{self, Scylla};
 use crate::utils::ApiError;
+use crate::{bad, info, internal_err};
 use crate::{conf::Conf, error};
 
 /// Tries to execute a future 10 times with a custom timeout
@@ -56,6 +56,7 @@ macro_rules! retry {
 }
 
 /// A client for sending emails from Thorium
+#[derive(Clone)]
 pub struct EmailClient {
     /// The address to send emails from
     from: Mailbox,
@@ -108,6 +109,12 @@ impl EmailClient {
     }
 
     /// Send an email
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to send this email to
+    /// * `subject` - The subject of this email
+    /// * `body` - The body of this email
     pub async fn send<S: Into<String>, B: IntoBody>(
         &self,
         addr: &str,
@@ -115,17 +122,25 @@ impl EmailClient {
         body: B,
     ) -> Result<(), ApiError> {
         // try to parse the email address we are sending email too
-        let to = addr.parse().unwrap();
+        let to = match addr.parse() {
+            Ok(to) => to,
+            Err(err) => return bad!(format!("Invalid email address {addr}: {err}")),
+        };
         // build the email to send
-        let email = lettre::Message::builder()
+        let email = match lettre::Message::builder()
             .from(self.from.clone())
             .to(to)
             .subject(subject)
             .header(ContentType::TEXT_PLAIN)
             .body(body)
-            .unwrap();
+        {
+            Ok(email) => email,
+            Err(err) => return internal_err!(format!("Failed to build email: {err}")),
+        };
         // send our email
-        self.client.send(email).await.unwrap();
+        if let Err(err) = self.client.send(email).await {
+            return internal_err!(format!("Failed to send email: {err}"));
+        }
         Ok(())
     }
 }
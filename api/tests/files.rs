@@ -15,9 +15,12 @@ use thorium::{
 use uuid::Uuid;
 
 use thorium::models::{
-    Buffer, CommentRequest, DeleteCommentParams, FileDeleteOpts, FileDownloadOpts, FileListOpts,
-    GroupUpdate, GroupUsersUpdate, ImageVersion, OnDiskFile, OriginRequest, OutputDisplayType,
-    OutputRequest, ResultGetParams, SampleRequest, SubmissionUpdate, TagDeleteRequest, TagRequest,
+    Buffer, BulkTagRequest, BulkTagStatus, CommentReaction, CommentRequest, CommentUpdate,
+    DeleteCommentParams, FileDeleteOpts, FileDownloadOpts, FileListOpts, GroupUpdate,
+    GroupUsersUpdate, ImageVersion, OnDiskFile, OriginRequest, OutputDisplayType, OutputRequest,
+    Resources, ResultGetParams, SampleRequest, SubmissionUpdate, TagDeleteRequest, TagKeyRules,
+    TagProvenanceParams, TagProvenanceRequest, TagRequest, TagSource, TagVocabulary,
+    UploadSessionRequest,
 };
 
 #[tokio::test]
@@ -39,6 +42,124 @@ async fn create() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+/// Build a random buffer that spans multiple upload chunks and get its sha256
+fn chunked_buffer() -> (Vec<u8>, String) {
+    // build an 11 mebibyte buffer from a random seed
+    let seed = Uuid::new_v4();
+    let data = seed.as_bytes().repeat(720_896);
+    // get the sha256 of this buffer
+    let sha256 = HEXLOWER.encode(&Sha256::digest(&data));
+    (data, sha256)
+}
+
+#[tokio::test]
+async fn chunked_upload() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // build a buffer to upload in chunks
+    let (data, sha256) = chunked_buffer();
+    // start an upload session
+    let session_req = UploadSessionRequest::new(&sha256, data.len() as u64, vec![&group])
+        .file_name("chunked")
+        .tag("chunked", "yes");
+    let session = client.files.create_upload(&session_req).await?;
+    is!(session.chunks, 3);
+    is!(session.missing(), vec![0, 1, 2]);
+    // upload our chunks out of order
+    for chunk in [2, 0] {
+        let range = session.chunk_range(chunk).unwrap();
+        let buff = data[range.start as usize..range.end as usize].to_vec();
+        client.files.upload_chunk(&session.id, chunk, buff).await?;
+    }
+    // starting the same upload again should resume our existing session
+    let resumed = client.files.create_upload(&session_req).await?;
+    is!(resumed.id, session.id);
+    is!(resumed.missing(), vec![1]);
+    // resuming this session with different tags or groups should fail
+    let retagged = session_req.clone().tag("chunked", "no");
+    fail!(client.files.create_upload(&retagged).await, 409);
+    let other = generators::groups(1, &client).await?.remove(0).name;
+    let regrouped = UploadSessionRequest::new(&sha256, data.len() as u64, vec![&group, &other])
+        .file_name("chunked")
+        .tag("chunked", "yes");
+    fail!(client.files.create_upload(&regrouped).await, 409);
+    // finishing before all chunks are uploaded should fail
+    fail!(client.files.finish_upload(&session.id).await, 400);
+    // upload our last chunk and finish this upload
+    let range = session.chunk_range(1).unwrap();
+    let buff = data[range.start as usize..range.end as usize].to_vec();
+    client.files.upload_chunk(&session.id, 1, buff).await?;
+    let resp = client.files.finish_upload(&session.id).await?;
+    is!(resp.sha256, sha256);
+    // make sure our sample has the right metadata
+    let sample = client.files.get(&sha256).await?;
+    has_tag!(sample.tags, "chunked", "yes");
+    // our session should be cleaned up now that its finished
+    fail!(client.files.get_upload(&session.id).await, 404);
+    Ok(())
+}
+
+#[tokio::test]
+async fn chunked_upload_invalid() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // build a buffer to upload in chunks
+    let (data, _) = chunked_buffer();
+    // start an upload session with the wrong sha256
+    let (_, wrong) = chunked_buffer();
+    let session_req = UploadSessionRequest::new(&wrong, data.len() as u64, vec![group]);
+    let session = client.files.create_upload(&session_req).await?;
+    // chunks that are out of bounds or the wrong size should be rejected
+    fail!(
+        client.files.upload_chunk(&session.id, 3, vec![0; 10]).await,
+        400
+    );
+    fail!(
+        client.files.upload_chunk(&session.id, 0, vec![0; 10]).await,
+        400
+    );
+    // upload all of our chunks
+    for chunk in session.missing() {
+        let range = session.chunk_range(chunk).unwrap();
+        let buff = data[range.start as usize..range.end as usize].to_vec();
+        client.files.upload_chunk(&session.id, chunk, buff).await?;
+    }
+    // finishing should fail since the sha256 does not match
+    fail!(client.files.finish_upload(&session.id).await, 400);
+    fail!(client.files.get(&wrong).await, 404);
+    Ok(())
+}
+
+#[tokio::test]
+async fn chunked_upload_abort() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // build a buffer to upload in chunks
+    let (data, sha256) = chunked_buffer();
+    // start an upload session and upload a single chunk
+    let session_req = UploadSessionRequest::new(&sha256, data.len() as u64, vec![group]);
+    let session = client.files.create_upload(&session_req).await?;
+    let range = session.chunk_range(0).unwrap();
+    let buff = data[range.start as usize..range.end as usize].to_vec();
+    client.files.upload_chunk(&session.id, 0, buff).await?;
+    // make sure this session is listed
+    let sessions = client.files.list_uploads().await?;
+    is_in!(
+        sessions.iter().map(|s| s.id).collect::<Vec<Uuid>>(),
+        session.id
+    );
+    // abort this session
+    client.files.abort_upload(&session.id).await?;
+    fail!(client.files.get_upload(&session.id).await, 404);
+    Ok(())
+}
+
 #[tokio::test]
 async fn download() -> Result<(), thorium::Error> {
     // get admin client
@@ -256,6 +377,235 @@ async fn tag() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn tag_vocabulary() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // give this group a tag vocabulary
+    let vocabulary = TagVocabulary::default()
+        .key(
+            "family",
+            TagKeyRules::default()
+                .value("Emotet")
+                .value("Qakbot")
+                .required(),
+        )
+        .key("cve", TagKeyRules::default().regex("CVE-[0-9]{4}-[0-9]+"))
+        .alias("malware_family", "family");
+    let update = GroupUpdate::default().tag_vocabulary(vocabulary);
+    client.groups.update(&group, &update).await?;
+    // uploads without the required tags should fail
+    let file_req = SampleRequest::new_buffer(Buffer::new("vocab"), vec![&group])
+        .description("vocab")
+        .tag("plants", "corn");
+    fail!(client.files.create(file_req.clone()).await, 400);
+    // uploads with an alias of a required tag should be rewritten and succeed
+    let hashes = client
+        .files
+        .create(file_req.tag("malware_family", "emotet"))
+        .await?;
+    let sample = client.files.get(&hashes.sha256).await?;
+    has_tag!(sample.tags, "family", "Emotet");
+    no_tag!(sample.tags, "malware_family");
+    // values that are not allowed should be rejected
+    let tag_req = TagRequest::default().group(&group).add("family", "Corn");
+    fail!(client.files.tag(&hashes.sha256, &tag_req).await, 400);
+    // values that don't match the regex should be rejected
+    let tag_req = TagRequest::default().group(&group).add("cve", "CVE-corn");
+    fail!(client.files.tag(&hashes.sha256, &tag_req).await, 400);
+    let tag_req = TagRequest::default()
+        .group(&group)
+        .add("cve", "CVE-2024-1234");
+    client.files.tag(&hashes.sha256, &tag_req).await?;
+    let sample = client.files.get(&hashes.sha256).await?;
+    has_tag!(sample.tags, "cve", "CVE-2024-1234");
+    // bulk jobs whose replacement is rewritten back to the old tag should be rejected
+    let req = BulkTagRequest::replace_value("family", "Emotet", "emotet").group(&group);
+    fail!(client.tags.bulk(&req).await, 400);
+    let req = BulkTagRequest::rename_key("family", "malware_family").group(&group);
+    fail!(client.tags.bulk(&req).await, 400);
+    // bulk jobs whose replacement is not allowed should be rejected
+    let req = BulkTagRequest::replace_value("family", "Emotet", "Dridex").group(&group);
+    fail!(client.tags.bulk(&req).await, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn bulk_tags() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // upload some files with the same tag
+    let mut sha256s = Vec::with_capacity(3);
+    for i in 0..3 {
+        let file_req = SampleRequest::new_buffer(Buffer::new(format!("bulk{i}")), vec![&group])
+            .description("bulk")
+            .tag("plants", "corn");
+        sha256s.push(client.files.create(file_req).await?.sha256);
+    }
+    // count how many files would be modified without changing anything
+    let req = BulkTagRequest::replace_value("plants", "corn", "Corn")
+        .group(&group)
+        .dry_run();
+    let mut job = client.tags.bulk(&req).await?;
+    while !job.is_finished() {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        job = client.tags.get_bulk(&job.id).await?;
+    }
+    is!(job.status, BulkTagStatus::Completed);
+    is!(job.matched, 3);
+    is!(job.modified, 0);
+    let sample = client.files.get(&sha256s[0]).await?;
+    has_tag!(sample.tags, "plants", "corn");
+    // replace this tag on all of our files
+    let req = BulkTagRequest::replace_value("plants", "corn", "Corn").group(&group);
+    let mut job = client.tags.bulk(&req).await?;
+    while !job.is_finished() {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        job = client.tags.get_bulk(&job.id).await?;
+    }
+    is!(job.status, BulkTagStatus::Completed);
+    is!(job.matched, 3);
+    is!(job.modified, 3);
+    for sha256 in &sha256s {
+        let sample = client.files.get(sha256).await?;
+        has_tag!(sample.tags, "plants", "Corn");
+        no_tag!(sample.tags, "plants", "corn");
+    }
+    // make sure this job is listed
+    let jobs = client.tags.list_bulk().await?;
+    is!(jobs.iter().any(|listed| listed.id == job.id), true);
+    // bulk jobs without groups should be rejected
+    let req = BulkTagRequest::delete("plants", "Corn");
+    fail!(client.tags.bulk(&req).await, 400);
+    Ok(())
+}
+
+#[tokio::test]
+async fn tag_provenance() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // claim a job so we can set tags as a tool
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    let req = generators::gen_reaction(&group, &pipe, None);
+    client.reactions.create(&req).await?;
+    let stage = &pipe.order[0][0];
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    generators::worker(
+        "cluster0",
+        "node0",
+        "provenance",
+        &group,
+        &pipe.name,
+        stage,
+        &client,
+    )
+    .await?;
+    let job = client
+        .jobs
+        .claim(
+            &group,
+            &pipe.name,
+            stage,
+            "cluster0",
+            "node0",
+            "provenance",
+            1,
+        )
+        .await?
+        .remove(0);
+    // upload a file
+    let file_req =
+        SampleRequest::new_buffer(Buffer::new("provenance"), vec![&group]).description("prov");
+    let sha256 = client.files.create(file_req).await?.sha256;
+    // add a tag directly as a user
+    let tag_req = TagRequest::default().add("plants", "corn");
+    client.files.tag(&sha256, &tag_req).await?;
+    // add some tags as a tool with different confidences
+    let result = Uuid::new_v4();
+    let provenance = TagProvenanceRequest::default()
+        .job(job.id)
+        .result(result)
+        .confidence(0.4);
+    let tag_req = TagRequest::default()
+        .add("family", "Qakbot")
+        .provenance(provenance.clone());
+    client.files.tag(&sha256, &tag_req).await?;
+    let tag_req = TagRequest::default()
+        .add("family", "Emotet")
+        .provenance(provenance.confidence(0.9));
+    client.files.tag(&sha256, &tag_req).await?;
+    // get all of this files tag provenance
+    let params = TagProvenanceParams::default();
+    let provs = client.files.tag_provenance(&sha256, &params).await?;
+    is!(provs.len(), 3);
+    let corn = provs.iter().find(|prov| prov.value == "corn").unwrap();
+    is!(corn.source, TagSource::User);
+    is!(corn.tool, None);
+    let qakbot = provs.iter().find(|prov| prov.value == "Qakbot").unwrap();
+    is!(qakbot.source, TagSource::Tool);
+    is!(qakbot.tool.as_ref(), Some(stage));
+    is!(qakbot.result, Some(result));
+    is!(qakbot.confidence, Some(0.4));
+    // only get analyst set tags
+    let params = TagProvenanceParams::default().source(TagSource::User);
+    let provs = client.files.tag_provenance(&sha256, &params).await?;
+    is!(provs.len(), 1);
+    is!(provs[0].value, "corn");
+    // only get confident tool tags
+    let params = TagProvenanceParams::default()
+        .source(TagSource::Tool)
+        .min_confidence(0.5);
+    let provs = client.files.tag_provenance(&sha256, &params).await?;
+    is!(provs.len(), 1);
+    is!(provs[0].value, "Emotet");
+    // confirm a tool tag as a user
+    let tag_req = TagRequest::default().add("family", "Qakbot");
+    client.files.tag(&sha256, &tag_req).await?;
+    let params = TagProvenanceParams::default().source(TagSource::User);
+    let provs = client.files.tag_provenance(&sha256, &params).await?;
+    is!(provs.len(), 2);
+    // a tool setting a confirmed tag again should not overwrite the analysts provenance
+    let tag_req = TagRequest::default()
+        .add("family", "Qakbot")
+        .provenance(TagProvenanceRequest::default().job(job.id));
+    client.files.tag(&sha256, &tag_req).await?;
+    let provs = client.files.tag_provenance(&sha256, &params).await?;
+    is!(provs.len(), 2);
+    let provs = client
+        .files
+        .tag_provenance(&sha256, &TagProvenanceParams::default())
+        .await?;
+    let qakbots = provs.iter().filter(|prov| prov.value == "Qakbot").count();
+    is!(qakbots, 2);
+    // deleting a tag should delete its provenance
+    let tag_del = TagDeleteRequest::default().add("plants", "corn");
+    client.files.delete_tags(&sha256, &tag_del).await?;
+    let provs = client.files.tag_provenance(&sha256, &params).await?;
+    is!(provs.len(), 1);
+    // confidence must be between 0 and 1
+    let tag_req = TagRequest::default()
+        .add("family", "Dridex")
+        .provenance(TagProvenanceRequest::default().job(job.id).confidence(1.5));
+    fail!(client.files.tag(&sha256, &tag_req).await, 400);
+    // tags can only be attributed to our own running jobs
+    let tag_req = TagRequest::default()
+        .add("family", "Dridex")
+        .provenance(TagProvenanceRequest::default().job(Uuid::new_v4()));
+    fail!(client.files.tag(&sha256, &tag_req).await, 404);
+    // delete our worker
+    generators::delete_worker("provenance", &client).await?;
+    Ok(())
+}
+
 #[tokio::test]
 async fn delete_tag() -> Result<(), thorium::Error> {
     // get admin client
@@ -707,6 +1057,62 @@ async fn delete_comment_fail() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn threaded_comment() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // build a sample request
+    let file_req = SampleRequest::new_buffer(Buffer::new("not_a_comment5"), vec![group])
+        .description("also not a comment5")
+        .origin(OriginRequest::downloaded(
+            "https://google.com",
+            Some("google".to_string()),
+        ));
+    // upload this file
+    let hashes = client.files.create(file_req.clone()).await?;
+    // comment on this file and then reply to that comment
+    let root = CommentRequest::new(&hashes.sha256, "I am a comment");
+    let root = client.files.comment(root).await?;
+    let reply = CommentRequest::new(&hashes.sha256, "I am a reply").parent(root.id);
+    let reply = client.files.comment(reply).await?;
+    // edit our reply and acknowledge the original comment
+    let edit = CommentUpdate::default().comment("I am an edited reply");
+    client
+        .files
+        .update_comment(&hashes.sha256, &reply.id, &edit)
+        .await?;
+    let react = CommentUpdate::default().add_reaction(CommentReaction::Acknowledge);
+    client
+        .files
+        .update_comment(&hashes.sha256, &root.id, &react)
+        .await?;
+    // get the file we just commented on
+    let sample = client.files.get(&hashes.sha256).await?;
+    is!(sample.comments.len(), 2);
+    let username = client.users.info().await?.username;
+    for comment in &sample.comments {
+        if comment.id == reply.id {
+            // make sure our reply is threaded and its original text was retained
+            is!(comment.parent, Some(root.id));
+            is!(comment.comment, "I am an edited reply");
+            is!(comment.edited.is_some(), true);
+            is!(comment.revisions.len(), 1);
+            is!(comment.revisions[0].comment, "I am a reply");
+        } else {
+            // make sure our acknowledgement was saved
+            is!(comment.parent, None);
+            is!(comment.edited, None);
+            is_in!(comment.reactions[&CommentReaction::Acknowledge], username);
+        }
+    }
+    // replies to comments that do not exist should fail
+    let orphan = CommentRequest::new(&hashes.sha256, "I am an orphan").parent(Uuid::new_v4());
+    fail!(client.files.comment(orphan).await, 404);
+    Ok(())
+}
+
 /// Tests that attachments are pruned when comments are deleted from all groups
 #[tokio::test]
 async fn comment_attachment_prune() -> Result<(), thorium::Error> {
@@ -6,14 +6,14 @@ use std::path::PathBuf;
 use futures::{ stream, stream, stream, stream};
 use thorium::models::{
     ArgStrategy, AutoTagLogic, AutoTagUpdate, ChildFilters, ChildFiltersUpdate, CleanupUpdate,
-    DependenciesUpdate, DependencyPassStrategy, DependencySettingsUpdate,
+    CpuArch, DependenciesUpdate, DependencyPassStrategy, DependencySettingsUpdate,
     EphemeralDependencySettingsUpdate, FilesHandlerUpdate, GroupUpdate, GroupUsersUpdate,
-    HostPathWhitelistUpdate, ImageBan, ImageBanKind, ImageBanUpdate, ImageLifetime,
-    ImageNetworkPolicyUpdate, ImageScaler, ImageUpdate, ImageVersion, NetworkPolicyRequest,
-    NotificationLevel, NotificationParams, NotificationRequest, OutputCollectionUpdate,
-    OutputDisplayType, OutputHandler, PipelineRequest, ResourcesUpdate,
-    ResultDependencySettingsUpdate, SystemSettingsResetParams, SystemSettingsUpdate,
-    SystemSettingsUpdateParams, Volume, VolumeTypes,
+    HostPathWhitelistUpdate, Hypervisor, ImageBan, ImageBanKind, ImageBanUpdate, ImageLifetime,
+    ImageNetworkPolicyUpdate, ImageScaler, ImageUpdate, ImageVersion, Kvm, MicroVm,
+    NetworkPolicyRequest, NotificationLevel, NotificationParams, NotificationRequest,
+    OutputCollectionUpdate, OutputDisplayType, OutputHandler, PipelineRequest, ResourcesUpdate,
+    ResultDependencySettingsUpdate, StreamHandlerUpdate, SystemSettingsResetParams,
+    SystemSettingsUpdate, SystemSettingsUpdateParams, Volume, VolumeTypes,
 };
 use thorium::test_utilities::{self, generators};
 use thorium::{Error, Error, Error, Error, contains, fail, is, is_in, unwra};
@@ -96,6 +96,165 @@ async fn create_bad_child_filter() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn create_microvm() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // microVM settings require the kvm scaler
+    let microvm = MicroVm::new("/vms/vmlinux", "/vms/rootfs.ext4");
+    let image_req = generators::gen_image(&group).microvm(microvm.clone());
+    let resp = client.images.create(&image_req).await;
+    fail!(resp, 400, "MicroVM settings require the Kvm scaler");
+    // create an image that runs in a cloud-hypervisor microVM
+    let image_req = generators::gen_image(&group)
+        .scaler(ImageScaler::Kvm)
+        .lifetime(ImageLifetime::jobs(1))
+        .microvm(microvm.hypervisor(Hypervisor::CloudHypervisor));
+    client.images.create(&image_req).await?;
+    let image = client.images.get(&group, &image_req.name).await?;
+    is!(image, image_req);
+    // microVM settings can't be kept when moving to another scaler
+    let update = ImageUpdate::default().scaler(ImageScaler::K8s);
+    let resp = client.images.update(&group, &image.name, &update).await;
+    fail!(resp, 400, "MicroVM settings require the Kvm scaler");
+    // clear our microVM settings
+    let update = ImageUpdate::default().clear_microvm();
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated, update);
+    is!(updated.microvm, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_kvm() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // kvm images can only execute one job per vm
+    let kvm = Kvm::new("/vms/win10.xml", "/vms/win10.qcow2");
+    let image_req = generators::gen_image(&group)
+        .scaler(ImageScaler::Kvm)
+        .kvm(kvm.clone());
+    let resp = client.images.create(&image_req).await;
+    fail!(resp, 400, "Kvm images must have a lifetime of one job");
+    // create a kvm image that restores a saved memory state and dumps memory
+    let image_req = generators::gen_image(&group)
+        .scaler(ImageScaler::Kvm)
+        .lifetime(ImageLifetime::jobs(1))
+        .kvm(kvm.memory_state("/vms/win10.state").dump_memory());
+    client.images.create(&image_req).await?;
+    let image = client.images.get(&group, &image_req.name).await?;
+    is!(image, image_req);
+    is!(image.kvm, image_req.kvm);
+    // clear our saved memory state
+    let mut update = ImageUpdate::default();
+    update.kvm.clear_memory_state = true;
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    let kvm = updated
+        .kvm
+        .ok_or_else(|| Error::new("Kvm settings were cleared"))?;
+    is!(kvm.memory_state, None);
+    is!(kvm.dump_memory, true);
+    // clearing the lifetime of a kvm image still leaves it at one job
+    let update = ImageUpdate::default().clear_lifetime();
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated.lifetime, Some(ImageLifetime::jobs(1)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_capture_network() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // windows workers can't capture network traffic
+    let image_req = generators::gen_image(&group)
+        .scaler(ImageScaler::Windows)
+        .capture_network();
+    let resp = client.images.create(&image_req).await;
+    fail!(
+        resp,
+        400,
+        "Network capture is not supported for the Windows scaler"
+    );
+    // create an image that captures its network traffic in k8s
+    let image_req = generators::gen_image(&group).capture_network();
+    client.images.create(&image_req).await?;
+    let image = client.images.get(&group, &image_req.name).await?;
+    is!(image, image_req);
+    is!(image.capture_network, true);
+    // network capture can't be kept when moving to windows
+    let update = ImageUpdate::default().scaler(ImageScaler::Windows);
+    let resp = client.images.update(&group, &image.name, &update).await;
+    fail!(
+        resp,
+        400,
+        "Network capture is not supported for the Windows scaler"
+    );
+    // bare metal workers capture network traffic in their own network namespace
+    let update = ImageUpdate::default().scaler(ImageScaler::BareMetal);
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated.capture_network, true);
+    // stop capturing network traffic
+    let update = ImageUpdate::default().disable_capture_network();
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated, update);
+    is!(updated.capture_network, false);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_arch() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // vms can't be run under qemu-user emulation
+    let image_req = generators::gen_image(&group)
+        .scaler(ImageScaler::Kvm)
+        .lifetime(ImageLifetime::jobs(1))
+        .arch(CpuArch::Arm64)
+        .emulation();
+    let resp = client.images.create(&image_req).await;
+    fail!(resp, 400, "Emulation is not supported for the Kvm scaler");
+    // create an arm64 image that can be emulated in k8s
+    let image_req = generators::gen_image(&group)
+        .arch(CpuArch::Arm64)
+        .emulation();
+    client.images.create(&image_req).await?;
+    let image = client.images.get(&group, &image_req.name).await?;
+    is!(image, image_req);
+    is!(image.arch, CpuArch::Arm64);
+    is!(image.emulation, true);
+    // emulation can't be kept when moving to windows
+    let update = ImageUpdate::default().scaler(ImageScaler::Windows);
+    let resp = client.images.update(&group, &image.name, &update).await;
+    fail!(
+        resp,
+        400,
+        "Emulation is not supported for the Windows scaler"
+    );
+    // retarget this image at native riscv64 nodes only
+    let update = ImageUpdate::default()
+        .arch(CpuArch::Riscv64)
+        .disable_emulation();
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated, update);
+    is!(updated.arch, CpuArch::Riscv64);
+    is!(updated.emulation, false);
+    Ok(())
+}
+
 #[serial_test::serial]
 #[tokio::test]
 async fn create_host_path() -> Result<(), Error> {
@@ -598,6 +757,42 @@ async fn update_clear_version() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn update_stream_handler() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // setup a random image
+    let image = generators::images(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // update the image to stream its output over a unix socket
+    let update = ImageUpdate::default().output_collection(
+        OutputCollectionUpdate::default()
+            .handler(OutputHandler::Stream)
+            .stream(StreamHandlerUpdate::default().socket("/tmp/thorium/output.sock")),
+    );
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated, update);
+    is!(updated.output_collection.handler, OutputHandler::Stream);
+    // switch to streaming over a port instead
+    let update = ImageUpdate::default().output_collection(
+        OutputCollectionUpdate::default()
+            .stream(StreamHandlerUpdate::default().port(8675).clear_socket()),
+    );
+    client.images.update(&group, &image.name, &update).await?;
+    let updated = client.images.get(&group, &image.name).await?;
+    is!(updated, update);
+    is!(updated.output_collection.stream.port, Some(8675));
+    is!(
+        updated.output_collection.stream.socket,
+        Option::<String>::None
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn update_child_filters() -> Result<(), Error> {
     // get admin client
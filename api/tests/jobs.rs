@@ -1,9 +1,12 @@
 //! Tests the Jobs routes in Thorium
 
 use chrono::prelude::*;
-use thorium::models::{ImageScaler, JobResets, ReactionListParams, Resources};
+use http::StatusCode;
+use thorium::models::{
+    ImageScaler, JobHandleStatus, JobProgressUpdate, JobResets, ReactionListParams, Resources,
+};
 use thorium::test_utilities::{self, generators};
-use thorium::{is, Error};
+use thorium::{fail, is, Error};
 
 /// unwraps the status counts for a specific user and image
 macro_rules! get_stats {
@@ -505,3 +508,195 @@ async fn sleep() -> Result<(), thorium::Error> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn progress() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test job progress in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    let id = client.reactions.create(&req).await?;
+    // get the name of the first stage of this pipeline
+    let stage = &pipe.order[0][0];
+    // register our test node and worker
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    generators::worker(
+        "cluster0", "node0", "progress", &group, &pipe.name, stage, &client,
+    )
+    .await?;
+    // claim a job for the first stage
+    let job = client
+        .jobs
+        .claim(
+            &req.group, &pipe.name, stage, "cluster0", "node0", "progress", 1,
+        )
+        .await?
+        .remove(0);
+    // report some progress for this job
+    let update = JobProgressUpdate::default()
+        .percent(42.5)
+        .phase("fuzzing")
+        .eta(3600);
+    let progress = client.jobs.progress(&job.id, &update).await?;
+    is!(progress.job, job.id);
+    is!(&progress.stage, stage);
+    is!(progress.percent, Some(42.5));
+    is!(progress.phase, Some("fuzzing".to_owned()));
+    // make sure our progress shows up on our reaction
+    let react = client.reactions.get(&req.group, &id.id).await?;
+    is!(react.job_progress.get(&job.id), Some(&progress));
+    // percents outside of 0 to 100 should be rejected
+    let resp = client
+        .jobs
+        .progress(&job.id, &JobProgressUpdate::default().percent(120.0))
+        .await;
+    fail!(resp, StatusCode::BAD_REQUEST);
+    // etas that can't be converted to a timestamp should be rejected
+    let resp = client
+        .jobs
+        .progress(&job.id, &JobProgressUpdate::default().eta(u64::MAX))
+        .await;
+    fail!(resp, StatusCode::BAD_REQUEST);
+    // proceed with this job and make sure its progress was cleared
+    client
+        .jobs
+        .proceed(&job, &generators::stage_logs(), 10)
+        .await?;
+    let react = client.reactions.get(&req.group, &id.id).await?;
+    is!(react.job_progress.contains_key(&job.id), false);
+    // completed jobs can no longer report progress
+    let resp = client.jobs.progress(&job.id, &update).await;
+    fail!(resp, StatusCode::CONFLICT);
+    // delete our worker
+    generators::delete_worker("progress", &client).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn checkpoint_blob() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test checkpoint blobs in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    client.reactions.create(&req).await?;
+    // get the name of the first stage of this pipeline
+    let stage = &pipe.order[0][0];
+    // register our test node and worker
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    generators::worker(
+        "cluster0", "node0", "resume", &group, &pipe.name, stage, &client,
+    )
+    .await?;
+    // claim a job for the first stage
+    let job = client
+        .jobs
+        .claim(
+            &req.group, &pipe.name, stage, "cluster0", "node0", "resume", 1,
+        )
+        .await?
+        .remove(0);
+    is!(job.checkpoint_blob, None);
+    // jobs without a checkpoint blob have nothing to download
+    let resp = client.jobs.download_checkpoint_blob(&job.id).await;
+    fail!(resp, StatusCode::NOT_FOUND);
+    // save a checkpoint blob for this job
+    let path = std::env::temp_dir().join(format!("{}.checkpoint", job.id));
+    tokio::fs::write(&path, b"fuzzed 1000 of 5000 inputs").await?;
+    let blob = client.jobs.upload_checkpoint_blob(&job.id, &path).await?;
+    is!(blob.size, 26);
+    // reset this job like we lost its worker
+    let resets = JobResets::with_capacity(ImageScaler::K8s, "Test", 1).add(job.id);
+    client.jobs.bulk_reset(&resets).await?;
+    // reclaim this job and make sure it can resume from its checkpoint blob
+    let job = client
+        .jobs
+        .claim(
+            &req.group, &pipe.name, stage, "cluster0", "node0", "resume", 1,
+        )
+        .await?
+        .remove(0);
+    is!(job.checkpoint_blob, Some(blob));
+    let data = client.jobs.download_checkpoint_blob(&job.id).await?;
+    is!(&data[..], b"fuzzed 1000 of 5000 inputs");
+    // proceed with this job and make sure its checkpoint blob was deleted
+    client
+        .jobs
+        .proceed(&job, &generators::stage_logs(), 10)
+        .await?;
+    let resp = client.jobs.download_checkpoint_blob(&job.id).await;
+    fail!(resp, StatusCode::NOT_FOUND);
+    // completed jobs can no longer save checkpoint blobs
+    let resp = client.jobs.upload_checkpoint_blob(&job.id, &path).await;
+    fail!(resp, StatusCode::CONFLICT);
+    tokio::fs::remove_file(&path).await?;
+    // delete our worker
+    generators::delete_worker("resume", &client).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn preempt() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test preemption in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    // get the pipeline for this pipeline order
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    // Create a random reaction based on our pipeline request
+    let req = generators::gen_reaction(&group, &pipe, None);
+    client.reactions.create(&req).await?;
+    // get the name of the first stage of this pipeline
+    let stage = &pipe.order[0][0];
+    // register our test node and worker
+    generators::node("cluster0", "node0", Resources::default(), &client).await?;
+    generators::worker(
+        "cluster0", "node0", "preempt", &group, &pipe.name, stage, &client,
+    )
+    .await?;
+    // claim a job for the first stage
+    let job = client
+        .jobs
+        .claim(
+            &req.group, &pipe.name, stage, "cluster0", "node0", "preempt", 1,
+        )
+        .await?
+        .remove(0);
+    // preempt this job and make sure it was returned to the queue
+    let resp = client.jobs.preempt(&job.id).await?;
+    is!(resp.status, JobHandleStatus::Preempted);
+    // jobs that are not running can't be preempted
+    let resp = client.jobs.preempt(&job.id).await;
+    fail!(resp, StatusCode::CONFLICT);
+    // make sure our preempted job can be claimed again
+    let reclaimed = client
+        .jobs
+        .claim(
+            &req.group, &pipe.name, stage, "cluster0", "node0", "preempt", 1,
+        )
+        .await?
+        .remove(0);
+    is!(reclaimed.id, job.id);
+    // delete our worker
+    generators::delete_worker("preempt", &client).await?;
+    Ok(())
+}
@@ -398,6 +398,51 @@ async fn update_bad() -> Result<(), thorium::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn simulated_internet() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create groups
+    let groups = generators::groups(2, &client)
+        .await?
+        .into_iter()
+        .map(|g| g.name)
+        .collect::<Vec<String>>();
+    // create a network policy request without any random rules
+    let name = generators::gen_network_policy(&groups).name;
+    let req = NetworkPolicyRequest::new(&name, groups.clone());
+    // fail to redirect ingress to the simulated internet
+    let bad_req = req
+        .clone()
+        .add_ingress_rule(NetworkPolicyRuleRaw::default().simulated_internet());
+    let resp = client.network_policies.create(bad_req).await;
+    fail!(resp, 400, "Only egress rules can use");
+    // fail to mix the simulated internet with the real one
+    let bad_req = req
+        .clone()
+        .add_egress_rule(NetworkPolicyRuleRaw::default().simulated_internet())
+        .add_egress_rule(NetworkPolicyRuleRaw::default().allow_internet());
+    let resp = client.network_policies.create(bad_req).await;
+    fail!(resp, 400, "cannot allow internet or all access");
+    // redirect egress to the simulated internet
+    let req = req.add_egress_rule(NetworkPolicyRuleRaw::default().simulated_internet());
+    client.network_policies.create(req).await?;
+    // make sure our policy uses the simulated internet
+    let network_policy = client.network_policies.get(&name, None).await?;
+    is!(network_policy.simulated_internet(), true);
+    // fail to add a rule allowing all egress to our redirected policy
+    let update =
+        NetworkPolicyUpdate::default().add_egress_rule(NetworkPolicyRuleRaw::default().allow_all());
+    let resp = client.network_policies.update(&name, None, &update).await;
+    fail!(resp, 400, "cannot allow internet or all access");
+    // stop redirecting egress to the simulated internet
+    let update = NetworkPolicyUpdate::default().deny_all_egress();
+    client.network_policies.update(&name, None, &update).await?;
+    let network_policy = client.network_policies.get(&name, None).await?;
+    is!(network_policy.simulated_internet(), false);
+    Ok(())
+}
+
 #[tokio::test]
 async fn delete() -> Result<(), thorium::Error> {
     // get admin client
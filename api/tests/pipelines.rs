@@ -2,9 +2,9 @@
 
 use rand::{rng, seq::SliceRandom};
 use thorium::models::{
-    ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate, NotificationLevel, NotificationParams,
-    NotificationRequest, PipelineBan, PipelineBanKind, PipelineBanUpdate, PipelineRequest,
-    PipelineUpdate,
+    EventTrigger, ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate, NotificationLevel,
+    NotificationParams, NotificationRequest, PipelineBan, PipelineBanKind, PipelineBanUpdate,
+    PipelineRequest, PipelineUpdate,
 };
 use thorium::test_utilities::{self, generators};
 use thorium::{contains, fail, is, is_in, unwrap_variant, vec_in_vec, Error};
@@ -68,6 +68,40 @@ async fn create_conflict() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn create_commitish_trigger() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create the pipeline tests groups
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // generate a random pipeline request with an invalid commitish trigger
+    let trigger = EventTrigger::NewCommitish {
+        branches: vec!["release/(".to_owned()],
+        paths: Vec::default(),
+    };
+    let pipe_req = generators::gen_pipe(&group, 20, false, &client)
+        .await?
+        .trigger("commits", trigger);
+    // make sure invalid patterns are rejected
+    let resp = client.pipelines.create(&pipe_req).await;
+    fail!(resp, 400, "invalid pattern");
+    // create this pipeline with a valid commitish trigger
+    let trigger = EventTrigger::NewCommitish {
+        branches: vec!["main".to_owned(), "release/.*".to_owned()],
+        paths: vec!["src/.*\\.rs".to_owned()],
+    };
+    let pipe_req = pipe_req.trigger("commits", trigger.clone());
+    let resp = client.pipelines.create(&pipe_req).await?;
+    is!(resp.status().as_u16(), 204);
+    // make sure our trigger was saved
+    let pipeline = client
+        .pipelines
+        .get(&pipe_req.group, &pipe_req.name)
+        .await?;
+    is!(pipeline.triggers.get("commits"), Some(&trigger));
+    Ok(())
+}
+
 #[tokio::test]
 async fn create_banned_image() -> Result<(), Error> {
     // get admin client
@@ -1,7 +1,8 @@
 //! Tests the Images routes in Thorium
 
 use thorium::models::{
-    GenericJobArgsUpdate, ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate, PipelineBan,
+    Buffer, CommentRequest, CommentUpdate, DeleteCommentParams, GenericJobArgsUpdate, GroupUpdate,
+    GroupUsersUpdate, ImageBan, ImageBanKind, ImageBanUpdate, ImageUpdate, PipelineBan,
     PipelineBanKind, PipelineBanUpdate, PipelineRequest, PipelineUpdate, ReactionStatus,
     ReactionUpdate, Resources,
 };
@@ -1585,3 +1586,123 @@ async fn parent_ephemeral() -> Result<(), Error> {
     is!(download, "I am a parent test file");
     Ok(())
 }
+
+#[tokio::test]
+async fn comment() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reaction comments in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline and a reaction to comment on
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    let react_req = generators::gen_reaction(&group, &pipe, None);
+    let id = client.reactions.create(&react_req).await?.id;
+    // comment on this reaction with an attachment
+    let comment_req =
+        CommentRequest::text("I am a comment").buffer(Buffer::new("I am an attachment"));
+    let resp = client.reactions.comment(&group, &id, comment_req).await?;
+    // make sure this comment was added
+    let reaction = client.reactions.get(&group, &id).await?;
+    is!(reaction.comments.len(), 1);
+    is!(reaction.comments[0].id, resp.id);
+    is!(reaction.comments[0].comment, "I am a comment");
+    // download this comments attachment and make sure it matches
+    let (_, attach) = reaction.comments[0].attachments.iter().next().unwrap();
+    let attachment = client
+        .reactions
+        .download_attachment(&group, &id, &resp.id, attach)
+        .await?;
+    is!(attachment.data, "I am an attachment".as_bytes());
+    // edit this comment and make sure its original text was retained
+    let edit = CommentUpdate::default().comment("I am an edited comment");
+    client
+        .reactions
+        .update_comment(&group, &id, &resp.id, &edit)
+        .await?;
+    let reaction = client.reactions.get(&group, &id).await?;
+    is!(reaction.comments[0].comment, "I am an edited comment");
+    is!(reaction.comments[0].revisions.len(), 1);
+    is!(reaction.comments[0].revisions[0].comment, "I am a comment");
+    // delete this comment from all groups
+    client
+        .reactions
+        .delete_comment(&group, &id, &resp.id, &DeleteCommentParams::default())
+        .await?;
+    let reaction = client.reactions.get(&group, &id).await?;
+    is_empty!(reaction.comments);
+    Ok(())
+}
+
+#[tokio::test]
+async fn comment_fail() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group to test reaction comments in
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a random pipeline and a reaction to comment on
+    let pipe_req = generators::pipelines(&group, 1, false, &client)
+        .await?
+        .remove(0);
+    let pipe = client.pipelines.get(&group, &pipe_req.name).await?;
+    let react_req = generators::gen_reaction(&group, &pipe, None);
+    let id = client.reactions.create(&react_req).await?.id;
+    // comment on this reaction as the admin
+    let resp = client
+        .reactions
+        .comment(&group, &id, CommentRequest::text("I am a comment"))
+        .await?;
+    // users outside of our group can't comment on, edit, or delete comments on this reaction
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let result = user_client
+        .reactions
+        .comment(&group, &id, CommentRequest::text("I am not allowed"))
+        .await;
+    fail!(result, 404);
+    let edit = CommentUpdate::default().comment("I am not allowed");
+    let result = user_client
+        .reactions
+        .update_comment(&group, &id, &resp.id, &edit)
+        .await;
+    fail!(result, 404);
+    let result = user_client
+        .reactions
+        .delete_comment(&group, &id, &resp.id, &DeleteCommentParams::default())
+        .await;
+    fail!(result, 404);
+    // add the user to our group
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username));
+    client.groups.update(&group, &group_update).await?;
+    // users can't edit or delete comments they did not author
+    let result = user_client
+        .reactions
+        .update_comment(&group, &id, &resp.id, &edit)
+        .await;
+    fail!(result, 401);
+    let result = user_client
+        .reactions
+        .delete_comment(&group, &id, &resp.id, &DeleteCommentParams::default())
+        .await;
+    fail!(result, 401);
+    // comments that don't exist can't be edited or deleted
+    let result = client
+        .reactions
+        .update_comment(&group, &id, &Uuid::new_v4(), &edit)
+        .await;
+    fail!(result, 404);
+    let result = client
+        .reactions
+        .delete_comment(
+            &group,
+            &id,
+            &Uuid::new_v4(),
+            &DeleteCommentParams::default(),
+        )
+        .await;
+    fail!(result, 404);
+    Ok(())
+}
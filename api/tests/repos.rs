@@ -1,12 +1,15 @@
 //! Tests the repos routes in Thorium
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use thorium::models::{
-    GroupUpdate, GroupUsersUpdate, RepoCheckout, RepoListLine, RepoListOpts, RepoRequest,
+    Buffer, CommentRequest, CommentUpdate, DeleteCommentParams, GroupUpdate, GroupUsersUpdate,
+    RepoCheckout, RepoListLine, RepoListOpts, RepoMirrorClaimOpts, RepoMirrorFetch,
+    RepoMirrorRequest, RepoRequest,
 };
 use thorium::test_utilities::{self, generators};
-use thorium::{contains, fail, is, is_desc, Error};
+use thorium::{contains, fail, is, is_desc, is_empty, Error};
+use uuid::Uuid;
 
 #[tokio::test]
 async fn create() -> Result<(), Error> {
@@ -232,3 +235,225 @@ async fn list_tagged() -> Result<(), Error> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn mirrors() -> Result<(), Error> {
+    // Get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // Start mirroring a repo with a random name
+    let remote = format!("https://github.com/mirrors/{}", Uuid::new_v4());
+    let req = RepoMirrorRequest::new(&remote, vec![group.clone()])
+        .interval(600)
+        .credentials("mcorn", "corn-token");
+    let mirror = client.repos.create_mirror(&req).await?;
+    // Make sure our credentials were not returned
+    is!(mirror.authenticated, true);
+    is!(mirror.credentials, None);
+    is!(mirror.last_fetch, None);
+    // Make sure our repo was registered in Thorium
+    client.repos.get(&mirror.url).await?;
+    // Get our mirror and make sure it is listed
+    let retrieved = client.repos.get_mirror(&mirror.url).await?;
+    is!(retrieved.remote, remote);
+    is!(retrieved.credentials, None);
+    let urls: Vec<String> = client
+        .repos
+        .list_mirrors()
+        .await?
+        .into_iter()
+        .map(|mirror| mirror.url)
+        .collect();
+    contains!(urls, &mirror.url);
+    // Claim our mirror along with its credentials
+    client.repos.sync_mirror(&mirror.url).await?;
+    let claimed = client
+        .repos
+        .claim_mirrors(&RepoMirrorClaimOpts::default().limit(1000))
+        .await?;
+    let claimed = claimed.into_iter().find(|m| m.url == mirror.url);
+    is!(
+        claimed.and_then(|m| m.credentials).map(|creds| creds.token),
+        Some("corn-token".to_owned())
+    );
+    // Report a successful fetch for this mirror
+    let refs = HashMap::from([("refs/tags/v1".to_owned(), "a".repeat(40))]);
+    let fetch = RepoMirrorFetch::success(refs.clone(), 1);
+    client.repos.mirror_fetched(&mirror.url, &fetch).await?;
+    let fetched = client.repos.get_mirror(&mirror.url).await?;
+    is!(fetched.refs, refs);
+    is!(fetched.ingested, 1);
+    is!(fetched.last_fetch.is_some(), true);
+    // Report a failed fetch and make sure our refs were kept
+    let fetch = RepoMirrorFetch::failed("remote hung up");
+    client.repos.mirror_fetched(&mirror.url, &fetch).await?;
+    let failed = client.repos.get_mirror(&mirror.url).await?;
+    is!(failed.refs, refs);
+    is!(failed.last_error, Some("remote hung up".to_owned()));
+    // Stop mirroring this repo but make sure the repo still exists
+    client.repos.delete_mirror(&mirror.url).await?;
+    let resp = client.repos.get_mirror(&mirror.url).await;
+    fail!(resp, 404);
+    client.repos.get(&mirror.url).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn mirrors_fail() -> Result<(), Error> {
+    // Get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // Create a user that is not in our group
+    let user_client = generators::client(&client).await?;
+    // Mirrors must be fetched over HTTP(S)
+    let remote = format!("github.com/mirrors/{}", Uuid::new_v4());
+    let req = RepoMirrorRequest::new(&remote, vec![group.clone()]);
+    let resp = client.repos.create_mirror(&req).await;
+    fail!(resp, 400, "HTTP(S)");
+    // Mirrors can't be fetched too often
+    let remote = format!("https://{remote}");
+    let req = RepoMirrorRequest::new(&remote, vec![group.clone()]).interval(10);
+    let resp = client.repos.create_mirror(&req).await;
+    fail!(resp, 400);
+    // Mirrors can't go too long between fetches
+    let req = RepoMirrorRequest::new(&remote, vec![group.clone()]).interval(u64::MAX);
+    let resp = client.repos.create_mirror(&req).await;
+    fail!(resp, 400);
+    // Credentials can only be sent over HTTPS
+    let plaintext = format!("http://github.com/mirrors/{}", Uuid::new_v4());
+    let req = RepoMirrorRequest::new(&plaintext, vec![group.clone()]).credentials("mcorn", "corn");
+    let resp = client.repos.create_mirror(&req).await;
+    fail!(resp, 400, "HTTPS");
+    // Create a valid mirror and make sure it can't be mirrored twice
+    let req = RepoMirrorRequest::new(&remote, vec![group.clone()]);
+    let mirror = client.repos.create_mirror(&req).await?;
+    let resp = client.repos.create_mirror(&req).await;
+    fail!(resp, 409);
+    // Users outside of our group can't see or delete this mirror
+    let resp = user_client.repos.get_mirror(&mirror.url).await;
+    fail!(resp, 404);
+    let resp = user_client.repos.delete_mirror(&mirror.url).await;
+    fail!(resp, 404);
+    // Only admins can claim mirrors or report fetches
+    let resp = user_client
+        .repos
+        .claim_mirrors(&RepoMirrorClaimOpts::default())
+        .await;
+    fail!(resp, 401);
+    let fetch = RepoMirrorFetch::failed("corn");
+    let resp = user_client.repos.mirror_fetched(&mirror.url, &fetch).await;
+    fail!(resp, 401);
+    Ok(())
+}
+
+#[tokio::test]
+async fn comment() -> Result<(), Error> {
+    // Get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // Create a repo to comment on
+    let url = format!("github.com/comments/{}", Uuid::new_v4());
+    let req = RepoRequest::new(&url, vec![group], Some(RepoCheckout::branch("main")));
+    client.repos.create(&req).await?;
+    // Comment on this repo with an attachment
+    let comment_req =
+        CommentRequest::text("I am a comment").buffer(Buffer::new("I am an attachment"));
+    let resp = client.repos.comment(&url, comment_req).await?;
+    // Make sure this comment was added
+    let repo = client.repos.get(&url).await?;
+    is!(repo.comments.len(), 1);
+    is!(repo.comments[0].id, resp.id);
+    is!(repo.comments[0].comment, "I am a comment");
+    // Download this comments attachment and make sure it matches
+    let (_, attach) = repo.comments[0].attachments.iter().next().unwrap();
+    let attachment = client
+        .repos
+        .download_attachment(&url, &resp.id, attach)
+        .await?;
+    is!(attachment.data, "I am an attachment".as_bytes());
+    // Edit this comment and make sure its original text was retained
+    let edit = CommentUpdate::default().comment("I am an edited comment");
+    client.repos.update_comment(&url, &resp.id, &edit).await?;
+    let repo = client.repos.get(&url).await?;
+    is!(repo.comments[0].comment, "I am an edited comment");
+    is!(repo.comments[0].revisions.len(), 1);
+    is!(repo.comments[0].revisions[0].comment, "I am a comment");
+    // Delete this comment from all groups
+    client
+        .repos
+        .delete_comment(&url, &resp.id, &DeleteCommentParams::default())
+        .await?;
+    let repo = client.repos.get(&url).await?;
+    is_empty!(repo.comments);
+    Ok(())
+}
+
+#[tokio::test]
+async fn comment_fail() -> Result<(), Error> {
+    // Get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // Create a repo to comment on
+    let url = format!("github.com/comments/{}", Uuid::new_v4());
+    let req = RepoRequest::new(
+        &url,
+        vec![group.clone()],
+        Some(RepoCheckout::branch("main")),
+    );
+    client.repos.create(&req).await?;
+    // Comment on this repo as the admin
+    let resp = client
+        .repos
+        .comment(&url, CommentRequest::text("I am a comment"))
+        .await?;
+    // Users outside of our group can't see, comment on, edit, or delete comments on this repo
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let result = user_client
+        .repos
+        .comment(&url, CommentRequest::text("I am not allowed"))
+        .await;
+    fail!(result, 404);
+    let edit = CommentUpdate::default().comment("I am not allowed");
+    let result = user_client
+        .repos
+        .update_comment(&url, &resp.id, &edit)
+        .await;
+    fail!(result, 404);
+    let result = user_client
+        .repos
+        .delete_comment(&url, &resp.id, &DeleteCommentParams::default())
+        .await;
+    fail!(result, 404);
+    // Add the user to our group
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username));
+    client.groups.update(&group, &group_update).await?;
+    // Users can't edit or delete comments they did not author
+    let result = user_client
+        .repos
+        .update_comment(&url, &resp.id, &edit)
+        .await;
+    fail!(result, 401);
+    let result = user_client
+        .repos
+        .delete_comment(&url, &resp.id, &DeleteCommentParams::default())
+        .await;
+    fail!(result, 401);
+    // Comments that don't exist can't be edited or deleted
+    let result = client
+        .repos
+        .update_comment(&url, &Uuid::new_v4(), &edit)
+        .await;
+    fail!(result, 404);
+    let result = client
+        .repos
+        .delete_comment(&url, &Uuid::new_v4(), &DeleteCommentParams::default())
+        .await;
+    fail!(result, 404);
+    Ok(())
+}
@@ -0,0 +1,124 @@
+//! Tests the saved search routes in Thorium
+
+use http::StatusCode;
+use thorium::models::{ElasticIndex, SavedSearchRequest};
+use thorium::test_utilities::{self, generators};
+use thorium::{fail, is, is_in, is_not_in};
+
+#[tokio::test]
+async fn create_saved() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a user to save searches as
+    let user_client = generators::client(&client).await?;
+    // build and save a search
+    let req = SavedSearchRequest::new("corn-pe", "corn AND pe32")
+        .indexes(vec![ElasticIndex::SampleResults, ElasticIndex::RepoResults])
+        .description("PE files that mention corn");
+    let saved = user_client.search.create_saved(&req).await?;
+    is!(saved, req);
+    // make sure we can get our saved search back
+    let retrieved = user_client.search.get_saved(&req.name).await?;
+    is!(retrieved, req);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_saved_conflict() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a user to save searches as
+    let user_client = generators::client(&client).await?;
+    // save a search
+    let req = SavedSearchRequest::new("conflict", "corn");
+    user_client.search.create_saved(&req).await?;
+    // try to save a different search with the same name
+    let resp = user_client
+        .search
+        .create_saved(&SavedSearchRequest::new("conflict", "not corn"))
+        .await;
+    fail!(resp, StatusCode::CONFLICT);
+    // make sure the original search was not overwritten
+    let retrieved = user_client.search.get_saved(&req.name).await?;
+    is!(retrieved, req);
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_saved_invalid() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a user to save searches as
+    let user_client = generators::client(&client).await?;
+    // names that can't be used in a url should be rejected
+    let resp = user_client
+        .search
+        .create_saved(&SavedSearchRequest::new("corn/pe", "corn"))
+        .await;
+    fail!(resp, StatusCode::BAD_REQUEST);
+    // empty indexes should be rejected
+    let req = SavedSearchRequest::new("no-indexes", "corn").indexes(Vec::default());
+    let resp = user_client.search.create_saved(&req).await;
+    fail!(resp, StatusCode::BAD_REQUEST);
+    // groups the user is not in should be rejected
+    let groups = generators::groups(1, &client).await?;
+    let req = SavedSearchRequest::new("other-group", "corn").group(&groups[0].name);
+    let resp = user_client.search.create_saved(&req).await;
+    fail!(resp, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_saved() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create two users to save searches as
+    let user_client = generators::client(&client).await?;
+    let other_client = generators::client(&client).await?;
+    // save some searches out of order
+    let reqs = vec![
+        SavedSearchRequest::new("b-search", "corn"),
+        SavedSearchRequest::new("a-search", "pe32"),
+        SavedSearchRequest::new("c-search", "elf"),
+    ];
+    for req in &reqs {
+        user_client.search.create_saved(req).await?;
+    }
+    let other = SavedSearchRequest::new("other", "corn");
+    other_client.search.create_saved(&other).await?;
+    // make sure our saved searches are listed sorted by name
+    let saved = user_client.search.list_saved().await?;
+    is!(saved.len(), 3);
+    let names = saved
+        .iter()
+        .map(|saved| saved.name.as_str())
+        .collect::<Vec<_>>();
+    is!(names, vec!["a-search", "b-search", "c-search"]);
+    for req in reqs {
+        is_in!(saved, req);
+    }
+    // make sure other users saved searches are not listed
+    is_not_in!(saved, other);
+    Ok(())
+}
+
+#[tokio::test]
+async fn delete_saved() -> Result<(), thorium::Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // create a user to save searches as
+    let user_client = generators::client(&client).await?;
+    // save and then delete a search
+    let req = SavedSearchRequest::new("delete-me", "corn");
+    user_client.search.create_saved(&req).await?;
+    let resp = user_client.search.delete_saved(&req.name).await?;
+    is!(resp.status().as_u16(), 204);
+    // make sure our search is gone
+    let resp = user_client.search.get_saved(&req.name).await;
+    fail!(resp, StatusCode::NOT_FOUND);
+    let resp = user_client.search.delete_saved(&req.name).await;
+    fail!(resp, StatusCode::NOT_FOUND);
+    // we should be able to save a search with the same name again
+    user_client.search.create_saved(&req).await?;
+    Ok(())
+}
@@ -4,9 +4,10 @@ use std::collections::HashSet;
 use std::path::PathBuf;
 
 use thorium::models::{
-    HostPathWhitelistUpdate, ImageBanKind, PipelineBanKind, PipelineRequest, PipelineUpdate,
-    SystemSettings, SystemSettingsResetParams, SystemSettingsUpdate, SystemSettingsUpdateParams,
-    Volume, VolumeTypes,
+    CpuArch, HostPathWhitelistUpdate, ImageBanKind, NodeGetParams, NodeHealth, NodeRegistration,
+    NodeUpdate, PipelineBanKind, PipelineRequest, PipelineUpdate, Resources, SystemSettings,
+    SystemSettingsResetParams, SystemSettingsUpdate, SystemSettingsUpdateParams, Volume,
+    VolumeTypes,
 };
 use thorium::test_utilities::{self, generators};
 use thorium::{contains, fail, is, is_not, unwrap_variant, vec_in_vec, Error};
@@ -118,6 +119,49 @@ async fn restore_create_reactions() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn node_arch() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // register an arm64 node that can emulate x86_64
+    let node = NodeRegistration::new("cluster0", "arm-node0", Resources::default())
+        .arch(CpuArch::Arm64)
+        .emulate(CpuArch::X86_64);
+    client.system.register_node(&node).await?;
+    let params = NodeGetParams::default();
+    let info = client
+        .system
+        .get_node("cluster0", "arm-node0", &params)
+        .await?;
+    is!(info.arch, CpuArch::Arm64);
+    is!(info.emulated, vec![CpuArch::X86_64]);
+    // updates without any architectures should leave them alone
+    let update = NodeUpdate::new(NodeHealth::Healthy, Resources::default());
+    client
+        .system
+        .update_node("cluster0", "arm-node0", &update)
+        .await?;
+    let info = client
+        .system
+        .get_node("cluster0", "arm-node0", &params)
+        .await?;
+    is!(info.arch, CpuArch::Arm64);
+    is!(info.emulated, vec![CpuArch::X86_64]);
+    // remove this nodes ability to emulate x86_64
+    let update = NodeUpdate::new(NodeHealth::Healthy, Resources::default()).emulated(Vec::new());
+    client
+        .system
+        .update_node("cluster0", "arm-node0", &update)
+        .await?;
+    let info = client
+        .system
+        .get_node("cluster0", "arm-node0", &params)
+        .await?;
+    is!(info.arch, CpuArch::Arm64);
+    is!(info.emulated, Vec::<CpuArch>::new());
+    Ok(())
+}
+
 #[serial_test::serial]
 #[tokio::test]
 async fn update_settings() -> Result<(), Error> {
@@ -1,7 +1,12 @@
 //! Tests the users routes in Thorium
 
+use thorium::models::{
+    CommentRequest, GroupUpdate, GroupUsersUpdate, SubscriptionEvent, SubscriptionRequest,
+    SubscriptionTarget,
+};
 use thorium::test_utilities::{self, generators};
 use thorium::Error;
+use thorium::{contains, fail, is, is_empty};
 
 #[tokio::test]
 async fn delete() -> Result<(), Error> {
@@ -15,3 +20,47 @@ async fn delete() -> Result<(), Error> {
     client.users.delete(&info.username).await?;
     Ok(())
 }
+
+#[tokio::test]
+async fn subscriptions() -> Result<(), Error> {
+    // get admin client
+    let client = test_utilities::admin_client().await?;
+    // Create a group
+    let group = generators::groups(1, &client).await?.remove(0).name;
+    // create a user and add them to our group
+    let user_client = generators::client(&client).await?;
+    let username = user_client.users.info().await?.username;
+    let group_update =
+        GroupUpdate::default().users(GroupUsersUpdate::default().direct_add(username));
+    client.groups.update(&group, &group_update).await?;
+    // upload a file as the admin
+    let sample = generators::gen_sample(&group);
+    let hashes = client.files.create(sample).await?;
+    // subscribe to comments on this file as our user
+    let req = SubscriptionRequest::new(SubscriptionTarget::sample(&hashes.sha256))
+        .event(SubscriptionEvent::Comments);
+    let resp = user_client.users.subscribe(&req).await?;
+    // subscribing to the same file twice should fail
+    fail!(user_client.users.subscribe(&req).await, 409);
+    // make sure our subscription was saved
+    let subs = user_client.users.list_subscriptions().await?;
+    is!(subs.len(), 1);
+    is!(subs[0].id, resp.id);
+    is!(subs[0], req);
+    // comment on this file as the admin
+    let comment = CommentRequest::new(&hashes.sha256, "Someone should look at this");
+    client.files.comment(comment).await?;
+    // make sure our user was notified about this comment
+    let notifications = user_client.users.notifications().await?;
+    is!(notifications.len(), 1);
+    contains!(notifications[0].msg, &hashes.sha256);
+    // clear our inbox
+    user_client.users.clear_notifications().await?;
+    is_empty!(user_client.users.notifications().await?);
+    // unsubscribe from this file
+    user_client.users.unsubscribe(&resp.id).await?;
+    is_empty!(user_client.users.list_subscriptions().await?);
+    // unsubscribing twice should fail
+    fail!(user_client.users.unsubscribe(&resp.id).await, 404);
+    Ok(())
+}
@@ -19,6 +19,8 @@ pub enum Error {
     TryReserve(TryReserveError),
     /// Finish was called before any data was specified
     FinishBeforeData,
+    /// A sealed chunk failed authentication
+    Authentication(u64),
 }
 
 impl Error {
@@ -45,6 +47,9 @@ impl std::fmt::Display for Error {
             Error::TryFromInt(err) => write!(fmt, "TryFromInt: {err}"),
             Error::TryReserve(err) => write!(fmt, "TryReserve: {err}"),
             Error::FinishBeforeData => write!(fmt, "FinishBeforeData"),
+            Error::Authentication(chunk) => {
+                write!(fmt, "Authentication: chunk {chunk} failed authentication")
+            }
         }
     }
 }
@@ -9,6 +9,13 @@
 //! downloads. This library is largely intended to allow the Thorium API to stream
 //! `CaRTed` files to and from s3.
 //!
+//! Cart-rs can also write sealed (version 2) `CaRT` files. Sealed files are split into fixed-size
+//! chunks that are each compressed and then encrypted with AES-256-GCM or ChaCha20-Poly1305, so
+//! any corruption or truncation is detected when uncarting. The key is stored in the header so
+//! sealing does not protect against deliberate tampering. Sealed files can also store metadata
+//! like the original file name in their header. [`UncartStream`] detects which format it is
+//! reading automatically. See the [`sealed`] module for details on the format.
+//!
 //! # Examples
 //!
 //! ## `CaRTing` a File
@@ -237,6 +244,86 @@
 //! #    exec().await.unwrap()
 //! # })
 //! ```
+//!
+//! ## Sealing a File
+//!
+//! ```
+//! use tokio::fs::{File, OpenOptions};
+//! use tokio::io::{BufReader, AsyncWriteExt};
+//! use cart_rs::{CartStream, Cipher, SealOptions, UncartStream};
+//! use generic_array::{typenum::U16, GenericArray};
+//!
+//! # async fn exec() -> Result<(), cart_rs::Error> {
+//! # let mut source = File::create("/tmp/SneakyBean").await?;
+//! # let data = "ImMalware".repeat(10_000);
+//! # source.write_all(data.as_bytes()).await?;
+//! # // open our file in read mode
+//! # let source = File::open("/tmp/SneakyBean").await?;
+//! // have a cart password to use
+//! let password: GenericArray<u8, U16> =
+//!     GenericArray::clone_from_slice(&"SecretCornIsBest".as_bytes()[..16]);
+//! // seal this file in 16 KiB chunks and keep its original name
+//! let opts = SealOptions::default()
+//!     .cipher(Cipher::ChaCha20Poly1305)
+//!     .chunk_size(16_384)
+//!     .file_name("SneakyBean");
+//! // build our sealed cart stream
+//! let mut cart_stream = CartStream::sealed(BufReader::new(source), &password, opts)?;
+//! // write our sealed file to disk
+//! let mut output = File::create("/tmp/SealedBean").await?;
+//! tokio::io::copy(&mut cart_stream, &mut output).await?;
+//! // uncart our sealed file just like any other carted file
+//! let sealed = File::open("/tmp/SealedBean").await?;
+//! let mut uncart_stream = UncartStream::new(BufReader::new(sealed));
+//! let mut uncarted = Vec::new();
+//! tokio::io::copy(&mut uncart_stream, &mut uncarted).await?;
+//! // the original file name is available from the sealed header
+//! let header = uncart_stream.sealed_header().unwrap();
+//! assert_eq!(header.file_name(), Some("SneakyBean"));
+//! # assert_eq!(uncarted, data.as_bytes());
+//! # // make sure corrupting a sealed file is detected
+//! # let mut corrupted = tokio::fs::read("/tmp/SealedBean").await?;
+//! # let last = corrupted.len() - 1;
+//! # corrupted[last] ^= 1;
+//! # let mut uncart_stream = UncartStream::new(BufReader::new(&corrupted[..]));
+//! # assert!(tokio::io::copy(&mut uncart_stream, &mut Vec::new()).await.is_err());
+//! # // make sure truncated sealed files are detected
+//! # let mut uncart_stream = UncartStream::new(BufReader::new(&corrupted[..last - 64]));
+//! # assert!(tokio::io::copy(&mut uncart_stream, &mut Vec::new()).await.is_err());
+//! # // seal the same file manually and make sure it can be uncarted too
+//! # let mut cart = cart_rs::CartStreamManual::sealed(&password, 16_384, SealOptions::default())?;
+//! # let mut manual = Vec::new();
+//! # for chunk in data.as_bytes().chunks(7_000) {
+//! #     if cart.next_bytes(bytes::Bytes::copy_from_slice(chunk))? {
+//! #         while cart.process()? {
+//! #             if cart.ready() >= 8_192 {
+//! #                 manual.extend_from_slice(cart.carted_bytes());
+//! #                 cart.consume();
+//! #             }
+//! #         }
+//! #     }
+//! #     // make sure our sealed output is drained instead of growing forever
+//! #     assert!(cart.ready() < 16_384 * 3);
+//! # }
+//! # manual.extend_from_slice(cart.finish()?);
+//! # let mut uncart_stream = UncartStream::new(BufReader::new(&manual[..]));
+//! # let mut uncarted = Vec::new();
+//! # tokio::io::copy(&mut uncart_stream, &mut uncarted).await?;
+//! # assert_eq!(uncarted, data.as_bytes());
+//! # // make sure sealed files are still detected when our reads are shorter than the magic
+//! # let mut uncart_stream = UncartStream::new(BufReader::with_capacity(1, &manual[..]));
+//! # let mut uncarted = Vec::new();
+//! # tokio::io::copy(&mut uncart_stream, &mut uncarted).await?;
+//! # assert_eq!(uncarted, data.as_bytes());
+//! # std::fs::remove_file("/tmp/SneakyBean")?;
+//! # std::fs::remove_file("/tmp/SealedBean")?;
+//! # Ok(())
+//! # }
+//!
+//! # tokio_test::block_on(async {
+//! #    exec().await.unwrap()
+//! # })
+//! ```
 use bytes::{Buf, Bytes};
 use crypto::rc4::Rc4;
 use crypto::symmetriccipher::SynchronousStreamCipher;
@@ -257,7 +344,8 @@ mod errors;
 mod libs;
 
 pub use errors::Error;
-pub use libs::{footer, footer::Footer, header, header::Header};
+pub use libs::sealed::{Cipher, SealOptions, SealedHeader, Sealer, Unsealer};
+pub use libs::{footer, footer::Footer, header, header::Header, sealed};
 
 /// Packs a Cart file using streaming
 #[pin_project::pin_project]
@@ -277,6 +365,12 @@ pub struct CartStream<R: AsyncBufRead, T: ArrayLength<u8>> {
     footer_written: bool,
     /// Signals that the input has been exhausted and all data has been written to the output
     finished: bool,
+    /// The sealer to use if we are writing a sealed `CaRT` file
+    sealed: Option<Sealer>,
+    /// Sealed data that has not been written to the read buffer yet
+    pending: Vec<u8>,
+    /// How much of our pending sealed data has already been written to the read buffer
+    pending_pos: usize,
 }
 
 impl<R: AsyncBufRead, T: ArrayLength<u8>> CartStream<R, T> {
@@ -302,10 +396,77 @@ impl<R: AsyncBufRead, T: ArrayLength<u8>> CartStream<R, T> {
             header_written: false,
             footer_written: false,
             finished: false,
+            sealed: None,
+            pending: Vec::new(),
+            pending_pos: 0,
         };
         Ok(cart)
     }
 
+    /// Create a new cart stream that writes a sealed `CaRT` file
+    ///
+    /// Sealed files authenticate each chunk with an AEAD cipher so corruption is detected when
+    /// uncarting.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A reader for the file to cart
+    /// * `key` - The 16 byte key to use for encryption
+    /// * `opts` - The options to seal this file with
+    pub fn sealed(input: R, key: &GenericArray<u8, T>, opts: SealOptions) -> Result<Self, Error> {
+        // build a normal cart stream and then add our sealer
+        let mut cart = Self::new(input, key)?;
+        cart.sealed = Some(Sealer::new(key, opts)?);
+        Ok(cart)
+    }
+
+    /// Try to read from our stream and seal any data to the internal read buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - The current context
+    /// * `buf` - The internal buffer to write sealed data to
+    fn do_poll_sealed(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        // project our struct
+        let mut this = self.project();
+        // keep sealing data until we have something to return or the input is exhausted
+        while *this.pending_pos == this.pending.len() {
+            // return immediately (signalling completion) if our final chunk was already written
+            if *this.finished {
+                return Poll::Ready(Ok(()));
+            }
+            // reset our pending buffer
+            this.pending.clear();
+            *this.pending_pos = 0;
+            // read in the next chunk of bytes
+            let chunk = ready!(this.input.as_mut().poll_fill_buf(cx))?;
+            let sealer = this.sealed.as_mut().expect("only called when sealing");
+            if chunk.is_empty() {
+                // the input is exhausted so seal our final chunk
+                if let Err(err) = sealer.finish(this.pending) {
+                    return Poll::Ready(Err(std::io::Error::new(ErrorKind::InvalidData, err)));
+                }
+                *this.finished = true;
+            } else {
+                // seal this data and mark it as consumed
+                let consumed = chunk.len();
+                if let Err(err) = sealer.push(chunk, this.pending) {
+                    return Poll::Ready(Err(std::io::Error::new(ErrorKind::InvalidData, err)));
+                }
+                this.input.as_mut().consume(consumed);
+            }
+        }
+        // copy as much of our pending data as we can to the read buffer
+        let end = std::cmp::min(this.pending.len(), *this.pending_pos + buf.remaining());
+        buf.put_slice(&this.pending[*this.pending_pos..end]);
+        *this.pending_pos = end;
+        Poll::Ready(Ok(()))
+    }
+
     /// Write the `CaRT` header to the internal read buffer, returning the number of bytes written
     ///
     /// # Arguments
@@ -422,7 +583,10 @@ impl<R: AsyncBufRead, T: ArrayLength<u8>> AsyncRead for CartStream<R, T> {
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
-        if !self.header_written {
+        if self.sealed.is_some() {
+            // sealed files write their own header and have no footer
+            self.do_poll_sealed(cx, buf)
+        } else if !self.header_written {
             // write the header if it hasn't been written
             match self.write_header(buf) {
                 Ok(bytes_written) => {
@@ -455,7 +619,8 @@ impl<R: AsyncBufRead, T: ArrayLength<u8>> AsyncRead for CartStream<R, T> {
 
 /// Unpacks a Cart file using streaming
 ///
-/// This Cart file cannot have any comments for this to work.
+/// This Cart file cannot have any comments for this to work. Both version 1 and sealed `CaRT`
+/// files are supported and the format is detected automatically.
 #[pin_project::pin_project]
 pub struct UncartStream<R: AsyncBufRead> {
     /// The carted stream to read from
@@ -479,6 +644,16 @@ pub struct UncartStream<R: AsyncBufRead> {
     decompressed_consumed: usize,
     /// The zlib decompressor to use
     zlib: Box<InflateState>,
+    /// Whether we have checked which `CaRT` format we are reading yet
+    detected: bool,
+    /// The unsealer to use if we are reading a sealed `CaRT` file
+    sealed: Option<Unsealer>,
+    /// Authenticated plaintext that has not been written to the read buffer yet
+    plain: Vec<u8>,
+    /// How much of our authenticated plaintext has already been written to the read buffer
+    plain_pos: usize,
+    /// The start of our file if we had to consume it to detect its format
+    magic: Vec<u8>,
 }
 
 impl<R: AsyncBufRead> UncartStream<R> {
@@ -508,7 +683,103 @@ impl<R: AsyncBufRead> UncartStream<R> {
             decompressed_remaining: 0,
             decompressed_consumed: 0,
             zlib: InflateState::new_boxed_with_window_bits(15),
+            detected: false,
+            sealed: None,
+            plain: Vec::new(),
+            plain_pos: 0,
+            magic: Vec::new(),
+        }
+    }
+
+    /// Get the header of the sealed `CaRT` file we are reading if one has been read
+    ///
+    /// This will always be None for version 1 `CaRT` files.
+    pub fn sealed_header(&self) -> Option<&SealedHeader> {
+        self.sealed.as_ref().and_then(Unsealer::header)
+    }
+
+    /// Check whether we are reading a version 1 or sealed `CaRT` file
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - The current context
+    fn detect(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let mut this = self.project();
+        loop {
+            // peek at the start of our file without consuming anything
+            let raw = ready!(this.cart.as_mut().poll_fill_buf(cx))?;
+            // if we haven't had to consume anything yet then just check our peeked bytes
+            if this.magic.is_empty() && (raw.is_empty() || raw.len() >= sealed::MAGIC_LEN) {
+                if sealed::is_sealed(raw) {
+                    *this.sealed = Some(Unsealer::default());
+                }
+                break;
+            }
+            // we got a short read so consume bytes until we have our entire magic or hit EOF
+            let take = std::cmp::min(raw.len(), sealed::MAGIC_LEN - this.magic.len());
+            this.magic.extend_from_slice(&raw[..take]);
+            this.cart.as_mut().consume(take);
+            if take == 0 || this.magic.len() == sealed::MAGIC_LEN {
+                if !sealed::is_sealed(this.magic) {
+                    // version 1 files must have their entire header in the first read
+                    return Poll::Ready(Err(std::io::Error::new(
+                        ErrorKind::InvalidData,
+                        "Invalid CaRT file! CaRT header is malformed or missing.",
+                    )));
+                }
+                // feed the magic we already consumed to our unsealer
+                let mut unsealer = Unsealer::default();
+                if let Err(err) = unsealer.feed(this.magic, this.plain) {
+                    return Poll::Ready(Err(std::io::Error::new(ErrorKind::InvalidData, err)));
+                }
+                *this.sealed = Some(unsealer);
+                break;
+            }
         }
+        *this.detected = true;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Try to read from our stream and unseal any data to the internal read buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - The current context
+    /// * `buf` - The internal read buffer to read uncarted data to
+    fn do_poll_sealed(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        // project our struct
+        let mut this = self.project();
+        // keep reading until we have authenticated plaintext to return or the input is exhausted
+        while *this.plain_pos == this.plain.len() {
+            // reset our plaintext buffer
+            this.plain.clear();
+            *this.plain_pos = 0;
+            // read in the next chunk of sealed data
+            let raw = ready!(this.cart.as_mut().poll_fill_buf(cx))?;
+            let unsealer = this.sealed.as_mut().expect("only called when unsealing");
+            if raw.is_empty() {
+                // make sure we read the entire file before signalling that uncarting is complete
+                return match unsealer.finish() {
+                    Ok(()) => Poll::Ready(Ok(())),
+                    Err(err) => Poll::Ready(Err(std::io::Error::new(ErrorKind::InvalidData, err))),
+                };
+            }
+            // authenticate and decompress this data and mark it as consumed
+            let consumed = raw.len();
+            if let Err(err) = unsealer.feed(raw, this.plain) {
+                return Poll::Ready(Err(std::io::Error::new(ErrorKind::InvalidData, err)));
+            }
+            this.cart.as_mut().consume(consumed);
+        }
+        // copy as much of our plaintext as we can to the read buffer
+        let end = std::cmp::min(this.plain.len(), *this.plain_pos + buf.remaining());
+        buf.put_slice(&this.plain[*this.plain_pos..end]);
+        *this.plain_pos = end;
+        Poll::Ready(Ok(()))
     }
 
     /// Try to read from our stream and uncart any data to the internal read buffer
@@ -744,7 +1015,7 @@ impl<R: AsyncBufRead> AsyncRead for UncartStream<R> {
     /// * `cx` - The current context
     /// * `buf` - The internal buffer to store available uncarted data
     fn poll_read(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
@@ -752,8 +1023,16 @@ impl<R: AsyncBufRead> AsyncRead for UncartStream<R> {
         if buf.remaining() == 0 {
             return Poll::Ready(Ok(()));
         }
+        // check which format we are reading if we haven't yet
+        if !self.detected {
+            ready!(self.as_mut().detect(cx))?;
+        }
         // try to read more data
-        self.do_poll_read(cx, buf)
+        if self.sealed.is_some() {
+            self.do_poll_sealed(cx, buf)
+        } else {
+            self.do_poll_read(cx, buf)
+        }
     }
 }
 
@@ -774,6 +1053,10 @@ pub struct CartStreamManual<T: ArrayLength<u8>> {
     rc4: rc4::Rc4<T>,
     /// The vector to store our compressed and encrypted data
     output: Vec<u8>,
+    /// The sealer to use if we are writing a sealed `CaRT` file
+    sealed: Option<Sealer>,
+    /// The number of sealed bytes to buffer before they must be consumed
+    sealed_limit: usize,
 }
 
 impl<T: ArrayLength<u8>> CartStreamManual<T> {
@@ -800,10 +1083,69 @@ impl<T: ArrayLength<u8>> CartStreamManual<T> {
             zlib,
             rc4,
             output,
+            sealed: None,
+            sealed_limit: 0,
+        };
+        Ok(cart)
+    }
+
+    /// Create a new manual cart stream buffer that writes a sealed `CaRT` file
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use when encrypting this data
+    /// * `len` - The size of the buffer to allocate
+    /// * `opts` - The options to seal this file with
+    pub fn sealed(key: &GenericArray<u8, T>, len: usize, opts: SealOptions) -> Result<Self, Error> {
+        // build our sealer first so we validate our key and options
+        let sealer = Sealer::new(key, opts)?;
+        let cart = CartStreamManual {
+            // sealed data is appended to our output buffer as it is sealed
+            skip: 0,
+            on_deck: None,
+            current: None,
+            zlib: Compress::new(Compression::default(), true),
+            rc4: rc4::Rc4::new(key),
+            output: Vec::with_capacity(len),
+            sealed: Some(sealer),
+            sealed_limit: len,
         };
         Ok(cart)
     }
 
+    /// Seal some bytes
+    ///
+    /// Returns true if we have more data to seal from this buffer or our sealed data needs to be
+    /// consumed before we can seal more.
+    ///
+    /// # Arguments
+    ///
+    /// * `finish` - Whether this is the last buffer to seal
+    fn seal_bytes(&mut self, finish: bool) -> Result<bool, Error> {
+        // get our current buffer and sealer
+        let (Some(buff), Some(sealer)) = (self.current.as_mut(), self.sealed.as_mut()) else {
+            return Ok(false);
+        };
+        // if our output buffer is full then just tell the user to consume it
+        if !finish && self.output.len() >= self.sealed_limit {
+            return Ok(true);
+        }
+        if finish {
+            // seal all of our remaining data and our final chunk
+            sealer.push(&buff[..], &mut self.output)?;
+            buff.advance(buff.remaining());
+            sealer.finish(&mut self.output)?;
+        } else {
+            // only seal a single chunk at a time to keep our output buffer bounded
+            let take = std::cmp::min(buff.remaining(), sealer.chunk_size());
+            sealer.push(&buff[..take], &mut self.output)?;
+            buff.advance(take);
+        }
+        // sealed data is always appended to the end of our output buffer
+        self.skip = self.output.len();
+        Ok(buff.has_remaining() || self.skip >= self.sealed_limit)
+    }
+
     /// process some bytes
     ///
     /// Returns true if we have more data to cart from this buffer.
@@ -812,6 +1154,10 @@ impl<T: ArrayLength<u8>> CartStreamManual<T> {
     ///
     /// * `flush` - The flush setting to use
     fn cart_bytes(&mut self, flush: FlushCompress) -> Result<bool, Error> {
+        // seal our data instead if we are writing a sealed file
+        if self.sealed.is_some() {
+            return self.seal_bytes(flush == FlushCompress::Finish);
+        }
         // get our current buffer
         let Some(buff) = self.current.as_mut() else {
             return Ok(false);
@@ -888,6 +1234,10 @@ impl<T: ArrayLength<u8>> CartStreamManual<T> {
     /// Consume our currently carted bytes
     pub fn consume(&mut self) {
         self.skip = 0;
+        // sealed data is appended to our output buffer so drop what was consumed
+        if self.sealed.is_some() {
+            self.output.clear();
+        }
     }
 
     /// Finish packing this file and write the CART footer
@@ -900,6 +1250,10 @@ impl<T: ArrayLength<u8>> CartStreamManual<T> {
         self.current = Some(buff);
         // process our old bytes
         self.cart_bytes(FlushCompress::Finish)?;
+        // sealed files have no footer
+        if self.sealed.is_some() {
+            return Ok(&self.output[..]);
+        }
         // determine if we need to extend our output buffer
         if self.skip + footer::FOOTER_LEN > self.output.capacity() {
             // extend our buffer by the length of the footer
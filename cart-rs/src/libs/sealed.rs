@@ -0,0 +1,656 @@
+//! The sealed (version 2) container format for `CaRT`
+//!
+//! Version 1 `CaRT` files are protected by rc4 and zlib alone, which defangs a file but does
+//! nothing to detect corruption. Sealed files instead split the plaintext into
+//! fixed-size chunks that are each compressed and then encrypted with an AEAD cipher, so every
+//! chunk is authenticated on its own and can be verified as soon as it is read.
+//!
+//! Just like version 1 the key is stored in the header. The goal is to safely store and transfer
+//! malware while detecting accidental corruption, not to keep its contents secret or to stop
+//! deliberate tampering since anyone can reseal a modified file with the same key.
+//!
+//! A sealed file is laid out as follows (all integers are little endian):
+//!
+//! ```text
+//! +-------------------------------------------------------------+
+//! | "CART" | version (u16) | cipher (u8) | reserved (u8)        |
+//! | chunk size (u32) | key (16 bytes) | salt (8 bytes)          |
+//! | metadata length (u32) | metadata (bincode map of strings)  |
+//! +-------------------------------------------------------------+
+//! | length (u32) | flags (u8) | ciphertext + tag                |  <- repeated per chunk
+//! +-------------------------------------------------------------+
+//! ```
+//!
+//! Each chunk is authenticated against a digest of the entire header, its index, and whether it
+//! is the final chunk. This means reordered, dropped, or truncated chunks and modified metadata
+//! are all detected.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::ChaCha20Poly1305;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use std::collections::BTreeMap;
+
+use super::header::{self, KEY_LEN};
+use crate::Error;
+
+/// The version number used by sealed `CaRT` files
+pub const VERSION: u8 = 2;
+/// The length of the fixed portion of a sealed header
+pub const HEADER_LEN: usize = 40;
+/// The length of the record header in front of each sealed chunk
+pub const RECORD_HEADER_LEN: usize = 5;
+/// The length of the salt used to derive keys and nonces
+pub const SALT_LEN: usize = 8;
+/// The length of the authentication tag appended to each chunk
+pub const TAG_LEN: usize = 16;
+/// The default number of plaintext bytes in each chunk
+pub const DEFAULT_CHUNK_SIZE: u32 = 1_048_576;
+/// The largest chunk size we will accept when reading a sealed file
+pub const MAX_CHUNK_SIZE: u32 = 67_108_864;
+/// The largest amount of metadata we will accept when reading a sealed file
+pub const MAX_METADATA_LEN: u32 = 1_048_576;
+/// The record flag marking the final chunk in a file
+const FLAG_LAST: u8 = 1;
+
+/// The number of bytes needed to tell whether a file is a sealed `CaRT` file
+pub const MAGIC_LEN: usize = 6;
+
+/// Check if a buffer starts with a sealed `CaRT` header
+///
+/// # Arguments
+///
+/// * `raw` - The start of a possibly carted file
+#[must_use]
+pub fn is_sealed(raw: &[u8]) -> bool {
+    raw.len() >= MAGIC_LEN && raw[..4] == *header::MAGIC_NUM && raw[4] == VERSION && raw[5] == 0
+}
+
+/// The AEAD ciphers that can be used to seal a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cipher {
+    /// AES-256 in Galois/Counter Mode
+    #[default]
+    AesGcm,
+    /// `ChaCha20` with a Poly1305 authenticator
+    ChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// Get the id used to store this cipher in a sealed header
+    #[must_use]
+    pub fn id(self) -> u8 {
+        match self {
+            Cipher::AesGcm => 1,
+            Cipher::ChaCha20Poly1305 => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for Cipher {
+    type Error = Error;
+
+    /// Get a cipher from its id in a sealed header
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the cipher
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            1 => Ok(Cipher::AesGcm),
+            2 => Ok(Cipher::ChaCha20Poly1305),
+            _ => Err(Error::new(format!("Unknown sealed CaRT cipher {id}"))),
+        }
+    }
+}
+
+/// The options to use when sealing a file
+#[derive(Debug, Clone)]
+pub struct SealOptions {
+    /// The cipher to seal chunks with
+    pub cipher: Cipher,
+    /// The number of plaintext bytes in each chunk
+    pub chunk_size: u32,
+    /// Any metadata to store in the header
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Default for SealOptions {
+    fn default() -> Self {
+        SealOptions {
+            cipher: Cipher::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            metadata: BTreeMap::default(),
+        }
+    }
+}
+
+impl SealOptions {
+    /// Set the cipher to seal chunks with
+    ///
+    /// # Arguments
+    ///
+    /// * `cipher` - The cipher to use
+    #[must_use]
+    pub fn cipher(mut self, cipher: Cipher) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Set the number of plaintext bytes in each chunk
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_size` - The chunk size to use
+    #[must_use]
+    pub fn chunk_size(mut self, chunk_size: u32) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Add a metadata entry to the header
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the metadata to add
+    /// * `value` - The value of the metadata to add
+    #[must_use]
+    pub fn metadata<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the original name of the file being sealed
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the file
+    #[must_use]
+    pub fn file_name<T: Into<String>>(self, name: T) -> Self {
+        self.metadata("name", name)
+    }
+}
+
+/// The header for a sealed `CaRT` file
+#[derive(Debug, Clone)]
+pub struct SealedHeader {
+    /// The cipher used to seal each chunk
+    pub cipher: Cipher,
+    /// The number of plaintext bytes in each chunk
+    pub chunk_size: u32,
+    /// The key used to seal this file
+    pub key: Vec<u8>,
+    /// The random salt used to derive this files cipher key and nonces
+    pub salt: [u8; SALT_LEN],
+    /// Any metadata stored in this header
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl SealedHeader {
+    /// Get the original name of the sealed file if one was stored
+    #[must_use]
+    pub fn file_name(&self) -> Option<&str> {
+        self.metadata.get("name").map(String::as_str)
+    }
+
+    /// Serialize this header
+    ///
+    /// # Errors
+    ///
+    /// If the metadata cannot be encoded or is too large then an error will be returned.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        // encode our metadata
+        let metadata = bincode::encode_to_vec(&self.metadata, bincode::config::standard())?;
+        let metadata_len = u32::try_from(metadata.len())?;
+        if metadata_len > MAX_METADATA_LEN {
+            return Err(Error::new("Sealed CaRT metadata is too large"));
+        }
+        // build our header
+        let mut raw = Vec::with_capacity(HEADER_LEN + metadata.len());
+        raw.extend_from_slice(header::MAGIC_NUM);
+        raw.extend_from_slice(&[VERSION, 0, self.cipher.id(), 0]);
+        raw.extend_from_slice(&self.chunk_size.to_le_bytes());
+        raw.extend_from_slice(&self.key);
+        raw.extend_from_slice(&self.salt);
+        raw.extend_from_slice(&metadata_len.to_le_bytes());
+        raw.extend_from_slice(&metadata);
+        Ok(raw)
+    }
+
+    /// Try to parse a sealed header from the start of a buffer
+    ///
+    /// Returns the header and its length or None if more data is needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The start of a sealed file
+    ///
+    /// # Errors
+    ///
+    /// If this is not a valid sealed header then an error will be returned.
+    pub fn get(raw: &[u8]) -> Result<Option<(Self, usize)>, Error> {
+        // make sure we have enough data to read the fixed portion of our header
+        if raw.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        // make sure this is actually a sealed header
+        if !is_sealed(raw) {
+            return Err(Error::new("File does not start with a sealed CaRT header"));
+        }
+        let cipher = Cipher::try_from(raw[6])?;
+        let chunk_size = read_u32(&raw[8..12]);
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(Error::new(format!(
+                "Sealed CaRT chunk size of {chunk_size} is invalid"
+            )));
+        }
+        let key = raw[12..12 + KEY_LEN].to_vec();
+        let mut salt = [0; SALT_LEN];
+        salt.copy_from_slice(&raw[28..28 + SALT_LEN]);
+        let metadata_len = read_u32(&raw[36..40]);
+        if metadata_len > MAX_METADATA_LEN {
+            return Err(Error::new("Sealed CaRT metadata is too large"));
+        }
+        // make sure we have all of our metadata
+        let end = HEADER_LEN + metadata_len as usize;
+        if raw.len() < end {
+            return Ok(None);
+        }
+        // decode our metadata
+        let (metadata, _) =
+            bincode::decode_from_slice(&raw[HEADER_LEN..end], bincode::config::standard())?;
+        let header = SealedHeader {
+            cipher,
+            chunk_size,
+            key,
+            salt,
+            metadata,
+        };
+        Ok(Some((header, end)))
+    }
+}
+
+/// Read a little endian u32 from a 4 byte slice
+///
+/// # Arguments
+///
+/// * `raw` - The bytes to read
+fn read_u32(raw: &[u8]) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&raw[..4]);
+    u32::from_le_bytes(bytes)
+}
+
+/// A cipher instance keyed for a specific sealed file
+enum Keyed {
+    /// An AES-256-GCM cipher
+    AesGcm(Box<Aes256Gcm>),
+    /// A ChaCha20-Poly1305 cipher
+    ChaCha20Poly1305(Box<ChaCha20Poly1305>),
+}
+
+/// The state needed to seal or open chunks for a single file
+struct ChunkCipher {
+    /// Our keyed cipher
+    keyed: Keyed,
+    /// The salt for this file
+    salt: [u8; SALT_LEN],
+    /// A digest of this files header
+    digest: [u8; 32],
+}
+
+impl ChunkCipher {
+    /// Build the cipher for a file from its header
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header for this file
+    /// * `raw` - The serialized header
+    fn new(header: &SealedHeader, raw: &[u8]) -> Self {
+        // derive our cipher key from the header key and salt
+        let mut hasher = Sha256::new();
+        hasher.input(b"cart-rs sealed v2");
+        hasher.input(&header.key);
+        hasher.input(&header.salt);
+        let mut derived = [0; 32];
+        hasher.result(&mut derived);
+        // get a digest of the entire header so it is authenticated with each chunk
+        let mut hasher = Sha256::new();
+        hasher.input(raw);
+        let mut digest = [0; 32];
+        hasher.result(&mut digest);
+        // build our cipher
+        let keyed = match header.cipher {
+            Cipher::AesGcm => Keyed::AesGcm(Box::new(Aes256Gcm::new(&derived.into()))),
+            Cipher::ChaCha20Poly1305 => {
+                Keyed::ChaCha20Poly1305(Box::new(ChaCha20Poly1305::new(&derived.into())))
+            }
+        };
+        ChunkCipher {
+            keyed,
+            salt: header.salt,
+            digest,
+        }
+    }
+
+    /// Build the nonce and associated data for a chunk
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of this chunk
+    /// * `flags` - The flags for this chunk
+    fn nonce_and_aad(&self, index: u32, flags: u8) -> ([u8; 12], [u8; 37]) {
+        let mut nonce = [0; 12];
+        nonce[..SALT_LEN].copy_from_slice(&self.salt);
+        nonce[SALT_LEN..].copy_from_slice(&index.to_le_bytes());
+        let mut aad = [0; 37];
+        aad[..32].copy_from_slice(&self.digest);
+        aad[32..36].copy_from_slice(&index.to_le_bytes());
+        aad[36] = flags;
+        (nonce, aad)
+    }
+
+    /// Seal a single chunk
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of this chunk
+    /// * `flags` - The flags for this chunk
+    /// * `compressed` - The compressed chunk to seal
+    fn seal(&self, index: u32, flags: u8, compressed: &[u8]) -> Result<Vec<u8>, Error> {
+        let (nonce, aad) = self.nonce_and_aad(index, flags);
+        let payload = Payload {
+            msg: compressed,
+            aad: &aad,
+        };
+        let sealed = match &self.keyed {
+            Keyed::AesGcm(cipher) => cipher.encrypt(&nonce.into(), payload),
+            Keyed::ChaCha20Poly1305(cipher) => cipher.encrypt(&nonce.into(), payload),
+        };
+        sealed.map_err(|_| Error::new(format!("Failed to seal chunk {index}")))
+    }
+
+    /// Open and authenticate a single chunk
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of this chunk
+    /// * `flags` - The flags for this chunk
+    /// * `sealed` - The sealed chunk to open
+    fn open(&self, index: u32, flags: u8, sealed: &[u8]) -> Result<Vec<u8>, Error> {
+        let (nonce, aad) = self.nonce_and_aad(index, flags);
+        let payload = Payload {
+            msg: sealed,
+            aad: &aad,
+        };
+        let opened = match &self.keyed {
+            Keyed::AesGcm(cipher) => cipher.decrypt(&nonce.into(), payload),
+            Keyed::ChaCha20Poly1305(cipher) => cipher.decrypt(&nonce.into(), payload),
+        };
+        opened.map_err(|_| Error::Authentication(u64::from(index)))
+    }
+}
+
+/// Seals a stream of data into the sealed `CaRT` format
+///
+/// Data is buffered until a full chunk is available. The final chunk is only sealed once
+/// [`Sealer::finish`] is called so it can be marked as the last chunk in the file.
+pub struct Sealer {
+    /// The serialized header that still needs to be written
+    header: Option<Vec<u8>>,
+    /// The cipher for this file
+    cipher: ChunkCipher,
+    /// The number of plaintext bytes in each chunk
+    chunk_size: usize,
+    /// The plaintext that has not been sealed yet
+    pending: Vec<u8>,
+    /// The index of the next chunk to seal
+    index: u32,
+}
+
+impl Sealer {
+    /// Create a new sealer
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The 16 byte key to store in the header
+    /// * `opts` - The options to seal with
+    ///
+    /// # Errors
+    ///
+    /// If the key or chunk size is invalid then an error will be returned.
+    pub fn new(key: &[u8], opts: SealOptions) -> Result<Self, Error> {
+        header::Header::validate_key(key)?;
+        if opts.chunk_size == 0 || opts.chunk_size > MAX_CHUNK_SIZE {
+            return Err(Error::new(format!(
+                "Sealed CaRT chunk size must be between 1 and {MAX_CHUNK_SIZE} bytes"
+            )));
+        }
+        // generate a random salt for this file
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        // build and serialize our header
+        let header = SealedHeader {
+            cipher: opts.cipher,
+            chunk_size: opts.chunk_size,
+            key: key.to_vec(),
+            salt,
+            metadata: opts.metadata,
+        };
+        let raw = header.to_bytes()?;
+        let cipher = ChunkCipher::new(&header, &raw);
+        let chunk_size = opts.chunk_size as usize;
+        Ok(Sealer {
+            header: Some(raw),
+            cipher,
+            chunk_size,
+            pending: Vec::with_capacity(chunk_size),
+            index: 0,
+        })
+    }
+
+    /// Get the number of plaintext bytes in each chunk
+    #[must_use]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Compress, seal, and write a chunk to an output buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `plain` - The plaintext chunk to seal
+    /// * `flags` - The flags for this chunk
+    /// * `out` - The buffer to write our sealed record to
+    fn write_chunk(&mut self, plain: &[u8], flags: u8, out: &mut Vec<u8>) -> Result<(), Error> {
+        // compress and seal this chunk
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(plain, 6);
+        let sealed = self.cipher.seal(self.index, flags, &compressed)?;
+        // write this record
+        out.try_reserve(RECORD_HEADER_LEN + sealed.len())?;
+        out.extend_from_slice(&u32::try_from(sealed.len())?.to_le_bytes());
+        out.push(flags);
+        out.extend_from_slice(&sealed);
+        // move to the next chunk
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| Error::new("Too many chunks to seal in a single CaRT file"))?;
+        Ok(())
+    }
+
+    /// Add more data to seal, writing any completed chunks to an output buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to seal
+    /// * `out` - The buffer to write sealed data to
+    ///
+    /// # Errors
+    ///
+    /// If a chunk fails to be sealed then an error will be returned.
+    pub fn push(&mut self, mut data: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        // write our header if we haven't yet
+        if let Some(header) = self.header.take() {
+            out.extend_from_slice(&header);
+        }
+        while !data.is_empty() {
+            // seal our pending chunk only once we know it isn't the last chunk
+            if self.pending.len() == self.chunk_size {
+                let pending = std::mem::take(&mut self.pending);
+                self.write_chunk(&pending, 0, out)?;
+                self.pending = pending;
+                self.pending.clear();
+            }
+            // fill our pending chunk
+            let take = std::cmp::min(self.chunk_size - self.pending.len(), data.len());
+            self.pending.extend_from_slice(&data[..take]);
+            data = &data[take..];
+        }
+        Ok(())
+    }
+
+    /// Seal the final chunk and write it to an output buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The buffer to write sealed data to
+    ///
+    /// # Errors
+    ///
+    /// If the final chunk fails to be sealed then an error will be returned.
+    pub fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), Error> {
+        // write our header if we haven't yet
+        if let Some(header) = self.header.take() {
+            out.extend_from_slice(&header);
+        }
+        let pending = std::mem::take(&mut self.pending);
+        self.write_chunk(&pending, FLAG_LAST, out)
+    }
+}
+
+/// Opens a stream of sealed `CaRT` data, authenticating each chunk as it is read
+#[derive(Default)]
+pub struct Unsealer {
+    /// The header for this file once it has been read
+    header: Option<SealedHeader>,
+    /// The cipher for this file once its header has been read
+    cipher: Option<ChunkCipher>,
+    /// Any sealed data that has not been processed yet
+    buffered: Vec<u8>,
+    /// The index of the next chunk to open
+    index: u32,
+    /// Whether the final chunk has been opened
+    finished: bool,
+}
+
+impl Unsealer {
+    /// Get this files header if it has been read
+    #[must_use]
+    pub fn header(&self) -> Option<&SealedHeader> {
+        self.header.as_ref()
+    }
+
+    /// Whether the final chunk in this file has been opened
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Add more sealed data, writing any authenticated plaintext to an output buffer
+    ///
+    /// Plaintext is only written once the entire chunk containing it has been authenticated.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The sealed data to add
+    /// * `out` - The buffer to write plaintext to
+    ///
+    /// # Errors
+    ///
+    /// If a chunk fails authentication or the file is malformed then an error will be returned.
+    pub fn feed(&mut self, raw: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        self.buffered.try_reserve(raw.len())?;
+        self.buffered.extend_from_slice(raw);
+        // track how much of our buffer we have processed
+        let mut start = 0;
+        // read our header if we haven't yet
+        if self.cipher.is_none() {
+            let Some((header, len)) = SealedHeader::get(&self.buffered)? else {
+                return Ok(());
+            };
+            self.cipher = Some(ChunkCipher::new(&header, &self.buffered[..len]));
+            self.header = Some(header);
+            start = len;
+        }
+        // get the largest a sealed chunk can be
+        let max_sealed = self.max_sealed_len();
+        // get our cipher and header
+        let (Some(cipher), Some(header)) = (self.cipher.as_ref(), self.header.as_ref()) else {
+            return Ok(());
+        };
+        // open any complete records we have
+        while self.buffered.len() - start >= RECORD_HEADER_LEN {
+            // make sure nothing follows our final chunk
+            if self.finished {
+                return Err(Error::new("Sealed CaRT has data after its final chunk"));
+            }
+            let record = &self.buffered[start..];
+            let len = read_u32(&record[..4]) as usize;
+            let flags = record[4];
+            if len > max_sealed || len < TAG_LEN || flags & !FLAG_LAST != 0 {
+                return Err(Error::new(format!(
+                    "Sealed CaRT chunk {} is malformed",
+                    self.index
+                )));
+            }
+            // wait for more data if we don't have this entire record
+            if record.len() < RECORD_HEADER_LEN + len {
+                break;
+            }
+            // open and decompress this chunk
+            let sealed = &record[RECORD_HEADER_LEN..RECORD_HEADER_LEN + len];
+            let compressed = cipher.open(self.index, flags, sealed)?;
+            let plain = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(
+                &compressed,
+                header.chunk_size as usize,
+            )
+            .map_err(|_| Error::new(format!("Sealed CaRT chunk {} is corrupt", self.index)))?;
+            out.try_reserve(plain.len())?;
+            out.extend_from_slice(&plain);
+            // move to the next record
+            start += RECORD_HEADER_LEN + len;
+            self.finished = flags & FLAG_LAST != 0;
+            self.index = self.index.wrapping_add(1);
+        }
+        // drop the data we have processed
+        self.buffered.drain(..start);
+        Ok(())
+    }
+
+    /// Make sure the entire sealed file was read
+    ///
+    /// # Errors
+    ///
+    /// If the file was truncated then an error will be returned.
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.cipher.is_none() {
+            return Err(Error::new(
+                "Invalid CaRT file! Sealed CaRT header is malformed or missing.",
+            ));
+        }
+        if !self.finished || !self.buffered.is_empty() {
+            return Err(Error::new("Sealed CaRT file is truncated"));
+        }
+        Ok(())
+    }
+
+    /// Get the largest a sealed chunk can be for this file
+    fn max_sealed_len(&self) -> usize {
+        let chunk_size = self.header.as_ref().map_or(0, |header| header.chunk_size) as usize;
+        // zlib can expand incompressible data slightly so leave room for that
+        chunk_size + chunk_size / 64 + 1024 + TAG_LEN
+    }
+}
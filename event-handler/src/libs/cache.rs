@@ -375,6 +375,11 @@ impl DataCache {
                 }
             }
             (EventData::NewTags { .. }, EventTrigger::NewSample) => false,
+            (EventData::NewTags { .. }, EventTrigger::NewCommitish { .. }) => false,
+            // commitish triggers can be fully checked without any extra data
+            (EventData::NewCommitish { .. }, _) => {
+                matches!(event.could_trigger(trigger), TriggerPotential::Confirmed)
+            }
         }
     }
 
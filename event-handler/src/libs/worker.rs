@@ -6,8 +6,8 @@ use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 use thorium::models::{
-    Event, EventData, EventIds, EventPopOpts, EventType, ReactionRequest, RepoDependencyRequest,
-    TagType,
+    CommitishKinds, Event, EventData, EventIds, EventPopOpts, EventType, ReactionRequest,
+    RepoDependencyRequest, TagType,
 };
 use thorium::{Error, Thorium};
 use tracing::{event, instrument, Level};
@@ -172,7 +172,7 @@ impl EventWorker {
             // get the new depth for this event
             let depth = event.depth + 1;
             // create reactions for each of the confirmed triggers from this event
-            for (group, pipeline, _) in triggers {
+            for (group, pipeline, trigger) in triggers {
                 // build the base reaction request for this trigger
                 let req = ReactionRequest::new(group, pipeline).trigger_depth(depth);
                 // add our dependency info
@@ -185,6 +185,16 @@ impl EventWorker {
                             TagType::Repos => req.repo(RepoDependencyRequest::new(item)),
                         }
                     }
+                    EventData::NewCommitish { repo, .. } => {
+                        // run against the commit that met this trigger if we can
+                        let repo_req = match event.commitish_target(trigger) {
+                            Some(commit) => RepoDependencyRequest::new(repo)
+                                .commitish(commit)
+                                .kind(CommitishKinds::Commit),
+                            None => RepoDependencyRequest::new(repo),
+                        };
+                        req.repo(repo_req)
+                    }
                 };
                 // get an entry to this users reaction requests
                 let entry: &mut Vec<ReactionRequest> = reqs.entry(event.user.clone()).or_default();
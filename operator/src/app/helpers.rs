@@ -10,6 +10,7 @@ use aws_sdk_s3::error::SdkError;
 use aws_sdk_s3::operation::create_bucket::CreateBucketError;
 
 use crate::k8s::clusters::ClusterMeta;
+use crate::k8s::services;
 
 /// Build an API url string
 ///
@@ -35,6 +36,30 @@ pub fn get_thorium_host(meta: &ClusterMeta, url: Option<&String>) -> String {
     }
 }
 
+/// Build an API preview url string
+///
+/// The preview service routes traffic to new API pods while they are being rolled out. Like
+/// [`get_thorium_host`] the url string will be none outside of a development environment.
+///
+/// # Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `url` - The Thorium API preview URL passed to the operator as an argument
+pub fn get_thorium_preview_host(meta: &ClusterMeta, url: Option<&String>) -> String {
+    match url {
+        // grab url if passed to the operator as an arg, mostly for development
+        Some(url) => url.to_owned(),
+        // use internal k8s networking by default
+        None => {
+            format!(
+                "http://{}.{}.svc.cluster.local:80",
+                services::API_PREVIEW_SERVICE,
+                &meta.namespace
+            )
+        }
+    }
+}
+
 /// Create an S3 bucket
 ///
 /// # Arguments
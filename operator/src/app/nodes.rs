@@ -1,5 +1,5 @@
 use thorium::{
-    models::{NodeRegistration, Resources},
+    models::{CpuArch, NodeRegistration, Resources},
     Error, Thorium,
 };
 
@@ -30,6 +30,9 @@ pub async fn add_nodes_to_thorium(meta: &ClusterMeta, thorium: &Thorium) -> Resu
                 },
                 name: node.clone(),
                 resources: resources,
+                // the scaler will detect the real architectures from each nodes labels
+                arch: CpuArch::default(),
+                emulated: Vec::default(),
             };
             // register node config w/ Thorium API
             let reg_result = thorium.system.register_node(&node_reg).await?;
@@ -1,9 +1,444 @@
+use std::collections::HashMap;
 use thorium::Error;
+use thorium::client::Basic;
+use thorium::models::ResponseCounts;
+use tokio::time::Duration;
 
 use crate::k8s::clusters::ClusterMeta;
+use crate::k8s::crds::{ApiColor, ApiRolloutSettings, ApiRolloutStatus, RolloutPhase};
+use crate::k8s::{deployments, jobs, services};
+
+/// How often to check the health of a new API version while watching it
+const WATCH_INTERVAL_SECS: u64 = 5u64;
+
+/// The least number of responses new API pods must send before their error rate is checked
+const MIN_WATCHED_RESPONSES: u64 = 20u64;
 
 /// Upgrade handler for cluster version changes
 pub async fn handler(_meta: &ClusterMeta) -> Result<(), Error> {
     println!("Upgrading thorium version... this is just a stub");
     Ok(())
 }
+
+/// The outcome of rolling out the Thorium API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RolloutOutcome {
+    /// The API was already running the desired revision
+    Current,
+    /// A new API revision was rolled out and is serving all traffic
+    Complete,
+    /// The new API revision failed and the previous revision is still serving traffic
+    RolledBack,
+    /// The new API revision failed and there is no previous revision to serve traffic
+    Unavailable,
+}
+
+/// Tracks and reports the progress of an API rollout
+struct Rollout<'a> {
+    /// Thorium cluster client and metadata
+    meta: &'a ClusterMeta,
+    /// The current status of this rollout
+    status: ApiRolloutStatus,
+}
+
+impl Rollout<'_> {
+    /// Move this rollout to a new phase and report it on the ThoriumCluster status
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The phase to move to
+    /// * `message` - A message describing this rollouts progress
+    async fn update(&mut self, phase: RolloutPhase, message: String) -> Result<(), Error> {
+        println!("API rollout {:?}: {}", phase, message);
+        self.status.phase = phase;
+        self.status.message = Some(message);
+        self.status.updated = chrono::Utc::now().timestamp();
+        self.meta.update_rollout(&self.status).await
+    }
+}
+
+/// Run a set of smoke tests against an API
+///
+/// # Arguments
+///
+/// * `client` - The client to send requests with
+/// * `host` - The API to smoke test
+/// * `settings` - The rollout settings containing the routes to smoke test
+async fn smoke_test(
+    client: &reqwest::Client,
+    host: &str,
+    settings: &ApiRolloutSettings,
+) -> Result<(), Error> {
+    for route in &settings.smoke_tests {
+        let url = format!("{}{}", host.trim_end_matches('/'), route);
+        match client.get(&url).send().await {
+            Ok(resp) if resp.status().is_success() => (),
+            Ok(resp) => {
+                return Err(Error::new(format!(
+                    "Smoke test {} returned {}",
+                    url,
+                    resp.status()
+                )));
+            }
+            Err(error) => return Err(Error::new(format!("Smoke test {} failed: {}", url, error))),
+        }
+    }
+    Ok(())
+}
+
+/// Wait for a set amount of time for new API pods to be ready and pass their smoke tests
+///
+/// # Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `color` - The color of the API pods to verify
+/// * `preview` - The url of the preview service for the API pods to verify
+/// * `replicas` - The number of API pods that must be ready
+/// * `settings` - The rollout settings to use
+async fn verify(
+    meta: &ClusterMeta,
+    color: ApiColor,
+    preview: &str,
+    replicas: u16,
+    settings: &ApiRolloutSettings,
+) -> Result<(), Error> {
+    let selector = format!(
+        "app={},color={}",
+        deployments::API_APP_LABEL,
+        color.as_str()
+    );
+    // wait for our pods to be running and ready
+    deployments::timeout_wait_for_ready(
+        meta,
+        &selector,
+        replicas as usize,
+        settings.health_timeout,
+    )
+    .await?;
+    // keep smoke testing our new pods until they pass or we time out
+    let client = reqwest::Client::new();
+    let one_second = Duration::from_secs(1);
+    let mut last_error = None;
+    let smoke_future = async {
+        loop {
+            match smoke_test(&client, preview, settings).await {
+                Ok(()) => return,
+                Err(error) => {
+                    println!(
+                        "Waiting for {} API pods to pass smoke tests: {}",
+                        color.as_str(),
+                        error
+                    );
+                    last_error = Some(error);
+                    tokio::time::sleep(one_second).await;
+                }
+            }
+        }
+    };
+    match tokio::time::timeout(Duration::from_secs(settings.health_timeout), smoke_future).await {
+        Ok(()) => Ok(()),
+        Err(_) => Err(last_error.unwrap_or_else(|| {
+            Error::new(format!(
+                "{} API pods did not pass smoke tests within {} seconds",
+                color.as_str(),
+                settings.health_timeout
+            ))
+        })),
+    }
+}
+
+/// Get how many responses each API pod has sent
+///
+/// Pods that can't be reached are skipped since failed health checks already catch them.
+///
+/// # Arguments
+///
+/// * `client` - The client to send requests with
+/// * `ips` - The IPs of the API pods to check
+async fn response_counts(
+    client: &reqwest::Client,
+    ips: &[String],
+) -> HashMap<String, ResponseCounts> {
+    let mut counts = HashMap::with_capacity(ips.len());
+    for ip in ips {
+        let basic = Basic::new(&format!("http://{ip}"), client);
+        match basic.responses().await {
+            Ok(count) => {
+                counts.insert(ip.clone(), count);
+            }
+            Err(error) => println!(
+                "Failed to get response counts from API pod {}: {}",
+                ip, error
+            ),
+        }
+    }
+    counts
+}
+
+/// Watch new API pods after switching traffic to them
+///
+/// An error is returned if any new API pod restarts, too many health checks fail, or too many of
+/// the responses sent by the new pods are server errors.
+///
+/// # Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `color` - The color of the API pods to watch
+/// * `host` - The url of the API service
+/// * `replicas` - The number of API pods that should be ready
+/// * `settings` - The rollout settings to use
+async fn watch(
+    meta: &ClusterMeta,
+    color: ApiColor,
+    host: &str,
+    replicas: u16,
+    settings: &ApiRolloutSettings,
+) -> Result<(), Error> {
+    let selector = format!(
+        "app={},color={}",
+        deployments::API_APP_LABEL,
+        color.as_str()
+    );
+    // get how many times our pods have restarted so far
+    let baseline = deployments::pod_health(meta, &selector).await?.restarts;
+    // build a client to check the health of our API
+    let client = reqwest::Client::new();
+    let basic = Basic::new(host, &client);
+    // get how many responses our pods have sent before we start watching them
+    let ips = deployments::ready_pod_ips(meta, &selector).await?;
+    let responses_baseline = response_counts(&client, &ips).await;
+    // determine how many checks to run over our watch window
+    let checks = std::cmp::max(settings.watch_window / WATCH_INTERVAL_SECS, 1);
+    let mut failures = 0;
+    for _ in 0..checks {
+        tokio::time::sleep(Duration::from_secs(WATCH_INTERVAL_SECS)).await;
+        // any restarts mean our new API is crashing
+        let health = deployments::pod_health(meta, &selector).await?;
+        if health.restarts > baseline {
+            return Err(Error::new(format!(
+                "{} API pods restarted {} times",
+                color.as_str(),
+                health.restarts - baseline
+            )));
+        }
+        // check that all of our pods are still ready and that the API is healthy
+        let healthy = health.ready >= replicas as usize && basic.health().await.unwrap_or(false);
+        if !healthy {
+            failures += 1;
+        }
+        // bail early if we have already failed too many checks
+        if failures * 100 > u64::from(settings.max_failed_check_percent) * checks {
+            return Err(Error::new(format!(
+                "{} API pods failed {} of {} health checks",
+                color.as_str(),
+                failures,
+                checks
+            )));
+        }
+        // count the responses our pods have sent since we started watching them
+        let ips = deployments::ready_pod_ips(meta, &selector).await?;
+        let (total, server_errors) = response_counts(&client, &ips).await.iter().fold(
+            (0, 0),
+            |(total, server_errors), (ip, count)| {
+                // pods that weren't ready when we started sent all of their responses since then
+                let start = responses_baseline.get(ip).copied().unwrap_or_default();
+                (
+                    total + count.total.saturating_sub(start.total),
+                    server_errors + count.server_errors.saturating_sub(start.server_errors),
+                )
+            },
+        );
+        // bail if too many of these responses were server errors
+        if total >= MIN_WATCHED_RESPONSES
+            && server_errors * 100 > u64::from(settings.max_error_percent) * total
+        {
+            return Err(Error::new(format!(
+                "{} API pods returned server errors for {} of {} responses",
+                color.as_str(),
+                server_errors,
+                total
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Roll out the Thorium API using a blue/green deployment
+///
+/// The Scylla schema is migrated for the new version before any new API pods are started. New
+/// API pods are then brought up alongside the current ones and must become ready and pass a set
+/// of smoke tests through a preview service before any traffic is switched to them. Once traffic
+/// is switched the new pods are watched for a window of time and traffic is switched back if they
+/// restart, fail too many health checks or return too many server errors. The previous API pods
+/// are only scaled down once the new pods have made it through this window.
+///
+/// # Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `host` - The url of the API service
+/// * `preview` - The url of the API preview service
+pub async fn rollout_api(
+    meta: &ClusterMeta,
+    host: &str,
+    preview: &str,
+) -> Result<RolloutOutcome, Error> {
+    // if the api is not in our cluster spec then clean up any api deployments
+    let Some(api_spec) = meta.cluster.get_api_spec() else {
+        deployments::delete_api(meta).await?;
+        return Ok(RolloutOutcome::Current);
+    };
+    let settings = &api_spec.rollout;
+    let revision = meta.cluster.get_api_revision().unwrap_or_default();
+    let version = meta.cluster.get_version();
+    // get the status of our last rollout
+    let previous = meta.cluster.get_api_rollout().cloned();
+    let active_color = previous.as_ref().and_then(|status| status.active_color);
+    let active_revision = previous
+        .as_ref()
+        .and_then(|status| status.active_revision.clone());
+    // clusters deployed before blue/green rollouts will have a single api deployment
+    let legacy = deployments::exists("api", meta).await?;
+    // check if our active api pods are already running this revision
+    let active = match active_color {
+        Some(color) => deployments::exists(&color.deployment(), meta).await?,
+        None => false,
+    };
+    if let (true, Some(color)) = (active, active_color) {
+        if active_revision.as_ref() == Some(&revision) {
+            // make sure the active api is scaled correctly
+            deployments::scale(&color.deployment(), api_spec.replicas, meta).await?;
+            return Ok(RolloutOutcome::Current);
+        }
+    }
+    // track whether there are api pods to fall back to if this rollout fails
+    let serving = active || legacy;
+    // don't retry a revision that already failed until the spec changes
+    let failed_revision = previous
+        .as_ref()
+        .and_then(|status| status.failed_revision.clone());
+    if serving && failed_revision.as_ref() == Some(&revision) {
+        println!(
+            "API revision {} previously failed to roll out, skipping until the API spec changes",
+            revision
+        );
+        return Ok(RolloutOutcome::RolledBack);
+    }
+    // roll out to whichever color is not currently serving traffic
+    let target = active_color.map_or(ApiColor::Blue, |color| color.other());
+    let mut rollout = Rollout {
+        meta,
+        status: ApiRolloutStatus {
+            phase: RolloutPhase::Deploying,
+            active_color,
+            active_revision,
+            active_version: previous
+                .as_ref()
+                .and_then(|status| status.active_version.clone()),
+            target_revision: revision.clone(),
+            target_version: version.clone(),
+            failed_revision,
+            message: None,
+            updated: chrono::Utc::now().timestamp(),
+        },
+    };
+    rollout
+        .update(
+            RolloutPhase::Migrating,
+            format!("Migrating the Scylla schema for {}", version),
+        )
+        .await?;
+    // migrate our schema before any new api pods are started
+    if let Err(error) = jobs::migrate(meta, settings.migration_timeout).await {
+        rollout.status.failed_revision = Some(revision);
+        rollout
+            .update(
+                RolloutPhase::Failed,
+                format!("{} failed to migrate the Scylla schema: {}", version, error),
+            )
+            .await?;
+        if serving {
+            return Ok(RolloutOutcome::RolledBack);
+        }
+        return Ok(RolloutOutcome::Unavailable);
+    }
+    rollout
+        .update(
+            RolloutPhase::Deploying,
+            format!("Deploying {} to {} API pods", version, target.as_str()),
+        )
+        .await?;
+    // bring up our new api pods and a preview service to test them through
+    deployments::deploy_api(meta, target).await?;
+    services::create_or_update_preview(meta, target).await?;
+    rollout
+        .update(
+            RolloutPhase::Verifying,
+            format!("Verifying {} API pods", target.as_str()),
+        )
+        .await?;
+    // make sure our new pods are healthy before sending any traffic to them
+    if let Err(error) = verify(meta, target, preview, api_spec.replicas, settings).await {
+        // tear down our failed pods and leave the current pods serving traffic
+        deployments::scale(&target.deployment(), 0, meta).await?;
+        services::delete_one(meta, services::API_PREVIEW_SERVICE).await?;
+        rollout.status.failed_revision = Some(revision);
+        rollout
+            .update(
+                RolloutPhase::Failed,
+                format!("{} failed verification: {}", version, error),
+            )
+            .await?;
+        if serving {
+            return Ok(RolloutOutcome::RolledBack);
+        }
+        return Ok(RolloutOutcome::Unavailable);
+    }
+    // switch traffic to our new pods
+    services::switch_api(meta, Some(target)).await?;
+    // only watch our new pods if there are old pods to roll back to
+    if serving {
+        rollout
+            .update(
+                RolloutPhase::Watching,
+                format!(
+                    "Watching {} API pods for {} seconds",
+                    target.as_str(),
+                    settings.watch_window
+                ),
+            )
+            .await?;
+        if let Err(error) = watch(meta, target, host, api_spec.replicas, settings).await {
+            // switch traffic back to our old pods and tear down our new ones
+            services::switch_api(meta, active_color).await?;
+            deployments::scale(&target.deployment(), 0, meta).await?;
+            services::delete_one(meta, services::API_PREVIEW_SERVICE).await?;
+            rollout.status.failed_revision = Some(revision);
+            rollout
+                .update(
+                    RolloutPhase::RolledBack,
+                    format!("Rolled back {}: {}", version, error),
+                )
+                .await?;
+            return Ok(RolloutOutcome::RolledBack);
+        }
+    }
+    // our new pods are healthy so scale down the old ones but keep them around for manual rollbacks
+    if let Some(color) = active_color {
+        deployments::scale(&color.deployment(), 0, meta).await?;
+    }
+    if legacy {
+        deployments::delete_one("api", meta).await?;
+    }
+    services::delete_one(meta, services::API_PREVIEW_SERVICE).await?;
+    rollout.status.active_color = Some(target);
+    rollout.status.active_revision = Some(revision);
+    rollout.status.active_version = Some(version.clone());
+    rollout.status.failed_revision = None;
+    rollout
+        .update(
+            RolloutPhase::Complete,
+            format!("{} API pods are serving {}", target.as_str(), version),
+        )
+        .await?;
+    Ok(RolloutOutcome::Complete)
+}
@@ -23,4 +23,7 @@ pub struct OperateCluster {
     /// Thorium URL when not running local to k8s
     #[clap(short, long)]
     pub url: Option<String>,
+    /// Thorium preview URL used to smoke test new API pods when not running local to k8s
+    #[clap(short, long)]
+    pub preview_url: Option<String>,
 }
@@ -3,6 +3,7 @@ pub mod config_maps;
 pub mod controller;
 pub mod crds;
 pub mod deployments;
+pub mod jobs;
 pub mod namespaces;
 pub mod nodes;
 pub mod operate;
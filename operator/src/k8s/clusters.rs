@@ -1,7 +1,9 @@
 use k8s_openapi::api::{
     apps::v1::Deployment,
+    batch::v1::Job,
     core::v1::{ConfigMap, Node, Pod, Secret, Service},
 };
+use kube::api::{Patch, PatchParams};
 use kube::{Api, Client};
 use std::sync::Arc;
 use thorium::Error;
@@ -23,6 +25,8 @@ pub struct ClusterMeta {
     pub cm_api: Api<ConfigMap>,
     /// k8s api instance for Deployments
     pub deploy_api: Api<Deployment>,
+    /// k8s api instance for Jobs
+    pub job_api: Api<Job>,
     /// k8s api instance for Nodes
     pub node_api: Api<Node>,
     /// k8s api instance for Pods
@@ -31,6 +35,8 @@ pub struct ClusterMeta {
     pub secret_api: Api<Secret>,
     /// k8s api instance for Services
     pub service_api: Api<Service>,
+    /// k8s api instance for ThoriumClusters
+    pub cluster_api: Api<crds::ThoriumCluster>,
 }
 
 impl ClusterMeta {
@@ -63,10 +69,12 @@ impl ClusterMeta {
         // build kube api client
         let cm_api: Api<ConfigMap> = Api::namespaced(client.clone(), &namespace);
         let deploy_api: Api<Deployment> = Api::namespaced(client.clone(), &namespace);
+        let job_api: Api<Job> = Api::namespaced(client.clone(), &namespace);
         let node_api: Api<Node> = Api::all(client.clone());
         let pod_api: Api<Pod> = Api::namespaced(client.clone(), &namespace);
         let secret_api: Api<Secret> = Api::namespaced(client.clone(), &namespace);
         let service_api: Api<Service> = Api::namespaced(client.clone(), &namespace);
+        let cluster_api: Api<crds::ThoriumCluster> = Api::namespaced(client.clone(), &namespace);
         // return the built cluster
         Ok(ClusterMeta {
             name: name,
@@ -75,10 +83,37 @@ impl ClusterMeta {
             cluster: cluster.clone(),
             cm_api,
             deploy_api,
+            job_api,
             node_api,
             pod_api,
             secret_api,
             service_api,
+            cluster_api,
         })
     }
+
+    /// Update the API rollout status of this ThoriumCluster
+    ///
+    /// # Arguments
+    ///
+    /// * `rollout` - The rollout status to save
+    pub async fn update_rollout(&self, rollout: &crds::ApiRolloutStatus) -> Result<(), Error> {
+        let patch = serde_json::json!({
+            "status": {
+                "api_rollout": rollout
+            }
+        });
+        let params = PatchParams::default();
+        match self
+            .cluster_api
+            .patch_status(&self.name, &params, &Patch::Merge(&patch))
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(Error::new(format!(
+                "Failed to update rollout status for {} ThoriumCluster: {}",
+                &self.name, error
+            ))),
+        }
+    }
 }
@@ -27,6 +27,8 @@ pub struct State {
     client: Client,
     /// ingress route for Thorium API
     url: Option<String>,
+    /// ingress route for the Thorium API preview service
+    preview_url: Option<String>,
 }
 
 /// Methods operating on controller state
@@ -63,7 +65,9 @@ pub async fn reconcile(cluster: Arc<ThoriumCluster>, state: Arc<State>) -> Resul
     );
     finalizer(&clusters_api, crds::CRD_NAME, cluster, |event| async {
         match event {
-            Finalizer::Apply(_cluster) => operate::apply(&meta, state.url.clone()).await,
+            Finalizer::Apply(_cluster) => {
+                operate::apply(&meta, state.url.clone(), state.preview_url.clone()).await
+            }
             Finalizer::Cleanup(_cluster) => operate::cleanup(&meta).await,
         }
     })
@@ -102,6 +106,7 @@ pub async fn run(args: &OperateCluster) {
     let state = State {
         client: client.clone(),
         url: args.url.clone(),
+        preview_url: args.preview_url.clone(),
     };
     // create the ThoriumCluster controller to watch for resource changes
     Controller::new(clusters_api, Config::default().any_semantic())
@@ -12,6 +12,7 @@ use kube_derive::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use thorium::Error;
 
@@ -110,6 +111,76 @@ fn default_api_resources() -> Resources {
     }
 }
 
+/// Serde helper for the default number of seconds to wait for new API pods to become healthy
+fn default_health_timeout() -> u64 {
+    300
+}
+
+/// Serde helper for the default number of seconds to watch a new API version before committing to it
+fn default_watch_window() -> u64 {
+    300
+}
+
+/// Serde helper for the default percent of failed health checks that triggers a rollback
+fn default_max_failed_check_percent() -> u8 {
+    5
+}
+
+/// Serde helper for the default percent of server error responses that triggers a rollback
+fn default_max_error_percent() -> u8 {
+    1
+}
+
+/// Serde helper for the default routes to smoke test new API pods with
+fn default_smoke_tests() -> Vec<String> {
+    vec![
+        "/api/".to_owned(),
+        "/api/health".to_owned(),
+        "/api/version".to_owned(),
+    ]
+}
+
+/// Serde helper for the default time to wait for schema migrations in seconds
+fn default_migration_timeout() -> u64 {
+    600
+}
+
+/// The settings for blue/green rollouts of the Thorium API
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, Eq, PartialEq)]
+pub struct ApiRolloutSettings {
+    /// How long to wait in seconds for new API pods to pass health and smoke tests
+    #[serde(default = "default_health_timeout")]
+    pub health_timeout: u64,
+    /// How long to watch a new API version in seconds after switching traffic to it
+    #[serde(default = "default_watch_window")]
+    pub watch_window: u64,
+    /// The percent of failed health checks during the watch window that will trigger a rollback
+    #[serde(default = "default_max_failed_check_percent")]
+    pub max_failed_check_percent: u8,
+    /// The percent of responses from new API pods during the watch window that may be server errors
+    #[serde(default = "default_max_error_percent")]
+    pub max_error_percent: u8,
+    /// The API routes that must succeed before traffic is switched to new API pods
+    #[serde(default = "default_smoke_tests")]
+    pub smoke_tests: Vec<String>,
+    /// How long to wait in seconds for Scylla schema migrations to finish
+    #[serde(default = "default_migration_timeout")]
+    pub migration_timeout: u64,
+}
+
+impl Default for ApiRolloutSettings {
+    fn default() -> Self {
+        ApiRolloutSettings {
+            health_timeout: default_health_timeout(),
+            watch_window: default_watch_window(),
+            max_failed_check_percent: default_max_failed_check_percent(),
+            max_error_percent: default_max_error_percent(),
+            smoke_tests: default_smoke_tests(),
+            migration_timeout: default_migration_timeout(),
+        }
+    }
+}
+
 /// Thorium API spec
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, Eq, PartialEq)]
 pub struct ThoriumApi {
@@ -131,6 +202,9 @@ pub struct ThoriumApi {
     /// The CPU and Memory needed by the API
     #[serde(default = "default_api_resources")]
     pub resources: Resources,
+    /// The settings to use when rolling out new API versions
+    #[serde(default)]
+    pub rollout: ApiRolloutSettings,
 }
 
 /// Serde helper for default kube config path
@@ -299,6 +373,86 @@ fn default_version() -> String {
 
 pub const CRD_NAME: &str = "thoriumclusters.sandia.gov";
 
+/// The two sets of API pods that are swapped between during rollouts
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, Hash, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiColor {
+    Blue,
+    Green,
+}
+
+impl ApiColor {
+    /// Get the other color to roll out to
+    pub fn other(&self) -> Self {
+        match self {
+            ApiColor::Blue => ApiColor::Green,
+            ApiColor::Green => ApiColor::Blue,
+        }
+    }
+
+    /// Get this color as a str
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiColor::Blue => "blue",
+            ApiColor::Green => "green",
+        }
+    }
+
+    /// Get the name of the API deployment for this color
+    pub fn deployment(&self) -> String {
+        format!("api-{}", self.as_str())
+    }
+}
+
+/// The current phase of an API rollout
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, Hash, Eq, PartialEq)]
+pub enum RolloutPhase {
+    /// The Scylla schema is being migrated for the new API version
+    Migrating,
+    /// New API pods are being deployed alongside the current ones
+    Deploying,
+    /// New API pods are being health checked and smoke tested
+    Verifying,
+    /// Traffic has been switched and the new API pods are being watched for errors
+    Watching,
+    /// The new API pods are serving all traffic
+    Complete,
+    /// The new API pods failed their checks and traffic was switched back
+    RolledBack,
+    /// The new API pods failed their checks before any traffic was switched to them
+    Failed,
+}
+
+/// The status of the latest API rollout
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq)]
+pub struct ApiRolloutStatus {
+    /// The current phase of this rollout
+    pub phase: RolloutPhase,
+    /// The color of the API pods currently serving traffic
+    pub active_color: Option<ApiColor>,
+    /// The revision of the API pods currently serving traffic
+    pub active_revision: Option<String>,
+    /// The version of the API pods currently serving traffic
+    pub active_version: Option<String>,
+    /// The revision being rolled out
+    pub target_revision: String,
+    /// The version being rolled out
+    pub target_version: String,
+    /// The last revision that failed to roll out, this will not be retried until the spec changes
+    pub failed_revision: Option<String>,
+    /// A human readable message on this rollouts progress
+    pub message: Option<String>,
+    /// When this status was last updated as a unix timestamp
+    pub updated: i64,
+}
+
+/// The status of a ThoriumCluster
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema, PartialEq)]
+pub struct ThoriumClusterStatus {
+    /// The status of the latest API rollout
+    pub api_rollout: Option<ApiRolloutStatus>,
+}
+
 /// ThoriumCluster CRD definition
 #[derive(CustomResource, Serialize, Deserialize, Clone, Debug, JsonSchema)]
 #[kube(
@@ -306,6 +460,10 @@ pub const CRD_NAME: &str = "thoriumclusters.sandia.gov";
     version = "v1",
     kind = "ThoriumCluster",
     namespaced,
+    status = "ThoriumClusterStatus",
+    printcolumn = r#"{"name":"Version", "type":"string", "jsonPath":".spec.version"}"#,
+    printcolumn = r#"{"name":"Rollout", "type":"string", "jsonPath":".status.api_rollout.phase"}"#,
+    printcolumn = r#"{"name":"Active", "type":"string", "jsonPath":".status.api_rollout.active_version"}"#,
     doc = "Custom resource representing a ThoriumCluster"
 )]
 pub struct ThoriumClusterSpec {
@@ -392,6 +550,33 @@ impl ThoriumCluster {
         }
     }
 
+    /// Get the current API rollout status if one exists
+    pub fn get_api_rollout(&self) -> Option<&ApiRolloutStatus> {
+        self.status
+            .as_ref()
+            .and_then(|status| status.api_rollout.as_ref())
+    }
+
+    /// Get the revision of the API that should be deployed
+    ///
+    /// This changes whenever the API image or its pod spec changes. Replica counts and rollout
+    /// settings are not included as changing them does not require new API pods. The revision is
+    /// a sha256 of the serialized pod settings so it is stable across operator builds.
+    pub fn get_api_revision(&self) -> Option<String> {
+        let spec = self.get_api_spec()?;
+        // serialize everything that makes up the API pods
+        let pod_spec = json!({
+            "image": self.get_image(),
+            "image_pull_policy": self.spec.image_pull_policy,
+            "env": spec.env,
+            "cmd": spec.cmd,
+            "args": spec.args,
+            "resources": spec.resources,
+        });
+        let digest = Sha256::digest(pod_spec.to_string().as_bytes());
+        Some(format!("{:x}", digest))
+    }
+
     /// List the components in the ThoriumCluster
     pub fn list_component_names(&self) -> Vec<String> {
         // a list of component names
@@ -3,18 +3,31 @@ use k8s_openapi::api::apps::v1::Deployment;
 use kube::api::{DeleteParams, ListParams, Patch, PatchParams, PostParams};
 use serde_json::Value;
 use std::time;
-use thorium::{client::Basic, Error};
+use thorium::Error;
 use tokio;
 
 use super::clusters::ClusterMeta;
 use super::crds;
 
+/// The annotation used to track which API revision a deployment is running
+pub const REVISION_ANNOTATION: &str = "thorium.sandia.gov/revision";
+
+/// The app label for blue/green API pods
+///
+/// This differs from the `api` label used by clusters deployed before blue/green rollouts so the
+/// legacy API service never routes traffic to new API pods before they have been verified.
+pub const API_APP_LABEL: &str = "thorium-api";
+
 /// Build JSON template for api deployment
 ///
+/// API deployments are split into a blue and green deployment so new versions can be
+/// brought up alongside the current version before any traffic is switched to them.
+///
 ///  Arguments
 ///
 /// * `meta` - Thorium cluster client and metadata
-async fn api_template(meta: &ClusterMeta) -> Option<Value> {
+/// * `color` - The color of the API deployment to build
+async fn api_template(meta: &ClusterMeta, color: crds::ApiColor) -> Option<Value> {
     let api_spec = meta.cluster.get_api_spec();
     match api_spec {
         Some(api_spec) => Some(serde_json::json!({
@@ -22,23 +35,29 @@ async fn api_template(meta: &ClusterMeta) -> Option<Value> {
             "kind": "Deployment",
             "metadata": {
                 "namespace": meta.cluster.metadata.namespace.clone(),
-                "name": "api",
+                "name": color.deployment(),
                 "labels": {
-                    "app": "api",
+                    "app": API_APP_LABEL,
+                    "color": color.as_str(),
                     "version": meta.cluster.get_version(),
+                },
+                "annotations": {
+                    REVISION_ANNOTATION: meta.cluster.get_api_revision(),
                 }
             },
             "spec": {
                 "replicas": api_spec.replicas.clone(),
                 "selector": {
                     "matchLabels": {
-                        "app": "api",
+                        "app": API_APP_LABEL,
+                        "color": color.as_str(),
                     }
                 },
                 "template": {
                     "metadata": {
                         "labels": {
-                            "app": "api",
+                            "app": API_APP_LABEL,
+                            "color": color.as_str(),
                             "version": meta.cluster.get_version(),
                         }
                     },
@@ -572,8 +591,13 @@ async fn search_streamer_template(meta: &ClusterMeta) -> Option<Value> {
 #[allow(dead_code)]
 pub async fn get_templates(meta: &ClusterMeta) -> Result<Vec<Deployment>, Error> {
     let mut deployments: Vec<Deployment> = Vec::with_capacity(5);
-    // add any api deployment templates
-    if let Some(deployment) = api_template(meta).await {
+    // add the api deployment template for the color currently serving traffic
+    let color = meta
+        .cluster
+        .get_api_rollout()
+        .and_then(|rollout| rollout.active_color)
+        .unwrap_or(crds::ApiColor::Blue);
+    if let Some(deployment) = api_template(meta, color).await {
         let deployment: Deployment = serde_json::from_value(deployment)?;
         deployments.push(deployment);
     }
@@ -695,23 +719,252 @@ pub async fn delete_one(name: &str, meta: &ClusterMeta) -> Result<(), Error> {
     }
     Ok(())
 }
-/// Create or update the API deployment
+/// Create or update the API deployment for a specific color
+///
+/// Returns false if the API is not present in the cluster spec.
 ///
 ///  Arguments
 ///
 /// * `meta` - Thorium cluster client and metadata
-pub async fn deploy_api(meta: &ClusterMeta) -> Result<(), Error> {
+/// * `color` - The color of the API deployment to create or update
+pub async fn deploy_api(meta: &ClusterMeta, color: crds::ApiColor) -> Result<bool, Error> {
     // add any api deployment templates
-    if let Some(deployment) = api_template(meta).await {
-        let deployment: Deployment = serde_json::from_value(deployment)?;
-        create_or_update(deployment, meta).await?;
-    // component not present in cluster spec during upgrades, cleanup
-    } else {
-        delete_one("api", meta).await?;
+    match api_template(meta, color).await {
+        Some(deployment) => {
+            let deployment: Deployment = serde_json::from_value(deployment)?;
+            create_or_update(deployment, meta).await?;
+            Ok(true)
+        }
+        None => Ok(false),
     }
+}
+
+/// Delete all API deployments
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+pub async fn delete_api(meta: &ClusterMeta) -> Result<(), Error> {
+    // delete the legacy api deployment and both colors
+    delete_one("api", meta).await?;
+    delete_one(&crds::ApiColor::Blue.deployment(), meta).await?;
+    delete_one(&crds::ApiColor::Green.deployment(), meta).await?;
     Ok(())
 }
 
+/// Check if a deployment exists
+///
+///  Arguments
+///
+/// * `name` - Name of the deployment to check for
+/// * `meta` - Thorium cluster client and metadata
+pub async fn exists(name: &str, meta: &ClusterMeta) -> Result<bool, Error> {
+    match meta.deploy_api.get_opt(name).await {
+        Ok(deployment) => Ok(deployment.is_some()),
+        Err(error) => Err(Error::new(format!(
+            "Failed to get {} deployment in namespace {}: {}",
+            name, &meta.namespace, error
+        ))),
+    }
+}
+
+/// Scale a deployment to a specific number of replicas
+///
+///  Arguments
+///
+/// * `name` - Name of the deployment to scale
+/// * `replicas` - The number of replicas to scale to
+/// * `meta` - Thorium cluster client and metadata
+pub async fn scale(name: &str, replicas: u16, meta: &ClusterMeta) -> Result<(), Error> {
+    let patch = serde_json::json!({
+        "spec": {
+            "replicas": replicas
+        }
+    });
+    let params = PatchParams::default();
+    match meta
+        .deploy_api
+        .patch(name, &params, &Patch::Merge(&patch))
+        .await
+    {
+        Ok(_) => {
+            println!(
+                "Scaled {} deployment in namespace {} to {} replicas",
+                name, &meta.namespace, replicas
+            );
+            Ok(())
+        }
+        Err(kube::Error::Api(error)) => {
+            // don't fail if the deployment doesn't exist, there is nothing to scale
+            if error.code == 404 {
+                println!(
+                    "No {} deployment in namespace {} to scale, skipping",
+                    name, &meta.namespace
+                );
+                return Ok(());
+            }
+            Err(Error::new(format!(
+                "Failed to scale {} deployment: {}",
+                name, error.message
+            )))
+        }
+        Err(error) => Err(Error::new(format!(
+            "Failed to scale {} deployment: {}",
+            name, error
+        ))),
+    }
+}
+
+/// The health of a set of pods
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PodHealth {
+    /// The number of pods that are ready
+    pub ready: usize,
+    /// The total number of times the containers in these pods have restarted
+    pub restarts: u64,
+}
+
+/// Get the health of the pods matching a label selector
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `selector` - The label selector for the pods to check
+pub async fn pod_health(meta: &ClusterMeta, selector: &str) -> Result<PodHealth, Error> {
+    let params = ListParams::default().labels(selector);
+    let pods = match meta.pod_api.list(&params).await {
+        Ok(pods) => pods,
+        Err(error) => {
+            return Err(Error::new(format!(
+                "Failed to list pods with labels \"{}\" in namespace {}: {}",
+                selector, &meta.namespace, error
+            )));
+        }
+    };
+    let mut health = PodHealth::default();
+    for pod in pods.items {
+        // skip any pods that are being torn down
+        if pod.metadata.deletion_timestamp.is_some() {
+            continue;
+        }
+        let Some(status) = pod.status else {
+            continue;
+        };
+        // a pod is ready once its Ready condition is true
+        let ready = status
+            .conditions
+            .unwrap_or_default()
+            .iter()
+            .any(|condition| condition.type_ == "Ready" && condition.status == "True");
+        if ready {
+            health.ready += 1;
+        }
+        // add up how many times this pods containers have restarted
+        health.restarts += status
+            .container_statuses
+            .unwrap_or_default()
+            .iter()
+            .map(|container| u64::try_from(container.restart_count).unwrap_or_default())
+            .sum::<u64>();
+    }
+    Ok(health)
+}
+
+/// Get the IPs of the ready pods matching a label selector
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `selector` - The label selector for the pods to get IPs for
+pub async fn ready_pod_ips(meta: &ClusterMeta, selector: &str) -> Result<Vec<String>, Error> {
+    let params = ListParams::default().labels(selector);
+    let pods = match meta.pod_api.list(&params).await {
+        Ok(pods) => pods,
+        Err(error) => {
+            return Err(Error::new(format!(
+                "Failed to list pods with labels \"{}\" in namespace {}: {}",
+                selector, &meta.namespace, error
+            )));
+        }
+    };
+    let mut ips = Vec::with_capacity(pods.items.len());
+    for pod in pods.items {
+        // skip any pods that are being torn down
+        if pod.metadata.deletion_timestamp.is_some() {
+            continue;
+        }
+        let Some(status) = pod.status else {
+            continue;
+        };
+        // only get the IPs of pods that are ready to serve requests
+        let ready = status
+            .conditions
+            .unwrap_or_default()
+            .iter()
+            .any(|condition| condition.type_ == "Ready" && condition.status == "True");
+        if let (true, Some(ip)) = (ready, status.pod_ip) {
+            ips.push(ip);
+        }
+    }
+    Ok(ips)
+}
+
+/// Wait for a number of pods matching a label selector to be ready
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `selector` - The label selector for the pods to wait for
+/// * `replicas` - The number of pods that must be ready
+pub async fn wait_for_ready(
+    meta: &ClusterMeta,
+    selector: &str,
+    replicas: usize,
+) -> Result<(), Error> {
+    let one_second = tokio::time::Duration::from_secs(1);
+    loop {
+        let health = pod_health(meta, selector).await?;
+        // stop waiting once enough pods are ready
+        if health.ready >= replicas {
+            println!(
+                "{} pods with labels \"{}\" are ready",
+                health.ready, selector
+            );
+            return Ok(());
+        }
+        println!(
+            "Waiting for pods with labels \"{}\" to be ready ({}/{})...",
+            selector, health.ready, replicas
+        );
+        tokio::time::sleep(one_second).await;
+    }
+}
+
+/// Wait for a set amount of time for pods matching a label selector to be ready
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `selector` - The label selector for the pods to wait for
+/// * `replicas` - The number of pods that must be ready
+/// * `timeout_secs` - Number of seconds to wait before timing out
+pub async fn timeout_wait_for_ready(
+    meta: &ClusterMeta,
+    selector: &str,
+    replicas: usize,
+    timeout_secs: u64,
+) -> Result<(), Error> {
+    let wait_future = wait_for_ready(meta, selector, replicas);
+    // wait for our pods to be ready with the specified timeout
+    match tokio::time::timeout(time::Duration::from_secs(timeout_secs), wait_future).await {
+        Ok(result) => result,
+        Err(_) => Err(Error::new(format!(
+            "Pods with labels \"{}\" were not ready within {} seconds",
+            selector, timeout_secs
+        ))),
+    }
+}
+
 /// Create or update the scaler deployment
 ///
 ///  Arguments
@@ -771,112 +1024,6 @@ pub async fn deploy_search_streamer(meta: &ClusterMeta) -> Result<(), Error> {
     Ok(())
 }
 
-/// Wait for pods with correct app label to be online
-///
-///  Arguments
-///
-/// * `meta` - Thorium cluster client and metadata
-/// * `app_tag` - App name to wait for running state
-/// * `host` - Optional hostname to ping to determine healthy state of pods after they are running
-pub async fn wait_for_pods(
-    meta: &ClusterMeta,
-    app_tag: &str,
-    host: Option<&String>,
-) -> Result<(), Error> {
-    let label_selector = format!("app={},version={}", app_tag, meta.cluster.get_version()).clone();
-    let params = ListParams::default().labels(label_selector.as_str());
-    // create some durations for sleeps
-    let one_second = tokio::time::Duration::from_secs(1);
-    let five_seconds = tokio::time::Duration::from_secs(5);
-    // app pods may need some time to start spinning up
-    tokio::time::sleep(five_seconds).await;
-    // check if pods are running
-    println!("Waiting for {} labeled pods to be running", &label_selector);
-    loop {
-        let mut running = true;
-        // get a list of pods with the api label
-        let pods = meta.pod_api.list(&params).await?;
-        // check pods status
-        for pod in &pods.items {
-            let status = pod
-                .status
-                .as_ref()
-                .map(|s| s.phase.clone().unwrap_or_default())
-                .unwrap_or_default();
-            if status != "Running" {
-                running = false;
-            }
-        }
-        // break if all pods were found running
-        if running == true {
-            println!(
-                "All \"{}\" pods with labels \"{}\" are now running",
-                app_tag, label_selector
-            );
-            break;
-        }
-        println!(
-            "Waiting for \"{}\" pods with labels \"{}\" to be running...",
-            app_tag, label_selector
-        );
-        tokio::time::sleep(one_second).await;
-    }
-    // if app is the API, then lets make sure the API is responding
-    if app_tag == "api" && host.is_some() {
-        let thorium_client = reqwest::Client::new();
-        let basic = Basic::new(
-            host.expect("expected host to be some url but found none"),
-            &thorium_client,
-        );
-        // status check loop
-        loop {
-            // ping Thorium API for status
-            match basic.health().await {
-                Ok(response) => {
-                    // API responds, return success
-                    println!("Thorium API is up and returned response \"{}\"", response);
-                    break;
-                }
-                Err(error) => {
-                    println!("Waiting for API pods to respond: {}", error);
-                    tokio::time::sleep(one_second).await;
-                    continue;
-                }
-            }
-        }
-    }
-    Ok(())
-}
-
-/// Wait for a set amount of time for pods to be running
-///
-///  Arguments
-///
-/// * `meta` - Thorium cluster client and metadata
-/// * `app_tag` - App tag to use for retrieving API pod status
-/// * `timeout_secs` - Number of seconds to wait before timing out
-/// * `host` - Optional hostname to ping to determine healthy state of pods after they are running
-pub async fn timeout_wait_for_pods(
-    meta: &ClusterMeta,
-    app_tag: &str,
-    timeout_secs: u64,
-    host: Option<&String>,
-) -> Result<(), Error> {
-    // build label_selector string
-    let label_selector = format!("app={},version={}", app_tag, meta.cluster.get_version()).clone();
-    let wait_future = wait_for_pods(meta, app_tag, host);
-    // run wait for pods with specified timeout
-    if let Err(_error) =
-        tokio::time::timeout(time::Duration::from_secs(timeout_secs), wait_future).await
-    {
-        return Err(Error::new(format!(
-            "\"{}\" pods with labels \"{}\" did not start within {} seconds",
-            app_tag, label_selector, timeout_secs
-        )));
-    }
-    Ok(())
-}
-
 /// Restart a Thorium component pod by deployment name
 ///
 ///  Arguments
@@ -927,7 +1074,13 @@ pub async fn delete(meta: &ClusterMeta) -> Result<(), Error> {
     println!("Cleaning up deployments using CRD");
     // delete each deployment from the namespace
     let params = DeleteParams::default();
+    // the api is split into blue and green deployments
+    delete_api(meta).await?;
     for deployment in meta.cluster.list_component_names().into_iter() {
+        // skip the api since we already cleaned it up
+        if deployment == "api" {
+            continue;
+        }
         match meta.deploy_api.delete(&deployment, &params).await {
             Ok(_) => println!(
                 "Deleted {} deployment from {} namespace",
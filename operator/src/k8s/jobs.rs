@@ -0,0 +1,219 @@
+use k8s_openapi::api::batch::v1::Job;
+use kube::api::{DeleteParams, PostParams, PropagationPolicy};
+use serde_json::Value;
+use thorium::Error;
+use tokio::time::Duration;
+
+use super::clusters::ClusterMeta;
+
+/// The name of the job that migrates the Scylla schema
+pub const MIGRATE_JOB: &str = "scylla-migrate";
+
+/// How often to check on the status of a job in seconds
+const JOB_POLL_SECS: u64 = 2u64;
+
+/// Build JSON template for the Scylla schema migration job
+///
+/// The migrations are run with the Thoradm binary in the image being rolled out so the
+/// migrations for that version of Thorium are applied.
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+fn migrate_template(meta: &ClusterMeta) -> Value {
+    serde_json::json!({
+        "apiVersion": "batch/v1",
+        "kind": "Job",
+        "metadata": {
+            "namespace": meta.cluster.metadata.namespace.clone(),
+            "name": MIGRATE_JOB,
+            "labels": {
+                "app": MIGRATE_JOB,
+                "version": meta.cluster.get_version(),
+            }
+        },
+        "spec": {
+            "backoffLimit": 0,
+            // keep finished jobs around for a day so their logs can be checked
+            "ttlSecondsAfterFinished": 86400,
+            "template": {
+                "metadata": {
+                    "labels": {
+                        "app": MIGRATE_JOB,
+                        "version": meta.cluster.get_version(),
+                    }
+                },
+                "spec": {
+                    "restartPolicy": "Never",
+                    "containers": [
+                        {
+                            "name": "migrate",
+                            "image": meta.cluster.get_image(),
+                            "command": ["/app/thoradm"],
+                            "args": ["--cluster-conf", "/conf/thorium.yml", "migrate", "apply"],
+                            "imagePullPolicy": meta.cluster.spec.image_pull_policy.clone(),
+                            "volumeMounts": [
+                                {
+                                    "name": "config",
+                                    "mountPath": "/conf/thorium.yml",
+                                    "subPath": "thorium.yml"
+                                }
+                            ]
+                        }
+                    ],
+                    "volumes": [
+                        {
+                            "name": "config",
+                            "secret": {
+                                "secretName": "thorium"
+                            }
+                        }
+                    ],
+                    "imagePullSecrets": [
+                        {
+                            "name": "registry-token"
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+/// Delete a job and its pods by name
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `name` - The name of the job to delete
+pub async fn delete_one(meta: &ClusterMeta, name: &str) -> Result<(), Error> {
+    // make sure the pods for this job are deleted too
+    let params = DeleteParams {
+        propagation_policy: Some(PropagationPolicy::Background),
+        ..DeleteParams::default()
+    };
+    match meta.job_api.delete(name, &params).await {
+        Ok(_) => println!("Deleted {} job", name),
+        Err(kube::Error::Api(error)) => {
+            // job was not found, continue on
+            if error.code == 404 {
+                println!("Job {} does not exist, skipping deletion", name);
+                return Ok(());
+            }
+            return Err(Error::new(format!(
+                "Could not delete {} job: {}",
+                name, error.message
+            )));
+        }
+        Err(error) => {
+            return Err(Error::new(format!(
+                "Could not delete {} job: {}",
+                name, error
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Wait for a job to either complete or fail
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `name` - The name of the job to wait for
+async fn wait_for_job(meta: &ClusterMeta, name: &str) -> Result<(), Error> {
+    loop {
+        let job = match meta.job_api.get(name).await {
+            Ok(job) => job,
+            Err(error) => {
+                return Err(Error::new(format!(
+                    "Failed to get {} job status: {}",
+                    name, error
+                )))
+            }
+        };
+        // check if this job has finished yet
+        let conditions = job
+            .status
+            .and_then(|status| status.conditions)
+            .unwrap_or_default();
+        for condition in conditions {
+            if condition.status != "True" {
+                continue;
+            }
+            match condition.type_.as_str() {
+                "Complete" => return Ok(()),
+                "Failed" => {
+                    return Err(Error::new(format!(
+                        "{} job failed: {}",
+                        name,
+                        condition.message.unwrap_or_default()
+                    )))
+                }
+                _ => (),
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(JOB_POLL_SECS)).await;
+    }
+}
+
+/// Migrate the Scylla schema to the version being rolled out
+///
+/// Any previous migration job is replaced so the migrations always run with the image that is
+/// about to be rolled out.
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `timeout_secs` - How long to wait for the migrations to finish
+pub async fn migrate(meta: &ClusterMeta, timeout_secs: u64) -> Result<(), Error> {
+    // remove any previous migration job
+    delete_one(meta, MIGRATE_JOB).await?;
+    // wait for the previous job to be removed so we can reuse its name
+    let removed = async {
+        while meta
+            .job_api
+            .get_opt(MIGRATE_JOB)
+            .await
+            .ok()
+            .flatten()
+            .is_some()
+        {
+            tokio::time::sleep(Duration::from_secs(JOB_POLL_SECS)).await;
+        }
+    };
+    if tokio::time::timeout(Duration::from_secs(timeout_secs), removed)
+        .await
+        .is_err()
+    {
+        return Err(Error::new(format!(
+            "Previous {} job was not removed within {} seconds",
+            MIGRATE_JOB, timeout_secs
+        )));
+    }
+    // create our migration job
+    let job: Job = serde_json::from_value(migrate_template(meta))?;
+    if let Err(error) = meta.job_api.create(&PostParams::default(), &job).await {
+        return Err(Error::new(format!(
+            "Failed to create {} job in namespace {}: {}",
+            MIGRATE_JOB, &meta.namespace, error
+        )));
+    }
+    println!(
+        "Created {} job in namespace {}",
+        MIGRATE_JOB, &meta.namespace
+    );
+    // wait for our migrations to finish
+    match tokio::time::timeout(
+        Duration::from_secs(timeout_secs),
+        wait_for_job(meta, MIGRATE_JOB),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => Err(Error::new(format!(
+            "{} job did not finish within {} seconds",
+            MIGRATE_JOB, timeout_secs
+        ))),
+    }
+}
@@ -2,10 +2,9 @@ use kube::runtime::controller::Action;
 use thorium::{Error, Thorium};
 use tokio::time::Duration;
 
-use crate::app;
+use crate::app::{self, upgrades::RolloutOutcome};
 use crate::k8s::{self, clusters::ClusterMeta};
 
-const POD_WAIT_TIMEOUT: u64 = 300u64;
 const APPLY_REQUEUE_SECS: u64 = 86400u64;
 
 /// Create a ThoriumCluster
@@ -17,7 +16,12 @@ const APPLY_REQUEUE_SECS: u64 = 86400u64;
 ///
 /// * `meta` - Thorium cluster metadata being operated upon
 /// * `url` - Override url for Kubernetes api service.
-pub async fn apply(meta: &ClusterMeta, url: Option<String>) -> Result<Action, Error> {
+/// * `preview_url` - Override url for Kubernetes api preview service.
+pub async fn apply(
+    meta: &ClusterMeta,
+    url: Option<String>,
+    preview_url: Option<String>,
+) -> Result<Action, Error> {
     println!(
         "Applying {} ThoriumCluster in {} namespace",
         &meta.name, &meta.namespace
@@ -34,19 +38,22 @@ pub async fn apply(meta: &ClusterMeta, url: Option<String>) -> Result<Action, Er
     app::helpers::create_all_buckets(&meta).await?;
     // create or update API service
     k8s::services::create_or_update(&meta).await?;
-    // create or update api deployment from CR
-    k8s::deployments::deploy_api(&meta).await?;
-    // build API url host string
+    // build API url host strings
     let host: String = app::helpers::get_thorium_host(&meta, url.as_ref());
-    // wait for api pods to be up
-    let result =
-        k8s::deployments::timeout_wait_for_pods(&meta, "api", POD_WAIT_TIMEOUT, Some(&host)).await;
-    // exit cluster update if pod update timed out
-    if result.is_err() {
-        println!("{}", result.expect_err("expected error but found ()"));
-        println!("Error: Timed out waiting for API pod to be reachable, exiting cluster provision");
+    let preview: String = app::helpers::get_thorium_preview_host(&meta, preview_url.as_ref());
+    // roll out the api deployment from CR
+    match app::upgrades::rollout_api(&meta, &host, &preview).await? {
+        RolloutOutcome::Current | RolloutOutcome::Complete => (),
+        // keep the rest of the cluster on its current version if the new api was rolled back
+        RolloutOutcome::RolledBack => {
+            println!("Error: API rollout failed, keeping the current version of all components");
+            return Ok(Action::requeue(Duration::from_secs(APPLY_REQUEUE_SECS)));
+        }
         // exit cluster provision operation early here and requeue
-        return Ok(Action::requeue(Duration::from_secs(60)));
+        RolloutOutcome::Unavailable => {
+            println!("Error: API never became healthy, exiting cluster provision");
+            return Ok(Action::requeue(Duration::from_secs(60)));
+        }
     }
     // create operator user and retrieve token
     let operator_token = app::users::create_operator(&meta, &host).await?;
@@ -131,6 +138,8 @@ pub async fn cleanup(meta: &ClusterMeta) -> Result<Action, Error> {
     k8s::deployments::delete(&meta).await?;
     // delete api service
     k8s::services::delete(&meta).await?;
+    // delete the schema migration job
+    k8s::jobs::delete_one(&meta, k8s::jobs::MIGRATE_JOB).await?;
     // remove secrets including thorium.yml and keys.yml
     k8s::secrets::delete(&meta).await?;
     // remove configmaps such as tracing.yml
@@ -4,6 +4,28 @@ use serde_json::Value;
 use thorium::Error;
 
 use super::clusters::ClusterMeta;
+use super::crds::ApiColor;
+use super::deployments::API_APP_LABEL;
+
+/// The name of the service routing traffic to the active API pods
+pub const API_SERVICE: &str = "thorium-api";
+/// The name of the service routing traffic to API pods that are being rolled out
+pub const API_PREVIEW_SERVICE: &str = "thorium-api-preview";
+
+/// Build the label selector for a set of API pods
+///
+/// A color of None selects the API pods of clusters deployed before blue/green rollouts were
+/// supported. Blue/green API pods use a different app label so they are never selected this way.
+///
+///  Arguments
+///
+/// * `color` - The color of API pods to select
+fn api_selector(color: Option<ApiColor>) -> Value {
+    match color {
+        Some(color) => serde_json::json!({"app": API_APP_LABEL, "color": color.as_str()}),
+        None => serde_json::json!({"app": "api"}),
+    }
+}
 
 /// Build a Thorium API service template
 ///
@@ -13,7 +35,13 @@ use super::clusters::ClusterMeta;
 ///  Arguments
 ///
 /// * `meta` - Thorium cluster client and metadata
-async fn api_service_template(meta: &ClusterMeta) -> Option<Value> {
+/// * `name` - The name of the service to build
+/// * `color` - The color of API pods to route traffic to
+async fn api_service_template(
+    meta: &ClusterMeta,
+    name: &str,
+    color: Option<ApiColor>,
+) -> Option<Value> {
     let api_spec = meta.cluster.get_api_spec();
 
     return match api_spec {
@@ -21,12 +49,10 @@ async fn api_service_template(meta: &ClusterMeta) -> Option<Value> {
             "apiVersion": "v1",
             "kind": "Service",
             "metadata": {
-                "name": "thorium-api"
+                "name": name
             },
             "spec": {
-                "selector": {
-                    "app": "api"
-                },
+                "selector": api_selector(color),
                 "ports": [
                     {
                         "name": "web",
@@ -41,76 +67,139 @@ async fn api_service_template(meta: &ClusterMeta) -> Option<Value> {
     };
 }
 
+/// Create a service or patch it if it already exists
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `name` - The name of the service to create or update
+/// * `template` - The service template to apply
+async fn apply(meta: &ClusterMeta, name: &str, template: Value) -> Result<(), Error> {
+    let params = PostParams::default();
+    let service: Service = serde_json::from_value(template)?;
+    match meta.service_api.create(&params, &service).await {
+        Ok(_) => {
+            println!("Service {} created in namespace {}", name, &meta.namespace);
+            Ok(())
+        }
+        Err(kube::Error::Api(error)) => {
+            // do not panic if service exists
+            if error.reason == "AlreadyExists" {
+                let patch = serde_json::json!({
+                    "spec": service.spec
+                });
+                let patch = Patch::Merge(&patch);
+                let params: PatchParams = PatchParams::default();
+                match meta.service_api.patch(name, &params, &patch).await {
+                    Ok(_) => {
+                        println!("Patched {} service in namespace {}", name, &meta.namespace);
+                        Ok(())
+                    }
+                    Err(error) => Err(Error::new(format!(
+                        "Failed to patch {} service in namespace {}: {}",
+                        name, &meta.namespace, error
+                    ))),
+                }
+            } else {
+                Err(Error::new(format!(
+                    "Failed to create {} service in namespace {}: {}",
+                    name, &meta.namespace, error
+                )))
+            }
+        }
+        Err(error) => Err(Error::new(format!(
+            "Failed to create {} service in namespace {}: {}",
+            name, &meta.namespace, error
+        ))),
+    }
+}
+
 /// Create an API service from a template
 ///
 /// This creates an API service so network traffic can be routed to the API
 /// from internal or external locations (using an ingress proxy like Traefik).
+/// Traffic is routed to whichever color of API pods last completed a rollout.
 ///
 ///  Arguments
 ///
 /// * `meta` - Thorium cluster client and metadata
 pub async fn create_or_update(meta: &ClusterMeta) -> Result<(), Error> {
-    let params = PostParams::default();
+    // route traffic to the api pods that are currently active
+    let color = meta
+        .cluster
+        .get_api_rollout()
+        .and_then(|rollout| rollout.active_color);
     // build API service template
-    let template = api_service_template(meta).await;
+    let template = api_service_template(meta, API_SERVICE, color).await;
     if let Some(service_template) = template {
-        let service: Service = serde_json::from_value(service_template)?;
-        return match meta.service_api.create(&params, &service).await {
-            Ok(_) => {
-                println!(
-                    "Thorium API service created in namespace {}",
-                    &meta.namespace
-                );
-                Ok(())
-            }
-            Err(kube::Error::Api(error)) => {
-                // do not panic if deployment exists
-                if error.reason == "AlreadyExists" {
-                    let patch = serde_json::json!({
-                        "spec": service.spec
-                    });
-                    let patch = Patch::Merge(&patch);
-                    let params: PatchParams = PatchParams::default();
-                    return match meta.service_api.patch("thorium-api", &params, &patch).await {
-                        Ok(_) => {
-                            println!("Patched API service in namespace {}", &meta.namespace);
-                            Ok(())
-                        }
-                        Err(error) => Err(Error::new(format!(
-                            "Failed to patch API service in namespace {}: {}",
-                            &meta.namespace, error
-                        ))),
-                    };
-                } else {
-                    Err(Error::new(format!(
-                        "Failed to create API service in namespace {}: {}",
-                        &meta.namespace, error
-                    )))
-                }
-            }
-            Err(error) => Err(Error::new(format!(
-                "Failed to create API service in namespace {}: {}",
-                &meta.namespace, error
-            ))),
-        };
+        apply(meta, API_SERVICE, service_template).await?;
+    }
+    Ok(())
+}
+
+/// Create a preview service that routes traffic to API pods being rolled out
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `color` - The color of API pods being rolled out
+pub async fn create_or_update_preview(meta: &ClusterMeta, color: ApiColor) -> Result<(), Error> {
+    // build API preview service template
+    let template = api_service_template(meta, API_PREVIEW_SERVICE, Some(color)).await;
+    if let Some(service_template) = template {
+        apply(meta, API_PREVIEW_SERVICE, service_template).await?;
     }
     Ok(())
 }
 
-/// Cleanup Thorium API service
+/// Switch the API service to route traffic to a different set of API pods
 ///
-/// This deletes a Thorium API service from kubernetes based on the default
-/// service name thorium-api.
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+/// * `color` - The color of API pods to route traffic to
+pub async fn switch_api(meta: &ClusterMeta, color: Option<ApiColor>) -> Result<(), Error> {
+    // a merge patch with a null color will remove the color from our selector
+    let patch = serde_json::json!({
+        "spec": {
+            "selector": {
+                "app": color.map_or("api", |_| API_APP_LABEL),
+                "color": color.map(|color| color.as_str()),
+            }
+        }
+    });
+    let params = PatchParams::default();
+    match meta
+        .service_api
+        .patch(API_SERVICE, &params, &Patch::Merge(&patch))
+        .await
+    {
+        Ok(_) => {
+            println!(
+                "Switched {} service in namespace {} to {} API pods",
+                API_SERVICE,
+                &meta.namespace,
+                color.map_or("all", |color| color.as_str())
+            );
+            Ok(())
+        }
+        Err(error) => Err(Error::new(format!(
+            "Failed to switch {} service in namespace {}: {}",
+            API_SERVICE, &meta.namespace, error
+        ))),
+    }
+}
+
+/// Delete a service by name
 ///
 ///  Arguments
 ///
 /// * `meta` - Thorium cluster client and metadata
-pub async fn delete(meta: &ClusterMeta) -> Result<(), Error> {
+/// * `service_name` - The name of the service to delete
+pub async fn delete_one(meta: &ClusterMeta, service_name: &str) -> Result<(), Error> {
     let params: DeleteParams = DeleteParams::default();
-    // delete the Thorium service
-    let service_name = "thorium-api".to_string();
-    match meta.service_api.delete(&service_name, &params).await {
-        Ok(_) => println!("Deleted {} service", &service_name),
+    match meta.service_api.delete(service_name, &params).await {
+        Ok(_) => println!("Deleted {} service", service_name),
         Err(kube::Error::Api(error)) => {
             // service was not found, continue on
             if error.code == 404 {
@@ -126,8 +215,21 @@ pub async fn delete(meta: &ClusterMeta) -> Result<(), Error> {
             return Err(Error::new(format!(
                 "Could not delete {} service: {}",
                 service_name, error
-            )))
+            )));
         }
     }
     Ok(())
 }
+
+/// Cleanup Thorium API services
+///
+/// This deletes the Thorium API and API preview services from kubernetes.
+///
+///  Arguments
+///
+/// * `meta` - Thorium cluster client and metadata
+pub async fn delete(meta: &ClusterMeta) -> Result<(), Error> {
+    // delete the Thorium services
+    delete_one(meta, API_SERVICE).await?;
+    delete_one(meta, API_PREVIEW_SERVICE).await
+}
@@ -1,6 +1,7 @@
 //! The arguments to pass to the Thorium node reactor daemon
 
 use clap::Parser;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 use thorium::{models::ImageScaler, Error};
 
@@ -51,8 +52,7 @@ impl Args {
 pub enum Launchers {
     /// Spawn jobs the current bare metal node
     #[cfg(target_os = "linux")]
-    #[clap(version, author)]
-    BareMetal,
+    BareMetal(BareMetal),
     /// Spawn Windows containers on the current node
     #[cfg(target_os = "windows")]
     #[clap(version, author)]
@@ -60,6 +60,9 @@ pub enum Launchers {
     #[cfg(feature = "kvm")]
     #[cfg(target_os = "linux")]
     Kvm(Kvm),
+    /// Spawn per worker Firecracker or cloud-hypervisor microVMs on the current node
+    #[cfg(target_os = "linux")]
+    MicroVm(MicroVm),
 }
 
 impl Launchers {
@@ -67,16 +70,33 @@ impl Launchers {
     pub fn scaler(&self) -> ImageScaler {
         match self {
             #[cfg(target_os = "linux")]
-            Launchers::BareMetal => ImageScaler::BareMetal,
+            Launchers::BareMetal(_) => ImageScaler::BareMetal,
             #[cfg(target_os = "windows")]
             Launchers::Windows => ImageScaler::Windows,
             #[cfg(feature = "kvm")]
             #[cfg(target_os = "linux")]
             Launchers::Kvm(_) => ImageScaler::Kvm,
+            #[cfg(target_os = "linux")]
+            Launchers::MicroVm(_) => ImageScaler::Kvm,
         }
     }
 }
 
+/// Spawn jobs on the current bare metal node
+#[derive(Parser, Debug, Clone)]
+#[clap(version, author)]
+pub struct BareMetal {
+    /// The simulated internet to redirect workers to if their network policies use it
+    #[clap(long)]
+    pub simulated_internet: Option<IpAddr>,
+    /// The networks redirected workers can still reach directly like the Thorium API
+    #[clap(long)]
+    pub simulated_internet_exempt: Vec<String>,
+    /// The /16 network to address the network namespaces of workers that capture their traffic from
+    #[clap(long, default_value = "10.251.0.0")]
+    pub capture_network: Ipv4Addr,
+}
+
 /// Spawn KVM based vms on the current node
 #[derive(Parser, Debug, Clone)]
 #[clap(version, author)]
@@ -87,4 +107,22 @@ pub struct Kvm {
     /// Where to write our temp qcow2 images and isos too
     #[clap(short, long, default_value = "/tmp/qcow2")]
     pub temp: PathBuf,
+    /// How long to wait in seconds for cold booted vms before launching the agent
+    #[clap(short, long, default_value = "60")]
+    pub boot_wait: u64,
+}
+
+/// Spawn per worker microVMs on the current node
+#[derive(Parser, Debug, Clone)]
+#[clap(version, author)]
+pub struct MicroVm {
+    /// Where to write each microVMs rootfs copy and job drive
+    #[clap(short, long, default_value = "/tmp/thorium-microvms")]
+    pub temp: PathBuf,
+    /// The linux agent to inject into each microVM
+    #[clap(short, long, default_value = "/opt/thorium/thorium-agent")]
+    pub agent: PathBuf,
+    /// The bridge to attach each microVMs tap device to
+    #[clap(short, long, default_value = "thorium0")]
+    pub bridge: String,
 }
@@ -7,12 +7,16 @@ use thorium::models::{Node, Worker};
 use thorium::{Error, Thorium};
 use tracing::Span;
 
+#[cfg(target_os = "linux")]
+mod artifacts;
 #[cfg(target_os = "linux")]
 mod bare_metal;
 //#[cfg(feature = "kvm")]
 #[cfg(target_os = "linux")]
 #[cfg(feature = "kvm")]
 mod kvm;
+#[cfg(target_os = "linux")]
+mod microvm;
 #[cfg(target_os = "windows")]
 mod windows;
 
@@ -22,6 +26,8 @@ use bare_metal::BareMetal;
 #[cfg(feature = "kvm")]
 #[cfg(target_os = "linux")]
 use kvm::Kvm;
+#[cfg(target_os = "linux")]
+use microvm::MicroVm;
 #[cfg(target_os = "windows")]
 use windows::Windows;
 
@@ -78,10 +84,10 @@ pub trait Launcher {
 pub fn new(args: &Args) -> Box<dyn Launcher> {
     match &args.launchers {
         #[cfg(target_os = "linux")]
-        Launchers::BareMetal => {
+        Launchers::BareMetal(bare_metal) => {
             // get our node name
             let node = args.node().expect("Failed to get node name");
-            Box::new(BareMetal::new(&args.cluster, node))
+            Box::new(BareMetal::new(&args.cluster, node, bare_metal))
         }
         #[cfg(target_os = "windows")]
         Launchers::Windows => Box::new(Windows::default()),
@@ -93,5 +99,7 @@ pub fn new(args: &Args) -> Box<dyn Launcher> {
             // box and return our kvm launcher
             Box::new(kvm)
         }
+        #[cfg(target_os = "linux")]
+        Launchers::MicroVm(microvm) => Box::new(MicroVm::new(microvm)),
     }
 }
@@ -0,0 +1,93 @@
+//! Captures and submits the artifacts vm and bare metal workers produce while running jobs
+//!
+//! Vms and captured bare metal workers only ever run a single job so anything
+//! captured over their lifetime can be tied to the samples of the job they ran.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use thorium::client::api_capture_filter;
+use thorium::models::{ActiveJob, OriginRequest, SampleRequest};
+use thorium::{Error, Thorium};
+use tokio::process::{Child, Command};
+
+use crate::libs::keys;
+
+/// Submit a file a vm produced as a child of the samples its job analyzed
+///
+/// # Arguments
+///
+/// * `user` - The user that ran this job
+/// * `group` - The group this job is in
+/// * `job` - The job this file was produced by
+/// * `path` - The path to the file to submit
+/// * `origin` - Builds the origin for this file from a parent samples sha256
+pub async fn submit_child<F>(
+    user: &str,
+    group: &str,
+    job: &ActiveJob,
+    path: &Path,
+    origin: F,
+) -> Result<(), Error>
+where
+    F: Fn(&String) -> OriginRequest,
+{
+    // submit our file as the user that ran this job
+    let keys = keys::path(user);
+    let thorium = Thorium::from_key_file(&keys.to_string_lossy()).await?;
+    let reaction = thorium.reactions.get(group, &job.reaction).await?;
+    for sha256 in &reaction.samples {
+        let req = SampleRequest::new(path, vec![group]).origin(origin(sha256));
+        thorium.files.create(req).await?;
+    }
+    Ok(())
+}
+
+/// A tcpdump capture of a vms tap device or a bare metal workers veth
+pub struct TapCapture {
+    /// The tcpdump process capturing this vms traffic
+    child: Child,
+    /// The path our pcap is being written to
+    path: PathBuf,
+}
+
+impl TapCapture {
+    /// Start capturing the traffic on a vms tap device or a bare metal workers veth
+    ///
+    /// Traffic to and from the Thorium API is left out of our capture.
+    ///
+    /// # Arguments
+    ///
+    /// * `tap` - The tap device or veth to capture traffic on
+    /// * `path` - The path to write our pcap to
+    /// * `api` - The url of the Thorium API
+    pub fn start(tap: &str, path: PathBuf, api: &str) -> Result<Self, Error> {
+        let filter = api_capture_filter(api)?;
+        let child = Command::new("tcpdump")
+            .arg("-i")
+            .arg(tap)
+            .arg("-U")
+            .arg("-w")
+            .arg(&path)
+            .arg(filter)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        Ok(TapCapture { child, path })
+    }
+
+    /// Stop capturing traffic and get the path to our finished pcap
+    pub async fn stop(mut self) -> Result<PathBuf, Error> {
+        // interrupt tcpdump so it flushes our pcap before exiting
+        if let Some(pid) = self.child.id() {
+            Command::new("kill")
+                .arg("-INT")
+                .arg(pid.to_string())
+                .status()
+                .await?;
+        }
+        self.child.wait().await?;
+        Ok(self.path)
+    }
+}
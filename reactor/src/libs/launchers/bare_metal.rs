@@ -1,17 +1,32 @@
 //! Launches bare metal jobs
+//!
+//! Workers whose images capture network traffic run a single job in their own
+//! network namespace whose veth is captured for the lifetime of the worker.
 use rustix::process::Signal;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
-use thorium::models::{ArgStrategy, ImageScaler, Node, Worker, WorkerDeleteMap, WorkerStatus};
+use std::path::{Path, PathBuf};
+use thorium::models::{
+    ActiveJob, ArgStrategy, Image, ImageScaler, Node, OriginRequest, Worker, WorkerDeleteMap,
+    WorkerStatus,
+};
 use thorium::{Error, Thorium};
 use tokio::process::{Child, Command};
 use tracing::{event, span, Level, Span};
 
 mod cgroups;
+mod netns;
+mod redirect;
 
+use super::artifacts::{self, TapCapture};
 use super::Launcher;
+use crate::args;
 use crate::libs::keys;
 use cgroups::Cgroup;
+use netns::{Namespaces, Netns};
+use redirect::{Redirect, SimulatedInternet};
+
+/// The directory to write the captures of bare metal workers to
+const CAPTURE_DIR: &str = "/tmp/thorium-capture";
 
 /// purge a directory if its a file or directory
 macro_rules! purge_parent {
@@ -93,21 +108,196 @@ fn isolate<P: AsRef<Path>>(raw: P, id: &str) -> Result<String, Error> {
     }
 }
 
+/// Remove a worker's simulated internet redirect rules if it has any
+///
+/// # Arguments
+///
+/// * `redirect` - The redirect rules to remove
+/// * `span` - The span to log traces under
+fn discard_redirect(redirect: Option<Redirect>, span: &Span) {
+    if let Some(Err(error)) = redirect.map(Redirect::remove) {
+        // we failed to remove this workers redirect rules
+        event!(
+            parent: span,
+            Level::ERROR,
+            error = true,
+            error_msg = error.to_string()
+        );
+    }
+}
+
+/// Remove a worker's network namespace if it has one
+///
+/// # Arguments
+///
+/// * `netns` - The network namespace to remove
+/// * `span` - The span to log traces under
+fn discard_netns(netns: Option<Netns>, span: &Span) {
+    if let Some(Err(error)) = netns.as_ref().map(Netns::remove) {
+        // we failed to remove this workers network namespace
+        event!(
+            parent: span,
+            Level::ERROR,
+            error = true,
+            error_msg = error.to_string()
+        );
+    }
+}
+
 /// A currently active bare metal worker
 struct ActiveWorker {
     /// The control group this worker is tied too
     cgroup: Cgroup,
     /// The spawned child process if we have one
     child: Option<Child>,
+    /// The rules redirecting this worker to the simulated internet if it has any
+    redirect: Option<Redirect>,
+    /// The network namespace this worker runs in if its traffic is captured
+    netns: Option<Netns>,
+    /// The capture of this workers network traffic if its image captures it
+    capture: Option<TapCapture>,
+    /// The user this worker is executing jobs as
+    user: String,
+    /// The group this workers image is in
+    group: String,
+    /// The name of this workers image
+    image: String,
+    /// The job this worker was last seen executing
+    job: Option<ActiveJob>,
 }
 
 impl ActiveWorker {
     /// Spawn a new active worker
-    pub async fn new(thorium: &Thorium, worker: &Worker, span: &Span) -> Result<Self, Error> {
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `worker` - The worker to spawn
+    /// * `simulated_internet` - The simulated internet to redirect to if one is configured
+    /// * `namespaces` - The network namespaces to give workers that capture their traffic
+    /// * `span` - The span to log traces under
+    pub async fn new(
+        thorium: &Thorium,
+        worker: &Worker,
+        simulated_internet: Option<&SimulatedInternet>,
+        namespaces: &mut Namespaces,
+        span: &Span,
+    ) -> Result<Self, Error> {
         // get our image
         let image = thorium.images.get(&worker.group, &worker.stage).await?;
+        // give this worker its own network namespace to capture if its traffic is captured
+        let netns = if image.capture_network {
+            Some(namespaces.create(&worker.name)?)
+        } else {
+            None
+        };
+        let capture = match &netns {
+            Some(netns) => Some(Self::start_capture(thorium, &worker.name, netns).await),
+            None => None,
+        };
+        let capture = match capture.transpose() {
+            Ok(capture) => capture,
+            Err(error) => {
+                discard_netns(netns, span);
+                return Err(error);
+            }
+        };
+        // spawn our agent in this workers network namespace if it has one
+        match Self::spawn(
+            thorium,
+            worker,
+            &image,
+            simulated_internet,
+            netns.as_ref(),
+            span,
+        )
+        .await
+        {
+            Ok((cgroup, child, redirect)) => Ok(ActiveWorker {
+                cgroup,
+                child: Some(child),
+                redirect,
+                netns,
+                capture,
+                user: worker.user.clone(),
+                group: worker.group.clone(),
+                image: image.name.clone(),
+                job: None,
+            }),
+            Err(error) => {
+                // stop our capture and remove our network namespace since this worker never started
+                if let Some(capture) = capture {
+                    match capture.stop().await {
+                        Ok(pcap) => {
+                            if let Err(error) = tokio::fs::remove_file(&pcap).await {
+                                event!(
+                                    parent: span,
+                                    Level::ERROR,
+                                    error = true,
+                                    error_msg = error.to_string()
+                                );
+                            }
+                        }
+                        Err(error) => {
+                            event!(
+                                parent: span,
+                                Level::ERROR,
+                                error = true,
+                                error_msg = error.to_string()
+                            );
+                        }
+                    }
+                }
+                discard_netns(netns, span);
+                Err(error)
+            }
+        }
+    }
+
+    /// Start capturing the traffic of a workers network namespace
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `name` - The name of the worker to capture
+    /// * `netns` - The network namespace this worker runs in
+    async fn start_capture(
+        thorium: &Thorium,
+        name: &str,
+        netns: &Netns,
+    ) -> Result<TapCapture, Error> {
+        tokio::fs::create_dir_all(CAPTURE_DIR).await?;
+        let path = PathBuf::from(CAPTURE_DIR).join(format!("{name}.pcap"));
+        TapCapture::start(&netns.veth(), path, &thorium.host)
+    }
+
+    /// Spawn the agent for a new worker
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `worker` - The worker to spawn
+    /// * `image` - The image this worker is for
+    /// * `simulated_internet` - The simulated internet to redirect to if one is configured
+    /// * `netns` - The network namespace to spawn our agent in if any
+    /// * `span` - The span to log traces under
+    async fn spawn(
+        thorium: &Thorium,
+        worker: &Worker,
+        image: &Image,
+        simulated_internet: Option<&SimulatedInternet>,
+        netns: Option<&Netns>,
+        span: &Span,
+    ) -> Result<(Cgroup, Child, Option<Redirect>), Error> {
         // build the control group for this worker
-        let mut cgroup = Cgroup::new(&worker.name, &image)?;
+        let mut cgroup = Cgroup::new(&worker.name, image)?;
+        // get the simulated internet if this worker's network policies redirect it there
+        let simulated_internet = match simulated_internet {
+            Some(simulated_internet) if SimulatedInternet::redirected(thorium, image).await? => {
+                Some(simulated_internet)
+            }
+            _ => None,
+        };
         // build the path to this users keys
         let keys = keys::path(&worker.user);
         // convert our keys path to a str
@@ -140,14 +330,45 @@ impl ActiveWorker {
             keys_str,
             "bare-metal",
         ];
+        // build the command to spawn our agent
+        let mut cmd = match netns {
+            Some(netns) => netns.command("/opt/thorium/thorium-agent"),
+            None => Command::new("/opt/thorium/thorium-agent"),
+        };
+        cmd.args(args);
+        // redirect this worker to the simulated internet before it starts
+        let redirect = match simulated_internet {
+            Some(simulated_internet) => {
+                let netns = netns.map(|netns| netns.name.as_str());
+                Some(simulated_internet.redirect(&worker.name, netns)?)
+            }
+            None => None,
+        };
         // spawn our agent
-        let child = Command::new("/opt/thorium/thorium-agent")
-            .args(args)
-            .spawn()?;
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(error) => {
+                // remove our redirect rules since this worker never started
+                discard_redirect(redirect, span);
+                return Err(error.into());
+            }
+        };
         // get the pid of the process we just spawned if it has one
         if let Some(pid) = child.id() {
             // add this pid to our cgroup
-            cgroup.add(pid)?;
+            if let Err(error) = cgroup.add(pid) {
+                // stop our agent and remove our redirect rules since we can't track this worker
+                if let Err(error) = child.kill().await {
+                    event!(
+                        parent: span,
+                        Level::ERROR,
+                        error = true,
+                        error_msg = error.to_string()
+                    );
+                }
+                discard_redirect(redirect, span);
+                return Err(error);
+            }
         } else {
             event!(
                 parent: span,
@@ -156,12 +377,52 @@ impl ActiveWorker {
                 error_msg = "Failed to add child to cgroup!"
             )
         }
-        // build our worker struct
-        let active = ActiveWorker {
-            cgroup,
-            child: Some(child),
+        Ok((cgroup, child, redirect))
+    }
+
+    /// Stop capturing this workers network traffic and submit it if its job finished
+    ///
+    /// # Arguments
+    ///
+    /// * `finished` - Whether this worker finished its job
+    async fn finish_capture(&mut self, finished: bool) -> Result<(), Error> {
+        let Some(capture) = self.capture.take() else {
+            return Ok(());
+        };
+        let pcap = capture.stop().await?;
+        let submitted = match (&self.job, finished) {
+            (Some(job), true) => {
+                artifacts::submit_child(&self.user, &self.group, job, &pcap, |sha256| {
+                    OriginRequest::transformed(
+                        sha256,
+                        Some(self.image.clone()),
+                        vec!["capture_network"],
+                        None,
+                    )
+                })
+                .await
+            }
+            _ => Ok(()),
         };
-        Ok(active)
+        // remove our pcap even if we failed to submit it
+        tokio::fs::remove_file(&pcap).await?;
+        submitted
+    }
+
+    /// Stop capturing this worker's traffic and remove its network namespace and redirect rules
+    ///
+    /// # Arguments
+    ///
+    /// * `finished` - Whether this worker finished its job and its capture should be submitted
+    /// * `span` - The span to log traces under
+    pub async fn teardown_network(&mut self, finished: bool, span: &Span) {
+        // stop capturing this workers network traffic
+        if let Err(error) = self.finish_capture(finished).await {
+            event!(parent: span, Level::ERROR, msg = "Failed to submit network capture", error = error.to_string());
+        }
+        // redirect rules in a network namespace must be removed before it is
+        discard_redirect(self.redirect.take(), span);
+        discard_netns(self.netns.take(), span);
     }
 
     /// Checks if this worker is alive still
@@ -244,6 +505,10 @@ pub struct BareMetal {
     node: String,
     /// A map of currently active workers
     active: HashMap<String, ActiveWorker>,
+    /// The simulated internet to redirect workers to if one is configured
+    simulated_internet: Option<SimulatedInternet>,
+    /// The network namespaces to give workers that capture their traffic
+    namespaces: Namespaces,
 }
 
 impl BareMetal {
@@ -253,11 +518,18 @@ impl BareMetal {
     ///
     /// * `cluster` - The cluster we are in
     /// * `node` - The node we are on
-    pub fn new<C: Into<String>, N: Into<String>>(cluster: C, node: N) -> Self {
+    /// * `args` - The bare metal launcher args
+    pub fn new<C: Into<String>, N: Into<String>>(
+        cluster: C,
+        node: N,
+        args: &args::BareMetal,
+    ) -> Self {
         BareMetal {
             cluster: cluster.into(),
             node: node.into(),
             active: HashMap::with_capacity(25),
+            simulated_internet: SimulatedInternet::new(args),
+            namespaces: Namespaces::new(args.capture_network),
         }
     }
 
@@ -277,29 +549,44 @@ impl BareMetal {
     ) -> Result<(), Error> {
         // keep a list of workers that should be deleted since they no longer exist
         let mut deletes = WorkerDeleteMap::default();
+        // remember the jobs our workers are executing so we can tie their captures to them
+        for (name, worker_info) in info.workers.iter() {
+            if let (Some(worker), Some(job)) = (self.active.get_mut(name), &worker_info.active) {
+                worker.job = Some(job.clone());
+            }
+        }
+        // find any workers that have completed
+        let exited = self
+            .active
+            .iter_mut()
+            .filter_map(|(name, worker)| {
+                // get whether this worker is alive or not
+                let alive = match worker.alive(span) {
+                    Ok(alive) => alive,
+                    Err(error) => {
+                        // we failed to get whether this worker was alive or not
+                        event!(
+                            parent: span,
+                            Level::ERROR,
+                            error = true,
+                            error_msg = error.to_string()
+                        );
+                        // default to this worker still being alive
+                        true
+                    }
+                };
+                (!alive).then(|| name.clone())
+            })
+            .collect::<Vec<String>>();
         // drop any workers that have completed
-        self.active.retain(|name, worker| {
-            // get whether this worker is alive or not
-            let alive = match worker.alive(span) {
-                Ok(alive) => alive,
-                Err(error) => {
-                    // we failed to get whether this worker was alive or not
-                    event!(
-                        parent: span,
-                        Level::ERROR,
-                        error = true,
-                        error_msg = error.to_string()
-                    );
-                    // default to this worker still being alive
-                    true
-                }
-            };
-            // delete this workers info its not alive
-            if !alive {
-                // get this workers and add it to our deletes
-                if let Some(info) = active.remove(name) {
-                    deletes.add_mut(info.name);
-                }
+        for name in exited {
+            // get this workers and add it to our deletes
+            if let Some(info) = active.remove(&name) {
+                deletes.add_mut(info.name);
+            }
+            if let Some(mut worker) = self.active.remove(&name) {
+                // submit this workers capture and stop redirecting it to the simulated internet
+                worker.teardown_network(true, span).await;
                 // try to delete this workers cgroup
                 if let Err(error) = worker.cgroup.delete() {
                     // we failed to delete a cgroup
@@ -310,11 +597,8 @@ impl BareMetal {
                         error_msg = error.to_string()
                     );
                 }
-                false
-            } else {
-                true
             }
-        });
+        }
         // Add any running workers that do not exist on our node to our delete map
         for (name, worker) in info.workers.iter() {
             // add any workers that are running and not in our active set to our delete list
@@ -348,7 +632,7 @@ impl BareMetal {
         let recovered = false;
         // filter out any workers that we already know about
         // crawl the workers that should exist on this node and check if their control group exists
-        for (name, _) in info.workers.iter() {
+        for (name, worker) in info.workers.iter() {
             // skip any workers that we know about
             if self.active.contains_key(name) {
                 continue;
@@ -364,10 +648,38 @@ impl BareMetal {
                     msg = "Recovered worker",
                     name = &name
                 );
-                // build our recovered worker without a child
+                // rebuild this worker's network namespace so its removed once it exits
+                let netns = self.namespaces.recover(name);
+                // rebuild this worker's redirect rules so they are removed once it exits
+                let netns_name = netns.as_ref().map(|netns| netns.name.as_str());
+                let redirect = match &self.simulated_internet {
+                    Some(simulated_internet) => {
+                        match simulated_internet.recover(name, netns_name) {
+                            Ok(redirect) => redirect,
+                            Err(error) => {
+                                event!(
+                                    parent: span,
+                                    Level::ERROR,
+                                    error = true,
+                                    error_msg = error.to_string()
+                                );
+                                None
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                // build our recovered worker without a child or a capture
                 let recovered = ActiveWorker {
                     cgroup,
                     child: None,
+                    redirect,
+                    netns,
+                    capture: None,
+                    user: worker.user.clone(),
+                    group: worker.group.clone(),
+                    image: worker.stage.clone(),
+                    job: None,
                 };
                 // add this worker to our active map
                 self.active.insert(name.clone(), recovered);
@@ -403,6 +715,8 @@ impl BareMetal {
                 // this worker is alive so kill all of its processes
                 worker.kill(&span).await?;
             }
+            // stop capturing and redirecting this worker
+            worker.teardown_network(false, &span).await;
         }
         Ok(())
     }
@@ -515,7 +829,14 @@ impl Launcher for BareMetal {
             stage = worker.stage
         );
         // start our agent
-        let active = ActiveWorker::new(thorium, worker, &span).await?;
+        let active = ActiveWorker::new(
+            thorium,
+            worker,
+            self.simulated_internet.as_ref(),
+            &mut self.namespaces,
+            &span,
+        )
+        .await?;
         // add this active worker to our map
         self.active.insert(worker.name.clone(), active);
         Ok(())
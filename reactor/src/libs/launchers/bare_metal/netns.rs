@@ -0,0 +1,242 @@
+//! Give bare metal workers that capture their network traffic their own network namespace
+//!
+//! Each captured worker runs in a network namespace that is connected to the host by
+//! a veth pair and NATed out through the host. The reactor captures the host side of
+//! this veth so only that worker's traffic ends up in its capture. The host must have
+//! ip forwarding enabled for these namespaces to reach the network.
+
+use std::net::Ipv4Addr;
+use std::process::Command;
+use thorium::Error;
+
+/// The number of /30 networks a /16 can be split into
+const SLOTS: u32 = 16_384;
+
+/// Run an `ip` command to manage network namespaces
+///
+/// # Arguments
+///
+/// * `args` - The args to pass to `ip`
+fn ip(args: &[&str]) -> Result<(), Error> {
+    let output = Command::new("ip").args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "ip {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Add or delete the rule that NATs a namespace out through the host
+///
+/// # Arguments
+///
+/// * `action` - Whether to add or delete this rule
+/// * `ip` - The ip of the namespace to NAT
+fn masquerade(action: &str, ip: Ipv4Addr) -> Result<(), Error> {
+    let source = format!("{ip}/32");
+    let status = Command::new("iptables")
+        .args([
+            "-t",
+            "nat",
+            action,
+            "POSTROUTING",
+            "-s",
+            &source,
+            "-j",
+            "MASQUERADE",
+        ])
+        .status()?;
+    if !status.success() {
+        return Err(Error::new(format!(
+            "Failed to {action} masquerade rule for {ip}: {status}"
+        )));
+    }
+    Ok(())
+}
+
+/// Get the name of the network namespace for a worker
+///
+/// # Arguments
+///
+/// * `name` - The name of the worker
+fn netns_name(name: &str) -> String {
+    format!("thorium-{name}")
+}
+
+/// Hands out the addresses for our workers network namespaces
+pub struct Namespaces {
+    /// The /16 network to give our namespaces addresses from
+    network: Ipv4Addr,
+    /// The next /30 network to try to use
+    next: u32,
+}
+
+impl Namespaces {
+    /// Create a new network namespace allocator
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The /16 network to give our namespaces addresses from
+    pub fn new(network: Ipv4Addr) -> Self {
+        Namespaces { network, next: 0 }
+    }
+
+    /// Create a network namespace for a worker
+    ///
+    /// Our slot counter restarts whenever the reactor does so any veths that still exist
+    /// from workers started before a restart are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the worker to create a network namespace for
+    pub fn create(&mut self, name: &str) -> Result<Netns, Error> {
+        // find a slot whose veth isn't already in use
+        let mut tries = 0;
+        let netns = loop {
+            if tries == SLOTS {
+                return Err(Error::new("No free network namespace addresses"));
+            }
+            let netns = Netns {
+                name: netns_name(name),
+                network: self.network,
+                slot: self.next % SLOTS,
+            };
+            self.next += 1;
+            tries += 1;
+            let veth = netns.veth();
+            if !Command::new("ip")
+                .args(["link", "show", "dev", &veth])
+                .output()?
+                .status
+                .success()
+            {
+                break netns;
+            }
+        };
+        // remove any partially created namespace if we fail to set it up
+        if let Err(error) = netns.setup() {
+            return match netns.remove() {
+                Ok(()) => Err(error),
+                Err(cleanup) => Err(Error::new(format!("{error} ({cleanup})"))),
+            };
+        }
+        Ok(netns)
+    }
+
+    /// Rebuild the network namespace for a worker that was started before the reactor restarted
+    ///
+    /// Returns None if this worker has no network namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the worker to rebuild the network namespace for
+    pub fn recover(&self, name: &str) -> Option<Netns> {
+        let netns = netns_name(name);
+        // get the address of this namespaces side of its veth
+        let output = Command::new("ip")
+            .args(["-n", &netns, "-4", "-o", "addr", "show", "dev", "eth0"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let ip = stdout
+            .split_whitespace()
+            .skip_while(|field| *field != "inet")
+            .nth(1)?
+            .split('/')
+            .next()?
+            .parse::<Ipv4Addr>()
+            .ok()?;
+        // our namespaces side is always the second address in its slot
+        let offset = u32::from(ip).checked_sub(u32::from(self.network))?;
+        Some(Netns {
+            name: netns,
+            network: self.network,
+            slot: offset / 4,
+        })
+    }
+}
+
+/// The network namespace for a single worker
+pub struct Netns {
+    /// The name of this network namespace
+    pub name: String,
+    /// The /16 network this namespace got its addresses from
+    network: Ipv4Addr,
+    /// The /30 network in our /16 that this namespace uses
+    slot: u32,
+}
+
+impl Netns {
+    /// Get the name of the host side of this namespaces veth
+    pub fn veth(&self) -> String {
+        // interface names are limited to 15 chars
+        format!("thbm{}", self.slot)
+    }
+
+    /// Get the address of the host side of this namespaces veth
+    fn host_ip(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.network) + self.slot * 4 + 1)
+    }
+
+    /// Get the address of this namespaces side of its veth
+    pub fn ip(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.network) + self.slot * 4 + 2)
+    }
+
+    /// Create this network namespace and connect it to the host
+    fn setup(&self) -> Result<(), Error> {
+        let veth = self.veth();
+        let host = format!("{}/30", self.host_ip());
+        let local = format!("{}/30", self.ip());
+        let gateway = self.host_ip().to_string();
+        ip(&["netns", "add", &self.name])?;
+        ip(&[
+            "link", "add", &veth, "type", "veth", "peer", "name", "eth0", "netns", &self.name,
+        ])?;
+        ip(&["addr", "add", &host, "dev", &veth])?;
+        ip(&["link", "set", &veth, "up"])?;
+        ip(&["-n", &self.name, "addr", "add", &local, "dev", "eth0"])?;
+        ip(&["-n", &self.name, "link", "set", "eth0", "up"])?;
+        ip(&["-n", &self.name, "link", "set", "lo", "up"])?;
+        ip(&["-n", &self.name, "route", "add", "default", "via", &gateway])?;
+        masquerade("-A", self.ip())
+    }
+
+    /// Build a command that runs inside this network namespace
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program to run
+    pub fn command(&self, program: &str) -> tokio::process::Command {
+        let mut cmd = tokio::process::Command::new("ip");
+        cmd.args(["netns", "exec", &self.name, program]);
+        cmd
+    }
+
+    /// Remove this network namespace and the rule NATing it out through the host
+    ///
+    /// Deleting our namespace also deletes its veth pair. Both are removed even if
+    /// removing the other fails.
+    pub fn remove(&self) -> Result<(), Error> {
+        let errors = [
+            masquerade("-D", self.ip()),
+            ip(&["netns", "delete", &self.name]),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(errors.join(", ")))
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Redirect bare metal workers to the simulated internet
+//!
+//! Bare metal workers don't get their own network namespace so we match their
+//! traffic by control group and NAT it to the simulated internet instead. This
+//! requires the unified (v2) cgroup hierarchy. Workers that capture their network
+//! traffic run in their own network namespace so their rules are added there.
+
+use std::net::IpAddr;
+use std::process::Command;
+use thorium::models::{Image, NetworkPolicyListOpts};
+use thorium::{Error, Thorium};
+
+use crate::args;
+
+/// Build a command that runs iptables in a network namespace or on the host
+///
+/// # Arguments
+///
+/// * `iptables` - The iptables binary to run
+/// * `netns` - The network namespace to run in if any
+fn iptables_cmd(iptables: &str, netns: Option<&str>) -> Command {
+    match netns {
+        Some(netns) => {
+            let mut cmd = Command::new("ip");
+            cmd.args(["netns", "exec", netns, iptables]);
+            cmd
+        }
+        None => Command::new(iptables),
+    }
+}
+
+/// The simulated internet to redirect workers to
+#[derive(Debug, Clone)]
+pub struct SimulatedInternet {
+    /// The ip of the simulated internet
+    ip: IpAddr,
+    /// The networks redirected workers can still reach directly
+    exempt: Vec<String>,
+}
+
+impl SimulatedInternet {
+    /// Get the simulated internet to redirect to if one is configured
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The bare metal launcher args
+    pub fn new(args: &args::BareMetal) -> Option<Self> {
+        args.simulated_internet.map(|ip| SimulatedInternet {
+            ip,
+            exempt: args.simulated_internet_exempt.clone(),
+        })
+    }
+
+    /// Check if an image's network policies redirect it to the simulated internet
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `image` - The image to check
+    pub async fn redirected(thorium: &Thorium, image: &Image) -> Result<bool, Error> {
+        // get all of the network policies in this image's group
+        let opts = NetworkPolicyListOpts::default().group(&image.group);
+        let mut cursor = thorium.network_policies.list_details(&opts).await?;
+        loop {
+            // check if any forced or image specific policies use the simulated internet
+            if cursor.data.iter().any(|policy| {
+                (policy.forced_policy || image.network_policies.contains(&policy.name))
+                    && policy.simulated_internet()
+            }) {
+                return Ok(true);
+            }
+            if cursor.exhausted() {
+                return Ok(false);
+            }
+            cursor.refill().await?;
+        }
+    }
+
+    /// Get the iptables binary to manage our rules with
+    fn iptables(&self) -> &'static str {
+        if self.ip.is_ipv4() {
+            "iptables"
+        } else {
+            "ip6tables"
+        }
+    }
+
+    /// Rebuild the redirect for a worker that was started before the reactor restarted
+    ///
+    /// Returns None if this worker has no redirect rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the worker to rebuild the redirect for
+    /// * `netns` - The network namespace this worker runs in if any
+    pub fn recover(&self, name: &str, netns: Option<&str>) -> Result<Option<Redirect>, Error> {
+        let iptables = self.iptables();
+        // list the current rules in the nat table's output chain
+        let output = iptables_cmd(iptables, netns)
+            .args(["-t", "nat", "-S", "OUTPUT"])
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "Failed to list simulated internet rules: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        // find the rules that match on this worker's control group
+        let path = format!("thorium/{name}");
+        let rules = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_prefix("-A OUTPUT "))
+            .map(|rule| {
+                rule.split_whitespace()
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<String>>()
+            })
+            .filter(|rule| {
+                rule.windows(2)
+                    .any(|pair| pair[0] == "--path" && pair[1] == path)
+            })
+            .collect::<Vec<Vec<String>>>();
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Redirect {
+            iptables,
+            netns: netns.map(ToOwned::to_owned),
+            rules,
+        }))
+    }
+
+    /// Redirect a worker's traffic to the simulated internet
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the worker to redirect
+    /// * `netns` - The network namespace this worker runs in if any
+    pub fn redirect(&self, name: &str, netns: Option<&str>) -> Result<Redirect, Error> {
+        let ip = self.ip;
+        let loopback = if ip.is_ipv4() {
+            "127.0.0.0/8"
+        } else {
+            "::1/128"
+        };
+        // sinkhole DNS first so exempt networks can't resolve real domains
+        let mut rules = vec![
+            format!("-p udp --dport 53 -j DNAT --to-destination {ip}"),
+            format!("-p tcp --dport 53 -j DNAT --to-destination {ip}"),
+            format!("-d {loopback} -j RETURN"),
+        ];
+        // let any exempt networks through untouched
+        for network in &self.exempt {
+            rules.push(format!("-d {network} -j RETURN"));
+        }
+        // send everything else to the simulated internet
+        rules.push(format!("-p tcp -j DNAT --to-destination {ip}"));
+        rules.push(format!("-p udp -j DNAT --to-destination {ip}"));
+        // prefix each rule with a match on this worker's control group
+        let path = format!("thorium/{name}");
+        let mut redirect = Redirect {
+            iptables: self.iptables(),
+            netns: netns.map(ToOwned::to_owned),
+            rules: Vec::with_capacity(rules.len()),
+        };
+        for rule in rules {
+            let rule = ["-m", "cgroup", "--path", path.as_str()]
+                .into_iter()
+                .chain(rule.split_whitespace())
+                .map(ToOwned::to_owned)
+                .collect::<Vec<String>>();
+            // remove any rules we already added if we fail to add this one
+            if let Err(error) = redirect.iptables("-A", &rule) {
+                return match redirect.remove() {
+                    Ok(()) => Err(error),
+                    Err(cleanup) => Err(Error::new(format!("{error} ({cleanup})"))),
+                };
+            }
+            redirect.rules.push(rule);
+        }
+        Ok(redirect)
+    }
+}
+
+/// The NAT rules redirecting a single worker to the simulated internet
+pub struct Redirect {
+    /// The iptables binary to use
+    iptables: &'static str,
+    /// The network namespace our rules are in if any
+    netns: Option<String>,
+    /// The rules we have added
+    rules: Vec<Vec<String>>,
+}
+
+impl Redirect {
+    /// Add or delete a rule in the nat table's output chain
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - Whether to add or delete this rule
+    /// * `rule` - The rule to add or delete
+    fn iptables(&self, action: &str, rule: &[String]) -> Result<(), Error> {
+        let status = iptables_cmd(self.iptables, self.netns.as_deref())
+            .args(["-t", "nat", action, "OUTPUT"])
+            .args(rule)
+            .status()?;
+        if !status.success() {
+            return Err(Error::new(format!(
+                "Failed to {action} simulated internet rule {rule:?}: {status}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Remove this worker's redirect rules
+    ///
+    /// Every rule is removed even if removing an earlier one fails.
+    pub fn remove(self) -> Result<(), Error> {
+        let errors = self
+            .rules
+            .iter()
+            .rev()
+            .filter_map(|rule| self.iptables("-D", rule).err())
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(errors.join(", ")))
+        }
+    }
+}
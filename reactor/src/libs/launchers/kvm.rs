@@ -1,23 +1,136 @@
 //! Laucnhes KVM vms for Thorium
+//!
+//! Each worker boots from a disposable qcow2 overlay on top of its image's golden
+//! qcow2 so no state is left behind between jobs. Vms are either cold booted or
+//! restored directly from a saved memory state and are destroyed once their agent
+//! exits. Restored vms keep the name and uuid of the domain they were saved from so
+//! the scaler only places one vm restored from a memory state on a node at a time.
+//! Images that capture network traffic have their vms tap device captured for the
+//! lifetime of the vm.
 
-use std::collections::{BTreeSet, HashMap, HashSet};
-use std::path::PathBuf;
-use thorium::models::{Image, Node, Worker};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use thorium::models::{
+    ActiveJob, Image, ImageScaler, Kvm as KvmSettings, Node, OriginRequest, Worker, WorkerDeleteMap,
+};
 use thorium::{Error, Thorium};
 use tokio::process::Command;
-use tracing::{span, Level, Span};
+use tokio::time::{Duration, Instant};
+use tracing::{event, span, Level, Span};
 use virt::connect::Connect;
 use virt::domain::Domain;
-use virt::domain_snapshot::DomainSnapshot;
 
+use super::artifacts::{self, TapCapture};
 use super::Launcher;
 use crate::libs::keys;
 
+/// The extensions of the temp files we create for each vm
+const TEMP_EXTENSIONS: [&str; 5] = ["qcow2", "iso", "xml", "dump", "pcap"];
+
+/// Cast a libvirt error to a Thorium error
+///
+/// # Arguments
+///
+/// * `error` - The libvirt error to cast
+fn virt_err(error: virt::error::Error) -> Error {
+    Error::new(format!("Libvirt error: {error}"))
+}
+
+/// Get the contents of the first instance of an xml element
+///
+/// # Arguments
+///
+/// * `xml` - The xml to search
+/// * `element` - The element to get
+fn get_element<'a>(xml: &'a str, element: &str) -> Option<&'a str> {
+    let open = format!("<{element}>");
+    let close = format!("</{element}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(xml[start..end].trim())
+}
+
+/// Replace the contents of the first instance of an xml element
+///
+/// # Arguments
+///
+/// * `xml` - The xml to update
+/// * `element` - The element to update
+/// * `value` - The value to set
+fn replace_element(xml: &str, element: &str, value: &str) -> Option<String> {
+    let open = format!("<{element}>");
+    let close = format!("</{element}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(format!("{}{value}{}", &xml[..start], &xml[end..]))
+}
+
+/// Remove the first instance of an xml element if it exists
+///
+/// # Arguments
+///
+/// * `xml` - The xml to update
+/// * `element` - The element to remove
+fn remove_element(xml: &str, element: &str) -> String {
+    let open = format!("<{element}>");
+    let close = format!("</{element}>");
+    if let Some(start) = xml.find(&open) {
+        if let Some(end) = xml[start..].find(&close) {
+            return format!("{}{}", &xml[..start], &xml[start + end + close.len()..]);
+        }
+    }
+    xml.to_owned()
+}
+
+/// Run a virsh command against our libvirt daemon
+///
+/// # Arguments
+///
+/// * `socket` - The socket to connect to our libvirt daemon at
+/// * `args` - The args to pass to virsh
+async fn virsh<S: AsRef<OsStr>>(socket: &str, args: &[S]) -> Result<(), Error> {
+    let output = Command::new("virsh")
+        .arg("-c")
+        .arg(socket)
+        .args(args)
+        .output()
+        .await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "virsh failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// A vm we have launched for a worker
+struct ActiveVm {
+    /// The libvirt domain for this vm once it has been created
+    domain: Option<String>,
+    /// The user this vm is executing jobs as
+    user: String,
+    /// The group this vms image is in
+    group: String,
+    /// The name of this vms image
+    image: String,
+    /// The job this vm was last seen executing
+    job: Option<ActiveJob>,
+    /// Whether to dump this vms memory before destroying it
+    dump_memory: bool,
+    /// When to launch our agent if it hasn't been launched yet
+    launch_at: Option<Instant>,
+    /// The capture of this vms network traffic if its image captures it
+    capture: Option<TapCapture>,
+}
+
 pub struct Kvm {
     /// The kvm specific args
     args: crate::args::Kvm,
-    /// The inactive domains in a golden state we can spawn new workers on
-    golden: HashMap<String, HashMap<String, BTreeSet<String>>>,
+    /// The vms we have launched mapped by worker name
+    vms: HashMap<String, ActiveVm>,
 }
 
 impl Kvm {
@@ -30,27 +143,84 @@ impl Kvm {
         // build our kvm launcher
         let kvm = Kvm {
             args: args.clone(),
-            golden: HashMap::default(),
+            vms: HashMap::default(),
         };
         Ok(kvm)
     }
 
-    /// Find a golden vm for this image type
+    /// Build the path to a temp file for a worker
     ///
     /// # Arguments
     ///
-    /// * `image` - The image to find a golden image slot for
-    fn find_golden(&mut self, image: &Image) -> Option<String> {
-        // get this images group set
-        if let Some(image_set) = self.golden.get_mut(&image.group) {
-            // get the golden vms set
-            if let Some(golden_set) = image_set.get_mut(&image.name) {
-                // pop the first golden vm name
-                return golden_set.pop_first();
-            }
+    /// * `name` - The name of the worker
+    /// * `extension` - The extension of this temp file
+    fn temp_path(&self, name: &str, extension: &str) -> PathBuf {
+        let mut path = self.args.temp.join(name);
+        path.set_extension(extension);
+        path
+    }
+
+    /// Create a disposable overlay on top of a golden qcow2 image
+    ///
+    /// # Arguments
+    ///
+    /// * `golden` - The golden qcow2 image to overlay
+    /// * `overlay` - The path to write our overlay to
+    async fn create_overlay(golden: &str, overlay: &Path) -> Result<(), Error> {
+        let output = Command::new("qemu-img")
+            .args(["create", "-f", "qcow2", "-F", "qcow2", "-b", golden])
+            .arg(overlay)
+            .output()
+            .await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(Error::new(format!(
+                "Failed to create overlay: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    /// Build the domain xml for a worker from our golden xml
+    ///
+    /// This returns the name of the domain along with its xml.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The kvm settings for this workers image
+    /// * `name` - The name of the worker
+    /// * `overlay` - The overlay this worker should boot from
+    async fn build_domain_xml(
+        settings: &KvmSettings,
+        name: &str,
+        overlay: &Path,
+    ) -> Result<(String, String), Error> {
+        let golden = tokio::fs::read_to_string(&settings.xml).await?;
+        let (domain, xml) = if settings.memory_state.is_some() {
+            // restored vms must keep the name and uuid of the domain their state was saved from
+            let Some(domain) = get_element(&golden, "name") else {
+                return Err(Error::new(format!("{} has no domain name", settings.xml)));
+            };
+            (domain.to_owned(), golden.clone())
+        } else {
+            // cold booted vms get a unique name and uuid so they can run side by side
+            let Some(xml) = replace_element(&golden, "name", name) else {
+                return Err(Error::new(format!("{} has no domain name", settings.xml)));
+            };
+            (name.to_owned(), remove_element(&xml, "uuid"))
+        };
+        // boot from our overlay instead of the golden qcow2
+        if !xml.contains(&settings.qcow2) {
+            return Err(Error::new(format!(
+                "{} does not use {}",
+                settings.xml, settings.qcow2
+            )));
         }
-        // we did not find a golden vm
-        None
+        Ok((
+            domain,
+            xml.replace(&settings.qcow2, &overlay.to_string_lossy()),
+        ))
     }
 
     /// Build the xml to attach an isoi
@@ -104,38 +274,310 @@ impl Kvm {
         Ok(xml)
     }
 
-    /// Convert a golden vm into an active worker VM
+    /// Boot a vm for a worker on a disposable overlay
     ///
     /// # Arguments
     ///
-    /// * `golden` - The name of the golden image that was used
-    /// * `worker` - The worker we are assining a golden image too
+    /// * `image` - The image this worker is for
+    /// * `worker` - The worker to boot a vm for
+    /// * `vm` - The vm to set the domain for once its created
     /// * `span` - The span to log traces under
-    pub async fn convert(
-        &mut self,
-        golden: String,
+    async fn boot(
+        &self,
+        image: &Image,
         worker: &Worker,
+        vm: &mut ActiveVm,
         span: &Span,
-    ) -> Result<(), Error> {
-        // start our start kvm domain span
-        let span = span!(parent: span, Level::INFO, "Convert Golden Domain", name = &worker.name);
-        // build the iso for this vm
-        let xml = self.build_iso_xml(worker, &span).await?;
+    ) -> Result<Instant, Error> {
+        // start our boot vm span
+        let span = span!(parent: span, Level::INFO, "Boot VM", name = &worker.name);
+        // get the kvm settings for this image
+        let Some(settings) = &image.kvm else {
+            return Err(Error::new(format!(
+                "{}:{} does not have kvm settings",
+                image.group, image.name
+            )));
+        };
+        // build a disposable overlay for this job
+        let overlay = self.temp_path(&worker.name, "qcow2");
+        Self::create_overlay(&settings.qcow2, &overlay).await?;
+        // build our domain and iso xml
+        let (domain, xml) = Self::build_domain_xml(settings, &worker.name, &overlay).await?;
+        let iso_xml = self.build_iso_xml(worker, &span).await?;
         // connect to our kvm daemon
-        let client = Connect::open(&self.args.socket).unwrap();
-        // get the domain for our vm
-        let domain = Domain::lookup_by_name(&client, &golden).unwrap();
-        // rename our vm
-        domain.rename(&worker.name, 0).unwrap();
-        // get the golden snapshot for this vm
-        let snapshot = DomainSnapshot::lookup_by_name(&domain, "golden", 0).unwrap();
-        // revert to our snapshot
-        snapshot.revert(0).unwrap();
+        let client = Connect::open(&self.args.socket).map_err(virt_err)?;
+        // either restore our saved memory state or cold boot our vm
+        let launch_at = match &settings.memory_state {
+            Some(memory_state) => {
+                // restored vms share a name so the scaler only places one on a node at a time
+                if Domain::lookup_by_name(&client, &domain).is_ok() {
+                    return Err(Error::new(format!(
+                        "{domain} is already restored on this node"
+                    )));
+                }
+                // write our domain xml so virsh can restore with it
+                let xml_path = self.temp_path(&worker.name, "xml");
+                tokio::fs::write(&xml_path, &xml).await?;
+                // restoring only reads our saved memory state so it doesn't need to be copied
+                let args: [&OsStr; 5] = [
+                    "restore".as_ref(),
+                    memory_state.as_ref(),
+                    "--xml".as_ref(),
+                    xml_path.as_os_str(),
+                    "--running".as_ref(),
+                ];
+                virsh(&self.args.socket, &args).await?;
+                vm.domain = Some(domain.clone());
+                // our restored vm is ready for our agent now
+                Instant::now()
+            }
+            None => {
+                // start a transient domain that is removed once its destroyed
+                Domain::create_xml(&client, &xml, 0).map_err(virt_err)?;
+                vm.domain = Some(domain.clone());
+                // give our vm time to boot before launching our agent
+                Instant::now() + Duration::from_secs(self.args.boot_wait)
+            }
+        };
         // attach our iso
-        domain.attach_device(&xml).unwrap();
+        let domain = Domain::lookup_by_name(&client, &domain).map_err(virt_err)?;
+        domain.attach_device(&iso_xml).map_err(virt_err)?;
+        Ok(launch_at)
+    }
+
+    /// Launch our agent on any vms that are ready for it
+    ///
+    /// # Arguments
+    ///
+    /// * `active` - The names of the currently active workers in the reactor
+    /// * `span` - The span to log traces under
+    async fn launch_agents(&mut self, active: &HashMap<String, Worker>, span: &Span) {
+        let now = Instant::now();
+        // get the vms that are ready for their agent
+        let ready = self
+            .vms
+            .iter()
+            .filter(|(_, vm)| vm.launch_at.is_some_and(|launch_at| launch_at <= now))
+            .filter_map(|(name, vm)| Some((name.clone(), vm.domain.clone()?)))
+            .collect::<Vec<(String, String)>>();
+        for (name, domain) in ready {
+            // get this workers info
+            let Some(worker) = active.get(&name) else {
+                continue;
+            };
+            // get the vnc display for this vm and launch our agent
+            let launch = match self.get_vnc_display(&domain).await {
+                Ok(display) => self.launch_agent(&display, worker).await,
+                Err(error) => Err(error),
+            };
+            match launch {
+                Ok(()) => {
+                    if let Some(vm) = self.vms.get_mut(&name) {
+                        vm.launch_at = None;
+                    }
+                }
+                Err(error) => {
+                    event!(parent: span, Level::ERROR, worker = &name, error = error.to_string());
+                }
+            }
+        }
+    }
+
+    /// Start capturing a vms network traffic on its tap device
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the worker whose vm to capture
+    /// * `domain` - The libvirt domain for this workers vm
+    /// * `api` - The url of the Thorium API to leave out of our capture
+    async fn start_capture(
+        &self,
+        name: &str,
+        domain: &str,
+        api: &str,
+    ) -> Result<TapCapture, Error> {
+        // find the tap device libvirt attached this vm to
+        let output = Command::new("virsh")
+            .arg("-c")
+            .arg(&self.args.socket)
+            .args(["domiflist", domain])
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "Failed to list interfaces for {domain}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        // skip the header and separator lines and get the first tap device
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let tap = stdout
+            .lines()
+            .skip(2)
+            .filter_map(|line| line.split_whitespace().next())
+            .find(|tap| *tap != "-");
+        match tap {
+            Some(tap) => TapCapture::start(tap, self.temp_path(name, "pcap"), api),
+            None => Err(Error::new(format!("{domain} has no tap device to capture"))),
+        }
+    }
+
+    /// Submit a vms memory dump as a child of the samples its job analyzed
+    ///
+    /// # Arguments
+    ///
+    /// * `vm` - The vm this memory dump came from
+    /// * `dump` - The path to the memory dump
+    async fn submit_dump(vm: &ActiveVm, dump: &Path) -> Result<(), Error> {
+        // skip vms that never started a job
+        let Some(job) = &vm.job else {
+            return Ok(());
+        };
+        artifacts::submit_child(&vm.user, &vm.group, job, dump, |sha256| {
+            OriginRequest::memory_dump(
+                sha256,
+                Some("Physical".to_owned()),
+                Vec::<String>::default(),
+                None,
+            )
+        })
+        .await
+    }
+
+    /// Stop capturing a vms network traffic and submit it if its job finished
+    ///
+    /// # Arguments
+    ///
+    /// * `vm` - The vm to stop capturing traffic for
+    /// * `finished` - Whether this vm finished its job
+    async fn finish_capture(vm: &mut ActiveVm, finished: bool) -> Result<(), Error> {
+        let Some(capture) = vm.capture.take() else {
+            return Ok(());
+        };
+        let pcap = capture.stop().await?;
+        match (&vm.job, finished) {
+            (Some(job), true) => {
+                artifacts::submit_child(&vm.user, &vm.group, job, &pcap, |sha256| {
+                    OriginRequest::transformed(
+                        sha256,
+                        Some(vm.image.clone()),
+                        vec!["capture_network"],
+                        None,
+                    )
+                })
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Destroy a vms domain and remove its temp files
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The socket to connect to our libvirt daemon at
+    /// * `vm` - The vm to destroy
+    /// * `dump` - Where to dump this vms memory to before destroying it if it should be dumped
+    /// * `temp_files` - The temp files to remove for this vm
+    /// * `span` - The span to log traces under
+    async fn destroy(
+        socket: &str,
+        vm: &ActiveVm,
+        dump: Option<&Path>,
+        temp_files: &[PathBuf],
+        span: &Span,
+    ) -> Result<(), Error> {
+        // look up this vms domain if one was created
+        let domain = match &vm.domain {
+            Some(domain) => {
+                let client = Connect::open(socket).map_err(virt_err)?;
+                Domain::lookup_by_name(&client, domain).ok()
+            }
+            None => None,
+        };
+        if let Some(domain) = domain {
+            // dump this vms memory before destroying it
+            match (dump, &vm.domain) {
+                (Some(dump_path), Some(name)) if domain.is_active().map_err(virt_err)? => {
+                    let args: [&OsStr; 6] = [
+                        "dump".as_ref(),
+                        "--memory-only".as_ref(),
+                        "--format".as_ref(),
+                        "elf".as_ref(),
+                        name.as_ref(),
+                        dump_path.as_os_str(),
+                    ];
+                    let dumped = match virsh(socket, &args).await {
+                        Ok(()) => Self::submit_dump(vm, dump_path).await,
+                        Err(error) => Err(error),
+                    };
+                    if let Err(error) = dumped {
+                        event!(parent: span, Level::ERROR, msg = "Failed to submit memory dump", error = error.to_string());
+                    }
+                }
+                _ => (),
+            }
+            // destroy this vm if its still running
+            if domain.is_active().map_err(virt_err)? {
+                domain.destroy().map_err(virt_err)?;
+            }
+            // remove any persistent definition left by a restore
+            let _ = domain.undefine();
+        }
+        // remove this vms temp files
+        for path in temp_files {
+            if path.exists() {
+                tokio::fs::remove_file(path).await?;
+            }
+        }
         Ok(())
     }
 
+    /// Destroy a workers vm and remove its overlay and temp files
+    ///
+    /// Vms whose memory is dumped are destroyed in the background since dumping their memory
+    /// can take a while.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the worker to tear down
+    /// * `vm` - The vm to tear down
+    /// * `finished` - Whether this vm finished its job and its artifacts should be submitted
+    /// * `span` - The span to log traces under
+    async fn teardown(
+        &self,
+        name: &str,
+        mut vm: ActiveVm,
+        finished: bool,
+        span: &Span,
+    ) -> Result<(), Error> {
+        // start our teardown span
+        let span = span!(parent: span, Level::INFO, "Teardown VM", vm = name);
+        // stop capturing this vms network traffic
+        if let Err(error) = Self::finish_capture(&mut vm, finished).await {
+            event!(parent: &span, Level::ERROR, msg = "Failed to submit network capture", error = error.to_string());
+        }
+        // get the temp files to remove for this vm
+        let temp_files = TEMP_EXTENSIONS
+            .iter()
+            .map(|extension| self.temp_path(name, extension))
+            .collect::<Vec<PathBuf>>();
+        // dump this vms memory in the background so we don't block checking other workers
+        if finished && vm.dump_memory {
+            let socket = self.args.socket.clone();
+            let dump_path = self.temp_path(name, "dump");
+            tokio::spawn(async move {
+                let destroyed =
+                    Self::destroy(&socket, &vm, Some(&dump_path), &temp_files, &span).await;
+                if let Err(error) = destroyed {
+                    event!(parent: &span, Level::ERROR, error = error.to_string());
+                }
+            });
+            return Ok(());
+        }
+        Self::destroy(&self.args.socket, &vm, None, &temp_files, &span).await
+    }
+
     /// Get the vncdisplay for a target vm
     ///
     /// # Arguments
@@ -212,58 +654,6 @@ impl Kvm {
         }
     }
 }
-
-/// Shutdown and rename a vm
-///
-/// # Arguments
-///
-/// * `name` -
-fn shutdown_vm(name: &str, args: &crate::args::Kvm, span: &Span) -> Result<(), Error> {
-    // start our shutdown vm span
-    span!(parent: span, Level::INFO, "Shutdown VM", vm=name);
-    // build the path to delete our iso file at
-    let mut tmp_iso = PathBuf::new();
-    tmp_iso.push(&args.temp);
-    tmp_iso.push(name);
-    tmp_iso.set_extension("iso");
-    // remove the old iso file if it exists
-    if std::fs::metadata(&tmp_iso).is_ok() {
-        // remove the old iso file
-        std::fs::remove_file(&tmp_iso)?;
-    }
-    // connect to our kvm daemon
-    let client = Connect::open(&args.socket).unwrap();
-    // get the domain for our vm
-    if let Ok(domain) = Domain::lookup_by_name(&client, name) {
-        // only shutdown running vms
-        if domain.is_active().unwrap() {
-            // shutdown our vm
-            domain.shutdown().unwrap();
-            // every 10 seconds send the shutdown command again
-            let mut checks = 0;
-            // wait until this vm is shutdown
-            while domain.is_active().unwrap() {
-                // sleep for 1 second
-                std::thread::sleep(std::time::Duration::from_secs(1));
-                // increment our check count
-                checks += 1;
-                // if we have checked 10 times then resend our shutdown command
-                if checks >= 10 {
-                    // send our shutdown command
-                    domain.shutdown().unwrap();
-                    // reset our check count
-                    checks = 0;
-                }
-            }
-            // build our new vm name
-            let new_name = format!("thorium_corn_test");
-            // rename our vm
-            domain.rename(&new_name, 0).unwrap();
-        }
-    }
-    Ok(())
-}
-
 #[async_trait::async_trait]
 impl Launcher for Kvm {
     /// Spawn a worker and then return a process id that can be used to track it
@@ -283,17 +673,48 @@ impl Launcher for Kvm {
         let span = span!(parent: span, Level::INFO, "Launch Kvm Worker");
         // get the image info for this worker
         let image = thorium.images.get(&worker.group, &worker.stage).await?;
-        // try to find a golden vm for this job
-        if let Some(golden) = self.find_golden(&image) {
-            // try to define and spawn a new vm
-            self.convert(golden, &worker, &span).await?;
-            // get the vnc display for this vm
-            let vnc_display = self.get_vnc_display(&worker.name).await?;
-            println!("vnc port -> {}", vnc_display);
-            // launch our agent
-            self.launch_agent(&vnc_display, worker).await?;
+        // track this vm even if it failed to boot so its cleaned up
+        let mut vm = ActiveVm {
+            domain: None,
+            user: worker.user.clone(),
+            group: worker.group.clone(),
+            image: image.name.clone(),
+            job: None,
+            dump_memory: image.kvm.as_ref().is_some_and(|kvm| kvm.dump_memory),
+            launch_at: None,
+            capture: None,
+        };
+        // boot our vm and start capturing its network traffic if its requested
+        let booted = match self.boot(&image, worker, &mut vm, &span).await {
+            Ok(launch_at) if image.capture_network => {
+                vm.launch_at = Some(launch_at);
+                match &vm.domain {
+                    Some(domain) => self
+                        .start_capture(&worker.name, domain, &thorium.host)
+                        .await
+                        .map(|capture| vm.capture = Some(capture)),
+                    None => Err(Error::new(format!("{} has no domain", worker.name))),
+                }
+            }
+            Ok(launch_at) => {
+                vm.launch_at = Some(launch_at);
+                Ok(())
+            }
+            Err(error) => Err(error),
+        };
+        match booted {
+            Ok(()) => {
+                self.vms.insert(worker.name.clone(), vm);
+                Ok(())
+            }
+            Err(error) => {
+                // clean up any partially booted vm
+                if let Err(error) = self.teardown(&worker.name, vm, false, &span).await {
+                    event!(parent: &span, Level::ERROR, error = error.to_string());
+                }
+                Err(error)
+            }
         }
-        Ok(())
     }
 
     /// Check if any of our current workers have completed or died
@@ -313,59 +734,48 @@ impl Launcher for Kvm {
     ) -> Result<(), Error> {
         // start our launch kvm job span
         let span = span!(parent: span, Level::INFO, "Check KVM Workers");
-        // connect to our kvm daemon
-        let client = Connect::open(&self.args.socket).unwrap();
-        // build a map of existing vms
-        let mut existing = HashSet::with_capacity(50);
-        // build the flags for listing domains
-        let flags = virt::sys::VIR_CONNECT_LIST_DOMAINS_ACTIVE
-            | virt::sys::VIR_CONNECT_LIST_DOMAINS_INACTIVE;
-        // clear our golden images
-        self.golden.clear();
+        // remember the jobs our vms are executing so we can tie their artifacts to them
+        for (name, worker) in info.workers.iter() {
+            if let (Some(vm), Some(job)) = (self.vms.get_mut(name), &worker.active) {
+                vm.job = Some(job.clone());
+            }
+        }
         // remove any no longer active workers
         active.retain(|name, _| info.workers.contains_key(name));
-        // crawl over the domains on this node
-        for dom in client.list_all_domains(flags).unwrap() {
-            let name = dom.get_name().unwrap_or_else(|_| String::from("no-name"));
-            // if this worker starts with the name thorium-golden then add it to our golden list
-            if name.starts_with("thorium_") {
-                println!("domain -> {}", name);
-                // split our string by - to extract the group and image name
-                let mut split = name.split('_').skip(1);
-                // get our group name
-                if let Some(group) = split.next() {
-                    // get our image name
-                    if let Some(image) = split.next() {
-                        // get an entry to this groups images
-                        let image_entry = self.golden.entry(group.to_owned()).or_default();
-                        // get an entry to our golden vms
-                        let golden_entry = image_entry.entry(image.to_owned()).or_default();
-                        // insert our golden image
-                        golden_entry.insert(name.clone());
-                    }
+        // launch our agent on any vms that have finished booting
+        self.launch_agents(active, &span).await;
+        // connect to our kvm daemon
+        let client = Connect::open(&self.args.socket).map_err(virt_err)?;
+        // get the vms that are still running
+        let running = client
+            .list_all_domains(virt::sys::VIR_CONNECT_LIST_DOMAINS_ACTIVE)
+            .map_err(virt_err)?
+            .iter()
+            .filter_map(|domain| domain.get_name().ok())
+            .collect::<HashSet<String>>();
+        // get the vms whose agent has exited or that have died
+        let finished = self
+            .vms
+            .iter()
+            .filter(|(name, vm)| {
+                !active.contains_key(*name)
+                    || !vm
+                        .domain
+                        .as_ref()
+                        .is_some_and(|domain| running.contains(domain))
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<String>>();
+        // tear down any finished vms so they can't leave state behind
+        for name in finished {
+            if let Some(vm) = self.vms.remove(&name) {
+                if let Err(error) = self.teardown(&name, vm, true, &span).await {
+                    event!(parent: &span, Level::ERROR, worker = &name, error = error.to_string());
                 }
             }
-            existing.insert(name);
         }
-        println!("active -> {:#?}", active);
-        // shutdown any vms that are no longer tied to workers
-        for name in existing.iter() {
-            println!("maybe shutdown? -> {}", name);
-            println!(
-                "{} && {} && {}",
-                !active.contains_key(name),
-                name != "thorium_corn_test",
-                name != "win10-james"
-            );
-            // shutdown any vms not in our existing set
-            if !active.contains_key(name) && name != "thorium_corn_test" && name != "win10-james" {
-                println!("shutdown -> {}", name);
-                // shutdown our vm
-                shutdown_vm(&name, &self.args, &span)?;
-            }
-        }
-        // drop any workers that do not still exist
-        active.retain(|name, _| existing.contains(name));
+        // drop any workers that do not still have a vm
+        active.retain(|name, _| self.vms.contains_key(name));
         Ok(())
     }
 
@@ -378,10 +788,31 @@ impl Launcher for Kvm {
     /// * `span` - The span to log traces under
     async fn shutdown(
         &mut self,
-        _thorium: &Thorium,
-        mut _workers: HashSet<String>,
-        _span: &Span,
+        thorium: &Thorium,
+        workers: HashSet<String>,
+        span: &Span,
     ) -> Result<(), Error> {
-        panic!("AHHHHH");
+        // start our shutdown workers span
+        let span = span!(parent: span, Level::INFO, "Shutdown Workers", workers = workers.len());
+        // assume we will delete all requested workers
+        let mut deletes = WorkerDeleteMap::with_capacity(workers.len());
+        // crawl over the workers we want to shut down
+        for worker in workers {
+            // tear down this workers vm without dumping the memory of an unfinished job
+            if let Some(vm) = self.vms.remove(&worker) {
+                if let Err(error) = self.teardown(&worker, vm, false, &span).await {
+                    // log that we failed to shut down a worker
+                    event!(parent: &span, Level::ERROR, worker = &worker, error = error.to_string());
+                    continue;
+                }
+            }
+            deletes.add_mut(&worker);
+        }
+        // remove this workers from Thorium
+        thorium
+            .system
+            .delete_workers(ImageScaler::Kvm, &deletes)
+            .await?;
+        Ok(())
     }
 }
@@ -0,0 +1,597 @@
+//! Launches per worker Firecracker or cloud-hypervisor microVMs
+//!
+//! Each worker gets a fresh copy of its image's golden rootfs and a read only
+//! job drive containing the agent, the user's keys, and a launch script. The
+//! golden rootfs is expected to mount the job drive at `/thorium` and run
+//! `/thorium/launch.sh` on boot. The agent runs a single job before the guest
+//! shuts down and its microVM is torn down.
+//! Images that capture network traffic have their microVMs tap device captured
+//! for the lifetime of the microVM.
+
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use thorium::models::{
+    ActiveJob, Hypervisor, Image, ImageScaler, MicroVm as MicroVmSettings, Node, OriginRequest,
+    Worker, WorkerDeleteMap, WorkerStatus,
+};
+use thorium::{Error, Thorium};
+use tokio::process::{Child, Command};
+use tracing::{event, span, Level, Span};
+
+use super::artifacts::{self, TapCapture};
+use super::Launcher;
+use crate::libs::keys;
+
+/// The size of the sparse job drive to build for each microVM in bytes
+const JOB_DRIVE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Run an `ip` command to manage tap devices
+///
+/// # Arguments
+///
+/// * `args` - The args to pass to `ip`
+async fn ip(args: &[&str]) -> Result<(), Error> {
+    let output = Command::new("ip").args(args).output().await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "ip {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Check if a tap device already exists
+///
+/// # Arguments
+///
+/// * `tap` - The name of the tap device to check for
+async fn tap_exists(tap: &str) -> Result<bool, Error> {
+    let output = Command::new("ip")
+        .args(["link", "show", "dev", tap])
+        .output()
+        .await?;
+    Ok(output.status.success())
+}
+
+/// A currently active microVM
+struct ActiveVm {
+    /// The hypervisor process running this microVM
+    child: Child,
+    /// The directory holding this microVMs drives and sockets
+    dir: PathBuf,
+    /// The tap device this microVM is attached to
+    tap: String,
+    /// The user this microVM is executing jobs as
+    user: String,
+    /// The group this microVMs image is in
+    group: String,
+    /// The name of this microVMs image
+    image: String,
+    /// The job this microVM was last seen executing
+    job: Option<ActiveJob>,
+    /// The capture of this microVMs network traffic if its image captures it
+    capture: Option<TapCapture>,
+}
+
+impl ActiveVm {
+    /// Checks if this microVM is still running
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The span to log traces under
+    fn alive(&mut self, span: &Span) -> bool {
+        match self.child.try_wait() {
+            // this microVM has shutdown
+            Ok(Some(status)) => {
+                // log any hypervisor failures
+                if !status.success() {
+                    event!(
+                        parent: span,
+                        Level::ERROR,
+                        error = true,
+                        error_msg = status.to_string()
+                    );
+                }
+                false
+            }
+            // this microVM is still running
+            Ok(None) => true,
+            Err(error) => {
+                // we failed to get this microVMs status so assume its still alive
+                event!(
+                    parent: span,
+                    Level::ERROR,
+                    error = true,
+                    error_msg = error.to_string()
+                );
+                true
+            }
+        }
+    }
+
+    /// Stop capturing this microVMs network traffic and submit it if its job finished
+    ///
+    /// # Arguments
+    ///
+    /// * `finished` - Whether this microVM finished its job
+    async fn finish_capture(&mut self, finished: bool) -> Result<(), Error> {
+        let Some(capture) = self.capture.take() else {
+            return Ok(());
+        };
+        let pcap = capture.stop().await?;
+        match (&self.job, finished) {
+            (Some(job), true) => {
+                artifacts::submit_child(&self.user, &self.group, job, &pcap, |sha256| {
+                    OriginRequest::transformed(
+                        sha256,
+                        Some(self.image.clone()),
+                        vec!["capture_network"],
+                        None,
+                    )
+                })
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Kill this microVM if its still running and remove its drives and tap device
+    ///
+    /// # Arguments
+    ///
+    /// * `finished` - Whether this microVM finished its job and its capture should be submitted
+    /// * `span` - The span to log traces under
+    async fn teardown(mut self, finished: bool, span: &Span) -> Result<(), Error> {
+        // start our teardown span
+        span!(parent: span, Level::INFO, "Teardown MicroVM", tap = &self.tap);
+        // stop capturing this microVMs network traffic
+        if let Err(error) = self.finish_capture(finished).await {
+            event!(parent: span, Level::ERROR, msg = "Failed to submit network capture", error = error.to_string());
+        }
+        // kill our hypervisor if its still running
+        if self.alive(span) {
+            self.child.kill().await?;
+        }
+        // remove our tap device
+        ip(&["link", "delete", &self.tap]).await?;
+        // remove this microVMs drives
+        if self.dir.exists() {
+            tokio::fs::remove_dir_all(&self.dir).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Handles launching jobs in per worker microVMs
+pub struct MicroVm {
+    /// The microVM specific args
+    args: crate::args::MicroVm,
+    /// A map of currently active microVMs
+    active: HashMap<String, ActiveVm>,
+    /// The number of tap devices we have created
+    taps: u64,
+}
+
+impl MicroVm {
+    /// Create a new microVM launcher
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The args for the microVM launcher
+    pub fn new(args: &crate::args::MicroVm) -> Self {
+        MicroVm {
+            args: args.clone(),
+            active: HashMap::with_capacity(25),
+            taps: 0,
+        }
+    }
+
+    /// Build the read only job drive for a worker
+    ///
+    /// # Arguments
+    ///
+    /// * `worker` - The worker to build a job drive for
+    /// * `settings` - The microVM settings for this workers image
+    /// * `dir` - The directory to build this job drive in
+    async fn build_job_drive(
+        &self,
+        worker: &Worker,
+        settings: &MicroVmSettings,
+        dir: &Path,
+    ) -> Result<PathBuf, Error> {
+        // stage the files to add to our job drive
+        let staging = dir.join("job");
+        tokio::fs::create_dir_all(&staging).await?;
+        tokio::fs::copy(&self.args.agent, staging.join("thorium-agent")).await?;
+        tokio::fs::copy(keys::path(&worker.user), staging.join("keys.yml")).await?;
+        // Firecracker exits when the guest reboots while cloud-hypervisor exits on power off
+        let shutdown = match settings.hypervisor {
+            Hypervisor::Firecracker => "reboot -f",
+            Hypervisor::CloudHypervisor => "poweroff -f",
+        };
+        // build the script the guest runs to start our agent and shut down once its job is done
+        let script = format!(
+            "#!/bin/sh\n\
+            /thorium/thorium-agent --cluster {cluster} --node {node} \
+            --group {group} --pipeline {pipeline} --stage {stage} --name {name} \
+            --keys /thorium/keys.yml kvm\n\
+            {shutdown}\n",
+            cluster = &worker.cluster,
+            node = &worker.node,
+            group = &worker.group,
+            pipeline = &worker.pipeline,
+            stage = &worker.stage,
+            name = &worker.name,
+        );
+        let script_path = staging.join("launch.sh");
+        tokio::fs::write(&script_path, script).await?;
+        tokio::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).await?;
+        // build a sparse ext4 image from our staged files
+        let drive = dir.join("job.ext4");
+        let file = tokio::fs::File::create(&drive).await?;
+        file.set_len(JOB_DRIVE_SIZE).await?;
+        let output = Command::new("mkfs.ext4")
+            .arg("-q")
+            .arg("-F")
+            .arg("-d")
+            .arg(&staging)
+            .arg(&drive)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "Failed to build job drive: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        // our staged files are on the job drive now
+        tokio::fs::remove_dir_all(&staging).await?;
+        Ok(drive)
+    }
+
+    /// Create a tap device on our bridge for a new microVM
+    ///
+    /// Our tap counter restarts whenever the reactor does so any taps that still exist from
+    /// microVMs started before a restart are skipped.
+    async fn create_tap(&mut self) -> Result<String, Error> {
+        let tap = loop {
+            // tap device names are limited to 15 chars
+            let tap = format!("thvm{}", self.taps % 100_000_000_000);
+            self.taps += 1;
+            // skip any tap devices that are already in use
+            if !tap_exists(&tap).await? {
+                break tap;
+            }
+        };
+        ip(&["tuntap", "add", "dev", &tap, "mode", "tap"]).await?;
+        ip(&["link", "set", &tap, "master", &self.args.bridge]).await?;
+        ip(&["link", "set", &tap, "up"]).await?;
+        Ok(tap)
+    }
+
+    /// Boot a microVM for a worker
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image this worker is for
+    /// * `settings` - The microVM settings for this image
+    /// * `dir` - The directory holding this microVMs drives
+    /// * `tap` - The tap device to attach this microVM to
+    fn boot(
+        image: &Image,
+        settings: &MicroVmSettings,
+        dir: &Path,
+        tap: &str,
+    ) -> Result<Child, Error> {
+        // enforce our images resources with the microVMs shape
+        let vcpus = image.resources.cpu.div_ceil(1000).max(1);
+        let memory = image.resources.memory.max(128);
+        // send our console to a log file
+        let console = std::fs::File::create(dir.join("console.log"))?;
+        let rootfs = dir.join("rootfs.ext4");
+        let job = dir.join("job.ext4");
+        let mut cmd = match settings.hypervisor {
+            Hypervisor::Firecracker => {
+                // build our boot args
+                let mut boot_args = "console=ttyS0 reboot=k panic=1 pci=off".to_owned();
+                if let Some(extra) = &settings.boot_args {
+                    boot_args.push(' ');
+                    boot_args.push_str(extra);
+                }
+                // build the config for this microVM
+                let config = json!({
+                    "boot-source": {
+                        "kernel_image_path": &settings.kernel,
+                        "boot_args": boot_args,
+                    },
+                    "drives": [
+                        {
+                            "drive_id": "rootfs",
+                            "path_on_host": rootfs,
+                            "is_root_device": true,
+                            "is_read_only": false,
+                        },
+                        {
+                            "drive_id": "job",
+                            "path_on_host": job,
+                            "is_root_device": false,
+                            "is_read_only": true,
+                        }
+                    ],
+                    "machine-config": {
+                        "vcpu_count": vcpus,
+                        "mem_size_mib": memory,
+                    },
+                    "network-interfaces": [
+                        {
+                            "iface_id": "eth0",
+                            "host_dev_name": tap,
+                        }
+                    ],
+                });
+                let config_path = dir.join("firecracker.json");
+                std::fs::write(&config_path, serde_json::to_vec(&config)?)?;
+                let mut cmd = Command::new(settings.hypervisor.binary());
+                cmd.arg("--api-sock")
+                    .arg(dir.join("firecracker.sock"))
+                    .arg("--config-file")
+                    .arg(config_path);
+                cmd
+            }
+            Hypervisor::CloudHypervisor => {
+                // build our boot args
+                let mut boot_args = "console=ttyS0 root=/dev/vda rw reboot=k panic=1".to_owned();
+                if let Some(extra) = &settings.boot_args {
+                    boot_args.push(' ');
+                    boot_args.push_str(extra);
+                }
+                let mut cmd = Command::new(settings.hypervisor.binary());
+                cmd.arg("--api-socket")
+                    .arg(format!("path={}", dir.join("ch.sock").to_string_lossy()))
+                    .arg("--kernel")
+                    .arg(&settings.kernel)
+                    .arg("--cmdline")
+                    .arg(boot_args)
+                    .arg("--disk")
+                    .arg(format!("path={}", rootfs.to_string_lossy()))
+                    .arg(format!("path={},readonly=on", job.to_string_lossy()))
+                    .arg("--cpus")
+                    .arg(format!("boot={vcpus}"))
+                    .arg("--memory")
+                    .arg(format!("size={memory}M"))
+                    .arg("--net")
+                    .arg(format!("tap={tap}"))
+                    .arg("--serial")
+                    .arg("tty")
+                    .arg("--console")
+                    .arg("off");
+                cmd
+            }
+        };
+        // start our microVM
+        let child = cmd
+            .stdin(Stdio::null())
+            .stdout(console.try_clone()?)
+            .stderr(console)
+            .kill_on_drop(true)
+            .spawn()?;
+        Ok(child)
+    }
+
+    /// Setup and boot a microVM for a worker
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `worker` - The worker to launch
+    /// * `dir` - The directory to write this microVMs drives to
+    /// * `span` - The span to log traces under
+    async fn spawn(
+        &mut self,
+        thorium: &Thorium,
+        worker: &Worker,
+        dir: &Path,
+        span: &Span,
+    ) -> Result<ActiveVm, Error> {
+        // get the microVM settings for this workers image
+        let image = thorium.images.get(&worker.group, &worker.stage).await?;
+        let Some(settings) = &image.microvm else {
+            return Err(Error::new(format!(
+                "{}:{} does not have microVM settings",
+                image.group, image.name
+            )));
+        };
+        tokio::fs::create_dir_all(dir).await?;
+        // give this microVM its own copy of the golden rootfs
+        tokio::fs::copy(&settings.rootfs, dir.join("rootfs.ext4")).await?;
+        // build the drive to inject our agent and job files with
+        self.build_job_drive(worker, settings, dir).await?;
+        // attach this microVM to our bridge
+        let tap = self.create_tap().await?;
+        // boot our microVM and start capturing its network traffic if its requested
+        let booted = Self::boot(&image, settings, dir, &tap).and_then(|child| {
+            let capture = if image.capture_network {
+                Some(TapCapture::start(
+                    &tap,
+                    dir.join("capture.pcap"),
+                    &thorium.host,
+                )?)
+            } else {
+                None
+            };
+            Ok((child, capture))
+        });
+        match booted {
+            Ok((child, capture)) => Ok(ActiveVm {
+                child,
+                dir: dir.to_owned(),
+                tap,
+                user: worker.user.clone(),
+                group: worker.group.clone(),
+                image: image.name.clone(),
+                job: None,
+                capture,
+            }),
+            Err(error) => {
+                // don't leak our tap device if we failed to boot but return our boot error
+                if let Err(cleanup) = ip(&["link", "delete", &tap]).await {
+                    event!(
+                        parent: span,
+                        Level::ERROR,
+                        msg = "Failed to delete tap device",
+                        tap = &tap,
+                        error = cleanup.to_string()
+                    );
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Launcher for MicroVm {
+    /// Spawn a worker and then return a process id that can be used to track it
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `worker` - The worker to launch
+    /// * `span` - The span to log traces under
+    async fn launch(
+        &mut self,
+        thorium: &Thorium,
+        worker: &Worker,
+        span: &Span,
+    ) -> Result<(), Error> {
+        // start our launch microVM span
+        let span = span!(
+            parent: span,
+            Level::INFO,
+            "Launch MicroVM Worker",
+            name = worker.name,
+            user = worker.user,
+            group = worker.group,
+            pipeline = worker.pipeline,
+            stage = worker.stage
+        );
+        // build the directory to write this microVMs drives to
+        let dir = self.args.temp.join(&worker.name);
+        match self.spawn(thorium, worker, &dir, &span).await {
+            Ok(vm) => {
+                // add this microVM to our active map
+                self.active.insert(worker.name.clone(), vm);
+                Ok(())
+            }
+            Err(error) => {
+                // clean up any partially built drives
+                if dir.exists() {
+                    if let Err(error) = tokio::fs::remove_dir_all(&dir).await {
+                        event!(parent: &span, Level::ERROR, error = error.to_string());
+                    }
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Check if any of our current workers have completed or died
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `info` - Info about our node and its workers
+    /// * `active` - The names of the currently active workers in the reactor
+    /// * `span` - The span to log traces under
+    async fn check(
+        &mut self,
+        thorium: &Thorium,
+        info: &mut Node,
+        active: &mut HashMap<String, Worker>,
+        span: &Span,
+    ) -> Result<(), Error> {
+        // start our check microVMs span
+        let span = span!(parent: span, Level::INFO, "Check MicroVM Workers");
+        // keep a list of workers that should be deleted since they no longer exist
+        let mut deletes = WorkerDeleteMap::default();
+        // remember the jobs our microVMs are executing so we can tie their captures to them
+        for (name, worker) in info.workers.iter() {
+            if let (Some(vm), Some(job)) = (self.active.get_mut(name), &worker.active) {
+                vm.job = Some(job.clone());
+            }
+        }
+        // find any microVMs that have shutdown
+        let exited = self
+            .active
+            .iter_mut()
+            .filter_map(|(name, vm)| (!vm.alive(&span)).then(|| name.clone()))
+            .collect::<Vec<String>>();
+        // tear down any microVMs that have shutdown
+        for name in exited {
+            if let Some(vm) = self.active.remove(&name) {
+                if let Err(error) = vm.teardown(true, &span).await {
+                    event!(parent: &span, Level::ERROR, worker = &name, error = error.to_string());
+                }
+            }
+            // remove this worker from Thorium
+            if let Some(info) = active.remove(&name) {
+                deletes.add_mut(info.name);
+            }
+        }
+        // Add any running workers that do not exist on our node to our delete map
+        for (name, worker) in info.workers.iter() {
+            if worker.status == WorkerStatus::Running && !self.active.contains_key(name) {
+                deletes.add_mut(name);
+            }
+        }
+        // delete the workers that no longer exist
+        thorium
+            .system
+            .delete_workers(ImageScaler::Kvm, &deletes)
+            .await?;
+        Ok(())
+    }
+
+    /// Shutdown a list of workers
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A Thorium client
+    /// * `workers` - The workers to shutdown
+    /// * `span` - The span to log traces under
+    async fn shutdown(
+        &mut self,
+        thorium: &Thorium,
+        workers: HashSet<String>,
+        span: &Span,
+    ) -> Result<(), Error> {
+        // start our shutdown workers span
+        let span = span!(parent: span, Level::INFO, "Shutdown Workers", workers = workers.len());
+        // assume we will delete all requested workers
+        let mut deletes = WorkerDeleteMap::with_capacity(workers.len());
+        // crawl over the workers we want to shut down
+        for worker in workers {
+            // tear down this workers microVM if it still exists
+            if let Some(vm) = self.active.remove(&worker) {
+                if let Err(error) = vm.teardown(false, &span).await {
+                    // log that we failed to shut down a worker
+                    event!(parent: &span, Level::ERROR, worker = &worker, error = error.to_string());
+                    continue;
+                }
+            }
+            deletes.add_mut(&worker);
+        }
+        // remove this workers from Thorium
+        thorium
+            .system
+            .delete_workers(ImageScaler::Kvm, &deletes)
+            .await?;
+        Ok(())
+    }
+}
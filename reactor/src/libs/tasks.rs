@@ -4,10 +4,13 @@ use chrono::prelude::*;
 use std::collections::BTreeMap;
 use std::path::Path;
 use sysinfo::{Disks, System};
-use thorium::models::{NodeHealth, NodeUpdate, Resources};
+use thorium::models::{CpuArch, NodeHealth, NodeUpdate, Resources};
 use thorium::{Error, Thorium};
 use tracing::{event, span, Level, Span};
 
+/// Where `binfmt_misc` handlers are registered
+const BINFMT_MISC: &str = "/proc/sys/fs/binfmt_misc";
+
 /// gets a timestamp N seconds from now
 #[doc(hidden)]
 #[macro_export]
@@ -116,6 +119,29 @@ fn get_resources(system: &mut System, span: &Span) -> Result<Resources, Error> {
     Ok(resources)
 }
 
+/// Get the architecture this node runs natively and any it can emulate
+///
+/// A node can emulate any architecture that has an enabled qemu-user `binfmt_misc` handler.
+fn get_arch() -> Result<(CpuArch, Vec<CpuArch>), Error> {
+    // get the architecture of this node
+    let Some(arch) = CpuArch::host() else {
+        return Err(Error::new(format!(
+            "Unsupported architecture {}",
+            std::env::consts::ARCH
+        )));
+    };
+    // check which architectures have qemu-user registered
+    let emulated = CpuArch::ALL
+        .into_iter()
+        .filter(|emulated| *emulated != arch)
+        .filter(|emulated| {
+            std::fs::read_to_string(Path::new(BINFMT_MISC).join(emulated.qemu()))
+                .is_ok_and(|handler| handler.starts_with("enabled"))
+        })
+        .collect();
+    Ok((arch, emulated))
+}
+
 /// Get this nodes resources and update Thorium
 pub async fn update_resources(
     cluster: &str,
@@ -135,8 +161,20 @@ pub async fn update_resources(
             return Err(error);
         }
     };
+    // get the architectures this node can run
+    let (arch, emulated) = match get_arch() {
+        Ok(arch) => arch,
+        Err(error) => {
+            // log that we can't run anything on this architecture
+            event!(parent: span, Level::ERROR, error = true, msg = error.msg());
+            return Err(error);
+        }
+    };
     // build the update to apply to this node
-    let update = NodeUpdate::new(NodeHealth::Healthy, resources).heart_beat();
+    let update = NodeUpdate::new(NodeHealth::Healthy, resources)
+        .arch(arch)
+        .emulated(emulated)
+        .heart_beat();
     // update this nodes info in Thorium
     match thorium.system.update_node(cluster, node, &update).await {
         Ok(_) => Ok(()),
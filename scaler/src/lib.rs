@@ -5,6 +5,7 @@
 mod args;
 mod libs;
 
+pub use libs::provisioner::{HookProvisioner, NodeProvisioner};
 pub use libs::{Scaler, Spawned};
 
 // these are only for tests
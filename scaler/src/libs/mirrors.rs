@@ -0,0 +1,459 @@
+//! Fetches mirrored repos and ingests any new commits, branches, and tags into Thorium
+//!
+//! Each mirror is restored from the data already in Thorium when possible so that only new
+//! objects need to be fetched from its remote.
+
+use chrono::prelude::*;
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    AutotagOption, BranchType, Cred, FetchOptions, FetchPrune, Oid, ProxyOptions, ReferenceType,
+    RemoteCallbacks, Repository, Signature, Time,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thorium::models::{
+    BranchRequest, CommitListOpts, CommitRequest, CommitishDetails, CommitishKinds,
+    CommitishMapRequest, CommitishRequest, GitTagRequest, RepoDownloadOpts, RepoMirror,
+    RepoMirrorClaimOpts, RepoMirrorCredentials, RepoMirrorFetch, UntarredRepo,
+};
+use thorium::{Error, Thorium};
+use tracing::{Level, event, instrument, span};
+use uuid::Uuid;
+
+use super::tasks::TaskResult;
+
+/// The max number of mirrors to fetch each time this task runs
+const MIRRORS_PER_RUN: usize = 10;
+
+/// The max number of commitishes to send to Thorium at once
+const BATCH_SIZE: usize = 500;
+
+/// Build the options to fetch a mirror with
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to fetch this mirror with if it has any
+fn fetch_opts(creds: Option<&RepoMirrorCredentials>) -> FetchOptions<'_> {
+    // build our proxy options
+    let mut proxy = ProxyOptions::new();
+    proxy.auto();
+    // always pull all tags and drop any branches that were deleted
+    let mut opts = FetchOptions::new();
+    opts.proxy_options(proxy);
+    opts.download_tags(AutotagOption::All);
+    opts.prune(FetchPrune::On);
+    // authenticate with our credentials if we have any
+    if let Some(creds) = creds {
+        let mut callbacks = RemoteCallbacks::new();
+        // only try our credentials once so bad creds don't retry forever
+        let mut attempted = false;
+        callbacks.credentials(move |_url, _username, _allowed| {
+            if attempted {
+                return Err(git2::Error::from_str("Mirror credentials were rejected"));
+            }
+            attempted = true;
+            Cred::userpass_plaintext(&creds.username, &creds.token)
+        });
+        opts.remote_callbacks(callbacks);
+    }
+    opts
+}
+
+/// Get the name of a mirrored repo from its url
+///
+/// # Arguments
+///
+/// * `url` - The url of the repo in Thorium
+fn repo_name(url: &str) -> &str {
+    url.rsplit('/').next().unwrap_or(url)
+}
+
+/// Get a local copy of a mirror, preferring the data already in Thorium
+///
+/// # Arguments
+///
+/// * `thorium` - A Thorium client
+/// * `mirror` - The mirror to get a local copy of
+/// * `root` - The directory to place this mirror in
+async fn local_copy(thorium: &Thorium, mirror: &RepoMirror, root: &Path) -> Result<PathBuf, Error> {
+    // try to restore this repo from the data already in Thorium
+    let opts = RepoDownloadOpts::default();
+    if let Ok(untarred) = thorium
+        .repos
+        .download_unpack(&mirror.url, &opts, root)
+        .await
+    {
+        return Ok(untarred.path);
+    }
+    // this repo has no data in Thorium yet so clone it directly
+    let path = root.join(repo_name(&mirror.url));
+    let remote = mirror.remote.clone();
+    let creds = mirror.credentials.clone();
+    let clone_path = path.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut builder = RepoBuilder::new();
+        builder.fetch_options(fetch_opts(creds.as_ref()));
+        builder.clone(&remote, &clone_path)
+    })
+    .await??;
+    Ok(path)
+}
+
+/// Fetch any updates for a mirror and bring its local branches up to date
+///
+/// # Arguments
+///
+/// * `path` - The path to our local copy of this mirror
+/// * `remote` - The remote to fetch from
+/// * `creds` - The credentials to fetch with if we have any
+fn update(path: &Path, remote: &str, creds: Option<&RepoMirrorCredentials>) -> Result<(), Error> {
+    let repo = Repository::open(path)?;
+    // make sure we are fetching from this mirrors current remote
+    if repo.find_remote("origin").is_ok() {
+        repo.remote_set_url("origin", remote)?;
+    } else {
+        repo.remote("origin", remote)?;
+    }
+    let mut origin = repo.find_remote("origin")?;
+    origin.fetch(
+        &["+refs/heads/*:refs/remotes/origin/*"],
+        Some(&mut fetch_opts(creds)),
+        None,
+    )?;
+    // remember which branch was checked out so we can restore it
+    let checked_out = match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().map(ToOwned::to_owned),
+        _ => None,
+    };
+    // detach our head so any local branch can be moved
+    if let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) {
+        repo.set_head_detached(commit.id())?;
+    }
+    // move each local branch to where its remote branch now points
+    for branch in repo.branches(Some(BranchType::Remote))? {
+        let (branch, _) = branch?;
+        // skip symbolic references like origin/HEAD
+        if branch.get().kind() == Some(ReferenceType::Symbolic) {
+            continue;
+        }
+        let Some(name) = branch.name()? else {
+            continue;
+        };
+        let commit = branch.get().peel_to_commit()?;
+        repo.branch(name.trim_start_matches("origin/"), &commit, true)?;
+    }
+    // restore our checked out branch if it still exists
+    let checked_out = checked_out.filter(|name| repo.find_branch(name, BranchType::Local).is_ok());
+    if let Some(name) = checked_out {
+        repo.set_head(&format!("refs/heads/{name}"))?;
+        repo.checkout_head(Some(CheckoutBuilder::default().force()))?;
+    }
+    Ok(())
+}
+
+/// Get the commit each branch already in Thorium points to
+///
+/// This lets the first fetch of a mirror for a repo that was already ingested skip the
+/// commits Thorium already has.
+///
+/// # Arguments
+///
+/// * `thorium` - A Thorium client
+/// * `mirror` - The mirror to get the stored branches for
+async fn stored_refs(
+    thorium: &Thorium,
+    mirror: &RepoMirror,
+) -> Result<HashMap<String, String>, Error> {
+    let opts = CommitListOpts {
+        kinds: vec![CommitishKinds::Branch],
+        ..Default::default()
+    }
+    .groups(mirror.groups.clone());
+    let mut cursor = thorium
+        .repos
+        .list_commitish_details(&mirror.url, &opts)
+        .await?;
+    let mut refs = HashMap::default();
+    loop {
+        for details in cursor.data.drain(..) {
+            // branches are listed newest first so keep the first commit we see for each
+            if let CommitishDetails::Branch(branch) = details {
+                refs.entry(format!("refs/remotes/origin/{}", branch.name))
+                    .or_insert(branch.commit);
+            }
+        }
+        if cursor.exhausted() {
+            break;
+        }
+        cursor.refill().await?;
+    }
+    Ok(refs)
+}
+
+/// Convert a git time to a timestamp
+///
+/// # Arguments
+///
+/// * `time` - The git time to convert
+fn timestamp(time: Time) -> DateTime<Utc> {
+    Utc.timestamp_opt(time.seconds(), 0)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
+
+/// Get the email from a git signature
+///
+/// # Arguments
+///
+/// * `signature` - The signature to get an email from
+fn email(signature: &Signature) -> String {
+    String::from_utf8_lossy(signature.email_bytes()).into_owned()
+}
+
+/// Build the request to ingest a branch or tag
+///
+/// Returns None if this reference is not a branch or tag or does not point to a commit.
+///
+/// # Arguments
+///
+/// * `repo` - The repo this reference is in
+/// * `full` - The full name of this reference
+/// * `target` - The object this reference points to
+fn commitish(
+    repo: &Repository,
+    full: &str,
+    target: Oid,
+) -> Result<Option<(String, CommitishRequest)>, Error> {
+    // skip any branches or tags that don't point to a commit
+    let object = repo.find_object(target, None)?;
+    let Ok(commit) = object.peel_to_commit() else {
+        return Ok(None);
+    };
+    if let Some(name) = full.strip_prefix("refs/remotes/origin/") {
+        let branch = BranchRequest {
+            commit: commit.id().to_string(),
+            timestamp: timestamp(commit.time()),
+        };
+        return Ok(Some((name.to_owned(), CommitishRequest::Branch(branch))));
+    }
+    if let Some(name) = full.strip_prefix("refs/tags/") {
+        // use the tagger for annotated tags and the commit author for lightweight ones
+        let tag = object.as_tag();
+        let signature = tag
+            .and_then(|tag| tag.tagger())
+            .unwrap_or_else(|| commit.author());
+        let tag = GitTagRequest {
+            commit: commit.id().to_string(),
+            author: email(&signature),
+            timestamp: timestamp(signature.when()),
+        };
+        return Ok(Some((name.to_owned(), CommitishRequest::Tag(tag))));
+    }
+    Ok(None)
+}
+
+/// Find the branches, tags, and commits that are new since a mirrors last fetch
+///
+/// # Arguments
+///
+/// * `path` - The path to our local copy of this mirror
+/// * `known` - The commit each branch and tag pointed to at the last fetch
+fn crawl(
+    path: &Path,
+    known: &HashMap<String, String>,
+) -> Result<(HashMap<String, String>, HashMap<String, CommitishRequest>), Error> {
+    let repo = Repository::open(path)?;
+    let mut refs = HashMap::default();
+    let mut commitishes = HashMap::default();
+    // find any branches or tags that were added or moved
+    let mut changed = Vec::default();
+    for refer in repo.references()? {
+        let Ok(refer) = refer else {
+            continue;
+        };
+        // skip any symbolic references
+        let (Some(full), Some(target)) = (refer.name(), refer.target()) else {
+            continue;
+        };
+        // we only mirror the branches on our remote and tags
+        if !full.starts_with("refs/remotes/origin/") && !full.starts_with("refs/tags/") {
+            continue;
+        }
+        // ingest this branch or tag if it is new or has moved
+        let hash = target.to_string();
+        if known.get(full) != Some(&hash) {
+            if let Some((name, req)) = commitish(&repo, full, target)? {
+                commitishes.insert(name, req);
+            }
+            changed.push(target);
+        }
+        refs.insert(full.to_owned(), hash);
+    }
+    // walk any new commits reachable from the branches and tags that changed
+    let mut walk = repo.revwalk()?;
+    for target in changed {
+        // skip any tags that don't point to a commit
+        if let Ok(commit) = repo
+            .find_object(target, None)
+            .and_then(|obj| obj.peel_to_commit())
+        {
+            walk.push(commit.id())?;
+        }
+    }
+    // skip any commits we already ingested
+    for target in known.values() {
+        // commits that are no longer in this repo can't be hidden
+        let commit = Oid::from_str(target)
+            .and_then(|oid| repo.find_object(oid, None))
+            .and_then(|obj| obj.peel_to_commit());
+        if let Ok(commit) = commit {
+            walk.hide(commit.id())?;
+        }
+    }
+    for oid in walk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        // track the paths each commit changed so triggers can filter on them
+        let req = CommitRequest::new(commit).changed_paths(&repo, oid)?;
+        commitishes.insert(oid.to_string(), CommitishRequest::Commit(req));
+    }
+    Ok((refs, commitishes))
+}
+
+/// Upload our local copy of a mirror and its new commitishes to Thorium
+///
+/// # Arguments
+///
+/// * `thorium` - A Thorium client
+/// * `mirror` - The mirror to upload data for
+/// * `path` - The path to our local copy of this mirror
+/// * `commitishes` - The new commitishes to ingest
+async fn ingest(
+    thorium: &Thorium,
+    mirror: &RepoMirror,
+    path: PathBuf,
+    mut commitishes: HashMap<String, CommitishRequest>,
+) -> Result<(), Error> {
+    // tar and upload this repos data
+    let tarred = path.with_extension("tar");
+    let tar = UntarredRepo::new(path)?.tar(&tarred).await?;
+    let resp = thorium
+        .repos
+        .upload(&mirror.url, tar, mirror.groups.clone())
+        .await?;
+    // track the oldest commitish we ingest
+    let earliest = commitishes
+        .values()
+        .map(CommitishRequest::timestamp)
+        .min()
+        .unwrap_or_else(Utc::now);
+    // send our commitishes in batches with the last batch closing out this upload
+    let keys = commitishes.keys().cloned().collect::<Vec<String>>();
+    let mut chunks = keys.chunks(BATCH_SIZE).peekable();
+    while let Some(chunk) = chunks.next() {
+        let end = chunks.peek().is_none();
+        let map = CommitishMapRequest {
+            groups: mirror.groups.clone(),
+            earliest: end.then_some(earliest),
+            end,
+            commitishes: chunk
+                .iter()
+                .filter_map(|key| commitishes.remove_entry(key))
+                .collect(),
+            trigger_depth: 0,
+        };
+        thorium
+            .repos
+            .add_commits(&mirror.url, &resp.sha256, &map)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Fetch a single mirror and ingest anything new into Thorium
+///
+/// # Arguments
+///
+/// * `thorium` - A Thorium client
+/// * `mirror` - The mirror to fetch
+/// * `root` - The directory to fetch this mirror in
+#[instrument(name = "mirrors::fetch", skip_all, fields(url = &mirror.url), err(Debug))]
+async fn fetch(
+    thorium: &Thorium,
+    mirror: &RepoMirror,
+    root: &Path,
+) -> Result<RepoMirrorFetch, Error> {
+    // get a local copy of this repo
+    let path = local_copy(thorium, mirror, root).await?;
+    // fetch any updates and find what is new
+    let blocking_path = path.clone();
+    let remote = mirror.remote.clone();
+    let creds = mirror.credentials.clone();
+    // seed a mirror that has never been fetched from the branches already in Thorium
+    let known = if mirror.refs.is_empty() {
+        stored_refs(thorium, mirror).await?
+    } else {
+        mirror.refs.clone()
+    };
+    let (refs, commitishes) = tokio::task::spawn_blocking(move || {
+        update(&blocking_path, &remote, creds.as_ref())?;
+        crawl(&blocking_path, &known)
+    })
+    .await??;
+    // only upload new data if something changed
+    let ingested = commitishes.len() as u64;
+    event!(Level::INFO, ingested);
+    if !commitishes.is_empty() {
+        ingest(thorium, mirror, path, commitishes).await?;
+    }
+    Ok(RepoMirrorFetch::success(refs, ingested))
+}
+
+/// Fetch any mirrored repos that are due and ingest their new commits
+///
+/// Errors are reported on each mirror instead of failing this task so one bad
+/// remote can't stop the others from being fetched.
+///
+/// # Arguments
+///
+/// * `thorium` - A reference counted Thorium client
+pub async fn fetch_mirrors(thorium: Arc<Thorium>) -> Result<TaskResult, Error> {
+    // start our mirror fetch span
+    let _ = span!(Level::INFO, "Fetching Repo Mirrors");
+    // claim the mirrors that are due to be fetched
+    let opts = RepoMirrorClaimOpts::default().limit(MIRRORS_PER_RUN);
+    let mirrors = match thorium.repos.claim_mirrors(&opts).await {
+        Ok(mirrors) => mirrors,
+        Err(error) => {
+            event!(
+                Level::ERROR,
+                msg = "Failed to claim repo mirrors",
+                error = error.to_string()
+            );
+            return Ok(TaskResult::RepoMirrors);
+        }
+    };
+    for mirror in mirrors {
+        // fetch this mirror in its own directory
+        let root = std::env::temp_dir()
+            .join("thorium-mirrors")
+            .join(Uuid::new_v4().to_string());
+        let outcome = match fetch(&thorium, &mirror, &root).await {
+            Ok(outcome) => outcome,
+            Err(error) => RepoMirrorFetch::failed(error.to_string()),
+        };
+        // clean up our local copy of this mirror
+        match tokio::fs::remove_dir_all(&root).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                event!(Level::ERROR, url = &mirror.url, error = error.to_string());
+            }
+            _ => (),
+        }
+        // tell Thorium how this fetch went and when to fetch it next
+        if let Err(error) = thorium.repos.mirror_fetched(&mirror.url, &outcome).await {
+            event!(Level::ERROR, url = &mirror.url, error = error.to_string());
+        }
+    }
+    Ok(TaskResult::RepoMirrors)
+}
@@ -1,6 +1,8 @@
 mod cache;
 mod helpers;
 mod inspect;
+mod mirrors;
+pub mod provisioner;
 mod scaler;
 pub mod schedulers;
 mod tasks;
@@ -0,0 +1,681 @@
+//! Provisions new nodes when queued demand outgrows the capacity this scaler can see
+//!
+//! The scaler only ever schedules onto capacity reported by its schedulers. When a provisioner
+//! is configured, demand that could not be placed for longer then the configured dwell will
+//! request new nodes from a user provided hook and nodes that sit idle will be retired.
+
+use chrono::prelude::*;
+use hashbrown::HashMap;
+use serde_derive::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use thorium::conf::{NodeShape, Provisioner};
+use thorium::models::{CpuArch, NodeHealth, NodeListParams, NodeUpdate, Pools, Resources};
+use thorium::{Error, Thorium};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{Level, event, instrument};
+
+use super::schedulers::{Allocatable, Demand, NodeResources};
+
+/// The methods required to provision and retire nodes for Thorium
+#[async_trait::async_trait]
+pub trait NodeProvisioner {
+    /// Request new nodes of a specific shape
+    ///
+    /// Returns the names the new nodes will join the cluster as.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster the new nodes should join
+    /// * `shape` - The shape of node to request
+    /// * `count` - The number of nodes to request
+    async fn provision(
+        &mut self,
+        cluster: &str,
+        shape: &NodeShape,
+        count: u64,
+    ) -> Result<Vec<String>, Error>;
+
+    /// Retire nodes that are no longer needed
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster these nodes are in
+    /// * `nodes` - The names of the nodes to retire
+    async fn retire(&mut self, cluster: &str, nodes: &[String]) -> Result<(), Error>;
+}
+
+/// A request sent to a provisioning hook
+#[derive(Serialize, Debug)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum HookRequest<'a> {
+    /// Request new nodes of a specific shape
+    Provision {
+        /// The cluster the new nodes should join
+        cluster: &'a str,
+        /// The shape of node to request
+        shape: &'a NodeShape,
+        /// The number of nodes to request
+        count: u64,
+    },
+    /// Retire some nodes
+    Retire {
+        /// The cluster these nodes are in
+        cluster: &'a str,
+        /// The nodes to retire
+        nodes: &'a [String],
+    },
+}
+
+/// A response from a provisioning hook
+#[derive(Deserialize, Debug, Default)]
+struct HookResponse {
+    /// The names of any nodes that were provisioned
+    #[serde(default)]
+    nodes: Vec<String>,
+}
+
+/// A provisioner that calls a user provided executable or http endpoint
+pub struct HookProvisioner {
+    /// The executable to call and the args to pass it
+    command: Option<(String, Vec<String>)>,
+    /// The endpoint to call
+    url: Option<String>,
+    /// The client to use when calling our endpoint
+    client: reqwest::Client,
+    /// How long a single call to our hook can run before it is abandoned
+    timeout: Duration,
+}
+
+impl HookProvisioner {
+    /// Create a new hook provisioner
+    ///
+    /// # Arguments
+    ///
+    /// * `conf` - The provisioner settings to use
+    pub fn new(conf: &Provisioner) -> Result<Self, Error> {
+        // make sure we have a command or url to call
+        if conf.command.is_none() && conf.url.is_none() {
+            return Err(Error::new("Provisioner requires either a command or url"));
+        }
+        // build our hook provisioner
+        let provisioner = HookProvisioner {
+            command: conf
+                .command
+                .as_ref()
+                .map(|path| (path.to_string_lossy().to_string(), conf.args.clone())),
+            url: conf.url.clone(),
+            client: reqwest::Client::new(),
+            timeout: Duration::from_secs(conf.timeout),
+        };
+        Ok(provisioner)
+    }
+
+    /// Send a request to our hook and get its response
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request to send
+    async fn call(&self, req: &HookRequest<'_>) -> Result<HookResponse, Error> {
+        // serialize our request
+        let body = serde_json::to_vec(req)?;
+        // prefer our command if one was set
+        let raw = if let Some((path, args)) = &self.command {
+            // spawn our hook with our request piped to its stdin
+            let mut child = Command::new(path)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+            // write our request and close stdin so the hook sees the end of it
+            let run = async {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(&body).await?;
+                }
+                // wait for our hook to complete
+                child.wait_with_output().await
+            };
+            // kill our hook if it runs for too long
+            let output = match tokio::time::timeout(self.timeout, run).await {
+                Ok(output) => output?,
+                Err(_) => {
+                    return Err(Error::new(format!(
+                        "Provisioner {} timed out after {:?}",
+                        path, self.timeout
+                    )));
+                }
+            };
+            // make sure our hook succeeded
+            if !output.status.success() {
+                return Err(Error::new(format!(
+                    "Provisioner {} failed with {}: {}",
+                    path,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                )));
+            }
+            output.stdout
+        } else if let Some(url) = &self.url {
+            // post our request to our endpoint
+            let resp = self
+                .client
+                .post(url)
+                .header("content-type", "application/json")
+                .timeout(self.timeout)
+                .body(body)
+                .send()
+                .await?
+                .error_for_status()?;
+            resp.bytes().await?.to_vec()
+        } else {
+            return Err(Error::new("Provisioner requires either a command or url"));
+        };
+        // an empty response is valid for retires
+        if raw.iter().all(u8::is_ascii_whitespace) {
+            return Ok(HookResponse::default());
+        }
+        Ok(serde_json::from_slice(&raw)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeProvisioner for HookProvisioner {
+    /// Request new nodes of a specific shape
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster the new nodes should join
+    /// * `shape` - The shape of node to request
+    /// * `count` - The number of nodes to request
+    async fn provision(
+        &mut self,
+        cluster: &str,
+        shape: &NodeShape,
+        count: u64,
+    ) -> Result<Vec<String>, Error> {
+        // build our request
+        let req = HookRequest::Provision {
+            cluster,
+            shape,
+            count,
+        };
+        // ask our hook for new nodes
+        let resp = self.call(&req).await?;
+        Ok(resp.nodes)
+    }
+
+    /// Retire nodes that are no longer needed
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster these nodes are in
+    /// * `nodes` - The names of the nodes to retire
+    async fn retire(&mut self, cluster: &str, nodes: &[String]) -> Result<(), Error> {
+        // build our request
+        let req = HookRequest::Retire { cluster, nodes };
+        // ask our hook to retire these nodes
+        self.call(&req).await?;
+        Ok(())
+    }
+}
+
+/// A node we have provisioned
+#[derive(Debug)]
+struct Provisioned {
+    /// When this node was requested
+    requested: DateTime<Utc>,
+    /// When this node was first seen idle
+    idle_since: Option<DateTime<Utc>>,
+    /// The architecture of this node
+    arch: CpuArch,
+    /// The resources this node will provide once it joins
+    resources: Resources,
+}
+
+/// Decides when to provision or retire nodes based on unmet demand
+pub struct Autoscaler {
+    /// The provisioner settings to follow
+    conf: Provisioner,
+    /// The provisioner to request and retire nodes with
+    provisioner: Box<dyn NodeProvisioner + Send>,
+    /// When each pool started having demand it could not place
+    pressure: HashMap<&'static str, DateTime<Utc>>,
+    /// The nodes we have provisioned
+    provisioned: HashMap<String, Provisioned>,
+}
+
+impl Autoscaler {
+    /// Create a new autoscaler
+    ///
+    /// # Arguments
+    ///
+    /// * `conf` - The provisioner settings to follow
+    /// * `provisioner` - The provisioner to request and retire nodes with
+    pub fn new(conf: &Provisioner, provisioner: Box<dyn NodeProvisioner + Send>) -> Self {
+        Autoscaler {
+            conf: conf.clone(),
+            provisioner,
+            pressure: HashMap::default(),
+            provisioned: HashMap::default(),
+        }
+    }
+
+    /// Pick the smallest shape that can fit the largest unmet request
+    ///
+    /// # Arguments
+    ///
+    /// * `shapes` - The shapes we can provision
+    /// * `arch` - The architecture our demand requires
+    /// * `demand` - The demand to pick a shape for
+    fn pick_shape<'a>(
+        shapes: &'a [NodeShape],
+        arch: CpuArch,
+        demand: &Demand,
+    ) -> Option<&'a NodeShape> {
+        shapes
+            .iter()
+            .filter(|shape| shape.arch == arch && shape.resources().enough(&demand.largest))
+            .min_by_key(|shape| (shape.cpu, shape.memory))
+    }
+
+    /// Determine how many nodes of a shape are needed to meet some demand
+    ///
+    /// # Arguments
+    ///
+    /// * `shape` - The resources of the shape we are provisioning
+    /// * `demand` - The demand to meet
+    /// * `pending` - The resources of nodes that were provisioned but have not joined yet
+    fn nodes_needed(shape: &Resources, demand: &Demand, pending: &Resources) -> u64 {
+        // our pending nodes will meet this demand once they join
+        if pending.enough(&demand.resources) {
+            return 0;
+        }
+        // get the demand our pending nodes will not cover
+        let mut unmet = demand.resources;
+        unmet -= *pending;
+        // get the number of nodes needed to meet our cpu and memory demands
+        let cpu = unmet.cpu.div_ceil(shape.cpu.max(1));
+        let memory = unmet.memory.div_ceil(shape.memory.max(1));
+        cpu.max(memory).max(1)
+    }
+
+    /// Get how long a single check can run before it is abandoned
+    ///
+    /// Each pool can call our hook once per architecture and retiring nodes calls it once more.
+    pub fn timeout(&self) -> Duration {
+        let calls = self.conf.shapes.len() as u32 * 2 + 1;
+        Duration::from_secs(self.conf.timeout) * calls
+    }
+
+    /// Track any enabled nodes in our cluster that we are not already tracking
+    ///
+    /// We only track the nodes we provision in memory, so this lets us retire nodes
+    /// that were provisioned before the scaler restarted.
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client for the Thorium api
+    #[instrument(name = "Autoscaler::recover", skip_all, err(Debug))]
+    async fn recover(&mut self, thorium: &Thorium) -> Result<(), Error> {
+        // get the current timestamp to compare against
+        let now = Utc::now();
+        // list the nodes in the cluster our provisioned nodes join
+        let params = NodeListParams::default()
+            .cluster(&self.conf.cluster)
+            .limit(50);
+        let mut cursor = thorium.system.list_node_details(&params).await?;
+        loop {
+            // track any enabled nodes we don't know about yet
+            for node in cursor.data.drain(..) {
+                // skip disabled nodes as they are already being retired
+                if matches!(node.health, NodeHealth::Disabled(_)) {
+                    continue;
+                }
+                // start tracking this node if we aren't already
+                if !self.provisioned.contains_key(&node.name) {
+                    event!(
+                        Level::INFO,
+                        msg = "Tracking existing node",
+                        node = node.name
+                    );
+                    self.provisioned.insert(
+                        node.name,
+                        Provisioned {
+                            requested: now,
+                            idle_since: None,
+                            arch: node.arch,
+                            resources: node.resources,
+                        },
+                    );
+                }
+            }
+            // stop once we have crawled all of our nodes
+            if cursor.exhausted() {
+                break;
+            }
+            // get the next page of nodes
+            cursor.refill().await?;
+        }
+        Ok(())
+    }
+
+    /// Get the nodes that have joined the cluster our provisioned nodes join
+    ///
+    /// # Arguments
+    ///
+    /// * `allocatable` - The resources this scaler can see
+    fn joined<'a>(&self, allocatable: &'a Allocatable) -> HashMap<&'a String, &'a NodeResources> {
+        allocatable
+            .clusters
+            .values()
+            .flatten()
+            .find(|(name, _)| **name == self.conf.cluster)
+            .map(|(_, cluster)| cluster.nodes.values().flatten().collect::<HashMap<_, _>>())
+            .unwrap_or_default()
+    }
+
+    /// Get the resources of our provisioned nodes that have not joined yet by architecture
+    ///
+    /// # Arguments
+    ///
+    /// * `allocatable` - The resources this scaler can see
+    fn pending(&self, allocatable: &Allocatable) -> HashMap<CpuArch, Resources> {
+        // get the nodes that have already joined
+        let joined = self.joined(allocatable);
+        let mut pending: HashMap<CpuArch, Resources> = HashMap::default();
+        // add up the resources for any nodes that have not joined yet
+        for (name, provisioned) in &self.provisioned {
+            if !joined.contains_key(name) {
+                *pending.entry(provisioned.arch).or_default() += provisioned.resources;
+            }
+        }
+        pending
+    }
+
+    /// Provision nodes for any pools whose demand has exceeded capacity for longer then our dwell
+    ///
+    /// # Arguments
+    ///
+    /// * `allocatable` - The resources and demand this scaler can see
+    #[instrument(name = "Autoscaler::provision", skip_all, err(Debug))]
+    async fn provision(&mut self, allocatable: &Allocatable) -> Result<(), Error> {
+        // get the current timestamp to compare against
+        let now = Utc::now();
+        // get the capacity we already requested that has not joined yet
+        let mut pending = self.pending(allocatable);
+        // check each of our pools
+        for (name, pool) in [
+            ("FairShare", Pools::FairShare),
+            ("Deadline", Pools::Deadline),
+        ] {
+            // get the demand this pool could not place
+            let demand = allocatable.demand.get(pool);
+            // if this pool has no unmet demand then it is no longer under pressure
+            if demand.is_empty() {
+                self.pressure.remove(name);
+                continue;
+            }
+            // get when this pool started being under pressure
+            let since = *self.pressure.entry(name).or_insert(now);
+            // skip this pool if it has not been under pressure for longer then our dwell
+            if (now - since).num_seconds() < self.conf.dwell as i64 {
+                continue;
+            }
+            // restart our dwell so new nodes have time to join before we request more
+            self.pressure.insert(name, now);
+            // request nodes for each architecture this pool needs
+            for (arch, arch_demand) in &demand {
+                // determine how many more nodes we are allowed to provision
+                let remaining = self
+                    .conf
+                    .max_nodes
+                    .saturating_sub(self.provisioned.len() as u64);
+                // stop if we can't provision any more nodes
+                if remaining == 0 {
+                    event!(
+                        Level::WARN,
+                        msg = "Max provisioned nodes reached",
+                        pool = name
+                    );
+                    return Ok(());
+                }
+                // pick a shape for this demand
+                let Some(shape) = Self::pick_shape(&self.conf.shapes, *arch, arch_demand).cloned()
+                else {
+                    event!(
+                        Level::WARN,
+                        msg = "No shape can fit demand",
+                        pool = name,
+                        arch = arch.as_str()
+                    );
+                    continue;
+                };
+                // get the capacity for this architecture that is still joining
+                let arch_pending = pending.entry(*arch).or_default();
+                // determine how many nodes to request
+                let count = Self::nodes_needed(&shape.resources(), arch_demand, arch_pending)
+                    .min(remaining);
+                // consume the pending capacity this demand will use
+                *arch_pending -= arch_demand.resources;
+                // skip this demand if our pending nodes will already meet it
+                if count == 0 {
+                    continue;
+                }
+                // log the nodes we are requesting
+                event!(
+                    Level::INFO,
+                    pool = name,
+                    shape = shape.name,
+                    count,
+                    workers = arch_demand.workers
+                );
+                // request our new nodes
+                let nodes = self
+                    .provisioner
+                    .provision(&self.conf.cluster, &shape, count)
+                    .await?;
+                // track the nodes we provisioned
+                for node in nodes {
+                    self.provisioned.insert(
+                        node,
+                        Provisioned {
+                            requested: now,
+                            idle_since: None,
+                            arch: *arch,
+                            resources: shape.resources(),
+                        },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Retire any provisioned nodes that have been idle for longer then our idle timeout
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client for the Thorium api
+    /// * `allocatable` - The resources this scaler can see
+    #[instrument(name = "Autoscaler::retire", skip_all, err(Debug))]
+    async fn retire(&mut self, thorium: &Thorium, allocatable: &Allocatable) -> Result<(), Error> {
+        // get the current timestamp to compare against
+        let now = Utc::now();
+        // get the nodes in the cluster our provisioned nodes join
+        let nodes = self.joined(allocatable);
+        // track the nodes we want to retire
+        let mut retire = Vec::default();
+        for (name, provisioned) in &mut self.provisioned {
+            match nodes.get(name) {
+                // this node has joined and is running workers so it is not idle
+                Some(node) if !node.spawned.is_empty() => provisioned.idle_since = None,
+                // this node has joined but is idle
+                Some(node) => {
+                    // get when this node became idle
+                    let since = *provisioned.idle_since.get_or_insert(now);
+                    // retire this node if it has been idle for too long
+                    if (now - since).num_seconds() >= self.conf.idle as i64 {
+                        retire.push((name.clone(), Some(node.total)));
+                    }
+                }
+                // this node never joined our cluster so give up on it after our idle timeout
+                None => {
+                    if (now - provisioned.requested).num_seconds() >= self.conf.idle as i64 {
+                        retire.push((name.clone(), None));
+                    }
+                }
+            }
+        }
+        // skip retiring nodes if we have none to retire
+        if retire.is_empty() {
+            return Ok(());
+        }
+        // disable any joined nodes so no new workers are scheduled on them
+        for (name, total) in &retire {
+            if let Some(total) = total {
+                // build the update to disable this node
+                let health = NodeHealth::Disabled(Some("Retired by provisioner".to_owned()));
+                let update = NodeUpdate::new(health, *total);
+                // disable this node
+                thorium
+                    .system
+                    .update_node(&self.conf.cluster, name, &update)
+                    .await?;
+            }
+        }
+        // get the names of the nodes we are retiring
+        let names = retire.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        // log the nodes we are retiring
+        event!(Level::INFO, retiring = names.len());
+        // retire these nodes
+        self.provisioner.retire(&self.conf.cluster, &names).await?;
+        // stop tracking our retired nodes
+        self.provisioned.retain(|name, _| !names.contains(name));
+        Ok(())
+    }
+
+    /// Provision or retire nodes based on our current demand
+    ///
+    /// # Arguments
+    ///
+    /// * `thorium` - A client for the Thorium api
+    /// * `allocatable` - The resources and demand this scaler can see
+    #[instrument(name = "Autoscaler::check", skip_all, err(Debug))]
+    pub async fn check(
+        &mut self,
+        thorium: &Thorium,
+        allocatable: &Allocatable,
+    ) -> Result<(), Error> {
+        // track any nodes we provisioned before a restart
+        self.recover(thorium).await?;
+        // provision any nodes to meet unmet demand
+        self.provision(allocatable).await?;
+        // retire any nodes that are no longer needed
+        self.retire(thorium, allocatable).await
+    }
+}
+
+impl std::fmt::Debug for Autoscaler {
+    /// Allow our autoscaler to be printed in a debug format
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Autoscaler")
+            .field("conf", &self.conf)
+            .field("pressure", &self.pressure)
+            .field("provisioned", &self.provisioned)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a node shape for testing
+    fn shape(name: &str, cpu: u64, memory: u64, arch: CpuArch) -> NodeShape {
+        NodeShape {
+            name: name.to_owned(),
+            cpu,
+            memory,
+            ephemeral_storage: 0,
+            nvidia_gpu: 0,
+            amd_gpu: 0,
+            arch,
+        }
+    }
+
+    /// Build some demand for testing
+    fn demand(total: Resources, largest: Resources) -> Demand {
+        Demand {
+            resources: total,
+            workers: 1,
+            largest,
+        }
+    }
+
+    #[test]
+    fn pick_shape_smallest_fit() {
+        let shapes = vec![
+            shape("large", 16, 65536, CpuArch::X86_64),
+            shape("small", 2, 4096, CpuArch::X86_64),
+            shape("medium", 4, 16384, CpuArch::X86_64),
+        ];
+        // the smallest shape that fits our largest request should be picked
+        let needs = demand(
+            Resources::new(8000, 16384, 0, 0),
+            Resources::new(3000, 8192, 0, 0),
+        );
+        let picked = Autoscaler::pick_shape(&shapes, CpuArch::X86_64, &needs);
+        assert_eq!(picked.map(|shape| shape.name.as_str()), Some("medium"));
+        // a request larger then any shape should not pick one
+        let needs = demand(
+            Resources::new(32000, 1024, 0, 0),
+            Resources::new(32000, 1024, 0, 0),
+        );
+        assert!(Autoscaler::pick_shape(&shapes, CpuArch::X86_64, &needs).is_none());
+    }
+
+    #[test]
+    fn pick_shape_matches_arch() {
+        let shapes = vec![
+            shape("x86", 2, 4096, CpuArch::X86_64),
+            shape("arm", 4, 8192, CpuArch::Arm64),
+        ];
+        let needs = demand(
+            Resources::new(1000, 1024, 0, 0),
+            Resources::new(1000, 1024, 0, 0),
+        );
+        let picked = Autoscaler::pick_shape(&shapes, CpuArch::Arm64, &needs);
+        assert_eq!(picked.map(|shape| shape.name.as_str()), Some("arm"));
+        assert!(Autoscaler::pick_shape(&shapes, CpuArch::Riscv64, &needs).is_none());
+    }
+
+    #[test]
+    fn nodes_needed_covers_cpu_and_memory() {
+        let shape = shape("node", 4, 8192, CpuArch::X86_64).resources();
+        let none = Resources::default();
+        // cpu bound demand
+        let needs = demand(Resources::new(9000, 1024, 0, 0), Resources::default());
+        assert_eq!(Autoscaler::nodes_needed(&shape, &needs, &none), 3);
+        // memory bound demand
+        let needs = demand(Resources::new(1000, 20000, 0, 0), Resources::default());
+        assert_eq!(Autoscaler::nodes_needed(&shape, &needs, &none), 3);
+        // any unmet demand needs at least one node
+        let needs = demand(Resources::new(1, 1, 0, 0), Resources::default());
+        assert_eq!(Autoscaler::nodes_needed(&shape, &needs, &none), 1);
+    }
+
+    #[test]
+    fn nodes_needed_subtracts_pending() {
+        let shape = shape("node", 4, 8192, CpuArch::X86_64).resources();
+        let needs = demand(Resources::new(12000, 8192, 0, 0), Resources::default());
+        // pending nodes that cover all of our demand mean no new nodes are needed
+        let pending = Resources::new(16000, 16384, 0, 0);
+        assert_eq!(Autoscaler::nodes_needed(&shape, &needs, &pending), 0);
+        // pending nodes that cover part of our demand reduce the nodes needed
+        let pending = Resources::new(4000, 8192, 0, 0);
+        assert_eq!(Autoscaler::nodes_needed(&shape, &needs, &pending), 2);
+    }
+}
@@ -14,6 +14,8 @@ use tokio::task::JoinHandle;
 use tracing::{Level, event, instrument, span};
 
 use super::Cache;
+use super::mirrors;
+use super::provisioner::{Autoscaler, HookProvisioner};
 use super::schedulers::{self, Allocatable, ReqMap, Scheduler, WorkerDeletion};
 use super::tasks::{self, TaskResult, Tasks, ZombieChecker};
 use crate::args::Args;
@@ -248,6 +250,8 @@ pub struct Scaler {
     schedulers: HashMap<String, Box<dyn Scheduler + Send>>,
     /// The resources currently available in this cluster
     pub allocatable: Allocatable,
+    /// Provisions and retires nodes when demand outgrows our capacity if configured
+    autoscaler: Option<Autoscaler>,
     /// A queue of tasks to complete sorted by the time to start executing them
     tasks: BTreeMap<DateTime<Utc>, Tasks>,
     /// The currently active background tasks that have been spawned
@@ -308,6 +312,15 @@ impl Scaler {
         let settings = thorium.system.get_settings().await?;
         // start with an empty allocatable object
         let allocatable = Allocatable::new(scaler_type, &conf, &settings, &cache);
+        // build our autoscaler if a provisioner is configured
+        let autoscaler = match &conf.thorium.scaler.provisioner {
+            Some(prov_conf) => {
+                // build the provisioner to call
+                let provisioner = HookProvisioner::new(prov_conf)?;
+                Some(Autoscaler::new(prov_conf, Box::new(provisioner)))
+            }
+            None => None,
+        };
         // build our task queue
         let tasks = tasks::Tasks::setup_queue(&conf);
         // instance the scaler
@@ -319,6 +332,7 @@ impl Scaler {
             zombies,
             schedulers,
             allocatable,
+            autoscaler,
             tasks,
             active: Vec::default(),
         };
@@ -382,6 +396,8 @@ impl Scaler {
                     }
                     // Thorium has updated image runtimes
                     TaskResult::UpdateRuntimes => add_task!(self, Tasks::UpdateRuntimes),
+                    // Any due repo mirrors have been fetched
+                    TaskResult::RepoMirrors => add_task!(self, Tasks::RepoMirrors),
                 };
             } else {
                 // this task hasn't completed so keep tracking it
@@ -452,11 +468,36 @@ impl Scaler {
                     let client = self.thorium.clone();
                     spawn!(self.active, tasks::cleanup(client));
                 }
+                // fetch any mirrored repos that are due
+                Tasks::RepoMirrors => {
+                    // clone client so we can send it to another thread
+                    let client = self.thorium.clone();
+                    spawn!(self.active, mirrors::fetch_mirrors(client));
+                }
                 // tell Thorium to decrease our users fair share ranks
                 Tasks::DecreaseFairShare => {
                     // decrease our users fair share ranks
                     self.allocatable.decrease_fair_share_ranks(&self.conf);
                 }
+                // provision or retire nodes based on our unmet demand
+                Tasks::Provision => {
+                    // only check our demand if we have an autoscaler
+                    if let Some(autoscaler) = &mut self.autoscaler {
+                        // don't let a stuck hook or a failed check block our scale loop
+                        let timeout = autoscaler.timeout();
+                        let check = autoscaler.check(&self.thorium, &self.allocatable);
+                        match tokio::time::timeout(timeout, check).await {
+                            Ok(Ok(())) => (),
+                            Ok(Err(error)) => event!(Level::ERROR, error = error.msg()),
+                            Err(_) => event!(
+                                Level::ERROR,
+                                msg = "Provisioning check timed out",
+                                timeout = timeout.as_secs()
+                            ),
+                        }
+                    }
+                    completed.push(Tasks::Provision);
+                }
             };
         }
         // add any blocking completed tasks back to our task list
@@ -14,7 +14,9 @@ pub mod dry_run;
 pub mod k8s;
 pub mod requisitions;
 
-pub use allocatable::{Allocatable, AllocatableUpdate, NodeAllocatableUpdate, NodeResources};
+pub use allocatable::{
+    Allocatable, AllocatableUpdate, Demand, NodeAllocatableUpdate, NodeResources,
+};
 pub use direct::Direct;
 pub use k8s::K8s;
 pub use requisitions::{ReqMap, Spawned};
@@ -8,8 +8,8 @@ use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashSet};
 use thorium::conf::{FairShareWeights, IsRestricted, WorkerRestrictions};
 use thorium::models::{
-    Deadline, Image, ImageScaler, NodeListParams, Pools, Requisition, Resources, SpawnLimits,
-    SpawnMap, SystemSettings, WorkerDeleteMap,
+    CpuArch, Deadline, Image, ImageScaler, NodeListParams, Pools, PriorityClass, Requisition,
+    Resources, SpawnLimits, SpawnMap, SystemSettings, WorkerDeleteMap,
 };
 use thorium::{Conf, Error, Thorium};
 use tracing::{Level, Span, event, instrument};
@@ -19,7 +19,7 @@ mod pool;
 use crate::from_now;
 use crate::libs::schedulers::ReqMap;
 use crate::libs::{BanSets, Cache, Spawned};
-pub use pool::{Pool, PoolFrees};
+pub use pool::{Demand, Pool, PoolDemand, PoolFrees};
 
 /// An update for a specific node
 #[derive(Debug, Clone)]
@@ -30,6 +30,10 @@ pub struct NodeAllocatableUpdate {
     pub total: Resources,
     /// The workers that are active on this node
     pub active: HashSet<String>,
+    /// The CPU architecture of this node
+    pub arch: CpuArch,
+    /// The architectures this node can run under emulation
+    pub emulated: Vec<CpuArch>,
 }
 
 impl NodeAllocatableUpdate {
@@ -44,8 +48,23 @@ impl NodeAllocatableUpdate {
             available,
             total,
             active: HashSet::default(),
+            arch: CpuArch::default(),
+            emulated: Vec::default(),
         }
     }
+
+    /// Set the architectures for this node
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - The CPU architecture of this node
+    /// * `emulated` - The architectures this node can run under emulation
+    #[must_use]
+    pub fn arch(mut self, arch: CpuArch, emulated: Vec<CpuArch>) -> Self {
+        self.arch = arch;
+        self.emulated = emulated;
+        self
+    }
 }
 
 /// An update to the allocatable resources for a single cluster
@@ -89,6 +108,8 @@ pub struct Allocatable {
     spawn_limit: usize,
     /// The containing our currently pending changes to worker allocations
     pub changes: ReqMap,
+    /// The demand we could not place on existing capacity during our last allocation
+    pub demand: PoolDemand,
 }
 
 impl Allocatable {
@@ -132,6 +153,7 @@ impl Allocatable {
             fair_share_counts: HashMap::default(),
             spawn_limit: 0,
             changes: ReqMap::default(),
+            demand: PoolDemand::default(),
         }
     }
 
@@ -323,22 +345,30 @@ impl Allocatable {
     ///
     /// * `image` - The image to allocate resources for
     fn allocate_cluster_helper(&mut self, image: &Image) -> Option<(u64, String, NodeResources)> {
-        // crawl over all nodes until we find one that we can fit on
-        for (cpus, cluster_map) in self.clusters.iter_mut().rev() {
-            // iterate over the clusters that have the same number of cores
-            for (cluster_name, cluster) in cluster_map.iter_mut() {
-                // check if this image has any restrictions
-                let nodes = match self.restrictions.check(cluster_name, image) {
-                    IsRestricted::No => None,
-                    IsRestricted::Yes(nodes) => Some(nodes),
-                    IsRestricted::WrongCluster => continue,
-                };
-                // try to consume the resources for this image on a node
-                if let Some(node) = cluster.allocate_node(image, nodes) {
-                    // clone our values
-                    let cluster_name = cluster_name.to_owned();
-                    // return the info we found
-                    return Some((*cpus, cluster_name, node));
+        // only fall back to emulating this image if no node can run it natively
+        let passes: &[bool] = if image.emulation {
+            &[false, true]
+        } else {
+            &[false]
+        };
+        for emulated in passes {
+            // crawl over all nodes until we find one that we can fit on
+            for (cpus, cluster_map) in self.clusters.iter_mut().rev() {
+                // iterate over the clusters that have the same number of cores
+                for (cluster_name, cluster) in cluster_map.iter_mut() {
+                    // check if this image has any restrictions
+                    let nodes = match self.restrictions.check(cluster_name, image) {
+                        IsRestricted::No => None,
+                        IsRestricted::Yes(nodes) => Some(nodes),
+                        IsRestricted::WrongCluster => continue,
+                    };
+                    // try to consume the resources for this image on a node
+                    if let Some(node) = cluster.allocate_node(image, nodes, *emulated) {
+                        // clone our values
+                        let cluster_name = cluster_name.to_owned();
+                        // return the info we found
+                        return Some((*cpus, cluster_name, node));
+                    }
                 }
             }
         }
@@ -387,21 +417,29 @@ impl Allocatable {
     ///
     /// # Arguments
     ///
+    /// * `req` - The requisition to allocate resources for
     /// * `image` - The image to allocate resources for
     /// * `pool` - The pool we are trying to allocate resources in
-    fn try_allocate(&mut self, image: &Image, pool: Pools) -> Option<(String, String)> {
+    fn try_allocate(
+        &mut self,
+        req: &Requisition,
+        image: &Image,
+        pool: Pools,
+    ) -> Option<(String, String)> {
         // check if we have enough resources in the target pool
-        if self.enough(image, pool) {
+        let enough = self.enough(image, pool);
+        if enough {
             // try to allocate this image on a node
-            return match self.allocate_cluster(image) {
-                Some((cluster, node)) => {
-                    // consume the resources from the correct pool
-                    self.consume(image, pool);
-                    // return our cluster and node
-                    Some((cluster, node))
-                }
-                None => None,
-            };
+            if let Some((cluster, node)) = self.allocate_cluster(image) {
+                // consume the resources from the correct pool
+                self.consume(image, pool);
+                // return our cluster and node
+                return Some((cluster, node));
+            }
+        }
+        // track this unmet demand unless it was only blocked by the fair share pools own limits
+        if enough || pool == Pools::Deadline {
+            self.demand.add(pool, req, image);
         }
         // we could not spawn this image
         None
@@ -461,10 +499,18 @@ impl Allocatable {
                         #[allow(clippy::cast_possible_truncation)]
                         let deadline = from_now!(image.runtime as i64);
                         // try to spawn this requisition
-                        if let Some((cluster, node)) = self.try_allocate(image, Pools::FairShare) {
-                            // build our newly spawned worker
-                            let spawned =
-                                Spawned::new(&cluster, &node, req.clone(), image, Pools::FairShare);
+                        if let Some((cluster, node)) =
+                            self.try_allocate(req, image, Pools::FairShare)
+                        {
+                            // fair share spawns are not tied to a reaction so use our images priority
+                            let spawned = Spawned::new(
+                                &cluster,
+                                &node,
+                                req.clone(),
+                                image,
+                                Pools::FairShare,
+                                PriorityClass::Standard,
+                            );
                             // get an entry to this clusters map in our change map
                             let cluster_entry = self.changes.spawns.entry(cluster).or_default();
                             // get an entry to the deadline group for this spawn
@@ -580,9 +626,10 @@ impl Allocatable {
                     // add this size of this group to our node cnt
                     node_cnt += node_group.len();
                     // reset this nodes spawn limit
-                    node_group
-                        .values_mut()
-                        .for_each(|node| node.spawn_slots = 2);
+                    node_group.values_mut().for_each(|node| {
+                        node.spawn_slots = 2;
+                        node.restoring.clear();
+                    });
                 }
             }
         }
@@ -610,8 +657,9 @@ impl Allocatable {
         let deadlines = self.get_deadlines(thorium, cache).await?;
         // crawl over these deadlines and try to meet them
         for deadline in deadlines {
-            // get this deadlines timestamp
+            // get this deadlines timestamp and priority class
             let timestamp = deadline.deadline;
+            let priority = deadline.priority;
             // build a requisition for this deadline
             let req = Requisition::from(deadline);
             // check if we spawned this image in the past
@@ -648,9 +696,16 @@ impl Allocatable {
                 continue;
             };
             // try to allocate resources for this deadline
-            if let Some((cluster, node)) = self.try_allocate(image, Pools::Deadline) {
+            if let Some((cluster, node)) = self.try_allocate(&req, image, Pools::Deadline) {
                 // build our newly spawned worker
-                let spawned = Spawned::new(&cluster, &node, req.clone(), image, Pools::Deadline);
+                let spawned = Spawned::new(
+                    &cluster,
+                    &node,
+                    req.clone(),
+                    image,
+                    Pools::Deadline,
+                    priority,
+                );
                 // get an entry to this clusters map in our change map
                 let cluster_entry = self.changes.spawns.entry(cluster).or_default();
                 // get an entry to the deadline group for this spawn
@@ -661,8 +716,12 @@ impl Allocatable {
                 // we would like to spawn this image but can't so check if we are low on resources
                 // and out of spawn slots
                 if self.low_resources && *spawn_slots > 0 {
-                    // try to find something to scale down to meet this deadline
-                    if self.scale_down_to_meet(timestamp, &req, image) {
+                    // a reactions priority class overrides its images
+                    let urgent = image.priority.overridden_by(priority) == PriorityClass::Urgent;
+                    // try to find something to scale down or preempt to meet this deadline
+                    if self.scale_down_to_meet(timestamp, &req, image)
+                        || (urgent && self.preempt_to_meet(&req, image))
+                    {
                         // consume a spawn slot
                         *spawn_slots -= 1;
                     }
@@ -745,6 +804,77 @@ impl Allocatable {
         false
     }
 
+    /// Preempt any preemptible workers to meet urgent deadlines
+    ///
+    /// Unlike normal scale downs this does not wait for workers to complete a job. Preempted
+    /// workers are given time to save a checkpoint and return their job to the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The requisition for the urgent deadline to meet
+    /// * `image` - The image for the urgent deadline to meet
+    fn preempt_to_meet(&mut self, req: &Requisition, image: &Image) -> bool {
+        // track the workers we will preempt to meet this deadline
+        let mut preempts = Vec::default();
+        // crawl all cluster cpu groups to determine if we can preempt anything to meet this requisition
+        for (_, cluster_group) in self.clusters.iter_mut().rev() {
+            // crawl the clusters in this cpu group that are low on resources
+            for cluster in cluster_group
+                .values_mut()
+                .filter(|cluster| cluster.low_resources)
+            {
+                // crawl the node cpu groups in this cluster
+                for node_group in cluster.nodes.values_mut().rev() {
+                    // crawl the nodes in this node cpu group
+                    for node in node_group.values_mut() {
+                        // track the resources we can free on this node
+                        let mut freeable = node.available;
+                        // clear our preempt set
+                        preempts.clear();
+                        // crawl the worker deadline groups on this node starting with the latest
+                        for (_, spawned_group) in node.spawned.iter_mut().rev() {
+                            // crawl the workers in this worker deadline group
+                            for spawned in spawned_group {
+                                // skip any workers that are not preemptible or are already scaling down
+                                if spawned.preemptible
+                                    && !spawned.scaled_down
+                                    && spawned.req != *req
+                                {
+                                    // add this workers resources to our freeable set
+                                    freeable += spawned.resources;
+                                    // add this worker to our preempt set
+                                    preempts.push(spawned);
+                                    // check if we have enough resources to spawn this image now
+                                    if freeable.enough(&image.resources) {
+                                        // log the workers we are preempting
+                                        event!(
+                                            Level::INFO,
+                                            msg = "Preempting workers",
+                                            req = req.to_string(),
+                                            preempts = preempts.len()
+                                        );
+                                        // set all of these workers to be preempted
+                                        for spawned in preempts.drain(..) {
+                                            // set our scale down and preempted flags to true
+                                            spawned.scaled_down = true;
+                                            spawned.preempted = true;
+                                            // add this worker to our scale down orders
+                                            self.changes.scale_down(spawned.to_owned());
+                                        }
+                                        // we were able to find things to preempt
+                                        return true;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // we could not find anything to preempt
+        false
+    }
+
     /// Allocate resources for all pools
     ///
     /// # Arguments
@@ -760,6 +890,8 @@ impl Allocatable {
         // empty our req map
         self.changes.spawns.clear();
         self.changes.scale_down.clear();
+        // clear any demand from our last allocation
+        self.demand.clear();
         // reset our nodes spawn slot count
         self.reset_spawns();
         // track the number of spawn slots we have consumed this loop
@@ -1214,6 +1346,8 @@ impl ClusterResources {
             // apply our update to this node
             entry.available = node_update.available;
             entry.total = node_update.total;
+            entry.arch = node_update.arch;
+            entry.emulated = node_update.emulated;
         }
         // sort all of our nodes back in
         for (name, node) in temp_nodes {
@@ -1232,10 +1366,12 @@ impl ClusterResources {
     ///
     /// * `image` - The image to base these pods on
     /// * `nodes` - The nodes that this image is restricted to if any
+    /// * `emulated` - Whether to find a node that can emulate this image
     fn allocate_node(
         &mut self,
         image: &Image,
         nodes: Option<&HashSet<String>>,
+        emulated: bool,
     ) -> Option<NodeResources> {
         // start crawling through the nodes by total cpu
         for node_map in self.nodes.values_mut().rev() {
@@ -1246,7 +1382,7 @@ impl ClusterResources {
                     .iter_mut()
                     // filter out any nodes that do not meet our restrictions
                     .filter(|(name, _)| restrictions.contains(*name))
-                    .find(|(_, node)| node.spawnable(image))
+                    .find(|(_, node)| node.spawnable(image, emulated))
                     .map(|(name, _)| name.to_owned())
                 {
                     // get this node from our map
@@ -1255,6 +1391,8 @@ impl ClusterResources {
                         node.available.consume(&image.resources, 1);
                         // consume a spawn slot
                         node.spawn_slots -= 1;
+                        // track any restored vms placed on this node
+                        node.restore(image);
                         // we spawned something so return true
                         return Some(node);
                     }
@@ -1264,7 +1402,7 @@ impl ClusterResources {
                 // get the first node that has enough resources for us
                 if let Some(name) = node_map
                     .iter()
-                    .find(|(_, node)| node.spawnable(image))
+                    .find(|(_, node)| node.spawnable(image, emulated))
                     .map(|(name, _)| name.to_owned())
                 {
                     // get this node from our map
@@ -1273,6 +1411,8 @@ impl ClusterResources {
                         node.available.consume(&image.resources, 1);
                         // consume a spawn slot
                         node.spawn_slots -= 1;
+                        // track any restored vms placed on this node
+                        node.restore(image);
                         // we spawned something so return true
                         return Some(node);
                     }
@@ -1365,6 +1505,12 @@ pub struct NodeResources {
     pub spawned: BTreeMap<DateTime<Utc>, Vec<Spawned>>,
     /// The number of spawn slots for this node
     pub spawn_slots: u64,
+    /// The CPU architecture of this node
+    pub arch: CpuArch,
+    /// The architectures this node can run under emulation
+    pub emulated: Vec<CpuArch>,
+    /// The group and name of the restored vms placed on this node since our last reset
+    pub restoring: HashSet<(String, String)>,
 }
 
 impl NodeResources {
@@ -1380,6 +1526,33 @@ impl NodeResources {
             total: Resources::default(),
             spawned: BTreeMap::default(),
             spawn_slots: 2,
+            arch: CpuArch::default(),
+            emulated: Vec::default(),
+            restoring: HashSet::default(),
+        }
+    }
+
+    /// Check if an image is a vm restored from a saved memory state
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to check
+    fn restored(image: &Image) -> bool {
+        image
+            .kvm
+            .as_ref()
+            .is_some_and(|kvm| kvm.memory_state.is_some())
+    }
+
+    /// Track that a restored vm was placed on this node
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image that was placed on this node
+    fn restore(&mut self, image: &Image) {
+        if Self::restored(image) {
+            self.restoring
+                .insert((image.group.clone(), image.name.clone()));
         }
     }
 
@@ -1388,11 +1561,35 @@ impl NodeResources {
     /// # Arguments
     ///
     /// * `image` - The image we want to spawn
-    pub fn spawnable(&self, image: &Image) -> bool {
+    /// * `emulated` - Whether to spawn this image under emulation instead of natively
+    pub fn spawnable(&self, image: &Image, emulated: bool) -> bool {
         // check if we have enough spawn slots for this pod
         if self.spawn_slots == 0 {
             return false;
         }
+        // make sure this node can run this images architecture
+        let runnable = if emulated {
+            image.emulation && self.emulated.contains(&image.arch)
+        } else {
+            self.arch == image.arch
+        };
+        if !runnable {
+            return false;
+        }
+        // vms restored from a memory state keep the name of the saved domain so only one can
+        // be restored on each node at a time
+        if Self::restored(image)
+            && (self
+                .restoring
+                .contains(&(image.group.clone(), image.name.clone()))
+                || self
+                    .spawned
+                    .values()
+                    .flatten()
+                    .any(|spawn| spawn.req.group == image.group && spawn.req.stage == image.name))
+        {
+            return false;
+        }
         // make sure this node has enough resources for this image
         self.available.enough(&image.resources)
     }
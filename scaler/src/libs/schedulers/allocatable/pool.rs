@@ -1,6 +1,7 @@
 //! A pool of resources to schedule
 
-use thorium::models::{Image, Pools, Resources, SystemSettings};
+use hashbrown::HashMap;
+use thorium::models::{CpuArch, Image, Pools, Requisition, Resources, SystemSettings};
 
 /// Tracks what resources are freed from what pool
 #[derive(Debug, Default)]
@@ -26,6 +27,121 @@ impl PoolFrees {
     }
 }
 
+/// The demand that could not be placed onto existing capacity
+#[derive(Debug, Default, Clone)]
+pub struct Demand {
+    /// The total resources that could not be placed
+    pub resources: Resources,
+    /// The number of workers that could not be placed
+    pub workers: u64,
+    /// The largest resource request of any single worker that could not be placed
+    pub largest: Resources,
+}
+
+impl Demand {
+    /// Track the largest request for each resource type
+    ///
+    /// # Arguments
+    ///
+    /// * `resources` - The resources to compare against our largest request
+    fn grow_largest(&mut self, resources: &Resources) {
+        let largest = &mut self.largest;
+        largest.cpu = largest.cpu.max(resources.cpu);
+        largest.memory = largest.memory.max(resources.memory);
+        largest.ephemeral_storage = largest.ephemeral_storage.max(resources.ephemeral_storage);
+        largest.nvidia_gpu = largest.nvidia_gpu.max(resources.nvidia_gpu);
+        largest.amd_gpu = largest.amd_gpu.max(resources.amd_gpu);
+    }
+
+    /// Add an image that could not be placed to this demand
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image that could not be placed
+    pub fn add(&mut self, image: &Image) {
+        // add this images resources to our total
+        self.resources += image.resources;
+        self.workers += 1;
+        self.grow_largest(&image.resources);
+    }
+
+    /// Add another demand to this one
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The demand to add
+    pub fn merge(&mut self, other: &Demand) {
+        self.resources += other.resources;
+        self.workers += other.workers;
+        self.grow_largest(&other.largest);
+    }
+}
+
+/// Tracks the demand each pool could not place by requisition
+///
+/// A requisition that could not be placed in both pools is only counted in the deadline pool so
+/// the same jobs are not provisioned for twice.
+#[derive(Debug, Default)]
+pub struct PoolDemand {
+    /// The demand our fairshare pool could not place
+    fairshare: HashMap<Requisition, (CpuArch, Demand)>,
+    /// The demand our deadline pool could not place
+    deadline: HashMap<Requisition, (CpuArch, Demand)>,
+}
+
+impl PoolDemand {
+    /// Add an image that could not be placed to the correct pool
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of pool this image could not be placed in
+    /// * `req` - The requisition that could not be placed
+    /// * `image` - The image that could not be placed
+    pub fn add(&mut self, kind: Pools, req: &Requisition, image: &Image) {
+        // get the demand map for the right pool
+        let map = match kind {
+            Pools::FairShare => &mut self.fairshare,
+            Pools::Deadline => &mut self.deadline,
+        };
+        // add this image to the demand for its requisition
+        match map.get_mut(req) {
+            Some((_, demand)) => demand.add(image),
+            None => {
+                let mut demand = Demand::default();
+                demand.add(image);
+                map.insert(req.clone(), (image.arch, demand));
+            }
+        }
+    }
+
+    /// Get the demand for a specific pool by CPU architecture
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of pool to get demand for
+    pub fn get(&self, kind: Pools) -> HashMap<CpuArch, Demand> {
+        let mut demand: HashMap<CpuArch, Demand> = HashMap::default();
+        let reqs = match kind {
+            Pools::FairShare => &self.fairshare,
+            Pools::Deadline => &self.deadline,
+        };
+        for (req, (arch, req_demand)) in reqs {
+            // skip fairshare demand that our deadline pool is already tracking
+            if kind == Pools::FairShare && self.deadline.contains_key(req) {
+                continue;
+            }
+            demand.entry(*arch).or_default().merge(req_demand);
+        }
+        demand
+    }
+
+    /// Clear all tracked demand
+    pub fn clear(&mut self) {
+        self.fairshare.clear();
+        self.deadline.clear();
+    }
+}
+
 /// A pool of resources to schedule based solely on deadlines
 #[derive(Debug, Default)]
 pub struct Pool {
@@ -162,7 +162,8 @@ impl Direct {
 /// Determine the amount of free resources on a node based on its workers
 fn update_node(node: Node, update: &mut AllocatableUpdate) {
     // build or node update
-    let mut node_update = NodeAllocatableUpdate::new(node.resources, node.resources);
+    let mut node_update =
+        NodeAllocatableUpdate::new(node.resources, node.resources).arch(node.arch, node.emulated);
     // get a mutable ref to our resources for this node
     let resources = &mut node_update.available;
     // crawl over the workers on this node
@@ -201,6 +202,7 @@ impl Scheduler for Direct {
             Tasks::UpdateRuntimes => 300,
             Tasks::Cleanup => 25,
             Tasks::DecreaseFairShare => 600,
+            Tasks::Provision => 30,
         }
     }
 
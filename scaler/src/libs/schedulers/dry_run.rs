@@ -106,6 +106,7 @@ impl Scheduler for DryRun {
             Tasks::UpdateRuntimes => 300,
             Tasks::Cleanup => 25,
             Tasks::DecreaseFairShare => 600,
+            Tasks::Provision => 30,
         }
     }
 
@@ -139,7 +140,8 @@ impl Scheduler for DryRun {
                     let node_update = NodeAllocatableUpdate::new(
                         node.resources.available.clone(),
                         node.resources.total.clone(),
-                    );
+                    )
+                    .arch(node.resources.arch, node.resources.emulated.clone());
                     // add this update
                     update.nodes.insert(name.clone(), node_update);
                 }
@@ -148,7 +150,8 @@ impl Scheduler for DryRun {
                     let mut node_update = NodeAllocatableUpdate::new(
                         node.resources.available.clone(),
                         node.resources.total.clone(),
-                    );
+                    )
+                    .arch(node.resources.arch, node.resources.emulated.clone());
                     // add our active workers
                     node_update
                         .active
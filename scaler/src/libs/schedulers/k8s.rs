@@ -324,6 +324,8 @@ impl K8s {
         // create a cache of generated policy specs mapped by K8's name
         // to avoid generating multiple times
         let mut policy_specs_by_k8s_name: HashMap<String, NetworkPolicy> = HashMap::new();
+        // get the simulated internet service to redirect to if one is configured
+        let simulated_internet = self.network_policies.simulated_internet.clone();
         // see which K8's policies are in the cache and how they compare
         for (ns, k8s_policies) in k8s_policies {
             let network_policies = cache.network_policies.ids_by_group_k8s_name.get(&ns);
@@ -362,6 +364,7 @@ impl K8s {
                                 NetworkPolicies::generate(
                                     cached_policy.clone(),
                                     std::iter::once(ns.clone()),
+                                    simulated_internet.as_ref(),
                                 )
                                 .remove(0)
                                 .1,
@@ -404,6 +407,7 @@ impl K8s {
                                 NetworkPolicies::generate(
                                     cached_policy.clone(),
                                     std::iter::once(ns.clone()),
+                                    simulated_internet.as_ref(),
                                 )
                                 .remove(0)
                                 .1,
@@ -544,6 +548,8 @@ impl K8s {
         self.remove_network_policies(policies_to_remove).await;
         // create a map of policies to create by namespace
         let mut policy_specs_by_ns: HashMap<String, Vec<(Uuid, NetworkPolicy)>> = HashMap::new();
+        // get the simulated internet service to redirect to if one is configured
+        let simulated_internet = self.network_policies.simulated_internet.clone();
         for (id, add_policy) in self
             .network_policies
             .cache
@@ -555,8 +561,11 @@ impl K8s {
                 // generate the spec(s) for this policy
                 Some(add_policy) => {
                     // generate policy specs for all of the policy's groups
-                    let policy_specs =
-                        NetworkPolicies::generate(add_policy.clone(), add_policy.groups.clone());
+                    let policy_specs = NetworkPolicies::generate(
+                        add_policy.clone(),
+                        add_policy.groups.clone(),
+                        simulated_internet.as_ref(),
+                    );
                     // extend our map with those specs
                     policy_specs_by_ns.extend(
                         policy_specs
@@ -848,6 +857,7 @@ impl Scheduler for K8s {
             Tasks::UpdateRuntimes => 300,
             Tasks::Cleanup => 25,
             Tasks::DecreaseFairShare => 600,
+            Tasks::Provision => 30,
         }
     }
 
@@ -64,7 +64,9 @@ impl Cluster {
                         let node_update = NodeUpdate::new(
                             NodeHealth::Healthy,
                             node_alloc_update.available.clone(),
-                        );
+                        )
+                        .arch(node_alloc_update.arch)
+                        .emulated(node_alloc_update.emulated.clone());
                         // update this node in Thorium
                         thorium
                             .system
@@ -1,7 +1,10 @@
-use k8s_openapi::api::core::v1::{Container, EnvVar, SecurityContext};
+use k8s_openapi::api::core::v1::{
+    Container, EnvVar, EnvVarSource, ObjectFieldSelector, SecurityContext,
+};
 use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use serde_json::json;
 use std::collections::BTreeMap;
+use thorium::client::api_capture_filter;
 use thorium::models::{Image, Resources, ScrubbedUser};
 use thorium::Error;
 
@@ -15,10 +18,39 @@ macro_rules! quantity {
     ($($raw:tt)+) => {serde_json::from_value(json!($($raw)+))}
 }
 
+/// The script our capture sidecar runs to capture each jobs network traffic
+///
+/// The agent writes a `<job>.start` file to start a capture and a `<job>.stop`
+/// file to stop it. Once the pcap is fully written a `<job>.pcap.done` file is
+/// written so the agent knows it can submit it. Traffic to the Thorium API is
+/// dropped with the filter in `THORIUM_CAPTURE_FILTER`.
+const CAPTURE_SCRIPT: &str = r#"mkdir -p /tmp/thorium-capture && chmod 777 /tmp/thorium-capture
+cd /tmp/thorium-capture || exit 1
+while true; do
+  for start in *.start; do
+    [ -e "$start" ] || continue
+    job="${start%.start}"
+    rm -f "$start"
+    tcpdump -i any -U -w "$job.pcap" "$THORIUM_CAPTURE_FILTER" &
+    pid=$!
+    # stop capturing once this job is done or a new job has started
+    while [ ! -e "$job.stop" ] && [ -z "$(ls *.start 2>/dev/null)" ]; do sleep 1; done
+    kill -INT "$pid"
+    wait "$pid"
+    rm -f "$job.stop"
+    touch "$job.pcap.done"
+  done
+  sleep 1
+done"#;
+
 /// K8s API wrappers for containers
 pub struct Containers {
     /// The name of the cluster this contianer will be spawned on
     pub cluster_name: String,
+    /// The image to use for network capture sidecars
+    pub capture_image: String,
+    /// The url our pods reach the Thorium API at
+    pub api_url: String,
 }
 
 impl Containers {
@@ -27,9 +59,13 @@ impl Containers {
     /// # Arguments
     ///
     /// * `cluster_name` - The name of this cluster
-    pub fn new<T: Into<String>>(cluster_name: T) -> Self {
+    /// * `capture_image` - The image to use for network capture sidecars
+    /// * `api_url` - The url our pods reach the Thorium API at
+    pub fn new<T: Into<String>>(cluster_name: T, capture_image: &str, api_url: &str) -> Self {
         Containers {
             cluster_name: cluster_name.into(),
+            capture_image: capture_image.to_owned(),
+            api_url: api_url.to_owned(),
         }
     }
     /// converts a resource request to a BTreeMap
@@ -103,6 +139,31 @@ impl Containers {
         }
     }
 
+    /// Builds the environment variables the agent uses to pull its requests to the
+    /// simulated internet
+    ///
+    /// # Arguments
+    ///
+    /// * `logs` - The url to pull simulated internet request logs from
+    pub fn simulated_internet_env(logs: &str) -> Vec<EnvVar> {
+        // the simulated internet logs requests by source ip so pass in our pods ip
+        let pod_ip = EnvVar {
+            name: "THORIUM_POD_IP".to_owned(),
+            value_from: Some(EnvVarSource {
+                field_ref: Some(ObjectFieldSelector {
+                    field_path: "status.podIP".to_owned(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        vec![
+            Self::build_env_var("THORIUM_SIMULATED_INTERNET_LOGS", &Some(logs.to_owned())),
+            pod_ip,
+        ]
+    }
+
     /// Builds a container soecific security context
     ///
     /// # Arguments
@@ -198,4 +259,31 @@ impl Containers {
         container.volume_mounts = Some(MountGen::generate(&image, &user)?);
         Ok(vec![container])
     }
+    /// Generate the sidecar that captures a pods network traffic
+    ///
+    /// This is an init container that is always restarted so k8s treats it as a
+    /// sidecar that shares our pods network namespace and is stopped once our
+    /// agent exits. Native sidecars require Kubernetes 1.29 or later.
+    pub fn generate_capture(&self) -> Result<Container, Error> {
+        // keep our agents requests to the Thorium API out of our captures
+        let filter = api_capture_filter(&self.api_url)?;
+        let raw = json!({
+            "name": "thorium-capture",
+            "image": &self.capture_image,
+            "command": ["sh", "-c", CAPTURE_SCRIPT],
+            "env": [{"name": "THORIUM_CAPTURE_FILTER", "value": filter}],
+            "restartPolicy": "Always",
+            "resources": {
+                "requests": {"cpu": "100m", "memory": "64Mi"},
+                "limits": {"cpu": "500m", "memory": "256Mi"}
+            },
+            "securityContext": {
+                "runAsUser": 0,
+                "runAsGroup": 0,
+                "capabilities": {"add": ["NET_RAW", "NET_ADMIN"]}
+            },
+            "volumeMounts": [{"name": "thorium-scratch", "mountPath": "/tmp"}],
+        });
+        Ok(serde_json::from_value(raw)?)
+    }
 }
@@ -1,12 +1,18 @@
 use futures::stream::{self, StreamExt};
 use hashbrown::HashMap;
 use k8s_openapi::api::networking::v1::{
-    NetworkPolicy, NetworkPolicyEgressRule, NetworkPolicyIngressRule, NetworkPolicySpec,
+    NetworkPolicy, NetworkPolicyEgressRule, NetworkPolicyIngressRule, NetworkPolicyPeer,
+    NetworkPolicySpec,
 };
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
 use kube::api::{Api, DeleteParams, ListParams, ObjectList, PostParams};
 use serde_json::json;
 use std::collections::HashSet;
-use thorium::{conf::BaseNetworkPolicy, models::NetworkPolicyRule};
+use thorium::{
+    conf::{BaseNetworkPolicy, SimulatedInternet},
+    models::NetworkPolicyRule,
+};
+use tracing::{event, Level};
 use uuid::Uuid;
 
 use crate::{
@@ -14,10 +20,64 @@ use crate::{
     raw_entry_vec_extend, same,
 };
 
+/// Convert a Thorium egress rule to a K8's egress rule, pointing any simulated
+/// internet rules at the simulated internet service
+///
+/// # Arguments
+///
+/// * `rule` - The Thorium egress rule to convert
+/// * `simulated_internet` - The simulated internet service to redirect to if one is configured
+fn egress_rule(
+    rule: NetworkPolicyRule,
+    simulated_internet: Option<&SimulatedInternet>,
+) -> Option<NetworkPolicyEgressRule> {
+    let simulated = rule.simulated_internet;
+    let mut k8s_rule: NetworkPolicyEgressRule = rule.into();
+    if simulated {
+        match simulated_internet {
+            // allow traffic to the simulated internet service's pods
+            Some(simulated_internet) => {
+                let peer = NetworkPolicyPeer {
+                    ip_block: None,
+                    namespace_selector: Some(LabelSelector {
+                        match_expressions: None,
+                        match_labels: Some(
+                            [(
+                                "kubernetes.io/metadata.name".to_string(),
+                                simulated_internet.namespace.clone(),
+                            )]
+                            .into_iter()
+                            .collect(),
+                        ),
+                    }),
+                    pod_selector: Some(LabelSelector {
+                        match_expressions: None,
+                        match_labels: Some(simulated_internet.pod_labels.clone()),
+                    }),
+                };
+                k8s_rule.to.get_or_insert_with(Vec::new).push(peer);
+            }
+            None => {
+                event!(
+                    Level::WARN,
+                    msg = "A network policy uses the simulated internet but none is configured"
+                );
+                // a rule without any peers allows everything so drop it instead
+                if k8s_rule.to.is_none() {
+                    return None;
+                }
+            }
+        }
+    }
+    Some(k8s_rule)
+}
+
 /// Wrapper for network policies api routes in k8s
 pub struct NetworkPolicies {
     /// Client to use for creating namespaced clients
     client: kube::Client,
+    /// The simulated internet service to redirect egress to if one is configured
+    pub simulated_internet: Option<SimulatedInternet>,
     /// A cache of information related to network policies that needs to be
     /// retained between runs
     pub cache: NetworkPolicyCache,
@@ -46,7 +106,13 @@ impl NetworkPolicies {
                 thorium::Error::new(format!("One or more of the base network policies in the Thorium config is invalid! {err}"))
             })?;
         let cache = NetworkPolicyCache::default().base_policy_specs(base_policy_specs);
-        Ok(Self { client, cache })
+        // get the simulated internet service to redirect to if one is configured
+        let simulated_internet = conf.thorium.scaler.k8s.simulated_internet.clone();
+        Ok(Self {
+            client,
+            simulated_internet,
+            cache,
+        })
     }
 
     /// List [`NetworkPolicy`]'s in a namespace in k8s
@@ -73,9 +139,11 @@ impl NetworkPolicies {
     ///
     /// * `thorium_policy` - The Thorium network policy to generate the spec from
     /// * `namespaces` - The namespace to generate specs for
+    /// * `simulated_internet` - The simulated internet service to redirect to if one is configured
     pub fn generate<I, T>(
         thorium_policy: thorium::models::NetworkPolicy,
         namespaces: I,
+        simulated_internet: Option<&SimulatedInternet>,
     ) -> Vec<(String, NetworkPolicy)>
     where
         I: IntoIterator<Item = T>,
@@ -123,9 +191,12 @@ impl NetworkPolicies {
         netpol_spec.ingress = thorium_policy
             .ingress
             .map(|mut ingress| ingress.drain(..).map(Into::into).collect());
-        netpol_spec.egress = thorium_policy
-            .egress
-            .map(|mut egress| egress.drain(..).map(Into::into).collect());
+        netpol_spec.egress = thorium_policy.egress.map(|mut egress| {
+            egress
+                .drain(..)
+                .filter_map(|rule| egress_rule(rule, simulated_internet))
+                .collect()
+        });
         // return a policy spec for each given namespace
         namespaces
             .into_iter()
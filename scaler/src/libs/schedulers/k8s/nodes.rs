@@ -2,7 +2,8 @@ use k8s_openapi::api::core::v1::Node;
 use kube::api::{Api, ListParams, ObjectList, Patch, PatchParams};
 use serde_json::json;
 use std::collections::HashSet;
-use thorium::models::Resources;
+use std::str::FromStr;
+use thorium::models::{CpuArch, Resources};
 use thorium::{Conf, Error};
 use tracing::{event, instrument, Level};
 
@@ -49,6 +50,31 @@ fn get_resources(node: &Node) -> Result<Resources, Error> {
     )))
 }
 
+/// Get the architecture a node runs natively and any it can emulate
+///
+/// Nodes that have qemu-user registered with `binfmt_misc` should be labeled with
+/// `thorium-emulate-<arch>=enabled` for each architecture they can emulate.
+///
+/// # Arguments
+///
+/// * `node` - The node to get architectures for
+fn get_arch(node: &Node) -> Option<(CpuArch, Vec<CpuArch>)> {
+    let labels = node.metadata.labels.as_ref()?;
+    // k8s labels every node with its native architecture
+    let arch = CpuArch::from_str(labels.get("kubernetes.io/arch")?).ok()?;
+    // get any architectures this node has been labeled as being able to emulate
+    let emulated = CpuArch::ALL
+        .into_iter()
+        .filter(|emulated| *emulated != arch)
+        .filter(|emulated| {
+            labels
+                .get(&format!("thorium-emulate-{emulated}"))
+                .is_some_and(|value| value == "enabled")
+        })
+        .collect();
+    Some((arch, emulated))
+}
+
 /// Wrapper for node api routes in k8s
 pub struct Nodes {
     /// API client for node commands in k8s
@@ -129,6 +155,11 @@ impl Nodes {
                 }
             }
         }
+        // get the architectures this node can run
+        let Some((arch, emulated)) = get_arch(&node) else {
+            event!(Level::WARN, node = &name, msg = "Unsupported architecture");
+            return Ok(None);
+        };
         // get the total available resources for this node
         let total = get_resources(&node)?;
         event!(
@@ -196,6 +227,8 @@ impl Nodes {
             available,
             total,
             active,
+            arch,
+            emulated,
         };
         Ok(Some(node_update))
     }
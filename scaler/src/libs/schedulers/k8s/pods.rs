@@ -1,11 +1,11 @@
 use futures::stream::{self, StreamExt};
 use hashbrown::HashMap;
-use k8s_openapi::api::core::v1::{Pod, PodSecurityContext, PodSpec};
+use k8s_openapi::api::core::v1::{Pod, PodDNSConfig, PodSecurityContext, PodSpec};
 use kube::api::{Api, DeleteParams, ListParams, ObjectList, PostParams};
 use reqwest::StatusCode;
 use serde_json::json;
 use std::collections::{BTreeMap, HashSet};
-use thorium::conf::K8sHostAliases;
+use thorium::conf::{K8sHostAliases, SimulatedInternet};
 use thorium::models::Image;
 use thorium::{Conf, Error};
 use tracing::{event, instrument, Level};
@@ -30,6 +30,10 @@ pub struct Pods {
     api: Api<Pod>,
     /// The host aliases to apply to pods in this cluster
     host_aliases: Vec<K8sHostAliases>,
+    /// How long preempted pods have to save a checkpoint and return their job in seconds
+    preemption_grace_period: u32,
+    /// The simulated internet service to redirect egress to if one is configured
+    simulated_internet: Option<SimulatedInternet>,
     /// The deletes that are still pending
     pub pending_deletes: HashMap<String, HashMap<String, Spawned>>,
     /// An intermediary vec to store pending deletes when checking them
@@ -58,13 +62,22 @@ impl Pods {
         // build volumes wrapper
         let volumes = Volumes::new(client, conf, context_name);
         // build the containers wrapper
-        let containers = Containers::new(cluster_name);
+        let containers = Containers::new(
+            cluster_name,
+            &conf.thorium.scaler.k8s.capture_image,
+            secrets.api_url(),
+        );
         // get our host aliases
         let host_aliases = conf.thorium.scaler.k8s.host_aliases(context_name);
         // clone our host aliases
         let host_aliases = host_aliases
             .map(|aliases| aliases.clone())
             .unwrap_or_default();
+        // get how long preempted pods have to shutdown
+        let preemption_grace_period =
+            u32::try_from(conf.thorium.scaler.k8s.preemption_grace_period).unwrap_or(u32::MAX);
+        // get the simulated internet service to redirect to if one is configured
+        let simulated_internet = conf.thorium.scaler.k8s.simulated_internet.clone();
         // get client for creating namespaced clients with
         let client = client.clone();
         Pods {
@@ -74,6 +87,8 @@ impl Pods {
             containers,
             api,
             host_aliases,
+            preemption_grace_period,
+            simulated_internet,
             pending_deletes: HashMap::default(),
             temp_deletes: Vec::default(),
         }
@@ -257,21 +272,23 @@ impl Pods {
         let api: Api<Pod> = Api::namespaced(self.client.clone(), ns);
         // build delete params
         let params = DeleteParams::default().grace_period(0);
+        // give preempted pods time to save a checkpoint and return their job
+        let preempt_params = DeleteParams::default().grace_period(self.preemption_grace_period);
         // build a list to store our cloned names
         let mut cloned_names = Vec::with_capacity(spawns.len());
         // clone our names to work around lifetime issues
         for spawn in spawns {
             // log the pod we are deleting
-            event!(Level::INFO, pod = spawn.name);
+            event!(Level::INFO, pod = spawn.name, preempted = spawn.preempted);
             // add this pods name
-            cloned_names.push(spawn.name.clone());
+            cloned_names.push((spawn.name.clone(), spawn.preempted));
         }
         // delete pods 10 at a time
         let deletes = stream::iter(cloned_names)
-            .map(|name| {
+            .map(|(name, preempted)| {
                 // get references to minimize cloning
                 let api_ref = &api;
-                let params_ref = &params;
+                let params_ref = if preempted { &preempt_params } else { &params };
                 async move { api_ref.delete(&name, params_ref).await }
             })
             .buffered(5)
@@ -516,18 +533,20 @@ impl Pods {
         for policy in cache.conf_base_network_policies() {
             pod_labels.insert(policy.name.clone(), "base".to_string());
         }
+        // track whether any of our policies redirect us to the simulated internet
+        let mut simulated = false;
         // add any forced network policies in this group
         if let Some(forced_policies) = cache.forced_network_policies(&image.group)? {
             for policy in forced_policies {
                 pod_labels.insert(policy.k8s_name.clone(), "true".to_string());
+                simulated |= policy.simulated_internet();
             }
         }
         // add any user-defined network policies
         for policy_name in &image.network_policies {
-            let policy_k8s_name = &cache
-                .get_network_policy(&image.group, policy_name)?
-                .k8s_name;
-            pod_labels.insert(policy_k8s_name.clone(), "true".to_string());
+            let policy = cache.get_network_policy(&image.group, policy_name)?;
+            pod_labels.insert(policy.k8s_name.clone(), "true".to_string());
+            simulated |= policy.simulated_internet();
         }
         // get this pods specs or build defaults
         let pod_spec = pod.spec.get_or_insert(PodSpec::default());
@@ -537,6 +556,30 @@ impl Pods {
         pod_spec.termination_grace_period_seconds = Some(1);
         pod_spec.restart_policy = Some("Never".to_owned());
         pod_spec.security_context = Some(Self::build_security_ctx(cache, &spawn.req.user, image));
+        // add a sidecar to capture this pods network traffic if its requested
+        if image.capture_network {
+            pod_spec
+                .init_containers
+                .get_or_insert_with(Vec::new)
+                .push(self.containers.generate_capture()?);
+        }
+        // sinkhole this pods DNS if its redirected to the simulated internet
+        if let (true, Some(simulated_internet)) = (simulated, &self.simulated_internet) {
+            pod_spec.dns_policy = Some("None".to_owned());
+            pod_spec.dns_config = Some(PodDNSConfig {
+                nameservers: Some(vec![simulated_internet.dns.clone()]),
+                ..Default::default()
+            });
+            // tell the agent where to pull this pods simulated internet requests from
+            if let Some(logs) = &simulated_internet.logs {
+                for container in &mut pod_spec.containers {
+                    container
+                        .env
+                        .get_or_insert_with(Vec::new)
+                        .extend(Containers::simulated_internet_env(logs));
+                }
+            }
+        }
         Ok(pod)
     }
 
@@ -113,6 +113,11 @@ impl Secrets {
         self.api.list(&params).await
     }
 
+    /// Get the url our pods reach the Thorium API at
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
     /// Build Thorium registry token reference
     pub fn registry_token(&self) -> Vec<LocalObjectReference> {
         if self.registry_token.is_some() {
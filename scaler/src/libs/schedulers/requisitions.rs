@@ -2,7 +2,7 @@ use chrono::prelude::*;
 use chrono::{Duration, Utc};
 use hashbrown::HashMap;
 use std::collections::BTreeMap;
-use thorium::models::{Image, Pools, Requisition, Resources, SpawnedUpdate, Worker};
+use thorium::models::{Image, Pools, PriorityClass, Requisition, Resources, SpawnedUpdate, Worker};
 use thorium::same;
 
 use crate::libs::helpers;
@@ -28,6 +28,10 @@ pub struct Spawned {
     pub scaled_down: bool,
     /// When this resource can be scaled down to prevent flapping
     pub down_scalable: DateTime<Utc>,
+    /// Whether this resource can be preempted by urgent work
+    pub preemptible: bool,
+    /// Whether this resource is being preempted and should be given time to checkpoint
+    pub preempted: bool,
 }
 
 impl Spawned {
@@ -40,12 +44,14 @@ impl Spawned {
     /// * `req` - The requisition that led to this worker
     /// * `image` - The image for this worker
     /// * `pool` - The pool this worker was spawned in
+    /// * `priority` - The priority class of the reaction this worker was spawned for
     pub fn new<T: Into<String>>(
         cluster: T,
         node: T,
         req: Requisition,
         image: &Image,
         pool: Pools,
+        priority: PriorityClass,
     ) -> Self {
         // generate a random name
         let append = helpers::gen_string(8);
@@ -54,6 +60,8 @@ impl Spawned {
         let single_run_budget = 3.0 * image.runtime + (3.0 * image.runtime * 0.25);
         // calculate when this worker can be safely scaled down
         let down_scalable = Utc::now() + Duration::seconds(single_run_budget.ceil() as i64);
+        // a reactions priority class overrides its images
+        let priority = image.priority.overridden_by(priority);
         // create our spawned object
         Spawned {
             req,
@@ -65,6 +73,8 @@ impl Spawned {
             spawn: true,
             scaled_down: false,
             down_scalable,
+            preemptible: priority == PriorityClass::Preemptible,
+            preempted: false,
         }
     }
 }
@@ -36,6 +36,10 @@ pub enum Tasks {
     Cleanup,
     /// Decrease any users fair share ranks
     DecreaseFairShare,
+    /// Provision or retire nodes based on unmet demand
+    Provision,
+    /// Fetch any mirrored repos that are due and ingest their new commits
+    RepoMirrors,
 }
 
 impl Tasks {
@@ -53,7 +57,12 @@ impl Tasks {
         queue.insert(from_now!(55), Self::CacheReload);
         queue.insert(from_now!(57), Self::UpdateRuntimes);
         queue.insert(from_now!(63), Self::Resources);
+        queue.insert(from_now!(83), Self::RepoMirrors);
         queue.insert(from_now!(600), Self::DecreaseFairShare);
+        // only add node provisioning if a provisioner is configured
+        if conf.thorium.scaler.provisioner.is_some() {
+            queue.insert(from_now!(71), Self::Provision);
+        }
         queue
     }
 
@@ -67,6 +76,8 @@ impl Tasks {
             Tasks::UpdateRuntimes => conf.thorium.scaler.tasks.image_runtimes,
             Tasks::Cleanup => conf.thorium.scaler.tasks.cleanup,
             Tasks::DecreaseFairShare => conf.thorium.scaler.tasks.decrease_fair_share,
+            Tasks::Provision => conf.thorium.scaler.tasks.provision,
+            Tasks::RepoMirrors => conf.thorium.scaler.tasks.repo_mirrors,
         }
     }
 
@@ -81,6 +92,8 @@ impl Tasks {
             Tasks::UpdateRuntimes => "UpdateRuntimes",
             Tasks::Cleanup => "Cleanup",
             Tasks::DecreaseFairShare => "DecreaseFairShare",
+            Tasks::Provision => "Provision",
+            Tasks::RepoMirrors => "RepoMirrors",
         }
     }
 }
@@ -95,6 +108,8 @@ pub enum TaskResult {
     Cache(Cache),
     /// Calculate the average runtimes of all images and update it
     UpdateRuntimes,
+    /// Fetch any mirrored repos that are due and ingest their new commits
+    RepoMirrors,
 }
 
 impl TaskResult {
@@ -106,6 +121,7 @@ impl TaskResult {
             TaskResult::LdapSync => "LdapSync",
             TaskResult::Cache(_) => "CacheReload",
             TaskResult::UpdateRuntimes => "UpdateRuntimes",
+            TaskResult::RepoMirrors => "RepoMirrors",
         }
     }
 }
@@ -55,6 +55,12 @@ pub enum SubCommands {
     /// Censuse commands in Thorium
     #[clap(subcommand)]
     Census(CensusSubCommands),
+    /// Migrate the Scylla schema for a Thorium cluster
+    #[clap(subcommand)]
+    Migrate(MigrateSubCommands),
+    /// Manage existing tags in Thorium
+    #[clap(subcommand)]
+    Tags(TagsSubCommands),
 }
 
 /// The backup specific subcommands
@@ -257,6 +263,12 @@ pub struct ProvisionNode {
     /// Path to API keys file
     #[clap(short, long)]
     pub keys: String,
+    /// The cluster to join this baremetal node to
+    #[clap(long)]
+    pub cluster: Option<String>,
+    /// The name to register this baremetal node as (defaults to this nodes hostname)
+    #[clap(long)]
+    pub name: Option<String>,
 }
 
 /// The census specific subcommands
@@ -310,3 +322,41 @@ pub struct NewCensus {
     #[clap(short, long)]
     pub dry_run: bool,
 }
+
+/// The schema migration specific subcommands
+#[derive(Parser, Debug, Clone)]
+pub enum MigrateSubCommands {
+    /// Print the current schema version and any pending migrations
+    #[clap(version, author)]
+    Status,
+    /// Apply any pending migrations
+    #[clap(version, author)]
+    Apply(ApplyMigrations),
+}
+
+/// Apply pending schema migrations
+#[derive(Parser, Debug, Clone)]
+pub struct ApplyMigrations {
+    /// Print the statements that would be run without changing the schema
+    #[clap(short, long)]
+    pub dry_run: bool,
+}
+
+/// The tag specific subcommands
+#[derive(Parser, Debug, Clone)]
+pub enum TagsSubCommands {
+    /// Rewrite existing tags to the canonical forms in their groups tag vocabulary
+    #[clap(version, author)]
+    Normalize(NormalizeTags),
+}
+
+/// Rewrite existing tags to their canonical forms
+#[derive(Parser, Debug, Clone)]
+pub struct NormalizeTags {
+    /// The groups to normalize tags in (defaults to all groups with a tag vocabulary)
+    #[clap(short, long, value_delimiter = ',')]
+    pub groups: Vec<String>,
+    /// Only print the tags that would be rewritten
+    #[clap(short, long)]
+    pub dry_run: bool,
+}
@@ -40,6 +40,78 @@ pub struct Comment {
     pub comment: String,
     /// Any paths in s3 to files/attachements for this comment
     pub files: String,
+    /// The comment this comment is a reply to
+    pub parent: Option<Uuid>,
+    /// When this comment was last edited
+    pub edited: Option<DateTime<Utc>>,
+    /// The previous revisions of this comment
+    pub revisions: Option<String>,
+    /// The reaction and username for each reaction left on this comment
+    pub reactions: Option<Vec<(String, String)>>,
+}
+
+/// A comment from a backup taken before comments could be threaded, edited, or reacted to
+#[derive(Debug, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(Debug, CheckBytes))]
+pub struct LegacyComment {
+    /// The group to share this comment with
+    pub group: String,
+    /// The sha256 this comment was for
+    pub sha256: String,
+    /// When this comment was uploaded
+    pub uploaded: DateTime<Utc>,
+    /// The uuid for this comment
+    pub id: Uuid,
+    /// The author for this comment
+    pub author: String,
+    /// The comment for this file
+    pub comment: String,
+    /// Any paths in s3 to files/attachements for this comment
+    pub files: String,
+}
+
+impl From<LegacyComment> for Comment {
+    /// Upgrade a legacy comment to the current comment layout
+    ///
+    /// # Arguments
+    ///
+    /// * `legacy` - The legacy comment to upgrade
+    fn from(legacy: LegacyComment) -> Self {
+        Comment {
+            group: legacy.group,
+            sha256: legacy.sha256,
+            uploaded: legacy.uploaded,
+            id: legacy.id,
+            author: legacy.author,
+            comment: legacy.comment,
+            files: legacy.files,
+            parent: None,
+            edited: None,
+            revisions: None,
+            reactions: None,
+        }
+    }
+}
+
+impl Comment {
+    /// Load the comments from an archived partition
+    ///
+    /// Partitions from backups taken before comments could be threaded, edited, or reacted to
+    /// are upgraded to the current layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The archived partition to load
+    fn load(buffer: &[u8]) -> Result<Vec<Comment>, Error> {
+        // try to cast our buffer to the current archived type
+        if let Ok(rows) = rkyv::check_archived_root::<Vec<Comment>>(buffer) {
+            return Ok(rows.deserialize(&mut rkyv::Infallible)?);
+        }
+        // fall back to the layout used before comments could be threaded
+        let rows = rkyv::check_archived_root::<Vec<LegacyComment>>(buffer)?;
+        let rows: Vec<LegacyComment> = rows.deserialize(&mut rkyv::Infallible)?;
+        Ok(rows.into_iter().map(Comment::from).collect())
+    }
 }
 
 impl Utils for Comment {
@@ -69,7 +141,8 @@ impl Backup for Comment {
         // build logs get prepared statement
         scylla
             .prepare(format!(
-                "SELECT group, sha256, uploaded, id, author, comment, files \
+                "SELECT group, sha256, uploaded, id, author, comment, files, parent, edited, \
+                revisions, reactions \
                 FROM {}.{} \
                 Where token(group, sha256) >= ? AND token(group, sha256) <= ?",
                 ns,
@@ -116,8 +189,9 @@ impl Restore for Comment {
         scylla
             .prepare(format!(
                 "INSERT INTO {}.{} \
-                (group, sha256, uploaded, id, author, comment, files) \
-                VALUES (?, ?, ?, ?, ?, ?, ?)",
+                (group, sha256, uploaded, id, author, comment, files, parent, edited, \
+                revisions, reactions) \
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 ns,
                 Self::name(),
             ))
@@ -151,25 +225,27 @@ impl Restore for Comment {
         progress: &mut ProgressBar,
         prepared: &PreparedStatement,
     ) -> Result<(), Error> {
-        // cast our buffer to its archived type
-        let rows = rkyv::check_archived_root::<Vec<Comment>>(buffer)?;
+        // load the comments in this partition
+        let rows = Comment::load(buffer)?;
         // build a set of futures
         let mut futures = FuturesUnordered::new();
         // build our queries to insert this partitions rows
         for row in rows.iter() {
-            // deserialize this rows uploaded timestamp
-            let uploaded: DateTime<Utc> = row.uploaded.deserialize(&mut rkyv::Infallible)?;
             // restore this row to scylla
             let query = scylla.execute_unpaged(
                 prepared,
                 (
                     row.group.as_str(),
                     row.sha256.as_str(),
-                    uploaded,
+                    row.uploaded,
                     row.id,
                     row.author.as_str(),
                     row.comment.as_str(),
                     row.files.as_str(),
+                    row.parent,
+                    row.edited,
+                    row.revisions.as_deref(),
+                    &row.reactions,
                 ),
             );
             // add this to our futures
@@ -225,8 +301,8 @@ impl S3Backup for Comment {
         chars: usize,
         buffer: &[u8],
     ) -> Result<Vec<(String, String, PathBuf)>, Error> {
-        // cast our buffer to its archived type
-        let rows = rkyv::check_archived_root::<Vec<Comment>>(buffer)?;
+        // load the comments in this partition
+        let rows = Comment::load(buffer)?;
         // start with an empty list since most comments probably won't have attachments
         let mut downloads = Vec::default();
         // crawl our comments and build a list of attachments to write off to disk
@@ -244,7 +320,7 @@ impl S3Backup for Comment {
                 // get the sub folder for this key
                 path.push(&row.sha256[..chars]);
                 // add the rest of the key to this path
-                path.push(row.sha256.as_str());
+                path.push(&row.sha256);
                 path.push(row.id.to_string());
                 path.push(id.to_string());
                 // add this attachment to our download list
@@ -22,6 +22,8 @@ pub enum Error {
     ScyllaPagedQuery(scylla::errors::PagerExecutionError),
     /// A Scylla next row error occured
     ScyllaNextRow(scylla::client::pager::NextRowError),
+    /// A Scylla schema migration error occured
+    ScyllaMigration(thorium::models::MigrationError),
     /// A Redis error
     Redis(redis::RedisError),
     /// A tokio join error
@@ -80,6 +82,7 @@ impl std::fmt::Display for Error {
             Error::ScyllaQuery(err) => write!(f, "ScyllaQuery Error: {err}"),
             Error::ScyllaPagedQuery(err) => write!(f, "ScyllaPagedQuery Error: {err}"),
             Error::ScyllaNextRow(err) => write!(f, "ScyllaNextRow Error: {err}"),
+            Error::ScyllaMigration(err) => write!(f, "ScyllaMigration Error: {err}"),
             Error::Redis(err) => write!(f, "Redis Error: {err}"),
             Error::TokioJoin(err) => write!(f, "TokioJoin Error: {err}"),
             Error::KanalSend(err) => write!(f, "KanalSend Error: {err}"),
@@ -143,6 +146,12 @@ impl From<scylla::client::pager::NextRowError> for Error {
     }
 }
 
+impl From<thorium::models::MigrationError> for Error {
+    fn from(error: thorium::models::MigrationError) -> Self {
+        Error::ScyllaMigration(error)
+    }
+}
+
 impl From<redis::RedisError> for Error {
     fn from(error: redis::RedisError) -> Self {
         Error::Redis(error)
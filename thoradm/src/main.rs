@@ -6,9 +6,11 @@ mod args;
 mod backup;
 mod census;
 mod error;
+mod migrate;
 mod provision;
 mod settings;
 mod shared;
+mod tags;
 
 use clap::Parser;
 
@@ -24,6 +26,8 @@ async fn main() {
         args::SubCommands::Settings(settings_cmd) => settings::handle(settings_cmd, &args).await,
         args::SubCommands::Provision(provision_args) => provision::handle(provision_args).await,
         args::SubCommands::Census(census_cmd) => census::handle(census_cmd, &args).await,
+        args::SubCommands::Migrate(migrate_cmd) => migrate::handle(migrate_cmd, &args).await,
+        args::SubCommands::Tags(tags_cmd) => tags::handle(tags_cmd, &args).await,
     } {
         eprintln!("{err}");
         // TODO: return the proper exit code based on the error
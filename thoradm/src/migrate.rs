@@ -0,0 +1,110 @@
+//! Migrate the Scylla schema for a Thorium cluster
+
+use scylla::client::session::Session;
+use thorium::models::migrations;
+use thorium::Conf;
+
+use crate::args::{ApplyMigrations, Args, MigrateSubCommands};
+use crate::shared;
+use crate::Error;
+
+/// Get the version a keyspace's schema is currently at
+///
+/// Keyspaces that existed before schema versions were tracked are at version 0.
+///
+/// # Arguments
+///
+/// * `scylla` - The scylla client to use
+/// * `ns` - The keyspace to get the schema version for
+async fn current_version(scylla: &Session, ns: &str) -> Result<i32, Error> {
+    let row = migrations::get_schema_version(scylla, ns).await?;
+    Ok(row.map_or(0, |row| row.version))
+}
+
+/// Print the current schema version and any pending migrations
+///
+/// # Arguments
+///
+/// * `scylla` - The scylla client to use
+/// * `ns` - The keyspace to print the status of
+async fn status(scylla: &Session, ns: &str) -> Result<(), Error> {
+    // the API will create a fresh keyspace at the latest schema
+    if migrations::is_fresh(scylla, ns).await? {
+        println!("{ns} has no tables yet and will be created at the latest schema");
+        return Ok(());
+    }
+    let current = current_version(scylla, ns).await?;
+    println!(
+        "{ns} is at schema version {current} (latest is {})",
+        migrations::latest_version()
+    );
+    // list any migrations that still need to be applied
+    for migration in migrations::pending(current) {
+        println!("pending: {} {}", migration.version, migration.name);
+    }
+    Ok(())
+}
+
+/// Apply any pending migrations in order
+///
+/// # Arguments
+///
+/// * `cmd` - The apply command to execute
+/// * `scylla` - The scylla client to use
+/// * `ns` - The keyspace to migrate
+async fn apply(cmd: &ApplyMigrations, scylla: &Session, ns: &str) -> Result<(), Error> {
+    // the API will create a fresh keyspace at the latest schema so there is nothing to migrate
+    if migrations::is_fresh(scylla, ns).await? {
+        println!("{ns} has no tables yet, skipping migrations");
+        return Ok(());
+    }
+    let current = current_version(scylla, ns).await?;
+    let pending = migrations::pending(current);
+    if pending.is_empty() {
+        println!("{ns} is already at schema version {current}");
+        return Ok(());
+    }
+    // make sure we have somewhere to record our migrations
+    if !cmd.dry_run {
+        migrations::setup_schema_version_table(scylla, ns).await?;
+    }
+    for migration in pending {
+        println!("Migration {}: {}", migration.version, migration.name);
+        // print the statements this migration will run
+        for statement in migration.statements(ns) {
+            println!("  {statement};");
+        }
+        // apply this migration unless this is a dry run
+        if !cmd.dry_run {
+            migrations::apply(scylla, ns, migration).await?;
+            println!("  applied");
+        }
+    }
+    if cmd.dry_run {
+        println!("Dry run complete, no changes were made");
+    } else {
+        println!(
+            "{ns} is now at schema version {}",
+            migrations::latest_version()
+        );
+    }
+    Ok(())
+}
+
+/// Handle the migrate command
+///
+/// # Arguments
+///
+/// * `cmd` - The migrate subcommand
+/// * `args` - The Thoradm args
+pub async fn handle(cmd: &MigrateSubCommands, args: &Args) -> Result<(), Error> {
+    // load our config
+    let config = Conf::new(&args.cluster_conf)?;
+    // get a scylla client
+    let scylla = shared::scylla::get_client(&config).await?;
+    let ns = &config.thorium.namespace;
+    match cmd {
+        MigrateSubCommands::Status => status(&scylla, ns).await,
+        MigrateSubCommands::Apply(apply_cmd) => apply(apply_cmd, &scylla, ns).await,
+    }
+}
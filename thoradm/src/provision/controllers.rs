@@ -8,7 +8,9 @@ pub async fn handle(cmd: &ProvisionSubCommands) -> Result<(), Error> {
         ProvisionSubCommands::Node(node_args) => {
             // provision k8s servers by default
             if node_args.baremetal == true {
-                println!("Provisioning baremetal servers not yet supported");
+                // setup the agent and join this node to its cluster
+                provision::nodes::conf_thorium_dir(&node_args.keys).await?;
+                provision::nodes::register_node(node_args).await?;
             } else {
                 provision::nodes::conf_thorium_dir(&node_args.keys).await?;
             }
@@ -1,13 +1,19 @@
 use std::fs::Permissions;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
-use thorium::{models::Component, Error, Thorium};
+use thorium::{
+    models::{Component, CpuArch, NodeRegistration, Resources},
+    Error, Thorium,
+};
 use tokio::fs;
 
+use crate::args::ProvisionNode;
+
 const THORIUM_PATH: &str = "/opt/thorium";
 const AGENT_PATH: &str = "/opt/thorium/thorium-agent";
 const TRACING_MOUNT_PATH: &str = "/tmp/tracing.yml";
 const TRACING_PATH: &str = "/opt/thorium/tracing.yml";
+const HOSTNAME_PATH: &str = "/proc/sys/kernel/hostname";
 
 /// configure a thorium agent directory
 pub async fn conf_thorium_dir(keys: &String) -> Result<(), Error> {
@@ -49,3 +55,31 @@ pub async fn conf_thorium_dir(keys: &String) -> Result<(), Error> {
     fs::set_permissions(TRACING_PATH, Permissions::from_mode(0o644)).await?;
     Ok(())
 }
+
+/// Register a baremetal node with Thorium so the scaler can schedule onto it
+///
+/// The reactor on this node reports its real resources on its first heartbeat.
+pub async fn register_node(node_args: &ProvisionNode) -> Result<(), Error> {
+    // get the cluster to join this node to
+    let cluster = match &node_args.cluster {
+        Some(cluster) => cluster,
+        None => return Err(Error::new("Baremetal nodes require a cluster to join")),
+    };
+    // use our hostname if no name was set
+    let name = match &node_args.name {
+        Some(name) => name.clone(),
+        None => fs::read_to_string(HOSTNAME_PATH).await?.trim().to_owned(),
+    };
+    // build our node registration
+    let mut node = NodeRegistration::new(cluster, &name, Resources::default());
+    // set our architecture if we know it
+    if let Some(arch) = CpuArch::host() {
+        node = node.arch(arch);
+    }
+    println!("Registering {} in cluster {}", name, cluster);
+    // build Thorium client from keys secret
+    let thorium = Thorium::from_key_file(&node_args.keys).await?;
+    // register this node
+    thorium.system.register_node(&node).await?;
+    Ok(())
+}
@@ -0,0 +1,350 @@
+//! Tag commands in thoradm
+
+use futures::StreamExt;
+use indicatif::{ProgressBar, ProgressStyle};
+use kanal::{AsyncReceiver, AsyncSender};
+use scylla::client::session::Session;
+use scylla::statement::prepared::PreparedStatement;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thorium::models::{
+    Repo, Sample, TagDeleteRequest, TagProvenanceRequest, TagRequest, TagType, TagVocabulary,
+};
+use thorium::{Conf, Thorium};
+
+use crate::args::{Args, NormalizeTags, TagsSubCommands};
+use crate::shared::monitor::MonitorUpdate;
+use crate::shared::scylla::{ScyllaCrawlController, ScyllaCrawlSupport};
+use crate::Error;
+
+/// The columns we get when crawling tags in scylla
+type TagColumns = (TagType, String, String, String, String);
+
+/// The arguments shared by all tag normalization workers
+#[derive(Clone)]
+pub struct NormalizeArgs {
+    /// The Thorium client to rewrite tags with
+    thorium: Thorium,
+    /// The tag vocabulary for each group we are normalizing
+    vocabularies: Arc<HashMap<String, TagVocabulary>>,
+}
+
+pub struct NormalizeWorker {
+    /// The scylla client to talk to Scylla with
+    scylla: Arc<Session>,
+    /// The prepared statement to use
+    prepared: PreparedStatement,
+    /// The arguments shared by all workers
+    args: NormalizeArgs,
+    /// The kanal channel workers should send updates over
+    updates: AsyncSender<MonitorUpdate>,
+    /// The current number of tags this worker has rewritten
+    rewritten: u64,
+    /// The progress bar to write messages with
+    progress: ProgressBar,
+    /// Whether to actually rewrite tags or not
+    dry_run: bool,
+}
+
+impl NormalizeWorker {
+    /// Rewrite a single tag to its canonical form
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of item this tag is on
+    /// * `group` - The group this tag is in
+    /// * `item` - The item this tag is on
+    /// * `old` - The current key/value for this tag
+    /// * `new` - The canonical key/value for this tag
+    async fn rewrite(
+        &self,
+        kind: TagType,
+        group: &str,
+        item: &str,
+        old: (&str, &str),
+        new: (&str, &str),
+    ) -> Result<(), Error> {
+        // add the canonical tag before removing the old one so no tags are lost
+        match kind {
+            TagType::Files => {
+                let add = TagRequest::<Sample>::default()
+                    .group(group)
+                    .add(new.0, new.1)
+                    .provenance(TagProvenanceRequest::default().keep(old.0, old.1));
+                self.args.thorium.files.tag(item, &add).await?;
+                let del = TagDeleteRequest::<Sample>::default()
+                    .group(group)
+                    .add(old.0, old.1);
+                self.args.thorium.files.delete_tags(item, &del).await?;
+            }
+            TagType::Repos => {
+                let add = TagRequest::<Repo>::default()
+                    .group(group)
+                    .add(new.0, new.1)
+                    .provenance(TagProvenanceRequest::default().keep(old.0, old.1));
+                self.args.thorium.repos.tag(item, &add).await?;
+                let del = TagDeleteRequest::<Repo>::default()
+                    .group(group)
+                    .add(old.0, old.1);
+                self.args.thorium.repos.delete_tags(item, &del).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start crawling tags and rewriting any that are not canonical
+    ///
+    /// # Arguments
+    ///
+    /// * `orders` - The channel to receive token ranges on
+    pub async fn start(mut self, orders: AsyncReceiver<(i64, i64)>) -> Result<Self, Error> {
+        // handle messages in our channel until its closed
+        loop {
+            // get the next message in the queue
+            let (start, end) = match orders.recv().await {
+                Ok(range) => range,
+                Err(kanal::ReceiveError::Closed) => break,
+                Err(kanal::ReceiveError::SendClosed) => break,
+            };
+            // build and execute our paged query
+            let rows_stream = self
+                .scylla
+                .execute_iter(self.prepared.clone(), &(start, end))
+                .await?;
+            // build a typed iter for these rows
+            let mut typed_stream = match rows_stream.rows_stream::<TagColumns>() {
+                Ok(typed_stream) => typed_stream,
+                Err(error) => {
+                    // log that we failed to cast these rows
+                    let msg = format!("Failed to set type for row stream: with {error:#?}");
+                    self.progress.println(msg);
+                    continue;
+                }
+            };
+            // track how many tags we rewrote before we handle this stream
+            let old_rewritten = self.rewritten;
+            // crawl over our typed stream
+            while let Some(typed_row) = typed_stream.next().await {
+                let (kind, group, item, key, value) = match typed_row {
+                    Ok(typed_row) => typed_row,
+                    Err(error) => {
+                        // log that we failed to cast this row
+                        self.progress
+                            .println(format!("Tag crawl failure: with {error:#?}"));
+                        continue;
+                    }
+                };
+                // skip any groups we aren't normalizing
+                let Some(vocabulary) = self.args.vocabularies.get(&group) else {
+                    continue;
+                };
+                // skip any tags that are already canonical
+                let (new_key, new_value) = vocabulary.normalize(&key, &value);
+                if new_key == key && new_value == value {
+                    continue;
+                }
+                // build the message for this rewrite
+                let msg =
+                    format!("{kind} {item} in {group}: {key}={value} -> {new_key}={new_value}");
+                if self.dry_run {
+                    self.progress.println(format!("Would rewrite {msg}"));
+                } else {
+                    let old = (key.as_str(), value.as_str());
+                    let new = (new_key.as_str(), new_value.as_str());
+                    if let Err(error) = self.rewrite(kind, &group, &item, old, new).await {
+                        self.progress
+                            .println(format!("Failed to rewrite {msg}: {error}"));
+                        continue;
+                    }
+                }
+                self.rewritten += 1;
+            }
+            // set our current rewritten progress message
+            self.progress.set_message(self.rewritten.to_string());
+            // calculate how many tags we rewrote in this loop
+            let items = (self.rewritten - old_rewritten) as usize;
+            // send an update to our controller
+            self.updates
+                .send(MonitorUpdate::Update { items, bytes: 0 })
+                .await?;
+        }
+        Ok(self)
+    }
+}
+
+impl ScyllaCrawlSupport for NormalizeWorker {
+    /// The arguments to specify when creating workers
+    type WorkerArgs = NormalizeArgs;
+
+    /// Set the progress bars style for workers
+    fn bar_style() -> Result<ProgressStyle, Error> {
+        // build the style for our progress bar
+        let bar_style = ProgressStyle::with_template("{spinner:.green} Normalized Tags: {msg}")
+            .unwrap()
+            .tick_strings(&[
+                "🦀🏷️     📦",
+                " 🦀🏷️    📦",
+                "  🦀🏷️   📦",
+                "   🦀🏷️  📦",
+                "    🦀🏷️ 📦",
+                "     🦀🏷️📦",
+                "       🦀📦",
+                "      🦀 📦",
+                "     🦀  📦",
+                "    🦀   📦",
+                "   🦀    📦",
+                "  🦀     📦",
+                " 🦀      📦",
+                "🦀       📦",
+            ]);
+        Ok(bar_style)
+    }
+
+    /// Set the progress bar style for this controllers monitor
+    fn monitor_bar_style() -> Result<ProgressStyle, Error> {
+        // build the style for our progress bar
+        let bar_style = ProgressStyle::with_template(
+            "{spinner:.green} {elapsed_precise} Total Normalized Tags: {msg}",
+        )
+        .unwrap()
+        .tick_strings(&[
+            "🦀🏷️       ",
+            " 🦀🏷️      ",
+            "  🦀🏷️     ",
+            "   🦀🏷️    ",
+            "    🦀🏷️   ",
+            "     🦀🏷️  ",
+            "       🦀🏷️",
+            "      🦀🏷️ ",
+            "     🦀🏷️  ",
+            "    🦀🏷️   ",
+            "   🦀🏷️    ",
+            "  🦀🏷️     ",
+            " 🦀🏷️      ",
+            "🦀🏷️       ",
+        ]);
+        Ok(bar_style)
+    }
+
+    /// Build a single worker for this controller
+    async fn build_worker(
+        scylla: &Arc<Session>,
+        namespace: &str,
+        updates: AsyncSender<MonitorUpdate>,
+        args: &Self::WorkerArgs,
+        dry_run: bool,
+        bar: ProgressBar,
+    ) -> Result<Self, Error> {
+        // build the prepared statement to crawl all tags with
+        let prepared = scylla
+            .prepare(format!(
+                "SELECT type, group, item, key, value \
+                FROM {namespace}.tags \
+                WHERE token(type, group, year, bucket, key, value) >= ? \
+                AND token(type, group, year, bucket, key, value) <= ?"
+            ))
+            .await?;
+        // build our worker
+        let worker = NormalizeWorker {
+            scylla: scylla.clone(),
+            prepared,
+            args: args.clone(),
+            updates,
+            rewritten: 0,
+            progress: bar,
+            dry_run,
+        };
+        Ok(worker)
+    }
+
+    /// Start crawling data in scylla
+    async fn start(self, rx: AsyncReceiver<(i64, i64)>) -> Result<Self, Error> {
+        self.start(rx).await
+    }
+
+    /// Shutdown this worker
+    fn shutdown(self) {}
+}
+
+/// Get the tag vocabularies for the groups we are normalizing
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `groups` - The groups to get vocabularies for (all groups if empty)
+async fn get_vocabularies(
+    thorium: &Thorium,
+    groups: &[String],
+) -> Result<HashMap<String, TagVocabulary>, Error> {
+    let mut vocabularies = HashMap::default();
+    if groups.is_empty() {
+        // get the details for all groups
+        let mut cursor = thorium.groups.list().details().limit(u64::MAX);
+        loop {
+            cursor.next().await?;
+            for group in cursor.details.drain(..) {
+                vocabularies.insert(group.name, group.tag_vocabulary);
+            }
+            if cursor.exhausted {
+                break;
+            }
+        }
+    } else {
+        // get the details for just the requested groups
+        for name in groups {
+            let group = thorium.groups.get(name).await?;
+            vocabularies.insert(group.name, group.tag_vocabulary);
+        }
+    }
+    // skip any groups that don't have a vocabulary
+    vocabularies.retain(|_, vocabulary| !vocabulary.is_empty());
+    Ok(vocabularies)
+}
+
+/// Rewrite existing tags to their canonical forms
+///
+/// # Arguments
+///
+/// * `cmd` - The normalize command to run
+/// * `args` - The command line args for thoradm
+async fn normalize(cmd: &NormalizeTags, args: &Args) -> Result<(), Error> {
+    // load our config
+    let config = Conf::new(&args.cluster_conf)?;
+    // build a Thorium client to rewrite tags through
+    let thorium = Thorium::from_ctl_conf_file(&args.ctl_conf).await?;
+    // get the vocabularies for the groups we are normalizing
+    let vocabularies = get_vocabularies(&thorium, &cmd.groups).await?;
+    if vocabularies.is_empty() {
+        println!("No groups with a tag vocabulary to normalize tags in");
+        return Ok(());
+    }
+    // build a new scylla client
+    let scylla = Arc::new(crate::shared::scylla::get_client(&config).await?);
+    // build the arguments for our workers
+    let worker_args = NormalizeArgs {
+        thorium,
+        vocabularies: Arc::new(vocabularies),
+    };
+    // build a new tag normalize controller
+    let mut controller = ScyllaCrawlController::<NormalizeWorker>::new(
+        &config.thorium.namespace,
+        &scylla,
+        worker_args,
+        args.workers,
+    )?;
+    // start normalizing tags
+    controller.start(1000, cmd.dry_run).await?;
+    Ok(())
+}
+
+/// Handle all tag commands
+///
+/// # Arguments
+///
+/// * `cmd` - The tag command to execute
+/// * `args` - The command line args for thoradm
+pub async fn handle(cmd: &TagsSubCommands, args: &Args) -> Result<(), Error> {
+    match cmd {
+        TagsSubCommands::Normalize(cmd) => normalize(cmd, args).await,
+    }
+}
@@ -8,16 +8,19 @@ use uuid::Uuid;
 use self::{
     cart::Cart,
     clusters::{Clusters, Login},
+    comments::Comments,
     config::Config,
     files::Files,
     groups::Groups,
     images::Images,
     network_policies::NetworkPolicies,
+    notifications::Notifications,
     pipelines::Pipelines,
     reactions::Reactions,
     repos::Repos,
     results::Results,
     run::Run,
+    search::Search,
     tags::Tags,
     uncart::Uncart,
 };
@@ -25,17 +28,20 @@ use crate::utils::repos::validate_repo_url;
 
 pub mod cart;
 pub mod clusters;
+pub mod comments;
 pub mod config;
 pub mod files;
 pub mod groups;
 mod helpers;
 pub mod images;
 pub mod network_policies;
+pub mod notifications;
 pub mod pipelines;
 pub mod reactions;
 pub mod repos;
 pub mod results;
 pub mod run;
+pub mod search;
 pub mod tags;
 mod traits;
 pub mod uncart;
@@ -78,7 +84,7 @@ pub struct Args {
     /// Don't check for updates from the API
     #[clap(long)]
     pub skip_update: bool,
-    /// The command string to follow (files, images, pipelines, reactions, install, admins, agents, cart, uncart, update, config)
+    /// The command string to follow (files, images, pipelines, reactions, search, install, admins, agents, cart, uncart, update, config)
     #[clap(subcommand)]
     pub cmd: SubCommands,
     /// The number of parallel async actions to process at once
@@ -116,6 +122,15 @@ pub enum SubCommands {
     /// Perform tag related tasks
     #[clap(version, author, subcommand)]
     Tags(Tags),
+    /// Perform comment related tasks
+    #[clap(version, author, subcommand)]
+    Comments(Comments),
+    /// Manage your notification inbox and subscriptions
+    #[clap(version, author, subcommand)]
+    Notifications(Notifications),
+    /// Search results and tags and manage saved searches
+    #[clap(version, author)]
+    Search(Search),
     /// Perform repository related tasks
     #[clap(version, author, subcommand)]
     Repos(Repos),
@@ -67,4 +67,42 @@ pub struct Cart {
     /// Include hidden directories/files
     #[clap(long)]
     pub include_hidden: bool,
+    /// Write sealed CaRT files that authenticate each chunk so corruption is detected
+    ///     Note: sealed files can only be uncarted by cart-rs 1.3.0 or newer
+    #[clap(long, verbatim_doc_comment)]
+    pub sealed: bool,
+    /// The cipher to use when writing sealed CaRT files
+    #[clap(long, default_value_t, requires = "sealed")]
+    pub cipher: SealCipher,
+}
+
+/// The ciphers that can be used to seal CaRT files
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SealCipher {
+    /// AES-256 in Galois/Counter Mode
+    #[default]
+    AesGcm,
+    /// ChaCha20 with a Poly1305 authenticator
+    #[value(name = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl std::fmt::Display for SealCipher {
+    /// write our [`SealCipher`] to this formatter
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SealCipher::AesGcm => write!(f, "aes-gcm"),
+            SealCipher::ChaCha20Poly1305 => write!(f, "chacha20-poly1305"),
+        }
+    }
+}
+
+impl From<SealCipher> for cart_rs::Cipher {
+    /// Convert a [`SealCipher`] to its `cart_rs` cipher
+    fn from(cipher: SealCipher) -> Self {
+        match cipher {
+            SealCipher::AesGcm => cart_rs::Cipher::AesGcm,
+            SealCipher::ChaCha20Poly1305 => cart_rs::Cipher::ChaCha20Poly1305,
+        }
+    }
 }
@@ -0,0 +1,149 @@
+//! Arguments for comment-related Thorctl commands
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use thorium::models::CommentReaction;
+use uuid::Uuid;
+
+/// The commands to send to the comments task handler
+#[derive(Parser, Debug)]
+pub enum Comments {
+    /// List the comment threads on a file, repo, reaction, or result
+    #[clap(version, author)]
+    List(ListComments),
+    /// Add a comment to a file, repo, reaction, or result
+    #[clap(version, author)]
+    Add(AddComment),
+    /// Edit one of your comments
+    #[clap(version, author)]
+    Edit(EditComment),
+    /// React to a comment
+    #[clap(version, author)]
+    React(ReactToComment),
+    /// Delete a comment from a file, repo, reaction, or result
+    #[clap(version, author)]
+    Delete(DeleteComment),
+    /// Download an attachment from a comment
+    #[clap(version, author)]
+    Download(DownloadAttachment),
+}
+
+/// The object a comment is on where exactly one is set
+#[derive(clap::Args, Debug, Clone)]
+#[group(required = true, multiple = false)]
+pub struct CommentObject {
+    /// The SHA256 of the file the comment is on
+    #[clap(short, long)]
+    pub file: Option<String>,
+    /// The URL of the repo the comment is on
+    #[clap(short, long)]
+    pub repo: Option<String>,
+    /// The id of the reaction the comment is on
+    #[clap(long, requires = "reaction_group")]
+    pub reaction: Option<Uuid>,
+}
+
+/// The target for a comment command
+#[derive(clap::Args, Debug, Clone)]
+pub struct CommentTarget {
+    #[clap(flatten)]
+    pub object: CommentObject,
+    /// The group the reaction the comment is on is in
+    #[clap(long, requires = "reaction")]
+    pub reaction_group: Option<String>,
+    /// The tool that made the result the comment is on
+    ///
+    /// Note: Results can only be commented on for files or repos
+    #[clap(long, requires = "result", conflicts_with = "reaction")]
+    pub tool: Option<String>,
+    /// The id of the result the comment is on
+    #[clap(long, requires = "tool")]
+    pub result: Option<Uuid>,
+}
+
+/// A command to list the comment threads on a file, repo, reaction, or result
+#[derive(Parser, Debug)]
+pub struct ListComments {
+    #[clap(flatten)]
+    pub target: CommentTarget,
+    /// Print the full edit history for any edited comments
+    #[clap(long)]
+    pub revisions: bool,
+}
+
+/// A command to add a comment to a file, repo, reaction, or result
+#[derive(Parser, Debug)]
+pub struct AddComment {
+    /// The comment to add
+    ///     Note: Users can be mentioned and notified with `@<username>`
+    #[clap(verbatim_doc_comment)]
+    pub comment: String,
+    #[clap(flatten)]
+    pub target: CommentTarget,
+    /// The id of the comment to reply to
+    #[clap(short, long)]
+    pub parent: Option<Uuid>,
+    /// The groups the comment should be visible to
+    ///     Note: If no groups are given, the comment will be visible to all of the object's groups
+    #[clap(short = 'G', long, value_delimiter = ',', verbatim_doc_comment)]
+    pub groups: Vec<String>,
+    /// Any files to attach to this comment
+    #[clap(short, long)]
+    pub attachments: Vec<PathBuf>,
+}
+
+/// A command to delete a comment from a file, repo, reaction, or result
+#[derive(Parser, Debug)]
+pub struct DeleteComment {
+    /// The id of the comment to delete
+    pub id: Uuid,
+    #[clap(flatten)]
+    pub target: CommentTarget,
+    /// The groups to delete the comment from
+    ///     Note: If no groups are given, the comment will be deleted from all of its groups
+    #[clap(short = 'G', long, value_delimiter = ',', verbatim_doc_comment)]
+    pub groups: Vec<String>,
+}
+
+/// A command to edit one of your comments
+#[derive(Parser, Debug)]
+pub struct EditComment {
+    /// The id of the comment to edit
+    pub id: Uuid,
+    /// The new text for this comment
+    pub comment: String,
+    #[clap(flatten)]
+    pub target: CommentTarget,
+}
+
+/// A command to react to a comment
+#[derive(Parser, Debug)]
+pub struct ReactToComment {
+    /// The id of the comment to react to
+    pub id: Uuid,
+    /// The reaction to leave
+    #[clap(value_enum, ignore_case = true)]
+    pub reaction: CommentReaction,
+    #[clap(flatten)]
+    pub target: CommentTarget,
+    /// Remove this reaction instead of adding it
+    #[clap(long)]
+    pub remove: bool,
+}
+
+/// A command to download an attachment from a comment
+#[derive(Parser, Debug)]
+pub struct DownloadAttachment {
+    /// The id of the comment to download an attachment from
+    pub comment: Uuid,
+    /// The id of the attachment to download
+    pub attachment: Uuid,
+    #[clap(flatten)]
+    pub target: CommentTarget,
+    /// The path to write this attachment to
+    #[clap(short, long)]
+    pub output: PathBuf,
+}
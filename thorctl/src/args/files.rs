@@ -28,6 +28,9 @@ pub enum Files {
     /// Delete file submissions
     #[clap(version, author)]
     Delete(DeleteFiles),
+    /// Walk the origins, children, and tag links for files
+    #[clap(version, author)]
+    Tree(TreeFiles),
 }
 
 /// A command to upload some files to Thorium
@@ -65,6 +68,9 @@ pub struct UploadFiles {
     /// The tags keys to use for each folder name starting at the root of the specified targets
     #[clap(long)]
     pub folder_tags: Vec<String>,
+    /// Upload files at least this many mebibytes in size in resumable chunks
+    #[clap(long, default_value = "1024")]
+    pub chunk_threshold: u64,
 }
 
 impl UploadFiles {
@@ -88,6 +94,15 @@ impl UploadFiles {
         }
     }
 
+    /// Check if a file is large enough that it should be uploaded in resumable chunks
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The size of the file in bytes
+    pub fn is_chunked(&self, size: u64) -> bool {
+        size >= self.chunk_threshold.saturating_mul(1_048_576)
+    }
+
     /// Build a sample upload request for a specific path
     ///
     /// # Arguments
@@ -561,3 +576,31 @@ impl DescribeSealed for DescribeFiles {
 }
 
 impl DescribeCommand for DescribeFiles {}
+
+/// A command to walk the relationships for some files
+#[derive(Parser, Debug)]
+pub struct TreeFiles {
+    /// The SHA256's of the files to start walking relationships from
+    #[clap(required = true)]
+    pub files: Vec<String>,
+    /// The number of relationship levels to walk out from the starting files
+    #[clap(short, long, default_value = "3")]
+    pub depth: usize,
+    /// The format to output this tree in
+    #[clap(short, long, value_enum, default_value_t = TreeFormat::Ascii)]
+    pub format: TreeFormat,
+    /// The path to the file to write this tree to; if not provided, it will be output to stdout
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// The different formats a tree can be output in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TreeFormat {
+    /// A human readable ASCII tree
+    Ascii,
+    /// A Graphviz DOT graph
+    Dot,
+    /// The raw tree as JSON
+    Json,
+}
@@ -0,0 +1,131 @@
+//! Arguments for notification-related Thorctl commands
+
+#![allow(clippy::module_name_repetitions)]
+
+use clap::Parser;
+use thorium::models::{SubscriptionEvent, SubscriptionRequest, SubscriptionTarget};
+use thorium::Error;
+use uuid::Uuid;
+
+use super::GetNotificationOpts;
+
+/// The commands to send to the notifications task handler
+#[derive(Parser, Debug)]
+pub enum Notifications {
+    /// List the notifications in your inbox
+    #[clap(version, author)]
+    List(ListNotifications),
+    /// Delete a notification from your inbox
+    #[clap(version, author)]
+    Delete(DeleteNotification),
+    /// Delete all notifications from your inbox
+    #[clap(version, author)]
+    Clear,
+    /// Subscribe to a file, repo, tag, or pipeline
+    #[clap(version, author)]
+    Subscribe(Subscribe),
+    /// List your subscriptions
+    #[clap(version, author)]
+    Subscriptions(ListSubscriptions),
+    /// Delete one of your subscriptions
+    #[clap(version, author)]
+    Unsubscribe(Unsubscribe),
+}
+
+/// A command to list the notifications in your inbox
+#[derive(Parser, Debug)]
+pub struct ListNotifications {
+    /// The options for getting notifications
+    #[clap(flatten)]
+    pub opts: GetNotificationOpts,
+}
+
+/// A command to delete a notification from your inbox
+#[derive(Parser, Debug)]
+pub struct DeleteNotification {
+    /// The notification's unique ID
+    pub id: Uuid,
+}
+
+/// The thing to subscribe to where exactly one is set
+#[derive(clap::Args, Debug, Clone)]
+#[group(required = true, multiple = false)]
+pub struct SubscribeTarget {
+    /// The SHA256 of the file to subscribe to
+    #[clap(short, long)]
+    pub file: Option<String>,
+    /// The URL of the repo to subscribe to
+    #[clap(short, long)]
+    pub repo: Option<String>,
+    /// The tag to subscribe to in the format KEY=VALUE
+    #[clap(short, long)]
+    pub tag: Option<String>,
+    /// The pipeline to subscribe to (requires --group)
+    #[clap(short, long, requires = "group")]
+    pub pipeline: Option<String>,
+}
+
+/// A command to subscribe to something in Thorium
+#[derive(Parser, Debug)]
+pub struct Subscribe {
+    /// The thing to subscribe to
+    #[clap(flatten)]
+    pub target: SubscribeTarget,
+    /// The group the pipeline to subscribe to is in
+    #[clap(short, long)]
+    pub group: Option<String>,
+    /// The events to be notified about (all events if none are set)
+    #[clap(short, long, value_delimiter = ',')]
+    pub events: Vec<SubscriptionEvent>,
+    /// Also send these notifications to your verified email
+    #[clap(long)]
+    pub email: bool,
+}
+
+impl Subscribe {
+    /// Build the request to subscribe with
+    pub fn build_req(&self) -> Result<SubscriptionRequest, Error> {
+        // get the thing we are subscribing to
+        let target = if let Some(sha256) = &self.target.file {
+            SubscriptionTarget::sample(sha256)
+        } else if let Some(url) = &self.target.repo {
+            SubscriptionTarget::repo(url)
+        } else if let Some(tag) = &self.target.tag {
+            match tag.split_once('=') {
+                Some((key, value)) => SubscriptionTarget::tag(key, value),
+                None => {
+                    return Err(Error::new(format!(
+                        "Tag '{tag}' is not in the format KEY=VALUE"
+                    )))
+                }
+            }
+        } else if let Some(pipeline) = &self.target.pipeline {
+            // clap requires a group to be set when a pipeline is
+            let group = self.group.clone().unwrap_or_default();
+            SubscriptionTarget::pipeline(group, pipeline)
+        } else {
+            return Err(Error::new("Nothing to subscribe to was given"));
+        };
+        // build our request
+        let mut req = SubscriptionRequest::new(target).email(self.email);
+        for event in &self.events {
+            req = req.event(*event);
+        }
+        Ok(req)
+    }
+}
+
+/// A command to list your subscriptions
+#[derive(Parser, Debug)]
+pub struct ListSubscriptions {
+    /// Print subscriptions in JSON format
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// A command to delete one of your subscriptions
+#[derive(Parser, Debug)]
+pub struct Unsubscribe {
+    /// The subscription's unique ID
+    pub id: Uuid,
+}
@@ -114,6 +114,13 @@ pub struct DescribeReactions {
     /// The number of reactions to retrieve per request
     #[clap(long, default_value_t = 50)]
     pub page_size: usize,
+    /// Show live progress for the given reactions until they complete instead of their details
+    #[clap(
+        long,
+        requires = "reactions",
+        conflicts_with_all = ["reaction_list", "output", "condensed", "pipelines", "groups", "tags"]
+    )]
+    pub watch: bool,
 }
 
 impl SearchSealed for DescribeReactions {
@@ -40,6 +40,9 @@ pub enum Repos {
     /// List the contributors in a repo
     #[clap(version, author)]
     Contributors(ContributorsRepos),
+    /// Mirror remote repos so their new commits are ingested automatically
+    #[clap(subcommand)]
+    Mirrors(RepoMirrors),
 }
 
 /// A command to get repo info
@@ -850,3 +853,56 @@ impl From<RepoTarget> for RepoDependencyRequest {
         }
     }
 }
+
+/// The repo mirror specific subcommands
+#[derive(Parser, Debug, Clone)]
+pub enum RepoMirrors {
+    /// Start mirroring a remote repo
+    #[clap(version, author)]
+    Add(AddRepoMirror),
+    /// List the repos being mirrored
+    #[clap(version, author)]
+    List,
+    /// Get the status of a mirrored repo
+    #[clap(version, author)]
+    Status(RepoMirrorTarget),
+    /// Fetch a mirrored repo as soon as possible
+    #[clap(version, author)]
+    Sync(RepoMirrorTarget),
+    /// Stop mirroring a repo
+    ///     Note: Any data already ingested for this repo will not be deleted
+    #[clap(version, author, verbatim_doc_comment)]
+    Delete(RepoMirrorTarget),
+}
+
+/// A command to start mirroring a remote repo
+#[derive(Parser, Debug, Clone)]
+pub struct AddRepoMirror {
+    /// The HTTP(S) url to clone and fetch this repo from
+    #[clap(value_parser = NonEmptyStringValueParser::new())]
+    pub remote: String,
+    /// The groups to share this repo and its commits with
+    #[clap(short, long, value_delimiter = ',', required = true)]
+    pub groups: Vec<String>,
+    /// The number of seconds to wait between fetches
+    #[clap(short, long, default_value_t = 3600)]
+    pub interval: u64,
+    /// The username to fetch this repo with if it is not public
+    #[clap(short, long)]
+    pub username: Option<String>,
+    /// The token to fetch this repo with; you will be prompted for it if a username is set.
+    /// Only use this for non-interactive environments.
+    #[clap(long, requires = "username")]
+    pub token: Option<String>,
+    /// The branch to checkout by default
+    #[clap(short, long)]
+    pub branch: Option<String>,
+}
+
+/// A command that targets a single mirrored repo
+#[derive(Parser, Debug, Clone)]
+pub struct RepoMirrorTarget {
+    /// The url of the mirrored repo in Thorium
+    #[clap(value_parser = NonEmptyStringValueParser::new())]
+    pub url: String,
+}
@@ -0,0 +1,212 @@
+//! Arguments for search-related Thorctl commands
+
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use clap::builder::NonEmptyStringValueParser;
+use clap::{Parser, ValueEnum};
+use thorium::models::{ElasticIndex, ElasticSearchOpts, SavedSearch, SavedSearchRequest};
+use thorium::Error;
+
+/// Search results and tags in Thorium
+///
+/// Matching items can be piped into other commands:
+///     thorctl search "corn AND pe32" -f sha256 | xargs thorctl files download
+///     thorctl reactions create -p <PIPELINE> --file-list <(thorctl search --saved corn-pe -f sha256)
+#[derive(Parser, Debug)]
+#[clap(
+    verbatim_doc_comment,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+pub struct Search {
+    /// Manage saved searches
+    #[clap(subcommand)]
+    pub cmd: Option<SearchCommands>,
+    /// The query to search with (i.e. "corn AND pe32")
+    #[clap(required_unless_present = "saved", value_parser = NonEmptyStringValueParser::new())]
+    pub query: Option<String>,
+    /// Run a saved search; any other search parameters that are set will override the saved ones
+    #[clap(short, long)]
+    pub saved: Option<String>,
+    /// The indexes to search
+    #[clap(short, long, value_delimiter = ',')]
+    pub indexes: Vec<ElasticIndex>,
+    /// Any groups to restrict the search to
+    ///     Note: If no groups are given, the search will include all groups the user is apart of
+    #[clap(short, long, value_delimiter = ',', verbatim_doc_comment)]
+    pub groups: Vec<String>,
+    /// The most recent datetime to start searching at in UTC
+    #[clap(long)]
+    pub start: Option<String>,
+    /// The oldest datetime to stop searching at in UTC
+    #[clap(long)]
+    pub end: Option<String>,
+    /// The format string to use when parsing the start/end datetimes
+    ///     Example: The format of "2014-5-17T12:34:56" is "%Y-%m-%dT%H:%M:%S"
+    ///     (see <https://docs.rs/chrono/latest/chrono/format/strftime>)
+    #[clap(long, default_value = "%Y-%m-%dT%H:%M:%S", verbatim_doc_comment)]
+    pub date_fmt: String,
+    /// The max number of documents to return
+    ///     Note: If no limit is given, every matching document is returned
+    #[clap(short, long, verbatim_doc_comment)]
+    pub limit: Option<usize>,
+    /// The number of documents to retrieve in one request
+    #[clap(short, long, default_value = "100")]
+    pub page_size: usize,
+    /// The format to output matching documents in
+    #[clap(short, long, value_enum, default_value_t = SearchOutput::Table)]
+    pub format: SearchOutput,
+    /// The path to write matching documents to instead of stdout
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+impl Search {
+    /// Build the options to search with, overlaying any set params onto a saved search
+    ///
+    /// # Arguments
+    ///
+    /// * `saved` - The saved search to run if one was requested
+    pub fn build_opts(&self, saved: Option<SavedSearch>) -> Result<ElasticSearchOpts, Error> {
+        // start from our saved search or our query
+        let mut opts = match (saved, &self.query) {
+            (Some(saved), Some(query)) => {
+                let mut opts = saved.to_opts();
+                opts.query.clone_from(query);
+                opts
+            }
+            (Some(saved), None) => saved.to_opts(),
+            (None, Some(query)) => ElasticSearchOpts::new(query),
+            (None, None) => return Err(Error::new("A query or saved search is required")),
+        };
+        // overlay any params that were set
+        if !self.indexes.is_empty() {
+            opts = opts.indexes(self.indexes.clone());
+        }
+        if !self.groups.is_empty() {
+            opts = opts.groups(self.groups.clone());
+        }
+        if let Some(start) = &self.start {
+            opts = opts.start(NaiveDateTime::parse_from_str(start, &self.date_fmt)?.and_utc());
+        }
+        if let Some(end) = &self.end {
+            opts = opts.end(NaiveDateTime::parse_from_str(end, &self.date_fmt)?.and_utc());
+        }
+        // set our page size and limit
+        opts = opts.page_size(self.page_size);
+        if let Some(limit) = self.limit {
+            opts = opts.limit(limit);
+        }
+        Ok(opts)
+    }
+}
+
+/// The different formats search results can be output in
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchOutput {
+    /// A human readable table of matching items
+    Table,
+    /// One JSON document per line
+    Jsonl,
+    /// Comma separated item, group, index, and score columns
+    Csv,
+    /// Only the unique matching sha256s/repos, one per line
+    Sha256,
+}
+
+/// The commands for managing saved searches
+#[derive(Parser, Debug)]
+pub enum SearchCommands {
+    /// Manage saved searches that are shared between Thorctl and the Web UI
+    #[clap(version, author, subcommand)]
+    Saved(SavedSearches),
+}
+
+/// The commands for saved searches
+#[derive(Parser, Debug)]
+pub enum SavedSearches {
+    /// Save a search so it can be run again later
+    #[clap(version, author)]
+    Create(CreateSavedSearch),
+    /// List your saved searches
+    #[clap(version, author)]
+    List(ListSavedSearches),
+    /// Delete saved searches
+    #[clap(version, author)]
+    Delete(DeleteSavedSearches),
+}
+
+/// A command to save a search
+#[derive(Parser, Debug)]
+pub struct CreateSavedSearch {
+    /// The name to save this search as
+    #[clap(value_parser = NonEmptyStringValueParser::new())]
+    pub name: String,
+    /// The query to search with
+    #[clap(value_parser = NonEmptyStringValueParser::new())]
+    pub query: String,
+    /// The indexes to search
+    #[clap(short, long, value_delimiter = ',')]
+    pub indexes: Vec<ElasticIndex>,
+    /// Any groups to restrict the search to
+    ///     Note: If no groups are given, the search will include all groups the user is apart of
+    #[clap(short, long, value_delimiter = ',', verbatim_doc_comment)]
+    pub groups: Vec<String>,
+    /// The most recent datetime to start searching at in UTC
+    #[clap(long)]
+    pub start: Option<String>,
+    /// The oldest datetime to stop searching at in UTC
+    #[clap(long)]
+    pub end: Option<String>,
+    /// The format string to use when parsing the start/end datetimes
+    ///     Example: The format of "2014-5-17T12:34:56" is "%Y-%m-%dT%H:%M:%S"
+    ///     (see <https://docs.rs/chrono/latest/chrono/format/strftime>)
+    #[clap(long, default_value = "%Y-%m-%dT%H:%M:%S", verbatim_doc_comment)]
+    pub date_fmt: String,
+    /// A description of what this search finds
+    #[clap(short, long)]
+    pub description: Option<String>,
+    /// Replace any existing saved search with the same name
+    #[clap(long)]
+    pub force: bool,
+}
+
+impl CreateSavedSearch {
+    /// Build the request to save this search
+    pub fn build_req(&self) -> Result<SavedSearchRequest, Error> {
+        let mut req = SavedSearchRequest::new(&self.name, &self.query);
+        if !self.indexes.is_empty() {
+            req = req.indexes(self.indexes.clone());
+        }
+        for group in &self.groups {
+            req = req.group(group);
+        }
+        if let Some(start) = &self.start {
+            req = req.start(NaiveDateTime::parse_from_str(start, &self.date_fmt)?.and_utc());
+        }
+        if let Some(end) = &self.end {
+            req = req.end(NaiveDateTime::parse_from_str(end, &self.date_fmt)?.and_utc());
+        }
+        if let Some(description) = &self.description {
+            req = req.description(description);
+        }
+        Ok(req)
+    }
+}
+
+/// A command to list saved searches
+#[derive(Parser, Debug)]
+pub struct ListSavedSearches {
+    /// Print the saved searches as JSON instead of a table
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// A command to delete saved searches
+#[derive(Parser, Debug)]
+pub struct DeleteSavedSearches {
+    /// The names of the saved searches to delete
+    #[clap(required = true)]
+    pub names: Vec<String>,
+}
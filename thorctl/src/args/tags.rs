@@ -5,6 +5,8 @@
 use std::path::PathBuf;
 
 use clap::{builder::NonEmptyStringValueParser, Parser};
+use thorium::models::TagSource;
+use uuid::Uuid;
 
 use super::traits::search::{SearchParameterized, SearchParams, SearchSealed};
 
@@ -20,6 +22,12 @@ pub enum Tags {
     /// Delete tags from files/repos
     #[clap(version, author)]
     Delete(DeleteTags),
+    /// Show who or what set the tags on a file/repo
+    #[clap(version, author)]
+    Provenance(GetTagProvenance),
+    /// Rename, replace, or delete tags across everything in some groups
+    #[clap(subcommand)]
+    Bulk(BulkTags),
 }
 
 /// A command to get a list of tags for a file or repo
@@ -36,6 +44,25 @@ pub struct GetTags {
     pub condensed: bool,
 }
 
+/// A command to show who or what set the tags on a file or repo
+#[derive(Parser, Debug)]
+pub struct GetTagProvenance {
+    /// The sample SHA256 of the file or URL of the repo to get tag provenance for
+    #[clap(value_parser = NonEmptyStringValueParser::new())]
+    pub sha256_or_repo: String,
+    /// The groups to get tag provenance from
+    ///     Note: If no groups are given, tag provenance from all of the object's groups is shown
+    #[clap(short, long, value_delimiter = ',', verbatim_doc_comment)]
+    pub groups: Vec<String>,
+    /// Only show tags from this source (User or Tool)
+    #[clap(short, long)]
+    pub source: Option<TagSource>,
+    /// Only show tags with at least this confidence (from 0 to 1)
+    ///     Note: Tags without a confidence are treated as fully confident
+    #[clap(short, long, verbatim_doc_comment)]
+    pub min_confidence: Option<f32>,
+}
+
 /// A command to add tags to files/repos
 #[derive(Parser, Debug)]
 #[allow(clippy::struct_field_names)]
@@ -231,3 +258,96 @@ impl SearchSealed for DeleteTags {
         }
     }
 }
+
+/// The bulk tag specific subcommands
+#[derive(Parser, Debug, Clone)]
+pub enum BulkTags {
+    /// Rename a tag key, keeping all of its values
+    #[clap(version, author)]
+    Rename(BulkRenameTags),
+    /// Replace a value under a tag key
+    #[clap(version, author)]
+    Replace(BulkReplaceTags),
+    /// Delete a tag from everything
+    #[clap(version, author)]
+    Delete(BulkDeleteTags),
+    /// List bulk tag jobs
+    #[clap(version, author)]
+    List,
+    /// Get the status of a bulk tag job
+    #[clap(version, author)]
+    Status(BulkTagJobId),
+    /// Cancel a running bulk tag job
+    ///     Note: Any files/repos that were already modified will not be reverted
+    #[clap(version, author, verbatim_doc_comment)]
+    Cancel(BulkTagJobId),
+}
+
+/// The options shared by all bulk tag operations
+#[derive(Parser, Debug, Clone)]
+pub struct BulkTagOpts {
+    /// The groups to modify tags in
+    #[clap(short, long, value_delimiter = ',', required = true)]
+    pub groups: Vec<String>,
+    /// Modify tags on repos instead of files
+    #[clap(short = 'R', long)]
+    pub repos: bool,
+    /// Only count the files/repos that would be modified
+    #[clap(long)]
+    pub dry_run: bool,
+    /// Wait for the bulk tag job to finish, printing its progress
+    #[clap(short, long)]
+    pub wait: bool,
+}
+
+/// A command to rename a tag key across everything in some groups
+#[derive(Parser, Debug, Clone)]
+pub struct BulkRenameTags {
+    /// The tag key to rename
+    #[clap(long, value_parser = NonEmptyStringValueParser::new())]
+    pub from: String,
+    /// The new name for this tag key
+    #[clap(long, value_parser = NonEmptyStringValueParser::new())]
+    pub to: String,
+    /// The options for this bulk tag job
+    #[clap(flatten)]
+    pub opts: BulkTagOpts,
+}
+
+/// A command to replace a tag value across everything in some groups
+#[derive(Parser, Debug, Clone)]
+pub struct BulkReplaceTags {
+    /// The tag key to replace a value under
+    #[clap(short, long, value_parser = NonEmptyStringValueParser::new())]
+    pub key: String,
+    /// The tag value to replace
+    #[clap(long, value_parser = NonEmptyStringValueParser::new())]
+    pub from: String,
+    /// The new tag value
+    #[clap(long, value_parser = NonEmptyStringValueParser::new())]
+    pub to: String,
+    /// The options for this bulk tag job
+    #[clap(flatten)]
+    pub opts: BulkTagOpts,
+}
+
+/// A command to delete a tag from everything in some groups
+#[derive(Parser, Debug, Clone)]
+pub struct BulkDeleteTags {
+    /// The key of the tag to delete
+    #[clap(short, long, value_parser = NonEmptyStringValueParser::new())]
+    pub key: String,
+    /// The value of the tag to delete
+    #[clap(short, long, value_parser = NonEmptyStringValueParser::new())]
+    pub value: String,
+    /// The options for this bulk tag job
+    #[clap(flatten)]
+    pub opts: BulkTagOpts,
+}
+
+/// A command that targets a single bulk tag job
+#[derive(Parser, Debug, Clone)]
+pub struct BulkTagJobId {
+    /// The id of the bulk tag job
+    pub id: Uuid,
+}
@@ -1,5 +1,6 @@
 pub mod cart;
 pub mod clusters;
+pub mod comments;
 pub mod config;
 mod controllers;
 pub mod files;
@@ -7,12 +8,14 @@ pub mod groups;
 pub mod images;
 mod monitor;
 pub mod network_policies;
+pub mod notifications;
 pub mod pipelines;
 pub mod progress;
 pub mod reactions;
 pub mod repos;
 pub mod results;
 pub mod run;
+pub mod search;
 pub mod tags;
 pub mod uncart;
 pub mod update;
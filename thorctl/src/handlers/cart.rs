@@ -3,7 +3,7 @@ use std::{
     sync::Arc,
 };
 
-use cart_rs::CartStream;
+use cart_rs::{CartStream, SealOptions};
 
 use futures::{stream, StreamExt};
 use generic_array::{typenum::U16, GenericArray};
@@ -164,7 +164,16 @@ async fn cart_entry(
         .open(&out_path)
         .await?;
     // create a stream to cart the file and copy the stream's contents to the output path
-    let mut cart_stream = CartStream::new(BufStream::new(input), &password)?;
+    let mut cart_stream = if cmd.sealed {
+        // keep the original file name in the sealed header
+        let mut opts = SealOptions::default().cipher(cmd.cipher.into());
+        if let Some(name) = entry.file_name().to_str() {
+            opts = opts.file_name(name);
+        }
+        CartStream::sealed(BufStream::new(input), &password, opts)?
+    } else {
+        CartStream::new(BufStream::new(input), &password)?
+    };
     if let Err(err) = tokio::io::copy(&mut cart_stream, &mut output_cart).await {
         // if an error occurred while carting, delete the output file and return the error
         drop(output_cart);
@@ -0,0 +1,475 @@
+//! Handles comment commands
+
+use itertools::Itertools;
+use thorium::client::ResultsClient;
+use thorium::models::{
+    Attachment, Comment, CommentRequest, CommentResponse, CommentThread, CommentUpdate,
+    DeleteCommentParams, OnDiskFile, OutputMap, ResultGetParams,
+};
+use thorium::{Error, Thorium};
+use uuid::Uuid;
+
+use crate::args::comments::{
+    AddComment, CommentTarget, Comments, DeleteComment, DownloadAttachment, EditComment,
+    ListComments, ReactToComment,
+};
+use crate::args::Args;
+use crate::utils;
+
+/// The object a comment command is targeting
+enum Target<'a> {
+    /// A file by sha256
+    File(&'a str),
+    /// A repo by url
+    Repo(&'a str),
+    /// A reaction in a group
+    Reaction { group: &'a str, id: &'a Uuid },
+    /// A result for a file
+    FileResult {
+        sha256: &'a str,
+        tool: &'a str,
+        result_id: &'a Uuid,
+    },
+    /// A result for a repo
+    RepoResult {
+        repo: &'a str,
+        tool: &'a str,
+        result_id: &'a Uuid,
+    },
+}
+
+impl<'a> Target<'a> {
+    /// Get the object a comment command is targeting from its args
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The comment target args to parse
+    fn parse(cmd: &'a CommentTarget) -> Result<Self, Error> {
+        // get the result this comment is on if one was set
+        let result = cmd.tool.as_deref().zip(cmd.result.as_ref());
+        let target = match (&cmd.object.file, &cmd.object.repo, &cmd.object.reaction) {
+            (Some(sha256), _, _) => match result {
+                Some((tool, result_id)) => Target::FileResult {
+                    sha256,
+                    tool,
+                    result_id,
+                },
+                None => Target::File(sha256),
+            },
+            (_, Some(repo), _) => match result {
+                Some((tool, result_id)) => Target::RepoResult {
+                    repo,
+                    tool,
+                    result_id,
+                },
+                None => Target::Repo(repo),
+            },
+            (_, _, Some(id)) => match &cmd.reaction_group {
+                Some(group) => Target::Reaction { group, id },
+                None => return Err(Error::new("A reaction group must be set")),
+            },
+            _ => return Err(Error::new("A file, repo, or reaction must be set")),
+        };
+        Ok(target)
+    }
+}
+
+/// Get the comments on a result from a map of results
+///
+/// # Arguments
+///
+/// * `results` - The results to search
+/// * `result_id` - The id of the result to get comments for
+fn result_comments(results: OutputMap, result_id: &Uuid) -> Result<Vec<Comment>, Error> {
+    results
+        .results
+        .into_values()
+        .flatten()
+        .find(|output| &output.id == result_id)
+        .map(|output| output.comments)
+        .ok_or_else(|| Error::new(format!("Result {result_id} not found")))
+}
+
+/// Get the comments on the object a comment command is targeting
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `target` - The object to get comments for
+async fn get_comments(thorium: &Thorium, target: &Target<'_>) -> Result<Vec<Comment>, Error> {
+    let comments = match target {
+        Target::File(sha256) => thorium.files.get(sha256).await?.comments,
+        Target::Repo(repo) => thorium.repos.get(repo).await?.comments,
+        Target::Reaction { group, id } => thorium.reactions.get(group, id).await?.comments,
+        Target::FileResult {
+            sha256,
+            tool,
+            result_id,
+        } => {
+            // results may be hidden so make sure we get them
+            let params = ResultGetParams::default().hidden().tool(*tool);
+            let results = thorium.files.get_results(sha256, &params).await?;
+            result_comments(results, result_id)?
+        }
+        Target::RepoResult {
+            repo,
+            tool,
+            result_id,
+        } => {
+            // results may be hidden so make sure we get them
+            let params = ResultGetParams::default().hidden().tool(*tool);
+            let results = thorium.repos.get_results(repo, &params).await?;
+            result_comments(results, result_id)?
+        }
+    };
+    Ok(comments)
+}
+
+/// Print a comment thread indented by its depth
+///
+/// # Arguments
+///
+/// * `thread` - The thread to print
+/// * `depth` - How many replies deep this thread is
+/// * `revisions` - Whether to print the edit history for edited comments
+fn print_thread(thread: &CommentThread, depth: usize, revisions: bool) {
+    let comment = &thread.comment;
+    let indent = "    ".repeat(depth);
+    // print who wrote this comment and when
+    let edited = comment
+        .edited
+        .map(|edited| format!(" (edited {})", edited.format("%Y-%m-%d %H:%M:%S")))
+        .unwrap_or_default();
+    println!(
+        "{indent}{} @{} [{}]{edited}",
+        comment.uploaded.format("%Y-%m-%d %H:%M:%S"),
+        comment.author,
+        comment.id,
+    );
+    // print the comment itself
+    for line in comment.comment.lines() {
+        println!("{indent}  {line}");
+    }
+    // print the older versions of this comment if requested
+    if revisions {
+        for revision in comment.revisions.iter().rev() {
+            println!(
+                "{indent}  ~ {}: {}",
+                revision.written.format("%Y-%m-%d %H:%M:%S"),
+                revision.comment.lines().join(" ")
+            );
+        }
+    }
+    // print any attachments and reactions
+    if !comment.attachments.is_empty() {
+        println!(
+            "{indent}  attachments: {}",
+            comment
+                .attachments
+                .iter()
+                .map(|(name, id)| format!("{name} ({id})"))
+                .join(", ")
+        );
+    }
+    if !comment.reactions.is_empty() {
+        println!(
+            "{indent}  reactions: {}",
+            comment
+                .reactions
+                .iter()
+                .map(|(reaction, users)| format!("{reaction:?} ({})", users.iter().join(", ")))
+                .join(", ")
+        );
+    }
+    // print the replies to this comment
+    for reply in &thread.replies {
+        print_thread(reply, depth + 1, revisions);
+    }
+}
+
+/// List the comment threads on a file, repo, reaction, or result
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The list comments command that was run
+async fn list(thorium: &Thorium, cmd: &ListComments) -> Result<(), Error> {
+    // get the comments on this object and thread them
+    let comments = get_comments(thorium, &Target::parse(&cmd.target)?).await?;
+    for thread in CommentThread::build(&comments) {
+        print_thread(&thread, 0, cmd.revisions);
+        println!();
+    }
+    Ok(())
+}
+
+/// Update a comment on a file, repo, reaction, or result
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `target` - The object the comment is on
+/// * `id` - The id of the comment to update
+/// * `update` - The update to apply
+async fn send_update(
+    thorium: &Thorium,
+    target: &CommentTarget,
+    id: &Uuid,
+    update: &CommentUpdate,
+) -> Result<(), Error> {
+    // update this comment on the right object
+    match Target::parse(target)? {
+        Target::File(sha256) => thorium.files.update_comment(sha256, id, update).await?,
+        Target::Repo(repo) => thorium.repos.update_comment(repo, id, update).await?,
+        Target::Reaction {
+            group,
+            id: reaction,
+        } => {
+            thorium
+                .reactions
+                .update_comment(group, reaction, id, update)
+                .await?
+        }
+        Target::FileResult {
+            sha256,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .files
+                .update_result_comment(sha256, tool, result_id, id, update)
+                .await?
+        }
+        Target::RepoResult {
+            repo,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .repos
+                .update_result_comment(repo, tool, result_id, id, update)
+                .await?
+        }
+    };
+    Ok(())
+}
+
+/// Edit one of your comments
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The edit comment command that was run
+async fn edit(thorium: &Thorium, cmd: &EditComment) -> Result<(), Error> {
+    let update = CommentUpdate::default().comment(&cmd.comment);
+    send_update(thorium, &cmd.target, &cmd.id, &update).await?;
+    println!("Edited comment {}", cmd.id);
+    Ok(())
+}
+
+/// React to a comment
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The react command that was run
+async fn react(thorium: &Thorium, cmd: &ReactToComment) -> Result<(), Error> {
+    // add or remove this reaction
+    let update = if cmd.remove {
+        CommentUpdate::default().remove_reaction(cmd.reaction)
+    } else {
+        CommentUpdate::default().add_reaction(cmd.reaction)
+    };
+    send_update(thorium, &cmd.target, &cmd.id, &update).await?;
+    println!("Updated reactions on comment {}", cmd.id);
+    Ok(())
+}
+
+/// Add a comment to a file, repo, reaction, or result
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The add comment command that was run
+async fn add(thorium: &Thorium, cmd: &AddComment) -> Result<(), Error> {
+    // build our comment request
+    let mut req = CommentRequest::text(&cmd.comment)
+        .groups(cmd.groups.clone())
+        .files(cmd.attachments.iter().map(OnDiskFile::new).collect());
+    // reply to another comment if requested
+    if let Some(parent) = cmd.parent {
+        req = req.parent(parent);
+    }
+    // add this comment to the right object
+    let resp: CommentResponse = match Target::parse(&cmd.target)? {
+        Target::File(sha256) => {
+            let req = CommentRequest {
+                sha256: sha256.to_owned(),
+                ..req
+            };
+            thorium.files.comment(req).await?
+        }
+        Target::Repo(repo) => thorium.repos.comment(repo, req).await?,
+        Target::Reaction { group, id } => thorium.reactions.comment(group, id, req).await?,
+        Target::FileResult {
+            sha256,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .files
+                .comment_result(sha256, tool, result_id, req)
+                .await?
+        }
+        Target::RepoResult {
+            repo,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .repos
+                .comment_result(repo, tool, result_id, req)
+                .await?
+        }
+    };
+    println!("Added comment {}", resp.id);
+    Ok(())
+}
+
+/// Delete a comment from a file, repo, reaction, or result
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The delete comment command that was run
+async fn delete(thorium: &Thorium, cmd: &DeleteComment) -> Result<(), Error> {
+    // build our delete params
+    let params = DeleteCommentParams::default().groups(cmd.groups.clone());
+    // delete this comment from the right object
+    match Target::parse(&cmd.target)? {
+        Target::File(sha256) => {
+            thorium
+                .files
+                .delete_comment(sha256, &cmd.id, &params)
+                .await?
+        }
+        Target::Repo(repo) => thorium.repos.delete_comment(repo, &cmd.id, &params).await?,
+        Target::Reaction { group, id } => {
+            thorium
+                .reactions
+                .delete_comment(group, id, &cmd.id, &params)
+                .await?
+        }
+        Target::FileResult {
+            sha256,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .files
+                .delete_result_comment(sha256, tool, result_id, &cmd.id, &params)
+                .await?
+        }
+        Target::RepoResult {
+            repo,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .repos
+                .delete_result_comment(repo, tool, result_id, &cmd.id, &params)
+                .await?
+        }
+    };
+    println!("Deleted comment {}", cmd.id);
+    Ok(())
+}
+
+/// Download an attachment from a comment
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The download attachment command that was run
+async fn download(thorium: &Thorium, cmd: &DownloadAttachment) -> Result<(), Error> {
+    // download this attachment from the right object
+    let attachment: Attachment = match Target::parse(&cmd.target)? {
+        Target::File(sha256) => {
+            thorium
+                .files
+                .download_attachment(sha256, &cmd.comment, &cmd.attachment)
+                .await?
+        }
+        Target::Repo(repo) => {
+            thorium
+                .repos
+                .download_attachment(repo, &cmd.comment, &cmd.attachment)
+                .await?
+        }
+        Target::Reaction { group, id } => {
+            thorium
+                .reactions
+                .download_attachment(group, id, &cmd.comment, &cmd.attachment)
+                .await?
+        }
+        Target::FileResult {
+            sha256,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .files
+                .download_result_comment_attachment(
+                    sha256,
+                    tool,
+                    result_id,
+                    &cmd.comment,
+                    &cmd.attachment,
+                )
+                .await?
+        }
+        Target::RepoResult {
+            repo,
+            tool,
+            result_id,
+        } => {
+            thorium
+                .repos
+                .download_result_comment_attachment(
+                    repo,
+                    tool,
+                    result_id,
+                    &cmd.comment,
+                    &cmd.attachment,
+                )
+                .await?
+        }
+    };
+    // write this attachment to disk
+    tokio::fs::write(&cmd.output, &attachment.data).await?;
+    Ok(())
+}
+
+/// Handle all comment commands
+///
+/// # Arguments
+///
+/// * `args` - The arguments passed to Thorctl
+/// * `cmd` - The comments command to execute
+pub async fn handle(args: &Args, cmd: &Comments) -> Result<(), Error> {
+    // load our config and instance our client
+    let (conf, thorium) = utils::get_client(args).await?;
+    // warn about insecure connections if not set to skip
+    if !conf.skip_insecure_warning.unwrap_or_default() {
+        utils::warn_insecure_conf(&conf)?;
+    }
+    // call the right comments handler
+    match cmd {
+        Comments::List(cmd) => list(&thorium, cmd).await,
+        Comments::Add(cmd) => add(&thorium, cmd).await,
+        Comments::Edit(cmd) => edit(&thorium, cmd).await,
+        Comments::React(cmd) => react(&thorium, cmd).await,
+        Comments::Delete(cmd) => delete(&thorium, cmd).await,
+        Comments::Download(cmd) => download(&thorium, cmd).await,
+    }
+}
@@ -16,6 +16,7 @@ use uuid::Uuid;
 use walkdir::DirEntry;
 
 mod download;
+mod tree;
 
 use super::{update, Controller};
 use crate::args::files::{DeleteFiles, DescribeFiles, DownloadFiles, Files, GetFiles, UploadFiles};
@@ -114,8 +115,14 @@ async fn uploader(
     if exists.id.is_none() {
         // Build the sample request for this file
         let sample_req = cmd.build_req(path);
-        // upload this file
-        let resp = thorium.files.create(sample_req).await;
+        // get the size of this file
+        let size = tokio::fs::metadata(path).await?.len();
+        // upload this file in resumable chunks if its large enough
+        let resp = if cmd.is_chunked(size) {
+            thorium.files.create_chunked(sample_req, &sha256).await
+        } else {
+            thorium.files.create(sample_req).await
+        };
         // determine if we should print an error message or not
         match resp {
             Ok(resp) => {
@@ -718,5 +725,6 @@ pub async fn handle(args: &Args, cmd: &Files) -> Result<(), Error> {
         Files::Get(cmd) => get(&thorium, cmd).await,
         Files::Describe(cmd) => describe(&thorium, cmd).await,
         Files::Delete(cmd) => delete(&thorium, cmd).await,
+        Files::Tree(cmd) => tree::tree(&thorium, cmd).await,
     }
 }
@@ -0,0 +1,231 @@
+//! Walk and render the relationships for files in thorctl
+
+use itertools::Itertools;
+use std::collections::HashSet;
+use std::fmt::Write;
+use thorium::models::{Origin, Tree, TreeNode, TreeNodeData, TreeQuery, TreeRelationships};
+use thorium::{Error, Thorium};
+
+use crate::args::files::{TreeFiles, TreeFormat};
+
+/// Get a short label for an origin
+///
+/// # Arguments
+///
+/// * `origin` - The origin to label
+fn origin_label(origin: &Origin) -> String {
+    match origin {
+        Origin::Downloaded { url, .. } => format!("downloaded from {url}"),
+        Origin::Unpacked {
+            tool: Some(tool), ..
+        } => format!("unpacked by {tool}"),
+        Origin::Unpacked { .. } => "unpacked".to_owned(),
+        Origin::Transformed {
+            tool: Some(tool), ..
+        } => format!("transformed by {tool}"),
+        Origin::Transformed { .. } => "transformed".to_owned(),
+        Origin::Wire { .. } => "sniffed".to_owned(),
+        Origin::Incident { .. } => "incident".to_owned(),
+        Origin::MemoryDump { .. } => "memory dump".to_owned(),
+        Origin::Source { .. } => "built".to_owned(),
+        Origin::Carved { .. } => "carved".to_owned(),
+        Origin::None => "none".to_owned(),
+    }
+}
+
+/// Get the label for the relationships a node has to its parent
+///
+/// # Arguments
+///
+/// * `node` - The node to label the relationships for
+fn edge_label(node: &TreeNode) -> String {
+    node.relationship
+        .iter()
+        .filter_map(|relationship| match relationship {
+            TreeRelationships::Initial => None,
+            TreeRelationships::Origin(origin) => Some(origin_label(origin)),
+            TreeRelationships::Tags => Some("tags".to_owned()),
+            TreeRelationships::BuiltFrom => Some("built from".to_owned()),
+            TreeRelationships::ResultChildOf { tool, .. } => Some(format!("result of {tool}")),
+        })
+        .unique()
+        .join(", ")
+}
+
+/// Get the label for a node
+///
+/// # Arguments
+///
+/// * `node` - The node to label
+fn node_label(node: &TreeNode) -> String {
+    match &node.data {
+        TreeNodeData::Sample(sample) => {
+            // get the unique names for this sample
+            let names = sample
+                .submissions
+                .iter()
+                .filter_map(|sub| sub.name.as_deref())
+                .unique()
+                .join(", ");
+            if names.is_empty() {
+                sample.sha256.clone()
+            } else {
+                format!("{} ({names})", sample.sha256)
+            }
+        }
+        TreeNodeData::Tag(tags) => tags
+            .tags
+            .iter()
+            .map(|(key, values)| format!("{key}={}", values.iter().join(",")))
+            .join(" "),
+        TreeNodeData::Repo(repo) => repo.url.clone(),
+        TreeNodeData::Commitish(commitish) => match &commitish.commitish {
+            Some(name) => format!("{}@{} ({name})", commitish.repo, commitish.commit),
+            None => format!("{}@{}", commitish.repo, commitish.commit),
+        },
+    }
+}
+
+/// Get the children of a node sorted by their labels
+///
+/// # Arguments
+///
+/// * `tree` - The tree to get children from
+/// * `hash` - The hash of the node to get children for
+fn sorted_children(tree: &Tree, hash: u64) -> Vec<(u64, &TreeNode)> {
+    tree.branches
+        .get(&hash)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| tree.data_map.get(child).map(|node| (*child, node)))
+        .sorted_by_key(|(_, node)| node_label(node))
+        .collect()
+}
+
+/// Render a branch of a tree as ASCII
+///
+/// # Arguments
+///
+/// * `tree` - The tree to render
+/// * `hash` - The hash of the node whose children to render
+/// * `prefix` - The prefix to draw before each child
+/// * `seen` - The nodes that have already been rendered
+/// * `out` - The string to render to
+fn ascii_branch(tree: &Tree, hash: u64, prefix: &str, seen: &mut HashSet<u64>, out: &mut String) {
+    // get this nodes children
+    let children = sorted_children(tree, hash);
+    let last_index = children.len().saturating_sub(1);
+    for (index, (child, node)) in children.into_iter().enumerate() {
+        // pick the right connector for this child
+        let (connector, indent) = if index == last_index {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        // only walk nodes we haven't already rendered so cycles end
+        let first = seen.insert(child);
+        let _ = writeln!(
+            out,
+            "{prefix}{connector}[{}] {}{}",
+            edge_label(node),
+            node_label(node),
+            if first { "" } else { " (see above)" }
+        );
+        if first {
+            ascii_branch(tree, child, &format!("{prefix}{indent}"), seen, out);
+        }
+    }
+}
+
+/// Render a tree as ASCII
+///
+/// # Arguments
+///
+/// * `tree` - The tree to render
+fn ascii(tree: &Tree) -> String {
+    let mut out = String::default();
+    let mut seen = HashSet::with_capacity(tree.data_map.len());
+    // render each of our initial nodes and their branches
+    for hash in &tree.initial {
+        if let Some(node) = tree.data_map.get(hash) {
+            seen.insert(*hash);
+            let _ = writeln!(out, "{}", node_label(node));
+            ascii_branch(tree, *hash, "", &mut seen, &mut out);
+        }
+    }
+    out
+}
+
+/// Escape a label so it can be used in a DOT graph
+///
+/// # Arguments
+///
+/// * `label` - The label to escape
+fn dot_escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Render a tree as a Graphviz DOT graph
+///
+/// # Arguments
+///
+/// * `tree` - The tree to render
+fn dot(tree: &Tree) -> String {
+    let mut out = String::from("digraph tree {\n");
+    // add all of our nodes
+    for (hash, node) in tree.data_map.iter().sorted_by_key(|(hash, _)| **hash) {
+        // highlight the nodes this tree was started from
+        let style = if tree.initial.contains(hash) {
+            ", style=bold"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  \"{hash}\" [label=\"{}\"{style}];",
+            dot_escape(&node_label(node))
+        );
+    }
+    // link all of our nodes together
+    for (parent, children) in tree.branches.iter().sorted_by_key(|(hash, _)| **hash) {
+        for child in children.iter().sorted() {
+            if let Some(node) = tree.data_map.get(child) {
+                let _ = writeln!(
+                    out,
+                    "  \"{parent}\" -> \"{child}\" [label=\"{}\"];",
+                    dot_escape(&edge_label(node))
+                );
+            }
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Walk the relationships for some files and render them as a tree
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The tree command to execute
+pub async fn tree(thorium: &Thorium, cmd: &TreeFiles) -> Result<(), Error> {
+    // start our tree from all of the requested files
+    let query = cmd
+        .files
+        .iter()
+        .fold(TreeQuery::default(), |query, sha256| query.sample(sha256));
+    // grow this tree out to the requested depth
+    let tree = thorium.trees.build(&query, cmd.depth).await?;
+    // render this tree in the requested format
+    let rendered = match cmd.format {
+        TreeFormat::Ascii => ascii(&tree),
+        TreeFormat::Dot => dot(&tree),
+        TreeFormat::Json => serde_json::to_string_pretty(&tree)?,
+    };
+    // write our tree to a file or stdout
+    match &cmd.output {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
@@ -6,11 +6,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use thorium::models::{
     AutoTag, AutoTagUpdate, ChildFilters, ChildFiltersUpdate, ChildrenDependencySettings,
-    ChildrenDependencySettingsUpdate, Cleanup, CleanupUpdate, Dependencies, DependenciesUpdate,
-    DependencySettingsUpdate, EphemeralDependencySettings, EphemeralDependencySettingsUpdate,
-    FilesHandler, FilesHandlerUpdate, Image, ImageArgs, ImageArgsUpdate, ImageBan, ImageBanUpdate,
-    ImageLifetime, ImageNetworkPolicyUpdate, ImageScaler, ImageUpdate, ImageVersion, Kvm,
-    KvmUpdate, OutputCollection, OutputCollectionUpdate, OutputDisplayType, RepoDependencySettings,
+    ChildrenDependencySettingsUpdate, Cleanup, CleanupUpdate, CpuArch, Dependencies,
+    DependenciesUpdate, DependencySettingsUpdate, EphemeralDependencySettings,
+    EphemeralDependencySettingsUpdate, FilesHandler, FilesHandlerUpdate, Image, ImageArgs,
+    ImageArgsUpdate, ImageBan, ImageBanUpdate, ImageLifetime, ImageNetworkPolicyUpdate,
+    ImageScaler, ImageUpdate, ImageVersion, Kvm, KvmUpdate, MicroVm, OutputCollection,
+    OutputCollectionUpdate, OutputDisplayType, PriorityClass, RepoDependencySettings,
     ResourcesUpdate, ResultDependencySettings, ResultDependencySettingsUpdate,
     SampleDependencySettings, SecurityContext, SecurityContextUpdate, SpawnLimits,
     TagDependencySettings, TagDependencySettingsUpdate, Volume,
@@ -48,6 +49,8 @@ struct EditableImage {
     pub resources: ResourcesUpdate,
     /// The limit to use for how many workers of this image type can be spawned
     pub spawn_limit: SpawnLimits,
+    /// How urgently this images workers should be scheduled compared to other work
+    pub priority: PriorityClass,
     /// The environment variables to set
     pub env: HashSet<String>,
     /// How long this image takes to execute on average in seconds (defaults to
@@ -67,6 +70,12 @@ struct EditableImage {
     pub collect_logs: bool,
     /// Whether this is a generator or not
     pub generator: bool,
+    /// Whether to capture this images network traffic for each job
+    pub capture_network: bool,
+    /// The CPU architecture this image is built for
+    pub arch: CpuArch,
+    /// Whether this image can run under qemu-user emulation
+    pub emulation: bool,
     /// How to handle dependencies for this image
     pub dependencies: Dependencies,
     /// The type of display class to use in the UI for this images output
@@ -79,6 +88,8 @@ struct EditableImage {
     pub clean_up: Option<Cleanup>,
     /// The settings to use for Kvm jobs
     pub kvm: Option<Kvm>,
+    /// The settings to use for microVM jobs
+    pub microvm: Option<MicroVm>,
     /// A list of reasons an image is banned mapped by ban UUID;
     /// if the list has any bans, the image cannot be spawned
     pub bans: HashMap<Uuid, ImageBan>,
@@ -107,12 +118,16 @@ impl PartialEq for EditableImage {
             && self.security_context == other.security_context
             && self.collect_logs == other.collect_logs
             && self.generator == other.generator
+            && self.capture_network == other.capture_network
+            && self.arch == other.arch
+            && self.emulation == other.emulation
             && self.dependencies == other.dependencies
             && self.display_type == other.display_type
             && self.output_collection == other.output_collection
             && self.child_filters == other.child_filters
             && self.clean_up == other.clean_up
             && self.kvm == other.kvm
+            && self.microvm == other.microvm
             && self.bans == other.bans
             && self.network_policies == other.network_policies
     }
@@ -137,6 +152,7 @@ impl From<Image> for EditableImage {
                 amd_gpu: Some(image.resources.amd_gpu),
             },
             spawn_limit: image.spawn_limit,
+            priority: image.priority,
             env: image
                 .env
                 .into_iter()
@@ -150,12 +166,16 @@ impl From<Image> for EditableImage {
             security_context: image.security_context,
             collect_logs: image.collect_logs,
             generator: image.generator,
+            capture_network: image.capture_network,
+            arch: image.arch,
+            emulation: image.emulation,
             dependencies: image.dependencies,
             display_type: image.display_type,
             output_collection: image.output_collection,
             child_filters: image.child_filters,
             clean_up: image.clean_up,
             kvm: image.kvm,
+            microvm: image.microvm,
             bans: image.bans,
             network_policies: image.network_policies,
         }
@@ -264,6 +284,7 @@ fn calculate_image_args_update(
             commit: set_modified_opt!(old_args.commit, new_args.commit),
             // TODO: template
             output: set_modified!(old_args.output, new_args.output),
+            resume: set_modified!(old_args.resume, new_args.resume),
         })
     }
 }
@@ -537,6 +558,8 @@ fn calculate_files_handler_update(
                 new_files_handler.result_files
             ),
             tags: set_modified!(old_files_handler.tags, new_files_handler.tags),
+            progress: set_modified!(old_files_handler.progress, new_files_handler.progress),
+            checkpoint: set_modified!(old_files_handler.checkpoint, new_files_handler.checkpoint),
             clear_names: set_clear_vec!(old_files_handler.names, new_files_handler.names),
             remove_names,
             add_names,
@@ -699,6 +722,9 @@ fn calculate_kvm_update(old_kvm: Option<Kvm>, new_kvm: Option<Kvm>) -> KvmUpdate
         (None, Some(new_kvm)) => KvmUpdate {
             xml: Some(new_kvm.xml),
             qcow2: Some(new_kvm.qcow2),
+            memory_state: new_kvm.memory_state,
+            clear_memory_state: false,
+            dump_memory: Some(new_kvm.dump_memory),
         },
         // TODO: we set it from some to none, so we ought to clear it, but there's currently no mechanism for that
         (Some(_), None) => KvmUpdate::default(),
@@ -709,6 +735,9 @@ fn calculate_kvm_update(old_kvm: Option<Kvm>, new_kvm: Option<Kvm>) -> KvmUpdate
                 KvmUpdate {
                     xml: set_modified!(old_kvm.xml, new_kvm.xml),
                     qcow2: set_modified!(old_kvm.qcow2, new_kvm.qcow2),
+                    clear_memory_state: set_clear!(old_kvm.memory_state, new_kvm.memory_state),
+                    memory_state: set_modified_opt!(old_kvm.memory_state, new_kvm.memory_state),
+                    dump_memory: set_modified!(old_kvm.dump_memory, new_kvm.dump_memory),
                 }
             }
         }
@@ -839,6 +868,7 @@ fn calculate_update(
         resources: set_modified!(image.resources, edited_image.resources),
         // TODO: template
         spawn_limit: set_modified!(image.spawn_limit, edited_image.spawn_limit),
+        priority: set_modified!(image.priority, edited_image.priority),
         add_volumes,
         remove_volumes,
         // TODO: template
@@ -862,6 +892,9 @@ fn calculate_update(
         ),
         collect_logs: set_modified!(image.collect_logs, edited_image.collect_logs),
         generator: set_modified!(image.generator, edited_image.generator),
+        capture_network: set_modified!(image.capture_network, edited_image.capture_network),
+        arch: set_modified!(image.arch, edited_image.arch),
+        emulation: set_modified!(image.emulation, edited_image.emulation),
         // TODO: template
         dependencies: calculate_dependencies_update(image.dependencies, edited_image.dependencies),
         // TODO: template
@@ -876,6 +909,8 @@ fn calculate_update(
         ),
         clean_up: calculate_clean_up_update(image.clean_up, edited_image.clean_up),
         kvm: calculate_kvm_update(image.kvm, edited_image.kvm),
+        clear_microvm: set_clear!(image.microvm, edited_image.microvm),
+        microvm: set_modified_opt!(image.microvm, edited_image.microvm),
         bans: calculate_bans_update(image.bans, edited_image.bans)?,
         network_policies: calculate_network_policies_update(
             image.network_policies,
@@ -0,0 +1,137 @@
+//! Handles notification inbox and subscription commands
+
+use itertools::Itertools;
+use thorium::models::Subscription;
+use thorium::{Error, Thorium};
+
+use crate::args::notifications::{
+    DeleteNotification, ListNotifications, ListSubscriptions, Notifications, Subscribe, Unsubscribe,
+};
+use crate::args::Args;
+use crate::utils;
+
+/// List the notifications in our inbox
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The list notifications command that was run
+async fn list(thorium: &Thorium, cmd: &ListNotifications) -> Result<(), Error> {
+    // get our notifications
+    let notifications = thorium.users.notifications().await?;
+    // print the notifications
+    utils::notifications::print_notifications(&notifications, cmd.opts.ids);
+    Ok(())
+}
+
+/// Delete a notification from our inbox
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The delete notification command that was run
+async fn delete(thorium: &Thorium, cmd: &DeleteNotification) -> Result<(), Error> {
+    thorium.users.delete_notification(&cmd.id).await?;
+    Ok(())
+}
+
+/// Delete all notifications from our inbox
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+async fn clear(thorium: &Thorium) -> Result<(), Error> {
+    thorium.users.clear_notifications().await?;
+    Ok(())
+}
+
+/// Subscribe to something in Thorium
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The subscribe command that was run
+async fn subscribe(thorium: &Thorium, cmd: &Subscribe) -> Result<(), Error> {
+    let req = cmd.build_req()?;
+    let resp = thorium.users.subscribe(&req).await?;
+    println!("Subscribed to {} with subscription {}", req.target, resp.id);
+    Ok(())
+}
+
+/// List our subscriptions
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The list subscriptions command that was run
+async fn subscriptions(thorium: &Thorium, cmd: &ListSubscriptions) -> Result<(), Error> {
+    let subscriptions = thorium.users.list_subscriptions().await?;
+    if cmd.json {
+        serde_json::to_writer_pretty(std::io::stdout(), &subscriptions)?;
+        println!();
+        return Ok(());
+    }
+    println!(
+        "{:<36} | {:<48} | {:<24} | {:<5}",
+        "ID", "TARGET", "EVENTS", "EMAIL"
+    );
+    println!("{:-<37}+{:-<50}+{:-<26}+{:-<6}", "", "", "", "");
+    for Subscription {
+        id,
+        target,
+        events,
+        email,
+        ..
+    } in &subscriptions
+    {
+        // an empty list of events means all events
+        let events = if events.is_empty() {
+            "All".to_owned()
+        } else {
+            events.iter().map(|event| format!("{event:?}")).join(",")
+        };
+        println!(
+            "{:<36} | {:<48} | {:<24} | {:<5}",
+            id,
+            target.to_string(),
+            events,
+            email
+        );
+    }
+    Ok(())
+}
+
+/// Delete one of our subscriptions
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The unsubscribe command that was run
+async fn unsubscribe(thorium: &Thorium, cmd: &Unsubscribe) -> Result<(), Error> {
+    thorium.users.unsubscribe(&cmd.id).await?;
+    Ok(())
+}
+
+/// Handle all notification commands
+///
+/// # Arguments
+///
+/// * `args` - The arguments passed to Thorctl
+/// * `cmd` - The notifications command to execute
+pub async fn handle(args: &Args, cmd: &Notifications) -> Result<(), Error> {
+    // load our config and instance our client
+    let (conf, thorium) = utils::get_client(args).await?;
+    // warn about insecure connections if not set to skip
+    if !conf.skip_insecure_warning.unwrap_or_default() {
+        utils::warn_insecure_conf(&conf)?;
+    }
+    // call the right notifications handler
+    match cmd {
+        Notifications::List(cmd) => list(&thorium, cmd).await,
+        Notifications::Delete(cmd) => delete(&thorium, cmd).await,
+        Notifications::Clear => clear(&thorium).await,
+        Notifications::Subscribe(cmd) => subscribe(&thorium, cmd).await,
+        Notifications::Subscriptions(cmd) => subscriptions(&thorium, cmd).await,
+        Notifications::Unsubscribe(cmd) => unsubscribe(&thorium, cmd).await,
+    }
+}
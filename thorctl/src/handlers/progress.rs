@@ -1,8 +1,12 @@
 //! Progress bar utilities
 
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use chrono::Utc;
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
+use std::collections::HashMap;
 use std::{borrow::Cow, time::Duration};
+use thorium::models::{JobProgress, Reaction};
+use uuid::Uuid;
 
 use crate::errors::Errors;
 
@@ -19,6 +23,8 @@ pub enum BarKind {
     /// An IO bar
     #[allow(dead_code)]
     IO,
+    /// A bar showing a percent complete
+    Percent,
 }
 
 impl BarKind {
@@ -58,6 +64,17 @@ impl BarKind {
                 // start this bars progress at 0
                 bar.set_position(0);
             }
+            BarKind::Percent => {
+                // build our style string
+                let style = format!(
+                    "[{{elapsed_precise}}] {name} {{bar:40.cyan/blue}} {{pos:>3}}% {{msg}}"
+                );
+                // set the style for our bar
+                bar.set_style(ProgressStyle::with_template(&style).unwrap());
+                // percents are always out of 100
+                bar.set_length(100);
+                bar.set_position(0);
+            }
             BarKind::UnboundIO | BarKind::IO => {
                 // build our style string
                 let style = format!(
@@ -107,6 +124,101 @@ impl MultiBar {
         self.multi.println(msg)?;
         Ok(())
     }
+
+    /// Remove a child progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `bar` - The bar to remove
+    pub fn remove(&self, bar: &Bar) {
+        self.multi.remove(&bar.bar);
+    }
+
+    /// Hide our progress bars while running a function that writes to the terminal
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function to run
+    pub fn suspend<F: FnOnce() -> R, R>(&self, func: F) -> R {
+        self.multi.suspend(func)
+    }
+}
+
+/// Live progress bars for the running jobs in a reaction
+#[derive(Default)]
+pub struct JobProgressBars {
+    /// The controller for our job progress bars
+    pub multi: MultiBar,
+    /// The progress bar for each job that has reported progress
+    bars: HashMap<Uuid, Bar>,
+}
+
+impl JobProgressBars {
+    /// Create job progress bars that share an existing progress bar controller
+    ///
+    /// # Arguments
+    ///
+    /// * `multi` - The controller to add our job progress bars to
+    pub fn new(multi: MultiBar) -> Self {
+        JobProgressBars {
+            multi,
+            bars: HashMap::default(),
+        }
+    }
+
+    /// Update our progress bars with the latest progress from a reaction
+    ///
+    /// # Arguments
+    ///
+    /// * `reaction` - The reaction to show progress for
+    pub fn update(&mut self, reaction: &Reaction) {
+        // remove the bars for any jobs that are no longer running
+        self.bars.retain(|job, bar| {
+            let running = reaction.job_progress.contains_key(job);
+            if !running {
+                bar.finish_and_clear();
+                self.multi.remove(bar);
+            }
+            running
+        });
+        for (job, progress) in &reaction.job_progress {
+            // get this jobs bar or add a new one
+            let bar = self.bars.entry(*job).or_insert_with(|| {
+                let name = format!("{} ({})", progress.stage, &job.to_string()[..8]);
+                self.multi.add(&name, BarKind::Percent)
+            });
+            if let Some(percent) = progress.percent {
+                bar.set_position(percent.round() as u64);
+            }
+            bar.set_message(Self::message(progress));
+        }
+    }
+
+    /// Build the message to show next to a jobs progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The progress to build a message for
+    fn message(progress: &JobProgress) -> String {
+        let mut msg = progress.phase.clone().unwrap_or_default();
+        // add how much time this job expects to still need
+        if let Some(eta) = progress.eta {
+            let left = (eta - Utc::now()).to_std().unwrap_or_default();
+            if !msg.is_empty() {
+                msg.push_str(" - ");
+            }
+            msg.push_str(&format!("{} remaining", HumanDuration(left)));
+        }
+        msg
+    }
+
+    /// Clear all of our progress bars
+    pub fn clear(&mut self) {
+        for (_, bar) in self.bars.drain() {
+            bar.finish_and_clear();
+            self.multi.remove(&bar);
+        }
+    }
 }
 
 /// A single progress bar in Thorctl
@@ -12,7 +12,7 @@ use thorium::Thorium;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
-use super::progress::Bar;
+use super::progress::{Bar, BarKind, JobProgressBars, MultiBar};
 use super::update;
 use crate::args::reactions::{
     DescribeReactions, GetReactions, LogsReactions, ReactionTarget, Reactions,
@@ -280,6 +280,68 @@ async fn get(thorium: &Thorium, cmd: &GetReactions) -> Result<(), Error> {
     Ok(())
 }
 
+/// Build the message to show for a reaction being watched
+///
+/// # Arguments
+///
+/// * `reaction` - The reaction to build a message for
+fn watch_message(reaction: &Reaction) -> String {
+    format!(
+        "{} - stage {} ({}/{} jobs complete)",
+        reaction.status,
+        reaction.current_stage + 1,
+        reaction.current_stage_progress,
+        reaction.current_stage_length
+    )
+}
+
+/// Show live progress for reactions until they complete
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The describe reactions command to execute
+async fn watch(thorium: &Thorium, cmd: &DescribeReactions) -> Result<(), Error> {
+    // get the reactions to watch
+    let mut watched = Vec::with_capacity(cmd.reactions.len());
+    let multi = MultiBar::default();
+    for raw in &cmd.reactions {
+        let reaction = ReactionTarget::try_from(raw)?.get_reaction(thorium).await?;
+        // add a bar for this reaction and its jobs
+        let bar = multi.add(
+            &format!("{} ({})", reaction.id, reaction.pipeline),
+            BarKind::Timer,
+        );
+        bar.set_message(watch_message(&reaction));
+        let jobs = JobProgressBars::new(multi.clone());
+        watched.push((reaction.group, reaction.id, bar, jobs));
+    }
+    // poll our reactions until they are all complete
+    while !watched.is_empty() {
+        let mut running = Vec::with_capacity(watched.len());
+        for (group, id, bar, mut jobs) in watched {
+            let reaction = thorium.reactions.get(&group, &id).await?;
+            if matches!(
+                reaction.status,
+                ReactionStatus::Completed | ReactionStatus::Failed
+            ) {
+                // this reaction is complete so stop watching it
+                jobs.clear();
+                bar.finish_with_message(reaction.status.to_string());
+            } else {
+                bar.set_message(watch_message(&reaction));
+                jobs.update(&reaction);
+                running.push((group, id, bar, jobs));
+            }
+        }
+        watched = running;
+        if !watched.is_empty() {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    }
+    Ok(())
+}
+
 /// Describe reactions by displaying/saving all of their JSON-formatted details
 ///
 /// # Arguments
@@ -287,6 +349,10 @@ async fn get(thorium: &Thorium, cmd: &GetReactions) -> Result<(), Error> {
 /// * `thorium` - The Thorium client
 /// * `cmd` - The describe reactions command to execute
 async fn describe(thorium: &Thorium, cmd: &DescribeReactions) -> Result<(), Error> {
+    // show live progress instead if we are watching these reactions
+    if cmd.watch {
+        return watch(thorium, cmd).await;
+    }
     cmd.describe(thorium).await
 }
 
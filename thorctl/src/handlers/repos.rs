@@ -17,6 +17,7 @@ use tokio_stream::wrappers::LinesStream;
 
 mod download;
 mod ingest;
+mod mirrors;
 
 use self::ingest::{IngestJob, IngestWorker};
 
@@ -740,6 +741,7 @@ pub async fn handle(args: &Args, cmd: &Repos) -> Result<(), Error> {
         Repos::Download(cmd) => download(&thorium, cmd, args, &conf).await,
         Repos::Compile(cmd) => compile(thorium, cmd).await,
         Repos::Contributors(cmd) => contributors(&thorium, cmd).await,
+        Repos::Mirrors(cmd) => mirrors::handle(thorium, cmd).await,
     }
 }
 
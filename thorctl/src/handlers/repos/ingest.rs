@@ -255,6 +255,8 @@ fn crawl_all(
 ) -> Result<(), Error> {
     // open our untarred repo as a git repo
     let repo = gix::open(&untarred.path)?;
+    // also open it with git2 so we can diff commits
+    let git = git2::Repository::open(&untarred.path)?;
     // track what commits we have already seen
     let mut commits: HashSet<_> = HashSet::with_capacity(1000);
     // get an iter over the references in this repo
@@ -288,8 +290,9 @@ fn crawl_all(
                 let commit = info.object().unwrap();
                 // get this commits hash
                 let hash = info.id.to_string();
-                // convert our commit to a commit request
-                let commit_req = CommitRequest::new_gix(commit);
+                // convert our commit to a commit request with its changed paths
+                let commit_req = CommitRequest::new_gix(commit)
+                    .changed_paths(&git, git2::Oid::from_str(&hash)?)?;
                 // send this commit to our ingestor
                 tx.send((hash, CommitishRequest::Commit(commit_req)))?;
                 // add this commit to our ingested commits
@@ -317,7 +320,9 @@ fn crawl_specific(
     bar: Bar,
 ) -> Result<(), Error> {
     // open our untarred repo as a git repo
-    let repo = gix::open(untarred.path).unwrap();
+    let repo = gix::open(&untarred.path).unwrap();
+    // also open it with git2 so we can diff commits
+    let git = git2::Repository::open(&untarred.path)?;
     // track what commits we have already seen
     let mut commits: HashSet<_> = HashSet::with_capacity(1000);
     // build a list of reference ids
@@ -335,8 +340,9 @@ fn crawl_specific(
             let commit = info.object().unwrap();
             // get this commits hash
             let hash = info.id.to_string();
-            // convert our commit to a commit request
-            let commit_req = CommitRequest::new_gix(commit);
+            // convert our commit to a commit request with its changed paths
+            let commit_req =
+                CommitRequest::new_gix(commit).changed_paths(&git, git2::Oid::from_str(&hash)?)?;
             // send this commit to our ingestor
             tx.send((hash, CommitishRequest::Commit(commit_req)))?;
             // add this commit to our ingested commits
@@ -378,6 +384,7 @@ impl CommitishIngestor {
                 earliest: None,
                 end: false,
                 commitishes: HashMap::with_capacity(500),
+                trigger_depth: 0,
             })
             .collect();
         // build our commit ingestor
@@ -421,6 +428,7 @@ impl CommitishIngestor {
                         earliest: None,
                         end: false,
                         commitishes: HashMap::with_capacity(500),
+                        trigger_depth: 0,
                     }
                 }
                 None => {
@@ -430,6 +438,7 @@ impl CommitishIngestor {
                         earliest: None,
                         end: false,
                         commitishes: HashMap::with_capacity(500),
+                        trigger_depth: 0,
                     }
                 }
             },
@@ -0,0 +1,162 @@
+//! Handle repo mirror related commands
+
+use thorium::models::{RepoCheckout, RepoMirror, RepoMirrorRequest};
+use thorium::{Error, Thorium};
+
+use crate::args::repos::{AddRepoMirror, RepoMirrorTarget, RepoMirrors};
+
+struct RepoMirrorLine;
+
+impl RepoMirrorLine {
+    /// Print this log lines header
+    pub fn header() {
+        println!(
+            "{:<50} | {:<8} | {:<25} | {:<25} | {:<8} | {:<30}",
+            "URL", "INTERVAL", "LAST FETCH", "NEXT FETCH", "INGESTED", "GROUPS",
+        );
+        println!(
+            "{:-<51}+{:-<10}+{:-<27}+{:-<27}+{:-<10}+{:-<30}",
+            "", "", "", "", "", ""
+        );
+    }
+
+    /// Print a repo mirror's info
+    ///
+    /// # Arguments
+    ///
+    /// * `mirror` - The repo mirror to print
+    pub fn print_mirror(mirror: &RepoMirror) {
+        // get when this mirror was last fetched if it ever was
+        let last_fetch = mirror
+            .last_fetch
+            .map_or_else(|| "-".to_owned(), |fetched| fetched.to_string());
+        println!(
+            "{:<50} | {:<8} | {:<25} | {:<25} | {:<8} | {:<30}",
+            mirror.url,
+            mirror.interval,
+            last_fetch,
+            mirror.next_fetch.to_string(),
+            mirror.ingested,
+            mirror.groups.join(","),
+        );
+    }
+}
+
+/// Print the full status of a repo mirror
+///
+/// # Arguments
+///
+/// * `mirror` - The repo mirror to print
+fn print_status(mirror: &RepoMirror) {
+    RepoMirrorLine::header();
+    RepoMirrorLine::print_mirror(mirror);
+    println!("\nRemote: {}", mirror.remote);
+    println!("Authenticated: {}", mirror.authenticated);
+    // print the error from the last fetch if it failed
+    if let Some(error) = &mirror.last_error {
+        println!("Last Error: {error}");
+    }
+    // print the refs we know about for this mirror
+    if !mirror.refs.is_empty() {
+        println!("\nRefs:");
+        let mut refs = mirror.refs.iter().collect::<Vec<_>>();
+        refs.sort_unstable();
+        for (name, commit) in refs {
+            println!("  {commit} {name}");
+        }
+    }
+}
+
+/// Start mirroring a remote repo
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The add repo mirror command that was run
+async fn add(thorium: Thorium, cmd: &AddRepoMirror) -> Result<(), Error> {
+    // build our repo mirror request
+    let mut req = RepoMirrorRequest::new(&cmd.remote, cmd.groups.clone()).interval(cmd.interval);
+    // get our credentials if we were given a username
+    if let Some(username) = &cmd.username {
+        // prompt for this users token if we didn't get it already
+        let token = match &cmd.token {
+            Some(token) => token.to_owned(),
+            None => rpassword::prompt_password("Token: ")?,
+        };
+        req = req.credentials(username, token);
+    }
+    // set our default checkout branch if one was set
+    if let Some(branch) = &cmd.branch {
+        req = req.default_checkout(RepoCheckout::branch(branch));
+    }
+    // start mirroring this repo
+    let mirror = thorium.repos.create_mirror(&req).await?;
+    println!("Mirroring {} from {}", mirror.url, mirror.remote);
+    Ok(())
+}
+
+/// List the repo mirrors we can see
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+async fn list(thorium: Thorium) -> Result<(), Error> {
+    let mirrors = thorium.repos.list_mirrors().await?;
+    RepoMirrorLine::header();
+    for mirror in &mirrors {
+        RepoMirrorLine::print_mirror(mirror);
+    }
+    Ok(())
+}
+
+/// Get the status of a repo mirror
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The repo mirror status command that was run
+async fn status(thorium: Thorium, cmd: &RepoMirrorTarget) -> Result<(), Error> {
+    let mirror = thorium.repos.get_mirror(&cmd.url).await?;
+    print_status(&mirror);
+    Ok(())
+}
+
+/// Fetch a repo mirror as soon as possible
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The repo mirror sync command that was run
+async fn sync(thorium: Thorium, cmd: &RepoMirrorTarget) -> Result<(), Error> {
+    thorium.repos.sync_mirror(&cmd.url).await?;
+    println!("{} will be fetched on the next mirror check", cmd.url);
+    Ok(())
+}
+
+/// Stop mirroring a repo
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The repo mirror delete command that was run
+async fn delete(thorium: Thorium, cmd: &RepoMirrorTarget) -> Result<(), Error> {
+    thorium.repos.delete_mirror(&cmd.url).await?;
+    println!("Stopped mirroring {}", cmd.url);
+    Ok(())
+}
+
+/// Handle repo mirror commands
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The repo mirrors sub command that was run
+pub async fn handle(thorium: Thorium, cmd: &RepoMirrors) -> Result<(), Error> {
+    match cmd {
+        RepoMirrors::Add(cmd) => add(thorium, cmd).await,
+        RepoMirrors::List => list(thorium).await,
+        RepoMirrors::Status(cmd) => status(thorium, cmd).await,
+        RepoMirrors::Sync(cmd) => sync(thorium, cmd).await,
+        RepoMirrors::Delete(cmd) => delete(thorium, cmd).await,
+    }
+}
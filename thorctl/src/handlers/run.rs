@@ -18,6 +18,7 @@ use tokio::time::{sleep, Duration};
 use tokio::{io::AsyncWriteExt, sync::mpsc};
 use uuid::Uuid;
 
+use super::progress::{JobProgressBars, MultiBar};
 use super::update;
 use crate::args::repos::RepoTarget;
 use crate::args::run::Run;
@@ -37,19 +38,28 @@ const REFRESH_RATE: Duration = Duration::from_secs(1);
 /// * `group` - The group in which the reaction is being run
 /// * `id` - The UUID of the running reaction
 /// * `complete` - Signals that the reaction is complete
+/// * `bars` - The progress bars to show each running jobs progress with
 async fn watch_reaction_complete(
     thorium: Arc<Thorium>,
     group: String,
     id: Uuid,
     complete: Arc<AtomicBool>,
+    mut bars: JobProgressBars,
 ) -> Result<(), Error> {
     // Wait until the reaction is complete
-    while !matches!(
-        thorium.reactions.get(&group, &id).await?.status,
-        ReactionStatus::Completed | ReactionStatus::Failed
-    ) {
+    loop {
+        let reaction = thorium.reactions.get(&group, &id).await?;
+        if matches!(
+            reaction.status,
+            ReactionStatus::Completed | ReactionStatus::Failed
+        ) {
+            break;
+        }
+        // show the latest progress for any running jobs
+        bars.update(&reaction);
         sleep(REFRESH_RATE).await;
     }
+    bars.clear();
     // Notify the listeners
     complete.store(true, Ordering::Relaxed);
     Ok(())
@@ -100,7 +110,11 @@ async fn forward_logs(
 /// # Arguments
 ///
 /// * `log_rx` - The channel to poll for log information
-async fn log(mut log_rx: mpsc::Receiver<(String, String)>) -> HashMap<String, Rgb> {
+/// * `multi` - The progress bars to print logs above
+async fn log(
+    mut log_rx: mpsc::Receiver<(String, String)>,
+    multi: MultiBar,
+) -> HashMap<String, Rgb> {
     // create a vector of shuffled colors to highlight different stages in a reaction
     let colors = {
         let mut colors = vec![
@@ -133,7 +147,7 @@ async fn log(mut log_rx: mpsc::Receiver<(String, String)>) -> HashMap<String, Rg
         });
         // print the log
         let label = format!("{stage}:");
-        println!("\r{} {}", label.color(*color).bold(), line);
+        multi.suspend(|| println!("\r{} {}", label.color(*color).bold(), line));
     }
     stage_colors
 }
@@ -407,16 +421,20 @@ async fn run(thorium: Arc<Thorium>, cmd: &Run) -> Result<(), Error> {
     }
     // drop the extra logs channel to ensure it doesn't keep their respective channels open
     drop(log_tx);
+    // build the progress bars to show each running jobs progress with
+    let bars = JobProgressBars::default();
+    let multi = bars.multi.clone();
     // continuously check if the reaction is complete in a new thread
     tokio::spawn(watch_reaction_complete(
         thorium.clone(),
         group.clone(),
         reaction.id,
         complete.clone(),
+        bars,
     ));
     // log all information in the channel until the reaction is complete
     // save the tool colors to use when printing result logs
-    let tool_colors = log(log_rx).await;
+    let tool_colors = log(log_rx, multi).await;
     println!("Reaction {} complete!", reaction.id.bright_green().bold());
     // write the results of the reaction to disk
     write_results(&thorium, cmd, pipeline, tool_colors, &run_mode).await?;
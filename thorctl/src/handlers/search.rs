@@ -0,0 +1,246 @@
+//! Handles search commands
+
+use std::collections::HashSet;
+use std::io::{BufWriter, Write};
+
+use http::StatusCode;
+use thorium::models::{ElasticDoc, SavedSearch};
+use thorium::{Error, Thorium};
+
+use crate::args::search::{
+    CreateSavedSearch, DeleteSavedSearches, ListSavedSearches, SavedSearches, Search,
+    SearchCommands, SearchOutput,
+};
+use crate::args::Args;
+use crate::utils;
+
+/// Escape a single CSV field
+///
+/// # Arguments
+///
+/// * `field` - The field to escape
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Writes search results in a specific format
+struct SearchWriter {
+    /// The format to write documents in
+    format: SearchOutput,
+    /// Where to write documents to
+    out: Box<dyn Write>,
+    /// The items we have already written when only writing unique items
+    seen: HashSet<String>,
+}
+
+impl SearchWriter {
+    /// Create a new search writer
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The search command that was run
+    fn new(cmd: &Search) -> Result<Self, Error> {
+        // write to a file if one was given otherwise write to stdout
+        let out: Box<dyn Write> = match &cmd.output {
+            Some(path) => Box::new(BufWriter::new(std::fs::File::create(path)?)),
+            None => Box::new(BufWriter::new(std::io::stdout())),
+        };
+        Ok(SearchWriter {
+            format: cmd.format,
+            out,
+            seen: HashSet::default(),
+        })
+    }
+
+    /// Write the header for our format if it has one
+    fn header(&mut self) -> Result<(), Error> {
+        match self.format {
+            SearchOutput::Table => {
+                writeln!(
+                    self.out,
+                    "{:<64} | {:<24} | {:<14} | {:<8}",
+                    "SHA256/REPO", "GROUP", "INDEX", "SCORE"
+                )?;
+                writeln!(self.out, "{:-<65}+{:-<26}+{:-<16}+{:-<9}", "", "", "", "")?;
+            }
+            SearchOutput::Csv => writeln!(self.out, "item,group,index,score")?,
+            SearchOutput::Jsonl | SearchOutput::Sha256 => (),
+        }
+        Ok(())
+    }
+
+    /// Write a single document
+    ///
+    /// # Arguments
+    ///
+    /// * `doc` - The document to write
+    fn write(&mut self, doc: &ElasticDoc) -> Result<(), Error> {
+        // get the score for this document or an empty string
+        let score = doc.score.map(|score| score.to_string()).unwrap_or_default();
+        match self.format {
+            SearchOutput::Table => writeln!(
+                self.out,
+                "{:<64} | {:<24} | {:<14} | {:<8}",
+                doc.item(),
+                doc.group().unwrap_or("-"),
+                doc.index,
+                score
+            )?,
+            SearchOutput::Jsonl => {
+                serde_json::to_writer(&mut self.out, doc)?;
+                writeln!(self.out)?;
+            }
+            SearchOutput::Csv => writeln!(
+                self.out,
+                "{},{},{},{}",
+                csv_escape(doc.item()),
+                csv_escape(doc.group().unwrap_or_default()),
+                csv_escape(&doc.index),
+                score
+            )?,
+            SearchOutput::Sha256 => {
+                // the same item can match in multiple groups so only write it once
+                if self.seen.insert(doc.item().to_owned()) {
+                    writeln!(self.out, "{}", doc.item())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush any buffered output
+    fn flush(&mut self) -> Result<(), Error> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Search Thorium and write out every matching document
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The search command that was run
+async fn search(thorium: &Thorium, cmd: &Search) -> Result<(), Error> {
+    // get our saved search if we are running one
+    let saved = match &cmd.saved {
+        Some(name) => Some(thorium.search.get_saved(name).await?),
+        None => None,
+    };
+    // build the options to search with
+    let opts = cmd.build_opts(saved)?;
+    let mut writer = SearchWriter::new(cmd)?;
+    writer.header()?;
+    // crawl over our cursor until its exhausted
+    let mut cursor = thorium.search.search(&opts).await?;
+    loop {
+        for doc in &cursor.data {
+            writer.write(doc)?;
+        }
+        // flush each page so results can be streamed into other commands
+        writer.flush()?;
+        // check if this cursor has been exhausted
+        if cursor.exhausted() {
+            break;
+        }
+        // get the next page of data
+        cursor.refill().await?;
+    }
+    Ok(())
+}
+
+/// Save a search
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The create saved search command that was run
+async fn create_saved(thorium: &Thorium, cmd: &CreateSavedSearch) -> Result<(), Error> {
+    let req = cmd.build_req()?;
+    // remove any existing search with this name if we are replacing it
+    if cmd.force {
+        match thorium.search.delete_saved(&cmd.name).await {
+            Ok(_) => (),
+            Err(err) if err.status() == Some(StatusCode::NOT_FOUND) => (),
+            Err(err) => return Err(err),
+        }
+    }
+    let saved = thorium.search.create_saved(&req).await?;
+    println!("Saved search {}: {}", saved.name, saved.query);
+    Ok(())
+}
+
+/// List our saved searches
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The list saved searches command that was run
+async fn list_saved(thorium: &Thorium, cmd: &ListSavedSearches) -> Result<(), Error> {
+    let saved = thorium.search.list_saved().await?;
+    if cmd.json {
+        serde_json::to_writer_pretty(std::io::stdout(), &saved)?;
+        println!();
+        return Ok(());
+    }
+    println!("{:<32} | {:<40} | {:<36}", "NAME", "QUERY", "DESCRIPTION");
+    println!("{:-<33}+{:-<42}+{:-<37}", "", "", "");
+    for SavedSearch {
+        name,
+        query,
+        description,
+        ..
+    } in &saved
+    {
+        println!(
+            "{:<32} | {:<40} | {:<36}",
+            name,
+            query,
+            description.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+/// Delete saved searches
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The delete saved searches command that was run
+async fn delete_saved(thorium: &Thorium, cmd: &DeleteSavedSearches) -> Result<(), Error> {
+    for name in &cmd.names {
+        thorium.search.delete_saved(name).await?;
+        println!("Deleted saved search {name}");
+    }
+    Ok(())
+}
+
+/// Handle all search commands
+///
+/// # Arguments
+///
+/// * `args` - The arguments passed to Thorctl
+/// * `cmd` - The search command to execute
+pub async fn handle(args: &Args, cmd: &Search) -> Result<(), Error> {
+    // load our config and instance our client
+    let (conf, thorium) = utils::get_client(args).await?;
+    // warn about insecure connections if not set to skip and we aren't piping results to stdout
+    let piped = cmd.cmd.is_none() && cmd.output.is_none() && cmd.format != SearchOutput::Table;
+    if !conf.skip_insecure_warning.unwrap_or_default() && !piped {
+        utils::warn_insecure_conf(&conf)?;
+    }
+    // call the right search handler
+    match &cmd.cmd {
+        Some(SearchCommands::Saved(saved)) => match saved {
+            SavedSearches::Create(cmd) => create_saved(&thorium, cmd).await,
+            SavedSearches::List(cmd) => list_saved(&thorium, cmd).await,
+            SavedSearches::Delete(cmd) => delete_saved(&thorium, cmd).await,
+        },
+        None => search(&thorium, cmd).await,
+    }
+}
@@ -5,17 +5,22 @@ use futures::stream::{self, StreamExt};
 use http::StatusCode;
 use owo_colors::OwoColorize;
 use thorium::{
-    models::{Repo, Sample, TagDeleteRequest, TagRequest},
+    models::{
+        ImageVersion, Repo, Sample, TagDeleteRequest, TagProvenance, TagProvenanceParams,
+        TagRequest,
+    },
     Error, Thorium,
 };
 
 use crate::args::{
-    tags::{AddTags, DeleteTags, GetTags, Tags},
+    tags::{AddTags, DeleteTags, GetTagProvenance, GetTags, Tags},
     Mode,
 };
 use crate::args::{Args, SearchParameterized};
 use crate::utils;
 
+mod bulk;
+
 /// Get a list of tags for a file or repo
 ///
 /// # Arguments
@@ -62,6 +67,92 @@ async fn get(thorium: Thorium, cmd: &GetTags) -> Result<(), Error> {
     Ok(())
 }
 
+struct TagProvenanceLine;
+
+impl TagProvenanceLine {
+    /// Print this log lines header
+    pub fn header() {
+        println!(
+            "{:<20} | {:<20} | {:<30} | {:<6} | {:<16} | {:<24} | {:<10} | {:<36}",
+            "GROUP", "KEY", "VALUE", "SOURCE", "USER", "TOOL", "CONFIDENCE", "RESULT",
+        );
+        println!(
+            "{:-<21}+{:-<22}+{:-<32}+{:-<8}+{:-<18}+{:-<26}+{:-<12}+{:-<36}",
+            "", "", "", "", "", "", "", ""
+        );
+    }
+
+    /// Print where a single tag came from
+    ///
+    /// # Arguments
+    ///
+    /// * `prov` - The tag provenance to print
+    pub fn print_provenance(prov: &TagProvenance) {
+        // build our tool name with its version if we have one
+        let tool = match (&prov.tool, &prov.tool_version) {
+            (Some(tool), Some(ImageVersion::SemVer(version))) => format!("{tool}:{version}"),
+            (Some(tool), Some(ImageVersion::Custom(version))) => format!("{tool}:{version}"),
+            (Some(tool), None) => tool.clone(),
+            (None, _) => "-".to_owned(),
+        };
+        let confidence = prov
+            .confidence
+            .map_or_else(|| "-".to_owned(), |confidence| confidence.to_string());
+        let result = prov
+            .result
+            .map_or_else(|| "-".to_owned(), |result| result.to_string());
+        println!(
+            "{:<20} | {:<20} | {:<30} | {:<6} | {:<16} | {:<24} | {:<10} | {:<36}",
+            prov.group,
+            prov.key,
+            prov.value,
+            prov.source.to_string(),
+            prov.user,
+            tool,
+            confidence,
+            result,
+        );
+    }
+}
+
+/// Show who or what set the tags on a file or repo
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The tag provenance command that was run
+async fn provenance(thorium: Thorium, cmd: &GetTagProvenance) -> Result<(), Error> {
+    // build the params to filter our tag provenance with
+    let mut params = TagProvenanceParams::default();
+    params.groups.clone_from(&cmd.groups);
+    params.source = cmd.source;
+    params.min_confidence = cmd.min_confidence;
+    // check if we were given a sha256 or a repo
+    let mode = Mode::try_from(&cmd.sha256_or_repo)?;
+    // get tag provenance depending on the mode
+    let mut provenance = match mode {
+        Mode::File => {
+            thorium
+                .files
+                .tag_provenance(&cmd.sha256_or_repo, &params)
+                .await?
+        }
+        Mode::Repo => {
+            thorium
+                .repos
+                .tag_provenance(&cmd.sha256_or_repo, &params)
+                .await?
+        }
+    };
+    // sort our tags by key, then value, then group
+    provenance.sort_by(|a, b| (&a.key, &a.value, &a.group).cmp(&(&b.key, &b.value, &b.group)));
+    TagProvenanceLine::header();
+    for prov in &provenance {
+        TagProvenanceLine::print_provenance(prov);
+    }
+    Ok(())
+}
+
 /// Attempt to condense raw tags to a single tag request
 macro_rules! raw_tags_to_req {
     ($cmd:expr, $tags:ident, $groups:ident, $build:ident) => {
@@ -310,5 +401,7 @@ pub async fn handle(args: &Args, cmd: &Tags) -> Result<(), Error> {
         Tags::Get(cmd) => get(thorium, cmd).await,
         Tags::Add(cmd) => add(thorium, cmd).await,
         Tags::Delete(cmd) => delete(thorium, cmd).await,
+        Tags::Provenance(cmd) => provenance(thorium, cmd).await,
+        Tags::Bulk(cmd) => bulk::handle(thorium, cmd).await,
     }
 }
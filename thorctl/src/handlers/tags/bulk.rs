@@ -0,0 +1,162 @@
+//! Handle bulk tag related commands
+
+use std::time::Duration;
+
+use thorium::models::{BulkTagJob, BulkTagRequest};
+use thorium::{Error, Thorium};
+
+use crate::args::tags::{BulkTagJobId, BulkTagOpts, BulkTags};
+
+/// How long to wait between checks on a bulk tag job we are waiting for
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+struct BulkTagJobLine;
+
+impl BulkTagJobLine {
+    /// Print this log lines header
+    pub fn header() {
+        println!(
+            "{:<36} | {:<10} | {:<7} | {:<10} | {:<10} | {:<50}",
+            "ID", "STATUS", "DRY RUN", "MATCHED", "MODIFIED", "OPERATION",
+        );
+        println!(
+            "{:-<37}+{:-<12}+{:-<9}+{:-<12}+{:-<12}+{:-<50}",
+            "", "", "", "", "", ""
+        );
+    }
+
+    /// Print a bulk tag job's info
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The bulk tag job to print
+    pub fn print_job(job: &BulkTagJob) {
+        println!(
+            "{:<36} | {:<10} | {:<7} | {:<10} | {:<10} | {} {} in {}",
+            job.id,
+            job.status.to_string(),
+            job.dry_run,
+            job.matched,
+            job.modified,
+            job.tag_type,
+            job.operation,
+            job.groups.join(","),
+        );
+    }
+}
+
+/// Print the full status of a bulk tag job
+///
+/// # Arguments
+///
+/// * `job` - The bulk tag job to print
+fn print_status(job: &BulkTagJob) {
+    BulkTagJobLine::header();
+    BulkTagJobLine::print_job(job);
+    // print any errors this job ran into
+    if !job.errors.is_empty() {
+        println!("\nErrors:");
+        for error in &job.errors {
+            println!("  {error}");
+        }
+    }
+}
+
+/// Start a bulk tag job and optionally wait for it to finish
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `req` - The bulk tag request to send
+/// * `opts` - The options for this bulk tag job
+async fn start(thorium: Thorium, mut req: BulkTagRequest, opts: &BulkTagOpts) -> Result<(), Error> {
+    // apply our options to this request
+    req.groups.clone_from(&opts.groups);
+    if opts.repos {
+        req = req.repos();
+    }
+    if opts.dry_run {
+        req = req.dry_run();
+    }
+    // start this bulk tag job
+    let mut job = thorium.tags.bulk(&req).await?;
+    if !opts.wait {
+        println!("Started bulk tag job {}", job.id);
+        return Ok(());
+    }
+    // poll this job until it finishes
+    while !job.is_finished() {
+        println!(
+            "{}: {} matched, {} modified",
+            job.status, job.matched, job.modified
+        );
+        tokio::time::sleep(POLL_INTERVAL).await;
+        job = thorium.tags.get_bulk(&job.id).await?;
+    }
+    print_status(&job);
+    Ok(())
+}
+
+/// List the bulk tag jobs we can see
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+async fn list(thorium: Thorium) -> Result<(), Error> {
+    let jobs = thorium.tags.list_bulk().await?;
+    BulkTagJobLine::header();
+    for job in &jobs {
+        BulkTagJobLine::print_job(job);
+    }
+    Ok(())
+}
+
+/// Get the status of a bulk tag job
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The bulk tag status command that was run
+async fn status(thorium: Thorium, cmd: &BulkTagJobId) -> Result<(), Error> {
+    let job = thorium.tags.get_bulk(&cmd.id).await?;
+    print_status(&job);
+    Ok(())
+}
+
+/// Cancel a bulk tag job
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The bulk tag cancel command that was run
+async fn cancel(thorium: Thorium, cmd: &BulkTagJobId) -> Result<(), Error> {
+    thorium.tags.cancel_bulk(&cmd.id).await?;
+    println!("Cancelling bulk tag job {}", cmd.id);
+    Ok(())
+}
+
+/// Handle bulk tag commands
+///
+/// # Arguments
+///
+/// * `thorium` - The Thorium client
+/// * `cmd` - The bulk tags sub command that was run
+pub async fn handle(thorium: Thorium, cmd: &BulkTags) -> Result<(), Error> {
+    match cmd {
+        BulkTags::Rename(cmd) => {
+            let req = BulkTagRequest::rename_key(&cmd.from, &cmd.to);
+            start(thorium, req, &cmd.opts).await
+        }
+        BulkTags::Replace(cmd) => {
+            let req = BulkTagRequest::replace_value(&cmd.key, &cmd.from, &cmd.to);
+            start(thorium, req, &cmd.opts).await
+        }
+        BulkTags::Delete(cmd) => {
+            let req = BulkTagRequest::delete(&cmd.key, &cmd.value);
+            start(thorium, req, &cmd.opts).await
+        }
+        BulkTags::List => list(thorium).await,
+        BulkTags::Status(cmd) => status(thorium, cmd).await,
+        BulkTags::Cancel(cmd) => cancel(thorium, cmd).await,
+    }
+}
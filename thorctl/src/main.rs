@@ -28,6 +28,11 @@ async fn main() {
         SubCommands::Reactions(reactions) => handlers::reactions::handle(&args, reactions).await,
         SubCommands::Results(results) => handlers::results::handle(&args, results).await,
         SubCommands::Tags(tags) => handlers::tags::handle(&args, tags).await,
+        SubCommands::Comments(comments) => handlers::comments::handle(&args, comments).await,
+        SubCommands::Notifications(notifications) => {
+            handlers::notifications::handle(&args, notifications).await
+        }
+        SubCommands::Search(search) => handlers::search::handle(&args, search).await,
         SubCommands::Repos(repos) => handlers::repos::handle(&args, repos).await,
         SubCommands::NetworkPolicies(network_policies) => {
             handlers::network_policies::handle(&args, network_policies).await
@@ -1,12 +1,40 @@
 import React, { Fragment, useEffect, useState } from 'react';
-import { Alert, Button, Card, Col, Form, Pagination, Row } from 'react-bootstrap';
+import { Alert, Badge, Button, Card, Col, Form, Pagination, Row } from 'react-bootstrap';
 
 // project imports
 import { UploadDropzone } from '@components/shared/uploaddropzone';
-import { downloadAttachment, getFileDetails, postFileComments } from '@thorpi';
+import { downloadAttachment, getFileDetails, postFileComments, updateFileComment } from '@thorpi';
+import { useAuth } from '@utilities';
+
+// the reactions users can leave on a comment
+const REACTIONS = ['Acknowledge', 'Agree', 'Disagree', 'Resolved'];
+
+// nest replies under the comment they reply to
+// replies to comments that are not visible start their own thread
+const buildThreads = (comments) => {
+  const ids = new Set(comments.map((comment) => comment.id));
+  const replies = {};
+  const roots = [];
+  for (const comment of comments) {
+    if (comment.parent && ids.has(comment.parent)) {
+      if (!(comment.parent in replies)) {
+        replies[comment.parent] = [];
+      }
+      replies[comment.parent].push(comment);
+    } else {
+      roots.push(comment);
+    }
+  }
+  const build = (comment) => ({ comment: comment, replies: (replies[comment.id] || []).map(build) });
+  return roots.map(build);
+};
 
 export const Comments = ({ sha256 }) => {
+  const { userInfo } = useAuth();
   const [newComment, setNewComment] = useState('');
+  const [replyTo, setReplyTo] = useState(null);
+  const [editing, setEditing] = useState(null);
+  const [showRevisions, setShowRevisions] = useState({});
   const [filesArray, setFilesArray] = useState([]);
   const [comments, setComments] = useState([]);
   const [limit, setLimit] = useState(0);
@@ -19,8 +47,9 @@ export const Comments = ({ sha256 }) => {
     const fileDetails = await getFileDetails(sha256, setCommentError);
 
     if (fileDetails && fileDetails.comments) {
-      setComments(fileDetails.comments);
-      setMaxPage(Math.ceil(fileDetails.comments.length / PAGELIMIT));
+      const threads = buildThreads(fileDetails.comments);
+      setComments(threads);
+      setMaxPage(Math.ceil(threads.length / PAGELIMIT));
       setLimit(PAGELIMIT);
     }
   };
@@ -64,6 +93,10 @@ export const Comments = ({ sha256 }) => {
     } else {
       // add comment text to form
       form.append('comment', commentValue);
+      // add the comment we are replying to
+      if (replyTo) {
+        form.append('parent', replyTo.id);
+      }
       // add file attachments to form
       if (filesArray.length > 0) {
         for (const file of filesArray) {
@@ -73,8 +106,14 @@ export const Comments = ({ sha256 }) => {
       // post comment form and check result was a success
       if (await postFileComments(sha256, form, setCommentError)) {
         setCommentError('Success');
+        setNewComment('');
         // fetch comment updates after successful posting
         fetchComments();
+        // replies stay on their threads page
+        if (replyTo) {
+          setReplyTo(null);
+          return;
+        }
         // ensure we are on same page as new comment
         const newPageValue = Math.ceil((comments.length + 1) / PAGELIMIT) - 1;
         if (newPageValue != page && newPageValue != -1) {
@@ -101,45 +140,116 @@ export const Comments = ({ sha256 }) => {
     );
   };
 
-  // CommentList component
-  const CommentList = () => {
+  // send an edit or reaction for a comment and refresh our comments
+  const handleUpdate = async (commentId, update) => {
+    if (await updateFileComment(sha256, commentId, update, setCommentError)) {
+      setEditing(null);
+      fetchComments();
+    }
+  };
+
+  // add or remove the current users reaction on a comment
+  const toggleReaction = (comment, reaction) => {
+    const reacted = comment.reactions && comment.reactions[reaction]?.includes(userInfo?.username);
+    const update = reacted ? { remove_reactions: [reaction] } : { add_reactions: [reaction] };
+    handleUpdate(comment.id, update);
+  };
+
+  // render a comment and all of its replies
+  // this is a plain function instead of a component so edits don't lose focus on rerender
+  const renderThread = (thread, depth) => {
+    const comment = thread.comment;
     return (
-      <Fragment>
-        {comments &&
-          comments.slice(page * limit, page * limit + limit).map((singleCommentobj, i) => (
-            <Card key={i} className="single-comment mb-2 panel">
-              <Card.Header>
-                {singleCommentobj.author} <i>{singleCommentobj.uploaded}</i>
-              </Card.Header>
-              <Card.Body>
-                <Row key={i}>
-                  <p key={i}>{singleCommentobj.comment}</p>
+      <div key={comment.id} className={depth > 0 ? 'comment-reply' : ''}>
+        <Card className="single-comment mb-2 panel">
+          <Card.Header>
+            {comment.author} <i>{comment.uploaded}</i>
+            {comment.edited && (
+              <a
+                href="#comments"
+                className="text ms-2"
+                onClick={() => setShowRevisions({ ...showRevisions, [comment.id]: !showRevisions[comment.id] })}
+              >
+                <i>(edited {comment.edited})</i>
+              </a>
+            )}
+          </Card.Header>
+          <Card.Body>
+            {editing && editing.id == comment.id ? (
+              <Fragment>
+                <Form.Control
+                  as="textarea"
+                  value={editing.text}
+                  onChange={(e) => setEditing({ id: comment.id, text: e.target.value })}
+                />
+                <Button className="mt-2 me-2 primary-btn auto-width" onClick={() => handleUpdate(comment.id, { comment: editing.text })}>
+                  Save
+                </Button>
+                <Button className="mt-2 secondary-btn auto-width" onClick={() => setEditing(null)}>
+                  Cancel
+                </Button>
+              </Fragment>
+            ) : (
+              <Row>
+                <p>{comment.comment}</p>
+              </Row>
+            )}
+            {showRevisions[comment.id] &&
+              comment.revisions &&
+              [...comment.revisions].reverse().map((revision, i) => (
+                <Row key={i} className="comment-revision">
+                  <p>
+                    <i>{revision.written}</i>: {revision.comment}
+                  </p>
                 </Row>
-                {singleCommentobj &&
-                  singleCommentobj.files &&
-                  Object.keys(singleCommentobj.files).map((name, i) => (
-                    <Col key={i}>
-                      <a
-                        href="#comments"
-                        className="text"
-                        onClick={() => getAttachment(singleCommentobj.id, name, singleCommentobj.files[name])}
-                      >
-                        {name}
-                      </a>
-                    </Col>
-                  ))}
-              </Card.Body>
-            </Card>
-          ))}
-      </Fragment>
+              ))}
+            {comment.attachments &&
+              Object.keys(comment.attachments).map((name, i) => (
+                <Col key={i}>
+                  <a href="#comments" className="text" onClick={() => getAttachment(comment.id, name, comment.attachments[name])}>
+                    {name}
+                  </a>
+                </Col>
+              ))}
+            <Row className="mt-2">
+              <Col>
+                {REACTIONS.map((reaction) => (
+                  <Button
+                    key={reaction}
+                    size="sm"
+                    className="me-1 comment-reaction"
+                    variant={comment.reactions && comment.reactions[reaction]?.includes(userInfo?.username) ? 'primary' : 'outline-secondary'}
+                    title={comment.reactions && comment.reactions[reaction] ? comment.reactions[reaction].join(', ') : ''}
+                    onClick={() => toggleReaction(comment, reaction)}
+                  >
+                    {reaction}
+                    {comment.reactions && comment.reactions[reaction] && (
+                      <Badge bg="secondary" className="ms-1">
+                        {comment.reactions[reaction].length}
+                      </Badge>
+                    )}
+                  </Button>
+                ))}
+                <Button size="sm" className="me-1" variant="outline-secondary" onClick={() => setReplyTo(comment)}>
+                  Reply
+                </Button>
+                {userInfo?.username == comment.author && (
+                  <Button size="sm" variant="outline-secondary" onClick={() => setEditing({ id: comment.id, text: comment.comment })}>
+                    Edit
+                  </Button>
+                )}
+              </Col>
+            </Row>
+          </Card.Body>
+        </Card>
+        {thread.replies.map((reply) => renderThread(reply, depth + 1))}
+      </div>
     );
   };
 
   return (
     <div id="comments-tab">
-      <div className="comments">
-        <CommentList />
-      </div>
+      <div className="comments">{comments && comments.slice(page * limit, page * limit + limit).map((thread) => renderThread(thread, 0))}</div>
       {comments.length == 0 && (
         <Fragment>
           <Alert variant="" className="info">
@@ -166,10 +276,18 @@ export const Comments = ({ sha256 }) => {
       </Row>
       <Row>
         <center>
+          {replyTo && (
+            <Alert className="attachment-card" variant="info">
+              Replying to {replyTo.author}
+              <Button size="sm" variant="outline-secondary" className="ms-2" onClick={() => setReplyTo(null)}>
+                Cancel
+              </Button>
+            </Alert>
+          )}
           <Form.Control
             className="comment-entry"
             as="textarea"
-            placeholder="Add Comment"
+            placeholder="Add Comment (mention users with @username)"
             onChange={(e) => setNewComment(e.target.value)}
             value={newComment}
           />
@@ -20,6 +20,7 @@ export { default as CondensedFileTags } from './tags/condensed_file_tags';
 export { default as CondensedEntityTags } from './tags/condensed_entity_tags';
 export { default as EditableTags } from './tags/editable_tags';
 export * from './tags/tags';
+export * from './tags/tag_provenance';
 export * from './tags/utilities';
 export * from './entities/filters';
 export * from './entities/browsing';
@@ -0,0 +1,116 @@
+import React, { useEffect, useState } from 'react';
+import { Alert, Card, Col, Form, Row, Table } from 'react-bootstrap';
+
+// project imports
+import { Subtitle } from '@components';
+import { getFileTagProvenance } from '@thorpi';
+import { TagProvenance, TagSource } from '@models';
+
+interface TagProvenanceTableProps {
+  sha256: string; // the file to show tag provenance for
+}
+
+// format a tool and its version for display
+const formatTool = (provenance: TagProvenance): string => {
+  if (!provenance.tool) {
+    return '-';
+  }
+  if (!provenance.tool_version) {
+    return provenance.tool;
+  }
+  // semver versions are strings while custom versions are wrapped in an object
+  const version =
+    typeof provenance.tool_version == 'string' ? provenance.tool_version : Object.values(provenance.tool_version).join('');
+  return `${provenance.tool}:${version}`;
+};
+
+const TagProvenanceTable: React.FC<TagProvenanceTableProps> = ({ sha256 }) => {
+  const [provenance, setProvenance] = useState<TagProvenance[]>([]);
+  const [source, setSource] = useState<TagSource | null>(null);
+  const [minConfidence, setMinConfidence] = useState<number | null>(null);
+  const [error, setError] = useState('');
+
+  // get tag provenance whenever our filters change
+  useEffect(() => {
+    const fetchProvenance = async () => {
+      // wait until we know which file to get tag provenance for
+      if (!sha256) {
+        return;
+      }
+      setError('');
+      const provs = await getFileTagProvenance(sha256, source, minConfidence, setError);
+      // sort by key, then value, then group
+      provs.sort((a, b) => a.key.localeCompare(b.key) || a.value.localeCompare(b.value) || a.group.localeCompare(b.group));
+      setProvenance(provs);
+    };
+    fetchProvenance();
+  }, [sha256, source, minConfidence]);
+
+  return (
+    <Card className="panel">
+      <Card.Body>
+        <Row className="mb-2">
+          <Col>
+            <Subtitle>Tag Provenance</Subtitle>
+          </Col>
+          <Col xs="auto">
+            <Form.Select
+              value={source ? source : ''}
+              onChange={(e) => setSource(e.target.value ? (e.target.value as TagSource) : null)}
+            >
+              <option value="">All Sources</option>
+              <option value={TagSource.User}>Analyst Confirmed</option>
+              <option value={TagSource.Tool}>Tool Generated</option>
+            </Form.Select>
+          </Col>
+          <Col xs="auto">
+            <Form.Control
+              type="number"
+              min={0}
+              max={1}
+              step={0.1}
+              placeholder="Min Confidence"
+              value={minConfidence == null ? '' : minConfidence}
+              onChange={(e) => setMinConfidence(e.target.value === '' ? null : Number(e.target.value))}
+            />
+          </Col>
+        </Row>
+        {error != '' && <Alert variant="danger">{error}</Alert>}
+        {provenance.length == 0 ? (
+          <Alert variant="info">No tag provenance found</Alert>
+        ) : (
+          <Table striped="row" hover={true} className="none-border">
+            <thead>
+              <tr>
+                <th>Key</th>
+                <th>Value</th>
+                <th>Group</th>
+                <th>Source</th>
+                <th>User</th>
+                <th>Tool</th>
+                <th>Confidence</th>
+                <th>Set</th>
+              </tr>
+            </thead>
+            <tbody>
+              {provenance.map((prov, idx) => (
+                <tr key={idx}>
+                  <td>{prov.key}</td>
+                  <td>{prov.value}</td>
+                  <td>{prov.group}</td>
+                  <td>{prov.source == TagSource.User ? 'Analyst' : 'Tool'}</td>
+                  <td>{prov.user}</td>
+                  <td>{formatTool(prov)}</td>
+                  <td>{prov.confidence == null ? '-' : prov.confidence}</td>
+                  <td>{prov.timestamp}</td>
+                </tr>
+              ))}
+            </tbody>
+          </Table>
+        )}
+      </Card.Body>
+    </Card>
+  );
+};
+
+export { TagProvenanceTable };
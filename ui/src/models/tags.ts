@@ -19,3 +19,22 @@ export enum TagTypes {
   Files = 'Files',
   Repos = 'Repos',
 }
+
+export enum TagSource {
+  User = 'User',
+  Tool = 'Tool',
+}
+
+// who or what last set a single tag in a group
+export type TagProvenance = {
+  group: string; // the group this tag is in
+  key: string; // the key for this tag
+  value: string; // the value for this tag
+  source: TagSource; // whether a user or a tool set this tag
+  user: string; // the user that set this tag or that the tool ran as
+  tool?: string; // the tool that set this tag
+  tool_version?: string | { [kind: string]: string }; // the version of the tool that set this tag
+  result?: string; // the result this tag was extracted from
+  confidence?: number; // how confident the source of this tag is from 0 to 1
+  timestamp: string; // when this tag was set
+};
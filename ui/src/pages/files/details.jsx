@@ -15,6 +15,7 @@ import {
   Results,
   RunPipelines,
   Subtitle,
+  TagProvenanceTable,
   Page,
   Time,
 } from '@components';
@@ -370,6 +371,11 @@ const FileInfo = ({ details, setDetails, groupDetails, screenWidth, setDeletionS
           />
         </Col>
       </Row>
+      <Row className="mt-4">
+        <Col>
+          <TagProvenanceTable sha256={details.sha256} />
+        </Col>
+      </Row>
       <Row className="my-3">
         <Col xs="auto" className="mt-3">
           <p>Select submission:</p>
@@ -58,3 +58,34 @@ export async function downloadAttachment(
       return null;
     });
 }
+
+/**
+ * Edit or react to a comment on a file.
+ * @async
+ * @function
+ * @param {string} sha256 - the sha256 hash of the sample the comment is about
+ * @param {string} commentId - the UUID of the comment to update
+ * @param {any} data - the update to apply including new comment text or reactions to add/remove
+ * @param {(error: string) => void} errorHandler - error handler function
+ * @returns {Promise<boolean>} - promise object representing whether the update succeeded
+ */
+export async function updateFileComment(
+  sha256: string,
+  commentId: string,
+  data: any,
+  errorHandler: (error: string) => void,
+): Promise<boolean> {
+  const url = `/files/comment/${sha256}/${commentId}`;
+  return client
+    .patch(url, data)
+    .then((res) => {
+      if (res?.status == 204) {
+        return true;
+      }
+      return false;
+    })
+    .catch((error) => {
+      parseRequestError(error, errorHandler, 'Update Comment');
+      return false;
+    });
+}
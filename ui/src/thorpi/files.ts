@@ -1,7 +1,7 @@
 // import the base client function that loads from the config
 // and injects the token via axios intercepts
 import client, { parseRequestError } from './client';
-import { CreateTags, Filters, FilterTags } from '@models';
+import { CreateTags, Filters, FilterTags, TagProvenance, TagSource } from '@models';
 
 // Debugging errors randomly inserted.
 // Valid values 0-100 (percentage chance of error)
@@ -220,6 +220,44 @@ export async function deleteTags(sha256: string, tags: CreateTags, errorHandler:
     });
 }
 
+/**
+ * Get where the tags on a file came from
+ * @async
+ * @function
+ * @param {string} sha256 - sha256 hash of sample to get tag provenance for
+ * @param {TagSource | null} source - only get tags from this source
+ * @param {number | null} minConfidence - only get tags with at least this confidence
+ * @param {(error: string) => void} errorHandler - error handler function
+ * @returns {Promise<TagProvenance[]>} - promise of the provenance for this file's tags
+ */
+export async function getFileTagProvenance(
+  sha256: string,
+  source: TagSource | null,
+  minConfidence: number | null,
+  errorHandler: (error: string) => void,
+): Promise<TagProvenance[]> {
+  const url = '/files/tag-provenance/' + sha256;
+  const params: { source?: TagSource; min_confidence?: number } = {};
+  if (source) {
+    params.source = source;
+  }
+  if (minConfidence != null) {
+    params.min_confidence = minConfidence;
+  }
+  return client
+    .get(url, { params: params })
+    .then((res) => {
+      if (res?.status == 200 && res.data) {
+        return res.data;
+      }
+      return [];
+    })
+    .catch((error) => {
+      parseRequestError(error, errorHandler, 'Get File Tag Provenance');
+      return [];
+    });
+}
+
 /**
  * Delete a file submission
  * @async